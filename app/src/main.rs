@@ -21,6 +21,20 @@ fn log_setup() -> Result<(), anyhow::Error> {
 }
 
 use clap::Parser;
+use node::cluster::PostgresCluster;
+use node::event_bus::DEFAULT_TX_EVENTS_TOPIC;
+use node::risk::HttpRiskProvider;
+use node::rpc_middleware::Role;
+use node::tenants::HostedNode;
+use primitives::data_structure::{ChainSupported, Token};
+use primitives::environment::NetworkEnvironment;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// how long the risk provider is waited on
+const RISK_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -28,6 +42,387 @@ struct Args {
     /// Database URL to use
     #[arg(short, long)]
     pub db_url: Option<String>,
+
+    /// Directory of the key encrypting payloads and memos in the db, next to the db by default
+    #[arg(long, env = "VANE_KEYSTORE")]
+    pub keystore: Option<String>,
+
+    /// Write a typed TypeScript client for the node rpc api to this path and exit
+    #[arg(long)]
+    pub emit_ts_client: Option<String>,
+
+    /// Rpc api key as `role:key`, role is viewer, operator or admin; repeatable
+    #[arg(long = "api-key", env = "VANE_API_KEYS", value_delimiter = ',', value_parser = parse_api_key)]
+    pub api_keys: Vec<(String, Role)>,
+
+    /// Scope the tx update subscriptions of an api key to an account as `key:account`; repeatable
+    #[arg(long = "api-key-account", value_parser = parse_key_account)]
+    pub api_key_accounts: Vec<(String, String)>,
+
+    /// Run the chains in memory with instant finality and a faucet, for demos and tests without
+    /// chain rpc endpoints or funds; fund accounts with `vane faucet`. Needs the dev feature
+    #[arg(long)]
+    pub dev: bool,
+
+    /// Run without signing capability, sending needs a signing device paired with `vane signers pair`
+    #[arg(long)]
+    pub watch_only: bool,
+
+    /// Return the tx id of a transfer right after the local checks, the registry, chain and risk
+    /// checks are reported through the tx update subscription
+    #[arg(long)]
+    pub optimistic_send: bool,
+
+    /// Refuse to send until the node is unlocked with this passphrase through `vane unlock`
+    #[arg(long, env = "VANE_SEND_PASSPHRASE", hide_env_values = true)]
+    pub send_passphrase: Option<String>,
+
+    /// How long an unlock with the send passphrase lasts, in minutes
+    #[arg(long, default_value_t = 15)]
+    pub unlock_minutes: u64,
+
+    /// Environment every network runs on, mainnet or testnet (sepolia, bnb testnet, solana devnet
+    /// and westend)
+    #[arg(long, env = "VANE_ENVIRONMENT", value_parser = parse_environment)]
+    pub environment: Option<NetworkEnvironment>,
+
+    /// Environment of one network as `Network:environment`, e.g. `Ethereum:testnet`; repeatable
+    #[arg(long = "network-environment", value_parser = parse_network_environment)]
+    pub network_environments: Vec<(ChainSupported, NetworkEnvironment)>,
+
+    /// Rpc endpoint of a network as `Network:url`, instead of the public one of its environment;
+    /// repeatable
+    #[arg(long = "chain-url", value_parser = parse_chain_url)]
+    pub chain_urls: Vec<(ChainSupported, String)>,
+
+    /// Rpc endpoint of an independent provider as `Network:url`, txs are only reported submitted
+    /// once it sees them; Ethereum and Bnb, repeatable
+    #[arg(long = "verify-url", value_parser = parse_chain_url)]
+    pub verify_urls: Vec<(ChainSupported, String)>,
+
+    /// Erc-4337 bundler of a network as `Network:url`, transfers of smart account senders are sent
+    /// to it as user operations; Ethereum and Bnb, repeatable
+    #[arg(long = "bundler-url", value_parser = parse_chain_url)]
+    pub bundler_urls: Vec<(ChainSupported, String)>,
+
+    /// Erc-7677 paymaster service of a network as `Network:url`, it sponsors the gas of the user
+    /// operations sent to the bundler of the network; repeatable
+    #[arg(long = "paymaster-url", value_parser = parse_chain_url)]
+    pub paymaster_urls: Vec<(ChainSupported, String)>,
+
+    /// Json context the paymaster services sponsor the user operations under, e.g. a policy id
+    #[arg(long, default_value = "{}", value_parser = parse_json_object)]
+    pub paymaster_context: serde_json::Value,
+
+    /// Compute units every Solana transfer requests, presets of the token by default
+    #[arg(long)]
+    pub solana_compute_unit_limit: Option<u32>,
+
+    /// Most micro lamports per compute unit Solana txs are built with, txs above are refused
+    #[arg(long)]
+    pub solana_max_compute_unit_price: Option<u64>,
+
+    /// Durable nonce account of a Solana sender as `sender:nonce_account`, its transfers are signed
+    /// against the nonce and do not expire while the receiver attests; repeatable
+    #[arg(long = "solana-nonce-account", value_parser = parse_nonce_account)]
+    pub solana_nonce_accounts: Vec<(String, String)>,
+
+    /// Create the missing token account of a Solana receiver in the transfer tx, the sender pays
+    /// its rent; without it such transfers are only warned about
+    #[arg(long)]
+    pub solana_create_token_accounts: bool,
+
+    /// Decline attestation requests from senders neither saved as peers nor attested for before
+    #[arg(long)]
+    pub reject_unknown_senders: bool,
+
+    /// Decline attestation requests below `Token:amount`, amount in the token smallest unit; repeatable
+    #[arg(long = "dust-threshold", value_parser = parse_dust_threshold)]
+    pub dust_thresholds: Vec<(Token, u128)>,
+
+    /// Refuse to send transfers below `Token:amount`, amount in the token smallest unit; repeatable
+    #[arg(long = "min-transfer", value_parser = parse_dust_threshold)]
+    pub min_transfers: Vec<(Token, u128)>,
+
+    /// Warn about transfer amounts with more decimals than `Token:decimals`; repeatable
+    #[arg(long = "max-decimals", value_parser = parse_max_decimals)]
+    pub max_decimals: Vec<(Token, u32)>,
+
+    /// Decline attestation requests past this many per sender and hour
+    #[arg(long)]
+    pub max_requests_per_hour: Option<u32>,
+
+    /// Show the identity attestations signed by `account:name` as verification badges; repeatable
+    #[arg(long = "trusted-issuer", env = "VANE_TRUSTED_ISSUERS", value_delimiter = ',', value_parser = parse_trusted_issuer)]
+    pub trusted_issuers: Vec<(String, String)>,
+
+    /// Wasm validation plugin run on the transfers of the node before attestation and before
+    /// submission; repeatable
+    #[arg(long = "plugin", env = "VANE_PLUGINS", value_delimiter = ',')]
+    pub plugins: Vec<std::path::PathBuf>,
+
+    /// Do not report anonymized node stats to any telemetry server, wins over `--telemetry-url`
+    #[arg(long)]
+    pub no_telemetry: bool,
+
+    /// Telemetry server websocket url to report anonymized node stats to, none are reported without
+    /// one
+    #[arg(long, env = "VANE_TELEMETRY_URL")]
+    pub telemetry_url: Option<String>,
+
+    /// Share the daily counts and rounded usd values of prevented transfers with the telemetry server
+    #[arg(long)]
+    pub share_loss_prevention_stats: bool,
+
+    /// Usd price of a whole token as `Token:price` to value prevented transfers and price fiat
+    /// transfers with; repeatable
+    #[arg(long = "usd-price", value_parser = parse_usd_price)]
+    pub usd_prices: Vec<(Token, f64)>,
+
+    /// Price move in basis points a fiat transfer tolerates until it is submitted, when its sender
+    /// picks none
+    #[arg(long)]
+    pub fiat_slippage_bps: Option<u32>,
+
+    /// Multi addr of an always-on node of yours, ending with /p2p/<peer id>, queuing the requests
+    /// to this node while it is offline
+    #[arg(long, env = "VANE_RELAY")]
+    pub relay: Option<String>,
+
+    /// Peer id of a mobile node of yours to queue requests for while it is offline; repeatable
+    #[arg(long = "relay-for")]
+    pub relay_for: Vec<String>,
+
+    /// Webhook in front of FCM or APNs called when a request gets queued for a mobile node
+    #[arg(long = "push-gateway", env = "VANE_PUSH_GATEWAY")]
+    pub push_gateway: Option<String>,
+
+    /// Device token of the mobile app, passed to the relay to wake the app up for queued requests
+    #[arg(long = "push-token", env = "VANE_PUSH_TOKEN")]
+    pub push_token: Option<String>,
+
+    /// Https endpoint of your approval system, called before every submission with the transfer
+    /// details and answering `{"decision":"allow"}` or `{"decision":"deny","reason":"…"}`
+    #[arg(long, env = "VANE_APPROVAL_WEBHOOK")]
+    pub approval_webhook: Option<String>,
+
+    /// How long the approval webhook is waited on, in seconds
+    #[arg(long, default_value_t = 10)]
+    pub approval_timeout_secs: u64,
+
+    /// Submit transfers the approval webhook did not answer for, instead of denying them
+    #[arg(long)]
+    pub approval_fail_open: bool,
+
+    /// Risk provider endpoint scoring the receivers of the transfers, posted the receiver address
+    /// and network and answering `{"score":…,"labels":[…],"jurisdiction":"…"}`
+    #[arg(long, env = "VANE_RISK_PROVIDER")]
+    pub risk_provider: Option<String>,
+
+    /// Bearer token of the risk provider
+    #[arg(long, env = "VANE_RISK_PROVIDER_API_KEY")]
+    pub risk_provider_api_key: Option<String>,
+
+    /// Refuse transfers to receivers the risk provider scores below this, from 0 to 100
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub min_risk_score: Option<u8>,
+
+    /// ISO country code of a jurisdiction transfers are refused to, e.g. KP
+    #[arg(long = "sanctioned-jurisdiction", value_delimiter = ',')]
+    pub sanctioned_jurisdictions: Vec<String>,
+
+    /// Binding registry contract as `Network:address`, the accounts anchor their binding to their
+    /// node in; Ethereum or Bnb
+    #[arg(long, value_parser = parse_binding_registry)]
+    pub binding_registry: Option<(ChainSupported, String)>,
+
+    /// Refuse transfers to evm receivers that did not anchor their binding to their node in the
+    /// binding registry
+    #[arg(long, requires = "binding_registry")]
+    pub require_anchored_bindings: bool,
+
+    /// Serve a tenant as `tenant:role:key` from one hosted node process, each tenant gets a node
+    /// with its own db, accounts and policies behind the gateway; repeatable
+    #[arg(long = "tenant", env = "VANE_TENANTS", value_delimiter = ',', value_parser = parse_tenant_key)]
+    pub tenants: Vec<(String, String, Role)>,
+
+    /// Directory holding a directory per tenant with its db and keystore
+    #[arg(long, default_value = "tenants")]
+    pub tenants_dir: String,
+
+    /// Address the rpc gateway of the tenants listens on
+    #[arg(long, default_value = "0.0.0.0:8545")]
+    pub gateway_address: SocketAddr,
+
+    /// Api key of the hosting operator, allowed to scrape the metrics of every tenant
+    #[arg(long, env = "VANE_OPERATOR_KEY", hide_env_values = true)]
+    pub operator_key: Option<String>,
+
+    /// Postgres url of the store shared by the instances of a clustered node, e.g.
+    /// `postgres://vane:<password>@db.internal/vane`, connected to over tls outside `--dev`
+    #[arg(long, env = "VANE_CLUSTER_STORE", hide_env_values = true)]
+    pub cluster_store: Option<String>,
+
+    /// How long the accounts of a crashed cluster instance wait before being claimed, in seconds
+    #[arg(long, default_value_t = 30)]
+    pub cluster_lease_secs: u64,
+
+    /// Postgres url of the store redundant nodes elect the one running the p2p identity through,
+    /// the nodes share the db and keystore
+    #[arg(long, env = "VANE_FAILOVER_STORE", hide_env_values = true)]
+    pub failover_store: Option<String>,
+
+    /// How long standbys wait on a crashed leader before taking the p2p identity over, in seconds
+    #[arg(long, default_value_t = 15)]
+    pub failover_lease_secs: u64,
+
+    /// Message bus every tx state transition is published on, `nats://host:4222` or
+    /// `kafka://host:9092,host2:9092`
+    #[arg(long)]
+    pub event_bus: Option<String>,
+
+    /// Nats subject or kafka topic of the tx lifecycle events
+    #[arg(long, default_value = DEFAULT_TX_EVENTS_TOPIC)]
+    pub event_topic: String,
+
+    /// Address the grpc api is served on, e.g. `0.0.0.0:50051`; not served when unset
+    #[arg(long)]
+    pub grpc_address: Option<SocketAddr>,
+
+    /// Serve transfers, counterparties, stats and peers over graphql at `/graphql` on the rpc port
+    #[arg(long)]
+    pub graphql: bool,
+}
+
+fn parse_api_key(value: &str) -> Result<(String, Role), String> {
+    let (role, key) = value
+        .split_once(':')
+        .ok_or(format!("expected role:key, found {value}"))?;
+    let role = role.parse::<Role>().map_err(|err| err.to_string())?;
+    Ok((key.to_string(), role))
+}
+
+fn parse_key_account(value: &str) -> Result<(String, String), String> {
+    let (key, account) = value
+        .split_once(':')
+        .ok_or(format!("expected key:account, found {value}"))?;
+    Ok((key.to_string(), account.to_string()))
+}
+
+fn parse_tenant_key(value: &str) -> Result<(String, String, Role), String> {
+    let (tenant, api_key) = value
+        .split_once(':')
+        .ok_or(format!("expected tenant:role:key, found {value}"))?;
+    let (key, role) = parse_api_key(api_key)?;
+    Ok((tenant.to_string(), key, role))
+}
+
+fn parse_trusted_issuer(value: &str) -> Result<(String, String), String> {
+    let (account, name) = value
+        .split_once(':')
+        .ok_or(format!("expected account:name, found {value}"))?;
+    Ok((account.to_string(), name.to_string()))
+}
+
+fn parse_token(token: &str) -> Result<Token, String> {
+    match token {
+        "Dot" | "Bnb" | "Sol" | "Eth" | "UsdtSol" | "UsdcSol" | "UsdtEth" | "UsdcEth"
+        | "UsdtDot" => Ok(Token::from(token)),
+        other => Err(format!("unsupported token: {other}")),
+    }
+}
+
+fn parse_environment(environment: &str) -> Result<NetworkEnvironment, String> {
+    match environment.to_lowercase().as_str() {
+        "mainnet" => Ok(NetworkEnvironment::Mainnet),
+        "testnet" => Ok(NetworkEnvironment::Testnet),
+        other => Err(format!("unsupported environment: {other}")),
+    }
+}
+
+fn parse_network(network: &str) -> Result<ChainSupported, String> {
+    match network {
+        "Polkadot" | "Ethereum" | "Bnb" | "Solana" => Ok(ChainSupported::from(network)),
+        other => Err(format!("unsupported network: {other}")),
+    }
+}
+
+fn parse_network_environment(value: &str) -> Result<(ChainSupported, NetworkEnvironment), String> {
+    let (network, environment) = value
+        .split_once(':')
+        .ok_or(format!("expected network:environment, found {value}"))?;
+    Ok((parse_network(network)?, parse_environment(environment)?))
+}
+
+fn parse_chain_url(value: &str) -> Result<(ChainSupported, String), String> {
+    let (network, url) = value
+        .split_once(':')
+        .ok_or(format!("expected network:url, found {value}"))?;
+    Ok((parse_network(network)?, url.to_string()))
+}
+
+fn parse_json_object(value: &str) -> Result<serde_json::Value, String> {
+    match serde_json::from_str(value) {
+        Ok(object @ serde_json::Value::Object(_)) => Ok(object),
+        Ok(_) => Err(format!("expected a json object, found {value}")),
+        Err(err) => Err(format!("invalid json {value}: {err}")),
+    }
+}
+
+fn parse_binding_registry(value: &str) -> Result<(ChainSupported, String), String> {
+    let (network, contract) = value
+        .split_once(':')
+        .ok_or(format!("expected network:address, found {value}"))?;
+    match parse_network(network)? {
+        network @ (ChainSupported::Ethereum | ChainSupported::Bnb) => {
+            Ok((network, contract.to_string()))
+        }
+        other => Err(format!(
+            "binding registries are evm contracts, not on {other:?}"
+        )),
+    }
+}
+
+fn parse_nonce_account(value: &str) -> Result<(String, String), String> {
+    let (sender, nonce_account) = value
+        .split_once(':')
+        .ok_or(format!("expected sender:nonce_account, found {value}"))?;
+    Ok((sender.to_string(), nonce_account.to_string()))
+}
+
+fn parse_max_decimals(value: &str) -> Result<(Token, u32), String> {
+    let (token, decimals) = value
+        .split_once(':')
+        .ok_or(format!("expected token:decimals, found {value}"))?;
+    let token = parse_token(token)?;
+    let decimals = decimals
+        .parse::<u32>()
+        .map_err(|err| format!("invalid decimals {decimals}: {err}"))?;
+    Ok((token, decimals))
+}
+
+fn parse_dust_threshold(value: &str) -> Result<(Token, u128), String> {
+    let (token, amount) = value
+        .split_once(':')
+        .ok_or(format!("expected token:amount, found {value}"))?;
+    let token = parse_token(token)?;
+    let amount = amount
+        .parse::<u128>()
+        .map_err(|err| format!("invalid amount {amount}: {err}"))?;
+    Ok((token, amount))
+}
+
+fn parse_usd_price(value: &str) -> Result<(Token, f64), String> {
+    let (token, price) = value
+        .split_once(':')
+        .ok_or(format!("expected token:price, found {value}"))?;
+    let token = parse_token(token)?;
+    let price = price
+        .parse::<f64>()
+        .ok()
+        .filter(|price| price.is_finite() && *price >= 0.0)
+        .ok_or(format!("invalid price {price}"))?;
+    Ok((token, price))
 }
 
 #[tokio::main]
@@ -35,6 +430,208 @@ async fn main() -> Result<(), anyhow::Error> {
     log_setup()?;
     let args = Args::parse();
 
-    node::MainServiceWorker::run(args.db_url).await?;
+    if let Some(path) = &args.emit_ts_client {
+        std::fs::write(path, node::ts_client::generate_ts_client()?)?;
+        log::info!("typescript client written to {path}");
+        return Ok(());
+    }
+
+    if args.tenants.is_empty() {
+        node::MainServiceWorker::run(node_builder(&args).await?).await?;
+        return Ok(());
+    }
+    if args.cluster_store.is_some() || args.failover_store.is_some() {
+        // clustered and redundant instances share their state, tenants must not
+        Err(anyhow::anyhow!("hosted tenants can not run clustered"))?
+    }
+    if args.grpc_address.is_some() {
+        // the tenants are only reachable through the json-rpc gateway
+        Err(anyhow::anyhow!("hosted tenants are not served over grpc"))?
+    }
+
+    let mut tenants: BTreeMap<String, HashMap<String, Role>> = BTreeMap::new();
+    for (tenant, key, role) in &args.tenants {
+        tenants
+            .entry(tenant.clone())
+            .or_default()
+            .insert(key.clone(), *role);
+    }
+    let mut hosted = HostedNode::new(&args.tenants_dir, args.gateway_address);
+    if let Some(key) = &args.operator_key {
+        hosted = hosted.operator_key(key);
+    }
+    for (tenant, api_keys) in tenants {
+        hosted = hosted.tenant(tenant, api_keys, node_builder(&args).await?);
+    }
+    let handle = hosted.start().await?;
+    log::info!(
+        "serving {} tenants on {}",
+        handle.tenants.len(),
+        handle.rpc_address
+    );
+    handle.stopped().await;
     Ok(())
 }
+
+/// node configuration from the arguments, every tenant of a hosted node gets its own copy
+async fn node_builder(args: &Args) -> Result<node::MainServiceWorkerBuilder, anyhow::Error> {
+    let mut builder = node::MainServiceWorkerBuilder::new();
+    if let Some(db_url) = &args.db_url {
+        builder = builder.db_url(db_url);
+    }
+    if let Some(keystore) = &args.keystore {
+        builder = builder.keystore(keystore);
+    }
+    for (key, role) in &args.api_keys {
+        builder = builder.api_key(key, *role);
+    }
+    for (key, account) in &args.api_key_accounts {
+        builder = builder.api_key_account(key, account);
+    }
+    if args.dev {
+        #[cfg(not(feature = "dev"))]
+        Err(anyhow::anyhow!(
+            "--dev needs the app built with the dev feature, `cargo build -p app --features dev`"
+        ))?;
+        #[cfg(feature = "dev")]
+        {
+            log::warn!("dev mode, transfers are simulated on in-memory chains");
+            builder = builder.dev();
+        }
+    }
+    if args.watch_only {
+        builder = builder.watch_only();
+    }
+    if args.optimistic_send {
+        builder = builder.optimistic_send();
+    }
+    if let Some(passphrase) = &args.send_passphrase {
+        builder =
+            builder.send_passphrase(passphrase, Duration::from_secs(args.unlock_minutes * 60));
+    }
+    if let Some(environment) = args.environment {
+        builder = builder.environment(environment);
+    }
+    for (network, environment) in &args.network_environments {
+        builder = builder.network_environment(*network, *environment);
+    }
+    for (network, url) in &args.chain_urls {
+        builder = builder.chain_url(*network, url);
+    }
+    for (network, url) in &args.verify_urls {
+        builder = builder.verify_url(*network, url);
+    }
+    for (network, url) in &args.bundler_urls {
+        builder = builder.bundler_url(*network, url);
+    }
+    for (network, url) in &args.paymaster_urls {
+        builder = builder.paymaster(*network, url, args.paymaster_context.clone());
+    }
+    if let Some(limit) = args.solana_compute_unit_limit {
+        builder = builder.solana_compute_unit_limit(limit);
+    }
+    if let Some(cap) = args.solana_max_compute_unit_price {
+        builder = builder.solana_max_compute_unit_price(cap);
+    }
+    for (sender, nonce_account) in &args.solana_nonce_accounts {
+        builder = builder.solana_nonce_account(sender, nonce_account);
+    }
+    if args.solana_create_token_accounts {
+        builder = builder.solana_create_token_accounts();
+    }
+    if args.reject_unknown_senders {
+        builder = builder.reject_unknown_senders();
+    }
+    for (token, amount) in &args.dust_thresholds {
+        builder = builder.dust_threshold(*token, *amount);
+    }
+    for (token, amount) in &args.min_transfers {
+        builder = builder.min_transfer_amount(*token, *amount);
+    }
+    for (token, decimals) in &args.max_decimals {
+        builder = builder.max_decimals(*token, *decimals);
+    }
+    for (issuer, name) in &args.trusted_issuers {
+        builder = builder.trusted_issuer(issuer, name);
+    }
+    for path in &args.plugins {
+        builder = builder.plugin(path);
+    }
+    if let Some(url) = &args.telemetry_url {
+        builder = builder.telemetry_url(url);
+    }
+    if args.no_telemetry {
+        builder = builder.no_telemetry();
+    }
+    if args.share_loss_prevention_stats {
+        builder = builder.share_loss_prevention_stats();
+    }
+    for (token, price) in &args.usd_prices {
+        builder = builder.usd_price(*token, *price);
+    }
+    if let Some(max_slippage_bps) = args.fiat_slippage_bps {
+        builder = builder.fiat_slippage(max_slippage_bps);
+    }
+    if let Some(relay) = &args.relay {
+        builder = builder.relay(relay);
+    }
+    for mobile in &args.relay_for {
+        builder = builder.relay_for(mobile);
+    }
+    if let Some(url) = &args.push_gateway {
+        builder = builder.push_gateway(url);
+    }
+    if let Some(token) = &args.push_token {
+        builder = builder.push_token(token);
+    }
+    if let Some(max_requests) = args.max_requests_per_hour {
+        builder = builder.max_requests_per_sender(max_requests, Duration::from_secs(3600));
+    }
+    if let Some(url) = &args.approval_webhook {
+        let timeout = Duration::from_secs(args.approval_timeout_secs);
+        builder = builder.approval_webhook(url, timeout, args.approval_fail_open);
+    }
+    if let Some(url) = &args.risk_provider {
+        let provider =
+            HttpRiskProvider::new(url, args.risk_provider_api_key.clone(), RISK_TIMEOUT)?;
+        builder = builder.risk_provider(Arc::new(provider));
+    }
+    if let Some(min_score) = args.min_risk_score {
+        builder = builder.min_risk_score(min_score);
+    }
+    for jurisdiction in &args.sanctioned_jurisdictions {
+        builder = builder.sanctioned_jurisdiction(jurisdiction);
+    }
+    if let Some((network, contract)) = &args.binding_registry {
+        builder = builder.binding_registry_contract(*network, contract);
+    }
+    if args.require_anchored_bindings {
+        builder = builder.require_anchored_bindings();
+    }
+    if let Some(url) = &args.cluster_store {
+        let store = PostgresCluster::connect(url, args.dev).await?;
+        let lease = Duration::from_secs(args.cluster_lease_secs);
+        builder = builder.cluster(Arc::new(store), lease);
+    }
+    if let Some(url) = &args.failover_store {
+        if args.cluster_store.is_some() {
+            // the instances of a cluster each run an identity of their own
+            Err(anyhow::anyhow!(
+                "clustered instances can not fail over a shared identity"
+            ))?
+        }
+        let store = PostgresCluster::connect(url, args.dev).await?;
+        let lease = Duration::from_secs(args.failover_lease_secs);
+        builder = builder.failover(Arc::new(store), lease);
+    }
+    if let Some(url) = &args.event_bus {
+        builder = builder.event_bus(node::event_bus::connect(url, &args.event_topic).await?);
+    }
+    if let Some(address) = args.grpc_address {
+        builder = builder.grpc_address(address);
+    }
+    if args.graphql {
+        builder = builder.graphql();
+    }
+    Ok(builder)
+}
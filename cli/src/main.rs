@@ -0,0 +1,1484 @@
+//! `vane` command line client, talks to a running vane node over rpc
+//!
+//! ```text
+//! vane send --chain eth --to 0x… --amount 1.5 --fee-tier high --max-fee-gwei 40 --private
+//! vane send --chain eth --to 0x… --amount 0.1 --environment sepolia
+//! vane send --to 5Grw… --amount 2
+//! vane detect 0x…
+//! vane counterparty 0x…
+//! vane fees --chain eth
+//! vane fee-stats
+//! vane faucet --chain eth --to 0x… --amount 10
+//! vane test-accounts --chain eth --count 2 --amount 10
+//! vane approve --token usdc-eth --spender 0x… --amount 100
+//! vane approvals
+//! vane policies set policies.json
+//! vane send --chain eth --to 0x… --amount 250 --reference INV-2024-031 --tag payroll
+//! vane send --chain eth --to 0x… --amount 5000 --token usdc-eth --travel-rule parties.json
+//! vane send --to … --amount 40 --token usdc-sol --reference INV-2024-033 --reference-memo
+//! vane pending
+//! vane history --search INV-2024-031
+//! vane history --search 0x… --chain eth --min-amount 1000000 --failed
+//! vane receipt 0x…
+//! vane travel-rule 0x…
+//! vane diagnose 0x…
+//! vane retry 0x… --stage broadcast
+//! vane decline 12 --reason wrong-chain
+//! vane decline 12 --reason wrong-chain --correct-to 0x… --correct-chain bnb
+//! vane restart 0x…
+//! vane split --part usdc-eth:0x…:600 --part eth:0x…:0.2 --reference INV-2024-032
+//! vane payment 0x…
+//! vane confirm <tx-id>
+//! vane confirm <tx-id> --accept-amount 0.8
+//! vane unlock
+//! vane peers list
+//! vane peers rotate --grace-secs 3600
+//! vane devices pair vane-pair:<code>@<multi addr>
+//! vane identity revoke --private-key 0x…
+//! vane assets set --account 0x… --token usdc-eth --token usdc-sol
+//! vane assets show 0x…
+//! vane tui
+//! ```
+
+use alloy::primitives::{keccak256, B256};
+use alloy::signers::local::PrivateKeySigner;
+use alloy::signers::SignerSync;
+use anyhow::anyhow;
+use clap::{Parser, Subcommand};
+use primitives::address::VaneAddress;
+use primitives::attestations::IdentityAttestation;
+use primitives::counterparty::CounterpartyProfile;
+use primitives::data_structure::{
+    AddressCorrection, ChainSupported, RejectReason, SenderProfile, SubmissionRoute, Token,
+    TxPriority, TxStateMachine, TxStatus, ETH_SIG_MSG_PREFIX, H256,
+};
+use primitives::diagnosis::RetryStage;
+use primitives::environment::NetworkEnvironment;
+use primitives::fees::{FeePreference, FeeTier};
+use primitives::history::HistorySearch;
+use primitives::payments::PaymentPart;
+use primitives::policy::parse_policies;
+use primitives::tx_builder::{format_units, parse_units};
+use std::time::{SystemTime, UNIX_EPOCH};
+use vane_client::jsonrpsee::ws_client::WsClient;
+use vane_client::{TransferOptions, VaneClient};
+
+mod tui;
+
+#[derive(Parser)]
+#[command(name = "vane", author, version, about, long_about = None)]
+struct Cli {
+    /// Node rpc websocket url
+    #[arg(long, env = "VANE_NODE_URL", default_value = "ws://127.0.0.1:8000")]
+    node: String,
+
+    /// Hex encoded private key used to sign attestations and payloads
+    #[arg(long, env = "VANE_PRIVATE_KEY", hide_env_values = true)]
+    private_key: Option<String>,
+
+    /// Rpc api key, nodes with access control only accept admin keys over websocket
+    #[arg(long, env = "VANE_API_KEY", hide_env_values = true)]
+    api_key: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Initiate a transfer, the receiver has to attest before it can be confirmed
+    Send {
+        /// eth, bnb, sol or dot, inferred from --token or else from the receiver address format
+        #[arg(long, value_parser = parse_chain)]
+        chain: Option<ChainSupported>,
+        /// Receiver address
+        #[arg(long)]
+        to: String,
+        /// Amount in whole tokens, e.g. 1.5
+        #[arg(long)]
+        amount: String,
+        /// Token to send, defaults to the chain native token
+        #[arg(long, value_parser = parse_token)]
+        token: Option<Token>,
+        /// Sender address, defaults to the signer address
+        #[arg(long)]
+        from: Option<String>,
+        /// low, medium or high, evm chains only
+        #[arg(long, value_parser = parse_fee_tier)]
+        fee_tier: Option<FeeTier>,
+        /// Refuse to build the tx if the tier fee per gas is above this, in gwei
+        #[arg(long)]
+        max_fee_gwei: Option<String>,
+        /// Submit through a private relay instead of the public mempool, ethereum only
+        #[arg(long)]
+        private: bool,
+        /// Memo or destination tag carried with the transfer, e.g. an exchange deposit reference
+        #[arg(long)]
+        memo: Option<String>,
+        /// Name shown to the receiver, signed with --private-key as proof of the sender address
+        #[arg(long)]
+        display_name: Option<String>,
+        /// mainnet or testnet (sepolia, bnb-testnet, solana-devnet, westend), refused by a node
+        /// running the chain on the other one
+        #[arg(long, value_parser = parse_environment)]
+        environment: Option<NetworkEnvironment>,
+        /// Invoice or order id the transfer settles, kept in the history for reconciliation
+        #[arg(long)]
+        reference: Option<String>,
+        /// Also send the reference on chain as the memo of the transfer, solana only
+        #[arg(long, requires = "reference", conflicts_with = "memo")]
+        reference_memo: bool,
+        /// Label of the transfer, searchable in the history, repeatable
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// JSON file of the originator and beneficiary, for transfers between service providers
+        #[arg(long)]
+        travel_rule: Option<std::path::PathBuf>,
+        /// Seconds the transfer may take until it is submitted, aborted on both nodes after
+        #[arg(long)]
+        ttl_secs: Option<u64>,
+        /// Low, normal or urgent. Urgent transfers are submitted first, the fee tier follows the
+        /// priority unless --fee-tier or --max-fee-gwei is given
+        #[arg(long, value_parser = parse_priority)]
+        priority: Option<TxPriority>,
+    },
+    /// Chains of the node an address can live on going by its format
+    Detect { address: String },
+    /// Past transfers with an address, attestation response times and the warnings raised about it
+    Counterparty { address: String },
+    /// Suggested fee tiers for the next block
+    Fees {
+        /// eth or bnb
+        #[arg(long, value_parser = parse_chain)]
+        chain: ChainSupported,
+    },
+    /// Network fees paid per chain and fee tier, and the tiers that keep overpaying
+    FeeStats,
+    /// Fund an account on the in-memory chain of a node started with --dev
+    Faucet {
+        /// eth, bnb, sol or dot
+        #[arg(long, value_parser = parse_chain)]
+        chain: ChainSupported,
+        /// Account to fund, defaults to the signer address
+        #[arg(long)]
+        to: Option<String>,
+        /// Amount in whole native tokens, e.g. 10
+        #[arg(long)]
+        amount: String,
+    },
+    /// Generate test accounts registered to the node, on a --dev or testnet node
+    TestAccounts {
+        /// eth or bnb
+        #[arg(long, value_parser = parse_chain)]
+        chain: ChainSupported,
+        /// Number of accounts, up to the free account slots of the node
+        #[arg(long, default_value_t = 1)]
+        count: u8,
+        /// Amount in whole native tokens each account is funded with, --dev nodes only
+        #[arg(long)]
+        amount: Option<String>,
+    },
+    /// Allow a spender to move a bounded amount of a contract token, confirmed like a transfer
+    Approve {
+        /// Contract token, e.g. usdc-eth
+        #[arg(long, value_parser = parse_token)]
+        token: Token,
+        /// Spender contract or account
+        #[arg(long)]
+        spender: String,
+        /// Amount in whole tokens, required unless --unlimited
+        #[arg(long, required_unless_present = "unlimited")]
+        amount: Option<String>,
+        /// Approve an unlimited amount, the spender can move the whole balance at any time
+        #[arg(long, conflicts_with = "amount")]
+        unlimited: bool,
+        /// Owner address, defaults to the signer address
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Revoke the allowance of a spender
+    Revoke {
+        #[arg(long, value_parser = parse_token)]
+        token: Token,
+        #[arg(long)]
+        spender: String,
+        /// Owner address, defaults to the signer address
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// List outstanding allowances granted through the node
+    Approvals {
+        /// Only the allowances of this owner
+        #[arg(long)]
+        owner: Option<String>,
+    },
+    /// List transactions waiting on an action
+    Pending,
+    /// List submitted transactions, succeeded and failed, newest first
+    History {
+        /// Only the transactions with this counterparty address, reference or tag, or whose sender
+        /// name or memo contains it
+        #[arg(long)]
+        search: Option<String>,
+        /// eth, bnb, sol or dot
+        #[arg(long, value_parser = parse_chain)]
+        chain: Option<ChainSupported>,
+        /// Smallest amount, in the smallest unit of the token
+        #[arg(long)]
+        min_amount: Option<u128>,
+        /// Largest amount, in the smallest unit of the token
+        #[arg(long)]
+        max_amount: Option<u128>,
+        /// Only the succeeded transactions
+        #[arg(long, conflicts_with = "failed")]
+        succeeded: bool,
+        /// Only the failed transactions
+        #[arg(long)]
+        failed: bool,
+        /// Most transactions listed
+        #[arg(long)]
+        limit: Option<u32>,
+    },
+    /// Explorer link, block and fee paid of a submitted transaction
+    Receipt { tx_hash: B256 },
+    /// Originator and beneficiary data attached to a transfer
+    TravelRule {
+        /// Transfer id printed by `vane send`
+        tx_id: B256,
+    },
+    /// Where a transfer is stuck and what to do about it
+    Diagnose {
+        /// Transfer id printed by `vane send`
+        tx_id: B256,
+    },
+    /// Drive a stuck transfer again instead of sending a new one
+    Retry {
+        /// Transfer id printed by `vane send`
+        tx_id: B256,
+        /// attestation, simulation or broadcast, the stage the transfer waits at by default
+        #[arg(long, value_parser = parse_retry_stage)]
+        stage: Option<RetryStage>,
+    },
+    /// List attestation requests waiting for the receiver, oldest first
+    Inbox {
+        /// eth or bnb
+        #[arg(long, value_parser = parse_chain)]
+        chain: Option<ChainSupported>,
+        /// Only requests received at least this many seconds ago
+        #[arg(long)]
+        min_age: Option<u64>,
+        /// Only requests received at most this many seconds ago
+        #[arg(long)]
+        max_age: Option<u64>,
+    },
+    /// Decline attestation requests, the senders see them as failed
+    Decline {
+        /// Transaction ids as shown by `vane inbox`
+        #[arg(required = true)]
+        tx_ids: Vec<u32>,
+        /// not-my-address, wrong-chain, unexpected-amount or suspected-phishing, told to the
+        /// senders instead of a plain failure
+        #[arg(long, value_parser = parse_reject_reason)]
+        reason: Option<RejectReason>,
+        /// Address to send to instead, signed with the private key of the rejected address
+        #[arg(long, requires_all = ["reason", "correct_chain"])]
+        correct_to: Option<String>,
+        /// Network to send on instead, with `--correct-to`
+        #[arg(long, value_parser = parse_chain, requires = "correct_to")]
+        correct_chain: Option<ChainSupported>,
+    },
+    /// Send a rejected transfer again to the address and network the receiver proposed
+    Restart {
+        /// Transfer id printed by `vane send`
+        tx_id: B256,
+    },
+    /// Split one payment into transfers to several receivers, tokens or chains, each confirmed on
+    /// its own
+    Split {
+        /// token:receiver:amount in whole tokens, e.g. usdc-eth:0x…:600, repeatable
+        #[arg(long = "part", required = true, value_parser = parse_payment_part)]
+        parts: Vec<PaymentPart>,
+        /// Sender address, defaults to the signer address
+        #[arg(long)]
+        from: Option<String>,
+        /// Invoice or order id the payment settles, kept with every part
+        #[arg(long)]
+        reference: Option<String>,
+        /// Label of the payment, searchable in the history, repeatable
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// Transfers of a split payment and whether it settled
+    Payment {
+        /// Payment id printed by `vane split`
+        payment_id: B256,
+    },
+    /// Confirm a pending transaction, as receiver (attestation), as sender (submission) or as the
+    /// designated second approver
+    Confirm {
+        /// Transaction id as shown by `vane pending`
+        tx_id: u32,
+        /// Sign even though a warning says the funds will be lost
+        #[arg(long)]
+        accept_risk: bool,
+        /// As receiver, settle for this lower amount in whole tokens
+        #[arg(long)]
+        accept_amount: Option<String>,
+    },
+    /// Unlock the send operations of a node started with a send passphrase, read from stdin when
+    /// not set
+    Unlock {
+        #[arg(long, env = "VANE_SEND_PASSPHRASE", hide_env_values = true)]
+        passphrase: Option<String>,
+    },
+    /// Lock the send operations again before the unlock window passed
+    Lock,
+    /// Live dashboard of transactions, peers, chain rpc health and history
+    Tui,
+    /// Peer discovery
+    Peers {
+        #[command(subcommand)]
+        command: PeersCommand,
+    },
+    /// Operator policies evaluated at each transaction transition
+    Policies {
+        #[command(subcommand)]
+        command: PoliciesCommand,
+    },
+    /// Signing devices paired with a watch-only node
+    Signers {
+        #[command(subcommand)]
+        command: SignersCommand,
+    },
+    /// Third party identity attestations and verification badges
+    Identity {
+        #[command(subcommand)]
+        command: IdentityCommand,
+    },
+    /// Other devices of the user syncing contacts, policies and pending transfers with the node
+    Devices {
+        #[command(subcommand)]
+        command: DevicesCommand,
+    },
+    /// Tokens the accounts of the node accept, senders are refused transfers of other tokens
+    Assets {
+        #[command(subcommand)]
+        command: AssetsCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum AssetsCommand {
+    /// Advertise the tokens an account of the node accepts, in order of preference. Without
+    /// --token the account accepts any token again
+    Set {
+        #[arg(long)]
+        account: String,
+        /// e.g. usdc-eth, repeat for each accepted token
+        #[arg(long = "token", value_parser = parse_token)]
+        tokens: Vec<Token>,
+    },
+    /// Tokens an address accepts, as advertised by its node
+    Show { address: String },
+}
+
+#[derive(Subcommand)]
+enum DevicesCommand {
+    /// Show a single-use pairing code, enter it or scan its uri on the new device
+    Code,
+    /// Pair with the device showing the pairing uri `vane-pair:<code>@<multi addr>`
+    Pair { uri: String },
+    /// Stop syncing with a paired device
+    Unpair { peer_id: String },
+    /// List the paired devices
+    List,
+    /// Pending transfers of the paired devices, as last synced
+    Transfers,
+}
+
+#[derive(Subcommand)]
+enum IdentityCommand {
+    /// Attest an account as issuer, signed with --private-key, the attestation is printed as json
+    Issue {
+        account: String,
+        /// e.g. "employee of Acme"
+        #[arg(long)]
+        claim: String,
+        /// Unix timestamp in seconds the attestation expires at
+        #[arg(long)]
+        expires_at: Option<u64>,
+    },
+    /// Store an attestation json file with the registry record of the node
+    Add { file: std::path::PathBuf },
+    /// Verification badges of an account from the issuers the node trusts
+    Badges { account: String },
+    /// Revoke the --private-key account as compromised, transfers to it are refused from then on.
+    /// A revocation cannot be withdrawn
+    Revoke,
+    /// List the account revocations the node verified
+    Revocations,
+}
+
+#[derive(Subcommand)]
+enum SignersCommand {
+    /// Pair the signer of --private-key, the node then sends for its account
+    Pair,
+    /// Remove a paired signing device
+    Unpair { account: String },
+    /// List the paired accounts
+    List,
+}
+
+#[derive(Subcommand)]
+enum PoliciesCommand {
+    /// Replace the node policies with the rules of a json file
+    Set { file: std::path::PathBuf },
+    /// List the policies in force
+    List,
+}
+
+#[derive(Subcommand)]
+enum PeersCommand {
+    /// List peers registered to vane discovery
+    List,
+    /// Show version, rtt, request success rate, last seen and bytes exchanged per peer
+    Stats,
+    /// Bind the --private-key account to the node, shared with the peers it connects to
+    Bind,
+    /// List the account to node bindings the node verified
+    Bindings,
+    /// Rotate the node to a new p2p identity, binding the --private-key account to it again
+    Rotate {
+        /// How long the old identity keeps answering, one day by default
+        #[arg(long)]
+        grace_secs: Option<u64>,
+    },
+}
+
+fn parse_chain(chain: &str) -> Result<ChainSupported, String> {
+    match chain.to_lowercase().as_str() {
+        "eth" | "ethereum" => Ok(ChainSupported::Ethereum),
+        "bnb" | "bsc" => Ok(ChainSupported::Bnb),
+        "sol" | "solana" => Ok(ChainSupported::Solana),
+        "dot" | "polkadot" => Ok(ChainSupported::Polkadot),
+        other => Err(format!("unsupported chain: {other}")),
+    }
+}
+
+fn parse_token(token: &str) -> Result<Token, String> {
+    match token.to_lowercase().as_str() {
+        "dot" => Ok(Token::Dot),
+        "bnb" => Ok(Token::Bnb),
+        "sol" => Ok(Token::Sol),
+        "eth" => Ok(Token::Eth),
+        "usdt-sol" => Ok(Token::UsdtSol),
+        "usdc-sol" => Ok(Token::UsdcSol),
+        "usdt-eth" => Ok(Token::UsdtEth),
+        "usdc-eth" => Ok(Token::UsdcEth),
+        "usdt-dot" => Ok(Token::UsdtDot),
+        other => Err(format!("unsupported token: {other}")),
+    }
+}
+
+fn parse_payment_part(part: &str) -> Result<PaymentPart, String> {
+    let [token, receiver, amount] = part.splitn(3, ':').collect::<Vec<_>>()[..] else {
+        return Err(format!("expected token:receiver:amount, got {part}"));
+    };
+    let token = parse_token(token)?;
+    Ok(PaymentPart {
+        receiver: receiver.to_string(),
+        amount: parse_units(amount, token.decimals()).map_err(|err| err.to_string())?,
+        token,
+        memo: None,
+    })
+}
+
+fn parse_fee_tier(tier: &str) -> Result<FeeTier, String> {
+    match tier.to_lowercase().as_str() {
+        "low" => Ok(FeeTier::Low),
+        "medium" => Ok(FeeTier::Medium),
+        "high" => Ok(FeeTier::High),
+        other => Err(format!("unsupported fee tier: {other}")),
+    }
+}
+
+fn parse_priority(priority: &str) -> Result<TxPriority, String> {
+    match priority.to_lowercase().as_str() {
+        "low" => Ok(TxPriority::Low),
+        "normal" => Ok(TxPriority::Normal),
+        "urgent" => Ok(TxPriority::Urgent),
+        other => Err(format!("unsupported priority: {other}")),
+    }
+}
+
+fn parse_reject_reason(reason: &str) -> Result<RejectReason, String> {
+    match reason.to_lowercase().as_str() {
+        "not-my-address" => Ok(RejectReason::NotMyAddress),
+        "wrong-chain" => Ok(RejectReason::WrongChain),
+        "unexpected-amount" => Ok(RejectReason::UnexpectedAmount),
+        "suspected-phishing" => Ok(RejectReason::SuspectedPhishing),
+        other => Err(format!("unsupported reject reason: {other}")),
+    }
+}
+
+fn parse_retry_stage(stage: &str) -> Result<RetryStage, String> {
+    match stage.to_lowercase().as_str() {
+        "attestation" => Ok(RetryStage::Attestation),
+        "simulation" => Ok(RetryStage::Simulation),
+        "broadcast" => Ok(RetryStage::Broadcast),
+        other => Err(format!("unsupported retry stage: {other}")),
+    }
+}
+
+fn parse_environment(environment: &str) -> Result<NetworkEnvironment, String> {
+    match environment.to_lowercase().as_str() {
+        "mainnet" => Ok(NetworkEnvironment::Mainnet),
+        "testnet" | "sepolia" | "bnb-testnet" | "solana-devnet" | "westend" => {
+            Ok(NetworkEnvironment::Testnet)
+        }
+        other => Err(format!("unsupported environment: {other}")),
+    }
+}
+
+/// `from` or the signer address
+fn owner(
+    from: Option<String>,
+    chain: ChainSupported,
+    private_key: &Option<String>,
+) -> Result<String, anyhow::Error> {
+    match from {
+        Some(from) => Ok(from),
+        None => {
+            ensure_evm(chain)?;
+            Ok(signer(private_key)?.address().to_string())
+        }
+    }
+}
+
+fn signer(private_key: &Option<String>) -> Result<PrivateKeySigner, anyhow::Error> {
+    let key = private_key.as_ref().ok_or(anyhow!(
+        "a private key is required, pass --private-key or set VANE_PRIVATE_KEY"
+    ))?;
+    key.parse::<PrivateKeySigner>()
+        .map_err(|err| anyhow!("invalid private key; caused by: {err}"))
+}
+
+fn ensure_evm(network: ChainSupported) -> Result<(), anyhow::Error> {
+    match network {
+        ChainSupported::Ethereum | ChainSupported::Bnb => Ok(()),
+        other => Err(anyhow!(
+            "signing for {} is not supported by the cli yet",
+            String::from(other)
+        )),
+    }
+}
+
+fn print_tx(tx: &TxStateMachine) {
+    println!(
+        "{:<10} {:<12} {:<28} {:<44} {:<44} {}",
+        tx.tx_nonce,
+        String::from(tx.network),
+        format!("{:?}", tx.status),
+        tx.sender_address,
+        tx.receiver_address,
+        tx.amount
+    );
+}
+
+/// summary of a counterparty profile, each line starting with `prefix`
+fn print_counterparty(profile: &CounterpartyProfile, prefix: &str) {
+    let (Some(first_seen), Some(last_seen)) = (profile.first_seen, profile.last_seen) else {
+        println!("{prefix}{} never dealt with before", profile.address);
+        return;
+    };
+    println!(
+        "{prefix}{}, {} sent to, {} received from, {} succeeded, {} failed, seen from {} to {}",
+        profile.address,
+        profile.sent,
+        profile.received,
+        profile.succeeded,
+        profile.failed,
+        first_seen,
+        last_seen
+    );
+    for volume in &profile.volumes {
+        println!(
+            "{prefix}volume {} {}",
+            format_units(volume.amount, volume.token.decimals()),
+            volume.token.symbol()
+        );
+    }
+    if let (Some(avg), Some(max)) = (profile.avg_attestation_secs, profile.max_attestation_secs) {
+        println!(
+            "{prefix}{} attestations in {avg}s on average, {max}s at most",
+            profile.attestations
+        );
+    }
+    for warning in &profile.warnings {
+        println!(
+            "{prefix}{:?} severity warning {} raised {} times, last at {}",
+            warning.severity, warning.code, warning.count, warning.last_seen
+        );
+    }
+}
+
+async fn confirm(
+    client: &VaneClient<WsClient>,
+    private_key: &Option<String>,
+    tx_id: u32,
+    accept_risk: bool,
+    accept_amount: Option<String>,
+) -> Result<(), anyhow::Error> {
+    let tx = client
+        .pending_updates()
+        .await?
+        .into_iter()
+        .find(|tx| tx.tx_nonce == tx_id)
+        .ok_or(anyhow!("no pending tx with id {tx_id}"))?;
+    ensure_evm(tx.network)?;
+    let signer = signer(private_key)?;
+
+    match tx.status.clone() {
+        TxStatus::Genesis => {
+            // receiver attests ownership of the receiving address
+            let msg = tx.receiver_address.to_string();
+            let signable_msg = format!("{ETH_SIG_MSG_PREFIX}{}{msg}", msg.len());
+            let signature = Vec::from(signer.sign_hash_sync(&keccak256(signable_msg.as_bytes()))?);
+            match accept_amount {
+                Some(amount) => {
+                    let token = tx.token.unwrap_or(Token::native(tx.network));
+                    let amount = parse_units(&amount, token.decimals())?;
+                    client
+                        .confirm_as_receiver_accepting(tx, signature, amount)
+                        .await?;
+                    println!("tx {tx_id}: receiver attestation sent, accepting {amount}");
+                }
+                None => {
+                    client.confirm_as_receiver(tx, signature).await?;
+                    println!("tx {tx_id}: receiver attestation sent");
+                }
+            }
+        }
+        TxStatus::RecvAddrConfirmationPassed | TxStatus::NetConfirmed => {
+            let call_payload = tx
+                .call_payload
+                .ok_or(anyhow!("tx {tx_id} has no call payload to sign"))?;
+            if let Some(intent) = &tx.intent {
+                println!("tx {tx_id}: signing {intent}");
+            }
+            if let Some(accepted) = tx.accepted_amount {
+                println!(
+                    "tx {tx_id}: the receiver accepted {accepted} of the {} initiated",
+                    tx.amount
+                );
+            }
+            // what the node knows of the receiver, worth a look before signing
+            let receiver = client.counterparty(tx.receiver_address.to_string()).await?;
+            print_counterparty(&receiver, &format!("tx {tx_id}: receiver "));
+            for warning in &tx.warnings {
+                println!(
+                    "tx {tx_id}: {:?} severity warning {}, {}",
+                    warning.severity, warning.code, warning.message
+                );
+            }
+            for outcome in &tx.policy_outcomes {
+                println!(
+                    "tx {tx_id}: policy {} ({:?}), {}",
+                    outcome.rule_id, outcome.action, outcome.description
+                );
+            }
+            let requires_override = tx
+                .warnings
+                .iter()
+                .any(|warning| warning.requires_override());
+            if requires_override && !accept_risk {
+                Err(anyhow!(
+                    "tx {tx_id} not signed, pass --accept-risk to send it anyway"
+                ))?
+            }
+            let signature = Vec::from(signer.sign_hash_sync(&B256::new(call_payload))?);
+            if requires_override {
+                client
+                    .confirm_as_sender_overriding_warnings(tx, signature)
+                    .await?;
+            } else {
+                client.confirm_as_sender(tx, signature).await?;
+            }
+            println!("tx {tx_id}: sender confirmation sent, submitting");
+        }
+        TxStatus::AwaitingSecondApproval => {
+            // the approver signs off the tx as signed by the sender
+            if let Some(intent) = &tx.intent {
+                println!("tx {tx_id}: approving {intent}");
+            }
+            let signature = signer.sign_message_sync(tx.second_approval_message().as_bytes())?;
+            client.confirm_as_approver(tx, Vec::from(signature)).await?;
+            println!("tx {tx_id}: second approval sent, submitting");
+        }
+        other => Err(anyhow!(
+            "tx {tx_id} can not be confirmed in state {other:?}"
+        ))?,
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    let cli = Cli::parse();
+    let client = match &cli.api_key {
+        Some(api_key) => VaneClient::connect_ws_with_api_key(&cli.node, api_key).await?,
+        None => VaneClient::connect_ws(&cli.node).await?,
+    };
+
+    match cli.command {
+        Command::Send {
+            chain,
+            to,
+            amount,
+            token,
+            from,
+            fee_tier,
+            max_fee_gwei,
+            private,
+            memo,
+            display_name,
+            environment,
+            reference,
+            reference_memo,
+            tags,
+            travel_rule,
+            ttl_secs,
+            priority,
+        } => {
+            let chain = match chain.or(token.map(ChainSupported::from)) {
+                Some(chain) => chain,
+                None => match client.detect_chains(&to).await?.as_slice() {
+                    [chain] => {
+                        let name = String::from(*chain);
+                        println!("sending on {name} going by the receiver address");
+                        *chain
+                    }
+                    [] => Err(anyhow!("{to} is not an address of a chain of the node"))?,
+                    candidates => Err(anyhow!(
+                        "{to} is valid on several chains, pick one of {} with --chain",
+                        candidates
+                            .iter()
+                            .map(|chain| String::from(*chain))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))?,
+                },
+            };
+            let token = token.unwrap_or(Token::native(chain));
+            if ChainSupported::from(token) != chain {
+                Err(anyhow!(
+                    "token {} is not on {}",
+                    String::from(token),
+                    String::from(chain)
+                ))?
+            }
+            if reference_memo && chain != ChainSupported::Solana {
+                Err(anyhow!("--reference-memo is only supported on sol"))?
+            }
+            let memo = if reference_memo {
+                reference.clone()
+            } else {
+                memo
+            };
+            let sender = owner(from, chain, &cli.private_key)?;
+            let amount = parse_units(&amount, token.decimals())?;
+            let fee_preference = if fee_tier.is_some() || max_fee_gwei.is_some() {
+                Some(FeePreference {
+                    tier: fee_tier.unwrap_or_default(),
+                    max_fee_per_gas: max_fee_gwei
+                        .map(|max_fee| parse_units(&max_fee, 9))
+                        .transpose()?,
+                })
+            } else {
+                None
+            };
+            let sender_profile = match display_name {
+                Some(display_name) => {
+                    let canonical = VaneAddress::parse(&sender)?.to_string();
+                    let message = SenderProfile::message(&display_name, &canonical);
+                    let signature =
+                        signer(&cli.private_key)?.sign_message_sync(message.as_bytes())?;
+                    Some(SenderProfile {
+                        display_name,
+                        signature: Vec::from(signature),
+                        ..Default::default()
+                    })
+                }
+                None => None,
+            };
+            let travel_rule = match travel_rule {
+                Some(file) => Some(serde_json::from_str(&std::fs::read_to_string(file)?)?),
+                None => None,
+            };
+            let options = TransferOptions {
+                fee_preference,
+                submission_route: private.then_some(SubmissionRoute::PrivateRelay),
+                memo,
+                sender_profile,
+                environment,
+                reference,
+                tags: (!tags.is_empty()).then_some(tags),
+                travel_rule,
+                ttl_secs,
+                priority,
+                ..Default::default()
+            };
+            let tx_id = client
+                .send_token_with_options(sender, to, amount, token, options)
+                .await?;
+            println!("transfer {tx_id:?} initiated, waiting for receiver attestation; track it with `vane pending`");
+        }
+        Command::Approve {
+            token,
+            spender,
+            amount,
+            unlimited,
+            from,
+        } => {
+            let owner = owner(from, ChainSupported::from(token), &cli.private_key)?;
+            let tx_id = match amount {
+                Some(amount) if !unlimited => {
+                    let amount = parse_units(&amount, token.decimals())?;
+                    client.approve_token(owner, spender, amount, token).await?
+                }
+                _ => {
+                    client
+                        .approve_token_unlimited(owner, spender, token)
+                        .await?
+                }
+            };
+            println!(
+                "approval {tx_id:?} initiated; confirm it with `vane pending` and `vane confirm`"
+            );
+        }
+        Command::Revoke {
+            token,
+            spender,
+            from,
+        } => {
+            let owner = owner(from, ChainSupported::from(token), &cli.private_key)?;
+            let tx_id = client.revoke_approval(owner, spender, token).await?;
+            println!(
+                "revocation {tx_id:?} initiated; confirm it with `vane pending` and `vane confirm`"
+            );
+        }
+        Command::Approvals { owner } => {
+            let approvals = client.approvals(owner).await?;
+            if approvals.is_empty() {
+                println!("no outstanding approvals");
+            }
+            for approval in approvals {
+                let amount = if approval.is_unlimited() {
+                    "unlimited".to_string()
+                } else {
+                    format_units(approval.amount, approval.token.decimals())
+                };
+                println!(
+                    "{:<44} {:<44} {:<10} {}",
+                    approval.owner,
+                    approval.spender,
+                    approval.token.symbol(),
+                    amount
+                );
+            }
+        }
+        Command::Pending => {
+            let pending = client.pending_updates().await?;
+            if pending.is_empty() {
+                println!("no pending transactions");
+            } else {
+                println!(
+                    "{:<10} {:<12} {:<28} {:<44} {:<44} {}",
+                    "TX-ID", "CHAIN", "STATUS", "FROM", "TO", "AMOUNT"
+                );
+                pending.iter().for_each(print_tx);
+            }
+        }
+        Command::History {
+            search,
+            chain,
+            min_amount,
+            max_amount,
+            succeeded,
+            failed,
+            limit,
+        } => {
+            let search = HistorySearch {
+                text: search,
+                network: chain,
+                min_amount,
+                max_amount,
+                success: (succeeded || failed).then_some(succeeded),
+                limit,
+            };
+            let history = client.search_history(search).await?;
+            if history.is_empty() {
+                println!("no transactions");
+            } else {
+                println!(
+                    "{:<12} {:<8} {:<44} {:<44} {:<40} {:<24} {}",
+                    "CHAIN", "RESULT", "FROM", "TO", "AMOUNT", "REFERENCE", "TAGS"
+                );
+            }
+            for tx in history {
+                println!(
+                    "{:<12} {:<8} {:<44} {:<44} {:<40} {:<24} {}",
+                    String::from(tx.network),
+                    if tx.success { "success" } else { "failed" },
+                    tx.sender_name.unwrap_or(tx.sender_address),
+                    tx.receiver_address,
+                    tx.amount,
+                    tx.reference.as_deref().unwrap_or("-"),
+                    tx.tags.join(",")
+                );
+            }
+        }
+        Command::Receipt { tx_hash } => {
+            let tx = client.tx_receipt(H256::from(tx_hash.0)).await?;
+            println!("explorer     {}", tx.explorer_url.as_deref().unwrap_or("-"));
+            match tx.receipt {
+                Some(receipt) => {
+                    println!("block        {}", receipt.block_number);
+                    if let Some(price) = receipt.effective_gas_price {
+                        println!("gas price    {:.3} gwei", price as f64 / 1e9);
+                    }
+                    let native = Token::native(tx.network);
+                    println!(
+                        "fee paid     {} {}",
+                        format_units(receipt.fee_paid, native.decimals()),
+                        native.symbol()
+                    );
+                    println!(
+                        "result       {}",
+                        if receipt.success {
+                            "included"
+                        } else {
+                            "reverted"
+                        }
+                    );
+                }
+                None => println!("not included in a block yet"),
+            }
+        }
+        Command::TravelRule { tx_id } => match client.travel_rule(H256::from(tx_id.0)).await? {
+            Some(data) => println!("{}", serde_json::to_string_pretty(&data)?),
+            None => println!("no travel rule data attached"),
+        },
+        Command::Diagnose { tx_id } => {
+            let diagnosis = client.diagnose_tx(H256::from(tx_id.0)).await?;
+            println!(
+                "{:?}{}, for {}s",
+                diagnosis.stage,
+                if diagnosis.stuck { " (stuck)" } else { "" },
+                diagnosis.waiting_secs
+            );
+            println!("{}", diagnosis.detail);
+            if let Some(explorer_url) = diagnosis.explorer_url {
+                println!("{explorer_url}");
+            }
+            for action in diagnosis.actions {
+                println!("- {action}");
+            }
+        }
+        Command::Restart { tx_id } => {
+            let tx_id = client.restart_with_correction(H256::from(tx_id.0)).await?;
+            println!("transfer {tx_id:?} initiated to the corrected address");
+        }
+        Command::Split {
+            parts,
+            from,
+            reference,
+            tags,
+        } => {
+            let chain = ChainSupported::from(parts[0].token);
+            let sender = owner(from, chain, &cli.private_key)?;
+            let tags = (!tags.is_empty()).then_some(tags);
+            let payment_id = client.split_payment(sender, parts, reference, tags).await?;
+            println!("payment {payment_id:?} initiated; track it with `vane payment`");
+        }
+        Command::Payment { payment_id } => {
+            let payment = client.payment(H256::from(payment_id.0)).await?;
+            println!("{:?}", payment.state);
+            for child in payment.children {
+                let accepted = child
+                    .tx
+                    .accepted_amount
+                    .map(|accepted| format!(", accepted {accepted}"))
+                    .unwrap_or_default();
+                println!(
+                    "{:?} {:<12} {:<44} {}{accepted} {:?}",
+                    child.tx_id,
+                    String::from(child.tx.network),
+                    child.tx.receiver_address,
+                    child.tx.amount,
+                    child.state
+                );
+            }
+        }
+        Command::Retry { tx_id, stage } => {
+            let stage = client.retry_tx(H256::from(tx_id.0), stage).await?;
+            println!("retrying at {stage:?}, follow it with `vane diagnose`");
+        }
+        Command::Inbox {
+            chain,
+            min_age,
+            max_age,
+        } => {
+            let receives = client.pending_receives(chain, min_age, max_age).await?;
+            if receives.is_empty() {
+                println!("no pending attestation requests");
+            } else {
+                println!(
+                    "{:<10} {:<12} {:<28} {:<44} {:<44} {}",
+                    "TX-ID", "CHAIN", "STATUS", "FROM", "TO", "AMOUNT"
+                );
+                for receive in &receives {
+                    print_tx(&receive.tx);
+                    if let Some(profile) = &receive.tx.sender_profile {
+                        let proof = if profile.verified {
+                            "verified"
+                        } else {
+                            "unverified"
+                        };
+                        println!(
+                            "{:<10} sent by {} ({proof}), {} past attestations",
+                            "", profile.display_name, profile.past_interactions
+                        );
+                    }
+                }
+            }
+        }
+        Command::Decline {
+            tx_ids,
+            reason: Some(reason),
+            correct_to: Some(correct_to),
+            correct_chain: Some(correct_chain),
+        } => {
+            let [tx_nonce] = tx_ids[..] else {
+                Err(anyhow!("a correction is proposed for a single transaction"))?
+            };
+            let receive = client
+                .pending_receives(None, None, None)
+                .await?
+                .into_iter()
+                .find(|receive| receive.tx.tx_nonce == tx_nonce)
+                .ok_or_else(|| anyhow!("no pending transaction {tx_nonce}"))?;
+            let address = VaneAddress::parse(&correct_to)?;
+            let message = AddressCorrection::message(receive.tx.tx_id(), correct_chain, &address);
+            let signature = signer(&cli.private_key)?.sign_message_sync(message.as_bytes())?;
+            let correction = AddressCorrection {
+                network: correct_chain,
+                address,
+                signature: Vec::from(signature),
+            };
+            client
+                .reject_with_correction(tx_nonce, reason, correction)
+                .await?;
+            println!("{tx_nonce} rejected, proposed {address} on {correct_chain:?}");
+        }
+        Command::Decline { tx_ids, reason, .. } => {
+            let outcomes = match reason {
+                Some(reason) => {
+                    client
+                        .reject_pending_receives_with_reason(tx_ids, reason)
+                        .await?
+                }
+                None => client.reject_pending_receives(tx_ids).await?,
+            };
+            for outcome in outcomes {
+                match outcome.error {
+                    None => println!("{} declined", outcome.tx_nonce),
+                    Some(err) => println!("{} not declined: {err}", outcome.tx_nonce),
+                }
+            }
+        }
+        Command::Faucet { chain, to, amount } => {
+            let account = owner(to, chain, &cli.private_key)?;
+            let decimals = Token::native(chain).decimals();
+            let balance = client
+                .faucet(&account, parse_units(&amount, decimals)?, chain)
+                .await?;
+            println!("{account} holds {}", format_units(balance, decimals));
+        }
+        Command::TestAccounts {
+            chain,
+            count,
+            amount,
+        } => {
+            let decimals = Token::native(chain).decimals();
+            let amount = amount
+                .map(|amount| parse_units(&amount, decimals))
+                .transpose()?
+                .unwrap_or_default();
+            let accounts = client.provision_test_accounts(count, chain, amount).await?;
+            println!("{:<44} {:<68} {}", "ACCOUNT", "PRIVATE KEY", "BALANCE");
+            for account in accounts {
+                println!(
+                    "{:<44} {:<68} {}",
+                    account.account,
+                    account.private_key,
+                    format_units(account.balance, decimals)
+                );
+            }
+        }
+        Command::Detect { address } => {
+            let chains = client.detect_chains(&address).await?;
+            if chains.is_empty() {
+                println!("{address} is not an address of a chain of the node");
+            }
+            for chain in chains {
+                println!("{}", String::from(chain));
+            }
+        }
+        Command::Counterparty { address } => {
+            print_counterparty(&client.counterparty(&address).await?, "")
+        }
+        Command::FeeStats => {
+            let stats = client.fee_stats().await?;
+            if stats.networks.is_empty() {
+                println!("no transactions with a receipt");
+            }
+            for network in &stats.networks {
+                let native = Token::native(network.network);
+                let fee = |amount: u128| {
+                    format!(
+                        "{} {}",
+                        format_units(amount, native.decimals()),
+                        native.symbol()
+                    )
+                };
+                println!(
+                    "{}: {} transfers, {} paid, {} on average",
+                    String::from(network.network),
+                    network.transfers,
+                    fee(network.total_fee),
+                    fee(network.average_fee)
+                );
+                for tier in &network.tiers {
+                    println!(
+                        "  {:<8} {} transfers, {} on average, {} above the low tier",
+                        format!("{:?}", tier.tier).to_lowercase(),
+                        tier.transfers,
+                        fee(tier.average_fee),
+                        fee(tier.overpaid)
+                    );
+                }
+            }
+            for warning in &stats.warnings {
+                println!("warning: {}", warning.message);
+            }
+        }
+        Command::Fees { chain } => {
+            let tiers = client.fee_tiers(chain).await?;
+            let gwei = |wei: u128| wei as f64 / 1e9;
+            println!(
+                "base fee {:.3} gwei (peak {:.3})",
+                gwei(tiers.base_fee_per_gas),
+                gwei(tiers.peak_base_fee_per_gas)
+            );
+            println!("{:<8} {:<16} {}", "TIER", "MAX FEE", "PRIORITY FEE");
+            for (tier, fee) in [
+                ("low", tiers.low),
+                ("medium", tiers.medium),
+                ("high", tiers.high),
+            ] {
+                println!(
+                    "{:<8} {:<16} {:.3} gwei",
+                    tier,
+                    format!("{:.3} gwei", gwei(fee.max_fee_per_gas)),
+                    gwei(fee.max_priority_fee_per_gas)
+                );
+            }
+        }
+        Command::Confirm {
+            tx_id,
+            accept_risk,
+            accept_amount,
+        } => confirm(&client, &cli.private_key, tx_id, accept_risk, accept_amount).await?,
+        Command::Tui => tui::run(client).await?,
+        Command::Peers {
+            command: PeersCommand::List,
+        } => {
+            for peer in client.list_peers().await? {
+                println!(
+                    "{:<54} {:<40} {}",
+                    peer.peer_id.unwrap_or_default(),
+                    peer.multi_addr.unwrap_or_default(),
+                    peer.account_ids.join(",")
+                );
+            }
+        }
+        Command::Peers {
+            command: PeersCommand::Stats,
+        } => {
+            let format_opt = |value: Option<String>| value.unwrap_or("-".to_string());
+            for peer in client.peer_stats().await? {
+                println!(
+                    "{:<54} {:<12} {:<14} {:>8} {:>6} {:>6} {:>8} {:>12} {:>10} {:>10}",
+                    peer.peer_id,
+                    if peer.connected {
+                        "connected"
+                    } else {
+                        "disconnected"
+                    },
+                    format_opt(peer.agent_version),
+                    format_opt(peer.rtt_ms.map(|rtt| format!("{rtt}ms"))),
+                    peer.requests_sent,
+                    peer.requests_failed,
+                    format_opt(
+                        peer.success_rate
+                            .map(|rate| format!("{:.0}%", rate * 100.0))
+                    ),
+                    format_opt(peer.last_seen.map(|seen| seen.to_string())),
+                    peer.bytes_sent,
+                    peer.bytes_received
+                );
+            }
+        }
+        Command::Peers {
+            command: PeersCommand::Bind,
+        } => {
+            let signer = signer(&cli.private_key)?;
+            let account = signer.address().to_string();
+            let signed_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            let message = client.peer_binding_message(&account, signed_at).await?;
+            let signature = signer.sign_message_sync(message.as_bytes())?;
+            client
+                .add_peer_binding(&account, signed_at, Vec::from(signature))
+                .await?;
+            println!("{account} bound to the node");
+        }
+        Command::Peers {
+            command: PeersCommand::Bindings,
+        } => {
+            for binding in client.peer_bindings().await? {
+                println!(
+                    "{:<44} {:<54} {}",
+                    binding.account, binding.peer_id, binding.signed_at
+                );
+            }
+        }
+        Command::Peers {
+            command: PeersCommand::Rotate { grace_secs },
+        } => {
+            let rotated = client.rotate_node_identity(grace_secs).await?;
+            let rotation = rotated.rotation;
+            println!(
+                "node rotated to {}, {} answers until {}",
+                rotation.new_peer_id, rotation.old_peer_id, rotation.grace_until
+            );
+            let mut rebind_accounts = rotated.rebind_accounts;
+            if cli.private_key.is_some() {
+                let signer = signer(&cli.private_key)?;
+                let account = signer.address().to_string();
+                if let Some(index) = rebind_accounts.iter().position(|bound| *bound == account) {
+                    let message = client
+                        .peer_binding_message(&account, rotation.rotated_at)
+                        .await?;
+                    let signature = signer.sign_message_sync(message.as_bytes())?;
+                    client
+                        .add_peer_binding(&account, rotation.rotated_at, Vec::from(signature))
+                        .await?;
+                    rebind_accounts.remove(index);
+                    println!("{account} bound to the node again");
+                }
+            }
+            for account in rebind_accounts {
+                println!("{account} is still bound to the old identity, run `vane peers bind`");
+            }
+        }
+        Command::Policies {
+            command: PoliciesCommand::Set { file },
+        } => {
+            let rules = parse_policies(&std::fs::read_to_string(&file)?)?;
+            let count = rules.len();
+            client.set_policies(rules).await?;
+            println!("{count} policy rules in force");
+        }
+        Command::Policies {
+            command: PoliciesCommand::List,
+        } => {
+            let rules = client.policies().await?;
+            if rules.is_empty() {
+                println!("no policies set");
+            }
+            for rule in rules {
+                println!(
+                    "{:<24} {:<22} {}",
+                    rule.id,
+                    format!("{:?}", rule.action),
+                    rule.description
+                );
+            }
+        }
+        Command::Unlock { passphrase } => {
+            let passphrase = match passphrase {
+                Some(passphrase) => passphrase,
+                None => {
+                    eprint!("send passphrase: ");
+                    let mut line = String::new();
+                    std::io::stdin().read_line(&mut line)?;
+                    line.trim_end_matches(['\r', '\n']).to_string()
+                }
+            };
+            let unlocked_until = client.unlock(passphrase).await?;
+            println!("send operations unlocked until {unlocked_until}");
+        }
+        Command::Lock => {
+            client.lock().await?;
+            println!("send operations locked");
+        }
+        Command::Signers {
+            command: SignersCommand::Pair,
+        } => {
+            let signer = signer(&cli.private_key)?;
+            let account = signer.address().to_string();
+            let message = client.pairing_message(&account).await?;
+            let signature = signer.sign_message_sync(message.as_bytes())?;
+            client.pair_signer(&account, Vec::from(signature)).await?;
+            println!("{account} paired");
+        }
+        Command::Signers {
+            command: SignersCommand::Unpair { account },
+        } => {
+            client.unpair_signer(&account).await?;
+            println!("{account} unpaired");
+        }
+        Command::Identity {
+            command:
+                IdentityCommand::Issue {
+                    account,
+                    claim,
+                    expires_at,
+                },
+        } => {
+            let signer = signer(&cli.private_key)?;
+            let mut attestation = IdentityAttestation {
+                account,
+                issuer: signer.address().to_string(),
+                claim,
+                issued_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+                expires_at,
+                signature: vec![],
+            };
+            let signature = signer.sign_message_sync(attestation.message().as_bytes())?;
+            attestation.signature = Vec::from(signature);
+            println!("{}", serde_json::to_string_pretty(&attestation)?);
+        }
+        Command::Identity {
+            command: IdentityCommand::Add { file },
+        } => {
+            let attestation: IdentityAttestation =
+                serde_json::from_str(&std::fs::read_to_string(&file)?)?;
+            let account = attestation.account.clone();
+            client.add_attestation(attestation).await?;
+            println!("attestation of {account} stored");
+        }
+        Command::Identity {
+            command: IdentityCommand::Badges { account },
+        } => {
+            let badges = client.badges(&account).await?;
+            if badges.is_empty() {
+                println!("no verification badges");
+            }
+            for badge in badges {
+                println!("{:<24} {}", badge.issuer_name, badge.claim);
+            }
+        }
+        Command::Identity {
+            command: IdentityCommand::Revoke,
+        } => {
+            let signer = signer(&cli.private_key)?;
+            let account = signer.address().to_string();
+            let revoked_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            let message = client.revocation_message(&account, revoked_at).await?;
+            let signature = signer.sign_message_sync(message.as_bytes())?;
+            client
+                .revoke_account(&account, revoked_at, Vec::from(signature))
+                .await?;
+            println!("{account} revoked as compromised");
+        }
+        Command::Identity {
+            command: IdentityCommand::Revocations,
+        } => {
+            for revocation in client.revocations().await? {
+                println!("{:<44} {}", revocation.account, revocation.revoked_at);
+            }
+        }
+        Command::Signers {
+            command: SignersCommand::List,
+        } => {
+            let accounts = client.paired_signers().await?;
+            if accounts.is_empty() {
+                println!("no signing device paired");
+            }
+            accounts.iter().for_each(|account| println!("{account}"));
+        }
+        Command::Devices {
+            command: DevicesCommand::Code,
+        } => {
+            let pairing = client.device_pairing_code().await?;
+            println!(
+                "pairing code {} expires at {}",
+                pairing.code, pairing.expires_at
+            );
+            println!("{}", pairing.uri);
+        }
+        Command::Devices {
+            command: DevicesCommand::Pair { uri },
+        } => {
+            let device = client.pair_device(uri).await?;
+            println!("paired with {}", device.peer_id);
+        }
+        Command::Devices {
+            command: DevicesCommand::Unpair { peer_id },
+        } => {
+            client.unpair_device(&peer_id).await?;
+            println!("{peer_id} unpaired");
+        }
+        Command::Devices {
+            command: DevicesCommand::List,
+        } => {
+            let devices = client.paired_devices().await?;
+            if devices.is_empty() {
+                println!("no device paired");
+            }
+            for device in devices {
+                println!("{:<54} {}", device.peer_id, device.multi_addr);
+            }
+        }
+        Command::Devices {
+            command: DevicesCommand::Transfers,
+        } => {
+            client.device_transfers().await?.iter().for_each(print_tx);
+        }
+        Command::Assets {
+            command: AssetsCommand::Set { account, tokens },
+        } => {
+            let accepts_any = tokens.is_empty();
+            client.set_accepted_assets(&account, tokens).await?;
+            if accepts_any {
+                println!("{account} accepts any token");
+            } else {
+                println!("accepted tokens of {account} advertised");
+            }
+        }
+        Command::Assets {
+            command: AssetsCommand::Show { address },
+        } => {
+            let tokens = client.accepted_assets(&address).await?;
+            if tokens.is_empty() {
+                println!("accepts any token");
+            }
+            for token in tokens {
+                println!("{:<6} on {:?}", token.symbol(), ChainSupported::from(token));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,281 @@
+// terminal dashboard for operators
+// live tx states are driven by the `subscribeTxUpdates` subscription,
+// peers, chain rpc health and history are refreshed periodically
+
+use primitives::data_structure::{
+    BreakerState, ChainHealth, DbTxStateMachine, Discovery, TxStateMachine,
+};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::widgets::{Block, Paragraph, Row, Table};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use vane_client::jsonrpsee::ws_client::WsClient;
+use vane_client::VaneClient;
+
+const TICK: Duration = Duration::from_millis(250);
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+const HEALTH_INTERVAL: Duration = Duration::from_secs(30);
+
+enum Update {
+    Tx(TxStateMachine),
+    Pending(Vec<TxStateMachine>),
+    Peers(Vec<Discovery>),
+    Health(Vec<ChainHealth>),
+    History(Vec<DbTxStateMachine>),
+    Error(String),
+    Quit,
+}
+
+#[derive(Default)]
+struct Dashboard {
+    txs: BTreeMap<u32, TxStateMachine>,
+    peers: Vec<Discovery>,
+    health: Vec<ChainHealth>,
+    history: Vec<DbTxStateMachine>,
+    last_error: Option<String>,
+}
+
+impl Dashboard {
+    /// returns false when the dashboard should exit
+    fn apply(&mut self, update: Update) -> bool {
+        match update {
+            Update::Tx(tx) => {
+                self.txs.insert(tx.tx_nonce, tx);
+            }
+            Update::Pending(pending) => {
+                for tx in pending {
+                    self.txs.insert(tx.tx_nonce, tx);
+                }
+            }
+            Update::Peers(peers) => self.peers = peers,
+            Update::Health(health) => self.health = health,
+            Update::History(history) => self.history = history,
+            Update::Error(err) => self.last_error = Some(err),
+            Update::Quit => return false,
+        }
+        true
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [txs_area, middle_area, history_area, status_area] = Layout::vertical([
+            Constraint::Percentage(40),
+            Constraint::Percentage(30),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [peers_area, health_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Fill(1)])
+                .areas(middle_area);
+
+        let tx_rows = self.txs.values().rev().map(|tx| {
+            Row::new(vec![
+                tx.tx_nonce.to_string(),
+                String::from(tx.network),
+                format!("{:?}", tx.status),
+                tx.sender_address.to_string(),
+                tx.receiver_address.to_string(),
+                tx.amount.to_string(),
+            ])
+        });
+        let txs = Table::new(
+            tx_rows,
+            [
+                Constraint::Length(8),
+                Constraint::Length(10),
+                Constraint::Length(28),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Length(24),
+            ],
+        )
+        .header(Row::new(vec!["TX-ID", "CHAIN", "STATUS", "FROM", "TO", "AMOUNT"]).bold())
+        .block(Block::bordered().title(" live transactions "));
+        frame.render_widget(txs, txs_area);
+
+        let peer_rows = self.peers.iter().map(|peer| {
+            Row::new(vec![
+                peer.peer_id.clone().unwrap_or_default(),
+                peer.account_ids.join(","),
+            ])
+        });
+        let peers = Table::new(peer_rows, [Constraint::Percentage(50), Constraint::Fill(1)])
+            .header(Row::new(vec!["PEER", "ACCOUNTS"]).bold())
+            .block(Block::bordered().title(format!(" peers ({}) ", self.peers.len())));
+        frame.render_widget(peers, peers_area);
+
+        let health_rows = self.health.iter().map(|health| {
+            let paused = health.breaker != BreakerState::Closed;
+            let style = match (health.reachable, paused) {
+                (true, false) => Style::default().fg(Color::Green),
+                (true, true) => Style::default().fg(Color::Yellow),
+                (false, _) => Style::default().fg(Color::Red),
+            };
+            Row::new(vec![
+                String::from(health.network),
+                health.environment.name(health.network).to_string(),
+                match (health.reachable, paused) {
+                    (true, false) => "up",
+                    // submissions wait for the circuit breaker to close
+                    (true, true) => "paused",
+                    (false, _) => "down",
+                }
+                .to_string(),
+                format!("{}ms", health.latency_ms),
+            ])
+            .style(style)
+        });
+        let health = Table::new(
+            health_rows,
+            [
+                Constraint::Length(10),
+                Constraint::Length(14),
+                Constraint::Length(6),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(Row::new(vec!["CHAIN", "ENV", "RPC", "LATENCY"]).bold())
+        .block(Block::bordered().title(" chain rpc health "));
+        frame.render_widget(health, health_area);
+
+        let history_rows = self.history.iter().rev().map(|tx| {
+            let style = if tx.success {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default().fg(Color::Red)
+            };
+            Row::new(vec![
+                String::from(tx.network),
+                if tx.success { "success" } else { "failed" }.to_string(),
+                format!("0x{}", alloy::hex::encode(&tx.tx_hash)),
+                tx.amount.to_string(),
+            ])
+            .style(style)
+        });
+        let history = Table::new(
+            history_rows,
+            [
+                Constraint::Length(10),
+                Constraint::Length(8),
+                Constraint::Fill(1),
+                Constraint::Length(24),
+            ],
+        )
+        .header(Row::new(vec!["CHAIN", "RESULT", "TX HASH", "AMOUNT"]).bold())
+        .block(Block::bordered().title(" recent history "));
+        frame.render_widget(history, history_area);
+
+        let status = match &self.last_error {
+            Some(err) => Paragraph::new(format!(" q: quit | last error: {err}")).red(),
+            None => Paragraph::new(" q: quit"),
+        };
+        frame.render_widget(status, status_area);
+    }
+}
+
+async fn watch_txs(client: Arc<VaneClient<WsClient>>, updates: UnboundedSender<Update>) {
+    let mut subscription = match client.subscribe_updates().await {
+        Ok(subscription) => subscription,
+        Err(err) => {
+            let _ = updates.send(Update::Error(format!("tx subscription failed: {err}")));
+            return;
+        }
+    };
+    while let Some(tx) = subscription.next().await {
+        let update = match tx {
+            Ok(tx) => Update::Tx(tx),
+            Err(err) => Update::Error(format!("tx subscription: {err}")),
+        };
+        if updates.send(update).is_err() {
+            break;
+        }
+    }
+}
+
+async fn refresh(client: Arc<VaneClient<WsClient>>, updates: UnboundedSender<Update>) {
+    let mut refresh = tokio::time::interval(REFRESH_INTERVAL);
+    let mut health = tokio::time::interval(HEALTH_INTERVAL);
+    loop {
+        let update = tokio::select! {
+            _ = refresh.tick() => {
+                let pending = client.pending_updates().await.map(Update::Pending);
+                let peers = client.list_peers().await.map(Update::Peers);
+                let history = client.tx_history().await.map(Update::History);
+                vec![pending, peers, history]
+            }
+            _ = health.tick() => vec![client.chain_health().await.map(Update::Health)],
+        };
+        for update in update {
+            let update = update.unwrap_or_else(|err| Update::Error(err.to_string()));
+            if updates.send(update).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// terminal input is blocking, read it on its own thread
+fn read_input(updates: UnboundedSender<Update>) {
+    loop {
+        match event::read() {
+            Ok(Event::Key(key))
+                if key.kind == KeyEventKind::Press
+                    && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) =>
+            {
+                let _ = updates.send(Update::Quit);
+                return;
+            }
+            Ok(_) => {}
+            Err(err) => {
+                let _ = updates.send(Update::Error(format!("terminal input: {err}")));
+                let _ = updates.send(Update::Quit);
+                return;
+            }
+        }
+    }
+}
+
+async fn run_dashboard(
+    terminal: &mut DefaultTerminal,
+    mut updates: UnboundedReceiver<Update>,
+) -> Result<(), anyhow::Error> {
+    let mut dashboard = Dashboard::default();
+    let mut tick = tokio::time::interval(TICK);
+    loop {
+        terminal.draw(|frame| dashboard.draw(frame))?;
+        tokio::select! {
+            update = updates.recv() => match update {
+                Some(update) => {
+                    if !dashboard.apply(update) {
+                        return Ok(());
+                    }
+                }
+                None => return Ok(()),
+            },
+            _ = tick.tick() => {}
+        }
+    }
+}
+
+/// run the dashboard until the user quits
+pub async fn run(client: VaneClient<WsClient>) -> Result<(), anyhow::Error> {
+    let client = Arc::new(client);
+    let (updates_tx, updates_rx) = unbounded_channel();
+
+    let watch_handle = tokio::spawn(watch_txs(client.clone(), updates_tx.clone()));
+    let refresh_handle = tokio::spawn(refresh(client, updates_tx.clone()));
+    std::thread::spawn(move || read_input(updates_tx));
+
+    let mut terminal = ratatui::init();
+    let result = run_dashboard(&mut terminal, updates_rx).await;
+    ratatui::restore();
+
+    watch_handle.abort();
+    refresh_handle.abort();
+    result
+}
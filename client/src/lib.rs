@@ -0,0 +1,1055 @@
+//! Typed client for a vane node
+//! wraps jsonrpsee ws and http clients so bots and services can drive vane flows
+//! without hand writing json-rpc calls
+//!
+//! builds for wasm32 as well, the light mode for browser extensions: sender side checks run locally
+//! and the node is reached over the browser websocket, http is native only
+//!
+//! ```ignore
+//! let client = VaneClient::connect_ws("ws://127.0.0.1:8000").await?;
+//! let mut updates = client.subscribe_updates().await?;
+//! client.send_token(sender, receiver, 1_000, Token::Eth).await?;
+//! while let Some(Ok(tx)) = updates.next().await { /* sign and confirm */ }
+//! ```
+
+use core::fmt::{Display, Formatter};
+use jsonrpsee::core::client::{Client, ClientT, Subscription, SubscriptionClientT};
+use jsonrpsee::core::Error as RpcError;
+#[cfg(not(target_arch = "wasm32"))]
+use jsonrpsee::http_client::{HeaderMap, HeaderValue, HttpClient, HttpClientBuilder};
+use jsonrpsee::rpc_params;
+use jsonrpsee::types::error::CallError;
+#[cfg(target_arch = "wasm32")]
+use jsonrpsee::wasm_client::WasmClientBuilder;
+#[cfg(not(target_arch = "wasm32"))]
+use jsonrpsee::ws_client::WsClientBuilder;
+use primitives::anchors::{AnchorCall, BindingAnchor};
+use primitives::approvals::{Approval, PermitPayload};
+use primitives::attestations::{IdentityAttestation, VerificationBadge};
+use primitives::compute_budget::ComputeBudgetTiers;
+use primitives::counterparty::CounterpartyProfile;
+use primitives::data_structure::{
+    AddressCorrection, BulkOutcome, ChainHealth, ChainSupported, DbTxStateMachine, Discovery,
+    PeerStats, PendingReceive, RejectReason, SenderProfile, SubmissionRoute, Token, TxPriority,
+    TxStateMachine, H256,
+};
+use primitives::device_sync::{DevicePairingCode, PairedDevice};
+use primitives::diagnosis::{RetryStage, TxDiagnosis};
+use primitives::environment::{NetworkEnvironment, TestAccount};
+use primitives::errors::VaneRpcError;
+use primitives::fees::{FeePreference, FeeStats, FeeTiers};
+use primitives::history::HistorySearch;
+use primitives::identity_rotation::RotatedIdentity;
+use primitives::locale::{AmountLocale, LocalizedAmount};
+use primitives::payments::{PaymentPart, PaymentStatus};
+use primitives::peer_exchange::PeerBinding;
+use primitives::policy::PolicyRule;
+use primitives::revocation::AccountRevocation;
+use primitives::routes::RouteSuggestion;
+use primitives::travel_rule::TravelRuleData;
+use primitives::validation::validate_transfer;
+
+pub use jsonrpsee;
+
+/// optional parameters of `initiateTransaction`
+#[derive(Clone, Debug, Default)]
+pub struct TransferOptions {
+    /// retries with the same key return the first tx id, derived from the transfer when missing
+    pub idempotency_key: Option<String>,
+    /// fee tier and max fee per gas, evm tokens only
+    pub fee_preference: Option<FeePreference>,
+    /// `PrivateRelay` keeps the signed tx out of the public mempool, ethereum only
+    pub submission_route: Option<SubmissionRoute>,
+    /// memo or destination tag carried with the transfer, e.g. the reference an exchange credits by
+    pub memo: Option<String>,
+    /// display name and proof of the sender address shown to the receiver, see `SenderProfile::message`
+    pub sender_profile: Option<SenderProfile>,
+    /// environment the transfer is meant for, refused when the node runs the network on another one
+    pub environment: Option<NetworkEnvironment>,
+    /// invoice or order id the transfer settles, kept in the history for reconciliation
+    pub reference: Option<String>,
+    /// labels of the transfer, searchable in the history
+    pub tags: Option<Vec<String>>,
+    /// id of the split payment the transfer is a part of
+    pub parent_payment: Option<H256>,
+    /// originator and beneficiary data for transfers between service providers, sealed to the
+    /// sender and receiver nodes
+    pub travel_rule: Option<TravelRuleData>,
+    /// seconds the transfer may take until it is submitted, both nodes abort it as `Expired` after
+    pub ttl_secs: Option<u64>,
+    /// `Urgent` transfers are submitted ahead of the queued ones, the fee tier follows the priority
+    /// when no fee preference is given
+    pub priority: Option<TxPriority>,
+}
+
+/// errors returned by the client
+#[derive(Debug)]
+pub enum VaneClientError {
+    /// the node rejected the call with a typed vane error
+    Node(VaneRpcError),
+    /// transport, serialization or untyped json-rpc error
+    Rpc(RpcError),
+}
+
+impl Display for VaneClientError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VaneClientError::Node(err) => write!(f, "vane node error {}: {err}", err.code()),
+            VaneClientError::Rpc(err) => write!(f, "rpc error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for VaneClientError {}
+
+impl From<RpcError> for VaneClientError {
+    fn from(value: RpcError) -> Self {
+        // typed errors carry the vane error in the json-rpc error `data`
+        if let RpcError::Call(CallError::Custom(ref err_obj)) = value {
+            if let Some(vane_err) = err_obj
+                .data()
+                .and_then(|data| serde_json::from_str::<VaneRpcError>(data.get()).ok())
+            {
+                return VaneClientError::Node(vane_err);
+            }
+        }
+        VaneClientError::Rpc(value)
+    }
+}
+
+pub type ClientResult<T> = Result<T, VaneClientError>;
+
+/// typed vane node client, generic over the underlying jsonrpsee client
+#[derive(Clone, Debug)]
+pub struct VaneClient<C> {
+    inner: C,
+}
+
+impl VaneClient<Client> {
+    /// connect over websocket, supports subscriptions
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn connect_ws(url: impl AsRef<str>) -> ClientResult<Self> {
+        let inner = WsClientBuilder::default().build(url).await?;
+        Ok(Self { inner })
+    }
+
+    /// connect over websocket to a node with access control, websocket connections need an admin key
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn connect_ws_with_api_key(
+        url: impl AsRef<str>,
+        api_key: &str,
+    ) -> ClientResult<Self> {
+        let inner = WsClientBuilder::default()
+            .set_headers(api_key_headers(api_key)?)
+            .build(url)
+            .await?;
+        Ok(Self { inner })
+    }
+
+    /// connect over the browser websocket, supports subscriptions
+    #[cfg(target_arch = "wasm32")]
+    pub async fn connect_ws(url: impl AsRef<str>) -> ClientResult<Self> {
+        let inner = WasmClientBuilder::default().build(url.as_ref()).await?;
+        Ok(Self { inner })
+    }
+
+    /// whether the websocket connection is still alive
+    pub fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl VaneClient<HttpClient> {
+    /// connect over http, subscriptions are not available; poll `pending_updates` instead
+    pub fn connect_http(url: impl AsRef<str>) -> ClientResult<Self> {
+        let inner = HttpClientBuilder::default().build(url)?;
+        Ok(Self { inner })
+    }
+
+    /// connect over http to a node with access control, calls are allowed by the api key role
+    pub fn connect_http_with_api_key(url: impl AsRef<str>, api_key: &str) -> ClientResult<Self> {
+        let inner = HttpClientBuilder::default()
+            .set_headers(api_key_headers(api_key)?)
+            .build(url)?;
+        Ok(Self { inner })
+    }
+}
+
+/// `Authorization` header carrying the node api key
+#[cfg(not(target_arch = "wasm32"))]
+fn api_key_headers(api_key: &str) -> ClientResult<HeaderMap> {
+    let value = HeaderValue::from_str(&format!("Bearer {api_key}"))
+        .map_err(|err| RpcError::Custom(format!("invalid api key; caused by: {err}")))?;
+    let mut headers = HeaderMap::new();
+    headers.insert("authorization", value);
+    Ok(headers)
+}
+
+impl<C> VaneClient<C> {
+    /// wrap an already configured jsonrpsee client
+    pub fn from_client(inner: C) -> Self {
+        Self { inner }
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+}
+
+impl<C: ClientT + Send + Sync> VaneClient<C> {
+    /// register user profile on the node and to vane discovery
+    pub async fn register(
+        &self,
+        name: impl Into<String>,
+        account_id: impl Into<String>,
+        network: ChainSupported,
+    ) -> ClientResult<()> {
+        let network: String = network.into();
+        self.inner
+            .request::<(), _>(
+                "register",
+                rpc_params![name.into(), account_id.into(), network],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// add more accounts to the user profile
+    pub async fn add_account(
+        &self,
+        name: impl Into<String>,
+        accounts: Vec<(String, ChainSupported)>,
+    ) -> ClientResult<()> {
+        self.inner
+            .request::<(), _>("addAccount", rpc_params![name.into(), accounts])
+            .await?;
+        Ok(())
+    }
+
+    /// initiate a transfer, the network is derived from the token, returns the tx id
+    /// the receiver must attest before the sender can confirm
+    /// sender side address checks run locally first, failing fast without a round trip
+    pub async fn send_token(
+        &self,
+        sender: impl Into<String>,
+        receiver: impl Into<String>,
+        amount: u128,
+        token: Token,
+    ) -> ClientResult<H256> {
+        self.send_token_with_options(sender, receiver, amount, token, TransferOptions::default())
+            .await
+    }
+
+    /// same as `send_token`, retrying with the same `idempotency_key` returns the first tx id
+    /// instead of starting a second flow
+    pub async fn send_token_idempotent(
+        &self,
+        sender: impl Into<String>,
+        receiver: impl Into<String>,
+        amount: u128,
+        token: Token,
+        idempotency_key: impl Into<String>,
+    ) -> ClientResult<H256> {
+        let options = TransferOptions {
+            idempotency_key: Some(idempotency_key.into()),
+            ..Default::default()
+        };
+        self.send_token_with_options(sender, receiver, amount, token, options)
+            .await
+    }
+
+    /// same as `send_token` with a fee tier and optional max fee per gas, evm tokens only.
+    /// the node refuses to build the tx if the tier fee is above the max fee or its own cap
+    pub async fn send_token_with_fee(
+        &self,
+        sender: impl Into<String>,
+        receiver: impl Into<String>,
+        amount: u128,
+        token: Token,
+        fee_preference: FeePreference,
+    ) -> ClientResult<H256> {
+        let options = TransferOptions {
+            fee_preference: Some(fee_preference),
+            ..Default::default()
+        };
+        self.send_token_with_options(sender, receiver, amount, token, options)
+            .await
+    }
+
+    /// same as `send_token` with every transfer option
+    pub async fn send_token_with_options(
+        &self,
+        sender: impl Into<String>,
+        receiver: impl Into<String>,
+        amount: u128,
+        token: Token,
+        options: TransferOptions,
+    ) -> ClientResult<H256> {
+        let (sender, receiver) = (sender.into(), receiver.into());
+        let network = validate_transfer(&sender, &receiver, token, ChainSupported::from(token))
+            .map_err(VaneClientError::Node)?;
+        let network: String = network.into();
+        let token: String = token.into();
+        Ok(self
+            .inner
+            .request(
+                "initiateTransaction",
+                rpc_params![
+                    sender,
+                    receiver,
+                    amount,
+                    token,
+                    network,
+                    options.idempotency_key,
+                    options.fee_preference,
+                    options.submission_route,
+                    options.memo,
+                    options.sender_profile,
+                    options.environment,
+                    options.reference,
+                    options.tags,
+                    options.parent_payment,
+                    options.travel_rule,
+                    options.ttl_secs,
+                    options.priority
+                ],
+            )
+            .await?)
+    }
+
+    /// send `usd_cents` worth of `token`, e.g. $100 of ETH. the node computes the amount at the
+    /// current usd price and refuses the confirmation or the submission once the price moved more
+    /// than `max_slippage_bps`, its own slippage when `None`
+    pub async fn send_fiat(
+        &self,
+        sender: impl Into<String>,
+        receiver: impl Into<String>,
+        usd_cents: u64,
+        token: Token,
+        max_slippage_bps: Option<u32>,
+    ) -> ClientResult<H256> {
+        let (sender, receiver) = (sender.into(), receiver.into());
+        let network = validate_transfer(&sender, &receiver, token, ChainSupported::from(token))
+            .map_err(VaneClientError::Node)?;
+        let network: String = network.into();
+        let token: String = token.into();
+        Ok(self
+            .inner
+            .request(
+                "initiateFiatTransaction",
+                rpc_params![
+                    sender,
+                    receiver,
+                    usd_cents,
+                    token,
+                    network,
+                    max_slippage_bps
+                ],
+            )
+            .await?)
+    }
+
+    /// fetch pending tx updates, the polling alternative to `subscribe_updates`
+    pub async fn pending_updates(&self) -> ClientResult<Vec<TxStateMachine>> {
+        Ok(self
+            .inner
+            .request("fetchPendingTxUpdates", rpc_params![])
+            .await?)
+    }
+
+    /// receiver attests the tx by signing its receiver address
+    /// `signature` is the receiver signature over the text of `tx.receiver_address`, its canonical form
+    pub async fn confirm_as_receiver(
+        &self,
+        mut tx: TxStateMachine,
+        signature: Vec<u8>,
+    ) -> ClientResult<()> {
+        tx.recv_signature = Some(signature);
+        self.inner
+            .request::<(), _>("receiverConfirm", rpc_params![tx])
+            .await?;
+        Ok(())
+    }
+
+    /// same as `confirm_as_receiver`, settling for `accepted_amount` below the initiated amount.
+    /// the sender signs a chain tx moving the accepted amount only
+    pub async fn confirm_as_receiver_accepting(
+        &self,
+        mut tx: TxStateMachine,
+        signature: Vec<u8>,
+        accepted_amount: u128,
+    ) -> ClientResult<()> {
+        tx.accepted_amount = Some(accepted_amount);
+        self.confirm_as_receiver(tx, signature).await
+    }
+
+    /// sender confirms an attested tx with the signed call payload, triggering submission.
+    /// refused by the node while the tx carries high severity warnings
+    pub async fn confirm_as_sender(
+        &self,
+        tx: TxStateMachine,
+        signed_call_payload: Vec<u8>,
+    ) -> ClientResult<()> {
+        self.sender_confirm(tx, signed_call_payload, false).await
+    }
+
+    /// sender confirms despite high severity warnings, e.g. after the user explicitly accepted them
+    pub async fn confirm_as_sender_overriding_warnings(
+        &self,
+        tx: TxStateMachine,
+        signed_call_payload: Vec<u8>,
+    ) -> ClientResult<()> {
+        self.sender_confirm(tx, signed_call_payload, true).await
+    }
+
+    async fn sender_confirm(
+        &self,
+        mut tx: TxStateMachine,
+        signed_call_payload: Vec<u8>,
+        override_warnings: bool,
+    ) -> ClientResult<()> {
+        tx.signed_call_payload = Some(signed_call_payload);
+        self.inner
+            .request::<(), _>("senderConfirm", rpc_params![tx, override_warnings])
+            .await?;
+        Ok(())
+    }
+
+    /// designated approver signs off a tx held after the sender confirmation, triggering submission.
+    /// `signature` is the approver EIP-191 signature over `tx.second_approval_message()`
+    pub async fn confirm_as_approver(
+        &self,
+        tx: TxStateMachine,
+        signature: Vec<u8>,
+    ) -> ClientResult<()> {
+        self.inner
+            .request::<(), _>("approverConfirm", rpc_params![tx, signature])
+            .await?;
+        Ok(())
+    }
+
+    /// attestation requests waiting for the node receiver, oldest first. ages are in seconds
+    pub async fn pending_receives(
+        &self,
+        network: Option<ChainSupported>,
+        min_age_secs: Option<u64>,
+        max_age_secs: Option<u64>,
+    ) -> ClientResult<Vec<PendingReceive>> {
+        let network: Option<String> = network.map(Into::into);
+        Ok(self
+            .inner
+            .request(
+                "listPendingReceives",
+                rpc_params![network, min_age_secs, max_age_secs],
+            )
+            .await?)
+    }
+
+    /// confirm several attestation requests, each tx carrying its receiver signature
+    pub async fn confirm_pending_receives(
+        &self,
+        txs: Vec<TxStateMachine>,
+    ) -> ClientResult<Vec<BulkOutcome>> {
+        Ok(self
+            .inner
+            .request("confirmPendingReceives", rpc_params![txs])
+            .await?)
+    }
+
+    /// decline attestation requests by tx nonce
+    pub async fn reject_pending_receives(
+        &self,
+        tx_nonces: Vec<u32>,
+    ) -> ClientResult<Vec<BulkOutcome>> {
+        Ok(self
+            .inner
+            .request("rejectPendingReceives", rpc_params![tx_nonces])
+            .await?)
+    }
+
+    /// reject attestation requests by tx nonce, the senders are told `reason`
+    pub async fn reject_pending_receives_with_reason(
+        &self,
+        tx_nonces: Vec<u32>,
+        reason: RejectReason,
+    ) -> ClientResult<Vec<BulkOutcome>> {
+        Ok(self
+            .inner
+            .request("rejectPendingReceives", rpc_params![tx_nonces, reason])
+            .await?)
+    }
+
+    /// reject the attestation request `tx_nonce`, proposing the address and network to send to
+    /// instead, signed with the key of the address the sender picked
+    pub async fn reject_with_correction(
+        &self,
+        tx_nonce: u32,
+        reason: RejectReason,
+        correction: AddressCorrection,
+    ) -> ClientResult<()> {
+        Ok(self
+            .inner
+            .request(
+                "rejectWithCorrection",
+                rpc_params![tx_nonce, reason, correction],
+            )
+            .await?)
+    }
+
+    /// send the rejected transfer `tx_id` again to the address the receiver proposed, returns the
+    /// new tx id
+    pub async fn restart_with_correction(&self, tx_id: H256) -> ClientResult<H256> {
+        Ok(self
+            .inner
+            .request("restartWithCorrection", rpc_params![tx_id])
+            .await?)
+    }
+
+    /// routes of the pending transfer `tx_nonce` on the other chains both peers registered an
+    /// account on and the sender balance covers, the largest balance first
+    pub async fn suggest_routes(&self, tx_nonce: u32) -> ClientResult<Vec<RouteSuggestion>> {
+        Ok(self
+            .inner
+            .request("suggestRoutes", rpc_params![tx_nonce])
+            .await?)
+    }
+
+    /// send the pending transfer `tx_nonce` again on `network`, failing the original one, returns
+    /// the new tx id
+    pub async fn reroute(&self, tx_nonce: u32, network: ChainSupported) -> ClientResult<H256> {
+        Ok(self
+            .inner
+            .request("rerouteTransaction", rpc_params![tx_nonce, network])
+            .await?)
+    }
+
+    /// split one payment of `sender` into transfers, returns the payment id. every part is
+    /// attested by its receiver and confirmed by the sender like a transfer of its own
+    pub async fn split_payment(
+        &self,
+        sender: impl Into<String>,
+        parts: Vec<PaymentPart>,
+        reference: Option<String>,
+        tags: Option<Vec<String>>,
+    ) -> ClientResult<H256> {
+        Ok(self
+            .inner
+            .request(
+                "initiateSplitPayment",
+                rpc_params![sender.into(), parts, reference, tags],
+            )
+            .await?)
+    }
+
+    /// transfers of a split payment and whether it settled
+    pub async fn payment(&self, payment_id: H256) -> ClientResult<PaymentStatus> {
+        Ok(self
+            .inner
+            .request("getPayment", rpc_params![payment_id])
+            .await?)
+    }
+
+    /// peers registered to vane discovery
+    pub async fn list_peers(&self) -> ClientResult<Vec<Discovery>> {
+        Ok(self.inner.request("listPeers", rpc_params![]).await?)
+    }
+
+    /// reachability and latency of the chain rpc endpoints used by the node
+    pub async fn chain_health(&self) -> ClientResult<Vec<ChainHealth>> {
+        Ok(self.inner.request("chainHealth", rpc_params![]).await?)
+    }
+
+    /// rtt, request success rate, last seen and bytes exchanged of the peers the node talked to
+    pub async fn peer_stats(&self) -> ClientResult<Vec<PeerStats>> {
+        Ok(self.inner.request("peerStats", rpc_params![]).await?)
+    }
+
+    /// networks of the node `address` can live on going by its format, a single one is the
+    /// network to send on
+    pub async fn detect_chains(
+        &self,
+        address: impl Into<String>,
+    ) -> ClientResult<Vec<ChainSupported>> {
+        Ok(self
+            .inner
+            .request("detectChains", rpc_params![address.into()])
+            .await?)
+    }
+
+    /// past interactions of the node with `address`, to weigh before confirming a transfer to it
+    pub async fn counterparty(
+        &self,
+        address: impl Into<String>,
+    ) -> ClientResult<CounterpartyProfile> {
+        Ok(self
+            .inner
+            .request("getCounterparty", rpc_params![address.into()])
+            .await?)
+    }
+
+    /// suggested fee tiers for the next block of an evm network
+    pub async fn fee_tiers(&self, network: ChainSupported) -> ClientResult<FeeTiers> {
+        let network: String = network.into();
+        Ok(self.inner.request("feeTiers", rpc_params![network]).await?)
+    }
+
+    /// suggested compute unit prices for the next solana slots, raised while it is congested
+    pub async fn compute_budget_tiers(&self) -> ClientResult<ComputeBudgetTiers> {
+        Ok(self
+            .inner
+            .request("computeBudgetTiers", rpc_params![])
+            .await?)
+    }
+
+    /// read a human amount, e.g. `1 234,56` in `fr`, into the token smallest unit, the node
+    /// reads it in english when `locale` is `None`
+    pub async fn parse_amount(
+        &self,
+        amount: impl Into<String>,
+        token: Token,
+        locale: Option<AmountLocale>,
+    ) -> ClientResult<LocalizedAmount> {
+        Ok(self
+            .inner
+            .request("parseAmount", rpc_params![amount.into(), token, locale])
+            .await?)
+    }
+
+    /// render an amount in the token smallest unit with the separators of `locale`
+    pub async fn format_amount(
+        &self,
+        units: u128,
+        token: Token,
+        locale: Option<AmountLocale>,
+    ) -> ClientResult<LocalizedAmount> {
+        Ok(self
+            .inner
+            .request("formatAmount", rpc_params![units, token, locale])
+            .await?)
+    }
+
+    /// network fees paid per network and fee tier, with a warning per consistently overpaying tier
+    pub async fn fee_stats(&self) -> ClientResult<FeeStats> {
+        Ok(self.inner.request("feeStats", rpc_params![]).await?)
+    }
+
+    /// credit `account` with `amount` in the token smallest unit on a dev mode node, returns its
+    /// balance
+    pub async fn faucet(
+        &self,
+        account: impl Into<String>,
+        amount: u128,
+        network: ChainSupported,
+    ) -> ClientResult<u128> {
+        let (account, network): (String, String) = (account.into(), network.into());
+        Ok(self
+            .inner
+            .request("faucet", rpc_params![account, amount, network])
+            .await?)
+    }
+
+    /// `count` evm test accounts registered to a dev mode or testnet node, funded with `amount`
+    /// each on dev chains
+    pub async fn provision_test_accounts(
+        &self,
+        count: u8,
+        network: ChainSupported,
+        amount: u128,
+    ) -> ClientResult<Vec<TestAccount>> {
+        let network: String = network.into();
+        Ok(self
+            .inner
+            .request("provisionTestAccounts", rpc_params![count, network, amount])
+            .await?)
+    }
+
+    /// approve `spender` for a bounded `amount` of a contract token, returns the tx id. the approval
+    /// comes back through the updates for the owner to sign like any transfer
+    pub async fn approve_token(
+        &self,
+        owner: impl Into<String>,
+        spender: impl Into<String>,
+        amount: u128,
+        token: Token,
+    ) -> ClientResult<H256> {
+        let (owner, spender, token): (String, String, String) =
+            (owner.into(), spender.into(), token.into());
+        Ok(self
+            .inner
+            .request(
+                "approveToken",
+                rpc_params![owner, spender, amount, token, Option::<bool>::None],
+            )
+            .await?)
+    }
+
+    /// approve `spender` for an unlimited amount, prefer `approve_token` or `build_permit`
+    pub async fn approve_token_unlimited(
+        &self,
+        owner: impl Into<String>,
+        spender: impl Into<String>,
+        token: Token,
+    ) -> ClientResult<H256> {
+        let (owner, spender, token): (String, String, String) =
+            (owner.into(), spender.into(), token.into());
+        Ok(self
+            .inner
+            .request(
+                "approveToken",
+                rpc_params![owner, spender, 0u128, token, Some(true)],
+            )
+            .await?)
+    }
+
+    /// revoke the allowance of `spender`, returns the tx id
+    pub async fn revoke_approval(
+        &self,
+        owner: impl Into<String>,
+        spender: impl Into<String>,
+        token: Token,
+    ) -> ClientResult<H256> {
+        let (owner, spender, token): (String, String, String) =
+            (owner.into(), spender.into(), token.into());
+        Ok(self
+            .inner
+            .request("revokeApproval", rpc_params![owner, spender, token])
+            .await?)
+    }
+
+    /// outstanding allowances granted through the node, of all owners when `owner` is `None`
+    pub async fn approvals(&self, owner: Option<String>) -> ClientResult<Vec<Approval>> {
+        Ok(self
+            .inner
+            .request("listApprovals", rpc_params![owner])
+            .await?)
+    }
+
+    /// EIP-2612 permit for the owner to sign, valid for `validity_secs` or the node default
+    pub async fn build_permit(
+        &self,
+        owner: impl Into<String>,
+        spender: impl Into<String>,
+        amount: u128,
+        token: Token,
+        validity_secs: Option<u64>,
+    ) -> ClientResult<PermitPayload> {
+        let (owner, spender, token): (String, String, String) =
+            (owner.into(), spender.into(), token.into());
+        Ok(self
+            .inner
+            .request(
+                "buildPermit",
+                rpc_params![owner, spender, amount, token, validity_secs],
+            )
+            .await?)
+    }
+
+    /// replace the operator policies of the node
+    pub async fn set_policies(&self, rules: Vec<PolicyRule>) -> ClientResult<()> {
+        Ok(self
+            .inner
+            .request("setPolicies", rpc_params![rules])
+            .await?)
+    }
+
+    /// operator policies in force on the node
+    pub async fn policies(&self) -> ClientResult<Vec<PolicyRule>> {
+        Ok(self.inner.request("listPolicies", rpc_params![]).await?)
+    }
+
+    /// message to sign with `account` to pair it with the node, bound to a one-time challenge
+    pub async fn pairing_message(&self, account: impl Into<String>) -> ClientResult<String> {
+        let account: String = account.into();
+        Ok(self
+            .inner
+            .request("pairingMessage", rpc_params![account])
+            .await?)
+    }
+
+    /// pair a signing device, `signature` is its EIP-191 signature of the latest pairing message
+    pub async fn pair_signer(
+        &self,
+        account: impl Into<String>,
+        signature: Vec<u8>,
+    ) -> ClientResult<()> {
+        let account: String = account.into();
+        Ok(self
+            .inner
+            .request("pairSigner", rpc_params![account, signature])
+            .await?)
+    }
+
+    pub async fn unpair_signer(&self, account: impl Into<String>) -> ClientResult<()> {
+        let account: String = account.into();
+        Ok(self
+            .inner
+            .request("unpairSigner", rpc_params![account])
+            .await?)
+    }
+
+    /// accounts of the signing devices paired with the node
+    pub async fn paired_signers(&self) -> ClientResult<Vec<String>> {
+        Ok(self
+            .inner
+            .request("listPairedSigners", rpc_params![])
+            .await?)
+    }
+
+    /// unlock the send operations with the send passphrase, returns the unix timestamp in seconds
+    /// the node locks itself again at
+    pub async fn unlock(&self, passphrase: impl Into<String>) -> ClientResult<u64> {
+        let passphrase: String = passphrase.into();
+        Ok(self
+            .inner
+            .request("unlock", rpc_params![passphrase])
+            .await?)
+    }
+
+    pub async fn lock(&self) -> ClientResult<()> {
+        Ok(self.inner.request("lock", rpc_params![]).await?)
+    }
+
+    pub async fn peer_binding_message(
+        &self,
+        account: impl Into<String>,
+        signed_at: u64,
+    ) -> ClientResult<String> {
+        let account: String = account.into();
+        Ok(self
+            .inner
+            .request("peerBindingMessage", rpc_params![account, signed_at])
+            .await?)
+    }
+
+    /// bind `account` to the node, `signature` is its EIP-191 signature of the peer binding message
+    pub async fn add_peer_binding(
+        &self,
+        account: impl Into<String>,
+        signed_at: u64,
+        signature: Vec<u8>,
+    ) -> ClientResult<()> {
+        let account: String = account.into();
+        Ok(self
+            .inner
+            .request("addPeerBinding", rpc_params![account, signed_at, signature])
+            .await?)
+    }
+
+    /// account to node bindings the node verified
+    pub async fn peer_bindings(&self) -> ClientResult<Vec<PeerBinding>> {
+        Ok(self
+            .inner
+            .request("listPeerBindings", rpc_params![])
+            .await?)
+    }
+
+    /// single-use code linking another device of the user, its uri is shown as a QR code
+    pub async fn device_pairing_code(&self) -> ClientResult<DevicePairingCode> {
+        Ok(self
+            .inner
+            .request("devicePairingCode", rpc_params![])
+            .await?)
+    }
+
+    /// pair the node with the device showing the pairing `uri`, they sync from then on
+    pub async fn pair_device(&self, uri: impl Into<String>) -> ClientResult<PairedDevice> {
+        let uri: String = uri.into();
+        Ok(self.inner.request("pairDevice", rpc_params![uri]).await?)
+    }
+
+    pub async fn unpair_device(&self, peer_id: impl Into<String>) -> ClientResult<()> {
+        let peer_id: String = peer_id.into();
+        Ok(self
+            .inner
+            .request("unpairDevice", rpc_params![peer_id])
+            .await?)
+    }
+
+    pub async fn paired_devices(&self) -> ClientResult<Vec<PairedDevice>> {
+        Ok(self
+            .inner
+            .request("listPairedDevices", rpc_params![])
+            .await?)
+    }
+
+    /// pending transfers of the paired devices, as last synced
+    pub async fn device_transfers(&self) -> ClientResult<Vec<TxStateMachine>> {
+        Ok(self
+            .inner
+            .request("listDeviceTransfers", rpc_params![])
+            .await?)
+    }
+
+    /// rotate the node to a new p2p identity, the old one keeps answering for `grace_secs`
+    pub async fn rotate_node_identity(
+        &self,
+        grace_secs: Option<u64>,
+    ) -> ClientResult<RotatedIdentity> {
+        Ok(self
+            .inner
+            .request("rotateNodeIdentity", rpc_params![grace_secs])
+            .await?)
+    }
+
+    pub async fn revocation_message(
+        &self,
+        account: impl Into<String>,
+        revoked_at: u64,
+    ) -> ClientResult<String> {
+        let account: String = account.into();
+        Ok(self
+            .inner
+            .request("revocationMessage", rpc_params![account, revoked_at])
+            .await?)
+    }
+
+    /// mark `account` as compromised, `signature` is its EIP-191 signature of the revocation
+    /// message. a revocation cannot be withdrawn
+    pub async fn revoke_account(
+        &self,
+        account: impl Into<String>,
+        revoked_at: u64,
+        signature: Vec<u8>,
+    ) -> ClientResult<()> {
+        let account: String = account.into();
+        Ok(self
+            .inner
+            .request("revokeAccount", rpc_params![account, revoked_at, signature])
+            .await?)
+    }
+
+    /// account revocations the node verified
+    pub async fn revocations(&self) -> ClientResult<Vec<AccountRevocation>> {
+        Ok(self.inner.request("listRevocations", rpc_params![]).await?)
+    }
+
+    /// store a third party attestation of one of the node accounts with its registry record
+    pub async fn add_attestation(&self, attestation: IdentityAttestation) -> ClientResult<()> {
+        Ok(self
+            .inner
+            .request("addAttestation", rpc_params![attestation])
+            .await?)
+    }
+
+    /// verification badges of `account` from the issuers the node trusts
+    pub async fn badges(&self, account: impl Into<String>) -> ClientResult<Vec<VerificationBadge>> {
+        let account: String = account.into();
+        Ok(self
+            .inner
+            .request("listBadges", rpc_params![account])
+            .await?)
+    }
+
+    /// advertise the tokens one of the node accounts accepts, in order of preference. senders are
+    /// refused transfers of other tokens, an empty list accepts any
+    pub async fn set_accepted_assets(
+        &self,
+        account: impl Into<String>,
+        tokens: Vec<Token>,
+    ) -> ClientResult<()> {
+        let account: String = account.into();
+        Ok(self
+            .inner
+            .request("setAcceptedAssets", rpc_params![account, tokens])
+            .await?)
+    }
+
+    /// tokens `address` accepts as advertised by its node, empty when it accepts any
+    pub async fn accepted_assets(&self, address: impl Into<String>) -> ClientResult<Vec<Token>> {
+        let address: String = address.into();
+        Ok(self
+            .inner
+            .request("acceptedAssets", rpc_params![address])
+            .await?)
+    }
+
+    /// binding registry call the wallet of `account`, an evm account of the node, sends to anchor
+    /// its binding to the node on chain
+    pub async fn anchor_call(&self, account: impl Into<String>) -> ClientResult<AnchorCall> {
+        let account: String = account.into();
+        Ok(self
+            .inner
+            .request("anchorCall", rpc_params![account])
+            .await?)
+    }
+
+    /// the binding of `account` to the node `peer_id` as anchored on chain, to the node the
+    /// registry serves it from when `None`
+    pub async fn verify_binding(
+        &self,
+        account: impl Into<String>,
+        peer_id: Option<String>,
+    ) -> ClientResult<BindingAnchor> {
+        let account: String = account.into();
+        Ok(self
+            .inner
+            .request("verifyBinding", rpc_params![account, peer_id])
+            .await?)
+    }
+
+    /// submitted txs, both succeeded and failed
+    pub async fn tx_history(&self) -> ClientResult<Vec<DbTxStateMachine>> {
+        Ok(self.inner.request("txHistory", rpc_params![]).await?)
+    }
+
+    /// history records matching every filter of `search`, newest first
+    pub async fn search_history(
+        &self,
+        search: HistorySearch,
+    ) -> ClientResult<Vec<DbTxStateMachine>> {
+        Ok(self
+            .inner
+            .request("searchHistory", rpc_params![search])
+            .await?)
+    }
+
+    /// history record of a submitted tx with its explorer link and chain receipt
+    pub async fn tx_receipt(&self, tx_hash: H256) -> ClientResult<DbTxStateMachine> {
+        Ok(self
+            .inner
+            .request("txReceipt", rpc_params![tx_hash])
+            .await?)
+    }
+
+    /// travel rule data attached to the transfer `tx_id`, `None` when it carried none
+    pub async fn travel_rule(&self, tx_id: H256) -> ClientResult<Option<TravelRuleData>> {
+        Ok(self
+            .inner
+            .request("getTravelRule", rpc_params![tx_id])
+            .await?)
+    }
+
+    /// stage the transfer `tx_id` waits at, since when and the suggested actions
+    pub async fn diagnose_tx(&self, tx_id: H256) -> ClientResult<TxDiagnosis> {
+        Ok(self.inner.request("diagnoseTx", rpc_params![tx_id]).await?)
+    }
+
+    /// drive the stuck transfer `tx_id` again, at `stage` or the one it waits at, returns the
+    /// stage retried
+    pub async fn retry_tx(
+        &self,
+        tx_id: H256,
+        stage: Option<RetryStage>,
+    ) -> ClientResult<RetryStage> {
+        Ok(self
+            .inner
+            .request("retryTx", rpc_params![tx_id, stage])
+            .await?)
+    }
+
+    /// the node OpenRPC document
+    pub async fn discover(&self) -> ClientResult<serde_json::Value> {
+        Ok(self.inner.request("rpc.discover", rpc_params![]).await?)
+    }
+}
+
+impl<C: SubscriptionClientT + Send + Sync> VaneClient<C> {
+    /// stream of tx state machine updates for this node
+    pub async fn subscribe_updates(&self) -> ClientResult<Subscription<TxStateMachine>> {
+        Ok(self
+            .inner
+            .subscribe("subscribeTxUpdates", rpc_params![], "unsubscribeTxUpdates")
+            .await?)
+    }
+}
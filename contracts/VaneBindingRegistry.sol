@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.20;
+
+/// account to vane node bindings anchored on chain. the binding is keyed by the sender of the
+/// anchoring tx, so only an account anchors its own binding:
+/// binding = keccak256(abi.encodePacked(account, bytes(peerId)))
+contract VaneBindingRegistry {
+    mapping(address => bytes32) public bindingOf;
+
+    event Anchored(address indexed account, bytes32 binding);
+
+    /// anchor the binding of the sender, replacing its previous one. the zero hash clears it
+    function anchor(bytes32 binding) external {
+        bindingOf[msg.sender] = binding;
+        emit Anchored(msg.sender, binding);
+    }
+}
@@ -0,0 +1,137 @@
+// at rest encryption of the sensitive fields of stored txs
+// call payloads, signed call payloads and memos are sealed with a key kept in the node keystore,
+// outside the db, so a copied db file does not reveal signable payloads or financial notes. they
+// are stored sealed in a field of the event of their own, the rest of a tx stays in the clear as
+// the node queries and folds the event log by it
+
+use aes_gcm::aead::{Aead, AeadCore, OsRng};
+use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
+use anyhow::anyhow;
+use codec::{Decode, Encode};
+use primitives::data_structure::{DbTxStateMachine, TxEvent};
+use primitives::device_sync::{SyncKind, SyncRecord};
+
+/// prefix of a sealed value, a stored value without it was written before sealing was enabled
+const SEALED_PREFIX: &[u8] = b"vane-sealed:v1:";
+
+/// key the sensitive fields are sealed with
+#[derive(Clone)]
+pub struct StorageKey(Key<Aes256Gcm>);
+
+impl StorageKey {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self(key.into())
+    }
+
+    fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(&self.0)
+            .encrypt(&nonce, plaintext)
+            .map_err(|err| anyhow!("failed to seal stored value; caused by: {err}"))?;
+        Ok([SEALED_PREFIX, nonce.as_slice(), &ciphertext].concat())
+    }
+
+    /// `None` when `value` is not sealed
+    fn open(&self, value: &[u8]) -> Option<Result<Vec<u8>, anyhow::Error>> {
+        let sealed = value.strip_prefix(SEALED_PREFIX)?;
+        if sealed.len() < 12 {
+            return Some(Err(anyhow!("sealed stored value is truncated")));
+        }
+        let (nonce, ciphertext) = sealed.split_at(12);
+        Some(
+            Aes256Gcm::new(&self.0)
+                .decrypt(Nonce::from_slice(nonce), ciphertext)
+                .map_err(|_| {
+                    anyhow!("failed to open stored value, the storage key does not match")
+                }),
+        )
+    }
+
+    /// moves the call payload, signed call payload and memo of the tx into the sealed fields
+    pub fn seal_event(&self, mut event: TxEvent) -> Result<TxEvent, anyhow::Error> {
+        let fields = (
+            event.tx.call_payload.take(),
+            event.tx.signed_call_payload.take(),
+            event.tx.memo.take(),
+        );
+        event.sealed_fields = Some(self.seal(&fields.encode())?);
+        Ok(event)
+    }
+
+    /// reverse of `seal_event`, events recorded before sealing was enabled are returned as they
+    /// are
+    pub fn open_event(&self, mut event: TxEvent) -> Result<TxEvent, anyhow::Error> {
+        // events sealed before the sealed fields had their own place kept them in the signed
+        // call payload
+        let legacy = event
+            .tx
+            .signed_call_payload
+            .as_deref()
+            .is_some_and(|value| value.starts_with(SEALED_PREFIX));
+        let sealed = match event.sealed_fields.take() {
+            Some(sealed) => Some(sealed),
+            None if legacy => event.tx.signed_call_payload.take(),
+            None => None,
+        };
+        let Some(sealed) = sealed else {
+            return Ok(event);
+        };
+        let fields = self
+            .open(&sealed)
+            .ok_or(anyhow!("sealed tx fields are missing their prefix"))??;
+        let fields: (Option<[u8; 32]>, Option<Vec<u8>>, Option<String>) =
+            Decode::decode(&mut &fields[..])
+                .map_err(|err| anyhow!("failed to decode sealed tx fields: {err:?}"))?;
+        (
+            event.tx.call_payload,
+            event.tx.signed_call_payload,
+            event.tx.memo,
+        ) = fields;
+        Ok(event)
+    }
+
+    /// seals the memo of a history record, references and tags stay in the clear so the history
+    /// can be searched by them
+    pub fn seal_history(
+        &self,
+        mut tx: DbTxStateMachine,
+    ) -> Result<DbTxStateMachine, anyhow::Error> {
+        if let Some(memo) = tx.memo.take() {
+            tx.memo = Some(hex::encode(self.seal(memo.as_bytes())?));
+        }
+        Ok(tx)
+    }
+
+    pub fn open_history(
+        &self,
+        mut tx: DbTxStateMachine,
+    ) -> Result<DbTxStateMachine, anyhow::Error> {
+        let sealed = tx.memo.as_deref().and_then(|memo| hex::decode(memo).ok());
+        if let Some(memo) = sealed.as_deref().and_then(|sealed| self.open(sealed)) {
+            tx.memo =
+                Some(String::from_utf8(memo?).map_err(|_| anyhow!("sealed memo is not utf8"))?);
+        }
+        Ok(tx)
+    }
+
+    /// pending transfers carry the payloads and memos of other devices, other records are kept
+    pub fn seal_sync_record(&self, record: SyncRecord) -> Result<SyncRecord, anyhow::Error> {
+        if record.kind != SyncKind::PendingTransfers {
+            return Ok(record);
+        }
+        Ok(SyncRecord {
+            value: self.seal(&record.value)?,
+            ..record
+        })
+    }
+
+    pub fn open_sync_record(&self, record: SyncRecord) -> Result<SyncRecord, anyhow::Error> {
+        match self.open(&record.value) {
+            Some(value) => Ok(SyncRecord {
+                value: value?,
+                ..record
+            }),
+            None => Ok(record),
+        }
+    }
+}
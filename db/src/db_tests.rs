@@ -1,38 +1,55 @@
-use crate::DbWorker;
+use crate::{DbWorker, DbWorkerInterface, StorageKey, IN_MEMORY_DB_URL};
 use aead::Aead;
 use aes_gcm::aes::cipher::consts::{U12, U16};
 use aes_gcm::{Aes128Gcm, Key, KeyInit, Nonce};
 use codec::Encode;
 use libp2p;
-use primitives::data_structure::{ChainSupported, DbTxStateMachine, PeerRecord, UserAccount};
+use primitives::address::VaneAddress;
+use primitives::data_structure::{
+    ChainSupported, DbTxStateMachine, PeerRecord, RejectReason, TxEvent, TxKind, TxStateMachine,
+    TxStatus, UserAccount, H256,
+};
+use primitives::device_sync::{PairedDevice, SyncKind, SyncRecord};
+use primitives::explorer::ChainReceipt;
+use primitives::fees::FeeTier;
+use primitives::history::HistorySearch;
+use primitives::peer_exchange::PeerBinding;
+use primitives::revocation::AccountRevocation;
 use tokio;
 
-async fn storing_success_n_failed_tx_works() -> Result<(), anyhow::Error> {
-    let db_client = DbWorker::initialize_db_client("./dev.db").await?;
+async fn storing_success_n_failed_tx_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
 
     let success_tx = DbTxStateMachine {
         tx_hash: b"0x12345".to_vec(),
         amount: 1000,
         network: ChainSupported::Polkadot,
         success: true,
+        reference: Some("INV-7".to_string()),
+        tags: vec!["payroll".to_string()],
+        ..Default::default()
     };
     let failed_tx = DbTxStateMachine {
         tx_hash: b"0x12222".to_vec(),
         amount: 1320,
         network: ChainSupported::Solana,
         success: false,
+        reject_reason: Some(RejectReason::WrongChain),
+        ..Default::default()
     };
     let success_tx_2 = DbTxStateMachine {
         tx_hash: b"0x123454r4".to_vec(),
         amount: 1500,
         network: ChainSupported::Polkadot,
         success: true,
+        ..Default::default()
     };
     let failed_tx_2 = DbTxStateMachine {
         tx_hash: b"0x12222ssdx".to_vec(),
         amount: 1600,
         network: ChainSupported::Solana,
         success: false,
+        ..Default::default()
     };
 
     // push to the db
@@ -44,14 +61,23 @@ async fn storing_success_n_failed_tx_works() -> Result<(), anyhow::Error> {
     assert_eq!(db_client.get_total_value_success().await?, 2500);
     assert_eq!(db_client.get_total_value_failed().await?, 2920);
     // fetch the streams and assert
-    assert_eq!(db_client.get_failed_txs().await?.len(), 2);
-    assert_eq!(db_client.get_success_txs().await?.len(), 2);
+    let failed_txs = db_client.get_failed_txs().await?;
+    assert_eq!(failed_txs.len(), 2);
+    // the reason the receiver rejected the transfer is kept in the history
+    assert!(failed_txs
+        .iter()
+        .any(|tx| tx.reject_reason == Some(RejectReason::WrongChain)));
+    let success_txs = db_client.get_success_txs().await?;
+    assert_eq!(success_txs.len(), 2);
+    assert!(success_txs
+        .iter()
+        .any(|tx| tx.reference.as_deref() == Some("INV-7") && tx.matches("payroll")));
 
     Ok(())
 }
 
-async fn user_creation_n_retrieving_works() -> Result<(), anyhow::Error> {
-    let db_client = DbWorker::initialize_db_client("./dev.db").await?;
+async fn user_creation_n_retrieving_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
 
     let user_account1 = UserAccount {
         user_name: "Mrisho".to_string(),
@@ -103,8 +129,8 @@ async fn user_creation_n_retrieving_works() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
-async fn storing_user_peer_id_n_retrieving_works() -> Result<(), anyhow::Error> {
-    let db_client = DbWorker::initialize_db_client("./dev.db").await?;
+async fn storing_user_peer_id_n_retrieving_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
 
     let test_keypair_peer = libp2p::identity::Keypair::generate_ed25519();
     let peer_id = test_keypair_peer.public().to_peer_id().to_base58();
@@ -149,8 +175,8 @@ async fn storing_user_peer_id_n_retrieving_works() -> Result<(), anyhow::Error>
     Ok(())
 }
 
-async fn storing_n_retrieving_saved_peers_works() -> Result<(), anyhow::Error> {
-    let db_client = DbWorker::initialize_db_client("./dev.db").await?;
+async fn storing_n_retrieving_saved_peers_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
 
     let test_keypair_peer = libp2p::identity::Keypair::generate_ed25519();
     let peer_id = test_keypair_peer.public().to_peer_id().to_base58();
@@ -187,11 +213,524 @@ async fn storing_n_retrieving_saved_peers_works() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+async fn storing_n_rebuilding_tx_events_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
+
+    let pending_tx = TxStateMachine {
+        amount: 1000,
+        tx_nonce: 1,
+        ..Default::default()
+    };
+    // the sqlite event log outlives a run, the sender is unique to this one
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_nanos();
+    let mut sender = [9u8; 20];
+    sender[..16].copy_from_slice(&nanos.to_be_bytes());
+    let mut submitted_tx = TxStateMachine {
+        amount: 2000,
+        tx_nonce: 2,
+        sender_address: VaneAddress::Evm(sender),
+        ..Default::default()
+    };
+    db_client
+        .record_tx_event(TxEvent::new(pending_tx.clone(), 1))
+        .await?;
+    db_client
+        .record_tx_event(TxEvent::new(submitted_tx.clone(), 2))
+        .await?;
+    submitted_tx.status = TxStatus::TxSubmissionPassed([1; 32]);
+    db_client
+        .record_tx_event(TxEvent::new(submitted_tx.clone(), 3))
+        .await?;
+
+    let events = db_client.get_tx_events(submitted_tx.tx_id()).await?;
+    assert!(events
+        .iter()
+        .all(|event| event.tx_id == submitted_tx.tx_id()));
+    assert_eq!(
+        events.last().map(|event| event.status.clone()),
+        Some(TxStatus::TxSubmissionPassed([1; 32]))
+    );
+
+    let sender = submitted_tx.sender_address.to_string();
+    let receiver = submitted_tx.receiver_address.to_string();
+    let sent = db_client
+        .get_sender_tx_events(sender.clone(), Some(receiver))
+        .await?;
+    assert_eq!(sent.len(), 2);
+    assert!(sent.iter().all(|event| event.tx_id == submitted_tx.tx_id()));
+    let to_itself = db_client
+        .get_sender_tx_events(sender.clone(), Some(sender.clone()))
+        .await?;
+    assert!(to_itself.is_empty());
+
+    // the txs to an account are found with the txs from it
+    let incoming = TxStateMachine {
+        tx_nonce: 3,
+        receiver_address: submitted_tx.sender_address.clone(),
+        ..Default::default()
+    };
+    db_client
+        .record_tx_event(TxEvent::new(incoming.clone(), 3))
+        .await?;
+    let involved = db_client.get_account_tx_events(sender).await?;
+    assert_eq!(involved.len(), 3);
+    assert_eq!(
+        involved.last().map(|event| event.tx_id),
+        Some(incoming.tx_id())
+    );
+
+    let recent = db_client.get_tx_events_since(3).await?;
+    assert!(recent.iter().all(|event| event.recorded_at >= 3));
+    assert!(recent
+        .iter()
+        .any(|event| event.tx_id == submitted_tx.tx_id()));
+
+    // the parts of a split payment are written in one batch
+    let payment_id = H256::from_low_u64_be(nanos as u64);
+    let parts: Vec<TxEvent> = (10..12)
+        .map(|tx_nonce| {
+            let part = TxStateMachine {
+                tx_nonce,
+                multi_id: H256::from_low_u64_be(nanos as u64),
+                sender_address: submitted_tx.sender_address.clone(),
+                parent_payment: Some(payment_id),
+                ..Default::default()
+            };
+            TxEvent::new(part, 4)
+        })
+        .collect();
+    db_client.record_tx_events(parts.clone()).await?;
+    for part in &parts {
+        assert_eq!(
+            db_client.get_tx_events(part.tx_id).await?,
+            vec![part.clone()]
+        );
+    }
+    assert_eq!(db_client.get_payment_tx_events(payment_id).await?, parts);
+
+    // only the approvals of the owner are found
+    let approval = TxStateMachine {
+        tx_nonce: 12,
+        sender_address: submitted_tx.sender_address.clone(),
+        kind: TxKind::Approval,
+        ..Default::default()
+    };
+    db_client
+        .record_tx_event(TxEvent::new(approval.clone(), 4))
+        .await?;
+    let approvals = db_client
+        .get_approval_tx_events(Some(submitted_tx.sender_address.to_string()))
+        .await?;
+    assert_eq!(approvals, vec![TxEvent::new(approval, 4)]);
+
+    // a refused submission can be retried, unlike a submitted tx
+    let failed_tx = TxStateMachine {
+        tx_nonce: 13,
+        multi_id: H256::from_low_u64_be(nanos as u64),
+        status: TxStatus::FailedToSubmitTxn("nonce too low".to_string()),
+        ..Default::default()
+    };
+    db_client
+        .record_tx_event(TxEvent::new(failed_tx.clone(), 5))
+        .await?;
+
+    // only the txs that did not reach a final status or can be retried are staged again
+    let pending = TxEvent::pending_txs(db_client.get_all_tx_events().await?);
+    assert!(pending.contains(&pending_tx));
+    assert_eq!(pending.first(), Some(&failed_tx));
+    assert!(!pending
+        .iter()
+        .any(|tx| tx.tx_nonce == submitted_tx.tx_nonce));
+    Ok(())
+}
+
+async fn replacing_policies_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
+
+    let warn_all = r#"[{"id":"all","description":"","conditions":[],"action":"Warn"}]"#;
+    db_client.set_policies("[]".to_string()).await?;
+    db_client.set_policies(warn_all.to_string()).await?;
+    assert_eq!(db_client.get_policies().await?, Some(warn_all.to_string()));
+    Ok(())
+}
+
+async fn pairing_n_unpairing_signers_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
+
+    let (phone, tablet) = (
+        "0x1111111111111111111111111111111111111111".to_string(),
+        "0x2222222222222222222222222222222222222222".to_string(),
+    );
+    db_client.pair_signer(phone.clone()).await?;
+    db_client.pair_signer(phone.clone()).await?;
+    db_client.pair_signer(tablet.clone()).await?;
+    assert_eq!(db_client.get_paired_signers().await?.len(), 2);
+
+    db_client.unpair_signer(phone.clone()).await?;
+    assert_eq!(db_client.get_paired_signers().await?, vec![tablet.clone()]);
+    db_client.unpair_signer(tablet).await?;
+    Ok(())
+}
+
+async fn keeping_the_latest_peer_binding_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
+
+    let binding = |peer_id: &str, signed_at: u64| PeerBinding {
+        account: "0x3333333333333333333333333333333333333333".to_string(),
+        peer_id: peer_id.to_string(),
+        multi_addr: "/ip4/127.0.0.1/tcp/30333".to_string(),
+        signed_at,
+        signature: vec![1; 65],
+    };
+    db_client
+        .record_peer_binding(binding("old node", 10))
+        .await?;
+    db_client
+        .record_peer_binding(binding("new node", 20))
+        .await?;
+    // a replayed earlier binding does not win
+    db_client
+        .record_peer_binding(binding("old node", 15))
+        .await?;
+    let bindings = db_client.get_peer_bindings().await?;
+    assert_eq!(bindings.len(), 1);
+    assert_eq!(bindings[0].peer_id, "new node");
+    let account = "0x3333333333333333333333333333333333333333".to_string();
+    let binding = db_client.get_peer_binding(account).await?;
+    assert_eq!(
+        binding.map(|binding| binding.peer_id),
+        Some("new node".to_string())
+    );
+    Ok(())
+}
+
+async fn pairing_devices_n_keeping_the_latest_sync_record_works(
+    db_url: &str,
+) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
+
+    let device = |multi_addr: &str| PairedDevice {
+        peer_id: "paired device".to_string(),
+        multi_addr: multi_addr.to_string(),
+        encryption_key: [7; 32],
+        paired_at: 10,
+    };
+    db_client
+        .record_paired_device(device("/ip4/127.0.0.1/tcp/30333"))
+        .await?;
+    // pairing again replaces the device
+    db_client
+        .record_paired_device(device("/ip4/127.0.0.1/tcp/30334"))
+        .await?;
+    assert_eq!(
+        db_client.get_paired_devices().await?,
+        vec![device("/ip4/127.0.0.1/tcp/30334")]
+    );
+    db_client.unpair_device("paired device".to_string()).await?;
+    assert!(db_client.get_paired_devices().await?.is_empty());
+
+    let record = |value: &[u8], updated_at: u64| SyncRecord {
+        kind: SyncKind::Policies,
+        key: String::new(),
+        value: value.to_vec(),
+        updated_at,
+    };
+    db_client.record_sync_record(record(b"old", 10)).await?;
+    db_client.record_sync_record(record(b"new", 20)).await?;
+    // an update made earlier on another device does not win
+    db_client.record_sync_record(record(b"older", 15)).await?;
+    let records = db_client.get_sync_records().await?;
+    assert_eq!(records, vec![record(b"new", 20)]);
+    Ok(())
+}
+
+async fn sealing_payloads_n_memos_at_rest_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
+    let sealed_client = db_client.clone().with_storage_key(StorageKey::new([9; 32]));
+
+    let tx = TxStateMachine {
+        amount: 3000,
+        tx_nonce: 3,
+        call_payload: Some([4; 32]),
+        signed_call_payload: Some(vec![5; 65]),
+        memo: Some("invoice 42".to_string()),
+        ..Default::default()
+    };
+    sealed_client
+        .record_tx_event(TxEvent::new(tx.clone(), 4))
+        .await?;
+    let events = sealed_client.get_tx_events(tx.tx_id()).await?;
+    assert_eq!(events.last().map(|event| &event.tx), Some(&tx));
+
+    // without the key only the sealed fields are stored
+    let stored = db_client.get_tx_events(tx.tx_id()).await?;
+    let stored = stored.last().expect("event is stored");
+    assert!(stored.sealed_fields.is_some());
+    assert_eq!(
+        (
+            stored.tx.call_payload,
+            &stored.tx.signed_call_payload,
+            &stored.tx.memo
+        ),
+        (None, &None, &None)
+    );
+    assert_eq!(stored.tx.amount, tx.amount);
+
+    // history memos are sealed too, references stay searchable
+    let record = DbTxStateMachine {
+        reference: Some("INV-42".to_string()),
+        ..DbTxStateMachine::of(&tx, vec![6; 32], true)
+    };
+    sealed_client.update_success_tx(record.clone()).await?;
+    let is_record = |tx: &&DbTxStateMachine| tx.reference == record.reference;
+    let history = sealed_client.get_success_txs().await?;
+    let opened = history.iter().find(is_record).expect("record is stored");
+    assert_eq!(opened.memo, record.memo);
+    let stored = db_client.get_success_txs().await?;
+    let stored = stored.iter().find(is_record).expect("record is stored");
+    assert_ne!(stored.memo, record.memo);
+    assert!(!stored.matches("invoice"));
+
+    let other_key = db_client.with_storage_key(StorageKey::new([8; 32]));
+    assert!(other_key.get_tx_events(tx.tx_id()).await.is_err());
+    Ok(())
+}
+
+async fn searching_history_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
+
+    // a customer of its own, the dev db keeps the records of previous runs
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_nanos();
+    let mut customer = [7u8; 20];
+    customer[..16].copy_from_slice(&nanos.to_be_bytes());
+    let customer = VaneAddress::Evm(customer);
+    let record = |amount: u128, network: ChainSupported, success: bool| DbTxStateMachine {
+        tx_hash: amount.to_be_bytes().to_vec(),
+        amount,
+        network,
+        success,
+        memo: Some(format!("order {amount}")),
+        sender_address: customer.to_string(),
+        receiver_address: VaneAddress::default().to_string(),
+        sender_name: Some("Acme Payroll".to_string()),
+        ..Default::default()
+    };
+    db_client
+        .update_success_tx(record(100, ChainSupported::Ethereum, true))
+        .await?;
+    db_client
+        .update_failed_tx(record(250, ChainSupported::Ethereum, false))
+        .await?;
+    db_client
+        .update_success_tx(record(300, ChainSupported::Bnb, true))
+        .await?;
+
+    let by_customer = HistorySearch {
+        text: Some(customer.to_string().to_lowercase()),
+        ..Default::default()
+    };
+    let amounts = |txs: Vec<DbTxStateMachine>| txs.iter().map(|tx| tx.amount).collect::<Vec<_>>();
+    // newest first
+    assert_eq!(
+        amounts(db_client.search_txs(&by_customer).await?),
+        vec![300, 250, 100]
+    );
+    let in_range = HistorySearch {
+        network: Some(ChainSupported::Ethereum),
+        min_amount: Some(200),
+        ..by_customer.clone()
+    };
+    assert_eq!(amounts(db_client.search_txs(&in_range).await?), vec![250]);
+    let succeeded = HistorySearch {
+        success: Some(true),
+        limit: Some(1),
+        ..by_customer
+    };
+    assert_eq!(amounts(db_client.search_txs(&succeeded).await?), vec![300]);
+    let by_memo = HistorySearch {
+        text: Some("ORDER 250".to_string()),
+        ..Default::default()
+    };
+    assert!(db_client
+        .search_txs(&by_memo)
+        .await?
+        .iter()
+        .all(|tx| tx.memo.as_deref() == Some("order 250")));
+    Ok(())
+}
+
+async fn recording_tx_receipts_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
+
+    // a hash of its own, the dev db keeps the records of previous runs
+    let tx_hash = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_nanos()
+        .to_be_bytes()
+        .to_vec();
+    db_client
+        .update_success_tx(DbTxStateMachine {
+            tx_hash: tx_hash.clone(),
+            amount: 1000,
+            network: ChainSupported::Ethereum,
+            success: true,
+            explorer_url: Some("https://etherscan.io/tx/0x01".to_string()),
+            fee_tier: Some(FeeTier::High),
+            ..Default::default()
+        })
+        .await?;
+    let receipt = ChainReceipt {
+        block_number: 19_000_000,
+        effective_gas_price: Some(30_000_000_000),
+        fee_paid: 21_000 * 30_000_000_000,
+        success: true,
+    };
+    db_client
+        .record_tx_receipt(tx_hash.clone(), receipt, Some(21_000 * 20_000_000_000))
+        .await?;
+
+    let recorded = db_client
+        .get_success_tx(tx_hash.clone())
+        .await?
+        .expect("recorded tx");
+    assert_eq!(recorded.receipt, Some(receipt));
+    assert_eq!(recorded.fee_tier, Some(FeeTier::High));
+    assert_eq!(recorded.low_tier_fee, Some(21_000 * 20_000_000_000));
+    assert_eq!(
+        recorded.explorer_url.as_deref(),
+        Some("https://etherscan.io/tx/0x01")
+    );
+
+    // the tier fee window starts with the latest transfer of the tier
+    let latest = db_client
+        .get_tier_fee_txs(ChainSupported::Ethereum, FeeTier::High, 1)
+        .await?;
+    assert_eq!(
+        latest.iter().map(|tx| &tx.tx_hash).collect::<Vec<_>>(),
+        vec![&tx_hash]
+    );
+    Ok(())
+}
+
+async fn rotating_peer_ids_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
+
+    let peer_id = || {
+        libp2p::identity::Keypair::generate_ed25519()
+            .public()
+            .to_peer_id()
+            .to_base58()
+    };
+    let (old_peer_id, new_peer_id) = (peer_id(), peer_id());
+    let node = PeerRecord {
+        record_id: "recn191".to_string(),
+        peer_id: Some(old_peer_id.clone()),
+        account_id1: Some(old_peer_id.clone()),
+        account_id2: None,
+        account_id3: None,
+        account_id4: None,
+        multi_addr: Some("/ip4/127.0.0.1/tcp/8080".to_string()),
+        keypair: Some(vec![1; 68]),
+    };
+    db_client.record_user_peer_id(node.clone()).await?;
+    db_client
+        .rotate_user_peer_id(
+            old_peer_id.clone(),
+            new_peer_id.clone(),
+            "/ip4/127.0.0.1/tcp/8081".to_string(),
+            vec![2; 68],
+        )
+        .await?;
+    let rotated: PeerRecord = db_client
+        .get_user_peer_id(None, Some(new_peer_id.clone()))
+        .await?;
+    assert_eq!(rotated.keypair, Some(vec![2; 68]));
+    assert_eq!(rotated.account_id1, node.account_id1);
+
+    let contact = PeerRecord {
+        keypair: None,
+        ..node
+    };
+    db_client.record_saved_user_peers(contact).await?;
+    db_client
+        .rotate_saved_peer_id(
+            old_peer_id.clone(),
+            new_peer_id.clone(),
+            "/ip4/127.0.0.1/tcp/8081".to_string(),
+        )
+        .await?;
+    let rotated = db_client.get_saved_user_peers(old_peer_id).await?;
+    assert_eq!(rotated.peer_id, Some(new_peer_id));
+    assert_eq!(
+        rotated.multi_addr,
+        Some("/ip4/127.0.0.1/tcp/8081".to_string())
+    );
+    Ok(())
+}
+
+async fn keeping_the_first_revocation_works(db_url: &str) -> Result<(), anyhow::Error> {
+    let db_client = DbWorker::initialize_db_client(db_url).await?;
+
+    let revocation = |account: &str, revoked_at: u64| AccountRevocation {
+        account: account.to_string(),
+        revoked_at,
+        signature: vec![1; 65],
+    };
+    db_client
+        .record_revocation(revocation("0x4444444444444444444444444444444444444AbC", 10))
+        .await?;
+    // the same account in another casing is revoked already
+    db_client
+        .record_revocation(revocation("0x4444444444444444444444444444444444444abc", 20))
+        .await?;
+    let revocations = db_client.get_revocations().await?;
+    assert_eq!(revocations.len(), 1);
+    assert_eq!(
+        revocations[0].account,
+        "0x4444444444444444444444444444444444444AbC"
+    );
+    assert_eq!(revocations[0].revoked_at, 10);
+    Ok(())
+}
+
 #[tokio::test]
 async fn all_db_tests_in_order_works() -> Result<(), anyhow::Error> {
-    user_creation_n_retrieving_works().await?;
-    storing_user_peer_id_n_retrieving_works().await?;
-    storing_success_n_failed_tx_works().await?;
-    storing_n_retrieving_saved_peers_works().await?;
+    user_creation_n_retrieving_works("./dev.db").await?;
+    storing_user_peer_id_n_retrieving_works("./dev.db").await?;
+    storing_success_n_failed_tx_works("./dev.db").await?;
+    storing_n_retrieving_saved_peers_works("./dev.db").await?;
+    storing_n_rebuilding_tx_events_works("./dev.db").await?;
+    replacing_policies_works("./dev.db").await?;
+    pairing_n_unpairing_signers_works("./dev.db").await?;
+    keeping_the_latest_peer_binding_works("./dev.db").await?;
+    pairing_devices_n_keeping_the_latest_sync_record_works("./dev.db").await?;
+    sealing_payloads_n_memos_at_rest_works("./dev.db").await?;
+    searching_history_works("./dev.db").await?;
+    recording_tx_receipts_works("./dev.db").await?;
+    rotating_peer_ids_works("./dev.db").await?;
+    keeping_the_first_revocation_works("./dev.db").await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn all_in_memory_db_tests_works() -> Result<(), anyhow::Error> {
+    user_creation_n_retrieving_works(IN_MEMORY_DB_URL).await?;
+    storing_user_peer_id_n_retrieving_works(IN_MEMORY_DB_URL).await?;
+    storing_success_n_failed_tx_works(IN_MEMORY_DB_URL).await?;
+    storing_n_retrieving_saved_peers_works(IN_MEMORY_DB_URL).await?;
+    storing_n_rebuilding_tx_events_works(IN_MEMORY_DB_URL).await?;
+    replacing_policies_works(IN_MEMORY_DB_URL).await?;
+    pairing_n_unpairing_signers_works(IN_MEMORY_DB_URL).await?;
+    keeping_the_latest_peer_binding_works(IN_MEMORY_DB_URL).await?;
+    pairing_devices_n_keeping_the_latest_sync_record_works(IN_MEMORY_DB_URL).await?;
+    sealing_payloads_n_memos_at_rest_works(IN_MEMORY_DB_URL).await?;
+    searching_history_works(IN_MEMORY_DB_URL).await?;
+    recording_tx_receipts_works(IN_MEMORY_DB_URL).await?;
+    rotating_peer_ids_works(IN_MEMORY_DB_URL).await?;
+    keeping_the_first_revocation_works(IN_MEMORY_DB_URL).await?;
     Ok(())
 }
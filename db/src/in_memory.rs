@@ -0,0 +1,538 @@
+// in-memory db, nothing is persisted
+// used by tests and ephemeral nodes so no sqlite file is needed
+
+use crate::{DbWorkerInterface, Ports};
+use alloc::sync::Arc;
+use anyhow::anyhow;
+use primitives::data_structure::{
+    ChainSupported, DbTxStateMachine, PeerRecord, TxEvent, TxKind, UserAccount,
+};
+use primitives::device_sync::{PairedDevice, SyncRecord};
+use primitives::explorer::ChainReceipt;
+use primitives::fees::FeeTier;
+use primitives::history::HistorySearch;
+use primitives::peer_exchange::PeerBinding;
+use primitives::revocation::AccountRevocation;
+use sp_core::H256;
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Default)]
+struct InMemoryState {
+    user_accounts: Vec<UserAccount>,
+    nonce: u32,
+    txs: Vec<DbTxStateMachine>,
+    success_value: u64,
+    failed_value: u64,
+    user_peers: Vec<PeerRecord>,
+    saved_peers: Vec<PeerRecord>,
+    ports: Option<Ports>,
+    tx_events: Vec<TxEvent>,
+    policies: Option<String>,
+    paired_signers: Vec<String>,
+    peer_bindings: Vec<PeerBinding>,
+    paired_devices: Vec<PairedDevice>,
+    sync_records: Vec<SyncRecord>,
+    revocations: Vec<AccountRevocation>,
+}
+
+/// in-memory counterpart of `LocalDbWorker`, clones share the same state
+#[derive(Clone, Default)]
+pub struct InMemoryDbWorker {
+    state: Arc<Mutex<InMemoryState>>,
+}
+
+impl InMemoryDbWorker {
+    fn state(&self) -> Result<MutexGuard<'_, InMemoryState>, anyhow::Error> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("in-memory db lock poisoned"))
+    }
+}
+
+/// `total` with the amount of `tx_state` added, refused rather than wrapped past `u64`
+fn add_amount(total: u64, tx_state: &DbTxStateMachine) -> Result<u64, anyhow::Error> {
+    u64::try_from(tx_state.amount)
+        .ok()
+        .and_then(|amount| total.checked_add(amount))
+        .ok_or(anyhow!(
+            "tx amount {} overflows the total value {total}",
+            tx_state.amount
+        ))
+}
+
+impl DbWorkerInterface for InMemoryDbWorker {
+    async fn initialize_db_client(_file_url: &str) -> Result<Self, anyhow::Error> {
+        Ok(Self::default())
+    }
+
+    async fn set_user_account(&self, user: UserAccount) -> Result<(), anyhow::Error> {
+        self.state()?.user_accounts.push(user);
+        Ok(())
+    }
+
+    async fn get_nonce(&self) -> Result<u32, anyhow::Error> {
+        Ok(self.state()?.nonce)
+    }
+
+    async fn get_user_accounts(
+        &self,
+        network: ChainSupported,
+    ) -> Result<Vec<UserAccount>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .user_accounts
+            .iter()
+            .filter(|account| account.network == network)
+            .cloned()
+            .collect())
+    }
+
+    async fn update_success_tx(&self, tx_state: DbTxStateMachine) -> Result<(), anyhow::Error> {
+        let mut state = self.state()?;
+        state.success_value = add_amount(state.success_value, &tx_state)?;
+        state.txs.push(tx_state);
+        Ok(())
+    }
+
+    async fn update_failed_tx(&self, tx_state: DbTxStateMachine) -> Result<(), anyhow::Error> {
+        let mut state = self.state()?;
+        state.failed_value = add_amount(state.failed_value, &tx_state)?;
+        state.txs.push(tx_state);
+        Ok(())
+    }
+
+    async fn get_failed_txs(&self) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .txs
+            .iter()
+            .filter(|tx| !tx.success)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_total_value_success(&self) -> Result<u64, anyhow::Error> {
+        Ok(self.state()?.success_value)
+    }
+
+    async fn get_total_value_failed(&self) -> Result<u64, anyhow::Error> {
+        Ok(self.state()?.failed_value)
+    }
+
+    async fn record_user_peer_id(&self, peer_record: PeerRecord) -> Result<(), anyhow::Error> {
+        self.state()?.user_peers.push(peer_record);
+        Ok(())
+    }
+
+    // same as the local db, the accounts of the first user peer record are updated
+    async fn update_user_peer_id_accounts(
+        &self,
+        peer_record: PeerRecord,
+    ) -> Result<(), anyhow::Error> {
+        let mut state = self.state()?;
+        let user_peer = state
+            .user_peers
+            .first_mut()
+            .ok_or(anyhow!("Peer not found in DB"))?;
+
+        if let Some(account_id) = peer_record.account_id1 {
+            user_peer.account_id1 = Some(account_id);
+        }
+        if let Some(account_id) = peer_record.account_id2 {
+            user_peer.account_id2 = Some(account_id);
+        }
+        if let Some(account_id) = peer_record.account_id3 {
+            user_peer.account_id3 = Some(account_id);
+        }
+        if let Some(account_id) = peer_record.account_id4 {
+            user_peer.account_id4 = Some(account_id);
+        }
+        Ok(())
+    }
+
+    async fn get_success_txs(&self) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .txs
+            .iter()
+            .filter(|tx| tx.success)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_success_tx(
+        &self,
+        tx_hash: Vec<u8>,
+    ) -> Result<Option<DbTxStateMachine>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .txs
+            .iter()
+            .find(|tx| tx.success && tx.tx_hash == tx_hash)
+            .cloned())
+    }
+
+    async fn get_tier_fee_txs(
+        &self,
+        network: ChainSupported,
+        tier: FeeTier,
+        limit: usize,
+    ) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .txs
+            .iter()
+            .rev()
+            .filter(|tx| {
+                tx.success
+                    && tx.network == network
+                    && tx.fee_tier == Some(tier)
+                    && tx.low_tier_fee.is_some()
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn search_txs(
+        &self,
+        search: &HistorySearch,
+    ) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .txs
+            .iter()
+            .rev()
+            .filter(|tx| search.matches_filters(tx))
+            .cloned()
+            .collect())
+    }
+
+    async fn record_tx_receipt(
+        &self,
+        tx_hash: Vec<u8>,
+        receipt: ChainReceipt,
+        low_tier_fee: Option<u128>,
+    ) -> Result<(), anyhow::Error> {
+        self.state()?
+            .txs
+            .iter_mut()
+            .filter(|tx| tx.tx_hash == tx_hash)
+            .for_each(|tx| {
+                tx.receipt = Some(receipt);
+                tx.low_tier_fee = low_tier_fee;
+            });
+        Ok(())
+    }
+
+    async fn get_user_peer_id(
+        &self,
+        account_id: Option<String>,
+        peer_id: Option<String>,
+    ) -> Result<PeerRecord, anyhow::Error> {
+        let state = self.state()?;
+        let peer = match (account_id, peer_id) {
+            (Some(acc_id), _) => state
+                .user_peers
+                .iter()
+                .find(|peer| peer.account_id1.as_ref() == Some(&acc_id)),
+            (_, Some(pid)) => state
+                .user_peers
+                .iter()
+                .find(|peer| peer.peer_id.as_ref() == Some(&pid)),
+            (None, None) => return Err(anyhow!("Please provide either account ID or peer ID")),
+        };
+        peer.cloned().ok_or(anyhow!("Peer not found in DB"))
+    }
+
+    async fn reserve_nonce(&self) -> Result<u32, anyhow::Error> {
+        let mut state = self.state()?;
+        state.nonce += 1;
+        Ok(state.nonce)
+    }
+
+    async fn set_ports(&self, rpc: u16, p2p: u16) -> Result<(), anyhow::Error> {
+        self.state()?.ports.get_or_insert(Ports {
+            rpc_port: rpc,
+            p_2_p_port: p2p,
+        });
+        Ok(())
+    }
+
+    async fn get_ports(&self) -> Result<Option<Ports>, anyhow::Error> {
+        Ok(self.state()?.ports.clone())
+    }
+
+    async fn record_saved_user_peers(&self, peer_record: PeerRecord) -> Result<(), anyhow::Error> {
+        self.state()?.saved_peers.push(peer_record);
+        Ok(())
+    }
+
+    async fn get_saved_user_peers(&self, account_id: String) -> Result<PeerRecord, anyhow::Error> {
+        self.state()?
+            .saved_peers
+            .iter()
+            .find(|peer| peer.account_id1.as_ref() == Some(&account_id))
+            .cloned()
+            .ok_or(anyhow!("Peer Not found in DB"))
+    }
+
+    async fn record_tx_event(&self, event: TxEvent) -> Result<(), anyhow::Error> {
+        self.state()?.tx_events.push(event);
+        Ok(())
+    }
+
+    async fn record_tx_events(&self, events: Vec<TxEvent>) -> Result<(), anyhow::Error> {
+        self.state()?.tx_events.extend(events);
+        Ok(())
+    }
+
+    async fn get_tx_events(&self, tx_id: H256) -> Result<Vec<TxEvent>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .tx_events
+            .iter()
+            .filter(|event| event.tx_id == tx_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_all_tx_events(&self) -> Result<Vec<TxEvent>, anyhow::Error> {
+        Ok(self.state()?.tx_events.clone())
+    }
+
+    async fn get_tx_events_since(&self, recorded_at: u64) -> Result<Vec<TxEvent>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .tx_events
+            .iter()
+            .filter(|event| event.recorded_at >= recorded_at)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_sender_tx_events(
+        &self,
+        sender: String,
+        receiver: Option<String>,
+    ) -> Result<Vec<TxEvent>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .tx_events
+            .iter()
+            .filter(|event| {
+                let (from, to) = (
+                    event.tx.sender_address.to_string(),
+                    event.tx.receiver_address.to_string(),
+                );
+                from.eq_ignore_ascii_case(&sender)
+                    && receiver
+                        .as_ref()
+                        .map_or(true, |receiver| to.eq_ignore_ascii_case(receiver))
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_account_tx_events(&self, account: String) -> Result<Vec<TxEvent>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .tx_events
+            .iter()
+            .filter(|event| {
+                let (from, to) = (
+                    event.tx.sender_address.to_string(),
+                    event.tx.receiver_address.to_string(),
+                );
+                from.eq_ignore_ascii_case(&account) || to.eq_ignore_ascii_case(&account)
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_payment_tx_events(&self, payment_id: H256) -> Result<Vec<TxEvent>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .tx_events
+            .iter()
+            .filter(|event| event.tx.parent_payment == Some(payment_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_approval_tx_events(
+        &self,
+        owner: Option<String>,
+    ) -> Result<Vec<TxEvent>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .tx_events
+            .iter()
+            .filter(|event| {
+                let from = event.tx.sender_address.to_string();
+                event.tx.kind == TxKind::Approval
+                    && owner
+                        .as_ref()
+                        .map_or(true, |owner| from.eq_ignore_ascii_case(owner))
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn set_policies(&self, document: String) -> Result<(), anyhow::Error> {
+        self.state()?.policies = Some(document);
+        Ok(())
+    }
+
+    async fn get_policies(&self) -> Result<Option<String>, anyhow::Error> {
+        Ok(self.state()?.policies.clone())
+    }
+
+    async fn pair_signer(&self, account: String) -> Result<(), anyhow::Error> {
+        let mut state = self.state()?;
+        if !state.paired_signers.contains(&account) {
+            state.paired_signers.push(account);
+        }
+        Ok(())
+    }
+
+    async fn unpair_signer(&self, account: String) -> Result<(), anyhow::Error> {
+        self.state()?
+            .paired_signers
+            .retain(|paired| *paired != account);
+        Ok(())
+    }
+
+    async fn get_paired_signers(&self) -> Result<Vec<String>, anyhow::Error> {
+        Ok(self.state()?.paired_signers.clone())
+    }
+
+    async fn record_peer_binding(&self, binding: PeerBinding) -> Result<(), anyhow::Error> {
+        let mut state = self.state()?;
+        let stored = state
+            .peer_bindings
+            .iter()
+            .position(|stored| stored.account.eq_ignore_ascii_case(&binding.account));
+        match stored {
+            Some(index) if binding.supersedes(&state.peer_bindings[index]) => {
+                state.peer_bindings[index] = binding
+            }
+            Some(_) => {}
+            None => state.peer_bindings.push(binding),
+        }
+        Ok(())
+    }
+
+    async fn get_peer_bindings(&self) -> Result<Vec<PeerBinding>, anyhow::Error> {
+        Ok(self.state()?.peer_bindings.clone())
+    }
+
+    async fn get_peer_binding(
+        &self,
+        account: String,
+    ) -> Result<Option<PeerBinding>, anyhow::Error> {
+        Ok(self
+            .state()?
+            .peer_bindings
+            .iter()
+            .find(|binding| binding.account.eq_ignore_ascii_case(&account))
+            .cloned())
+    }
+
+    async fn get_all_saved_user_peers(&self) -> Result<Vec<PeerRecord>, anyhow::Error> {
+        Ok(self.state()?.saved_peers.clone())
+    }
+
+    async fn record_paired_device(&self, device: PairedDevice) -> Result<(), anyhow::Error> {
+        let mut state = self.state()?;
+        state
+            .paired_devices
+            .retain(|paired| paired.peer_id != device.peer_id);
+        state.paired_devices.push(device);
+        Ok(())
+    }
+
+    async fn unpair_device(&self, peer_id: String) -> Result<(), anyhow::Error> {
+        self.state()?
+            .paired_devices
+            .retain(|paired| paired.peer_id != peer_id);
+        Ok(())
+    }
+
+    async fn get_paired_devices(&self) -> Result<Vec<PairedDevice>, anyhow::Error> {
+        Ok(self.state()?.paired_devices.clone())
+    }
+
+    async fn record_sync_record(&self, record: SyncRecord) -> Result<(), anyhow::Error> {
+        let mut state = self.state()?;
+        let stored = state
+            .sync_records
+            .iter()
+            .position(|stored| stored.id() == record.id());
+        match stored {
+            Some(index) if record.supersedes(&state.sync_records[index]) => {
+                state.sync_records[index] = record
+            }
+            Some(_) => {}
+            None => state.sync_records.push(record),
+        }
+        Ok(())
+    }
+
+    async fn get_sync_records(&self) -> Result<Vec<SyncRecord>, anyhow::Error> {
+        Ok(self.state()?.sync_records.clone())
+    }
+
+    async fn rotate_user_peer_id(
+        &self,
+        old_peer_id: String,
+        peer_id: String,
+        multi_addr: String,
+        keypair: Vec<u8>,
+    ) -> Result<(), anyhow::Error> {
+        let mut state = self.state()?;
+        let user_peer = state
+            .user_peers
+            .iter_mut()
+            .find(|peer| peer.peer_id.as_ref() == Some(&old_peer_id))
+            .ok_or(anyhow!("Peer not found in DB"))?;
+        user_peer.peer_id = Some(peer_id);
+        user_peer.multi_addr = Some(multi_addr);
+        user_peer.keypair = Some(keypair);
+        Ok(())
+    }
+
+    async fn rotate_saved_peer_id(
+        &self,
+        old_peer_id: String,
+        peer_id: String,
+        multi_addr: String,
+    ) -> Result<(), anyhow::Error> {
+        for peer in self
+            .state()?
+            .saved_peers
+            .iter_mut()
+            .filter(|peer| peer.peer_id.as_ref() == Some(&old_peer_id))
+        {
+            peer.peer_id = Some(peer_id.clone());
+            peer.multi_addr = Some(multi_addr.clone());
+        }
+        Ok(())
+    }
+
+    async fn record_revocation(&self, revocation: AccountRevocation) -> Result<(), anyhow::Error> {
+        let mut state = self.state()?;
+        if !state
+            .revocations
+            .iter()
+            .any(|stored| stored.revokes(&revocation.account))
+        {
+            state.revocations.push(revocation);
+        }
+        Ok(())
+    }
+
+    async fn get_revocations(&self) -> Result<Vec<AccountRevocation>, anyhow::Error> {
+        Ok(self.state()?.revocations.clone())
+    }
+}
@@ -5,32 +5,51 @@ extern crate alloc;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod db;
 
+mod at_rest;
 #[cfg(test)]
 mod db_tests;
+mod in_memory;
+
+pub use at_rest::StorageKey;
+pub use in_memory::InMemoryDbWorker;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::db::read_filters::{BoolFilter, StringFilter};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::db::transactions_data::{UniqueWhereParam, WhereParam};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::db::{
-    new_client_with_url, nonce, port,
+    account_revocation, new_client_with_url, nonce, paired_device, paired_signer, peer_binding,
+    policy, port,
     read_filters::{BigIntFilter, BytesFilter, IntFilter},
-    saved_peers, transaction, transactions_data, user_account, user_peer, PrismaClient,
-    PrismaClientBuilder, UserPeerScalarFieldEnum,
+    saved_peers, sync_record, transaction, transactions_data, tx_event, user_account, user_peer,
+    PrismaClient, PrismaClientBuilder, UserPeerScalarFieldEnum,
 };
+#[cfg(target_arch = "wasm32")]
+use alloc::collections::BTreeSet;
 use alloc::sync::Arc;
 use anyhow::{anyhow, Error};
 use codec::{Decode, Encode};
 use hex;
 use log::{debug, error, info, trace, warn};
-use primitives::data_structure::{ChainSupported, DbTxStateMachine, PeerRecord, UserAccount};
+use primitives::data_structure::{
+    ChainSupported, DbTxStateMachine, PeerRecord, TxEvent, TxKind, UserAccount,
+};
+use primitives::device_sync::{PairedDevice, SyncRecord};
+#[cfg(not(target_arch = "wasm32"))]
+use primitives::device_sync::SyncKind;
+use primitives::explorer::ChainReceipt;
+use primitives::fees::FeeTier;
+use primitives::history::HistorySearch;
+use primitives::peer_exchange::PeerBinding;
+use primitives::revocation::AccountRevocation;
+use sp_core::H256;
 #[cfg(not(target_arch = "wasm32"))]
 use prisma_client_rust::{query_core::RawQuery, BatchItem, Direction, PrismaValue, Raw};
 use serde::{Deserialize, Serialize};
 #[cfg(not(target_arch = "wasm32"))]
 use std::future::Future;
 #[cfg(target_arch = "wasm32")]
-use redb::{Database, ReadableTable, TableDefinition};
+use redb::{Database, MultimapTableDefinition, ReadableTable, TableDefinition};
 #[cfg(target_arch = "wasm32")]
 use web_sys::{FileSystemDirectoryHandle, StorageManager};
 #[cfg(not(target_arch = "wasm32"))]
@@ -47,6 +66,10 @@ const TRANSACTIONS_DATA_TABLE: TableDefinition<&str, Vec<u8>> = TableDefinition:
 // stores array of tx but all are encoded
 #[cfg(target_arch = "wasm32")]
 const TRANSACTION_TABLE: TableDefinition<&str, Vec<Vec<u8>>> = TableDefinition::new("transactions");
+// encoded success txs keyed by tx hash
+#[cfg(target_arch = "wasm32")]
+const SUCCESS_TX_BY_HASH_TABLE: TableDefinition<&[u8], Vec<u8>> =
+    TableDefinition::new("success_txs_by_hash");
 #[cfg(target_arch = "wasm32")]
 const NONCE_TABLE: TableDefinition<&str, u32> = TableDefinition::new("nonce");
 // stores array of user profiles
@@ -57,6 +80,57 @@ const USER_PEER_TABLE: TableDefinition<&str, Vec<u8>> = TableDefinition::new("us
 #[cfg(target_arch = "wasm32")]
 const SAVED_PEERS_TABLE: TableDefinition<&str, Vec<Vec<u8>>> = TableDefinition::new("saved_peers");
 
+// append only tx event log keyed by insertion sequence
+#[cfg(target_arch = "wasm32")]
+const TX_EVENTS_TABLE: TableDefinition<u64, Vec<u8>> = TableDefinition::new("tx_events");
+
+// tx event sequences keyed by tx id
+#[cfg(target_arch = "wasm32")]
+const TX_EVENTS_BY_TX_TABLE: MultimapTableDefinition<[u8; 32], u64> =
+    MultimapTableDefinition::new("tx_events_by_tx");
+
+// tx event sequences keyed by lowercase sender
+#[cfg(target_arch = "wasm32")]
+const TX_EVENTS_BY_SENDER_TABLE: MultimapTableDefinition<&str, u64> =
+    MultimapTableDefinition::new("tx_events_by_sender");
+
+// tx event sequences keyed by lowercase receiver
+#[cfg(target_arch = "wasm32")]
+const TX_EVENTS_BY_RECEIVER_TABLE: MultimapTableDefinition<&str, u64> =
+    MultimapTableDefinition::new("tx_events_by_receiver");
+
+// tx event sequences of the split payment parts keyed by payment id
+#[cfg(target_arch = "wasm32")]
+const TX_EVENTS_BY_PAYMENT_TABLE: MultimapTableDefinition<[u8; 32], u64> =
+    MultimapTableDefinition::new("tx_events_by_payment");
+
+// tx event sequences keyed by debug formatted tx kind
+#[cfg(target_arch = "wasm32")]
+const TX_EVENTS_BY_KIND_TABLE: MultimapTableDefinition<&str, u64> =
+    MultimapTableDefinition::new("tx_events_by_kind");
+
+#[cfg(target_arch = "wasm32")]
+const POLICY_TABLE: TableDefinition<&str, String> = TableDefinition::new("policies");
+
+#[cfg(target_arch = "wasm32")]
+const PAIRED_SIGNERS_TABLE: TableDefinition<&str, u64> = TableDefinition::new("paired_signers");
+
+// encoded peer bindings keyed by lowercase account
+#[cfg(target_arch = "wasm32")]
+const PEER_BINDINGS_TABLE: TableDefinition<&str, Vec<u8>> = TableDefinition::new("peer_bindings");
+
+// encoded paired devices keyed by peer id
+#[cfg(target_arch = "wasm32")]
+const PAIRED_DEVICES_TABLE: TableDefinition<&str, Vec<u8>> = TableDefinition::new("paired_devices");
+
+// encoded sync records keyed by record id
+#[cfg(target_arch = "wasm32")]
+const SYNC_RECORDS_TABLE: TableDefinition<&str, Vec<u8>> = TableDefinition::new("sync_records");
+
+// encoded account revocations keyed by lowercase account
+#[cfg(target_arch = "wasm32")]
+const REVOCATIONS_TABLE: TableDefinition<&str, Vec<u8>> = TableDefinition::new("revocations");
+
 // ===================================== DB KEYS ====================================== //
 #[cfg(target_arch = "wasm32")]
 pub const USER_ACC_KEY:&str = "user_account";
@@ -72,6 +146,8 @@ pub const USER_PEER_RECORD_KEY:&str = "user_peer";
 pub const SAVED_PEERS_KEY: &str = "saved_peers";
 #[cfg(target_arch = "wasm32")]
 pub const PORTS_KEY:&str = "saved_ports";
+#[cfg(target_arch = "wasm32")]
+pub const POLICY_KEY:&str = "policy_document";
 
 pub enum DbEngine {
     NativeLocal,
@@ -111,6 +187,37 @@ pub trait DbWorkerInterface:Sized {
 
     async fn get_success_txs(&self) -> Result<Vec<DbTxStateMachine>, anyhow::Error>;
 
+    // history record of the submitted tx with `tx_hash`
+    async fn get_success_tx(
+        &self,
+        tx_hash: Vec<u8>,
+    ) -> Result<Option<DbTxStateMachine>, anyhow::Error>;
+
+    // latest submitted txs on `network` built with `tier` whose low tier fee is known, newest
+    // first, at most `limit`
+    async fn get_tier_fee_txs(
+        &self,
+        network: ChainSupported,
+        tier: FeeTier,
+        limit: usize,
+    ) -> Result<Vec<DbTxStateMachine>, anyhow::Error>;
+
+    /// history records passing the structured filters and the counterparty address of `search`,
+    /// newest first. the rest of the text is matched by `DbWorker` once the memos are opened
+    async fn search_txs(
+        &self,
+        search: &HistorySearch,
+    ) -> Result<Vec<DbTxStateMachine>, anyhow::Error>;
+
+    /// enrich the history record of `tx_hash` with its chain receipt and the fee it would have paid
+    /// at the low tier of its block
+    async fn record_tx_receipt(
+        &self,
+        tx_hash: Vec<u8>,
+        receipt: ChainReceipt,
+        low_tier_fee: Option<u128>,
+    ) -> Result<(), anyhow::Error>;
+
     // get peer by account id by either account id or peerId
     async fn get_user_peer_id(
         &self,
@@ -118,7 +225,8 @@ pub trait DbWorkerInterface:Sized {
         peer_id: Option<String>,
     ) -> Result<PeerRecord, anyhow::Error>;
 
-    async fn increment_nonce(&self) -> Result<(), anyhow::Error>;
+    /// take the next vane tx nonce in one write, nodes sharing the db never take the same one
+    async fn reserve_nonce(&self) -> Result<u32, anyhow::Error>;
     // set port ids {
     async fn set_ports(&self, rpc: u16, p2p: u16) -> Result<(), anyhow::Error>;
     // get port ids
@@ -135,6 +243,102 @@ pub trait DbWorkerInterface:Sized {
         &self,
         account_id: String,
     ) -> Result<PeerRecord, anyhow::Error>;
+
+    // append a tx state transition to the event log, events are never updated
+    async fn record_tx_event(&self, event: TxEvent) -> Result<(), anyhow::Error>;
+
+    // append several events in one write, none is recorded when one fails
+    async fn record_tx_events(&self, events: Vec<TxEvent>) -> Result<(), anyhow::Error>;
+
+    // events of a single tx in recorded order
+    async fn get_tx_events(&self, tx_id: H256) -> Result<Vec<TxEvent>, anyhow::Error>;
+
+    // whole event log in recorded order
+    async fn get_all_tx_events(&self) -> Result<Vec<TxEvent>, anyhow::Error>;
+
+    // events recorded at or after `recorded_at`, in recorded order
+    async fn get_tx_events_since(&self, recorded_at: u64) -> Result<Vec<TxEvent>, anyhow::Error>;
+
+    // events of the txs from `sender`, to `receiver` when set, in recorded order
+    async fn get_sender_tx_events(
+        &self,
+        sender: String,
+        receiver: Option<String>,
+    ) -> Result<Vec<TxEvent>, anyhow::Error>;
+
+    // events of the txs from or to `account`, in recorded order
+    async fn get_account_tx_events(&self, account: String) -> Result<Vec<TxEvent>, anyhow::Error>;
+
+    // events of the parts of the split payment `payment_id`, in recorded order
+    async fn get_payment_tx_events(&self, payment_id: H256) -> Result<Vec<TxEvent>, anyhow::Error>;
+
+    // events of the token approval txs, of `owner` when set, in recorded order
+    async fn get_approval_tx_events(
+        &self,
+        owner: Option<String>,
+    ) -> Result<Vec<TxEvent>, anyhow::Error>;
+
+    // replace the operator policy document, kept as submitted
+    async fn set_policies(&self, document: String) -> Result<(), anyhow::Error>;
+
+    // policy document in force, none until the operator sets one
+    async fn get_policies(&self) -> Result<Option<String>, anyhow::Error>;
+
+    // pair a signing device account, pairing twice is a no-op
+    async fn pair_signer(&self, account: String) -> Result<(), anyhow::Error>;
+
+    async fn unpair_signer(&self, account: String) -> Result<(), anyhow::Error>;
+
+    // accounts of the paired signing devices
+    async fn get_paired_signers(&self) -> Result<Vec<String>, anyhow::Error>;
+
+    // store a verified peer binding, an earlier signed binding of the same account is replaced
+    async fn record_peer_binding(&self, binding: PeerBinding) -> Result<(), anyhow::Error>;
+
+    // latest verified binding of every account
+    async fn get_peer_bindings(&self) -> Result<Vec<PeerBinding>, anyhow::Error>;
+
+    // latest verified binding of one account
+    async fn get_peer_binding(&self, account: String)
+        -> Result<Option<PeerBinding>, anyhow::Error>;
+
+    // every saved peer, synced to the paired devices as contacts
+    async fn get_all_saved_user_peers(&self) -> Result<Vec<PeerRecord>, anyhow::Error>;
+
+    // store a device paired for sync, pairing it again replaces it
+    async fn record_paired_device(&self, device: PairedDevice) -> Result<(), anyhow::Error>;
+
+    async fn unpair_device(&self, peer_id: String) -> Result<(), anyhow::Error>;
+
+    async fn get_paired_devices(&self) -> Result<Vec<PairedDevice>, anyhow::Error>;
+
+    // store a synced record, an earlier update of the same record is replaced
+    async fn record_sync_record(&self, record: SyncRecord) -> Result<(), anyhow::Error>;
+
+    // latest update of every synced record
+    async fn get_sync_records(&self) -> Result<Vec<SyncRecord>, anyhow::Error>;
+
+    // move the node peer record to the rotated identity of the node
+    async fn rotate_user_peer_id(
+        &self,
+        old_peer_id: String,
+        peer_id: String,
+        multi_addr: String,
+        keypair: Vec<u8>,
+    ) -> Result<(), anyhow::Error>;
+
+    // move the saved peers of a contact node that rotated its identity to its new peer id
+    async fn rotate_saved_peer_id(
+        &self,
+        old_peer_id: String,
+        peer_id: String,
+        multi_addr: String,
+    ) -> Result<(), anyhow::Error>;
+
+    // store a verified account revocation, the first revocation of an account is kept
+    async fn record_revocation(&self, revocation: AccountRevocation) -> Result<(), anyhow::Error>;
+
+    async fn get_revocations(&self) -> Result<Vec<AccountRevocation>, anyhow::Error>;
 }
 
 /// handling connection and interaction with the browser based OPFS database
@@ -151,8 +355,7 @@ struct TransactionsData {
     failed_value: i64,
 }
 
-#[cfg(target_arch = "wasm32")]
-#[derive(Serialize, Deserialize, Encode, Decode)]
+#[derive(Clone, Debug, Serialize, Deserialize, Encode, Decode)]
 pub struct Ports {
     pub rpc_port: u16,
     pub p_2_p_port: u16,
@@ -170,9 +373,22 @@ impl OpfsRedbWorker {
             write_txn.open_table(PORT_TABLE)?;
             write_txn.open_table(TRANSACTIONS_DATA_TABLE)?;
             write_txn.open_table(TRANSACTION_TABLE)?;
+            write_txn.open_table(SUCCESS_TX_BY_HASH_TABLE)?;
             write_txn.open_table(NONCE_TABLE)?;
             write_txn.open_table(USER_PEER_TABLE)?;
             write_txn.open_table(SAVED_PEERS_TABLE)?;
+            write_txn.open_table(TX_EVENTS_TABLE)?;
+            write_txn.open_multimap_table(TX_EVENTS_BY_TX_TABLE)?;
+            write_txn.open_multimap_table(TX_EVENTS_BY_SENDER_TABLE)?;
+            write_txn.open_multimap_table(TX_EVENTS_BY_RECEIVER_TABLE)?;
+            write_txn.open_multimap_table(TX_EVENTS_BY_PAYMENT_TABLE)?;
+            write_txn.open_multimap_table(TX_EVENTS_BY_KIND_TABLE)?;
+            write_txn.open_table(POLICY_TABLE)?;
+            write_txn.open_table(PAIRED_SIGNERS_TABLE)?;
+            write_txn.open_table(PEER_BINDINGS_TABLE)?;
+            write_txn.open_table(PAIRED_DEVICES_TABLE)?;
+            write_txn.open_table(SYNC_RECORDS_TABLE)?;
+            write_txn.open_table(REVOCATIONS_TABLE)?;
         }
         write_txn.commit()?;
 
@@ -205,25 +421,28 @@ impl DbWorkerInterface for OpfsRedbWorker {
         Ok(table.get(&NONCE_KEY)?.map(|v| v.value()).unwrap_or(0))
     }
 
-    async fn increment_nonce(&self) -> Result<(), anyhow::Error> {
+    async fn reserve_nonce(&self) -> Result<u32, anyhow::Error> {
         let write_txn = self.db.begin_write()?;
-        {
+        let next = {
             let mut table = write_txn.open_table(NONCE_TABLE)?;
             let current = table.get(&NONCE_KEY)?.map(|v| v.value()).unwrap_or(0);
             table.insert(&NONCE_KEY, &(current + 1))?;
-        }
+            current + 1
+        };
         write_txn.commit()?;
-        Ok(())
+        Ok(next)
     }
 
     async fn update_success_tx(&self, tx_state: DbTxStateMachine) -> Result<(), anyhow::Error> {
         let write_txn = self.db.begin_write()?;
         {
             let mut tx_table = write_txn.open_table(TRANSACTION_TABLE)?;
+            let mut by_hash = write_txn.open_table(SUCCESS_TX_BY_HASH_TABLE)?;
             let mut data_table = write_txn.open_table(TRANSACTIONS_DATA_TABLE)?;
 
             // Update transaction
             let tx_data = tx_state.encode();
+            by_hash.insert(tx_state.tx_hash.as_slice(), tx_data.clone())?;
             let to_store = if let Some(get_txs) = tx_table.get(TXS_KEY).map_err(|err|anyhow!("error on txs:{err:?}"))?{
                 let mut saved_txs = get_txs.value();
                 saved_txs.push(tx_data);
@@ -338,6 +557,95 @@ impl DbWorkerInterface for OpfsRedbWorker {
         Ok(success_txs)
     }
 
+    async fn get_success_tx(
+        &self,
+        tx_hash: Vec<u8>,
+    ) -> Result<Option<DbTxStateMachine>, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(SUCCESS_TX_BY_HASH_TABLE)?;
+
+        let Some(value) = table.get(tx_hash.as_slice())? else {
+            return Ok(None);
+        };
+        let tx = Decode::decode(&mut &value.value()[..])
+            .map_err(|err| anyhow!("failed to decode: {err:?}"))?;
+        Ok(Some(tx))
+    }
+
+    async fn get_tier_fee_txs(
+        &self,
+        network: ChainSupported,
+        tier: FeeTier,
+        limit: usize,
+    ) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(TRANSACTION_TABLE)?;
+
+        let mut found = Vec::new();
+        let Some(values) = table.get(TXS_KEY)? else {
+            return Ok(found);
+        };
+        for value in values.value().iter().rev() {
+            if found.len() == limit {
+                break;
+            }
+            let tx: DbTxStateMachine = Decode::decode(&mut &value[..])
+                .map_err(|err| anyhow!("failed to decode: {err:?}"))?;
+            if tx.success
+                && tx.network == network
+                && tx.fee_tier == Some(tier)
+                && tx.low_tier_fee.is_some()
+            {
+                found.push(tx);
+            }
+        }
+        Ok(found)
+    }
+
+    async fn search_txs(&self, search: &HistorySearch) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(TRANSACTION_TABLE)?;
+
+        let mut found = Vec::new();
+        let values = table.get(TXS_KEY).map_err(|err|anyhow!("failed to get txs: {err:?}"))?.expect("failed to get txs");
+        for value in values.value().iter().rev() {
+            let tx: DbTxStateMachine = Decode::decode(&mut &value[..]).map_err(|err|anyhow!("failed to decode: {err:?}"))?;
+            if search.matches_filters(&tx) {
+                found.push(tx);
+            }
+        }
+        Ok(found)
+    }
+
+    async fn record_tx_receipt(&self, tx_hash: Vec<u8>, receipt: ChainReceipt, low_tier_fee: Option<u128>) -> Result<(), anyhow::Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut tx_table = write_txn.open_table(TRANSACTION_TABLE)?;
+            let mut saved_txs = tx_table.get(TXS_KEY).map_err(|err|anyhow!("error on txs:{err:?}"))?.map(|txs| txs.value()).unwrap_or_default();
+            for value in saved_txs.iter_mut() {
+                let mut tx: DbTxStateMachine = Decode::decode(&mut &value[..]).map_err(|err|anyhow!("failed to decode: {err:?}"))?;
+                if tx.tx_hash == tx_hash {
+                    tx.receipt = Some(receipt);
+                    tx.low_tier_fee = low_tier_fee;
+                    *value = tx.encode();
+                }
+            }
+            tx_table.insert(TXS_KEY, saved_txs)?;
+
+            let mut by_hash = write_txn.open_table(SUCCESS_TX_BY_HASH_TABLE)?;
+            let indexed = by_hash.get(tx_hash.as_slice())?.map(|value| value.value());
+            if let Some(value) = indexed {
+                let mut tx: DbTxStateMachine = Decode::decode(&mut &value[..])
+                    .map_err(|err| anyhow!("failed to decode: {err:?}"))?;
+                tx.receipt = Some(receipt);
+                tx.low_tier_fee = low_tier_fee;
+                by_hash.insert(tx_hash.as_slice(), tx.encode())?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
     async fn record_user_peer_id(&self, peer_record: PeerRecord) -> Result<(), anyhow::Error> {
         let write_txn = self.db.begin_write()?;
         {
@@ -498,123 +806,549 @@ impl DbWorkerInterface for OpfsRedbWorker {
 
         Err(anyhow!("No saved peer found for account ID: {}", account_id))
     }
-}
 
-/// Handling connection and interaction with the local database
-#[cfg(not(target_arch = "wasm32"))]
-#[derive(Clone)]
-pub struct LocalDbWorker {
-    db: Arc<PrismaClient>,
-}
+    async fn record_tx_event(&self, event: TxEvent) -> Result<(), Error> {
+        self.record_tx_events(vec![event]).await
+    }
 
-#[cfg(not(target_arch = "wasm32"))]
-const SERVER_DATA_ID: i32 = 1;
+    async fn record_tx_events(&self, events: Vec<TxEvent>) -> Result<(), Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(TX_EVENTS_TABLE)?;
+            let mut by_tx = write_txn.open_multimap_table(TX_EVENTS_BY_TX_TABLE)?;
+            let mut by_sender = write_txn.open_multimap_table(TX_EVENTS_BY_SENDER_TABLE)?;
+            let mut by_receiver = write_txn.open_multimap_table(TX_EVENTS_BY_RECEIVER_TABLE)?;
+            let mut by_payment = write_txn.open_multimap_table(TX_EVENTS_BY_PAYMENT_TABLE)?;
+            let mut by_kind = write_txn.open_multimap_table(TX_EVENTS_BY_KIND_TABLE)?;
+            for event in events {
+                let sequence = table.last()?.map(|(key, _)| key.value() + 1).unwrap_or(0);
+                table.insert(sequence, event.encode())?;
+                by_tx.insert(event.tx_id.as_fixed_bytes(), sequence)?;
+                let sender = event.tx.sender_address.to_string().to_lowercase();
+                by_sender.insert(sender.as_str(), sequence)?;
+                let receiver = event.tx.receiver_address.to_string().to_lowercase();
+                by_receiver.insert(receiver.as_str(), sequence)?;
+                if let Some(payment_id) = event.tx.parent_payment {
+                    by_payment.insert(payment_id.as_fixed_bytes(), sequence)?;
+                }
+                by_kind.insert(format!("{:?}", event.tx.kind).as_str(), sequence)?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
 
-#[cfg(not(target_arch = "wasm32"))]
-impl DbWorkerInterface for LocalDbWorker {
-    async fn initialize_db_client(file_url: &str) -> Result<Self, anyhow::Error> {
-        let url = format!("file:{}", file_url);
-        let client = new_client_with_url(&url)
-            .await
-            .map_err(|err| anyhow!("failed to initialize db client, caused by: {err}"))?;
+    async fn get_tx_events(&self, tx_id: H256) -> Result<Vec<TxEvent>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let by_tx = read_txn.open_multimap_table(TX_EVENTS_BY_TX_TABLE)?;
+        let table = read_txn.open_table(TX_EVENTS_TABLE)?;
+
+        let mut events = Vec::new();
+        for sequence in by_tx.get(tx_id.as_fixed_bytes())? {
+            if let Some(value) = table.get(sequence?.value())? {
+                events.push(
+                    TxEvent::decode(&mut &value.value()[..])
+                        .map_err(|err| anyhow!("failed to decode: {err:?}"))?,
+                );
+            }
+        }
+        Ok(events)
+    }
 
-        let client = Arc::new(client);
+    async fn get_all_tx_events(&self) -> Result<Vec<TxEvent>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(TX_EVENTS_TABLE)?;
 
-        cfg!(feature = "e2e");
-        client._migrate_deploy().await?;
+        let mut events = Vec::new();
+        for result in table.iter()? {
+            let (_, value) = result?;
+            let event: TxEvent = Decode::decode(&mut &value.value()[..]).map_err(|err|anyhow!("failed to decode: {err:?}"))?;
+            events.push(event);
+        }
+        Ok(events)
+    }
 
-        // we are initializing transaction data as all of following operations is going to be updating this storage item
-        let return_data = client
-            .transactions_data()
-            .find_first(vec![WhereParam::Id(IntFilter::Equals(1))])
-            .exec()
-            .await;
+    async fn get_tx_events_since(&self, recorded_at: u64) -> Result<Vec<TxEvent>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(TX_EVENTS_TABLE)?;
 
-        if let Ok(return_data) = return_data {
-            if let None = return_data {
-                client
-                    .transactions_data()
-                    .create(0, 0, vec![])
-                    .exec()
-                    .await?;
+        // the log is appended in recorded order, read back from its end
+        let mut events = Vec::new();
+        for result in table.iter()?.rev() {
+            let (_, value) = result?;
+            let event = TxEvent::decode(&mut &value.value()[..])
+                .map_err(|err| anyhow!("failed to decode: {err:?}"))?;
+            if event.recorded_at < recorded_at {
+                break;
             }
-        } else {
-            // create new tx data
-            if let Err(err) = client.transactions_data().create(0, 0, vec![]).exec().await {
-                error!(target:"db","failed to create new transaction data; caused by: {err}");
+            events.push(event);
+        }
+        events.reverse();
+        Ok(events)
+    }
+
+    async fn get_sender_tx_events(
+        &self,
+        sender: String,
+        receiver: Option<String>,
+    ) -> Result<Vec<TxEvent>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let by_sender = read_txn.open_multimap_table(TX_EVENTS_BY_SENDER_TABLE)?;
+        let table = read_txn.open_table(TX_EVENTS_TABLE)?;
+
+        let mut events = Vec::new();
+        for sequence in by_sender.get(sender.to_lowercase().as_str())? {
+            let Some(value) = table.get(sequence?.value())? else {
+                continue;
+            };
+            let event = TxEvent::decode(&mut &value.value()[..])
+                .map_err(|err| anyhow!("failed to decode: {err:?}"))?;
+            let to = event.tx.receiver_address.to_string();
+            if receiver
+                .as_ref()
+                .map_or(true, |receiver| to.eq_ignore_ascii_case(receiver))
+            {
+                events.push(event);
             }
         }
-        Ok(Self { db: client })
+        Ok(events)
     }
 
+    async fn get_account_tx_events(&self, account: String) -> Result<Vec<TxEvent>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let by_sender = read_txn.open_multimap_table(TX_EVENTS_BY_SENDER_TABLE)?;
+        let by_receiver = read_txn.open_multimap_table(TX_EVENTS_BY_RECEIVER_TABLE)?;
+        let table = read_txn.open_table(TX_EVENTS_TABLE)?;
+
+        // a tx to the account itself is indexed under both
+        let account = account.to_lowercase();
+        let mut sequences = BTreeSet::new();
+        for sequence in by_sender
+            .get(account.as_str())?
+            .chain(by_receiver.get(account.as_str())?)
+        {
+            sequences.insert(sequence?.value());
+        }
+        let mut events = Vec::new();
+        for sequence in sequences {
+            if let Some(value) = table.get(sequence)? {
+                events.push(
+                    TxEvent::decode(&mut &value.value()[..])
+                        .map_err(|err| anyhow!("failed to decode: {err:?}"))?,
+                );
+            }
+        }
+        Ok(events)
+    }
 
+    async fn get_payment_tx_events(&self, payment_id: H256) -> Result<Vec<TxEvent>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let by_payment = read_txn.open_multimap_table(TX_EVENTS_BY_PAYMENT_TABLE)?;
+        let table = read_txn.open_table(TX_EVENTS_TABLE)?;
+
+        let mut events = Vec::new();
+        for sequence in by_payment.get(payment_id.as_fixed_bytes())? {
+            if let Some(value) = table.get(sequence?.value())? {
+                events.push(
+                    TxEvent::decode(&mut &value.value()[..])
+                        .map_err(|err| anyhow!("failed to decode: {err:?}"))?,
+                );
+            }
+        }
+        Ok(events)
+    }
 
-    async fn set_user_account(&self, user: UserAccount) -> Result<(), anyhow::Error> {
-        self.db
-            .user_account()
-            .create(
-                user.user_name,
-                user.account_id,
-                user.network.into(),
-                Default::default(),
-            )
-            .exec()
-            .await?;
-        Ok(())
+    async fn get_approval_tx_events(&self, owner: Option<String>) -> Result<Vec<TxEvent>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let by_kind = read_txn.open_multimap_table(TX_EVENTS_BY_KIND_TABLE)?;
+        let table = read_txn.open_table(TX_EVENTS_TABLE)?;
+
+        let approval = format!("{:?}", TxKind::Approval);
+        let mut events = Vec::new();
+        for sequence in by_kind.get(approval.as_str())? {
+            let Some(value) = table.get(sequence?.value())? else {
+                continue;
+            };
+            let event = TxEvent::decode(&mut &value.value()[..])
+                .map_err(|err| anyhow!("failed to decode: {err:?}"))?;
+            let from = event.tx.sender_address.to_string();
+            if owner
+                .as_ref()
+                .map_or(true, |owner| from.eq_ignore_ascii_case(owner))
+            {
+                events.push(event);
+            }
+        }
+        Ok(events)
     }
 
-    async fn increment_nonce(&self) -> Result<(), anyhow::Error> {
-        self.db
-            .nonce()
-            .update(nonce::id::equals(1), vec![nonce::nonce::increment(1)])
-            .exec()
-            .await?;
+    async fn set_policies(&self, document: String) -> Result<(), Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(POLICY_TABLE)?;
+            table.insert(POLICY_KEY, document)?;
+        }
+        write_txn.commit()?;
         Ok(())
     }
 
-    async fn get_nonce(&self) -> Result<u32, anyhow::Error> {
-        let mut nonce = 0;
-        let nonce_data = self
-            .db
-            .nonce()
-            .find_unique(nonce::UniqueWhereParam::IdEquals(1))
-            .exec()
-            .await?;
-        if nonce_data.is_none() {
-            // create the entity
-            self.db.nonce().create(0, vec![]).exec().await?;
-        } else {
-            nonce = nonce_data.unwrap().nonce
+    async fn get_policies(&self) -> Result<Option<String>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(POLICY_TABLE)?;
+        Ok(table.get(POLICY_KEY)?.map(|value| value.value()))
+    }
+
+    async fn pair_signer(&self, account: String) -> Result<(), Error> {
+        let paired_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(PAIRED_SIGNERS_TABLE)?;
+            if table.get(account.as_str())?.is_none() {
+                table.insert(account.as_str(), paired_at)?;
+            }
         }
-        Ok(nonce as u32)
+        write_txn.commit()?;
+        Ok(())
     }
 
-    // get all related network id accounts
-    async fn get_user_accounts(
-        &self,
-        network: ChainSupported,
-    ) -> Result<Vec<user_account::Data>, anyhow::Error> {
-        let accounts = self
-            .db
-            .user_account()
-            .find_many(vec![user_account::WhereParam::NetworkId(
-                StringFilter::Equals(network.into()),
-            )])
-            .exec()
-            .await?;
-        Ok(accounts)
+    async fn unpair_signer(&self, account: String) -> Result<(), Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(PAIRED_SIGNERS_TABLE)?;
+            table.remove(account.as_str())?;
+        }
+        write_txn.commit()?;
+        Ok(())
     }
 
-    async fn update_success_tx(&self, tx_state: DbTxStateMachine) -> Result<(), anyhow::Error> {
-        let tx = self
-            .db
+    async fn get_paired_signers(&self) -> Result<Vec<String>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(PAIRED_SIGNERS_TABLE)?;
+
+        let mut accounts = Vec::new();
+        for result in table.iter()? {
+            let (account, _) = result?;
+            accounts.push(account.value().to_string());
+        }
+        Ok(accounts)
+    }
+
+    async fn record_peer_binding(&self, binding: PeerBinding) -> Result<(), Error> {
+        let key = binding.account.to_lowercase();
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(PEER_BINDINGS_TABLE)?;
+            let stored = match table.get(key.as_str())? {
+                Some(value) => Some(
+                    PeerBinding::decode(&mut &value.value()[..])
+                        .map_err(|err| anyhow!("failed to decode: {err:?}"))?,
+                ),
+                None => None,
+            };
+            if stored.map_or(true, |stored| binding.supersedes(&stored)) {
+                table.insert(key.as_str(), binding.encode())?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    async fn get_peer_bindings(&self) -> Result<Vec<PeerBinding>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(PEER_BINDINGS_TABLE)?;
+
+        let mut bindings = Vec::new();
+        for result in table.iter()? {
+            let (_, value) = result?;
+            let binding: PeerBinding = Decode::decode(&mut &value.value()[..]).map_err(|err|anyhow!("failed to decode: {err:?}"))?;
+            bindings.push(binding);
+        }
+        Ok(bindings)
+    }
+
+    async fn get_peer_binding(&self, account: String) -> Result<Option<PeerBinding>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(PEER_BINDINGS_TABLE)?;
+        match table.get(account.to_lowercase().as_str())? {
+            Some(value) => Ok(Some(
+                PeerBinding::decode(&mut &value.value()[..])
+                    .map_err(|err| anyhow!("failed to decode: {err:?}"))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_all_saved_user_peers(&self) -> Result<Vec<PeerRecord>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(SAVED_PEERS_TABLE)?;
+
+        let mut peers = Vec::new();
+        if let Some(saved_peers) = table.get(SAVED_PEERS_KEY).map_err(|err|anyhow!("failed to get saved peer records: {err:?}"))? {
+            for value in saved_peers.value() {
+                let peer: PeerRecord = Decode::decode(&mut &value[..]).map_err(|err|anyhow!("failed to decode: {err:?}"))?;
+                peers.push(peer);
+            }
+        }
+        Ok(peers)
+    }
+
+    async fn record_paired_device(&self, device: PairedDevice) -> Result<(), Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(PAIRED_DEVICES_TABLE)?;
+            table.insert(device.peer_id.as_str(), device.encode())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    async fn unpair_device(&self, peer_id: String) -> Result<(), Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(PAIRED_DEVICES_TABLE)?;
+            table.remove(peer_id.as_str())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    async fn get_paired_devices(&self) -> Result<Vec<PairedDevice>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(PAIRED_DEVICES_TABLE)?;
+
+        let mut devices = Vec::new();
+        for result in table.iter()? {
+            let (_, value) = result?;
+            let device: PairedDevice = Decode::decode(&mut &value.value()[..]).map_err(|err|anyhow!("failed to decode: {err:?}"))?;
+            devices.push(device);
+        }
+        Ok(devices)
+    }
+
+    async fn record_sync_record(&self, record: SyncRecord) -> Result<(), Error> {
+        let id = record.id();
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(SYNC_RECORDS_TABLE)?;
+            let stored = match table.get(id.as_str())? {
+                Some(value) => Some(
+                    SyncRecord::decode(&mut &value.value()[..])
+                        .map_err(|err| anyhow!("failed to decode: {err:?}"))?,
+                ),
+                None => None,
+            };
+            if stored.map_or(true, |stored| record.supersedes(&stored)) {
+                table.insert(id.as_str(), record.encode())?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    async fn get_sync_records(&self) -> Result<Vec<SyncRecord>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(SYNC_RECORDS_TABLE)?;
+
+        let mut records = Vec::new();
+        for result in table.iter()? {
+            let (_, value) = result?;
+            let record: SyncRecord = Decode::decode(&mut &value.value()[..]).map_err(|err|anyhow!("failed to decode: {err:?}"))?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    async fn rotate_user_peer_id(&self, old_peer_id: String, peer_id: String, multi_addr: String, keypair: Vec<u8>) -> Result<(), Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(USER_PEER_TABLE)?;
+            let encoded = table.get(USER_PEER_RECORD_KEY)?.map(|value| value.value());
+            if let Some(encoded) = encoded {
+                let mut peer: PeerRecord = Decode::decode(&mut &encoded[..]).map_err(|err| anyhow!("failed to decode: {err:?}"))?;
+                if peer.peer_id.as_ref() != Some(&old_peer_id) {
+                    Err(anyhow!("Peer not found"))?
+                }
+                peer.peer_id = Some(peer_id);
+                peer.multi_addr = Some(multi_addr);
+                peer.keypair = Some(keypair);
+                table.insert(USER_PEER_RECORD_KEY, peer.encode())?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    async fn rotate_saved_peer_id(&self, old_peer_id: String, peer_id: String, multi_addr: String) -> Result<(), Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(SAVED_PEERS_TABLE)?;
+            let saved_peers = table.get(SAVED_PEERS_KEY)?.map(|value| value.value()).unwrap_or_default();
+            let mut rotated = Vec::with_capacity(saved_peers.len());
+            for value in saved_peers {
+                let mut peer: PeerRecord = Decode::decode(&mut &value[..]).map_err(|err|anyhow!("failed to decode: {err:?}"))?;
+                if peer.peer_id.as_ref() == Some(&old_peer_id) {
+                    peer.peer_id = Some(peer_id.clone());
+                    peer.multi_addr = Some(multi_addr.clone());
+                }
+                rotated.push(peer.encode());
+            }
+            table.insert(SAVED_PEERS_KEY, rotated)?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    async fn record_revocation(&self, revocation: AccountRevocation) -> Result<(), Error> {
+        let key = revocation.account.to_lowercase();
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(REVOCATIONS_TABLE)?;
+            let stored = table.get(key.as_str())?.is_some();
+            if !stored {
+                table.insert(key.as_str(), revocation.encode())?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    async fn get_revocations(&self) -> Result<Vec<AccountRevocation>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(REVOCATIONS_TABLE)?;
+
+        let mut revocations = Vec::new();
+        for result in table.iter()? {
+            let (_, value) = result?;
+            let revocation: AccountRevocation = Decode::decode(&mut &value.value()[..]).map_err(|err|anyhow!("failed to decode: {err:?}"))?;
+            revocations.push(revocation);
+        }
+        Ok(revocations)
+    }
+}
+
+/// Handling connection and interaction with the local database
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone)]
+pub struct LocalDbWorker {
+    db: Arc<PrismaClient>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const SERVER_DATA_ID: i32 = 1;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DbWorkerInterface for LocalDbWorker {
+    async fn initialize_db_client(file_url: &str) -> Result<Self, anyhow::Error> {
+        let url = format!("file:{}", file_url);
+        let client = new_client_with_url(&url)
+            .await
+            .map_err(|err| anyhow!("failed to initialize db client, caused by: {err}"))?;
+
+        let client = Arc::new(client);
+
+        cfg!(feature = "e2e");
+        client._migrate_deploy().await?;
+
+        // we are initializing transaction data as all of following operations is going to be updating this storage item
+        let return_data = client
+            .transactions_data()
+            .find_first(vec![WhereParam::Id(IntFilter::Equals(1))])
+            .exec()
+            .await;
+
+        if let Ok(return_data) = return_data {
+            if let None = return_data {
+                client
+                    .transactions_data()
+                    .create(0, 0, vec![])
+                    .exec()
+                    .await?;
+            }
+        } else {
+            // create new tx data
+            if let Err(err) = client.transactions_data().create(0, 0, vec![]).exec().await {
+                error!(target:"db","failed to create new transaction data; caused by: {err}");
+            }
+        }
+        Ok(Self { db: client })
+    }
+
+
+
+    async fn set_user_account(&self, user: UserAccount) -> Result<(), anyhow::Error> {
+        self.db
+            .user_account()
+            .create(
+                user.user_name,
+                user.account_id,
+                user.network.into(),
+                Default::default(),
+            )
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn reserve_nonce(&self) -> Result<u32, anyhow::Error> {
+        // the increment is applied by sqlite in the upsert statement, not read back and written
+        let nonce = self
+            .db
+            .nonce()
+            .upsert(
+                nonce::id::equals(1),
+                nonce::create(1, vec![]),
+                vec![nonce::nonce::increment(1)],
+            )
+            .exec()
+            .await?;
+        Ok(nonce.nonce as u32)
+    }
+
+    async fn get_nonce(&self) -> Result<u32, anyhow::Error> {
+        let mut nonce = 0;
+        let nonce_data = self
+            .db
+            .nonce()
+            .find_unique(nonce::UniqueWhereParam::IdEquals(1))
+            .exec()
+            .await?;
+        if nonce_data.is_none() {
+            // create the entity
+            self.db.nonce().create(0, vec![]).exec().await?;
+        } else {
+            nonce = nonce_data.unwrap().nonce
+        }
+        Ok(nonce as u32)
+    }
+
+    // get all related network id accounts
+    async fn get_user_accounts(
+        &self,
+        network: ChainSupported,
+    ) -> Result<Vec<UserAccount>, anyhow::Error> {
+        let accounts = self
+            .db
+            .user_account()
+            .find_many(vec![user_account::WhereParam::NetworkId(
+                StringFilter::Equals(network.into()),
+            )])
+            .exec()
+            .await?;
+        Ok(accounts.into_iter().map(Into::into).collect())
+    }
+
+    async fn update_success_tx(&self, tx_state: DbTxStateMachine) -> Result<(), anyhow::Error> {
+        let tx = self
+            .db
             .transaction()
             .create(
-                tx_state.tx_hash,
+                tx_state.tx_hash.clone(),
                 tx_state.amount as i64,
                 tx_state.network.into(),
                 tx_state.success,
-                Default::default(),
+                history_params(&tx_state)?,
             )
             .exec()
             .await?;
@@ -638,11 +1372,11 @@ impl DbWorkerInterface for LocalDbWorker {
             .db
             .transaction()
             .create(
-                tx_state.tx_hash,
+                tx_state.tx_hash.clone(),
                 tx_state.amount as i64,
                 tx_state.network.into(),
                 tx_state.success,
-                Default::default(),
+                history_params(&tx_state)?,
             )
             .exec()
             .await?;
@@ -661,7 +1395,7 @@ impl DbWorkerInterface for LocalDbWorker {
         Ok(())
     }
 
-    async fn get_failed_txs(&self) -> Result<Vec<transaction::Data>, anyhow::Error> {
+    async fn get_failed_txs(&self) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
         let failed_txs = self
             .db
             .transaction()
@@ -670,10 +1404,10 @@ impl DbWorkerInterface for LocalDbWorker {
             ))])
             .exec()
             .await?;
-        Ok(failed_txs)
+        Ok(failed_txs.into_iter().map(Into::into).collect())
     }
 
-    async fn get_success_txs(&self) -> Result<Vec<transaction::Data>, anyhow::Error> {
+    async fn get_success_txs(&self) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
         let success_txs = self
             .db
             .transaction()
@@ -682,7 +1416,105 @@ impl DbWorkerInterface for LocalDbWorker {
             ))])
             .exec()
             .await?;
-        Ok(success_txs)
+        Ok(success_txs.into_iter().map(Into::into).collect())
+    }
+
+    async fn get_success_tx(
+        &self,
+        tx_hash: Vec<u8>,
+    ) -> Result<Option<DbTxStateMachine>, anyhow::Error> {
+        let success_tx = self
+            .db
+            .transaction()
+            .find_first(vec![
+                transaction::tx_hash::equals(tx_hash),
+                transaction::status::equals(true),
+            ])
+            .exec()
+            .await?;
+        Ok(success_tx.map(Into::into))
+    }
+
+    async fn get_tier_fee_txs(
+        &self,
+        network: ChainSupported,
+        tier: FeeTier,
+        limit: usize,
+    ) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        let txs = self
+            .db
+            .transaction()
+            .find_many(vec![
+                transaction::network::equals(network.into()),
+                transaction::fee_tier::equals(Some(serde_json::to_string(&tier)?)),
+                transaction::status::equals(true),
+                transaction::low_tier_fee::not(None),
+            ])
+            .order_by(transaction::id::order(Direction::Desc))
+            .take(limit as i64)
+            .exec()
+            .await?;
+        Ok(txs.into_iter().map(Into::into).collect())
+    }
+
+    async fn search_txs(
+        &self,
+        search: &HistorySearch,
+    ) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        // amounts are stored as i64, larger bounds are clamped
+        let clamp = |amount: u128| amount.min(i64::MAX as u128) as i64;
+        let mut filters = vec![];
+        if let Some(network) = search.network {
+            filters.push(transaction::network::equals(network.into()));
+        }
+        if let Some(min_amount) = search.min_amount {
+            filters.push(transaction::value::gte(clamp(min_amount)));
+        }
+        if let Some(max_amount) = search.max_amount {
+            filters.push(transaction::value::lte(clamp(max_amount)));
+        }
+        if let Some(success) = search.success {
+            filters.push(transaction::status::equals(success));
+        }
+        if let Some(address) = search.address() {
+            filters.push(prisma_client_rust::operator::or(vec![
+                transaction::sender_address::equals(address.clone()),
+                transaction::receiver_address::equals(address),
+            ]));
+        }
+        let txs = self
+            .db
+            .transaction()
+            .find_many(filters)
+            .order_by(transaction::id::order(Direction::Desc))
+            .exec()
+            .await?;
+        Ok(txs.into_iter().map(Into::into).collect())
+    }
+
+    async fn record_tx_receipt(
+        &self,
+        tx_hash: Vec<u8>,
+        receipt: ChainReceipt,
+        low_tier_fee: Option<u128>,
+    ) -> Result<(), anyhow::Error> {
+        self.db
+            .transaction()
+            .update_many(
+                vec![transaction::tx_hash::equals(tx_hash)],
+                vec![
+                    transaction::block_number::set(Some(receipt.block_number as i64)),
+                    transaction::effective_gas_price::set(
+                        receipt.effective_gas_price.map(|price| price.to_string()),
+                    ),
+                    transaction::fee_paid::set(Some(receipt.fee_paid.to_string())),
+                    transaction::reverted::set(Some(!receipt.success)),
+                    transaction::low_tier_fee::set(low_tier_fee.map(|fee| fee.to_string())),
+                ],
+            )
+            .exec()
+            .await?;
+        Ok(())
     }
 
     async fn get_total_value_success(&self) -> Result<u64, anyhow::Error> {
@@ -782,7 +1614,7 @@ impl DbWorkerInterface for LocalDbWorker {
         &self,
         account_id: Option<String>,
         peer_id: Option<String>,
-    ) -> Result<user_peer::Data, anyhow::Error> {
+    ) -> Result<PeerRecord, anyhow::Error> {
         let where_param = match (account_id, peer_id) {
             (Some(acc_id), _) => user_peer::WhereParam::AccountId1(StringFilter::Equals(acc_id)),
             (_, Some(pid)) => user_peer::WhereParam::PeerId(StringFilter::Equals(pid)),
@@ -794,6 +1626,7 @@ impl DbWorkerInterface for LocalDbWorker {
             .find_first(vec![where_param])
             .exec()
             .await?
+            .map(Into::into)
             .ok_or_else(|| anyhow!("Peer not found in DB"))
     }
 
@@ -809,14 +1642,14 @@ impl DbWorkerInterface for LocalDbWorker {
     }
 
     // get port ids
-    async fn get_ports(&self) -> Result<Option<port::Data>, anyhow::Error> {
+    async fn get_ports(&self) -> Result<Option<Ports>, anyhow::Error> {
         let ports = self
             .db
             .port()
             .find_unique(port::UniqueWhereParam::IdEquals(1))
             .exec()
             .await?;
-        Ok(ports)
+        Ok(ports.map(Into::into))
     }
 
     // saved peers interacted with
@@ -844,7 +1677,7 @@ impl DbWorkerInterface for LocalDbWorker {
     async fn get_saved_user_peers(
         &self,
         account_id: String,
-    ) -> Result<saved_peers::Data, anyhow::Error> {
+    ) -> Result<PeerRecord, anyhow::Error> {
         let peer_data = self
             .db
             .saved_peers()
@@ -854,7 +1687,776 @@ impl DbWorkerInterface for LocalDbWorker {
             .exec()
             .await?
             .ok_or(anyhow!("Peer Not found in DB"))?;
-        Ok(peer_data)
+        Ok(peer_data.into())
+    }
+
+    async fn record_tx_event(&self, event: TxEvent) -> Result<(), anyhow::Error> {
+        self.record_tx_events(vec![event]).await
+    }
+
+    async fn record_tx_events(&self, events: Vec<TxEvent>) -> Result<(), anyhow::Error> {
+        let creates = events
+            .into_iter()
+            .map(|event| {
+                self.db.tx_event().create(
+                    hex::encode(event.tx_id),
+                    format!("{:?}", event.status),
+                    event.encode(),
+                    event.recorded_at as i64,
+                    vec![
+                        tx_event::sender::set(event.tx.sender_address.to_string().to_lowercase()),
+                        tx_event::receiver::set(
+                            event.tx.receiver_address.to_string().to_lowercase(),
+                        ),
+                        tx_event::parent_payment::set(event.tx.parent_payment.map(hex::encode)),
+                        tx_event::kind::set(format!("{:?}", event.tx.kind)),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>();
+        // a batch runs in one sqlite transaction
+        self.db._batch(creates).await?;
+        Ok(())
+    }
+
+    async fn get_tx_events(&self, tx_id: H256) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let events = self
+            .db
+            .tx_event()
+            .find_many(vec![tx_event::tx_id::equals(hex::encode(tx_id))])
+            .order_by(tx_event::id::order(Direction::Asc))
+            .exec()
+            .await?;
+        events.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn get_all_tx_events(&self) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let events = self
+            .db
+            .tx_event()
+            .find_many(vec![])
+            .order_by(tx_event::id::order(Direction::Asc))
+            .exec()
+            .await?;
+        events.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn get_tx_events_since(&self, recorded_at: u64) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let events = self
+            .db
+            .tx_event()
+            .find_many(vec![tx_event::recorded_at::gte(recorded_at as i64)])
+            .order_by(tx_event::id::order(Direction::Asc))
+            .exec()
+            .await?;
+        events.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn get_sender_tx_events(
+        &self,
+        sender: String,
+        receiver: Option<String>,
+    ) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let mut filters = vec![tx_event::sender::equals(sender.to_lowercase())];
+        if let Some(receiver) = receiver {
+            filters.push(tx_event::receiver::equals(receiver.to_lowercase()));
+        }
+        let events = self
+            .db
+            .tx_event()
+            .find_many(filters)
+            .order_by(tx_event::id::order(Direction::Asc))
+            .exec()
+            .await?;
+        events.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn get_account_tx_events(&self, account: String) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let account = account.to_lowercase();
+        let events = self
+            .db
+            .tx_event()
+            .find_many(vec![prisma_client_rust::operator::or(vec![
+                tx_event::sender::equals(account.clone()),
+                tx_event::receiver::equals(account),
+            ])])
+            .order_by(tx_event::id::order(Direction::Asc))
+            .exec()
+            .await?;
+        events.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn get_payment_tx_events(&self, payment_id: H256) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let events = self
+            .db
+            .tx_event()
+            .find_many(vec![tx_event::parent_payment::equals(Some(hex::encode(
+                payment_id,
+            )))])
+            .order_by(tx_event::id::order(Direction::Asc))
+            .exec()
+            .await?;
+        events.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn get_approval_tx_events(
+        &self,
+        owner: Option<String>,
+    ) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let mut filters = vec![tx_event::kind::equals(format!("{:?}", TxKind::Approval))];
+        if let Some(owner) = owner {
+            filters.push(tx_event::sender::equals(owner.to_lowercase()));
+        }
+        let events = self
+            .db
+            .tx_event()
+            .find_many(filters)
+            .order_by(tx_event::id::order(Direction::Asc))
+            .exec()
+            .await?;
+        events.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn set_policies(&self, document: String) -> Result<(), anyhow::Error> {
+        let updated_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        self.db
+            .policy()
+            .create(document, updated_at as i64, Default::default())
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn get_policies(&self) -> Result<Option<String>, anyhow::Error> {
+        let policy = self
+            .db
+            .policy()
+            .find_first(vec![])
+            .order_by(policy::id::order(Direction::Desc))
+            .exec()
+            .await?;
+        Ok(policy.map(|policy| policy.document))
+    }
+
+    async fn pair_signer(&self, account: String) -> Result<(), anyhow::Error> {
+        let paired_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        self.db
+            .paired_signer()
+            .upsert(
+                paired_signer::account::equals(account.clone()),
+                paired_signer::create(account, paired_at as i64, vec![]),
+                vec![],
+            )
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn unpair_signer(&self, account: String) -> Result<(), anyhow::Error> {
+        self.db
+            .paired_signer()
+            .delete_many(vec![paired_signer::account::equals(account)])
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn get_paired_signers(&self) -> Result<Vec<String>, anyhow::Error> {
+        let signers = self
+            .db
+            .paired_signer()
+            .find_many(vec![])
+            .order_by(paired_signer::paired_at::order(Direction::Asc))
+            .exec()
+            .await?;
+        Ok(signers.into_iter().map(|signer| signer.account).collect())
+    }
+
+    async fn record_peer_binding(&self, binding: PeerBinding) -> Result<(), anyhow::Error> {
+        let account = binding.account.clone();
+        let stored = self
+            .db
+            .peer_binding()
+            .find_unique(peer_binding::account::equals(account.clone()))
+            .exec()
+            .await?;
+        if stored.is_some_and(|stored| PeerBinding::from(stored).signed_at >= binding.signed_at) {
+            return Ok(());
+        }
+        self.db
+            .peer_binding()
+            .upsert(
+                peer_binding::account::equals(account.clone()),
+                peer_binding::create(
+                    account,
+                    binding.peer_id.clone(),
+                    binding.multi_addr.clone(),
+                    binding.signed_at as i64,
+                    binding.signature.clone(),
+                    vec![],
+                ),
+                vec![
+                    peer_binding::peer_id::set(binding.peer_id),
+                    peer_binding::multi_addr::set(binding.multi_addr),
+                    peer_binding::signed_at::set(binding.signed_at as i64),
+                    peer_binding::signature::set(binding.signature),
+                ],
+            )
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn get_peer_bindings(&self) -> Result<Vec<PeerBinding>, anyhow::Error> {
+        let bindings = self
+            .db
+            .peer_binding()
+            .find_many(vec![])
+            .order_by(peer_binding::signed_at::order(Direction::Desc))
+            .exec()
+            .await?;
+        Ok(bindings.into_iter().map(Into::into).collect())
+    }
+
+    async fn get_peer_binding(
+        &self,
+        account: String,
+    ) -> Result<Option<PeerBinding>, anyhow::Error> {
+        let binding = self
+            .db
+            .peer_binding()
+            .find_unique(peer_binding::account::equals(account))
+            .exec()
+            .await?;
+        Ok(binding.map(Into::into))
+    }
+
+    async fn get_all_saved_user_peers(&self) -> Result<Vec<PeerRecord>, anyhow::Error> {
+        let peers = self.db.saved_peers().find_many(vec![]).exec().await?;
+        Ok(peers.into_iter().map(Into::into).collect())
+    }
+
+    async fn record_paired_device(&self, device: PairedDevice) -> Result<(), anyhow::Error> {
+        self.db
+            .paired_device()
+            .upsert(
+                paired_device::peer_id::equals(device.peer_id.clone()),
+                paired_device::create(
+                    device.peer_id,
+                    device.multi_addr.clone(),
+                    device.encryption_key.to_vec(),
+                    device.paired_at as i64,
+                    vec![],
+                ),
+                vec![
+                    paired_device::multi_addr::set(device.multi_addr),
+                    paired_device::encryption_key::set(device.encryption_key.to_vec()),
+                    paired_device::paired_at::set(device.paired_at as i64),
+                ],
+            )
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn unpair_device(&self, peer_id: String) -> Result<(), anyhow::Error> {
+        self.db
+            .paired_device()
+            .delete_many(vec![paired_device::peer_id::equals(peer_id)])
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn get_paired_devices(&self) -> Result<Vec<PairedDevice>, anyhow::Error> {
+        let devices = self
+            .db
+            .paired_device()
+            .find_many(vec![])
+            .order_by(paired_device::paired_at::order(Direction::Asc))
+            .exec()
+            .await?;
+        devices.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn record_sync_record(&self, record: SyncRecord) -> Result<(), anyhow::Error> {
+        let id = record.id();
+        let stored = self
+            .db
+            .sync_record()
+            .find_unique(sync_record::id::equals(id.clone()))
+            .exec()
+            .await?;
+        if stored.is_some_and(|stored| stored.updated_at as u64 >= record.updated_at) {
+            return Ok(());
+        }
+        self.db
+            .sync_record()
+            .upsert(
+                sync_record::id::equals(id.clone()),
+                sync_record::create(
+                    id,
+                    format!("{:?}", record.kind),
+                    record.key.clone(),
+                    record.value.clone(),
+                    record.updated_at as i64,
+                    vec![],
+                ),
+                vec![
+                    sync_record::value::set(record.value),
+                    sync_record::updated_at::set(record.updated_at as i64),
+                ],
+            )
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn get_sync_records(&self) -> Result<Vec<SyncRecord>, anyhow::Error> {
+        let records = self.db.sync_record().find_many(vec![]).exec().await?;
+        records.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn rotate_user_peer_id(
+        &self,
+        old_peer_id: String,
+        peer_id: String,
+        multi_addr: String,
+        keypair: Vec<u8>,
+    ) -> Result<(), anyhow::Error> {
+        self.db
+            .user_peer()
+            .update(
+                user_peer::peer_id::equals(old_peer_id),
+                vec![
+                    user_peer::peer_id::set(peer_id),
+                    user_peer::multi_addr::set(multi_addr),
+                    user_peer::keypair::set(keypair),
+                ],
+            )
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn rotate_saved_peer_id(
+        &self,
+        old_peer_id: String,
+        peer_id: String,
+        multi_addr: String,
+    ) -> Result<(), anyhow::Error> {
+        self.db
+            .saved_peers()
+            .update_many(
+                vec![saved_peers::node_id::equals(old_peer_id)],
+                vec![
+                    saved_peers::node_id::set(peer_id),
+                    saved_peers::multi_addr::set(multi_addr),
+                ],
+            )
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn record_revocation(&self, revocation: AccountRevocation) -> Result<(), anyhow::Error> {
+        let account = revocation.account.to_lowercase();
+        let stored = self
+            .db
+            .account_revocation()
+            .find_unique(account_revocation::account::equals(account.clone()))
+            .exec()
+            .await?;
+        if stored.is_some() {
+            return Ok(());
+        }
+        self.db
+            .account_revocation()
+            .create(
+                account,
+                revocation.account,
+                revocation.revoked_at as i64,
+                revocation.signature,
+                vec![],
+            )
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn get_revocations(&self) -> Result<Vec<AccountRevocation>, anyhow::Error> {
+        let revocations = self
+            .db
+            .account_revocation()
+            .find_many(vec![])
+            .exec()
+            .await?;
+        Ok(revocations.into_iter().map(Into::into).collect())
+    }
+}
+
+/// db url selecting the in-memory db instead of a sqlite file
+pub const IN_MEMORY_DB_URL: &str = ":memory:";
+
+/// db used by the node, the local sqlite db or the in-memory db when initialized with `IN_MEMORY_DB_URL`
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone)]
+pub struct DbWorker {
+    backend: DbBackend,
+    /// seals the sensitive tx fields before they are stored, they are stored as they are without it
+    storage_key: Option<StorageKey>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone)]
+enum DbBackend {
+    Local(LocalDbWorker),
+    InMemory(InMemoryDbWorker),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DbWorker {
+    /// seal call payloads, signed call payloads and memos with `key` at rest
+    pub fn with_storage_key(mut self, key: StorageKey) -> Self {
+        self.storage_key = Some(key);
+        self
+    }
+
+    pub fn storage_key(&self) -> Option<&StorageKey> {
+        self.storage_key.as_ref()
+    }
+
+    fn open_events(&self, events: Vec<TxEvent>) -> Result<Vec<TxEvent>, anyhow::Error> {
+        match &self.storage_key {
+            Some(key) => events
+                .into_iter()
+                .map(|event| key.open_event(event))
+                .collect(),
+            None => Ok(events),
+        }
+    }
+
+    fn open_history(
+        &self,
+        txs: Vec<DbTxStateMachine>,
+    ) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        match &self.storage_key {
+            Some(key) => txs.into_iter().map(|tx| key.open_history(tx)).collect(),
+            None => Ok(txs),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! dispatch {
+    ($self:ident.$method:ident($($arg:expr),*)) => {
+        match &$self.backend {
+            DbBackend::Local(db) => db.$method($($arg),*).await,
+            DbBackend::InMemory(db) => db.$method($($arg),*).await,
+        }
+    };
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DbWorkerInterface for DbWorker {
+    async fn initialize_db_client(file_url: &str) -> Result<Self, anyhow::Error> {
+        let backend = if file_url == IN_MEMORY_DB_URL {
+            DbBackend::InMemory(InMemoryDbWorker::initialize_db_client(file_url).await?)
+        } else {
+            DbBackend::Local(LocalDbWorker::initialize_db_client(file_url).await?)
+        };
+        Ok(DbWorker {
+            backend,
+            storage_key: None,
+        })
+    }
+
+    async fn set_user_account(&self, user: UserAccount) -> Result<(), anyhow::Error> {
+        dispatch!(self.set_user_account(user))
+    }
+
+    async fn get_nonce(&self) -> Result<u32, anyhow::Error> {
+        dispatch!(self.get_nonce())
+    }
+
+    async fn get_user_accounts(
+        &self,
+        network: ChainSupported,
+    ) -> Result<Vec<UserAccount>, anyhow::Error> {
+        dispatch!(self.get_user_accounts(network))
+    }
+
+    async fn update_success_tx(&self, tx_state: DbTxStateMachine) -> Result<(), anyhow::Error> {
+        let tx_state = match &self.storage_key {
+            Some(key) => key.seal_history(tx_state)?,
+            None => tx_state,
+        };
+        dispatch!(self.update_success_tx(tx_state))
+    }
+
+    async fn update_failed_tx(&self, tx_state: DbTxStateMachine) -> Result<(), anyhow::Error> {
+        let tx_state = match &self.storage_key {
+            Some(key) => key.seal_history(tx_state)?,
+            None => tx_state,
+        };
+        dispatch!(self.update_failed_tx(tx_state))
+    }
+
+    async fn get_failed_txs(&self) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        let txs = dispatch!(self.get_failed_txs())?;
+        self.open_history(txs)
+    }
+
+    async fn get_total_value_success(&self) -> Result<u64, anyhow::Error> {
+        dispatch!(self.get_total_value_success())
+    }
+
+    async fn get_total_value_failed(&self) -> Result<u64, anyhow::Error> {
+        dispatch!(self.get_total_value_failed())
+    }
+
+    async fn record_user_peer_id(&self, peer_record: PeerRecord) -> Result<(), anyhow::Error> {
+        dispatch!(self.record_user_peer_id(peer_record))
+    }
+
+    async fn update_user_peer_id_accounts(
+        &self,
+        peer_record: PeerRecord,
+    ) -> Result<(), anyhow::Error> {
+        dispatch!(self.update_user_peer_id_accounts(peer_record))
+    }
+
+    async fn get_success_txs(&self) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        let txs = dispatch!(self.get_success_txs())?;
+        self.open_history(txs)
+    }
+
+    async fn get_success_tx(
+        &self,
+        tx_hash: Vec<u8>,
+    ) -> Result<Option<DbTxStateMachine>, anyhow::Error> {
+        let tx = dispatch!(self.get_success_tx(tx_hash))?;
+        Ok(self.open_history(tx.into_iter().collect())?.pop())
+    }
+
+    async fn get_tier_fee_txs(
+        &self,
+        network: ChainSupported,
+        tier: FeeTier,
+        limit: usize,
+    ) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        let txs = dispatch!(self.get_tier_fee_txs(network, tier, limit))?;
+        self.open_history(txs)
+    }
+
+    async fn search_txs(
+        &self,
+        search: &HistorySearch,
+    ) -> Result<Vec<DbTxStateMachine>, anyhow::Error> {
+        let txs = dispatch!(self.search_txs(search))?;
+        let mut txs = self.open_history(txs)?;
+        txs.retain(|tx| search.matches(tx));
+        txs.truncate(search.limit());
+        Ok(txs)
+    }
+
+    async fn record_tx_receipt(
+        &self,
+        tx_hash: Vec<u8>,
+        receipt: ChainReceipt,
+        low_tier_fee: Option<u128>,
+    ) -> Result<(), anyhow::Error> {
+        dispatch!(self.record_tx_receipt(tx_hash, receipt, low_tier_fee))
+    }
+
+    async fn get_user_peer_id(
+        &self,
+        account_id: Option<String>,
+        peer_id: Option<String>,
+    ) -> Result<PeerRecord, anyhow::Error> {
+        dispatch!(self.get_user_peer_id(account_id, peer_id))
+    }
+
+    async fn reserve_nonce(&self) -> Result<u32, anyhow::Error> {
+        dispatch!(self.reserve_nonce())
+    }
+
+    async fn set_ports(&self, rpc: u16, p2p: u16) -> Result<(), anyhow::Error> {
+        dispatch!(self.set_ports(rpc, p2p))
+    }
+
+    async fn get_ports(&self) -> Result<Option<Ports>, anyhow::Error> {
+        dispatch!(self.get_ports())
+    }
+
+    async fn record_saved_user_peers(&self, peer_record: PeerRecord) -> Result<(), anyhow::Error> {
+        dispatch!(self.record_saved_user_peers(peer_record))
+    }
+
+    async fn get_saved_user_peers(&self, account_id: String) -> Result<PeerRecord, anyhow::Error> {
+        dispatch!(self.get_saved_user_peers(account_id))
+    }
+
+    async fn record_tx_event(&self, event: TxEvent) -> Result<(), anyhow::Error> {
+        let event = match &self.storage_key {
+            Some(key) => key.seal_event(event)?,
+            None => event,
+        };
+        dispatch!(self.record_tx_event(event))
+    }
+
+    async fn record_tx_events(&self, events: Vec<TxEvent>) -> Result<(), anyhow::Error> {
+        let events = match &self.storage_key {
+            Some(key) => events
+                .into_iter()
+                .map(|event| key.seal_event(event))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => events,
+        };
+        dispatch!(self.record_tx_events(events))
+    }
+
+    async fn get_tx_events(&self, tx_id: H256) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let events = dispatch!(self.get_tx_events(tx_id))?;
+        self.open_events(events)
+    }
+
+    async fn get_all_tx_events(&self) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let events = dispatch!(self.get_all_tx_events())?;
+        self.open_events(events)
+    }
+
+    async fn get_tx_events_since(&self, recorded_at: u64) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let events = dispatch!(self.get_tx_events_since(recorded_at))?;
+        self.open_events(events)
+    }
+
+    async fn get_sender_tx_events(
+        &self,
+        sender: String,
+        receiver: Option<String>,
+    ) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let events = dispatch!(self.get_sender_tx_events(sender, receiver))?;
+        self.open_events(events)
+    }
+
+    async fn get_account_tx_events(&self, account: String) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let events = dispatch!(self.get_account_tx_events(account))?;
+        self.open_events(events)
+    }
+
+    async fn get_payment_tx_events(&self, payment_id: H256) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let events = dispatch!(self.get_payment_tx_events(payment_id))?;
+        self.open_events(events)
+    }
+
+    async fn get_approval_tx_events(
+        &self,
+        owner: Option<String>,
+    ) -> Result<Vec<TxEvent>, anyhow::Error> {
+        let events = dispatch!(self.get_approval_tx_events(owner))?;
+        self.open_events(events)
+    }
+
+    async fn set_policies(&self, document: String) -> Result<(), anyhow::Error> {
+        dispatch!(self.set_policies(document))
+    }
+
+    async fn pair_signer(&self, account: String) -> Result<(), anyhow::Error> {
+        dispatch!(self.pair_signer(account))
+    }
+
+    async fn unpair_signer(&self, account: String) -> Result<(), anyhow::Error> {
+        dispatch!(self.unpair_signer(account))
+    }
+
+    async fn get_paired_signers(&self) -> Result<Vec<String>, anyhow::Error> {
+        dispatch!(self.get_paired_signers())
+    }
+
+    async fn get_policies(&self) -> Result<Option<String>, anyhow::Error> {
+        dispatch!(self.get_policies())
+    }
+
+    async fn record_peer_binding(&self, binding: PeerBinding) -> Result<(), anyhow::Error> {
+        dispatch!(self.record_peer_binding(binding))
+    }
+
+    async fn get_peer_bindings(&self) -> Result<Vec<PeerBinding>, anyhow::Error> {
+        dispatch!(self.get_peer_bindings())
+    }
+
+    async fn get_peer_binding(
+        &self,
+        account: String,
+    ) -> Result<Option<PeerBinding>, anyhow::Error> {
+        dispatch!(self.get_peer_binding(account))
+    }
+
+    async fn get_all_saved_user_peers(&self) -> Result<Vec<PeerRecord>, anyhow::Error> {
+        dispatch!(self.get_all_saved_user_peers())
+    }
+
+    async fn record_paired_device(&self, device: PairedDevice) -> Result<(), anyhow::Error> {
+        dispatch!(self.record_paired_device(device))
+    }
+
+    async fn unpair_device(&self, peer_id: String) -> Result<(), anyhow::Error> {
+        dispatch!(self.unpair_device(peer_id))
+    }
+
+    async fn get_paired_devices(&self) -> Result<Vec<PairedDevice>, anyhow::Error> {
+        dispatch!(self.get_paired_devices())
+    }
+
+    async fn record_sync_record(&self, record: SyncRecord) -> Result<(), anyhow::Error> {
+        let record = match &self.storage_key {
+            Some(key) => key.seal_sync_record(record)?,
+            None => record,
+        };
+        dispatch!(self.record_sync_record(record))
+    }
+
+    async fn get_sync_records(&self) -> Result<Vec<SyncRecord>, anyhow::Error> {
+        let records = dispatch!(self.get_sync_records())?;
+        match &self.storage_key {
+            Some(key) => records
+                .into_iter()
+                .map(|record| key.open_sync_record(record))
+                .collect(),
+            None => Ok(records),
+        }
+    }
+
+    async fn rotate_user_peer_id(
+        &self,
+        old_peer_id: String,
+        peer_id: String,
+        multi_addr: String,
+        keypair: Vec<u8>,
+    ) -> Result<(), anyhow::Error> {
+        dispatch!(self.rotate_user_peer_id(old_peer_id, peer_id, multi_addr, keypair))
+    }
+
+    async fn rotate_saved_peer_id(
+        &self,
+        old_peer_id: String,
+        peer_id: String,
+        multi_addr: String,
+    ) -> Result<(), anyhow::Error> {
+        dispatch!(self.rotate_saved_peer_id(old_peer_id, peer_id, multi_addr))
+    }
+
+    async fn record_revocation(&self, revocation: AccountRevocation) -> Result<(), anyhow::Error> {
+        dispatch!(self.record_revocation(revocation))
+    }
+
+    async fn get_revocations(&self) -> Result<Vec<AccountRevocation>, anyhow::Error> {
+        dispatch!(self.get_revocations())
     }
 }
 
@@ -891,6 +2493,40 @@ impl From<saved_peers::Data> for PeerRecord {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+impl From<peer_binding::Data> for PeerBinding {
+    fn from(value: peer_binding::Data) -> Self {
+        Self {
+            account: value.account,
+            peer_id: value.peer_id,
+            multi_addr: value.multi_addr,
+            signed_at: value.signed_at as u64,
+            signature: value.signature,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<account_revocation::Data> for AccountRevocation {
+    fn from(value: account_revocation::Data) -> Self {
+        Self {
+            account: value.revoked_account,
+            revoked_at: value.revoked_at as u64,
+            signature: value.signature,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<port::Data> for Ports {
+    fn from(value: port::Data) -> Self {
+        Self {
+            rpc_port: value.rpc_port as u16,
+            p_2_p_port: value.p_2_p_port as u16,
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 impl From<user_account::Data> for UserAccount {
     fn from(value: user_account::Data) -> Self {
@@ -913,6 +2549,117 @@ impl From<transaction::Data> for DbTxStateMachine {
                 .expect("failed to convert u128 to u64"),
             network: ChainSupported::from(value.network.as_str()),
             success: value.status,
+            memo: value.memo,
+            reference: value.reference,
+            tags: serde_json::from_str(&value.tags).unwrap_or_default(),
+            sender_address: value.sender_address,
+            receiver_address: value.receiver_address,
+            sender_name: value.sender_name,
+            explorer_url: value.explorer_url,
+            receipt: value.block_number.map(|block_number| ChainReceipt {
+                block_number: block_number as u64,
+                effective_gas_price: value
+                    .effective_gas_price
+                    .and_then(|price| price.parse().ok()),
+                fee_paid: value
+                    .fee_paid
+                    .and_then(|fee| fee.parse().ok())
+                    .unwrap_or_default(),
+                success: value.reverted != Some(true),
+            }),
+            fee_tier: value
+                .fee_tier
+                .and_then(|tier| serde_json::from_str(&tier).ok()),
+            low_tier_fee: value.low_tier_fee.and_then(|fee| fee.parse().ok()),
+            reject_reason: value
+                .reject_reason
+                .and_then(|reason| serde_json::from_str(&reason).ok()),
+            sponsorship: value
+                .sponsorship
+                .and_then(|sponsorship| serde_json::from_str(&sponsorship).ok()),
+            // opened from the tx event log when a receipt is exported
+            travel_rule: None,
         }
     }
 }
+
+/// reconciliation and search columns of a history record
+#[cfg(not(target_arch = "wasm32"))]
+fn history_params(
+    tx_state: &DbTxStateMachine,
+) -> Result<Vec<transaction::SetParam>, anyhow::Error> {
+    Ok(vec![
+        transaction::memo::set(tx_state.memo.clone()),
+        transaction::reference::set(tx_state.reference.clone()),
+        transaction::tags::set(serde_json::to_string(&tx_state.tags)?),
+        transaction::sender_address::set(tx_state.sender_address.clone()),
+        transaction::receiver_address::set(tx_state.receiver_address.clone()),
+        transaction::sender_name::set(tx_state.sender_name.clone()),
+        transaction::explorer_url::set(tx_state.explorer_url.clone()),
+        transaction::fee_tier::set(
+            tx_state
+                .fee_tier
+                .map(|tier| serde_json::to_string(&tier))
+                .transpose()?,
+        ),
+        transaction::reject_reason::set(
+            tx_state
+                .reject_reason
+                .map(|reason| serde_json::to_string(&reason))
+                .transpose()?,
+        ),
+        transaction::sponsorship::set(
+            tx_state
+                .sponsorship
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+        ),
+    ])
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TryFrom<tx_event::Data> for TxEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(value: tx_event::Data) -> Result<Self, Self::Error> {
+        Decode::decode(&mut &value.payload[..])
+            .map_err(|err| anyhow!("failed to decode tx event {}: {err:?}", value.id))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TryFrom<paired_device::Data> for PairedDevice {
+    type Error = anyhow::Error;
+
+    fn try_from(value: paired_device::Data) -> Result<Self, Self::Error> {
+        Ok(Self {
+            encryption_key: value.encryption_key.try_into().map_err(|_| {
+                anyhow!("invalid encryption key of paired device {}", value.peer_id)
+            })?,
+            peer_id: value.peer_id,
+            multi_addr: value.multi_addr,
+            paired_at: value.paired_at as u64,
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TryFrom<sync_record::Data> for SyncRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(value: sync_record::Data) -> Result<Self, Self::Error> {
+        let kind = match value.kind.as_str() {
+            "Contact" => SyncKind::Contact,
+            "Policies" => SyncKind::Policies,
+            "PendingTransfers" => SyncKind::PendingTransfers,
+            kind => Err(anyhow!("unknown kind {kind} of sync record {}", value.id))?,
+        };
+        Ok(Self {
+            kind,
+            key: value.key,
+            value: value.value,
+            updated_at: value.updated_at as u64,
+        })
+    }
+}
@@ -0,0 +1,335 @@
+//! Native bindings for embedding a vane node in mobile wallets
+//! exposes node startup, the send flow and attestation callbacks through UniFFI,
+//! kotlin and swift bindings are generated with the bundled `uniffi-bindgen` binary
+
+use jsonrpsee::core::Error as RpcError;
+use jsonrpsee::types::error::CallError;
+use node::rpc::{InitiateTransactionParams, TransactionRpcServer};
+use node::{MainServiceWorker, MainServiceWorkerBuilder, NodeHandle};
+use primitives::attestations::VerificationBadge;
+use primitives::data_structure::{ChainSupported, TxStateMachine, TxStatus};
+use primitives::errors::INTERNAL_ERROR_CODE;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use tokio::runtime::Runtime;
+
+uniffi::setup_scaffolding!();
+
+/// errors surfaced to the host app, `code` matches the node rpc error codes
+#[derive(Debug, uniffi::Error)]
+pub enum VaneFfiError {
+    Node { code: i32, message: String },
+}
+
+impl Display for VaneFfiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VaneFfiError::Node { code, message } => write!(f, "vane error {code}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for VaneFfiError {}
+
+impl From<anyhow::Error> for VaneFfiError {
+    fn from(value: anyhow::Error) -> Self {
+        VaneFfiError::Node {
+            code: INTERNAL_ERROR_CODE,
+            message: value.to_string(),
+        }
+    }
+}
+
+impl From<RpcError> for VaneFfiError {
+    fn from(value: RpcError) -> Self {
+        match value {
+            RpcError::Call(CallError::Custom(err_obj)) => VaneFfiError::Node {
+                code: err_obj.code(),
+                message: err_obj.message().to_string(),
+            },
+            other => VaneFfiError::Node {
+                code: INTERNAL_ERROR_CODE,
+                message: other.to_string(),
+            },
+        }
+    }
+}
+
+impl From<std::io::Error> for VaneFfiError {
+    fn from(value: std::io::Error) -> Self {
+        anyhow::Error::from(value).into()
+    }
+}
+
+/// node startup configuration
+#[derive(uniffi::Record)]
+pub struct NodeConfig {
+    /// sqlite db file path inside the app sandbox
+    pub db_path: String,
+    /// rpc port, `None` loads the persisted port or assigns a random one
+    pub rpc_port: Option<u16>,
+    /// p2p port, only used together with `rpc_port`
+    pub p2p_port: Option<u16>,
+    /// enabled networks (`Polkadot`, `Ethereum`, `Bnb`, `Solana`), empty enables all
+    pub chains: Vec<String>,
+}
+
+/// tx state update delivered to the host app
+#[derive(uniffi::Record)]
+pub struct TxUpdate {
+    pub tx_id: u32,
+    pub status: String,
+    pub sender_address: String,
+    pub receiver_address: String,
+    pub network: String,
+    /// amount in the token smallest unit, decimal string as u128 does not cross the ffi boundary
+    pub amount: String,
+    /// hash to be signed by the sender once the receiver attested
+    pub call_payload: Option<Vec<u8>>,
+    /// safety warnings as `CODE: message`, to be shown before the sender confirms
+    pub warnings: Vec<String>,
+    /// a warning is high severity, `sender_confirm` needs `override_warnings`
+    pub requires_override: bool,
+    /// message the second approver signs, set while the tx waits for it
+    pub approval_message: Option<String>,
+    /// display name the sender attached, only to be shown as the sender identity when verified
+    pub sender_name: Option<String>,
+    /// the sender proved it owns the sender address
+    pub sender_verified: bool,
+    /// txs from the sender this node attested before
+    pub sender_past_interactions: u32,
+    /// trusted third party attestations of the receiver as `issuer: claim`, for the sender
+    pub receiver_badges: Vec<String>,
+    /// trusted third party attestations of the sender as `issuer: claim`, for the receiver
+    pub sender_badges: Vec<String>,
+}
+
+impl From<TxStateMachine> for TxUpdate {
+    fn from(tx: TxStateMachine) -> Self {
+        let approval_message =
+            (tx.status == TxStatus::AwaitingSecondApproval).then(|| tx.second_approval_message());
+        let profile = tx.sender_profile.clone().unwrap_or_default();
+        Self {
+            tx_id: tx.tx_nonce,
+            status: format!("{:?}", tx.status),
+            sender_address: tx.sender_address.to_string(),
+            receiver_address: tx.receiver_address.to_string(),
+            network: tx.network.into(),
+            amount: tx.amount.to_string(),
+            call_payload: tx.call_payload.map(|payload| payload.to_vec()),
+            approval_message,
+            sender_name: tx.sender_profile.map(|profile| profile.display_name),
+            sender_verified: profile.verified,
+            sender_past_interactions: profile.past_interactions,
+            receiver_badges: tx.receiver_badges.iter().map(badge_label).collect(),
+            sender_badges: tx.sender_badges.iter().map(badge_label).collect(),
+            requires_override: tx
+                .warnings
+                .iter()
+                .any(|warning| warning.requires_override()),
+            warnings: tx
+                .warnings
+                .iter()
+                .map(|warning| format!("{}: {}", warning.code, warning.message))
+                .collect(),
+        }
+    }
+}
+
+fn badge_label(badge: &VerificationBadge) -> String {
+    format!("{}: {}", badge.issuer_name, badge.claim)
+}
+
+/// implemented by the host app to receive tx updates, e.g. to prompt the user for an attestation
+#[uniffi::export(with_foreign)]
+pub trait TxUpdateListener: Send + Sync {
+    fn on_tx_update(&self, update: TxUpdate);
+}
+
+/// a vane node running on its own tokio runtime inside the host app
+#[derive(uniffi::Object)]
+pub struct VaneNode {
+    runtime: Runtime,
+    worker: MainServiceWorker,
+    handle: Mutex<Option<NodeHandle>>,
+}
+
+impl VaneNode {
+    async fn pending_tx(&self, tx_id: u32) -> Result<TxStateMachine, VaneFfiError> {
+        self.worker
+            .moka_cache
+            .get(&u64::from(tx_id))
+            .await
+            .ok_or(VaneFfiError::Node {
+                code: primitives::errors::ATTESTATION_EXPIRED_CODE,
+                message: format!("no pending tx with id {tx_id}"),
+            })
+    }
+}
+
+#[uniffi::export]
+impl VaneNode {
+    /// build and start the node, tx updates are delivered to `listener`
+    #[uniffi::constructor]
+    pub fn start(
+        config: NodeConfig,
+        listener: Arc<dyn TxUpdateListener>,
+    ) -> Result<Arc<Self>, VaneFfiError> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("vane-node")
+            .build()?;
+
+        let mut builder = MainServiceWorkerBuilder::new().db_url(config.db_path);
+        if let (Some(rpc_port), Some(p2p_port)) = (config.rpc_port, config.p2p_port) {
+            builder = builder.ports(rpc_port, p2p_port);
+        }
+        if !config.chains.is_empty() {
+            let chains = config
+                .chains
+                .iter()
+                .map(|chain| match chain.as_str() {
+                    "Polkadot" | "Ethereum" | "Bnb" | "Solana" => {
+                        Ok(ChainSupported::from(chain.as_str()))
+                    }
+                    other => Err(VaneFfiError::Node {
+                        code: primitives::errors::UNSUPPORTED_NETWORK_CODE,
+                        message: format!("network not supported: {other}"),
+                    }),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            builder = builder.chains(chains);
+        }
+
+        let handle = runtime.block_on(async { builder.build().await?.start().await })?;
+        let worker = handle.worker.clone();
+
+        // forward tx updates to the host app, this consumes the updates otherwise streamed by `subscribeTxUpdates`
+        let rpc_receiver = runtime.block_on(async {
+            worker
+                .tx_rpc_worker
+                .lock()
+                .await
+                .rpc_receiver_channel
+                .clone()
+        });
+        runtime.spawn(async move {
+            while let Some(tx) = rpc_receiver.lock().await.recv().await {
+                listener.on_tx_update(tx.into());
+            }
+        });
+
+        Ok(Arc::new(Self {
+            runtime,
+            worker,
+            handle: Mutex::new(Some(handle)),
+        }))
+    }
+
+    /// rpc address the node is listening on, for tooling and remote clients
+    pub fn rpc_address(&self) -> Option<String> {
+        self.handle
+            .lock()
+            .ok()?
+            .as_ref()
+            .map(|handle| handle.rpc_address.to_string())
+    }
+
+    /// register the user account to vane discovery
+    pub fn register(
+        &self,
+        name: String,
+        account_id: String,
+        network: String,
+    ) -> Result<(), VaneFfiError> {
+        self.runtime.block_on(async {
+            let rpc_worker = self.worker.tx_rpc_worker.lock().await.clone();
+            Ok(rpc_worker
+                .register_vane_web3(name, account_id, network)
+                .await?)
+        })
+    }
+
+    /// initiate a transfer, `amount` is a decimal string in the token smallest unit
+    pub fn send(
+        &self,
+        sender: String,
+        receiver: String,
+        amount: String,
+        token: String,
+        network: String,
+    ) -> Result<(), VaneFfiError> {
+        let amount = amount
+            .parse::<u128>()
+            .map_err(|err| anyhow::anyhow!("invalid amount: {amount}; caused by: {err}"))?;
+        self.runtime.block_on(async {
+            let rpc_worker = self.worker.tx_rpc_worker.lock().await.clone();
+            Ok(rpc_worker
+                .initiate(InitiateTransactionParams {
+                    sender,
+                    receiver,
+                    amount,
+                    token,
+                    network,
+                    ..Default::default()
+                })
+                .await
+                .map(|_tx_id| ())?)
+        })
+    }
+
+    /// receiver attestation, `signature` is the receiver signature over its own address
+    pub fn receiver_confirm(&self, tx_id: u32, signature: Vec<u8>) -> Result<(), VaneFfiError> {
+        self.runtime.block_on(async {
+            let mut tx = self.pending_tx(tx_id).await?;
+            tx.recv_signature = Some(signature);
+            let rpc_worker = self.worker.tx_rpc_worker.lock().await.clone();
+            Ok(rpc_worker.receiver_confirm(tx).await?)
+        })
+    }
+
+    /// sender confirmation, `signature` is the sender signature over the tx call payload
+    pub fn sender_confirm(
+        &self,
+        tx_id: u32,
+        signature: Vec<u8>,
+        override_warnings: bool,
+    ) -> Result<(), VaneFfiError> {
+        self.runtime.block_on(async {
+            let mut tx = self.pending_tx(tx_id).await?;
+            tx.signed_call_payload = Some(signature);
+            let rpc_worker = self.worker.tx_rpc_worker.lock().await.clone();
+            Ok(rpc_worker
+                .sender_confirm(tx, Some(override_warnings))
+                .await?)
+        })
+    }
+
+    /// second approver sign-off, `signature` is the approver EIP-191 signature over the tx second
+    /// approval message
+    pub fn approver_confirm(&self, tx_id: u32, signature: Vec<u8>) -> Result<(), VaneFfiError> {
+        self.runtime.block_on(async {
+            let tx = self.pending_tx(tx_id).await?;
+            let rpc_worker = self.worker.tx_rpc_worker.lock().await.clone();
+            Ok(rpc_worker.approver_confirm(tx, signature).await?)
+        })
+    }
+
+    /// txs waiting on an attestation or confirmation
+    pub fn pending(&self) -> Vec<TxUpdate> {
+        self.worker
+            .moka_cache
+            .iter()
+            .map(|(_, tx)| tx.into())
+            .collect()
+    }
+
+    /// stop the node tasks
+    pub fn stop(&self) {
+        if let Ok(mut handle) = self.handle.lock() {
+            if let Some(handle) = handle.take() {
+                handle.stop();
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// local anvil devnet for e2e tests against a real evm node
+// requires `anvil` (foundry) on PATH, the process is killed when the instance is dropped
+
+use alloy::signers::local::PrivateKeySigner;
+use anyhow::anyhow;
+use std::io::{BufRead, BufReader};
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// private keys of the prefunded accounts derived from anvil's default mnemonic
+pub const ANVIL_DEV_KEYS: [&str; 3] = [
+    "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+    "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+    "5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a",
+];
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Anvil {
+    child: Child,
+    port: u16,
+}
+
+impl Anvil {
+    /// spawn anvil on a free port and wait until it accepts rpc requests
+    pub fn spawn() -> Result<Self, anyhow::Error> {
+        let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+        let mut child = Command::new("anvil")
+            .arg("--port")
+            .arg(port.to_string())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| {
+                anyhow!("failed to spawn anvil, is foundry installed?; caused by: {err}")
+            })?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or(anyhow!("anvil stdout not captured"))?;
+        let anvil = Self { child, port };
+
+        // keep draining stdout so anvil never blocks on a full pipe
+        let (ready_tx, ready_rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if line.contains("Listening on") {
+                    let _ = ready_tx.send(());
+                }
+            }
+        });
+        match ready_rx.recv_timeout(STARTUP_TIMEOUT) {
+            Ok(()) => Ok(anvil),
+            // dropping the instance kills the process
+            Err(_) => Err(anyhow!("anvil did not start listening on port {port}")),
+        }
+    }
+
+    pub fn endpoint(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    /// signer of the `index`th prefunded account
+    pub fn signer(&self, index: usize) -> Result<PrivateKeySigner, anyhow::Error> {
+        ANVIL_DEV_KEYS
+            .get(index)
+            .ok_or(anyhow!("anvil has {} dev keys", ANVIL_DEV_KEYS.len()))?
+            .parse::<PrivateKeySigner>()
+            .map_err(|err| anyhow!("invalid anvil dev key; caused by: {err}"))
+    }
+}
+
+impl Drop for Anvil {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -1,7 +1,7 @@
 use alloy::signers::SignerSync;
 use alloy::signers::{local::PrivateKeySigner, Signer};
 use alloy_primitives::{keccak256, B256};
-use codec::{Decode, Encode};
+use codec::Encode;
 use db::DbWorker;
 use jsonrpsee::core::client::ClientT;
 use jsonrpsee::http_client::HttpClient;
@@ -14,6 +14,9 @@ use std::fs::File;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+pub mod anvil;
+pub mod testnet;
+
 fn log_setup() -> Result<(), anyhow::Error> {
     CombinedLogger::init(vec![
         TermLogger::new(
@@ -34,29 +37,80 @@ fn log_setup() -> Result<(), anyhow::Error> {
 #[cfg(feature = "e2e")]
 mod e2e_tests {
     use super::*;
+    use crate::anvil::Anvil;
     use crate::log_setup;
+    use crate::testnet::{free_port, TestNet};
+    use alloy::consensus::{SignableTransaction, Signed, TxEip2930, TxEnvelope, TxLegacy};
+    use alloy::eips::eip2718::Encodable2718;
+    use alloy::providers::{Provider, ProviderBuilder};
     use alloy::signers::k256::ecdsa::SigningKey;
     use alloy::signers::k256::FieldBytes;
     use alloy::signers::local::LocalSigner;
+    use alloy::sol_types::SolCall;
     use alloy_primitives::{hex, Keccak256};
+    use alloy_primitives::{Address, Bytes, Signature, TxKind, U256};
     use anyhow::{anyhow, Error};
     use db::db::new_client_with_url;
     use jsonrpsee::core::client::{Client, Subscription, SubscriptionClientT};
     use jsonrpsee::core::params::ArrayParams;
     use jsonrpsee::http_client::HttpClientBuilder;
+    use jsonrpsee::server::{RpcModule, ServerBuilder};
+    use jsonrpsee::types::error::CallError;
     use jsonrpsee::ws_client::WsClientBuilder;
     use jsonrpsee::{rpc_params, SubscriptionMessage};
     use libp2p::futures::StreamExt;
     use libp2p::request_response::Message;
     use log::{error, info};
-    use node::rpc::Airtable;
-    use node::MainServiceWorker;
+    use node::anchors::BindingRegistry;
+    use node::approval_webhook::ApprovalWebhook;
+    use node::builder::TENANT_DB_FILE;
+    use node::chain::{ChainClient, EvmChainClient, MockChain};
+    use node::circuit_breaker::CircuitBreakers;
+    use node::cluster::LocalCluster;
+    use node::discovery::{LocalDiscovery, PeerDiscovery};
+    use node::event_bus::EventBus;
+    use node::p2p::{DialOutcome, AGENT_VERSION, DIAL_TIMEOUT, PROTOCOL_VERSION};
+    use node::pricing::StaticPrices;
+    use node::push::{PushGateway, PushNotification};
+    use node::risk::{HttpRiskProvider, RiskScreening};
+    use node::rotation::verify_rotation;
+    use node::rpc::{Airtable, TransactionRpcServer};
+    use node::rpc_middleware::Role;
+    use node::submission::{SubmissionQueues, VERIFY_TIMEOUT};
+    use node::tenants::HostedNode;
+    use node::tx_processing::TxProcessingWorker;
+    use node::user_operation::{ISmartAccount, UserOperation, ENTRY_POINT};
+    use node::watchdog::{HealthStatus, Subsystem, Watchdog};
+    use node::{MainServiceWorker, MainServiceWorkerBuilder, DEADLINE_SWEEP_INTERVAL};
+    use primitives::address::VaneAddress;
+    use primitives::approvals::UNLIMITED_APPROVAL;
+    use primitives::attestations::IdentityAttestation;
     use primitives::data_structure::{
-        AirtableRequestBody, Fields, PostRecord, SwarmMessage, TxStateMachine, TxStatus,
+        AddressCorrection, AirtableRequestBody, BreakerState, DbTxStateMachine, Fields, PostRecord,
+        RejectReason, SenderProfile, SubmissionRoute, SwarmMessage, Token, TxPriority,
+        TxStateMachine, TxStatus, TxWarning, H256,
     };
+    use primitives::device_sync::DevicePairingCode;
+    use primitives::diagnosis::{RetryStage, TxStage};
+    use primitives::envelope;
+    use primitives::environment::NetworkEnvironment;
+    use primitives::errors::VaneRpcError;
+    use primitives::fees::FeeTier;
+    use primitives::lifecycle::{TxLifecycleEvent, TX_LIFECYCLE_SCHEMA_VERSION};
+    use primitives::locale::{AmountLocale, Separators};
+    use primitives::payments::{PaymentPart, PaymentState};
+    use primitives::policy::{Condition, PolicyAction, PolicyRule};
+    use primitives::risk::RiskRequirements;
+    use primitives::travel_rule::{TravelRuleData, TravelRuleParty, Vasp};
+    use primitives::tx_state::{GenesisTx, SenderConfirmedTx};
     use rand::Rng;
+    use std::collections::HashMap;
     use std::hash::{DefaultHasher, Hash, Hasher};
+    use std::net::SocketAddr;
     use std::sync::Arc;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use vane_client::{TransferOptions, VaneClient, VaneClientError};
 
     // having 2 peers; peer 1 sends a tx-state-machine message to peer 2
     // and peer2 respond a modified version of tx-state-machine.
@@ -83,8 +137,10 @@ mod e2e_tests {
         });
         // ========================================================================//
 
-        let main_worker_1 = MainServiceWorker::e2e_new(3000, "../db/test1.db").await?;
-        let main_worker_2 = MainServiceWorker::e2e_new(4000, "../db/test2.db").await?;
+        let discovery = LocalDiscovery::new();
+        let port = rand::thread_rng().gen_range(1000..=u16::MAX - 1000);
+        let main_worker_1 = MainServiceWorker::e2e_new(port, discovery.clone()).await?;
+        let main_worker_2 = MainServiceWorker::e2e_new(port + 890, discovery).await?;
 
         let cloned_main_worker_1 = main_worker_1.clone();
         let cloned_main_worker_2 = main_worker_2.clone();
@@ -141,11 +197,14 @@ mod e2e_tests {
                         info!("Worker 1 received request");
                     }
                     Ok(SwarmMessage::Response { data, outbound_id }) => {
-                        let received_response: TxStateMachine =
-                            Decode::decode(&mut &data[..]).unwrap();
+                        let received_response = envelope::decode_tx(&data).unwrap();
                         assert_eq!(received_response, state_1.response_msg);
                         assert_eq!(1, 2);
                     }
+                    Ok(SwarmMessage::Delivered { tx_nonce }) => {
+                        info!("Worker 1 request of tx {tx_nonce} delivered");
+                    }
+                    Ok(SwarmMessage::Cancelled { .. }) => {}
                     Err(e) => error!("Worker 1 error: {}", e),
                 }
             }
@@ -163,93 +222,2908 @@ mod e2e_tests {
                         inbound_id.hash(&mut req_id_hash);
                         let req_id_hash = req_id_hash.finish();
 
-                        worker_2_for_task
-                            .p2p_network_service
-                            .lock()
-                            .await
-                            .send_response(
-                                req_id_hash,
-                                Arc::new(Mutex::new(state_2.response_msg.clone())),
-                            )
-                            .await?;
-                    }
-                    Ok(SwarmMessage::Response { data, outbound_id }) => {
-                        // nothing for now
-                    }
-                    Err(e) => error!("Worker 1 error: {}", e),
-                }
-            }
-            Ok::<(), Error>(())
+                        worker_2_for_task
+                            .p2p_network_service
+                            .lock()
+                            .await
+                            .send_response(
+                                req_id_hash,
+                                Arc::new(Mutex::new(state_2.response_msg.clone())),
+                            )
+                            .await?;
+                    }
+                    Ok(SwarmMessage::Response { data, outbound_id }) => {
+                        // nothing for now
+                    }
+                    Ok(SwarmMessage::Delivered { .. } | SwarmMessage::Cancelled { .. }) => {}
+                    Err(e) => error!("Worker 1 error: {}", e),
+                }
+            }
+            Ok::<(), Error>(())
+        });
+
+        // ========================================================================//
+        // Now use the separate clone for later operations
+        let peer_id_2 = worker_2_for_later.p2p_worker.lock().await.node_id;
+        let multi_addr_2 = worker_2_for_later.p2p_worker.lock().await.url.clone();
+
+        let dialed = main_worker_1
+            .p2p_network_service
+            .lock()
+            .await
+            .dial_to_peer_id(multi_addr_2.clone(), &peer_id_2, DIAL_TIMEOUT)
+            .await?;
+        assert_eq!(dialed, DialOutcome::Connected);
+
+        main_worker_1
+            .p2p_network_service
+            .lock()
+            .await
+            .send_request(
+                Arc::new(Mutex::new(test_state.sent_msg.clone())),
+                peer_id_2,
+                multi_addr_2,
+            )
+            .await?;
+
+        // ========================================================================//
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn airtable_test() -> Result<(), anyhow::Error> {
+        let _ =log_setup();
+
+        let client = Airtable::new().await?;
+        let mut peer = Fields::default();
+        let req_body = AirtableRequestBody::new(peer.clone());
+        let record_data = client.create_peer(req_body).await?;
+        assert_eq!(record_data.fields, peer.clone());
+
+        // try updating
+        peer.account_id1 = Some("4456".to_string());
+        let new_req_body = PostRecord::new(peer.clone());
+        let updated_record = client.update_peer(new_req_body, record_data.id).await?;
+        // try fetching
+        assert_eq!(updated_record.fields, peer);
+
+        client.delete_all().await?;
+        Ok(())
+    }
+
+    // Retry connection with backoff
+    async fn connect_with_retry(url: &str, max_attempts: u32) -> Result<Client, Error> {
+        let mut attempts = 0;
+        loop {
+            match WsClientBuilder::default().build(url).await {
+                Ok(client) => return Ok(client),
+                Err(e) => {
+                    attempts += 1;
+                    if attempts >= max_attempts {
+                        return Err(anyhow!("error failed to connect caused by; {e:?}"));
+                    }
+                    println!("Connection attempt {} failed, retrying in 1s...", attempts);
+                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+    // every registered rpc method must be described in the OpenRPC document and vice-versa
+    #[tokio::test]
+    async fn openrpc_document_matches_rpc_module() -> Result<(), anyhow::Error> {
+        let port = rand::thread_rng().gen_range(1000..=u16::MAX);
+        let main_worker = MainServiceWorker::e2e_new(port, LocalDiscovery::new()).await?;
+        let rpc_module = main_worker.tx_rpc_worker.lock().await.clone().into_rpc();
+
+        let mut registered = rpc_module.method_names().collect::<Vec<_>>();
+        registered.sort();
+
+        let mut documented = node::openrpc::rpc_methods()
+            .iter()
+            .flat_map(|m| std::iter::once(m.name).chain(m.unsubscribe))
+            .collect::<Vec<_>>();
+        documented.sort();
+
+        assert_eq!(registered, documented);
+
+        // the served document lists the registered methods, every one of them described
+        let served = node::openrpc::registered_document(rpc_module.method_names());
+        let served_methods = served["methods"]
+            .as_array()
+            .ok_or(anyhow!("missing methods"))?;
+        assert_eq!(served_methods.len(), node::openrpc::rpc_methods().len());
+        assert!(served_methods
+            .iter()
+            .all(|method| method["summary"].as_str().is_some_and(|s| !s.is_empty())));
+
+        let document = node::openrpc::openrpc_document();
+        let error_codes = document["components"]["errors"]
+            .as_object()
+            .ok_or(anyhow!("missing error components"))?;
+        for method in node::openrpc::rpc_methods() {
+            for code in method.errors {
+                assert!(error_codes.contains_key(&code.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    // nodes spawned together register to the same discovery and see each other's accounts
+    #[tokio::test]
+    async fn testnet_nodes_discover_each_other() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(3).await?;
+        let wallets = (0..3)
+            .map(|_| PrivateKeySigner::random())
+            .collect::<Vec<_>>();
+
+        for (node, wallet) in testnet.nodes.iter().zip(&wallets) {
+            node.client
+                .register(
+                    "tester",
+                    wallet.address().to_string(),
+                    ChainSupported::Ethereum,
+                )
+                .await?;
+        }
+
+        let peers = testnet.node(0).client.list_peers().await?;
+        assert_eq!(peers.len(), 3);
+        for wallet in wallets {
+            let account = wallet.address().to_string();
+            assert!(peers.iter().any(|peer| peer.account_ids.contains(&account)));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn retried_transfers_return_the_same_tx_id() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(1).await?;
+        let client = &testnet.node(0).client;
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+
+        let tx_id = client
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let retried_tx_id = client
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        assert_eq!(tx_id, retried_tx_id);
+
+        // distinct keys are distinct transfers
+        let first_tx_id = client
+            .send_token_idempotent(&sender, &receiver, 1_000, Token::Eth, "payment-1")
+            .await?;
+        let second_tx_id = client
+            .send_token_idempotent(&sender, &receiver, 1_000, Token::Eth, "payment-2")
+            .await?;
+        assert_ne!(first_tx_id, second_tx_id);
+        assert_ne!(first_tx_id, tx_id);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn blocking_policies_refuse_transfers() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(1).await?;
+        let client = &testnet.node(0).client;
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+
+        let rules = vec![PolicyRule {
+            id: "large-eth".to_string(),
+            description: "eth transfers above 1000 wei are blocked".to_string(),
+            conditions: vec![Condition::AmountAbove {
+                token: Token::Eth,
+                amount: 1_000,
+            }],
+            action: PolicyAction::Block,
+        }];
+        client.set_policies(rules.clone()).await?;
+        assert_eq!(client.policies().await?, rules);
+
+        let blocked = client
+            .send_token(&sender, &receiver, 2_000, Token::Eth)
+            .await;
+        assert!(matches!(
+            blocked,
+            Err(VaneClientError::Node(VaneRpcError::PolicyViolation { rule_id, .. }))
+                if rule_id == "large-eth"
+        ));
+        client
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        Ok(())
+    }
+
+    // with optimistic send the tx id comes back at once and the refusal through the subscription
+    #[tokio::test]
+    async fn optimistic_sends_report_refusals_as_tx_updates() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn_with(1, |_, builder| builder.optimistic_send()).await?;
+        let client = &testnet.node(0).client;
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        client
+            .set_policies(vec![PolicyRule {
+                id: "large-eth".to_string(),
+                description: "eth transfers above 1000 wei are blocked".to_string(),
+                conditions: vec![Condition::AmountAbove {
+                    token: Token::Eth,
+                    amount: 1_000,
+                }],
+                action: PolicyAction::Block,
+            }])
+            .await?;
+
+        let mut updates = client.subscribe_updates().await?;
+        let tx_id = client
+            .send_token(&sender, &receiver, 2_000, Token::Eth)
+            .await?;
+        let update = tokio::time::timeout(DIAL_TIMEOUT, updates.next())
+            .await?
+            .ok_or(anyhow!("update subscription closed"))??;
+        assert_eq!(update.tx_id(), tx_id);
+        assert!(matches!(
+            update.status,
+            TxStatus::Withdrawn(reason) if reason.contains("large-eth")
+        ));
+        Ok(())
+    }
+
+    // api keys restrict each rpc method to a role, over http and websocket alike
+    #[tokio::test]
+    async fn rpc_methods_are_restricted_by_role() -> Result<(), anyhow::Error> {
+        let handle = MainServiceWorkerBuilder::new()
+            .db_url(db::IN_MEMORY_DB_URL)
+            .ports(free_port()?, free_port()?)
+            .discovery(Arc::new(LocalDiscovery::new()))
+            .api_key("dashboard", Role::Viewer)
+            .api_key("ops", Role::Operator)
+            .api_key("root", Role::Admin)
+            .no_telemetry()
+            .build()
+            .await?
+            .start()
+            .await?;
+        let http_url = format!("http://{}", handle.rpc_address);
+        let ws_url = format!("ws://{}", handle.rpc_address);
+
+        let dashboard = VaneClient::connect_http_with_api_key(&http_url, "dashboard")?;
+        assert!(dashboard.policies().await?.is_empty());
+        assert!(matches!(
+            dashboard.set_policies(vec![]).await,
+            Err(VaneClientError::Node(VaneRpcError::AccessDenied { role, .. })) if role == "admin"
+        ));
+
+        let ops = VaneClient::connect_http_with_api_key(&http_url, "ops")?;
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        ops.send_token(
+            sender.address().to_string(),
+            receiver.address().to_string(),
+            1_000,
+            Token::Eth,
+        )
+        .await?;
+        assert!(ops.set_policies(vec![]).await.is_err());
+
+        assert!(VaneClient::connect_http(&http_url)?
+            .policies()
+            .await
+            .is_err());
+        let dashboard = VaneClient::connect_ws_with_api_key(&ws_url, "dashboard").await?;
+        dashboard.subscribe_updates().await?;
+        assert!(matches!(
+            dashboard.set_policies(vec![]).await,
+            Err(VaneClientError::Node(VaneRpcError::AccessDenied { role, .. })) if role == "admin"
+        ));
+        let root = VaneClient::connect_ws_with_api_key(&ws_url, "root").await?;
+        root.set_policies(vec![]).await?;
+
+        handle.stop();
+        Ok(())
+    }
+
+    // a node with a send passphrase only sends while unlocked and locks itself again
+    #[tokio::test]
+    async fn locked_nodes_refuse_to_send_until_unlocked() -> Result<(), anyhow::Error> {
+        let window = std::time::Duration::from_secs(2);
+        let testnet = TestNet::spawn_with(1, |_, builder| {
+            builder.send_passphrase("correct horse battery staple", window)
+        })
+        .await?;
+        let client = &testnet.node(0).client;
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        let locked = |result: Result<_, VaneClientError>| {
+            matches!(
+                result,
+                Err(VaneClientError::Node(VaneRpcError::NodeLocked {
+                    window_secs: 2
+                }))
+            )
+        };
+
+        assert!(locked(
+            client
+                .send_token(&sender, &receiver, 1_000, Token::Eth)
+                .await
+        ));
+        assert!(client.unlock("wrong passphrase").await.is_err());
+        client.unlock("correct horse battery staple").await?;
+        client
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+
+        client.lock().await?;
+        assert!(locked(
+            client
+                .send_token(&sender, &receiver, 2_000, Token::Eth)
+                .await
+        ));
+
+        // the unlock window passes
+        client.unlock("correct horse battery staple").await?;
+        tokio::time::sleep(window).await;
+        assert!(locked(
+            client
+                .send_token(&sender, &receiver, 3_000, Token::Eth)
+                .await
+        ));
+
+        // the refused sends took no nonce, the sent transfers have consecutive ones
+        client.unlock("correct horse battery staple").await?;
+        client
+            .send_token(&sender, &receiver, 4_000, Token::Eth)
+            .await?;
+        let mut nonces = client
+            .pending_updates()
+            .await?
+            .iter()
+            .map(|tx| tx.tx_nonce)
+            .collect::<Vec<_>>();
+        nonces.sort();
+        assert_eq!(nonces.len(), 2);
+        assert_eq!(nonces[1], nonces[0] + 1);
+        Ok(())
+    }
+
+    // passphrase guesses are rate limited per api key, one key guessing locks out no other
+    #[tokio::test]
+    async fn unlock_guesses_are_limited_per_api_key() -> Result<(), anyhow::Error> {
+        let handle = MainServiceWorkerBuilder::new()
+            .db_url(db::IN_MEMORY_DB_URL)
+            .ports(free_port()?, free_port()?)
+            .discovery(Arc::new(LocalDiscovery::new()))
+            .api_key("ops", Role::Operator)
+            .api_key("intruder", Role::Operator)
+            .send_passphrase(
+                "correct horse battery staple",
+                std::time::Duration::from_secs(60),
+            )
+            .no_telemetry()
+            .build()
+            .await?
+            .start()
+            .await?;
+        let http_url = format!("http://{}", handle.rpc_address);
+
+        let intruder = VaneClient::connect_http_with_api_key(&http_url, "intruder")?;
+        for _ in 0..5 {
+            assert!(matches!(
+                intruder.unlock("wrong passphrase").await,
+                Err(VaneClientError::Node(VaneRpcError::InvalidParams { .. }))
+            ));
+        }
+        assert!(matches!(
+            intruder.unlock("correct horse battery staple").await,
+            Err(VaneClientError::Node(VaneRpcError::RateLimited { .. }))
+        ));
+
+        let ops = VaneClient::connect_http_with_api_key(&http_url, "ops")?;
+        ops.unlock("correct horse battery staple").await?;
+
+        handle.stop();
+        Ok(())
+    }
+
+    // a watch-only node refuses to send until a signing device is paired for the sender
+    #[tokio::test]
+    async fn watch_only_nodes_send_for_paired_signers_only() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn_with(1, |_, builder| builder.watch_only()).await?;
+        let client = &testnet.node(0).client;
+        let (device, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (device.address().to_string(), receiver.address().to_string());
+
+        let unpaired = client
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await;
+        assert!(matches!(
+            unpaired,
+            Err(VaneClientError::Node(VaneRpcError::SignerNotPaired { .. }))
+        ));
+
+        let message = client.pairing_message(&sender).await?;
+        let stranger = PrivateKeySigner::random().sign_message_sync(message.as_bytes())?;
+        assert!(client
+            .pair_signer(&sender, Vec::from(stranger))
+            .await
+            .is_err());
+        // the failed attempt spent the challenge of the message
+        let message = client.pairing_message(&sender).await?;
+        let signature = Vec::from(device.sign_message_sync(message.as_bytes())?);
+        client.pair_signer(&sender, signature.clone()).await?;
+        assert_eq!(client.paired_signers().await?, vec![sender.clone()]);
+
+        // a replayed pairing signature does not pair the account again
+        client.unpair_signer(&sender).await?;
+        assert!(client
+            .pair_signer(&sender, signature.clone())
+            .await
+            .is_err());
+        client.pairing_message(&sender).await?;
+        assert!(client.pair_signer(&sender, signature).await.is_err());
+        assert!(client.paired_signers().await?.is_empty());
+        let message = client.pairing_message(&sender).await?;
+        let signature = device.sign_message_sync(message.as_bytes())?;
+        client.pair_signer(&sender, Vec::from(signature)).await?;
+
+        client
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        Ok(())
+    }
+
+    async fn pending_with_status(
+        client: &VaneClient<jsonrpsee::ws_client::WsClient>,
+        status: TxStatus,
+    ) -> Result<TxStateMachine, anyhow::Error> {
+        for _ in 0..50 {
+            let pending = client.pending_updates().await?;
+            if let Some(tx) = pending.into_iter().find(|tx| tx.status == status) {
+                return Ok(tx);
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        Err(anyhow!("no pending tx reached {status:?}"))
+    }
+
+    // the receiver node acknowledges the request before its user confirms
+    #[tokio::test]
+    async fn delivered_requests_are_acknowledged_before_the_receiver_confirms(
+    ) -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let delivered = pending_with_status(sender_node, TxStatus::DeliveredToReceiver).await?;
+        assert_eq!(delivered.receiver_address.to_string(), receiver);
+        Ok(())
+    }
+
+    // a second transfer the sender balance can not cover next to the first one in flight is warned
+    #[tokio::test]
+    async fn transfers_overdrawing_the_funds_in_flight_are_warned() -> Result<(), anyhow::Error> {
+        // dev nodes only talk to dev nodes
+        let testnet = TestNet::spawn_with(2, |_, builder| builder.dev()).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+        sender_node
+            .faucet(&sender, 1_500, ChainSupported::Ethereum)
+            .await?;
+
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let first = pending_with_status(sender_node, TxStatus::DeliveredToReceiver).await?;
+        assert!(first.warnings.is_empty());
+
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let mut second = None;
+        for _ in 0..50 {
+            second = sender_node
+                .pending_updates()
+                .await?
+                .into_iter()
+                .find(|tx| tx.tx_nonce != first.tx_nonce);
+            if second.is_some() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        let second = second.ok_or(anyhow!("second transfer not staged"))?;
+        let conflict = second
+            .warnings
+            .iter()
+            .find(|warning| warning.code == "CONFLICTING_TRANSFERS")
+            .ok_or(anyhow!("second transfer not warned"))?;
+        assert!(conflict.requires_override());
+        assert!(conflict.message.contains("commit 1000 of the 1500 balance"));
+        Ok(())
+    }
+
+    // transfers below the operator minimum are refused, amounts with too many decimals are warned
+    #[tokio::test]
+    async fn transfer_amounts_are_checked_against_operator_limits() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn_with(2, |i, builder| match i {
+            0 => builder
+                .min_transfer_amount(Token::Eth, 1_000)
+                .max_decimals(Token::Eth, 4),
+            _ => builder,
+        })
+        .await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        let below = sender_node
+            .send_token(&sender, &receiver, 999, Token::Eth)
+            .await;
+        assert!(below.is_err_and(|err| err.to_string().contains("at least")));
+
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let staged = pending_with_status(sender_node, TxStatus::DeliveredToReceiver).await?;
+        let precision = staged
+            .warnings
+            .iter()
+            .find(|warning| warning.code == "EXCESS_PRECISION")
+            .ok_or(anyhow!("amount precision not warned"))?;
+        assert!(!precision.requires_override());
+        assert!(precision.message.contains("15 decimals"));
+        Ok(())
+    }
+
+    // a transfer worth $100 of ETH is priced when initiated and refused once the price moved past
+    // the slippage before the sender confirms
+    #[tokio::test]
+    async fn fiat_transfers_are_refused_once_the_price_moved() -> Result<(), anyhow::Error> {
+        let chain = MockChain::new(ChainSupported::Ethereum);
+        let prices = StaticPrices::new([(Token::Eth, 2_500.0)]);
+        let testnet = TestNet::spawn_with(2, |i, builder| match i {
+            0 => builder
+                .chain_client(Arc::new(chain.clone()))
+                .price_source(Arc::new(prices.clone())),
+            _ => builder,
+        })
+        .await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender_key, receiver_key) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let sender = sender_key.address().to_string();
+        let receiver = receiver_key.address().to_string();
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        // the receiver node prices nothing
+        assert!(receiver_node
+            .send_fiat(&receiver, &sender, 10_000, Token::Eth, None)
+            .await
+            .is_err_and(|err| err.to_string().contains("no usd price")));
+
+        sender_node
+            .send_fiat(&sender, &receiver, 10_000, Token::Eth, Some(100))
+            .await?;
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if !receives.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 1);
+        let tx = receives[0].tx.clone();
+        assert_eq!(tx.amount, 40_000_000_000_000_000);
+        let msg = tx.receiver_address.to_string();
+        let signable_msg = format!("{ETH_SIG_MSG_PREFIX}{}{msg}", msg.len());
+        let signature = receiver_key.sign_hash_sync(&keccak256(signable_msg.as_bytes()))?;
+        receiver_node
+            .confirm_as_receiver(tx, Vec::from(signature))
+            .await?;
+
+        let tx = pending_with_status(sender_node, TxStatus::NetConfirmed).await?;
+        let quote = tx
+            .fiat_quote
+            .ok_or(anyhow!("fiat quote not kept with the tx"))?;
+        assert_eq!(
+            (quote.usd_cents, quote.locked_price),
+            (10_000, 2_500_000_000)
+        );
+        let call_payload = tx.call_payload.ok_or(anyhow!("call payload not created"))?;
+        let signature = Vec::from(sender_key.sign_hash_sync(&B256::new(call_payload))?);
+
+        prices.set(Token::Eth, 2_400.0);
+        assert!(sender_node
+            .confirm_as_sender(tx.clone(), signature.clone())
+            .await
+            .is_err_and(|err| err.to_string().contains("price moved")));
+        assert!(chain.submissions().await.is_empty());
+
+        // back within the slippage
+        prices.set(Token::Eth, 2_490.0);
+        sender_node.confirm_as_sender(tx, signature).await?;
+        for _ in 0..50 {
+            if !chain.submissions().await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(chain.submissions().await[0].amount, 40_000_000_000_000_000);
+        Ok(())
+    }
+
+    // a sender short of usdc on ethereum is pointed at its usdc on solana, where the receiver node
+    // bound an account as well, and sends there instead
+    #[tokio::test]
+    async fn transfers_are_rerouted_to_chains_the_sender_holds_funds_on(
+    ) -> Result<(), anyhow::Error> {
+        // dev nodes only talk to dev nodes
+        let testnet = TestNet::spawn_with(2, |_, builder| builder.dev()).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        let sol_sender = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string();
+        let sol_receiver = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string();
+        for (node, name, account, sol_account) in [
+            (sender_node, "sender", &sender, &sol_sender),
+            (receiver_node, "receiver", &receiver, &sol_receiver),
+        ] {
+            node.register(name, account.clone(), ChainSupported::Ethereum)
+                .await?;
+            node.add_account(name, vec![(sol_account.clone(), ChainSupported::Solana)])
+                .await?;
+        }
+        assert!(sender_node
+            .add_account("sender", vec![(sender.clone(), ChainSupported::Solana)])
+            .await
+            .is_err());
+        sender_node
+            .faucet(&sender, 500, ChainSupported::Ethereum)
+            .await?;
+        sender_node
+            .faucet(&sol_sender, 5_000, ChainSupported::Solana)
+            .await?;
+
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::UsdcEth)
+            .await?;
+        let tx = pending_with_status(sender_node, TxStatus::DeliveredToReceiver).await?;
+        let warning = tx
+            .warnings
+            .iter()
+            .find(|warning| warning.code == "ALTERNATIVE_ROUTE")
+            .ok_or(anyhow!("alternative route not warned"))?;
+        assert!(warning.message.contains("on Solana"));
+
+        let routes = sender_node.suggest_routes(tx.tx_nonce).await?;
+        assert_eq!(routes.len(), 1);
+        assert_eq!(
+            (routes[0].network, routes[0].token, routes[0].amount),
+            (ChainSupported::Solana, Token::UsdcSol, 1_000)
+        );
+        assert_eq!(
+            (routes[0].sender.as_str(), routes[0].receiver.as_str()),
+            (sol_sender.as_str(), sol_receiver.as_str())
+        );
+        assert_eq!(routes[0].balance, 5_000);
+        assert!(sender_node
+            .reroute(tx.tx_nonce, ChainSupported::Bnb)
+            .await
+            .is_err());
+
+        let rerouted = sender_node
+            .reroute(tx.tx_nonce, ChainSupported::Solana)
+            .await?;
+        assert_ne!(rerouted, tx.tx_id());
+        let mut sent = None;
+        for _ in 0..50 {
+            sent = sender_node
+                .pending_updates()
+                .await?
+                .into_iter()
+                .find(|pending| pending.network == ChainSupported::Solana);
+            if sent.is_some() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        let sent = sent.ok_or(anyhow!("rerouted transfer not staged"))?;
+        assert_eq!(sent.token, Some(Token::UsdcSol));
+        assert_eq!(sent.receiver_address.to_string(), sol_receiver);
+        // the original transfer is not sent once rerouted
+        assert!(sender_node
+            .reroute(tx.tx_nonce, ChainSupported::Solana)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    /// binding registry contract keeping the anchors in memory, `anchor` stands for the tx the
+    /// account sends
+    #[derive(Clone, Default)]
+    struct MemoryRegistry(Arc<std::sync::Mutex<HashMap<VaneAddress, H256>>>);
+
+    impl MemoryRegistry {
+        fn anchor(&self, account: &str, binding: H256) -> Result<(), anyhow::Error> {
+            let account = VaneAddress::parse(account)?;
+            self.0.lock().unwrap().insert(account, binding);
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl BindingRegistry for MemoryRegistry {
+        fn network(&self) -> ChainSupported {
+            ChainSupported::Ethereum
+        }
+
+        fn contract(&self) -> String {
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string()
+        }
+
+        async fn binding_of(&self, account: &VaneAddress) -> Result<Option<H256>, anyhow::Error> {
+            Ok(self.0.lock().unwrap().get(account).copied())
+        }
+    }
+
+    // a sender distrusting the off-chain registry only sends to receivers that anchored their
+    // binding to the node the registry serves them from
+    #[tokio::test]
+    async fn transfers_to_receivers_without_an_anchored_binding_are_refused(
+    ) -> Result<(), anyhow::Error> {
+        let registry = MemoryRegistry::default();
+        let testnet = TestNet::spawn_with(2, |i, builder| {
+            let builder = builder.binding_registry(Arc::new(registry.clone()));
+            match i {
+                0 => builder.require_anchored_bindings(),
+                _ => builder,
+            }
+        })
+        .await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        assert!(sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await
+            .is_err_and(|err| err.to_string().contains("did not anchor its binding")));
+        assert!(!sender_node.verify_binding(&receiver, None).await?.verified);
+
+        // only the accounts of the node get a call anchoring their binding to it
+        assert!(receiver_node.anchor_call(&sender).await.is_err());
+        let call = receiver_node.anchor_call(&receiver).await?;
+        assert_eq!(call.network, ChainSupported::Ethereum);
+        assert_eq!(call.data.len(), 4 + 32);
+        assert_eq!(&call.data[4..], call.binding.as_bytes());
+        // an anchor to another node does not verify
+        registry.anchor(&receiver, H256::repeat_byte(1))?;
+        assert!(!sender_node.verify_binding(&receiver, None).await?.verified);
+        registry.anchor(&receiver, call.binding)?;
+        let anchor = sender_node.verify_binding(&receiver, None).await?;
+        assert!(anchor.verified);
+        assert_eq!(anchor.peer_id, call.peer_id);
+
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        pending_with_status(sender_node, TxStatus::DeliveredToReceiver).await?;
+        Ok(())
+    }
+
+    // attestation requests wait in the receiver inbox until confirmed or declined, a declined request
+    // fails the sender tx
+    #[tokio::test]
+    async fn receivers_list_and_decline_pending_receives() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if !receives.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 1);
+        assert_eq!(receives[0].tx.receiver_address.to_string(), receiver);
+        assert!(receiver_node
+            .pending_receives(Some(ChainSupported::Bnb), None, None)
+            .await?
+            .is_empty());
+        assert!(receiver_node
+            .pending_receives(None, Some(3600), None)
+            .await?
+            .is_empty());
+
+        let tx_nonce = receives[0].tx.tx_nonce;
+        let outcomes = receiver_node
+            .reject_pending_receives(vec![tx_nonce, tx_nonce + 1])
+            .await?;
+        assert_eq!(outcomes[0].error, None);
+        assert!(outcomes[1].error.is_some());
+        assert!(receiver_node
+            .pending_receives(None, None, None)
+            .await?
+            .is_empty());
+
+        let declined = pending_with_status(sender_node, TxStatus::RecvAddrFailed).await?;
+        assert_eq!(declined.tx_nonce, tx_nonce);
+
+        // the sender node remembers the declined transfer to the receiver
+        let profile = sender_node.counterparty(receiver.to_lowercase()).await?;
+        assert_eq!(profile.address, receiver);
+        assert_eq!((profile.sent, profile.received), (1, 0));
+        assert_eq!((profile.succeeded, profile.failed), (0, 1));
+        assert_eq!(profile.attestations, 1);
+        assert!(profile.first_seen.is_some());
+        Ok(())
+    }
+
+    // a receiver rejecting a request on purpose tells the sender why, unlike a failed attestation
+    #[tokio::test]
+    async fn rejections_carry_their_reason_back_to_the_sender() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if !receives.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 1);
+        let outcomes = receiver_node
+            .reject_pending_receives_with_reason(
+                vec![receives[0].tx.tx_nonce],
+                RejectReason::WrongChain,
+            )
+            .await?;
+        assert_eq!(outcomes[0].error, None);
+
+        let rejected_status = TxStatus::RecvRejected(RejectReason::WrongChain);
+        let rejected = pending_with_status(sender_node, rejected_status).await?;
+        assert_eq!(rejected.amount, 1_000);
+        let history = sender_node.tx_history().await?;
+        assert!(history
+            .iter()
+            .any(|tx| !tx.success && tx.reject_reason == Some(RejectReason::WrongChain)));
+        Ok(())
+    }
+
+    // a receiver rejecting a transfer sent to its old address proposes the new one, signed with the
+    // old key, and the sender restarts the transfer to it in one call
+    #[tokio::test]
+    async fn rejected_transfers_restart_to_the_corrected_address() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let sender = PrivateKeySigner::random().address().to_string();
+        let (old_key, new_key) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (old, new) = (old_key.address().to_string(), new_key.address().to_string());
+        for receiver in [&old, &new] {
+            receiver_node
+                .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+                .await?;
+        }
+
+        sender_node
+            .send_token(&sender, &old, 1_000, Token::Eth)
+            .await?;
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if !receives.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 1);
+        let rejected_tx = receives[0].tx.clone();
+        let address = VaneAddress::parse(&new)?;
+        let message =
+            AddressCorrection::message(rejected_tx.tx_id(), ChainSupported::Ethereum, &address);
+        let correction = |key: &PrivateKeySigner| -> Result<AddressCorrection, anyhow::Error> {
+            Ok(AddressCorrection {
+                network: ChainSupported::Ethereum,
+                address,
+                signature: Vec::from(key.sign_message_sync(message.as_bytes())?),
+            })
+        };
+        // only the key of the rejected address vouches for the new one
+        assert!(receiver_node
+            .reject_with_correction(
+                rejected_tx.tx_nonce,
+                RejectReason::NotMyAddress,
+                correction(&new_key)?
+            )
+            .await
+            .is_err());
+        receiver_node
+            .reject_with_correction(
+                rejected_tx.tx_nonce,
+                RejectReason::NotMyAddress,
+                correction(&old_key)?,
+            )
+            .await?;
+
+        let rejected_status = TxStatus::RecvRejected(RejectReason::NotMyAddress);
+        let rejected = pending_with_status(sender_node, rejected_status).await?;
+        assert_eq!(rejected.address_correction, Some(correction(&old_key)?));
+        let diagnosis = sender_node.diagnose_tx(rejected.tx_id()).await?;
+        assert!(diagnosis.detail.contains(&new));
+
+        let restarted = sender_node
+            .restart_with_correction(rejected.tx_id())
+            .await?;
+        assert_ne!(restarted, rejected.tx_id());
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if !receives.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 1);
+        assert_eq!(receives[0].tx.receiver_address, address);
+        assert_eq!(receives[0].tx.amount, 1_000);
+        Ok(())
+    }
+
+    // a receiver settling for less attests with the accepted amount, the sender gets it back with
+    // the attested tx and never more than it initiated
+    #[tokio::test]
+    async fn receivers_settle_for_a_lower_amount() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let receiver_key = PrivateKeySigner::random();
+        let sender = PrivateKeySigner::random().address().to_string();
+        let receiver = receiver_key.address().to_string();
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if !receives.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 1);
+        let tx = receives[0].tx.clone();
+        let msg = tx.receiver_address.to_string();
+        let signable_msg = format!("{ETH_SIG_MSG_PREFIX}{}{msg}", msg.len());
+        let pre_hash = keccak256(signable_msg.as_bytes());
+        let signature = Vec::from(receiver_key.sign_hash_sync(&pre_hash)?);
+        assert!(receiver_node
+            .confirm_as_receiver_accepting(tx.clone(), signature.clone(), 1_500)
+            .await
+            .is_err());
+        receiver_node
+            .confirm_as_receiver_accepting(tx, signature, 800)
+            .await?;
+
+        let mut accepted = None;
+        for _ in 0..50 {
+            let pending = sender_node.pending_updates().await?;
+            accepted = pending.into_iter().find(|tx| tx.accepted_amount.is_some());
+            if accepted.is_some() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        let accepted = accepted.ok_or(anyhow!("the sender never saw the accepted amount"))?;
+        assert_eq!((accepted.amount, accepted.transfer_amount()), (1_000, 800));
+        Ok(())
+    }
+
+    // a split payment initiates a transfer per part under one payment id, its status folds the
+    // state of every part
+    #[tokio::test]
+    async fn split_payments_track_their_transfers() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let sender = PrivateKeySigner::random().address().to_string();
+        let (first, second) = (
+            PrivateKeySigner::random().address().to_string(),
+            PrivateKeySigner::random().address().to_string(),
+        );
+        for receiver in [&first, &second] {
+            receiver_node
+                .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+                .await?;
+        }
+        let part = |receiver: &str, amount: u128| PaymentPart {
+            receiver: receiver.to_string(),
+            amount,
+            token: Token::Eth,
+            memo: None,
+        };
+
+        assert!(sender_node
+            .split_payment(&sender, vec![part(&first, 600)], None, None)
+            .await
+            .is_err());
+        let parts = vec![part(&first, 600), part(&second, 400)];
+        let payment_id = sender_node
+            .split_payment(&sender, parts, Some("INV-9".to_string()), None)
+            .await?;
+
+        let payment = sender_node.payment(payment_id).await?;
+        assert_eq!(payment.state, PaymentState::Pending);
+        assert_eq!(payment.children.len(), 2);
+        assert_eq!(payment.children[0].tx.amount, 600);
+        assert!(payment.children.iter().all(|child| {
+            child.tx.parent_payment == Some(payment_id)
+                && child.tx.reference.as_deref() == Some("INV-9")
+        }));
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if receives.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 2);
+        assert!(sender_node.payment(H256::zero()).await.is_err());
+        Ok(())
+    }
+
+    // a receiver registered from two nodes gets the request on both, the first answer wins and the
+    // request is withdrawn from the other node
+    #[tokio::test]
+    async fn requests_fan_out_to_every_receiver_device() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(3).await?;
+        let sender_node = &testnet.node(0).client;
+        let (laptop, phone) = (&testnet.node(1).client, &testnet.node(2).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        for device in [laptop, phone] {
+            device
+                .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+                .await?;
+        }
+
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        for device in [laptop, phone] {
+            let mut receives = vec![];
+            for _ in 0..50 {
+                receives = device.pending_receives(None, None, None).await?;
+                if !receives.is_empty() {
+                    break;
+                }
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            }
+            assert_eq!(receives.len(), 1);
+        }
+
+        let tx_nonce = laptop.pending_receives(None, None, None).await?[0]
+            .tx
+            .tx_nonce;
+        laptop.reject_pending_receives(vec![tx_nonce]).await?;
+        let declined = pending_with_status(sender_node, TxStatus::RecvAddrFailed).await?;
+        assert_eq!(declined.tx_nonce, tx_nonce);
+        let cancelled = pending_with_status(phone, TxStatus::AnsweredOnOtherDevice).await?;
+        assert_eq!(cancelled.tx_nonce, tx_nonce);
+        assert!(phone.pending_receives(None, None, None).await?.is_empty());
+        Ok(())
+    }
+
+    // the phone proves it got the pairing code shown by the laptop, the laptop policies and
+    // pending transfers then sync to the phone
+    #[tokio::test]
+    async fn paired_devices_sync_policies_and_pending_transfers() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(3).await?;
+        let (laptop, phone) = (&testnet.node(0).client, &testnet.node(1).client);
+        let receiver_node = &testnet.node(2).client;
+        let receiver = PrivateKeySigner::random().address().to_string();
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+        let rules = vec![PolicyRule {
+            id: "large-eth".to_string(),
+            description: "eth transfers above 1000 wei are blocked".to_string(),
+            conditions: vec![Condition::AmountAbove {
+                token: Token::Eth,
+                amount: 1_000,
+            }],
+            action: PolicyAction::Block,
+        }];
+        laptop.set_policies(rules.clone()).await?;
+        let sender = PrivateKeySigner::random().address().to_string();
+        laptop
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+
+        let pairing = laptop.device_pairing_code().await?;
+        let (_, multi_addr) =
+            DevicePairingCode::parse_uri(&pairing.uri).ok_or(anyhow!("invalid pairing uri"))?;
+        let wrong_code = DevicePairingCode::uri("00000000x", &multi_addr);
+        assert!(phone.pair_device(wrong_code).await.is_err());
+        let laptop_device = phone.pair_device(pairing.uri.clone()).await?;
+        assert_eq!(phone.paired_devices().await?, vec![laptop_device.clone()]);
+        // the code is single use
+        assert!(receiver_node.pair_device(pairing.uri).await.is_err());
+
+        let mut transfers = vec![];
+        for _ in 0..50 {
+            transfers = phone.device_transfers().await?;
+            if !transfers.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].sender_address.to_string(), sender);
+        assert_eq!(phone.policies().await?, rules);
+        let phone_devices = laptop.paired_devices().await?;
+        assert_eq!(phone_devices.len(), 1);
+        assert_ne!(phone_devices[0].peer_id, laptop_device.peer_id);
+
+        phone.unpair_device(&laptop_device.peer_id).await?;
+        assert!(phone.paired_devices().await?.is_empty());
+        Ok(())
+    }
+
+    // the phone rotates its identity, its registry record and the laptop it is paired with move to
+    // the new peer id while the old one answers for the grace period
+    #[tokio::test]
+    async fn rotated_identity_is_announced_to_registry_and_contacts() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (laptop, phone) = (&testnet.node(0).client, &testnet.node(1).client);
+        let account = PrivateKeySigner::random().address().to_string();
+        phone
+            .register("phone", account.clone(), ChainSupported::Ethereum)
+            .await?;
+        let pairing = laptop.device_pairing_code().await?;
+        let laptop_device = phone.pair_device(pairing.uri).await?;
+
+        let rotated = phone.rotate_node_identity(Some(60)).await?;
+        let rotation = rotated.rotation;
+        let (old_peer_id, new_peer_id) = verify_rotation(&rotation)?;
+        assert_eq!(rotation.grace_until, rotation.rotated_at + 60);
+        assert_ne!(old_peer_id, new_peer_id);
+        // the old identity is still in its grace period
+        assert!(phone.rotate_node_identity(None).await.is_err());
+
+        let record = laptop
+            .list_peers()
+            .await?
+            .into_iter()
+            .find(|peer| peer.account_ids.contains(&account))
+            .ok_or(anyhow!("phone record not found"))?;
+        assert_eq!(record.peer_id, Some(rotation.new_peer_id.clone()));
+        assert_eq!(record.multi_addr, Some(rotation.new_multi_addr.clone()));
+
+        let mut devices = vec![];
+        for _ in 0..50 {
+            devices = laptop.paired_devices().await?;
+            if devices
+                .iter()
+                .any(|device| device.peer_id == rotation.new_peer_id)
+            {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].peer_id, rotation.new_peer_id);
+        assert_eq!(devices[0].multi_addr, rotation.new_multi_addr);
+        // the phone keeps the laptop it paired with
+        assert_eq!(phone.paired_devices().await?, vec![laptop_device]);
+        Ok(())
+    }
+
+    // lock wait and hold times of the workers are scraped from `GET /metrics`
+    #[tokio::test]
+    async fn lock_contention_is_served_as_metrics() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(1).await?;
+        let node = testnet.node(0);
+        node.client.pending_receives(None, None, None).await?;
+
+        let mut stream = tokio::net::TcpStream::connect(node.handle.rpc_address).await?;
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("# TYPE vane_lock_wait_seconds_total counter"));
+        assert!(response.contains("vane_lock_acquisitions_total{component=\"db_worker\"}"));
+        assert!(response.contains("vane_lock_hold_seconds_max{component=\"db_worker\"}"));
+        Ok(())
+    }
+
+    // a swarm failing its checks is restarted by the watchdog, recovers and keeps serving
+    // attestation requests
+    #[tokio::test]
+    async fn restarted_swarm_keeps_serving_attestation_requests() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let receiver_worker = &testnet.node(1).handle.worker;
+        let receiver = PrivateKeySigner::random().address().to_string();
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        let watchdog = receiver_worker
+            .watchdog
+            .clone()
+            .with_probe_timeout(tokio::time::Duration::from_millis(200));
+        {
+            // the swarm checks time out while the network service is held
+            let _wedged = receiver_worker.p2p_network_service.lock().await;
+            for _ in 0..Watchdog::FAILURES_BEFORE_RESTART {
+                watchdog.check(receiver_worker).await;
+            }
+            assert_eq!(
+                watchdog.health(Subsystem::Swarm).await,
+                Some(HealthStatus::Restarted)
+            );
+        }
+        watchdog.check(receiver_worker).await;
+        assert_eq!(
+            watchdog.health(Subsystem::Swarm).await,
+            Some(HealthStatus::Recovered)
+        );
+
+        let sender = PrivateKeySigner::random().address().to_string();
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if !receives.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 1);
+        Ok(())
+    }
+
+    // both ends of an attestation request record the peer, the receiver serves it on /metrics too
+    #[tokio::test]
+    async fn peer_stats_track_requests_and_bytes_exchanged() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let receiver = PrivateKeySigner::random().address().to_string();
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+        let sender = PrivateKeySigner::random().address().to_string();
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let mut received = vec![];
+        for _ in 0..50 {
+            received = receiver_node.peer_stats().await?;
+            if received.iter().any(|peer| peer.requests_received > 0) {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+
+        let sent = sender_node.peer_stats().await?;
+        assert_eq!(sent.len(), 1);
+        assert_eq!(received.len(), 1);
+        let (sent, received) = (&sent[0], &received[0]);
+        assert!(sent.connected && received.connected);
+        assert_eq!(sent.requests_sent, 1);
+        assert!(sent.bytes_sent > 0);
+        assert_eq!(received.requests_received, 1);
+        assert_eq!(received.bytes_received, sent.bytes_sent);
+        assert!(received.last_seen.is_some());
+        // no response before the receiver confirms, so no success rate yet
+        assert_eq!(sent.success_rate, None);
+        assert_eq!(sent.protocol_version.as_deref(), Some(PROTOCOL_VERSION));
+        assert_eq!(received.agent_version.as_deref(), Some(AGENT_VERSION));
+
+        let mut stream = tokio::net::TcpStream::connect(testnet.node(1).handle.rpc_address).await?;
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        let requests_received = format!(
+            "vane_peer_requests_received_total{{peer=\"{}\"}} 1",
+            received.peer_id
+        );
+        assert!(response.contains(&requests_received));
+        Ok(())
+    }
+
+    // the receiver binds its account to its node, the sender node learns the binding over peer
+    // exchange once the two nodes identified each other
+    #[tokio::test]
+    async fn connected_nodes_exchange_signed_peer_bindings() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let receiver = PrivateKeySigner::random();
+        let account = receiver.address().to_string();
+        receiver_node
+            .register("receiver", account.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        // only an account registered by the node and signing the binding itself is accepted
+        let signed_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let message = receiver_node
+            .peer_binding_message(&account, signed_at)
+            .await?;
+        let forged = PrivateKeySigner::random().sign_message_sync(message.as_bytes())?;
+        assert!(receiver_node
+            .add_peer_binding(&account, signed_at, Vec::from(forged))
+            .await
+            .is_err());
+        let signature = receiver.sign_message_sync(message.as_bytes())?;
+        receiver_node
+            .add_peer_binding(&account, signed_at, Vec::from(signature))
+            .await?;
+
+        let sender = PrivateKeySigner::random().address().to_string();
+        sender_node
+            .send_token(&sender, &account, 1_000, Token::Eth)
+            .await?;
+        let mut learned = vec![];
+        for _ in 0..50 {
+            learned = sender_node.peer_bindings().await?;
+            if !learned.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(learned, receiver_node.peer_bindings().await?);
+        assert_eq!(learned[0].account, account);
+        assert_eq!(learned[0].signed_at, signed_at);
+        Ok(())
+    }
+
+    // the receiver revokes its leaked account, the sender is warned about a transfer to it and the
+    // receiver node declines to attest it
+    #[tokio::test]
+    async fn transfers_to_revoked_accounts_are_warned_and_declined() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let receiver = PrivateKeySigner::random();
+        let account = receiver.address().to_string();
+        receiver_node
+            .register("receiver", account.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        // only the key of the account revokes it
+        let revoked_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let message = receiver_node
+            .revocation_message(&account, revoked_at)
+            .await?;
+        let forged = PrivateKeySigner::random().sign_message_sync(message.as_bytes())?;
+        assert!(receiver_node
+            .revoke_account(&account, revoked_at, Vec::from(forged))
+            .await
+            .is_err());
+        let signature = receiver.sign_message_sync(message.as_bytes())?;
+        receiver_node
+            .revoke_account(&account, revoked_at, Vec::from(signature))
+            .await?;
+        let record = sender_node
+            .list_peers()
+            .await?
+            .into_iter()
+            .find(|peer| peer.account_ids.contains(&account))
+            .ok_or(anyhow!("receiver record not found"))?;
+        assert_eq!(record.revocations, receiver_node.revocations().await?);
+
+        let sender = PrivateKeySigner::random().address().to_string();
+        sender_node
+            .send_token(&sender, &account, 1_000, Token::Eth)
+            .await?;
+        let declined = pending_with_status(sender_node, TxStatus::RecvAddrFailed).await?;
+        let revoked_warnings = declined
+            .warnings
+            .iter()
+            .filter(|warning| warning.code == "REVOKED_ACCOUNT")
+            .count();
+        assert_eq!(revoked_warnings, 1);
+        assert!(receiver_node
+            .pending_receives(None, None, None)
+            .await?
+            .is_empty());
+        assert_eq!(
+            sender_node.revocations().await?,
+            receiver_node.revocations().await?
+        );
+        Ok(())
+    }
+
+    // the reference and tags of a transfer reach the receiver node, invalid ones are refused
+    #[tokio::test]
+    async fn references_and_tags_travel_with_the_transfer() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        let too_many_tags = TransferOptions {
+            tags: Some((0..10).map(|i| format!("tag-{i}")).collect()),
+            ..Default::default()
+        };
+        assert!(sender_node
+            .send_token_with_options(&sender, &receiver, 1_000, Token::Eth, too_many_tags)
+            .await
+            .is_err());
+
+        let options = TransferOptions {
+            reference: Some(" INV-2031 ".to_string()),
+            tags: Some(vec!["payroll".to_string(), "Payroll".to_string()]),
+            ..Default::default()
+        };
+        sender_node
+            .send_token_with_options(&sender, &receiver, 1_000, Token::Eth, options)
+            .await?;
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if !receives.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 1);
+        assert_eq!(receives[0].tx.reference.as_deref(), Some("INV-2031"));
+        assert_eq!(receives[0].tx.tags, vec!["payroll".to_string()]);
+        Ok(())
+    }
+
+    // the receiver sees the priority of the transfer, the fee tier follows it unless picked
+    #[tokio::test]
+    async fn urgent_transfers_pay_the_high_fee_tier() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        let options = TransferOptions {
+            priority: Some(TxPriority::Urgent),
+            ..Default::default()
+        };
+        sender_node
+            .send_token_with_options(&sender, &receiver, 1_000, Token::Eth, options)
+            .await?;
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if !receives.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 1);
+        let urgent = &receives[0].tx;
+        assert_eq!(urgent.priority, TxPriority::Urgent);
+        assert_eq!(
+            urgent.fee_preference.map(|fees| fees.tier),
+            Some(FeeTier::High)
+        );
+        Ok(())
+    }
+
+    // both nodes abort the transfer once the deadline of the sender passes unconfirmed
+    #[tokio::test]
+    async fn transfers_expire_past_the_sender_deadline() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        let no_time = TransferOptions {
+            ttl_secs: Some(0),
+            ..Default::default()
+        };
+        assert!(matches!(
+            sender_node
+                .send_token_with_options(&sender, &receiver, 1_000, Token::Eth, no_time)
+                .await,
+            Err(VaneClientError::Node(VaneRpcError::InvalidParams { .. }))
+        ));
+
+        let options = TransferOptions {
+            ttl_secs: Some(1),
+            ..Default::default()
+        };
+        sender_node
+            .send_token_with_options(&sender, &receiver, 1_000, Token::Eth, options)
+            .await?;
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if !receives.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 1);
+        assert!(receives[0].tx.deadline.is_some());
+
+        // the deadline is checked every `DEADLINE_SWEEP_INTERVAL`
+        tokio::time::sleep(DEADLINE_SWEEP_INTERVAL).await;
+        let expired = pending_with_status(sender_node, TxStatus::Expired).await?;
+        assert_eq!(expired.tx_nonce, receives[0].tx.tx_nonce);
+        assert!(receiver_node
+            .pending_receives(None, None, None)
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn diagnoses_tell_where_a_transfer_waits() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+        assert!(sender_node.diagnose_tx(H256::repeat_byte(7)).await.is_err());
+
+        let tx_id = sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        // waiting on the receiver once its node acknowledged the request
+        let mut stage = None;
+        for _ in 0..50 {
+            stage = Some(sender_node.diagnose_tx(tx_id).await?.stage);
+            if stage == Some(TxStage::AwaitingReceiver) {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(stage, Some(TxStage::AwaitingReceiver));
+        let diagnosis = sender_node.diagnose_tx(tx_id).await?;
+        assert!(!diagnosis.stuck);
+        assert!(!diagnosis.actions.is_empty());
+
+        let receives = receiver_node.pending_receives(None, None, None).await?;
+        assert_eq!(receives.len(), 1);
+        let on_receiver = receiver_node.diagnose_tx(receives[0].tx.tx_id()).await?;
+        assert_eq!(on_receiver.stage, TxStage::AwaitingYourConfirmation);
+        Ok(())
+    }
+
+    // a transfer waiting on the receiver is driven again from its attestation request only
+    #[tokio::test]
+    async fn retries_resend_the_attestation_request() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+        assert!(sender_node
+            .retry_tx(H256::repeat_byte(7), None)
+            .await
+            .is_err());
+
+        let tx_id = sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        assert!(sender_node
+            .retry_tx(tx_id, Some(RetryStage::Broadcast))
+            .await
+            .is_err());
+        assert_eq!(
+            sender_node.retry_tx(tx_id, None).await?,
+            RetryStage::Attestation
+        );
+        let mut stage = None;
+        for _ in 0..50 {
+            stage = Some(sender_node.diagnose_tx(tx_id).await?.stage);
+            if stage == Some(TxStage::AwaitingReceiver) {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(stage, Some(TxStage::AwaitingReceiver));
+
+        // the receiver answers the request, it has nothing to drive again
+        let receives = receiver_node.pending_receives(None, None, None).await?;
+        assert!(!receives.is_empty());
+        assert!(receiver_node
+            .retry_tx(receives[0].tx.tx_id(), None)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    // a transfer never mixes environments, neither on the sender node nor on the receiver one
+    #[tokio::test]
+    async fn transfers_across_environments_are_refused() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn_with(2, |i, builder| match i {
+            0 => builder.environment(NetworkEnvironment::Testnet),
+            _ => builder,
+        })
+        .await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let receiver = PrivateKeySigner::random().address().to_string();
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+        let sender = PrivateKeySigner::random().address().to_string();
+
+        let options = TransferOptions {
+            environment: Some(NetworkEnvironment::Mainnet),
+            ..Default::default()
+        };
+        let mismatch = sender_node
+            .send_token_with_options(&sender, &receiver, 1_000, Token::Eth, options)
+            .await;
+        assert!(matches!(
+            mismatch,
+            Err(VaneClientError::Node(
+                VaneRpcError::EnvironmentMismatch { .. }
+            ))
+        ));
+        // the known token contracts are mainnet ones
+        assert!(sender_node
+            .send_token(&sender, &receiver, 1_000, Token::UsdcEth)
+            .await
+            .is_err());
+
+        // the sepolia transfer reaches the mainnet receiver node, which declines it
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let declined = pending_with_status(sender_node, TxStatus::RecvAddrFailed).await?;
+        assert_eq!(declined.environment, NetworkEnvironment::Testnet);
+        assert!(receiver_node
+            .pending_receives(None, None, None)
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    // the push gateway gets the device token and the queue length, nothing of the request
+    #[tokio::test]
+    async fn push_gateway_is_notified_with_the_device_token() -> Result<(), anyhow::Error> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let gateway = PushGateway::new(format!("http://{}/push", listener.local_addr()?))?;
+        let notification = PushNotification {
+            token: "device-token".into(),
+            peer_id: "12D3KooWMobile".into(),
+            queued: 2,
+        };
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await?;
+            let mut received = vec![0; 4096];
+            let mut read = 0;
+            while !received[..read].ends_with(b"}") {
+                match stream.read(&mut received[read..]).await? {
+                    0 => break,
+                    n => read += n,
+                }
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .await?;
+            Ok::<_, anyhow::Error>(String::from_utf8_lossy(&received[..read]).to_string())
+        });
+
+        gateway.notify(&notification).await?;
+        let request =
+            tokio::time::timeout(tokio::time::Duration::from_secs(10), server).await???;
+        assert!(request.starts_with("POST /push"));
+        assert!(request.contains(r#""token":"device-token""#));
+        assert!(request.contains(r#""peerId":"12D3KooWMobile""#));
+        assert!(request.contains(r#""queued":2"#));
+        Ok(())
+    }
+
+    // the approval system answers before submission, no answer denies unless failing open
+    #[tokio::test]
+    async fn approval_webhook_decides_before_submission() -> Result<(), anyhow::Error> {
+        let timeout = std::time::Duration::from_secs(2);
+        assert!(ApprovalWebhook::new("http://approvals.example.com", timeout, false).is_err());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let webhook = ApprovalWebhook::new(
+            format!("http://{}/approve", listener.local_addr()?),
+            timeout,
+            false,
+        )?;
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await?;
+            let mut received = vec![0; 4096];
+            let mut read = 0;
+            while !received[..read].ends_with(b"}") {
+                match stream.read(&mut received[read..]).await? {
+                    0 => break,
+                    n => read += n,
+                }
+            }
+            let answer = r#"{"decision":"deny","reason":"over the desk limit"}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{answer}",
+                answer.len()
+            );
+            stream.write_all(response.as_bytes()).await?;
+            Ok::<_, anyhow::Error>(String::from_utf8_lossy(&received[..read]).to_string())
+        });
+
+        let sender = PrivateKeySigner::random().address().to_string();
+        let receiver = PrivateKeySigner::random().address().to_string();
+        let tx = TxStateMachine::builder()
+            .eth()
+            .sender(&sender)
+            .receiver(&receiver)
+            .amount(1_000)
+            .reference("INV-2024-031")
+            .build()?;
+        assert_eq!(
+            webhook.approve(&tx).await,
+            Err(VaneRpcError::ApprovalDenied {
+                reason: "over the desk limit".to_string()
+            })
+        );
+        let request =
+            tokio::time::timeout(tokio::time::Duration::from_secs(10), server).await???;
+        assert!(request.starts_with("POST /approve"));
+        assert!(request.contains(r#""amount":"1000""#));
+        assert!(request.contains(r#""reference":"INV-2024-031""#));
+
+        // nothing listens anymore
+        let unanswered = format!("http://{}/approve", {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+            listener.local_addr()?
+        });
+        let closed = ApprovalWebhook::new(unanswered.clone(), timeout, false)?;
+        assert!(matches!(
+            closed.approve(&tx).await,
+            Err(VaneRpcError::ApprovalDenied { .. })
+        ));
+        let open = ApprovalWebhook::new(unanswered, timeout, true)?;
+        assert!(open.approve(&tx).await.is_ok());
+        Ok(())
+    }
+
+    // the risk provider scores the receivers at genesis, under the minimum score they are refused
+    #[tokio::test]
+    async fn receivers_under_the_minimum_risk_score_are_refused() -> Result<(), anyhow::Error> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/risk", listener.local_addr()?);
+        // screened once directly and once by the sender node
+        tokio::spawn(async move {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().await?;
+                let mut received = vec![0; 4096];
+                let mut read = 0;
+                while !received[..read].ends_with(b"}") {
+                    match stream.read(&mut received[read..]).await? {
+                        0 => break,
+                        n => read += n,
+                    }
+                }
+                let answer = r#"{"score":20,"labels":["mixer"],"jurisdiction":"KP"}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-length: {}\r\n\r\n{answer}",
+                    answer.len()
+                );
+                stream.write_all(response.as_bytes()).await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        });
+        let timeout = std::time::Duration::from_secs(2);
+        let provider = Arc::new(HttpRiskProvider::new(url, None, timeout)?);
+
+        let sender = PrivateKeySigner::random().address().to_string();
+        let receiver = PrivateKeySigner::random().address().to_string();
+        let tx = TxStateMachine::builder()
+            .eth()
+            .sender(&sender)
+            .receiver(&receiver)
+            .amount(1_000)
+            .build()?;
+        let recorded = RiskScreening::new(provider.clone(), RiskRequirements::default())
+            .screen(&tx)
+            .await?
+            .ok_or(anyhow!("receiver not assessed"))?;
+        assert_eq!(recorded.provider, "127.0.0.1");
+        assert_eq!(recorded.score, 20);
+        assert_eq!(recorded.labels, vec!["mixer".to_string()]);
+        assert_eq!(recorded.jurisdiction.as_deref(), Some("KP"));
+
+        let testnet = TestNet::spawn_with(2, |i, builder| match i {
+            0 => builder.risk_provider(provider.clone()).min_risk_score(50),
+            _ => builder,
+        })
+        .await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+        let refused = sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await;
+        assert!(matches!(
+            refused,
+            Err(VaneClientError::Node(VaneRpcError::RiskCheckFailed { .. }))
+        ));
+        assert!(receiver_node
+            .pending_receives(None, None, None)
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    // a receiver node that can't be dialed fails the tx right away instead of after a fixed wait
+    #[tokio::test]
+    async fn undialable_receivers_fail_the_tx_as_unreachable() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(1).await?;
+        let client = &testnet.node(0).client;
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+
+        // registered to discovery by a node no longer listening
+        let offline = libp2p::identity::Keypair::generate_ed25519()
+            .public()
+            .to_peer_id();
+        let fields = Fields {
+            multi_addr: Some(format!("/ip4/127.0.0.1/tcp/{}/p2p/{offline}", free_port()?)),
+            peer_id: Some(offline.to_base58()),
+            account_id1: Some(receiver.address().to_string()),
+            ..Fields::default()
+        };
+        testnet
+            .discovery
+            .create_peer(AirtableRequestBody::new(fields))
+            .await?;
+
+        let mut updates = client.subscribe_updates().await?;
+        client
+            .send_token(
+                sender.address().to_string(),
+                receiver.address().to_string(),
+                1_000,
+                Token::Eth,
+            )
+            .await?;
+        let update = tokio::time::timeout(DIAL_TIMEOUT * 2, updates.next())
+            .await?
+            .ok_or(anyhow!("update subscription closed"))??;
+        assert!(matches!(update.status, TxStatus::ReceiverUnreachable(_)));
+        Ok(())
+    }
+
+    // the receiver sees the sender display name with its proof checked by the receiver node
+    #[tokio::test]
+    async fn receivers_see_verified_sender_profiles() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender_address, receiver) =
+            (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        let message = SenderProfile::message("alice", &sender_address);
+        let profile_signed_by = |signer: &PrivateKeySigner| -> Result<_, anyhow::Error> {
+            Ok(TransferOptions {
+                sender_profile: Some(SenderProfile {
+                    display_name: "alice".to_string(),
+                    signature: Vec::from(signer.sign_message_sync(message.as_bytes())?),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        };
+        let forged = sender_node
+            .send_token_with_options(
+                &sender_address,
+                &receiver,
+                1_000,
+                Token::Eth,
+                profile_signed_by(&PrivateKeySigner::random())?,
+            )
+            .await;
+        assert!(matches!(
+            forged,
+            Err(VaneClientError::Node(VaneRpcError::InvalidParams { .. }))
+        ));
+
+        sender_node
+            .send_token_with_options(
+                &sender_address,
+                &receiver,
+                1_000,
+                Token::Eth,
+                profile_signed_by(&sender)?,
+            )
+            .await?;
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if !receives.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        let profile = receives[0]
+            .tx
+            .sender_profile
+            .clone()
+            .ok_or(anyhow!("sender profile missing"))?;
+        assert_eq!(profile.display_name, "alice");
+        assert!(profile.verified);
+        assert_eq!(profile.past_interactions, 0);
+        Ok(())
+    }
+
+    // attestations stored with the registry record show as badges on nodes trusting their issuer
+    #[tokio::test]
+    async fn trusted_issuer_attestations_show_as_badges() -> Result<(), anyhow::Error> {
+        let issuer = PrivateKeySigner::random();
+        let issuer_account = issuer.address().to_string();
+        let testnet = TestNet::spawn_with(2, |i, builder| match i {
+            0 => builder.trusted_issuer(issuer_account.clone(), "Acme"),
+            _ => builder,
+        })
+        .await?;
+        let (trusting_node, attested_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let account = PrivateKeySigner::random().address().to_string();
+        attested_node
+            .register("attested", account.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        let attest = |signer: &PrivateKeySigner, account: &str| -> Result<_, anyhow::Error> {
+            let mut attestation = IdentityAttestation {
+                account: account.to_string(),
+                issuer: issuer_account.clone(),
+                claim: "employee of Acme".to_string(),
+                issued_at: 1,
+                expires_at: None,
+                signature: vec![],
+            };
+            attestation.signature =
+                Vec::from(signer.sign_message_sync(attestation.message().as_bytes())?);
+            Ok(attestation)
+        };
+        let forged = attest(&PrivateKeySigner::random(), &account)?;
+        assert!(attested_node.add_attestation(forged).await.is_err());
+        let stranger = PrivateKeySigner::random().address().to_string();
+        let not_registered = attest(&issuer, &stranger)?;
+        assert!(attested_node.add_attestation(not_registered).await.is_err());
+
+        attested_node
+            .add_attestation(attest(&issuer, &account)?)
+            .await?;
+        let badges = trusting_node.badges(&account).await?;
+        assert_eq!(badges.len(), 1);
+        assert_eq!(badges[0].issuer_name, "Acme");
+        assert_eq!(badges[0].claim, "employee of Acme");
+        // the issuer is not trusted by the attested node itself
+        assert!(attested_node.badges(&account).await?.is_empty());
+        Ok(())
+    }
+
+    // transfers of a token the receiver does not accept are refused before the tx is built
+    #[tokio::test]
+    async fn transfers_of_unaccepted_tokens_are_refused() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let receiver = PrivateKeySigner::random().address().to_string();
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+        let stranger = PrivateKeySigner::random().address().to_string();
+        assert!(receiver_node
+            .set_accepted_assets(&stranger, vec![Token::UsdcEth])
+            .await
+            .is_err());
+        // tokens of other networks than the one of the account
+        assert!(receiver_node
+            .set_accepted_assets(&receiver, vec![Token::UsdcSol])
+            .await
+            .is_err());
+        receiver_node
+            .set_accepted_assets(&receiver, vec![Token::UsdcEth])
+            .await?;
+        assert_eq!(
+            sender_node.accepted_assets(&receiver).await?,
+            vec![Token::UsdcEth]
+        );
+
+        let sender = PrivateKeySigner::random().address().to_string();
+        let refused = sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await;
+        assert!(matches!(
+            refused,
+            Err(VaneClientError::Node(VaneRpcError::AssetNotAccepted { .. }))
+        ));
+
+        // an empty list accepts any token again
+        receiver_node.set_accepted_assets(&receiver, vec![]).await?;
+        assert!(sender_node.accepted_assets(&receiver).await?.is_empty());
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        Ok(())
+    }
+
+    // a validation plugin of the operator denies transfers before the receiver is asked to attest
+    #[tokio::test]
+    async fn plugins_deny_transfers_before_attestation() -> Result<(), anyhow::Error> {
+        let plugin = wat::parse_str(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 0) "{\"decision\":\"deny\",\"reason\":\"sanctioned receiver\"}")
+                (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+                (func (export "pre_attestation") (param i32 i32) (result i64) (i64.const 50)))"#,
+        )?;
+        let dir = std::env::temp_dir().join(format!("vane-plugins-{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("sanctions.wasm");
+        std::fs::write(&path, plugin)?;
+
+        let testnet = TestNet::spawn_with(2, |i, builder| match i {
+            0 => builder.plugin(path.clone()),
+            _ => builder,
+        })
+        .await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let receiver = PrivateKeySigner::random().address().to_string();
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+        let sender = PrivateKeySigner::random().address().to_string();
+
+        let denied = sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await;
+        match denied {
+            Err(VaneClientError::Node(VaneRpcError::PluginDenied { plugin, reason })) => {
+                assert_eq!(plugin, "sanctions");
+                assert_eq!(reason, "sanctioned receiver");
+            }
+            other => panic!("transfer not denied by the plugin: {other:?}"),
+        }
+        assert!(receiver_node
+            .pending_receives(None, None, None)
+            .await?
+            .is_empty());
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    // travel rule data reaches the receiver node sealed to it and stays readable on the sender
+    #[tokio::test]
+    async fn travel_rule_data_is_shared_with_the_receiver_node() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(2).await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let receiver = PrivateKeySigner::random().address().to_string();
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+        let sender = PrivateKeySigner::random().address().to_string();
+        let data = TravelRuleData {
+            originator: TravelRuleParty {
+                name: "Alice Doe".to_string(),
+                account: sender.clone(),
+                country_of_residence: Some("DE".to_string()),
+                ..Default::default()
+            },
+            beneficiary: TravelRuleParty {
+                name: "Acme GmbH".to_string(),
+                account: receiver.clone(),
+                ..Default::default()
+            },
+            originating_vasp: Some(Vasp {
+                name: "Exchange A".to_string(),
+                lei: Some("5299000J2N45DDNE4Y28".to_string()),
+            }),
+            beneficiary_vasp: None,
+        };
+
+        // the beneficiary has to hold the receiver account
+        let mut mismatched = data.clone();
+        mismatched.beneficiary.account = sender.clone();
+        let options = TransferOptions {
+            travel_rule: Some(mismatched),
+            ..Default::default()
+        };
+        let refused = sender_node
+            .send_token_with_options(&sender, &receiver, 1_000, Token::Eth, options)
+            .await;
+        assert!(matches!(
+            refused,
+            Err(VaneClientError::Node(VaneRpcError::InvalidParams { .. }))
+        ));
+
+        let options = TransferOptions {
+            travel_rule: Some(data.clone()),
+            ..Default::default()
+        };
+        let tx_id = sender_node
+            .send_token_with_options(&sender, &receiver, 1_000, Token::Eth, options)
+            .await?;
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            if !receives.is_empty() {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 1);
+        // clients only ever get the opened data
+        assert_eq!(receives[0].tx.travel_rule, None);
+        assert_eq!(receives[0].tx.tx_id(), tx_id);
+        assert_eq!(receiver_node.travel_rule(tx_id).await?, Some(data.clone()));
+        assert_eq!(sender_node.travel_rule(tx_id).await?, Some(data));
+
+        let plain = sender_node
+            .send_token(&sender, &receiver, 2_000, Token::Eth)
+            .await?;
+        assert_eq!(sender_node.travel_rule(plain).await?, None);
+        Ok(())
+    }
+
+    async fn scrape_metrics(address: SocketAddr, api_key: &str) -> Result<String, anyhow::Error> {
+        let mut stream = tokio::net::TcpStream::connect(address).await?;
+        let request = format!(
+            "GET /metrics HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {api_key}\r\n\
+             Connection: close\r\n\r\n"
+        );
+        stream.write_all(request.as_bytes()).await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    // a hosted node runs a node per tenant behind one gateway, api keys only reach the node of
+    // their tenant and the metrics are labelled with the tenant
+    #[tokio::test]
+    async fn hosted_tenants_are_isolated_by_api_key() -> Result<(), anyhow::Error> {
+        let discovery = LocalDiscovery::new();
+        let tenant_node = || -> Result<MainServiceWorkerBuilder, anyhow::Error> {
+            Ok(MainServiceWorkerBuilder::new()
+                .ports(free_port()?, free_port()?)
+                .discovery(Arc::new(discovery.clone()))
+                .no_telemetry())
+        };
+        let dir = std::env::temp_dir().join(format!("vane-tenants-{}", rand::random::<u64>()));
+        let acme_keys = HashMap::from([("acme-admin".to_string(), Role::Admin)]);
+        let globex_keys = HashMap::from([
+            ("globex-admin".to_string(), Role::Admin),
+            ("globex-viewer".to_string(), Role::Viewer),
+        ]);
+        let handle = HostedNode::new(&dir, format!("127.0.0.1:{}", free_port()?).parse()?)
+            .tenant("acme", acme_keys, tenant_node()?)
+            .tenant("globex", globex_keys, tenant_node()?)
+            .operator_key("operator")
+            .start()
+            .await?;
+        assert!(dir.join("acme").join(TENANT_DB_FILE).exists());
+        assert!(dir.join("globex").join(TENANT_DB_FILE).exists());
+
+        let url = format!("http://{}", handle.rpc_address);
+        let acme = VaneClient::connect_http_with_api_key(&url, "acme-admin")?;
+        let globex = VaneClient::connect_http_with_api_key(&url, "globex-admin")?;
+        let rules = vec![PolicyRule {
+            id: "large-eth".to_string(),
+            description: "eth transfers above 1000 wei are blocked".to_string(),
+            conditions: vec![Condition::AmountAbove {
+                token: Token::Eth,
+                amount: 1_000,
+            }],
+            action: PolicyAction::Block,
+        }];
+        acme.set_policies(rules.clone()).await?;
+        assert_eq!(acme.policies().await?, rules);
+        assert!(globex.policies().await?.is_empty());
+
+        // the role of a tenant key applies on its node, other keys reach no tenant
+        let viewer = VaneClient::connect_http_with_api_key(&url, "globex-viewer")?;
+        assert!(matches!(
+            viewer.set_policies(vec![]).await,
+            Err(VaneClientError::Node(VaneRpcError::AccessDenied { .. }))
+        ));
+        let operator = VaneClient::connect_http_with_api_key(&url, "operator")?;
+        assert!(operator.policies().await.is_err());
+        assert!(VaneClient::connect_http(&url)?.policies().await.is_err());
+
+        // tenants transfer to each other like any two nodes
+        let receiver = PrivateKeySigner::random().address().to_string();
+        globex
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+        let sender = PrivateKeySigner::random().address().to_string();
+        acme.send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let mut received = vec![];
+        for _ in 0..50 {
+            received = globex.peer_stats().await?;
+            if received.iter().any(|peer| peer.requests_received > 0) {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        let acme_peer = &received[0].peer_id;
+
+        let all = scrape_metrics(handle.rpc_address, "operator").await?;
+        assert!(all.starts_with("HTTP/1.1 200"));
+        assert!(all.contains("# TYPE vane_lock_acquisitions_total counter"));
+        assert_eq!(
+            all.matches("# TYPE vane_peer_requests_received_total counter")
+                .count(),
+            1
+        );
+        let globex_received = format!(
+            "vane_peer_requests_received_total{{tenant=\"globex\",peer=\"{acme_peer}\"}} 1"
+        );
+        assert!(all.contains(&globex_received));
+        assert!(all.contains("vane_peer_requests_sent_total{tenant=\"acme\","));
+
+        let own = scrape_metrics(handle.rpc_address, "globex-viewer").await?;
+        assert!(own.contains(&globex_received));
+        assert!(!own.contains("tenant=\"acme\""));
+        assert!(!own.contains("vane_lock_acquisitions_total"));
+        assert!(scrape_metrics(handle.rpc_address, "unknown")
+            .await?
+            .starts_with("HTTP/1.1 401"));
+
+        handle.stop();
+        let _ = std::fs::remove_dir_all(&dir);
+        Ok(())
+    }
+
+    // redundant nodes run one identity, the standby takes it over and re-announces its address
+    // once the lease of the stopped leader expired
+    #[tokio::test]
+    async fn standby_takes_the_p2p_identity_over() -> Result<(), anyhow::Error> {
+        let discovery = LocalDiscovery::new();
+        let store = Arc::new(LocalCluster::new());
+        let keypair = libp2p::identity::Keypair::generate_ed25519();
+        let redundant_node = || -> Result<MainServiceWorkerBuilder, anyhow::Error> {
+            Ok(MainServiceWorkerBuilder::new()
+                .db_url(db::IN_MEMORY_DB_URL)
+                .ports(free_port()?, free_port()?)
+                .discovery(Arc::new(discovery.clone()))
+                .keypair(keypair.clone())
+                .failover(store.clone(), std::time::Duration::from_secs(3))
+                .no_telemetry())
+        };
+        let announced = |discovery: LocalDiscovery, multi_addr: String| async move {
+            for _ in 0..100 {
+                let peers = discovery.list_all_peers().await?;
+                if peers
+                    .iter()
+                    .all(|peer| peer.multi_addr.as_deref() == Some(multi_addr.as_str()))
+                {
+                    return Ok::<_, anyhow::Error>(true);
+                }
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            }
+            Ok(false)
+        };
+
+        let leader = redundant_node()?.build().await?.start().await?;
+        let leader_failover = leader.worker.failover.clone().unwrap();
+        let leader_addr = leader.worker.p2p_worker.lock().await.url.to_string();
+        assert!(announced(discovery.clone(), leader_addr).await?);
+        assert!(leader_failover.is_leader());
+
+        let standby = redundant_node()?.build().await?.start().await?;
+        let standby_failover = standby.worker.failover.clone().unwrap();
+        let standby_addr = standby.worker.p2p_worker.lock().await.url.to_string();
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        assert!(!standby_failover.is_leader());
+
+        leader.stop();
+        assert!(announced(discovery.clone(), standby_addr).await?);
+        assert!(standby_failover.is_leader());
+
+        standby.stop();
+        Ok(())
+    }
+
+    /// bus keeping the published events in memory
+    #[derive(Clone, Default)]
+    struct RecordingBus(Arc<std::sync::Mutex<Vec<(String, Vec<u8>)>>>);
+
+    #[async_trait::async_trait]
+    impl EventBus for RecordingBus {
+        async fn publish(&self, key: &str, payload: Vec<u8>) -> Result<(), anyhow::Error> {
+            self.0.lock().unwrap().push((key.to_string(), payload));
+            Ok(())
+        }
+    }
+
+    // every recorded transition of a tx reaches the event bus in order, in the lifecycle schema
+    #[tokio::test]
+    async fn tx_transitions_are_published_on_the_event_bus() -> Result<(), anyhow::Error> {
+        let bus = RecordingBus::default();
+        let testnet = TestNet::spawn_with(2, |i, builder| match i {
+            0 => builder.event_bus(Arc::new(bus.clone())),
+            _ => builder.dust_threshold(Token::Eth, 10_000),
+        })
+        .await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        pending_with_status(sender_node, TxStatus::RecvAddrFailed).await?;
+
+        let mut events = vec![];
+        for _ in 0..50 {
+            events = bus
+                .0
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(key, payload)| Ok((key.clone(), serde_json::from_slice(payload)?)))
+                .collect::<Result<Vec<(String, TxLifecycleEvent)>, anyhow::Error>>()?;
+            if events.last().is_some_and(|(_, event)| event.is_final) {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        let (_, genesis) = events.first().ok_or(anyhow!("no event published"))?;
+        let (_, declined) = events.last().ok_or(anyhow!("no event published"))?;
+        assert_eq!(genesis.status, "Genesis");
+        assert_eq!(declined.status, "RecvAddrFailed");
+        assert!(declined.is_final);
+        for (key, event) in &events {
+            assert_eq!(key, &format!("{:?}", genesis.tx_id));
+            assert_eq!(event.tx_id, genesis.tx_id);
+            assert_eq!(event.schema_version, TX_LIFECYCLE_SCHEMA_VERSION);
+            assert_eq!(event.amount, "1000");
+            assert!(event.receiver.eq_ignore_ascii_case(&receiver));
+        }
+        Ok(())
+    }
+
+    // the grpc api serves the json-rpc methods under the same api keys and roles
+    #[tokio::test]
+    async fn grpc_api_mirrors_the_json_rpc_api() -> Result<(), anyhow::Error> {
+        use node::grpc::proto::{self, vane_client::VaneClient as GrpcClient};
+        let handle = MainServiceWorkerBuilder::new()
+            .db_url(db::IN_MEMORY_DB_URL)
+            .ports(free_port()?, free_port()?)
+            .discovery(Arc::new(LocalDiscovery::new()))
+            .api_key("dashboard", Role::Viewer)
+            .api_key("ops", Role::Operator)
+            .api_key("root", Role::Admin)
+            .grpc_address(([127, 0, 0, 1], 0).into())
+            .no_telemetry()
+            .build()
+            .await?
+            .start()
+            .await?;
+        let grpc_address = handle.grpc_address.ok_or(anyhow!("grpc api not served"))?;
+        let mut client = GrpcClient::connect(format!("http://{grpc_address}")).await?;
+        fn with_key<T>(key: &str, message: T) -> tonic::Request<T> {
+            let mut request = tonic::Request::new(message);
+            let value = format!("Bearer {key}").parse().unwrap();
+            request.metadata_mut().insert("authorization", value);
+            request
+        }
+
+        let account = PrivateKeySigner::random().address().to_string();
+        client
+            .register(with_key(
+                "root",
+                proto::RegisterRequest {
+                    name: "alice".to_string(),
+                    account_id: account.clone(),
+                    network: "Ethereum".to_string(),
+                },
+            ))
+            .await?;
+        let peers = client
+            .list_peers(with_key("dashboard", proto::Empty {}))
+            .await?
+            .into_inner()
+            .peers;
+        assert!(peers.iter().any(|peer| peer
+            .account_ids
+            .iter()
+            .any(|id| id.eq_ignore_ascii_case(&account))));
+
+        let transfer = proto::InitiateTransactionRequest {
+            sender: account.clone(),
+            receiver: PrivateKeySigner::random().address().to_string(),
+            amount: "1000".to_string(),
+            token: "Eth".to_string(),
+            network: "Ethereum".to_string(),
+            ..Default::default()
+        };
+        let denied = client
+            .initiate_transaction(with_key("dashboard", transfer.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(denied.code(), tonic::Code::PermissionDenied);
+        let details: VaneRpcError = serde_json::from_slice(denied.details())?;
+        assert!(matches!(details, VaneRpcError::AccessDenied { role, .. } if role == "operator"));
+        let unauthenticated = client
+            .initiate_transaction(tonic::Request::new(transfer.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(unauthenticated.code(), tonic::Code::Unauthenticated);
+        let invalid = proto::InitiateTransactionRequest {
+            amount: "a lot".to_string(),
+            ..transfer.clone()
+        };
+        let invalid = client
+            .initiate_transaction(with_key("ops", invalid))
+            .await
+            .unwrap_err();
+        assert_eq!(invalid.code(), tonic::Code::InvalidArgument);
+
+        let tx_id = client
+            .initiate_transaction(with_key("ops", transfer))
+            .await?
+            .into_inner()
+            .tx_id;
+        assert!(tx_id.starts_with("0x"));
+
+        handle.stop();
+        Ok(())
+    }
+
+    // the rest gateway serves transfers and history on the rpc port under the json-rpc roles
+    #[tokio::test]
+    async fn rest_gateway_initiates_and_looks_up_transfers() -> Result<(), anyhow::Error> {
+        let handle = MainServiceWorkerBuilder::new()
+            .db_url(db::IN_MEMORY_DB_URL)
+            .ports(free_port()?, free_port()?)
+            .discovery(Arc::new(LocalDiscovery::new()))
+            .api_key("dashboard", Role::Viewer)
+            .api_key("ops", Role::Operator)
+            .no_telemetry()
+            .build()
+            .await?
+            .start()
+            .await?;
+        let url = format!("http://{}", handle.rpc_address);
+        let http = reqwest::Client::new();
+        let transfer = serde_json::json!({
+            "sender": PrivateKeySigner::random().address().to_string(),
+            "receiver": PrivateKeySigner::random().address().to_string(),
+            "amount": "1000",
+            "token": "Eth",
+            "network": "Ethereum",
+            "reference": "INV-7",
         });
 
-        // ========================================================================//
-        // Now use the separate clone for later operations
-        let peer_id_2 = worker_2_for_later.p2p_worker.lock().await.node_id;
-        let multi_addr_2 = worker_2_for_later.p2p_worker.lock().await.url.clone();
+        let denied = http
+            .post(format!("{url}/transfers"))
+            .bearer_auth("dashboard")
+            .json(&transfer)
+            .send()
+            .await?;
+        assert_eq!(denied.status(), reqwest::StatusCode::FORBIDDEN);
+        let error: serde_json::Value = denied.json().await?;
+        let error: VaneRpcError = serde_json::from_value(error["data"].clone())?;
+        assert!(matches!(error, VaneRpcError::AccessDenied { role, .. } if role == "operator"));
 
-        main_worker_1
-            .p2p_network_service
-            .lock()
-            .await
-            .dial_to_peer_id(multi_addr_2.clone(), &peer_id_2)
+        let created = http
+            .post(format!("{url}/transfers"))
+            .bearer_auth("ops")
+            .header("Idempotency-Key", "order-7")
+            .json(&transfer)
+            .send()
             .await?;
+        assert_eq!(created.status(), reqwest::StatusCode::CREATED);
+        let location = created.headers()["location"].to_str()?.to_string();
+        let tx_id = created.json::<serde_json::Value>().await?["txId"]
+            .as_str()
+            .ok_or(anyhow!("no tx id"))?
+            .to_string();
+        assert_eq!(location, format!("/transfers/{tx_id}"));
+        let retried = http
+            .post(format!("{url}/transfers"))
+            .bearer_auth("ops")
+            .header("Idempotency-Key", "order-7")
+            .json(&transfer)
+            .send()
+            .await?;
+        assert_eq!(retried.json::<serde_json::Value>().await?["txId"], tx_id);
 
-        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+        // the transition is recorded once the node picked the tx up
+        let mut looked_up = None;
+        for _ in 0..50 {
+            let answer = http
+                .get(format!("{url}{location}"))
+                .bearer_auth("dashboard")
+                .send()
+                .await?;
+            if answer.status() == reqwest::StatusCode::OK {
+                looked_up = Some(answer.json::<serde_json::Value>().await?);
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        let looked_up = looked_up.ok_or(anyhow!("transfer {tx_id} not found"))?;
+        assert_eq!(looked_up["tx"]["reference"], "INV-7");
+        assert_eq!(looked_up["diagnosis"]["txId"], tx_id);
 
-        main_worker_1
-            .p2p_network_service
-            .lock()
-            .await
-            .send_request(
-                Arc::new(Mutex::new(test_state.sent_msg.clone())),
-                peer_id_2,
-                multi_addr_2,
-            )
+        let unknown = http
+            .get(format!("{url}/transfers/0x{}", "00".repeat(32)))
+            .bearer_auth("dashboard")
+            .send()
+            .await?;
+        assert_eq!(unknown.status(), reqwest::StatusCode::NOT_FOUND);
+        let history = http
+            .get(format!("{url}/history?query=INV-7"))
+            .bearer_auth("dashboard")
+            .send()
             .await?;
+        assert_eq!(history.status(), reqwest::StatusCode::OK);
+        assert!(history.json::<serde_json::Value>().await?.is_array());
 
-        // ========================================================================//
+        let openapi: serde_json::Value = http
+            .get(format!("{url}/openapi.json"))
+            .bearer_auth("dashboard")
+            .send()
+            .await?
+            .json()
+            .await?;
+        assert!(openapi["paths"]["/transfers/{txId}"]["get"].is_object());
+        assert!(openapi["components"]["schemas"]["TxDiagnosis"].is_object());
 
+        handle.stop();
         Ok(())
     }
 
+    // one graphql query fetches what several rpc calls would, only on nodes serving it
     #[tokio::test]
-    async fn airtable_test() -> Result<(), anyhow::Error> {
-        let _ =log_setup();
+    async fn graphql_queries_history_stats_and_peers() -> Result<(), anyhow::Error> {
+        let start = |graphql: bool| async move {
+            let builder = MainServiceWorkerBuilder::new()
+                .db_url(db::IN_MEMORY_DB_URL)
+                .ports(free_port()?, free_port()?)
+                .discovery(Arc::new(LocalDiscovery::new()))
+                .api_key("dashboard", Role::Viewer)
+                .no_telemetry();
+            let builder = if graphql { builder.graphql() } else { builder };
+            builder.build().await?.start().await
+        };
+        let handle = start(true).await?;
+        let url = format!("http://{}/graphql", handle.rpc_address);
+        let http = reqwest::Client::new();
+        let query = |query: &str| {
+            http.post(&url)
+                .bearer_auth("dashboard")
+                .json(&serde_json::json!({ "query": query }))
+                .send()
+        };
 
-        let client = Airtable::new().await?;
-        let mut peer = Fields::default();
-        let req_body = AirtableRequestBody::new(peer.clone());
-        let record_data = client.create_peer(req_body).await?;
-        assert_eq!(record_data.fields, peer.clone());
+        let answer: serde_json::Value = query(
+            "{ transfers(filter: { text: \"INV-7\", limit: 10 }) { txHash amount success } \
+             stats { fees { network totalFee } } peers { recordId traffic { connected } } }",
+        )
+        .await?
+        .json()
+        .await?;
+        assert!(answer["errors"].is_null(), "{answer}");
+        assert!(answer["data"]["transfers"].is_array());
+        assert!(answer["data"]["stats"]["fees"].is_array());
+        assert!(answer["data"]["peers"].is_array());
 
-        // try updating
-        peer.account_id1 = Some("4456".to_string());
-        let new_req_body = PostRecord::new(peer.clone());
-        let updated_record = client.update_peer(new_req_body, record_data.id).await?;
-        // try fetching
-        assert_eq!(updated_record.fields, peer);
+        let invalid: serde_json::Value =
+            query("{ transfers(filter: { network: \"Mars\" }) { txHash } }")
+                .await?
+                .json()
+                .await?;
+        assert_eq!(
+            invalid["errors"][0]["extensions"]["code"],
+            primitives::errors::INVALID_PARAMS_CODE
+        );
+        let sdl = http
+            .get(&url)
+            .bearer_auth("dashboard")
+            .send()
+            .await?
+            .text()
+            .await?;
+        assert!(sdl.contains("type Query"));
+        handle.stop();
 
-        client.delete_all().await?;
+        // the endpoint is opt in
+        let handle = start(false).await?;
+        let url = format!("http://{}/graphql", handle.rpc_address);
+        let answer = http
+            .post(&url)
+            .bearer_auth("dashboard")
+            .json(&serde_json::json!({ "query": "{ peers { recordId } }" }))
+            .send()
+            .await?
+            .text()
+            .await?;
+        assert!(!answer.contains("\"data\""));
+        handle.stop();
         Ok(())
     }
 
-    // Retry connection with backoff
-    async fn connect_with_retry(url: &str, max_attempts: u32) -> Result<Client, Error> {
-        let mut attempts = 0;
-        loop {
-            match WsClientBuilder::default().build(url).await {
-                Ok(client) => return Ok(client),
-                Err(e) => {
-                    attempts += 1;
-                    if attempts >= max_attempts {
-                        return Err(anyhow!("error failed to connect caused by; {e:?}"));
-                    }
-                    println!("Connection attempt {} failed, retrying in 1s...", attempts);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                }
+    // account scoped api keys only see the tx updates of their accounts
+    #[tokio::test]
+    async fn scoped_api_keys_only_see_their_tx_updates() -> Result<(), anyhow::Error> {
+        let discovery = LocalDiscovery::new();
+        let (alice, bob) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (alice, bob) = (alice.address().to_string(), bob.address().to_string());
+        let handle = MainServiceWorkerBuilder::new()
+            .db_url(db::IN_MEMORY_DB_URL)
+            .ports(free_port()?, free_port()?)
+            .discovery(Arc::new(discovery.clone()))
+            .api_key("alice", Role::Operator)
+            .api_key("bob", Role::Operator)
+            .api_key_account("alice", alice.clone())
+            .api_key_account("bob", bob.clone())
+            .api_key("auditor", Role::Admin)
+            .api_key_account("auditor", alice.clone())
+            .no_telemetry()
+            .build()
+            .await?
+            .start()
+            .await?;
+        let url = format!("http://{}", handle.rpc_address);
+        let http = reqwest::Client::new();
+
+        let subscribe = |key: &'static str| {
+            http.get(format!("{url}/tx-updates"))
+                .bearer_auth(key)
+                .send()
+        };
+        let mut alice_updates = subscribe("alice").await?;
+        let mut bob_updates = subscribe("bob").await?;
+        assert_eq!(alice_updates.headers()["content-type"], "text/event-stream");
+
+        // both transfers fail as the receiver node no longer listens
+        let receiver = PrivateKeySigner::random().address().to_string();
+        let offline = libp2p::identity::Keypair::generate_ed25519()
+            .public()
+            .to_peer_id();
+        let fields = Fields {
+            multi_addr: Some(format!("/ip4/127.0.0.1/tcp/{}/p2p/{offline}", free_port()?)),
+            peer_id: Some(offline.to_base58()),
+            account_id1: Some(receiver.clone()),
+            ..Fields::default()
+        };
+        discovery
+            .create_peer(AirtableRequestBody::new(fields))
+            .await?;
+        for (key, sender) in [("alice", &alice), ("bob", &bob)] {
+            let created = http
+                .post(format!("{url}/transfers"))
+                .bearer_auth(key)
+                .json(&serde_json::json!({
+                    "sender": sender,
+                    "receiver": receiver,
+                    "amount": "1000",
+                    "token": "Eth",
+                    "network": "Ethereum",
+                }))
+                .send()
+                .await?;
+            assert_eq!(created.status(), reqwest::StatusCode::CREATED);
+        }
+
+        for (updates, account) in [(&mut alice_updates, &alice), (&mut bob_updates, &bob)] {
+            // every update until the stream goes quiet is one of the account
+            let mut wait = DIAL_TIMEOUT * 2;
+            let mut seen = 0;
+            while let Ok(event) = tokio::time::timeout(wait, updates.chunk()).await {
+                let event = event?.ok_or(anyhow!("update stream closed"))?;
+                let event = std::str::from_utf8(&event)?;
+                let tx: TxStateMachine = serde_json::from_str(
+                    event
+                        .strip_prefix("data: ")
+                        .ok_or(anyhow!("not an event {event}"))?
+                        .trim_end(),
+                )?;
+                assert!(tx.sender_address.to_string().eq_ignore_ascii_case(account));
+                seen += 1;
+                wait = std::time::Duration::from_secs(1);
+            }
+            assert!(seen > 0, "no update for {account}");
+        }
+
+        // the reads of a scoped key only return the txs of its accounts as well
+        let pending: serde_json::Value = http
+            .post(&url)
+            .bearer_auth("alice")
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "fetchPendingTxUpdates",
+                "params": [],
+            }))
+            .send()
+            .await?
+            .json()
+            .await?;
+        let pending: Vec<TxStateMachine> = serde_json::from_value(pending["result"].clone())?;
+        assert!(!pending.is_empty());
+        assert!(pending
+            .iter()
+            .all(|tx| tx.sender_address.to_string().eq_ignore_ascii_case(&alice)));
+
+        // even admin keys can't open a websocket session seeing every update once scoped
+        let mut headers = jsonrpsee::http_client::HeaderMap::new();
+        headers.insert("authorization", "Bearer auditor".parse()?);
+        let ws = WsClientBuilder::default()
+            .set_headers(headers)
+            .build(format!("ws://{}", handle.rpc_address))
+            .await;
+        assert!(ws.is_err());
+        handle.stop();
+        Ok(())
+    }
+
+    // spam controls decline requests before they reach the receiver inbox
+    #[tokio::test]
+    async fn unwanted_attestation_requests_are_declined() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn_with(2, |i, builder| match i {
+            1 => builder
+                .dust_threshold(Token::Eth, 10_000)
+                .max_requests_per_sender(2, std::time::Duration::from_secs(3600)),
+            _ => builder,
+        })
+        .await?;
+        let (sender_node, receiver_node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let (sender, receiver) = (sender.address().to_string(), receiver.address().to_string());
+        receiver_node
+            .register("receiver", receiver.clone(), ChainSupported::Ethereum)
+            .await?;
+
+        // dust
+        sender_node
+            .send_token(&sender, &receiver, 1_000, Token::Eth)
+            .await?;
+        let declined = pending_with_status(sender_node, TxStatus::RecvAddrFailed).await?;
+        assert_eq!(declined.amount, 1_000);
+
+        // the second request is within the frequency, the third is not
+        sender_node
+            .send_token(&sender, &receiver, 20_000, Token::Eth)
+            .await?;
+        sender_node
+            .send_token(&sender, &receiver, 30_000, Token::Eth)
+            .await?;
+        let mut receives = vec![];
+        for _ in 0..50 {
+            receives = receiver_node.pending_receives(None, None, None).await?;
+            let third_declined = sender_node
+                .pending_updates()
+                .await?
+                .iter()
+                .any(|tx| tx.amount == 30_000 && tx.status == TxStatus::RecvAddrFailed);
+            if !receives.is_empty() && third_declined {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(receives.len(), 1);
+        assert_eq!(receives[0].tx.amount, 20_000);
+        Ok(())
+    }
+
+    // with an approver designated for all txs, sender confirmed txs are only submitted once it signed off
+    #[tokio::test]
+    async fn second_approver_holds_sender_confirmed_txs() -> Result<(), anyhow::Error> {
+        let chain = MockChain::new(ChainSupported::Ethereum);
+        let (owner, approver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let approver_account = approver.address().to_string();
+        let testnet = TestNet::spawn_with(1, |_, builder| {
+            builder
+                .chain_client(Arc::new(chain.clone()))
+                .second_approver(approver_account.clone(), true)
+        })
+        .await?;
+        let client = &testnet.node(0).client;
+
+        let spender = PrivateKeySigner::random().address().to_string();
+        client
+            .approve_token(
+                owner.address().to_string(),
+                spender,
+                1_000_000,
+                Token::UsdcEth,
+            )
+            .await?;
+        let tx = pending_with_status(client, TxStatus::NetConfirmed).await?;
+        let call_payload = tx.call_payload.ok_or(anyhow!("call payload not created"))?;
+        let signature = owner.sign_hash_sync(&B256::new(call_payload))?;
+        client.confirm_as_sender(tx, Vec::from(signature)).await?;
+
+        let held = pending_with_status(client, TxStatus::AwaitingSecondApproval).await?;
+        assert!(chain.submissions().await.is_empty());
+
+        // only the designated approver can sign off
+        let message = held.second_approval_message();
+        let stranger = PrivateKeySigner::random().sign_message_sync(message.as_bytes())?;
+        assert!(client
+            .confirm_as_approver(held.clone(), Vec::from(stranger))
+            .await
+            .is_err());
+
+        let signature = approver.sign_message_sync(message.as_bytes())?;
+        client
+            .confirm_as_approver(held, Vec::from(signature))
+            .await?;
+        for _ in 0..50 {
+            if !chain.submissions().await.is_empty() {
+                break;
             }
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         }
+        assert_eq!(chain.submissions().await.len(), 1);
+        Ok(())
     }
+
     #[tokio::test]
     async fn transaction_full_cycle_test() -> Result<(), anyhow::Error> {
         let _ = log_setup();
@@ -278,21 +3152,13 @@ mod e2e_tests {
 
         let network_id: String = ChainSupported::Ethereum.into();
 
-        let port = rand::thread_rng().gen_range(0..=u16::MAX);
+        let port = rand::thread_rng().gen_range(1000..=u16::MAX - 1000);
 
         // ----------------------------------------------------------------------------------------//
         // ============================================================================
-        let main_worker_1 = MainServiceWorker::e2e_new(port, "../db/test2.db").await?;
-        let main_worker_2 = MainServiceWorker::e2e_new(port + 890, "../db/test3.db").await?;
-        let airtable_client = main_worker_1
-            .tx_rpc_worker
-            .lock()
-            .await
-            .clone()
-            .airtable_client
-            .lock()
-            .await
-            .clone();
+        let discovery = LocalDiscovery::new();
+        let main_worker_1 = MainServiceWorker::e2e_new(port, discovery.clone()).await?;
+        let main_worker_2 = MainServiceWorker::e2e_new(port + 890, discovery).await?;
 
         let cloned_worker_1 = main_worker_1.clone();
         let worker_handle_1 =
@@ -395,7 +3261,7 @@ mod e2e_tests {
                         println!("\n in sub_handle 2 watching tx: {tx_state:?} \n");
                         match tx_state.status {
                             TxStatus::Genesis => {
-                                let msg = tx_state.clone().receiver_address;
+                                let msg = tx_state.receiver_address.to_string();
                                 let msg_len = msg.len().to_string();
                                 let signable_msg = format!("{ETH_SIG_MSG_PREFIX}{msg_len}{msg}");
                                 let pre_hash = keccak256(&signable_msg.as_bytes()[..]);
@@ -455,6 +3321,7 @@ mod e2e_tests {
                                     .await
                                     .expect("failed to confirm sender");
                             }
+                            TxStatus::DeliveredToReceiver => {}
                             _ => panic!("in sender's side txStatus is invalid"),
                         }
                     }
@@ -479,13 +3346,11 @@ mod e2e_tests {
         tx_params.insert("Ethereum".to_string()).unwrap();
 
         let _res_txn = rpc_client_1
-            .request::<(), _>("initiateTransaction", tx_params)
+            .request::<H256, _>("initiateTransaction", tx_params)
             .await?;
 
         // put timeout for the test
         tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
-        // clean up
-        airtable_client.delete_all().await?;
         Ok(())
     }
 
@@ -501,16 +3366,8 @@ mod e2e_tests {
         let network_id: String = ChainSupported::Ethereum.into();
 
         // ============================================================================
-        let main_worker_1 = MainServiceWorker::e2e_new(3000, "../db/test2.db").await?;
-        let airtable_client = main_worker_1
-            .tx_rpc_worker
-            .lock()
-            .await
-            .clone()
-            .airtable_client
-            .lock()
-            .await
-            .clone();
+        let port = rand::thread_rng().gen_range(1000..=u16::MAX);
+        let main_worker_1 = MainServiceWorker::e2e_new(port, LocalDiscovery::new()).await?;
 
         let cloned_worker_1 = main_worker_1.clone();
         let worker_handle_1 =
@@ -598,6 +3455,9 @@ mod e2e_tests {
                             TxStatus::ReceiverNotRegistered => {
                                 println!(" Recv not registered: {tx_state:?}")
                             }
+                            TxStatus::DeliveredToReceiver => {
+                                println!(" Delivered to the receiver node: {tx_state:?}")
+                            }
                             _ => panic!("in sender's side txStatus is invalid"),
                         }
                     }
@@ -622,24 +3482,804 @@ mod e2e_tests {
         tx_params.insert("Ethereum".to_string()).unwrap();
 
         let _res_txn = rpc_client_1
-            .request::<(), _>("initiateTransaction", tx_params)
+            .request::<H256, _>("initiateTransaction", tx_params)
             .await?;
 
         // put timeout for the test
         tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
-        // clean up
-        airtable_client.delete_all().await?;
         Ok(())
     }
 
     #[tokio::test]
     async fn telemetry_test() -> Result<(), anyhow::Error> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let telemetry_url = format!("ws://{}", listener.local_addr()?);
+        let handle = MainServiceWorkerBuilder::new()
+            .db_url(db::IN_MEMORY_DB_URL)
+            .ports(free_port()?, free_port()?)
+            .discovery(Arc::new(LocalDiscovery::new()))
+            .chains([primitives::data_structure::ChainSupported::Ethereum])
+            .telemetry_url(telemetry_url)
+            .build()
+            .await?
+            .start()
+            .await?;
+
+        let (stream, _) =
+            tokio::time::timeout(tokio::time::Duration::from_secs(10), listener.accept()).await??;
+        let mut socket = tokio_tungstenite::accept_async(stream).await?;
+        let mut messages = vec![];
+        while messages.len() < 2 {
+            let message = tokio::time::timeout(tokio::time::Duration::from_secs(10), socket.next())
+                .await?
+                .ok_or(anyhow!("telemetry connection closed"))??;
+            messages.push(serde_json::from_str::<serde_json::Value>(
+                message.to_text()?,
+            )?);
+        }
+
+        assert_eq!(messages[0]["payload"]["msg"], "system.connected");
+        assert_eq!(
+            messages[0]["payload"]["chains"],
+            serde_json::json!(["Ethereum"])
+        );
+        assert_eq!(messages[1]["payload"]["msg"], "system.interval");
+        assert_eq!(messages[1]["payload"]["txs_initiated"], 0);
+        assert_eq!(messages[1]["payload"]["error_rate"], 0.0);
+        // same anonymous session id, nothing identifying the node
+        assert_eq!(messages[0]["id"], messages[1]["id"]);
+        let node_id = handle.worker.p2p_worker.lock().await.node_id.to_string();
+        assert!(messages
+            .iter()
+            .all(|message| !message.to_string().contains(&node_id)));
+
+        handle.stop();
+        Ok(())
+    }
+
+    // sender side tx processing against a chain client, receiver attests, sender signs the call payload
+    // and the tx is submitted, returns the tx hash
+    async fn eth_transfer(
+        chain_client: Arc<dyn ChainClient>,
+        sender: &PrivateKeySigner,
+        receiver: &PrivateKeySigner,
+        amount: u128,
+    ) -> Result<[u8; 32], anyhow::Error> {
+        let tx = signed_eth_transfer(chain_client.clone(), sender, receiver, amount).await?;
+        TxProcessingWorker::new([chain_client]).submit_tx(&tx).await
+    }
+
+    // eth transfer attested by the receiver and signed by the sender, ready to be submitted
+    async fn signed_eth_transfer(
+        chain_client: Arc<dyn ChainClient>,
+        sender: &PrivateKeySigner,
+        receiver: &PrivateKeySigner,
+        amount: u128,
+    ) -> Result<SenderConfirmedTx, anyhow::Error> {
+        let mut tx_processing = TxProcessingWorker::new([chain_client]);
+
+        let tx = TxStateMachine::builder()
+            .eth()
+            .sender(sender.address().to_string())
+            .receiver(receiver.address().to_string())
+            .amount(amount)
+            .build()?;
+
+        // receiver attestation
+        let msg = tx.receiver_address.to_string();
+        let signable_msg = format!("{ETH_SIG_MSG_PREFIX}{}{msg}", msg.len());
+        let recv_sig = receiver.sign_hash_sync(&keccak256(signable_msg.as_bytes()))?;
+        let tx = GenesisTx::try_from(tx)?.recv_signed(Vec::from(recv_sig));
+        tx_processing.validate_receiver_sender_address(&tx, "Receiver")?;
+        let mut tx = tx.confirmation_passed();
+        tx_processing.create_tx(&mut tx).await?;
+
+        // sender confirmation
+        let call_payload = tx.call_payload.ok_or(anyhow!("call payload not created"))?;
+        let sender_sig = sender.sign_hash_sync(&B256::new(call_payload))?;
+        let tx = tx.sender_signed(Vec::from(sender_sig));
+        tx_processing.validate_receiver_sender_address(&tx, "Sender")?;
+        tx_processing.validate_multi_id(&tx)?;
+        Ok(tx)
+    }
+
+    #[tokio::test]
+    async fn user_flow_eth_works() -> Result<(), anyhow::Error> {
+        let chain = MockChain::new(ChainSupported::Ethereum);
+        let sender = PrivateKeySigner::random();
+        let receiver = PrivateKeySigner::random();
+
+        let tx_hash_1 = eth_transfer(Arc::new(chain.clone()), &sender, &receiver, 100_000).await?;
+        let tx_hash_2 = eth_transfer(Arc::new(chain.clone()), &sender, &receiver, 100_000).await?;
+        // same transfer twice still yields distinct txs as the sender nonce moved
+        assert_ne!(tx_hash_1, tx_hash_2);
+
+        let submissions = chain.submissions().await;
+        assert_eq!(submissions.len(), 2);
+        assert_eq!(submissions[0].tx_hash, tx_hash_1);
+        assert_eq!(
+            submissions[0].receiver.to_string(),
+            receiver.address().to_string()
+        );
+        assert_eq!(submissions[0].amount, 100_000);
+        assert_eq!(submissions[0].route, SubmissionRoute::Public);
+        Ok(())
+    }
+
+    // token transfers are contract calls, the sender sees the decoded call instead of raw calldata
+    #[tokio::test]
+    async fn contract_transfer_intent_is_decoded() -> Result<(), anyhow::Error> {
+        let receiver = PrivateKeySigner::random().address();
+        let receiver_address: VaneAddress = receiver.to_string().parse()?;
+        let (call_data, intent) = node::calldata::contract_transfer(
+            Token::UsdtEth,
+            ChainSupported::Ethereum,
+            &receiver_address,
+            1_500_000,
+        )?
+        .ok_or(anyhow!("usdt is a contract token"))?;
+        assert_eq!(&call_data[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+        assert!(intent.starts_with(&format!("transfer 1.5 USDT to {receiver}")));
+
+        let native = node::calldata::contract_transfer(
+            Token::Eth,
+            ChainSupported::Ethereum,
+            &receiver_address,
+            1,
+        )?;
+        assert!(native.is_none());
+
+        let unknown = node::calldata::describe_call(
+            ChainSupported::Ethereum,
+            receiver,
+            0,
+            &[0xde, 0xad, 0xbe, 0xef],
+        );
+        assert!(unknown.starts_with("undecoded call to unknown contract"));
+        Ok(())
+    }
+
+    // approvals are bounded `approve` calls, unlimited ones are only built when asked for
+    #[tokio::test]
+    async fn approvals_are_decoded_and_bounded() -> Result<(), anyhow::Error> {
+        let owner = PrivateKeySigner::random().address().to_string();
+        let spender = PrivateKeySigner::random().address();
+        let approval = |amount: u128| {
+            TxStateMachine::builder()
+                .eth()
+                .sender(&owner)
+                .receiver(spender.to_string())
+                .token(Token::UsdcEth)
+                .amount(amount)
+                .approval()
+                .build()
+        };
+
+        let (call_data, intent) = node::calldata::contract_call(&approval(2_000_000)?)?
+            .ok_or(anyhow!("approvals are contract calls"))?;
+        assert_eq!(&call_data[..4], &[0x09, 0x5e, 0xa7, 0xb3]);
+        assert!(intent.starts_with(&format!("allow {spender} to spend 2 USDC")));
+
+        let (_, intent) = node::calldata::contract_call(&approval(UNLIMITED_APPROVAL)?)?
+            .ok_or(anyhow!("approvals are contract calls"))?;
+        assert!(intent.contains("unlimited USDC"));
+
+        let native = TxStateMachine::builder()
+            .eth()
+            .sender(&owner)
+            .receiver(spender.to_string())
+            .amount(1)
+            .approval()
+            .build();
+        assert!(native.is_err());
+        Ok(())
+    }
+
+    // sending native value to a contract without a payable fallback is flagged before attestation
+    #[tokio::test]
+    async fn contract_recipients_are_flagged() -> Result<(), anyhow::Error> {
+        let chain = MockChain::new(ChainSupported::Ethereum);
+        let sender = PrivateKeySigner::random().address().to_string();
+        let contract = PrivateKeySigner::random().address().to_string();
+        chain.deploy_contract(&contract, false).await;
+
+        let tx = TxStateMachine::builder()
+            .eth()
+            .sender(&sender)
+            .receiver(&contract)
+            .amount(1)
+            .build()?;
+        assert_eq!(
+            chain.recipient_warnings(&tx).await?,
+            vec![
+                TxWarning::ContractRecipient,
+                TxWarning::ContractRejectsValue
+            ]
+        );
         Ok(())
     }
 
     // user creating an account, and sending a wrong eth address transaction reverts
     #[tokio::test]
     async fn user_flow_eth_wrong_address_reverts() -> Result<(), anyhow::Error> {
+        let chain = MockChain::new(ChainSupported::Ethereum);
+        let sender = PrivateKeySigner::random();
+        let receiver = PrivateKeySigner::random();
+        chain.revert_to(receiver.address().to_string()).await;
+
+        let res = eth_transfer(Arc::new(chain.clone()), &sender, &receiver, 100_000).await;
+        assert!(res.is_err());
+        assert!(chain.submissions().await.is_empty());
+        Ok(())
+    }
+
+    // a chain rejecting every submission is paused instead of failing the txs queued after
+    #[tokio::test]
+    async fn repeated_submission_failures_open_the_chain_breaker() -> Result<(), anyhow::Error> {
+        let chain: Arc<dyn ChainClient> = Arc::new(MockChain::new(ChainSupported::Ethereum));
+        let breakers = CircuitBreakers::default();
+        let (queues, mut outcomes) =
+            SubmissionQueues::spawn([chain], 16, breakers.clone(), &HashMap::new());
+
+        // unsigned txs are rejected by the chain
+        for tx_nonce in 0..CircuitBreakers::FAILURES_BEFORE_OPEN {
+            assert_eq!(
+                breakers.state(ChainSupported::Ethereum),
+                BreakerState::Closed
+            );
+            let tx = TxStateMachine {
+                network: ChainSupported::Ethereum,
+                status: TxStatus::SenderConfirmed,
+                tx_nonce,
+                ..Default::default()
+            };
+            queues.submit(SenderConfirmedTx::try_from(tx)?).await?;
+            let outcome = outcomes.recv().await.ok_or(anyhow!("outcomes closed"))?;
+            assert!(outcome.result.is_err());
+        }
+        assert_eq!(breakers.state(ChainSupported::Ethereum), BreakerState::Open);
+        assert!(breakers
+            .render_metrics()
+            .contains("vane_chain_breaker_state{network=\"Ethereum\"} 1"));
+
+        // the next tx waits in the queue for a passed probe
+        let tx = TxStateMachine {
+            network: ChainSupported::Ethereum,
+            status: TxStatus::SenderConfirmed,
+            ..Default::default()
+        };
+        queues.submit(SenderConfirmedTx::try_from(tx)?).await?;
+        let waiting =
+            tokio::time::timeout(tokio::time::Duration::from_secs(1), outcomes.recv()).await;
+        assert!(waiting.is_err());
+        queues.abort();
+        Ok(())
+    }
+
+    // txs the independent provider never sees are reported submitted with a warning, not failed
+    #[tokio::test]
+    async fn submissions_unseen_by_the_independent_provider_are_unverified(
+    ) -> Result<(), anyhow::Error> {
+        let chain = MockChain::new(ChainSupported::Ethereum);
+        let sender = PrivateKeySigner::random();
+        let receiver = PrivateKeySigner::random();
+        let chain_client: Arc<dyn ChainClient> = Arc::new(chain.clone());
+        let breakers = CircuitBreakers::default();
+
+        // the independent provider is a chain the tx never reaches
+        let verifiers = HashMap::from([(
+            ChainSupported::Ethereum,
+            Arc::new(MockChain::new(ChainSupported::Ethereum)) as Arc<dyn ChainClient>,
+        )]);
+        let (queues, mut outcomes) =
+            SubmissionQueues::spawn([chain_client.clone()], 16, breakers.clone(), &verifiers);
+        let tx = signed_eth_transfer(chain_client.clone(), &sender, &receiver, 100_000).await?;
+        queues.submit(tx).await?;
+        // the verification does not hold the queue up
+        let other_sender = PrivateKeySigner::random();
+        let tx =
+            signed_eth_transfer(chain_client.clone(), &other_sender, &receiver, 100_000).await?;
+        queues.submit(tx).await?;
+        tokio::time::timeout(VERIFY_TIMEOUT / 2, async {
+            while chain.submissions().await.len() < 2 {
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            }
+        })
+        .await?;
+        // reported submitted with its hash, flagged, without tripping the breaker
+        let outcome = outcomes.recv().await.ok_or(anyhow!("outcomes closed"))?;
+        assert!(outcome.unverified);
+        assert_eq!(outcome.result?, chain.submissions().await[0].tx_hash);
+        assert_eq!(
+            breakers.state(ChainSupported::Ethereum),
+            BreakerState::Closed
+        );
+        let outcome = outcomes.recv().await.ok_or(anyhow!("outcomes closed"))?;
+        assert!(outcome.unverified);
+        queues.abort();
+
+        // a provider seeing the tx confirms it right away
+        let verifiers = HashMap::from([(ChainSupported::Ethereum, chain_client.clone())]);
+        let (queues, mut outcomes) = SubmissionQueues::spawn(
+            [chain_client.clone()],
+            16,
+            CircuitBreakers::default(),
+            &verifiers,
+        );
+        let tx = signed_eth_transfer(chain_client, &sender, &receiver, 100_000).await?;
+        queues.submit(tx).await?;
+        let outcome = outcomes.recv().await.ok_or(anyhow!("outcomes closed"))?;
+        assert!(!outcome.unverified);
+        assert_eq!(outcome.result?, chain.submissions().await[2].tx_hash);
+        queues.abort();
+        Ok(())
+    }
+
+    // dev chains only submit funded transfers, the faucet funds senders through the node rpc
+    #[tokio::test]
+    async fn dev_chains_fund_senders_through_the_faucet() -> Result<(), anyhow::Error> {
+        let chain = MockChain::dev(ChainSupported::Ethereum);
+        let sender = PrivateKeySigner::random();
+        let receiver = PrivateKeySigner::random();
+        let (sender_account, receiver_account) =
+            (sender.address().to_string(), receiver.address().to_string());
+
+        let unfunded = eth_transfer(Arc::new(chain.clone()), &sender, &receiver, 100_000).await;
+        assert!(unfunded.is_err());
+        assert_eq!(chain.faucet(&sender_account, 150_000).await?, 150_000);
+        eth_transfer(Arc::new(chain.clone()), &sender, &receiver, 100_000).await?;
+        assert_eq!(chain.balance(&sender_account).await, 50_000);
+        assert_eq!(chain.balance(&receiver_account).await, 100_000);
+
+        let testnet = TestNet::spawn_with(2, |i, builder| match i {
+            0 => builder.dev(),
+            _ => builder,
+        })
+        .await?;
+        let (dev_node, node) = (&testnet.node(0).client, &testnet.node(1).client);
+        dev_node
+            .faucet(&sender_account, 1_000, ChainSupported::Solana)
+            .await?;
+        assert_eq!(
+            dev_node
+                .faucet(&sender_account, 0, ChainSupported::Solana)
+                .await?,
+            1_000
+        );
+        assert!(node
+            .faucet(&sender_account, 1_000, ChainSupported::Ethereum)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    // the receiver address format narrows the chains down, only the chains of the node are offered
+    #[tokio::test]
+    async fn chains_are_detected_from_the_receiver_address() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn_with(2, |i, builder| match i {
+            0 => builder.chains([ChainSupported::Ethereum, ChainSupported::Solana]),
+            _ => builder,
+        })
+        .await?;
+        let (evm_only, node) = (&testnet.node(0).client, &testnet.node(1).client);
+        let evm = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+        assert_eq!(
+            node.detect_chains(evm).await?,
+            vec![ChainSupported::Ethereum, ChainSupported::Bnb]
+        );
+        assert_eq!(
+            evm_only.detect_chains(evm).await?,
+            vec![ChainSupported::Ethereum]
+        );
+        assert_eq!(
+            node.detect_chains("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY")
+                .await?,
+            vec![ChainSupported::Polkadot]
+        );
+        // a broken checksum matches no format
+        assert!(node
+            .detect_chains("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD")
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    // frontends hand human amounts of their locale to the node and get back the raw amount
+    #[tokio::test]
+    async fn amounts_are_parsed_and_formatted_in_the_locale() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn(1).await?;
+        let node = &testnet.node(0).client;
+
+        let french = Some(AmountLocale::Tag("fr-FR".to_string()));
+        let parsed = node
+            .parse_amount("1 234,56", Token::UsdtEth, french.clone())
+            .await?;
+        assert_eq!(parsed.units, 1_234_560_000);
+        assert_eq!(parsed.display, "1 234,56");
+        assert_eq!(parsed.symbol, "USDT");
+        let english = node
+            .format_amount(parsed.units, Token::UsdtEth, None)
+            .await?;
+        assert_eq!(english.display, "1,234.56");
+
+        let swiss = AmountLocale::Separators(Separators {
+            decimal: '.',
+            group: Some('\''),
+        });
+        let parsed = node
+            .parse_amount("12'000.5", Token::Eth, Some(swiss))
+            .await?;
+        assert_eq!(parsed.units, 12_000_500_000_000_000_000_000);
+        // an english grouping read in french is refused, not taken for a smaller amount
+        assert!(node
+            .parse_amount("1,234.56", Token::UsdtEth, french)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    // provisioned test accounts are funded, registered to the node and kept out of discovery
+    #[tokio::test]
+    async fn dev_nodes_provision_funded_test_accounts() -> Result<(), anyhow::Error> {
+        let testnet = TestNet::spawn_with(2, |i, builder| match i {
+            0 => builder.dev(),
+            _ => builder,
+        })
+        .await?;
+        let (dev_node, node) = (&testnet.node(0).client, &testnet.node(1).client);
+
+        let accounts = dev_node
+            .provision_test_accounts(2, ChainSupported::Ethereum, 1_000_000)
+            .await?;
+        assert_eq!(accounts.len(), 2);
+        for account in &accounts {
+            assert_eq!(account.balance, 1_000_000);
+            let signer = account.private_key.parse::<PrivateKeySigner>()?;
+            assert_eq!(signer.address().to_string(), account.account);
+            let record = node
+                .list_peers()
+                .await?
+                .into_iter()
+                .find(|peer| peer.account_ids.contains(&account.account));
+            assert!(record.is_none());
+        }
+        // the peer record has four account slots
+        assert!(dev_node
+            .provision_test_accounts(3, ChainSupported::Ethereum, 0)
+            .await
+            .is_err());
+        // mainnet nodes never hand out keys
+        assert!(node
+            .provision_test_accounts(1, ChainSupported::Ethereum, 0)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    // run with `cargo test --features e2e -- --ignored`, requires `anvil` on PATH
+    #[tokio::test]
+    #[ignore]
+    async fn user_flow_eth_anvil_works() -> Result<(), anyhow::Error> {
+        let anvil = Anvil::spawn()?;
+        let chain = EvmChainClient::new(ChainSupported::Ethereum, &anvil.endpoint())?;
+        let sender = anvil.signer(0)?;
+        let receiver = anvil.signer(1)?;
+
+        let tx_hash = eth_transfer(Arc::new(chain), &sender, &receiver, 100_000).await?;
+
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint().parse()?);
+        let receipt = provider
+            .get_transaction_receipt(B256::new(tx_hash))
+            .await?
+            .ok_or(anyhow!("tx not mined"))?;
+        assert!(receipt.status());
+        Ok(())
+    }
+
+    // transfer signed whole by the wallet of `signer`, eip-2718 encoded
+    fn wallet_signed<T>(signer: &PrivateKeySigner, tx: T) -> Result<Vec<u8>, anyhow::Error>
+    where
+        T: SignableTransaction<Signature>,
+        TxEnvelope: From<Signed<T>>,
+    {
+        let signature = signer.sign_hash_sync(&tx.signature_hash())?;
+        Ok(TxEnvelope::from(tx.into_signed(signature)).encoded_2718())
+    }
+
+    // wallets signing the whole tx hand it back typed, blob txs and unknown types are refused by
+    // their type rather than failing in the rlp decoding
+    #[tokio::test]
+    async fn wallet_signed_txs_are_verified_by_their_type() -> Result<(), anyhow::Error> {
+        let chain_client: Arc<dyn ChainClient> = Arc::new(MockChain::new(ChainSupported::Ethereum));
+        let tx_processing = TxProcessingWorker::new([chain_client.clone()]);
+        let (sender, receiver) = (PrivateKeySigner::random(), PrivateKeySigner::random());
+        let mut tx = signed_eth_transfer(chain_client, &sender, &receiver, 100_000)
+            .await?
+            .into_inner();
+        let transfer = TxEip2930 {
+            chain_id: 1,
+            gas_price: 1_000_000_000,
+            gas_limit: 21_000,
+            to: TxKind::Call(receiver.address()),
+            value: U256::from(100_000),
+            ..Default::default()
+        };
+
+        tx.signed_call_payload = Some(wallet_signed(&sender, transfer.clone())?);
+        tx_processing.validate_receiver_sender_address(&tx, "Sender")?;
+        let legacy = TxLegacy {
+            chain_id: Some(1),
+            gas_price: 1_000_000_000,
+            gas_limit: 21_000,
+            to: TxKind::Call(receiver.address()),
+            value: U256::from(100_000),
+            ..Default::default()
+        };
+        tx.signed_call_payload = Some(wallet_signed(&sender, legacy)?);
+        tx_processing.validate_receiver_sender_address(&tx, "Sender")?;
+        tx.signed_call_payload = Some(wallet_signed(&receiver, transfer.clone())?);
+        assert!(tx_processing
+            .validate_receiver_sender_address(&tx, "Sender")
+            .is_err_and(|err| err.to_string().contains("not signed by the sender")));
+
+        let signed = wallet_signed(&sender, transfer)?;
+        for (tx_type, error) in [
+            (0x03, "blob txs (type 0x03) are not supported"),
+            (0x04, "set code txs (type 0x04) are not supported"),
+            (0x7e, "unknown tx type 0x7e"),
+            (0x80, "neither a tx type nor an rlp list"),
+        ] {
+            tx.signed_call_payload = Some([&[tx_type][..], &signed[1..]].concat());
+            assert!(tx_processing
+                .validate_receiver_sender_address(&tx, "Sender")
+                .is_err_and(|err| err.to_string().contains(error)));
+        }
+        Ok(())
+    }
+
+    // run with `cargo test --features e2e -- --ignored`, requires `anvil` on PATH
+    #[tokio::test]
+    #[ignore]
+    async fn wallet_signed_txs_are_submitted_on_anvil() -> Result<(), anyhow::Error> {
+        let anvil = Anvil::spawn()?;
+        let chain_client: Arc<dyn ChainClient> = Arc::new(EvmChainClient::new(
+            ChainSupported::Ethereum,
+            &anvil.endpoint(),
+        )?);
+        let mut tx_processing = TxProcessingWorker::new([chain_client.clone()]);
+        let (sender, receiver) = (anvil.signer(0)?, anvil.signer(1)?);
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint().parse()?);
+        let mut tx = signed_eth_transfer(chain_client, &sender, &receiver, 100_000)
+            .await?
+            .into_inner();
+        let transfer = TxEip2930 {
+            chain_id: provider.get_chain_id().await?,
+            nonce: provider.get_transaction_count(sender.address()).await?,
+            gas_price: provider.get_gas_price().await?,
+            gas_limit: 21_000,
+            to: TxKind::Call(receiver.address()),
+            value: U256::from(100_000),
+            ..Default::default()
+        };
+
+        // the wallet picks the type, not the transfer the receiver confirmed
+        let redirected = TxEip2930 {
+            to: TxKind::Call(sender.address()),
+            ..transfer.clone()
+        };
+        tx.signed_call_payload = Some(wallet_signed(&sender, redirected)?);
+        let redirected = SenderConfirmedTx::try_from(tx.clone())?;
+        assert!(tx_processing
+            .submit_tx(&redirected)
+            .await
+            .is_err_and(|err| err.to_string().contains("does not send the transfer")));
+
+        tx.signed_call_payload = Some(wallet_signed(&sender, transfer)?);
+        let tx_hash = tx_processing
+            .submit_tx(&SenderConfirmedTx::try_from(tx)?)
+            .await?;
+        let receipt = provider
+            .get_transaction_receipt(B256::new(tx_hash))
+            .await?
+            .ok_or(anyhow!("tx not mined"))?;
+        assert!(receipt.status());
+        Ok(())
+    }
+
+    // run with `cargo test --features e2e -- --ignored`, requires `anvil` on PATH
+    #[tokio::test]
+    #[ignore]
+    async fn smart_account_transfers_are_sent_to_the_bundler() -> Result<(), anyhow::Error> {
+        let anvil = Anvil::spawn()?;
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint().parse()?);
+        // any code makes the sender a smart account, the entry point answers a zero nonce
+        let account = PrivateKeySigner::random().address();
+        let (owner, receiver) = (PrivateKeySigner::random(), anvil.signer(1)?);
+        let set_code = |address: Address, code: &'static [u8]| {
+            provider.raw_request::<_, ()>("anvil_setCode".into(), (address, Bytes::from(code)))
+        };
+        set_code(account, &[0x00]).await?;
+        set_code(ENTRY_POINT, &[0x60, 0x20, 0x60, 0x00, 0xf3]).await?;
+
+        let sent = Arc::new(std::sync::Mutex::new(vec![]));
+        let mut bundler = RpcModule::new(sent.clone());
+        bundler.register_method("eth_estimateUserOperationGas", |_, _| {
+            Ok::<_, CallError>(serde_json::json!({
+                "callGasLimit": "0x186a0",
+                "verificationGasLimit": "0x249f0",
+                "preVerificationGas": "0xc350",
+            }))
+        })?;
+        bundler.register_method("eth_sendUserOperation", |params, sent| {
+            let (user_op, entry_point): (UserOperation, Address) = params.parse()?;
+            let hash = user_op.hash(entry_point, 31337);
+            sent.lock().unwrap().push(user_op);
+            Ok::<_, CallError>(hash)
+        })?;
+        bundler.register_method("eth_getUserOperationReceipt", |params, _| {
+            let (hash,): (B256,) = params.parse()?;
+            Ok::<_, CallError>(serde_json::json!({
+                "success": true,
+                "actualGasCost": "0x5af3107a4000",
+                "actualGasUsed": "0x186a0",
+                "receipt": { "transactionHash": hash, "blockNumber": "0x2" },
+            }))
+        })?;
+        let server = ServerBuilder::default().build("127.0.0.1:0").await?;
+        let bundler_url = format!("http://{}", server.local_addr()?);
+        let _bundler = server.start(bundler)?;
+
+        let chain_client: Arc<dyn ChainClient> = Arc::new(
+            EvmChainClient::new(ChainSupported::Ethereum, &anvil.endpoint())?
+                .with_bundler(&bundler_url)?,
+        );
+        let mut tx_processing = TxProcessingWorker::new([chain_client]);
+        let tx = TxStateMachine::builder()
+            .eth()
+            .sender(account.to_string())
+            .receiver(receiver.address().to_string())
+            .amount(100_000)
+            .build()?;
+        // the receiver attests as for any sender
+        let msg = tx.receiver_address.to_string();
+        let signable_msg = format!("{ETH_SIG_MSG_PREFIX}{}{msg}", msg.len());
+        let recv_sig = receiver.sign_hash_sync(&keccak256(signable_msg.as_bytes()))?;
+        let tx = GenesisTx::try_from(tx)?.recv_signed(Vec::from(recv_sig));
+        tx_processing.validate_receiver_sender_address(&tx, "Receiver")?;
+        let mut tx = tx.confirmation_passed();
+        tx_processing.create_tx(&mut tx).await?;
+
+        // the owner signs the operation hash, the account validates it on submission
+        let call_payload = tx.call_payload.ok_or(anyhow!("call payload not created"))?;
+        let signature = Vec::from(owner.sign_hash_sync(&B256::new(call_payload))?);
+        let tx = tx.sender_signed(signature.clone());
+        assert!(tx_processing
+            .validate_receiver_sender_address(&tx, "Sender")
+            .is_err());
+        tx_processing.validate_sender(&tx).await?;
+        let hash = tx_processing.submit_tx(&tx).await?;
+        assert_eq!(hash, call_payload);
+
+        let user_op = sent.lock().unwrap().pop();
+        let user_op = user_op.ok_or(anyhow!("no user operation sent"))?;
+        assert_eq!(user_op.sender, account);
+        assert_eq!(user_op.signature, Bytes::from(signature));
+        let call = ISmartAccount::executeCall::abi_decode(&user_op.call_data, true)?;
+        assert_eq!(call.dest, receiver.address());
+        assert_eq!(call.value, U256::from(100_000));
+        let receipt = tx_processing
+            .await_receipt(ChainSupported::Ethereum, hash)
+            .await?
+            .ok_or(anyhow!("user operation not included"))?;
+        assert!(receipt.success);
+        assert_eq!(receipt.block_number, 2);
+        assert_eq!(receipt.effective_gas_price, Some(1_000_000_000));
+        Ok(())
+    }
+
+    // run with `cargo test --features e2e -- --ignored`, requires `anvil` on PATH
+    #[tokio::test]
+    #[ignore]
+    async fn paymasters_sponsor_smart_account_transfers() -> Result<(), anyhow::Error> {
+        let anvil = Anvil::spawn()?;
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint().parse()?);
+        let account = PrivateKeySigner::random().address();
+        let (owner, receiver) = (PrivateKeySigner::random(), anvil.signer(1)?);
+        let set_code = |address: Address, code: &'static [u8]| {
+            provider.raw_request::<_, ()>("anvil_setCode".into(), (address, Bytes::from(code)))
+        };
+        set_code(account, &[0x00]).await?;
+        set_code(ENTRY_POINT, &[0x60, 0x20, 0x60, 0x00, 0xf3]).await?;
+
+        let sent = Arc::new(std::sync::Mutex::new(vec![]));
+        let mut bundler = RpcModule::new(sent.clone());
+        bundler.register_method("eth_estimateUserOperationGas", |_, _| {
+            Ok::<_, CallError>(serde_json::json!({
+                "callGasLimit": "0x186a0",
+                "verificationGasLimit": "0x249f0",
+                "preVerificationGas": "0xc350",
+            }))
+        })?;
+        bundler.register_method("eth_sendUserOperation", |params, sent| {
+            let (user_op, entry_point): (UserOperation, Address) = params.parse()?;
+            let hash = user_op.hash(entry_point, 31337);
+            sent.lock().unwrap().push(user_op);
+            Ok::<_, CallError>(hash)
+        })?;
+        // the merchant policy only sponsors the transfers to the receiver
+        let paymaster = Address::repeat_byte(0x77);
+        let mut paymaster_service = RpcModule::new(receiver.address());
+        let methods = [
+            ("pm_getPaymasterStubData", "0x00"),
+            ("pm_getPaymasterData", "0x5a"),
+        ];
+        for (method, data) in methods {
+            paymaster_service.register_method(method, move |params, merchant| {
+                let (_, _, chain_id, context): (UserOperation, Address, String, serde_json::Value) =
+                    params.parse()?;
+                assert_eq!(chain_id, "0x7a69");
+                assert_eq!(context["policyId"], "merchant");
+                assert_eq!(context["receiver"], merchant.to_string());
+                Ok::<_, CallError>(serde_json::json!({
+                    "paymaster": paymaster,
+                    "paymasterData": data,
+                    "paymasterVerificationGasLimit": "0x7530",
+                    "paymasterPostOpGasLimit": "0x2710",
+                    "sponsor": { "name": "Acme" },
+                }))
+            })?;
+        }
+        let mut urls = vec![];
+        let mut servers = vec![];
+        for module in [bundler, paymaster_service] {
+            let server = ServerBuilder::default().build("127.0.0.1:0").await?;
+            urls.push(format!("http://{}", server.local_addr()?));
+            servers.push(server.start(module)?);
+        }
+
+        let context = serde_json::json!({ "policyId": "merchant" });
+        let chain_client: Arc<dyn ChainClient> = Arc::new(
+            EvmChainClient::new(ChainSupported::Ethereum, &anvil.endpoint())?
+                .with_bundler(&urls[0])?
+                .with_paymaster(&urls[1], context)?,
+        );
+        let mut tx_processing = TxProcessingWorker::new([chain_client]);
+        let tx = TxStateMachine::builder()
+            .eth()
+            .sender(account.to_string())
+            .receiver(receiver.address().to_string())
+            .amount(100_000)
+            .build()?;
+        let msg = tx.receiver_address.to_string();
+        let signable_msg = format!("{ETH_SIG_MSG_PREFIX}{}{msg}", msg.len());
+        let recv_sig = receiver.sign_hash_sync(&keccak256(signable_msg.as_bytes()))?;
+        let tx = GenesisTx::try_from(tx)?.recv_signed(Vec::from(recv_sig));
+        let mut tx = tx.confirmation_passed();
+        tx_processing.create_tx(&mut tx).await?;
+
+        // the sender sees who pays the gas before signing
+        let sponsorship = tx.sponsorship.clone();
+        let sponsorship = sponsorship.ok_or(anyhow!("transfer not sponsored"))?;
+        assert_eq!(sponsorship.paymaster, paymaster.to_string());
+        assert_eq!(sponsorship.sponsor.as_deref(), Some("Acme"));
+        // call, verification, pre verification and paymaster gas at the max fee per gas
+        let gas_limit = 100_000 + 150_000 + 50_000 + 30_000 + 10_000;
+        assert_eq!(sponsorship.max_gas_cost % gas_limit, 0);
+
+        let call_payload = tx.call_payload.ok_or(anyhow!("call payload not created"))?;
+        let signature = Vec::from(owner.sign_hash_sync(&B256::new(call_payload))?);
+        let tx = tx.sender_signed(signature);
+        let hash = tx_processing.submit_tx(&tx).await?;
+        assert_eq!(hash, call_payload);
+        let user_op = sent.lock().unwrap().pop();
+        let user_op = user_op.ok_or(anyhow!("no user operation sent"))?;
+        assert_eq!(user_op.paymaster, Some(paymaster));
+        assert_eq!(user_op.paymaster_data, Some(Bytes::from(vec![0x5a])));
+
+        // and the history records the terms with the transfer
+        let record = DbTxStateMachine::of(&tx, hash.to_vec(), true);
+        assert_eq!(record.sponsorship, Some(sponsorship));
         Ok(())
     }
 
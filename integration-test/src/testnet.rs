@@ -0,0 +1,69 @@
+// multi node test network
+// boots nodes in process on ephemeral ports with in-memory dbs and a shared `LocalDiscovery`,
+// each node comes with a connected rpc client
+
+use anyhow::anyhow;
+use node::discovery::LocalDiscovery;
+use node::{MainServiceWorkerBuilder, NodeHandle};
+use std::net::TcpListener;
+use std::sync::Arc;
+use vane_client::jsonrpsee::ws_client::WsClient;
+use vane_client::VaneClient;
+
+/// free local port, released right away so the node can bind it
+pub fn free_port() -> Result<u16, anyhow::Error> {
+    Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+}
+
+pub struct TestNode {
+    pub handle: NodeHandle,
+    pub client: VaneClient<WsClient>,
+}
+
+pub struct TestNet {
+    pub nodes: Vec<TestNode>,
+    /// discovery shared by all nodes
+    pub discovery: LocalDiscovery,
+}
+
+impl TestNet {
+    /// boot `n` interconnected nodes
+    pub async fn spawn(n: usize) -> Result<Self, anyhow::Error> {
+        Self::spawn_with(n, |_, builder| builder).await
+    }
+
+    /// boot `n` interconnected nodes, `configure` can customize each node builder,
+    /// e.g. to inject a `MockChain`
+    pub async fn spawn_with(
+        n: usize,
+        configure: impl Fn(usize, MainServiceWorkerBuilder) -> MainServiceWorkerBuilder,
+    ) -> Result<Self, anyhow::Error> {
+        let discovery = LocalDiscovery::new();
+        let mut nodes = Vec::with_capacity(n);
+        for index in 0..n {
+            let builder = MainServiceWorkerBuilder::new()
+                .db_url(db::IN_MEMORY_DB_URL)
+                .ports(free_port()?, free_port()?)
+                .discovery(Arc::new(discovery.clone()))
+                .no_telemetry();
+            let handle = configure(index, builder).build().await?.start().await?;
+
+            let url = format!("ws://{}", handle.rpc_address);
+            let client = VaneClient::connect_ws(&url).await.map_err(|err| {
+                anyhow!("failed to connect to node {index} at {url}; caused by: {err}")
+            })?;
+            nodes.push(TestNode { handle, client });
+        }
+        Ok(Self { nodes, discovery })
+    }
+
+    pub fn node(&self, index: usize) -> &TestNode {
+        &self.nodes[index]
+    }
+}
+
+impl Drop for TestNet {
+    fn drop(&mut self) {
+        self.nodes.iter().for_each(|node| node.handle.stop());
+    }
+}
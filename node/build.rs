@@ -0,0 +1,6 @@
+// compiles the grpc contract of the node, with a vendored protoc so no system install is needed
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    tonic_build::compile_protos("proto/vane.proto")?;
+    Ok(())
+}
@@ -0,0 +1,48 @@
+// sender side amount limits of the operator
+// transfers below the minimum of their token are refused when staged, amounts carrying more decimals than
+// the operator allows for the token are warned as a likely typo, e.g. a pasted amount in the smallest unit
+
+use primitives::data_structure::{Token, TxKind, TxStateMachine, TxWarning};
+use primitives::errors::VaneRpcError;
+use primitives::tx_builder::format_units;
+use primitives::validation::amount_decimals;
+use std::collections::HashMap;
+
+/// per token limits on the transfer amounts, all disabled by default
+#[derive(Clone, Debug, Default)]
+pub struct AmountLimits {
+    /// per token amount, in the token smallest unit, transfers below it are refused
+    pub minimums: HashMap<Token, u128>,
+    /// per token decimals, transfer amounts with more are warned
+    pub max_decimals: HashMap<Token, u32>,
+}
+
+impl AmountLimits {
+    /// refuse `tx` below the minimum of its token, returns the precision warning
+    pub fn check(&self, tx: &TxStateMachine) -> Result<Option<TxWarning>, VaneRpcError> {
+        if tx.kind != TxKind::Transfer {
+            return Ok(None);
+        }
+        let token = tx.token.unwrap_or(Token::native(tx.network));
+        if let Some(minimum) = self.minimums.get(&token).filter(|min| tx.amount < **min) {
+            Err(VaneRpcError::InvalidParams {
+                reason: format!(
+                    "amount should be at least {} {}",
+                    format_units(*minimum, token.decimals()),
+                    token.symbol()
+                ),
+            })?
+        }
+        let decimals = amount_decimals(tx.amount, token);
+        let warning = self
+            .max_decimals
+            .get(&token)
+            .filter(|max_decimals| decimals > **max_decimals)
+            .map(|max_decimals| TxWarning::ExcessPrecision {
+                token,
+                decimals,
+                max_decimals: *max_decimals,
+            });
+        Ok(warning)
+    }
+}
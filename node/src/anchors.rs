@@ -0,0 +1,195 @@
+// on chain anchors of the account to node bindings
+// an evm account anchors the hash of its binding to the node serving it by sending `anchor` to the
+// registry contract from its own wallet, the node only builds the call. with a registry configured
+// anyone can verify an account is served by the node the off-chain registry claims, and an operator
+// distrusting the off-chain registry refuses transfers to receivers whose binding is not anchored
+
+use crate::calldata::evm_address;
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{Address, B256};
+use alloy::providers::{Provider, ProviderBuilder, ReqwestProvider};
+use alloy::rpc::types::TransactionRequest;
+use alloy::sol;
+use alloy::sol_types::SolCall;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use primitives::address::VaneAddress;
+use primitives::anchors::{binding_hash, AnchorCall, BindingAnchor};
+use primitives::data_structure::{ChainSupported, Discovery, TxKind, TxStateMachine, H256};
+use primitives::errors::VaneRpcError;
+use std::sync::Arc;
+
+sol! {
+    interface IVaneBindingRegistry {
+        function anchor(bytes32 binding) external;
+        function bindingOf(address account) external view returns (bytes32);
+    }
+}
+
+/// registry contract keeping the binding hash anchored by each account
+#[async_trait]
+pub trait BindingRegistry: Send + Sync {
+    /// network the registry contract is deployed on
+    fn network(&self) -> ChainSupported;
+
+    /// address of the registry contract
+    fn contract(&self) -> String;
+
+    /// binding hash anchored by `account`, `None` when it anchored none
+    async fn binding_of(&self, account: &VaneAddress) -> Result<Option<H256>, anyhow::Error>;
+}
+
+/// registry contract read over the json rpc of an evm chain
+pub struct EvmBindingRegistry {
+    network: ChainSupported,
+    contract: Address,
+    provider: ReqwestProvider,
+}
+
+impl EvmBindingRegistry {
+    pub fn new(
+        network: ChainSupported,
+        contract: &str,
+        rpc_url: &str,
+    ) -> Result<Self, anyhow::Error> {
+        if !matches!(network, ChainSupported::Ethereum | ChainSupported::Bnb) {
+            Err(anyhow!(
+                "binding registries are evm contracts, not on {network:?}"
+            ))?
+        }
+        let contract = contract
+            .parse()
+            .map_err(|err| anyhow!("invalid binding registry contract; caused by: {err}"))?;
+        let rpc_url = rpc_url
+            .parse()
+            .map_err(|err| anyhow!("{network:?} rpc url parse error: {err}"))?;
+        Ok(Self {
+            network,
+            contract,
+            provider: ProviderBuilder::new().on_http(rpc_url),
+        })
+    }
+}
+
+#[async_trait]
+impl BindingRegistry for EvmBindingRegistry {
+    fn network(&self) -> ChainSupported {
+        self.network
+    }
+
+    fn contract(&self) -> String {
+        self.contract.to_string()
+    }
+
+    async fn binding_of(&self, account: &VaneAddress) -> Result<Option<H256>, anyhow::Error> {
+        let account = evm_address(account)?;
+        let read = TransactionRequest::default()
+            .with_to(self.contract)
+            .with_input(IVaneBindingRegistry::bindingOfCall { account }.abi_encode());
+        let binding = self
+            .provider
+            .call(&read)
+            .await
+            .map_err(|err| anyhow!("failed to read the anchored binding; caused by: {err}"))?;
+        let binding = IVaneBindingRegistry::bindingOfCall::abi_decode_returns(&binding, true)?._0;
+        Ok((binding != B256::ZERO).then_some(H256(binding.0)))
+    }
+}
+
+/// peer id of the node the off-chain registry serves `account` from
+pub fn served_by(peers: &[Discovery], account: &VaneAddress) -> Option<String> {
+    peers
+        .iter()
+        .find(|peer| {
+            peer.account_ids
+                .iter()
+                .any(|id| VaneAddress::parse(id).is_ok_and(|id| id == *account))
+        })
+        .and_then(|peer| peer.peer_id.clone())
+}
+
+/// anchoring and checks of the bindings, all disabled without a registry
+#[derive(Clone, Default)]
+pub struct BindingAnchors {
+    registry: Option<Arc<dyn BindingRegistry>>,
+    /// transfers to receivers whose binding is not anchored are refused
+    required: bool,
+}
+
+impl BindingAnchors {
+    pub fn new(registry: Option<Arc<dyn BindingRegistry>>, required: bool) -> Self {
+        Self { registry, required }
+    }
+
+    fn registry(&self) -> Result<&Arc<dyn BindingRegistry>, VaneRpcError> {
+        self.registry.as_ref().ok_or(VaneRpcError::InvalidParams {
+            reason: "the node has no binding registry configured".to_string(),
+        })
+    }
+
+    /// registry call the wallet of `account` sends to anchor its binding to the node `peer_id`
+    pub fn anchor_call(
+        &self,
+        account: &VaneAddress,
+        peer_id: &str,
+    ) -> Result<AnchorCall, VaneRpcError> {
+        let registry = self.registry()?;
+        let binding = binding_hash(account, peer_id)?;
+        let data = IVaneBindingRegistry::anchorCall {
+            binding: B256::from(binding.0),
+        }
+        .abi_encode();
+        Ok(AnchorCall {
+            network: registry.network(),
+            registry: registry.contract(),
+            account: account.to_string(),
+            peer_id: peer_id.to_string(),
+            binding,
+            data,
+        })
+    }
+
+    /// the binding of `account` to the node `peer_id` as anchored on chain
+    pub async fn verify(
+        &self,
+        account: &VaneAddress,
+        peer_id: String,
+    ) -> Result<BindingAnchor, VaneRpcError> {
+        let registry = self.registry()?;
+        let anchored = registry.binding_of(account).await?;
+        BindingAnchor::new(
+            registry.network(),
+            registry.contract(),
+            account,
+            peer_id,
+            anchored,
+        )
+    }
+
+    /// refuse a transfer to an evm receiver whose binding to the node of its registry record is not
+    /// anchored, when the operator requires it
+    pub async fn check(
+        &self,
+        peers: &[Discovery],
+        tx: &TxStateMachine,
+    ) -> Result<(), VaneRpcError> {
+        if !self.required || tx.kind != TxKind::Transfer {
+            return Ok(());
+        }
+        let receiver = &tx.receiver_address;
+        if !matches!(receiver, VaneAddress::Evm(_)) {
+            return Ok(());
+        }
+        let peer_id = served_by(peers, receiver).ok_or(VaneRpcError::ReceiverNotRegistered {
+            address: receiver.to_string(),
+        })?;
+        let anchor = self.verify(receiver, peer_id).await?;
+        if !anchor.verified {
+            Err(VaneRpcError::BindingNotAnchored {
+                address: anchor.account,
+                peer_id: anchor.peer_id,
+            })?
+        }
+        Ok(())
+    }
+}
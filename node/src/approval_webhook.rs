@@ -0,0 +1,130 @@
+// external approval callout
+// before a sender confirmed transfer is submitted, the node posts its details to the approval
+// system of the operator and waits for an allow or deny. a webhook that can't be reached, answers
+// late or answers garbage denies the transfer, unless the node was configured to fail open
+
+use anyhow::anyhow;
+use log::warn;
+use primitives::data_structure::{ChainSupported, Token, TxKind, TxStateMachine, H256};
+use primitives::errors::VaneRpcError;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// body posted to the approval webhook
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalRequest {
+    pub tx_id: H256,
+    pub kind: TxKind,
+    pub sender: String,
+    pub receiver: String,
+    pub network: ChainSupported,
+    pub token: Token,
+    /// amount in the smallest unit of the token, as a decimal string
+    pub amount: String,
+    pub memo: Option<String>,
+    pub reference: Option<String>,
+    pub tags: Vec<String>,
+    /// codes of the warnings raised on the transfer
+    pub warnings: Vec<String>,
+}
+
+impl From<&TxStateMachine> for ApprovalRequest {
+    fn from(tx: &TxStateMachine) -> Self {
+        ApprovalRequest {
+            tx_id: tx.tx_id(),
+            kind: tx.kind,
+            sender: tx.sender_address.to_string(),
+            receiver: tx.receiver_address.to_string(),
+            network: tx.network,
+            token: tx.token.unwrap_or(Token::native(tx.network)),
+            amount: tx.transfer_amount().to_string(),
+            memo: tx.memo.clone(),
+            reference: tx.reference.clone(),
+            tags: tx.tags.clone(),
+            warnings: tx
+                .warnings
+                .iter()
+                .map(|warning| warning.code.clone())
+                .collect(),
+        }
+    }
+}
+
+/// answer of the approval system, e.g. `{"decision":"deny","reason":"over the desk limit"}`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "decision", rename_all = "lowercase")]
+pub enum ApprovalDecision {
+    Allow,
+    Deny {
+        #[serde(default)]
+        reason: String,
+    },
+}
+
+#[derive(Clone)]
+pub struct ApprovalWebhook {
+    url: String,
+    client: reqwest::Client,
+    /// submit the transfer when the webhook gives no answer
+    fail_open: bool,
+}
+
+impl ApprovalWebhook {
+    /// `url` is https, plain http is only allowed to a loopback host
+    pub fn new(
+        url: impl Into<String>,
+        timeout: Duration,
+        fail_open: bool,
+    ) -> Result<Self, anyhow::Error> {
+        let url: String = url.into();
+        let parsed = reqwest::Url::parse(&url)
+            .map_err(|err| anyhow!("invalid approval webhook url; caused by: {err}"))?;
+        let loopback = matches!(parsed.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+        if parsed.scheme() != "https" && !(parsed.scheme() == "http" && loopback) {
+            Err(anyhow!("the approval webhook should be an https url"))?
+        }
+        let client = reqwest::ClientBuilder::new()
+            .timeout(timeout)
+            .build()
+            .map_err(|err| {
+                anyhow!("failed to build the approval webhook client; caused by: {err}")
+            })?;
+        Ok(Self {
+            url,
+            client,
+            fail_open,
+        })
+    }
+
+    /// ask the approval system whether `tx` may be submitted
+    pub async fn approve(&self, tx: &TxStateMachine) -> Result<(), VaneRpcError> {
+        match self.decision(&ApprovalRequest::from(tx)).await {
+            Ok(ApprovalDecision::Allow) => Ok(()),
+            Ok(ApprovalDecision::Deny { reason }) => Err(VaneRpcError::ApprovalDenied { reason }),
+            Err(err) if self.fail_open => {
+                warn!(
+                    "tx {} submitted without approval; caused by: {err}",
+                    tx.tx_nonce
+                );
+                Ok(())
+            }
+            Err(err) => Err(VaneRpcError::ApprovalDenied {
+                reason: format!("no answer from the approval webhook, {err}"),
+            }),
+        }
+    }
+
+    async fn decision(&self, request: &ApprovalRequest) -> Result<ApprovalDecision, anyhow::Error> {
+        self.client
+            .post(&self.url)
+            .json(request)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| anyhow!("approval webhook call failed; caused by: {err}"))?
+            .json()
+            .await
+            .map_err(|err| anyhow!("invalid approval webhook answer; caused by: {err}"))
+    }
+}
@@ -0,0 +1,1073 @@
+// embeddable node construction
+// lets host applications inject the db path, discovery backend, chain set, chain clients, node identity and channels
+// and run the node inside their own tokio runtime
+
+use crate::amount_limits::AmountLimits;
+use crate::anchors::{BindingAnchors, BindingRegistry, EvmBindingRegistry};
+use crate::approval_webhook::ApprovalWebhook;
+#[cfg(feature = "dev")]
+use crate::chain::MockChain;
+use crate::chain::{
+    ChainClient, EvmChainClient, FLASHBOTS_PROTECT_SEPOLIA_URL, FLASHBOTS_PROTECT_URL,
+};
+use crate::circuit_breaker::CircuitBreakers;
+use crate::cluster::{Cluster, ClusterStore};
+#[cfg(feature = "dev")]
+use crate::discovery::LocalDiscovery;
+use crate::discovery::PeerDiscovery;
+use crate::durable_nonce::DurableNonces;
+use crate::event_bus::{EventBus, TxEventPublisher};
+use crate::failover::Failover;
+use crate::identity::TrustedIssuers;
+use crate::keystore::Keystore;
+use crate::locks::timed_lock;
+#[cfg(feature = "dev")]
+use crate::p2p::DEV_PROTOCOL_VERSION;
+use crate::p2p::{P2pNetworkService, P2pWorker, PROTOCOL_VERSION};
+use crate::plugins::Plugins;
+use crate::policy::SecondApprover;
+use crate::pricing::{FiatPricing, PriceSource, StaticPrices};
+use crate::priority_fees::PriorityFeeOracle;
+use crate::push::PushGateway;
+use crate::risk::{RiskProvider, RiskScreening};
+use crate::rpc::{Airtable, TransactionRpcWorker, TransactionRpcWorkerParams};
+use crate::rpc_middleware::{AccessControl, Role};
+use crate::send_lock::SendLock;
+use crate::spam::SpamFilter;
+use crate::submission::SubmissionQueues;
+use crate::telemetry::TelemetryWorker;
+use crate::token_accounts::TokenAccounts;
+use crate::tx_processing::TxProcessingWorker;
+use crate::tx_updates::AccountScope;
+use crate::watchdog::Watchdog;
+use crate::MainServiceWorker;
+use alloc::sync::Arc;
+use anyhow::anyhow;
+use db::{DbWorker, DbWorkerInterface, StorageKey};
+use libp2p::futures::future::select_all;
+use libp2p::identity::Keypair;
+use libp2p::{Multiaddr, PeerId};
+use moka::future::Cache as AsyncCache;
+use primitives::data_structure::{ChainSupported, NetworkCommand, Token, TxEvent, TxStateMachine};
+use primitives::environment::{Environments, NetworkEnvironment};
+use primitives::fiat::DEFAULT_MAX_SLIPPAGE_BPS;
+use primitives::risk::{RiskRequirements, MAX_RISK_SCORE};
+use rand::Rng;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+/// default db used when no path is provided
+pub const DEFAULT_DB_URL: &str = "db/dev.db";
+/// db file of a hosted tenant, in the tenant directory
+pub const TENANT_DB_FILE: &str = "vane.db";
+/// default capacity of the node internal channels
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 10;
+/// a db without ports gets a random rpc port above the privileged ones and their p2p offset
+const MIN_RANDOM_RPC_PORT: u16 = 1024 + P2P_PORT_OFFSET;
+/// the p2p port is this far below a random rpc port
+const P2P_PORT_OFFSET: u16 = 541;
+
+/// channel carrying tx state updates from the node to the user facing layer
+pub type TxUpdateChannel = (Sender<TxStateMachine>, Receiver<TxStateMachine>);
+/// channel carrying user tx updates into the node
+pub type UserUpdateChannel = (
+    Sender<Arc<Mutex<TxStateMachine>>>,
+    Receiver<Arc<Mutex<TxStateMachine>>>,
+);
+
+/// builder for `MainServiceWorker`, every option falls back to what `MainServiceWorker::run` uses
+pub struct MainServiceWorkerBuilder {
+    db_url: Option<String>,
+    keystore: Option<Keystore>,
+    ports: Option<(u16, u16)>,
+    discovery: Option<Arc<dyn PeerDiscovery>>,
+    chains: Vec<ChainSupported>,
+    chain_clients: Vec<Arc<dyn ChainClient>>,
+    #[cfg(feature = "dev")]
+    dev: bool,
+    environments: Environments,
+    max_fee_caps: HashMap<ChainSupported, u128>,
+    solana_compute_unit_limit: Option<u32>,
+    solana_max_compute_unit_price: Option<u64>,
+    solana_nonce_accounts: HashMap<String, String>,
+    solana_create_token_accounts: bool,
+    private_relay_url: Option<String>,
+    bundler_urls: HashMap<ChainSupported, String>,
+    paymasters: HashMap<ChainSupported, (String, serde_json::Value)>,
+    verifiers: Vec<Arc<dyn ChainClient>>,
+    verify_urls: HashMap<ChainSupported, String>,
+    keypair: Option<Keypair>,
+    channel_capacity: usize,
+    tx_update_channel: Option<TxUpdateChannel>,
+    user_update_channel: Option<UserUpdateChannel>,
+    second_approver: Option<SecondApprover>,
+    api_keys: HashMap<String, Role>,
+    key_accounts: HashMap<String, Vec<String>>,
+    watch_only: bool,
+    optimistic_send: bool,
+    send_lock: Option<SendLock>,
+    spam_filter: SpamFilter,
+    amount_limits: AmountLimits,
+    trusted_issuers: Vec<(String, String)>,
+    plugins: Vec<PathBuf>,
+    approval_webhook: Option<(String, Duration, bool)>,
+    risk_provider: Option<Arc<dyn RiskProvider>>,
+    risk_requirements: RiskRequirements,
+    binding_registry: Option<Arc<dyn BindingRegistry>>,
+    binding_registry_contract: Option<(ChainSupported, String)>,
+    require_anchored_bindings: bool,
+    telemetry_url: Option<String>,
+    share_loss_prevention_stats: bool,
+    usd_prices: BTreeMap<String, f64>,
+    price_source: Option<Arc<dyn PriceSource>>,
+    fiat_slippage_bps: u32,
+    watchdog_interval: Duration,
+    relay: Option<String>,
+    relay_for: Vec<String>,
+    push_gateway: Option<String>,
+    push_token: Option<String>,
+    cluster: Option<Cluster>,
+    failover: Option<Failover>,
+    event_bus: Option<Arc<dyn EventBus>>,
+    grpc_address: Option<SocketAddr>,
+    graphql: bool,
+}
+
+impl Default for MainServiceWorkerBuilder {
+    fn default() -> Self {
+        Self {
+            db_url: None,
+            keystore: None,
+            ports: None,
+            discovery: None,
+            chains: vec![
+                ChainSupported::Polkadot,
+                ChainSupported::Ethereum,
+                ChainSupported::Bnb,
+                ChainSupported::Solana,
+            ],
+            chain_clients: vec![],
+            #[cfg(feature = "dev")]
+            dev: false,
+            environments: Environments::default(),
+            max_fee_caps: HashMap::new(),
+            solana_compute_unit_limit: None,
+            solana_max_compute_unit_price: None,
+            solana_nonce_accounts: HashMap::new(),
+            solana_create_token_accounts: false,
+            private_relay_url: None,
+            bundler_urls: HashMap::new(),
+            paymasters: HashMap::new(),
+            verifiers: vec![],
+            verify_urls: HashMap::new(),
+            keypair: None,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            tx_update_channel: None,
+            user_update_channel: None,
+            second_approver: None,
+            api_keys: HashMap::new(),
+            key_accounts: HashMap::new(),
+            watch_only: false,
+            optimistic_send: false,
+            send_lock: None,
+            spam_filter: SpamFilter::default(),
+            amount_limits: AmountLimits::default(),
+            trusted_issuers: vec![],
+            plugins: vec![],
+            approval_webhook: None,
+            risk_provider: None,
+            risk_requirements: RiskRequirements::default(),
+            binding_registry: None,
+            binding_registry_contract: None,
+            require_anchored_bindings: false,
+            telemetry_url: None,
+            share_loss_prevention_stats: false,
+            usd_prices: BTreeMap::new(),
+            price_source: None,
+            fiat_slippage_bps: DEFAULT_MAX_SLIPPAGE_BPS,
+            watchdog_interval: Watchdog::DEFAULT_INTERVAL,
+            relay: None,
+            relay_for: vec![],
+            push_gateway: None,
+            push_token: None,
+            cluster: None,
+            failover: None,
+            event_bus: None,
+            grpc_address: None,
+            graphql: false,
+        }
+    }
+}
+
+impl MainServiceWorkerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// local db file path
+    pub fn db_url(mut self, db_url: impl Into<String>) -> Self {
+        self.db_url = Some(db_url.into());
+        self
+    }
+
+    /// keystore holding the db storage key, defaults to `keystore` next to the db file
+    pub fn keystore(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.keystore = Some(Keystore::new(dir));
+        self
+    }
+
+    /// fixed rpc and p2p ports, otherwise ports are loaded from the db or randomly assigned and persisted
+    pub fn ports(mut self, rpc_port: u16, p2p_port: u16) -> Self {
+        self.ports = Some((rpc_port, p2p_port));
+        self
+    }
+
+    /// peer discovery backend, airtable is used otherwise
+    pub fn discovery(mut self, discovery: Arc<dyn PeerDiscovery>) -> Self {
+        self.discovery = Some(discovery);
+        self
+    }
+
+    /// networks the node accepts transactions for
+    pub fn chains(mut self, chains: impl IntoIterator<Item = ChainSupported>) -> Self {
+        self.chains = chains.into_iter().collect();
+        self
+    }
+
+    /// client used to create and submit txs for its network, e.g. a `MockChain` or a local devnet,
+    /// ethereum and bnb default to their public rpc endpoints
+    pub fn chain_client(mut self, client: Arc<dyn ChainClient>) -> Self {
+        self.chain_clients
+            .retain(|c| c.network() != client.network());
+        self.chain_clients.push(client);
+        self
+    }
+
+    /// dev mode, the networks without a client of their own run on in-memory `MockChain`s
+    /// with instant finality and a faucet, and no telemetry is sent. no chain rpc endpoint or real
+    /// funds are needed to go through the whole transfer flow. the networks run on testnet and the
+    /// node speaks the dev protocol, registered with an in-process discovery unless one is set, so
+    /// dev nodes never meet the nodes moving real funds
+    #[cfg(feature = "dev")]
+    pub fn dev(mut self) -> Self {
+        self.dev = true;
+        self.telemetry_url = None;
+        self.environments.default = NetworkEnvironment::Testnet;
+        self
+    }
+
+    /// run every network on `environment`, mainnet by default
+    pub fn environment(mut self, environment: NetworkEnvironment) -> Self {
+        self.environments.default = environment;
+        self
+    }
+
+    /// run `network` on `environment` whatever the node wide environment
+    pub fn network_environment(
+        mut self,
+        network: ChainSupported,
+        environment: NetworkEnvironment,
+    ) -> Self {
+        self.environments.set(network, environment);
+        self
+    }
+
+    /// rpc endpoint of `network` used instead of the public one of its environment
+    pub fn chain_url(mut self, network: ChainSupported, url: impl Into<String>) -> Self {
+        self.environments.set_url(network, url.into());
+        self
+    }
+
+    /// max fee per gas in wei the default ethereum and bnb clients build txs with, txs above it are refused
+    pub fn max_fee_cap(mut self, network: ChainSupported, cap: u128) -> Self {
+        self.max_fee_caps.insert(network, cap);
+        self
+    }
+
+    /// compute units every solana transfer requests, the presets of its token otherwise
+    pub fn solana_compute_unit_limit(mut self, limit: u32) -> Self {
+        self.solana_compute_unit_limit = Some(limit);
+        self
+    }
+
+    /// compute unit price in micro lamports solana txs are built with at most, txs above it are
+    /// refused
+    pub fn solana_max_compute_unit_price(mut self, cap: u64) -> Self {
+        self.solana_max_compute_unit_price = Some(cap);
+        self
+    }
+
+    /// sign the solana transfers of `sender` against the durable nonce of `nonce_account` rather
+    /// than a recent blockhash, the sender has to be the authority of the account
+    pub fn solana_nonce_account(
+        mut self,
+        sender: impl Into<String>,
+        nonce_account: impl Into<String>,
+    ) -> Self {
+        self.solana_nonce_accounts
+            .insert(sender.into(), nonce_account.into());
+        self
+    }
+
+    /// create the missing token account of a solana receiver in the transfer tx, the sender pays
+    /// its rent. the transfer is only warned about otherwise
+    pub fn solana_create_token_accounts(mut self) -> Self {
+        self.solana_create_token_accounts = true;
+        self
+    }
+
+    /// private relay the default ethereum client sends private route txs to, Flashbots Protect of
+    /// the environment otherwise
+    pub fn private_relay_url(mut self, relay_url: impl Into<String>) -> Self {
+        self.private_relay_url = Some(relay_url.into());
+        self
+    }
+
+    /// erc-4337 bundler of `network`, the transfers of smart account senders are sent to it as
+    /// user operations. ethereum and bnb only
+    pub fn bundler_url(mut self, network: ChainSupported, url: impl Into<String>) -> Self {
+        self.bundler_urls.insert(network, url.into());
+        self
+    }
+
+    /// erc-7677 paymaster service of `network` sponsoring the gas of the user operations under the
+    /// policy of `context`, the receiver of each transfer is added to it. needs a bundler
+    pub fn paymaster(
+        mut self,
+        network: ChainSupported,
+        url: impl Into<String>,
+        context: serde_json::Value,
+    ) -> Self {
+        self.paymasters.insert(network, (url.into(), context));
+        self
+    }
+
+    /// independent provider of its network, txs are only reported submitted once it sees them
+    pub fn verifier(mut self, client: Arc<dyn ChainClient>) -> Self {
+        self.verifiers.retain(|c| c.network() != client.network());
+        self.verifiers.push(client);
+        self
+    }
+
+    /// rpc endpoint of an independent ethereum or bnb provider confirming the broadcast txs
+    pub fn verify_url(mut self, network: ChainSupported, url: impl Into<String>) -> Self {
+        self.verify_urls.insert(network, url.into());
+        self
+    }
+
+    /// node identity used to sign p2p traffic, a new ed25519 keypair is generated otherwise
+    pub fn keypair(mut self, keypair: Keypair) -> Self {
+        self.keypair = Some(keypair);
+        self
+    }
+
+    /// capacity of the channels created by the builder
+    pub fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity;
+        self
+    }
+
+    /// channel the node pushes tx state updates to, the receiving half feeds `subscribeTxUpdates`
+    pub fn tx_update_channel(
+        mut self,
+        sender: Sender<TxStateMachine>,
+        receiver: Receiver<TxStateMachine>,
+    ) -> Self {
+        self.tx_update_channel = Some((sender, receiver));
+        self
+    }
+
+    /// channel user tx updates are submitted through, keep a sender clone to drive the node without rpc
+    pub fn user_update_channel(
+        mut self,
+        sender: Sender<Arc<Mutex<TxStateMachine>>>,
+        receiver: Receiver<Arc<Mutex<TxStateMachine>>>,
+    ) -> Self {
+        self.user_update_channel = Some((sender, receiver));
+        self
+    }
+
+    /// approver whose sign-off sender confirmed txs wait for before submission, `all_txs` false only
+    /// holds the txs a `RequireSecondApprover` policy applied to
+    pub fn second_approver(mut self, account: impl Into<String>, all_txs: bool) -> Self {
+        self.second_approver = Some(SecondApprover {
+            account: account.into(),
+            all_txs,
+        });
+        self
+    }
+
+    /// rpc api key granted `role`, once a key is set every rpc request needs one
+    pub fn api_key(mut self, key: impl Into<String>, role: Role) -> Self {
+        self.api_keys.insert(key.into(), role);
+        self
+    }
+
+    /// scope the tx updates and tx reads of the api key `key` to `account`, a scoped key only sees
+    /// the txs sent from or to its accounts
+    pub fn api_key_account(mut self, key: impl Into<String>, account: impl Into<String>) -> Self {
+        self.key_accounts
+            .entry(key.into())
+            .or_default()
+            .push(account.into());
+        self
+    }
+
+    /// watch-only node, it attests, monitors and lists pending transfers but only sends for the accounts
+    /// of signing devices paired through `pairSigner`
+    pub fn watch_only(mut self) -> Self {
+        self.watch_only = true;
+        self
+    }
+
+    /// return the tx id of an initiated transfer right after the local checks, the registry, chain
+    /// and risk checks run in the background and the tx update subscriptions report their outcome
+    pub fn optimistic_send(mut self) -> Self {
+        self.optimistic_send = true;
+        self
+    }
+
+    /// refuse to initiate and sender confirm transfers until unlocked with `passphrase` through
+    /// `unlock`, an unlock lasts for `window`
+    pub fn send_passphrase(mut self, passphrase: &str, window: Duration) -> Self {
+        self.send_lock = Some(SendLock::new(passphrase, window));
+        self
+    }
+
+    /// decline attestation requests from senders neither in the peer book nor attested for before
+    pub fn reject_unknown_senders(mut self) -> Self {
+        self.spam_filter.reject_unknown_senders = true;
+        self
+    }
+
+    /// decline attestation requests of `token` below `amount`, in the token smallest unit
+    pub fn dust_threshold(mut self, token: Token, amount: u128) -> Self {
+        self.spam_filter.dust_thresholds.insert(token, amount);
+        self
+    }
+
+    /// refuse to send `token` transfers below `amount`, in the token smallest unit
+    pub fn min_transfer_amount(mut self, token: Token, amount: u128) -> Self {
+        self.amount_limits.minimums.insert(token, amount);
+        self
+    }
+
+    /// warn about `token` transfer amounts carrying more than `decimals` decimals
+    pub fn max_decimals(mut self, token: Token, decimals: u32) -> Self {
+        self.amount_limits.max_decimals.insert(token, decimals);
+        self
+    }
+
+    /// decline attestation requests past `max_requests` per sender within `window`
+    pub fn max_requests_per_sender(mut self, max_requests: u32, window: Duration) -> Self {
+        self.spam_filter
+            .limit_requests_per_sender(max_requests, window);
+        self
+    }
+
+    /// show the attestations signed by the evm account `issuer` as verification badges named `name`
+    pub fn trusted_issuer(mut self, issuer: impl Into<String>, name: impl Into<String>) -> Self {
+        self.trusted_issuers.push((issuer.into(), name.into()));
+        self
+    }
+
+    /// wasm validation plugin run on the transfers of the node before attestation and before
+    /// submission, see `primitives::plugins` for the interface it exports
+    pub fn plugin(mut self, path: impl Into<PathBuf>) -> Self {
+        self.plugins.push(path.into());
+        self
+    }
+
+    /// https endpoint approving sender confirmed transfers before submission, waited on for up to
+    /// `timeout`. without an answer the transfer is denied, or submitted when `fail_open`
+    pub fn approval_webhook(
+        mut self,
+        url: impl Into<String>,
+        timeout: Duration,
+        fail_open: bool,
+    ) -> Self {
+        self.approval_webhook = Some((url.into(), timeout, fail_open));
+        self
+    }
+
+    /// provider scoring the receiver of every transfer at genesis, the assessment is recorded
+    /// with the tx
+    pub fn risk_provider(mut self, provider: Arc<dyn RiskProvider>) -> Self {
+        self.risk_provider = Some(provider);
+        self
+    }
+
+    /// refuse transfers to receivers the risk provider scores below `min_score` or does not score
+    pub fn min_risk_score(mut self, min_score: u8) -> Self {
+        self.risk_requirements.min_score = Some(min_score);
+        self
+    }
+
+    /// refuse transfers to receivers the risk provider attributes to the ISO 3166-1 alpha-2
+    /// `jurisdiction`
+    pub fn sanctioned_jurisdiction(mut self, jurisdiction: impl Into<String>) -> Self {
+        self.risk_requirements
+            .sanctioned_jurisdictions
+            .push(jurisdiction.into());
+        self
+    }
+
+    /// registry contract the accounts anchor their binding to the node in, see
+    /// `contracts/VaneBindingRegistry.sol`. read over the rpc endpoint of `network`
+    pub fn binding_registry_contract(
+        mut self,
+        network: ChainSupported,
+        contract: impl Into<String>,
+    ) -> Self {
+        self.binding_registry_contract = Some((network, contract.into()));
+        self
+    }
+
+    /// registry of the anchored bindings read otherwise than from a contract of the node networks
+    pub fn binding_registry(mut self, registry: Arc<dyn BindingRegistry>) -> Self {
+        self.binding_registry = Some(registry);
+        self
+    }
+
+    /// refuse transfers to evm receivers that did not anchor their binding to the node the
+    /// off-chain registry serves them from
+    pub fn require_anchored_bindings(mut self) -> Self {
+        self.require_anchored_bindings = true;
+        self
+    }
+
+    /// opt in to report anonymized node stats to the telemetry server websocket endpoint `url`, no
+    /// telemetry is sent without one
+    pub fn telemetry_url(mut self, url: impl Into<String>) -> Self {
+        self.telemetry_url = Some(url.into());
+        self
+    }
+
+    /// drop the configured telemetry endpoint, no stats leave the node
+    pub fn no_telemetry(mut self) -> Self {
+        self.telemetry_url = None;
+        self
+    }
+
+    /// opt in to share the daily counts and rounded usd values of the prevented transfers with the
+    /// telemetry server
+    pub fn share_loss_prevention_stats(mut self) -> Self {
+        self.share_loss_prevention_stats = true;
+        self
+    }
+
+    /// usd price of a whole `token` the prevented transfer values are estimated with, stablecoins
+    /// default to 1 and transfers of unpriced tokens are only counted. the fiat denominated
+    /// transfers are priced with it as well unless a price source is plugged
+    pub fn usd_price(mut self, token: Token, price: f64) -> Self {
+        self.usd_prices.insert(token.into(), price);
+        self
+    }
+
+    /// live usd prices the amounts of the fiat denominated transfers are computed with
+    pub fn price_source(mut self, source: Arc<dyn PriceSource>) -> Self {
+        self.price_source = Some(source);
+        self
+    }
+
+    /// price move a fiat denominated transfer tolerates until it is submitted when its sender picks
+    /// none, in basis points
+    pub fn fiat_slippage(mut self, max_slippage_bps: u32) -> Self {
+        self.fiat_slippage_bps = max_slippage_bps;
+        self
+    }
+
+    /// how often the watchdog checks the swarm, the chain providers and the db
+    pub fn watchdog_interval(mut self, interval: Duration) -> Self {
+        self.watchdog_interval = interval;
+        self
+    }
+
+    /// always-on node of the same user queuing the requests to this node while it is offline, its
+    /// multi addr ending with /p2p/<peer id>. it has to relay for this node
+    pub fn relay(mut self, multi_addr: impl Into<String>) -> Self {
+        self.relay = Some(multi_addr.into());
+        self
+    }
+
+    /// relay the requests to the mobile node `peer_id`, queued while it is offline
+    pub fn relay_for(mut self, peer_id: impl Into<String>) -> Self {
+        self.relay_for.push(peer_id.into());
+        self
+    }
+
+    /// webhook in front of FCM or APNs called with the device token of a mobile node whenever a
+    /// request gets queued for it
+    pub fn push_gateway(mut self, url: impl Into<String>) -> Self {
+        self.push_gateway = Some(url.into());
+        self
+    }
+
+    /// device token of the mobile app, sent to the relay so its push gateway can wake the app up
+    pub fn push_token(mut self, token: impl Into<String>) -> Self {
+        self.push_token = Some(token.into());
+        self
+    }
+
+    /// run as an instance of a cluster sharing `store`, the accounts of an instance crashing are
+    /// claimed by the others once their `lease` expired
+    pub fn cluster(mut self, store: Arc<dyn ClusterStore>, lease: Duration) -> Self {
+        self.cluster = Some(Cluster::new(store, lease));
+        self
+    }
+
+    /// run as one of redundant nodes electing the instance running the p2p identity through
+    /// `store`, a standby takes over once the `lease` of the leader expired. the nodes share the
+    /// identity through the keystore unless a keypair is set
+    pub fn failover(mut self, store: Arc<dyn ClusterStore>, lease: Duration) -> Self {
+        self.failover = Some(Failover::new(store, lease));
+        self
+    }
+
+    /// mirror every tx state transition onto `bus` as a lifecycle event
+    pub fn event_bus(mut self, bus: Arc<dyn EventBus>) -> Self {
+        self.event_bus = Some(bus);
+        self
+    }
+
+    /// serve the transaction and peer methods over grpc on `address` as well
+    pub fn grpc_address(mut self, address: SocketAddr) -> Self {
+        self.grpc_address = Some(address);
+        self
+    }
+
+    /// serve transfers, counterparties, stats and peers over graphql at `/graphql` on the rpc port
+    pub fn graphql(mut self) -> Self {
+        self.graphql = true;
+        self
+    }
+
+    /// node of a hosted tenant, its db and keystore live in `dir` and only its own api keys are
+    /// accepted, so the db and keys must not be set on the builder already
+    pub(crate) fn tenant(
+        mut self,
+        dir: &Path,
+        api_keys: HashMap<String, Role>,
+    ) -> Result<Self, anyhow::Error> {
+        if self.db_url.is_some() || self.keystore.is_some() {
+            Err(anyhow!(
+                "tenant nodes keep their db and keystore in the tenant directory"
+            ))?
+        }
+        if !self.api_keys.is_empty() {
+            Err(anyhow!(
+                "tenant nodes only accept the api keys of their tenant"
+            ))?
+        }
+        if api_keys.is_empty() {
+            Err(anyhow!("a tenant needs at least one api key"))?
+        }
+        self.db_url = Some(dir.join(TENANT_DB_FILE).to_string_lossy().into_owned());
+        self.keystore = Some(Keystore::new(dir.join("keystore")));
+        self.api_keys = api_keys;
+        Ok(self)
+    }
+
+    pub async fn build(self) -> Result<MainServiceWorker, anyhow::Error> {
+        if self.chains.is_empty() {
+            Err(anyhow!("at least one chain should be enabled"))?
+        }
+        if self.share_loss_prevention_stats && self.telemetry_url.is_none() {
+            Err(anyhow!(
+                "loss prevention stats are shared through telemetry, a telemetry url should be set"
+            ))?
+        }
+        let mut trusted_issuers = TrustedIssuers::default();
+        for (issuer, name) in self.trusted_issuers {
+            trusted_issuers.insert(&issuer, name)?;
+        }
+        let mut plugins = Plugins::default();
+        for path in &self.plugins {
+            plugins.load(path)?;
+        }
+        let approval_webhook = self
+            .approval_webhook
+            .map(|(url, timeout, fail_open)| ApprovalWebhook::new(url, timeout, fail_open))
+            .transpose()?;
+        if self.fiat_slippage_bps >= 10_000 {
+            Err(anyhow!("the fiat slippage should be under 10000 bps"))?
+        }
+        if self.risk_requirements.min_score > Some(MAX_RISK_SCORE) {
+            Err(anyhow!(
+                "the minimum risk score is at most {MAX_RISK_SCORE}"
+            ))?
+        }
+        let risk_screening = match self.risk_provider {
+            Some(provider) => RiskScreening::new(provider, self.risk_requirements),
+            None if self.risk_requirements.is_empty() => RiskScreening::default(),
+            None => Err(anyhow!("risk requirements need a risk provider"))?,
+        };
+        let binding_registry = match (self.binding_registry, self.binding_registry_contract) {
+            (Some(registry), _) => Some(registry),
+            (None, Some((network, contract))) => {
+                let url = self.environments.url(network);
+                let registry = EvmBindingRegistry::new(network, &contract, &url)?;
+                Some(Arc::new(registry) as Arc<dyn BindingRegistry>)
+            }
+            (None, None) if self.require_anchored_bindings => {
+                Err(anyhow!("anchored bindings need a binding registry"))?
+            }
+            (None, None) => None,
+        };
+        let binding_anchors = BindingAnchors::new(binding_registry, self.require_anchored_bindings);
+        // CHANNELS
+        // ===================================================================================== //
+        // for rpc messages back and forth propagation
+        let (rpc_sender_channel, rpc_recv_channel) = self
+            .tx_update_channel
+            .unwrap_or_else(|| tokio::sync::mpsc::channel(self.channel_capacity));
+        let (user_rpc_update_sender_channel, user_rpc_update_recv_channel) = self
+            .user_update_channel
+            .unwrap_or_else(|| tokio::sync::mpsc::channel(self.channel_capacity));
+
+        // for p2p network commands
+        let (p2p_command_tx, p2p_command_recv) =
+            tokio::sync::mpsc::channel::<NetworkCommand>(self.channel_capacity);
+
+        // DATABASE WORKER (LOCAL AND REMOTE )
+        // ===================================================================================== //
+        let db_url = self.db_url.unwrap_or(DEFAULT_DB_URL.to_string());
+        // call payloads, signed call payloads and memos are sealed at rest, an in-memory db
+        // without a keystore gets a key that lives as long as its records
+        let keystore = match self.keystore {
+            Some(keystore) => Some(keystore),
+            None if db_url == db::IN_MEMORY_DB_URL => None,
+            None => Some(Keystore::for_db(&db_url)),
+        };
+        let storage_key = match &keystore {
+            Some(keystore) => keystore.storage_key()?,
+            None => StorageKey::new(rand::random()),
+        };
+        let db = DbWorker::initialize_db_client(db_url.as_str())
+            .await?
+            .with_storage_key(storage_key);
+
+        let (rpc_port, p2p_port) = match self.ports {
+            Some(ports) => ports,
+            None => match db.get_ports().await? {
+                Some(ports) => (ports.rpc_port, ports.p_2_p_port),
+                None => {
+                    let port = rand::thread_rng().gen_range(MIN_RANDOM_RPC_PORT..=u16::MAX);
+                    let p2p_port = port.checked_sub(P2P_PORT_OFFSET).ok_or(anyhow!(
+                        "no p2p port {P2P_PORT_OFFSET} below rpc port {port}"
+                    ))?;
+                    let (rpc_port, p2p_port) = (port, p2p_port);
+                    db.set_ports(rpc_port, p2p_port).await?;
+                    (rpc_port, p2p_port)
+                }
+            },
+        };
+
+        let db_worker = Arc::new(Mutex::new(db));
+
+        #[cfg(feature = "dev")]
+        let (discovery, protocol_version) = if self.dev {
+            let local: Arc<dyn PeerDiscovery> = Arc::new(LocalDiscovery::new());
+            (Some(self.discovery.unwrap_or(local)), DEV_PROTOCOL_VERSION)
+        } else {
+            (self.discovery, PROTOCOL_VERSION)
+        };
+        #[cfg(not(feature = "dev"))]
+        let (discovery, protocol_version) = (self.discovery, PROTOCOL_VERSION);
+        let discovery: Arc<dyn PeerDiscovery> = match discovery {
+            Some(discovery) => discovery,
+            None => Arc::new(Airtable::new().await.map_err(|err| {
+                anyhow!("failed to instantiate airtable client, caused by: {err}")
+            })?),
+        };
+
+        let moka_cache = AsyncCache::builder()
+            .max_capacity(10)
+            .name("TxStateMachine rpc tracker")
+            .time_to_live(tokio::time::Duration::from_secs(600))
+            .build();
+
+        // rebuild the pending txs from the event log so in flight txs survive a restart
+        let events = timed_lock(&db_worker, "db_worker")
+            .await
+            .get_all_tx_events()
+            .await?;
+        for tx in TxEvent::pending_txs(events) {
+            moka_cache.insert(tx.tx_nonce.into(), tx).await;
+        }
+
+        // PEER TO PEER NETWORKING WORKER
+        // ===================================================================================== //
+        // redundant nodes run the one identity of their keystore
+        let keypair = match (self.keypair, &self.failover, &keystore) {
+            (Some(keypair), _, _) => keypair,
+            (None, Some(_), Some(keystore)) => keystore.node_key()?,
+            (None, Some(_), None) => Err(anyhow!("redundant nodes need a keystore or a keypair"))?,
+            (None, None, _) => Keypair::generate_ed25519(),
+        };
+        let p2p_worker = P2pWorker::new(
+            discovery.clone(),
+            db_worker.clone(),
+            p2p_port,
+            p2p_command_recv,
+            keypair,
+            protocol_version,
+        )
+        .await?;
+
+        if let Some(relay) = self.relay {
+            let relay = relay
+                .parse::<Multiaddr>()
+                .map_err(|err| anyhow!("invalid relay multi addr {relay}; caused by: {err}"))?;
+            p2p_worker.relay.use_relay(relay)?;
+        }
+        for mobile in self.relay_for {
+            let mobile = mobile
+                .parse::<PeerId>()
+                .map_err(|err| anyhow!("invalid relayed peer id {mobile}; caused by: {err}"))?;
+            p2p_worker.relay.relay_for(mobile);
+        }
+        if let Some(url) = self.push_gateway {
+            p2p_worker.relay.use_push_gateway(PushGateway::new(url)?);
+        }
+        if let Some(token) = self.push_token {
+            p2p_worker.relay.use_push_token(token);
+        }
+        p2p_worker
+            .device_sync
+            .use_pending_transfers(moka_cache.clone());
+
+        let p2p_command_tx = Arc::new(p2p_command_tx);
+        let p2p_network_service =
+            P2pNetworkService::new(p2p_command_tx.clone(), p2p_worker.clone())?;
+
+        // CHAIN CLIENTS
+        // ===================================================================================== //
+        let mut chain_clients = self.chain_clients;
+        #[cfg(feature = "dev")]
+        if self.dev {
+            for network in [
+                ChainSupported::Polkadot,
+                ChainSupported::Ethereum,
+                ChainSupported::Bnb,
+                ChainSupported::Solana,
+            ] {
+                if !chain_clients.iter().any(|c| c.network() == network) {
+                    chain_clients.push(Arc::new(MockChain::dev(network)));
+                }
+            }
+        }
+        for network in [ChainSupported::Ethereum, ChainSupported::Bnb] {
+            if !chain_clients.iter().any(|c| c.network() == network) {
+                let environment = self.environments.of(network);
+                let mut client = EvmChainClient::new(network, &self.environments.url(network))?
+                    .with_environment(environment);
+                if let Some(cap) = self.max_fee_caps.get(&network) {
+                    client = client.with_max_fee_cap(*cap);
+                }
+                if let Some(url) = self.bundler_urls.get(&network) {
+                    client = client.with_bundler(url)?;
+                }
+                if let Some((url, context)) = self.paymasters.get(&network) {
+                    client = client.with_paymaster(url, context.clone())?;
+                }
+                if network == ChainSupported::Ethereum {
+                    client =
+                        client.with_private_relay(self.private_relay_url.as_deref().unwrap_or(
+                            match environment {
+                                NetworkEnvironment::Mainnet => FLASHBOTS_PROTECT_URL,
+                                NetworkEnvironment::Testnet => FLASHBOTS_PROTECT_SEPOLIA_URL,
+                            },
+                        ))?;
+                }
+                chain_clients.push(Arc::new(client));
+            }
+        }
+        if let Some(network) = self
+            .bundler_urls
+            .keys()
+            .find(|network| !matches!(network, ChainSupported::Ethereum | ChainSupported::Bnb))
+        {
+            Err(anyhow!("{network:?} has no bundlers of user operations"))?
+        }
+        if let Some(network) = self
+            .paymasters
+            .keys()
+            .find(|network| !self.bundler_urls.contains_key(network))
+        {
+            Err(anyhow!(
+                "{network:?} paymaster configured without a bundler"
+            ))?
+        }
+        let mut verifiers = HashMap::new();
+        for (network, url) in &self.verify_urls {
+            if !matches!(network, ChainSupported::Ethereum | ChainSupported::Bnb) {
+                Err(anyhow!(
+                    "{network:?} txs can not be verified by an independent provider"
+                ))?
+            }
+            let client = EvmChainClient::new(*network, url)?
+                .with_environment(self.environments.of(*network));
+            verifiers.insert(*network, Arc::new(client) as Arc<dyn ChainClient>);
+        }
+        for client in self.verifiers {
+            verifiers.insert(client.network(), client);
+        }
+
+        let price_source = self
+            .price_source
+            .unwrap_or_else(|| Arc::new(StaticPrices::from_names(&self.usd_prices)));
+        let fiat_pricing = FiatPricing::new(price_source, self.fiat_slippage_bps);
+        let mut priority_fees =
+            PriorityFeeOracle::new(&self.environments.url(ChainSupported::Solana))?;
+        if let Some(limit) = self.solana_compute_unit_limit {
+            priority_fees = priority_fees.with_compute_unit_limit(limit);
+        }
+        if let Some(cap) = self.solana_max_compute_unit_price {
+            priority_fees = priority_fees.with_max_compute_unit_price(cap);
+        }
+        let mut durable_nonces =
+            DurableNonces::new(&self.environments.url(ChainSupported::Solana))?;
+        for (sender, nonce_account) in &self.solana_nonce_accounts {
+            durable_nonces = durable_nonces.with_nonce_account(sender, nonce_account);
+        }
+        let mut token_accounts =
+            TokenAccounts::new(&self.environments.url(ChainSupported::Solana))?;
+        if self.solana_create_token_accounts {
+            token_accounts = token_accounts.with_creation();
+        }
+        let telemetry = self.telemetry_url.map(|url| {
+            let telemetry = TelemetryWorker::new(url, self.chains.clone(), db_worker.clone());
+            if self.share_loss_prevention_stats {
+                telemetry.with_loss_prevention(self.usd_prices)
+            } else {
+                telemetry
+            }
+        });
+        let event_publisher = self
+            .event_bus
+            .map(|bus| TxEventPublisher::new(bus, p2p_worker.node_id.to_string()));
+
+        // TRANSACTION RPC WORKER
+        // ===================================================================================== //
+        let circuit_breakers = CircuitBreakers::default();
+        let txn_rpc_worker = TransactionRpcWorker::new(TransactionRpcWorkerParams {
+            discovery: discovery.clone(),
+            db_worker: db_worker.clone(),
+            rpc_recv_channel: Arc::new(Mutex::new(rpc_recv_channel)),
+            user_rpc_update_sender_channel: Arc::new(Mutex::new(user_rpc_update_sender_channel)),
+            port: rpc_port,
+            identity: p2p_worker.identity.clone(),
+            moka_cache: moka_cache.clone(),
+            supported_chains: self.chains,
+            chain_clients: chain_clients.clone(),
+            circuit_breakers: circuit_breakers.clone(),
+            second_approver: self.second_approver.clone(),
+            watch_only: self.watch_only,
+            optimistic_send: self.optimistic_send,
+            send_lock: self.send_lock,
+            amount_limits: self.amount_limits,
+            fiat_pricing: fiat_pricing.clone(),
+            priority_fees,
+            durable_nonces,
+            token_accounts,
+            environments: self.environments.clone(),
+            trusted_issuers: trusted_issuers.clone(),
+            plugins,
+            approval_webhook,
+            risk_screening,
+            binding_anchors,
+            peer_metrics: p2p_worker.peer_metrics.clone(),
+            device_sync: p2p_worker.device_sync.clone(),
+            p2p_command_tx,
+            failover: self.failover.clone(),
+        })
+        .await?;
+
+        // TRANSACTION PROCESSING LAYER
+        // ===================================================================================== //
+        let (submission_queues, submission_outcomes) = SubmissionQueues::spawn(
+            chain_clients.clone(),
+            self.channel_capacity,
+            circuit_breakers,
+            &verifiers,
+        );
+        let watchdog = Watchdog::new(self.watchdog_interval, db_url, chain_clients.clone());
+        let tx_processing_worker = TxProcessingWorker::new(chain_clients);
+        // ===================================================================================== //
+
+        Ok(MainServiceWorker {
+            db_worker,
+            tx_rpc_worker: Arc::new(Mutex::new(txn_rpc_worker)),
+            tx_processing_worker: Arc::new(Mutex::new(tx_processing_worker)),
+            discovery,
+            p2p_worker: Arc::new(Mutex::new(p2p_worker)),
+            p2p_network_service: Arc::new(Mutex::new(p2p_network_service)),
+            rpc_sender_channel: Arc::new(Mutex::new(rpc_sender_channel)),
+            user_rpc_update_recv_channel: Arc::new(Mutex::new(user_rpc_update_recv_channel)),
+            moka_cache,
+            submission_queues,
+            submission_outcomes: Arc::new(Mutex::new(submission_outcomes)),
+            second_approver: self.second_approver,
+            fiat_pricing,
+            access_control: AccessControl::new(self.api_keys).with_scopes(
+                self.key_accounts
+                    .into_iter()
+                    .map(|(key, accounts)| (key, AccountScope::accounts(accounts)))
+                    .collect(),
+            ),
+            spam_filter: self.spam_filter,
+            environments: self.environments,
+            trusted_issuers,
+            telemetry,
+            watchdog,
+            cluster: self.cluster,
+            failover: self.failover,
+            event_publisher,
+            grpc_address: self.grpc_address,
+            graphql: self.graphql,
+        })
+    }
+}
+
+/// handle to a node started with `MainServiceWorker::start`
+pub struct NodeHandle {
+    /// address the rpc server is listening on
+    pub rpc_address: SocketAddr,
+    /// address the grpc server is listening on, none without a grpc address
+    pub grpc_address: Option<SocketAddr>,
+    /// the running node, shares state with the spawned tasks
+    pub worker: MainServiceWorker,
+    pub(crate) tasks: Vec<JoinHandle<()>>,
+}
+
+impl NodeHandle {
+    /// abort the node tasks
+    pub fn stop(&self) {
+        self.tasks.iter().for_each(|task| task.abort());
+        self.worker.submission_queues.abort();
+    }
+
+    /// wait until all node tasks exit
+    pub async fn stopped(self) {
+        for task in self.tasks {
+            let _ = task.await;
+        }
+    }
+
+    /// wait until one of the node tasks exits, they only do on a fatal error, and stop the others
+    pub async fn exited(mut self) -> Result<(), anyhow::Error> {
+        if self.tasks.is_empty() {
+            return Ok(());
+        }
+        let (outcome, _, _) = select_all(self.tasks.iter_mut()).await;
+        self.stop();
+        outcome.map_err(|err| anyhow!("node task failed; caused by: {err}"))?;
+        Err(anyhow!("node task exited"))
+    }
+}
@@ -0,0 +1,232 @@
+// contract call encoding and decoding
+// transfers of contract tokens are sent as calls to the token contract, the calldata is decoded back
+// against the known abis so the sender confirms a readable intent instead of a hex blob
+
+use alloy::primitives::{Address, B256, U256};
+use alloy::sol;
+use alloy::sol_types::{Eip712Domain, SolCall, SolStruct};
+use anyhow::anyhow;
+use primitives::address::VaneAddress;
+use primitives::approvals::UNLIMITED_APPROVAL;
+use primitives::data_structure::{ChainSupported, Token, TxKind, TxStateMachine};
+use primitives::tx_builder::format_units;
+
+sol! {
+    interface IERC20 {
+        function transfer(address to, uint256 amount) external returns (bool);
+        function approve(address spender, uint256 amount) external returns (bool);
+        function transferFrom(address from, address to, uint256 amount) external returns (bool);
+        function balanceOf(address account) external view returns (uint256);
+    }
+
+    interface IERC20Permit {
+        function name() external view returns (string);
+        function nonces(address owner) external view returns (uint256);
+    }
+
+    struct Permit {
+        address owner;
+        address spender;
+        uint256 value;
+        uint256 nonce;
+        uint256 deadline;
+    }
+}
+
+/// gas limit of an erc20 `transfer`
+pub const ERC20_TRANSFER_GAS: u128 = 65_000;
+
+/// contracts the node knows the abi of
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KnownContract {
+    pub network: ChainSupported,
+    pub address: Address,
+    pub token: Token,
+    /// EIP-712 domain version when the contract implements EIP-2612 `permit`
+    pub permit_version: Option<&'static str>,
+}
+
+pub const KNOWN_CONTRACTS: [KnownContract; 2] = [
+    KnownContract {
+        network: ChainSupported::Ethereum,
+        address: alloy::primitives::address!("dAC17F958D2ee523a2206206994597C13D831ec7"),
+        token: Token::UsdtEth,
+        permit_version: None,
+    },
+    KnownContract {
+        network: ChainSupported::Ethereum,
+        address: alloy::primitives::address!("A0b86991c6218b36c1d19D4a2E9Eb0cE3606eB48"),
+        token: Token::UsdcEth,
+        permit_version: Some("2"),
+    },
+];
+
+/// contract of a token on `network`, `None` for native tokens
+pub fn token_contract(token: Token, network: ChainSupported) -> Option<Address> {
+    KNOWN_CONTRACTS
+        .iter()
+        .find(|contract| contract.token == token && contract.network == network)
+        .map(|contract| contract.address)
+}
+
+pub fn known_contract(network: ChainSupported, address: Address) -> Option<&'static KnownContract> {
+    KNOWN_CONTRACTS
+        .iter()
+        .find(|contract| contract.network == network && contract.address == address)
+}
+
+fn allowance(amount: u128) -> U256 {
+    if amount == UNLIMITED_APPROVAL {
+        U256::MAX
+    } else {
+        U256::from(amount)
+    }
+}
+
+/// calldata of an erc20 transfer of `amount` to `receiver`
+pub fn erc20_transfer(receiver: Address, amount: u128) -> Vec<u8> {
+    IERC20::transferCall {
+        to: receiver,
+        amount: U256::from(amount),
+    }
+    .abi_encode()
+}
+
+/// calldata of an erc20 approval of `spender`, `UNLIMITED_APPROVAL` approves `U256::MAX`
+pub fn erc20_approve(spender: Address, amount: u128) -> Vec<u8> {
+    IERC20::approveCall {
+        spender,
+        amount: allowance(amount),
+    }
+    .abi_encode()
+}
+
+/// EIP-712 signing hash of an EIP-2612 permit
+pub fn permit_signing_hash(
+    contract: &KnownContract,
+    token_name: String,
+    chain_id: u64,
+    permit: &Permit,
+) -> Result<B256, anyhow::Error> {
+    let version = contract
+        .permit_version
+        .ok_or(anyhow!("{:?} does not support permit", contract.token))?;
+    let domain = Eip712Domain::new(
+        Some(token_name.into()),
+        Some(version.into()),
+        Some(U256::from(chain_id)),
+        Some(contract.address),
+        None,
+    );
+    Ok(permit.eip712_signing_hash(&domain))
+}
+
+/// human readable summary of a call to `contract` with `value` attached, unknown contracts and
+/// selectors are reported as such so the sender does not sign them blindly
+pub fn describe_call(
+    network: ChainSupported,
+    contract: Address,
+    value: u128,
+    data: &[u8],
+) -> String {
+    let amount = |amount: U256, token: Option<Token>| match (u128::try_from(amount), token) {
+        (Ok(amount), Some(token)) => {
+            format!(
+                "{} {}",
+                format_units(amount, token.decimals()),
+                token.symbol()
+            )
+        }
+        (_, Some(token)) if amount == U256::MAX => format!("unlimited {}", token.symbol()),
+        _ => format!("{amount} units"),
+    };
+    let token = known_contract(network, contract).map(|contract| contract.token);
+    let target = match token {
+        Some(token) => format!("{} contract {contract}", token.symbol()),
+        None => format!("unknown contract {contract}"),
+    };
+
+    let mut intent = if let Ok(call) = IERC20::transferCall::abi_decode(data, true) {
+        format!(
+            "transfer {} to {} via {target}",
+            amount(call.amount, token),
+            call.to
+        )
+    } else if let Ok(call) = IERC20::approveCall::abi_decode(data, true) {
+        format!(
+            "allow {} to spend {} via {target}",
+            call.spender,
+            amount(call.amount, token)
+        )
+    } else if let Ok(call) = IERC20::transferFromCall::abi_decode(data, true) {
+        format!(
+            "transfer {} from {} to {} via {target}",
+            amount(call.amount, token),
+            call.from,
+            call.to
+        )
+    } else {
+        let selector = data
+            .get(..4)
+            .map(alloy::hex::encode_prefixed)
+            .unwrap_or("none".to_string());
+        format!(
+            "undecoded call to {target}, selector {selector}, {} bytes of calldata",
+            data.len()
+        )
+    };
+    if value > 0 {
+        let native = Token::native(network);
+        intent.push_str(&format!(
+            ", attaching {} {}",
+            format_units(value, native.decimals()),
+            native.symbol()
+        ));
+    }
+    intent
+}
+
+/// calldata and intent of the contract call a tx makes, `None` for native transfers
+pub fn contract_call(tx: &TxStateMachine) -> Result<Option<(Vec<u8>, String)>, anyhow::Error> {
+    let token = tx.token.unwrap_or(Token::native(tx.network));
+    match tx.kind {
+        TxKind::Transfer => contract_transfer(
+            token,
+            tx.network,
+            &tx.receiver_address,
+            tx.transfer_amount(),
+        ),
+        TxKind::Approval => {
+            let contract = token_contract(token, tx.network)
+                .ok_or(anyhow!("no {token:?} contract known on {:?}", tx.network))?;
+            let spender = evm_address(&tx.receiver_address)?;
+            let call_data = erc20_approve(spender, tx.amount);
+            let intent = describe_call(tx.network, contract, 0, &call_data);
+            Ok(Some((call_data, intent)))
+        }
+    }
+}
+
+/// evm account of a tx address
+pub fn evm_address(address: &VaneAddress) -> Result<Address, anyhow::Error> {
+    match address {
+        VaneAddress::Evm(bytes) => Ok(Address::from(*bytes)),
+        other => Err(anyhow!("{other} is not an evm address")),
+    }
+}
+
+/// calldata and intent of a transfer of a contract token, `None` for native tokens
+pub fn contract_transfer(
+    token: Token,
+    network: ChainSupported,
+    receiver: &VaneAddress,
+    amount: u128,
+) -> Result<Option<(Vec<u8>, String)>, anyhow::Error> {
+    let Some(contract) = token_contract(token, network) else {
+        return Ok(None);
+    };
+    let receiver = evm_address(receiver)?;
+    let call_data = erc20_transfer(receiver, amount);
+    let intent = describe_call(network, contract, 0, &call_data);
+    Ok(Some((call_data, intent)))
+}
@@ -0,0 +1,1127 @@
+// chain clients the tx processing layer submits through
+// one client per network, `EvmChainClient` talks to an ethereum compatible json rpc endpoint,
+// `MockChain` is a deterministic in-memory chain with instant finality, backing the e2e tests and
+// the dev mode of the node
+
+use crate::calldata::{
+    evm_address, known_contract, permit_signing_hash, token_contract, IERC20Permit, Permit,
+    ERC20_TRANSFER_GAS, IERC20,
+};
+use crate::gas_oracle::GasOracle;
+use crate::typed_tx::{is_raw_tx, SignedEvmTx};
+use crate::user_operation::{Bundler, IEntryPoint, ISmartAccount, Paymaster, UserOperation};
+use alloc::sync::Arc;
+use alloy::consensus::{SignableTransaction, TxEip1559, TxEnvelope};
+use alloy::eips::eip2718::Encodable2718;
+use alloy::hex;
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{Address, Signature, U256};
+use alloy::providers::{Provider, ProviderBuilder, ReqwestProvider};
+use alloy::rpc::types::TransactionRequest;
+use alloy::sol_types::SolCall;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use primitives::address::VaneAddress;
+use primitives::approvals::PermitPayload;
+use primitives::data_structure::{
+    ChainSupported, SubmissionRoute, Token, TxStateMachine, TxWarning,
+};
+use primitives::environment::NetworkEnvironment;
+use primitives::explorer::ChainReceipt;
+use primitives::fees::{FeeEstimate, FeeTiers};
+use primitives::sponsorship::Sponsorship;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// gas limit of a native token transfer
+pub const NATIVE_TRANSFER_GAS: u128 = 21_000;
+/// calldata gas of a non zero byte, memos are attached as calldata of native transfers
+pub const MEMO_BYTE_GAS: u128 = 16;
+/// Flashbots Protect rpc, private ethereum submissions default to it
+pub const FLASHBOTS_PROTECT_URL: &str = "https://rpc.flashbots.net/fast";
+/// Flashbots Protect rpc of sepolia
+pub const FLASHBOTS_PROTECT_SEPOLIA_URL: &str = "https://rpc-sepolia.flashbots.net";
+
+/// client for a single chain network
+#[async_trait]
+pub trait ChainClient: Send + Sync {
+    /// network this client submits to
+    fn network(&self) -> ChainSupported;
+
+    /// build the tx to be signed by the externally owned account, returns the signing hash
+    async fn create_tx(&self, tx: &TxStateMachine) -> Result<[u8; 32], anyhow::Error>;
+
+    /// submit the externally signed tx, returns tx hash
+    async fn submit_tx(&self, tx: &TxStateMachine) -> Result<[u8; 32], anyhow::Error>;
+
+    /// whether `call_payload` is signed following the validation scheme of a smart account, the
+    /// account checks the signature on submission instead of the node recovering the sender
+    async fn validated_by_account(&self, _call_payload: [u8; 32]) -> bool {
+        false
+    }
+
+    /// paymaster sponsoring the gas of the tx of `call_payload`, shown to the sender before signing
+    async fn sponsorship(&self, _call_payload: [u8; 32]) -> Option<Sponsorship> {
+        None
+    }
+
+    /// receipt of a submitted tx, `None` until it is included in a block
+    async fn receipt(&self, _tx_hash: [u8; 32]) -> Result<Option<ChainReceipt>, anyhow::Error> {
+        Ok(None)
+    }
+
+    /// whether the provider knows the broadcast tx `tx_hash`, pending in its mempool or included
+    async fn knows_tx(&self, tx_hash: [u8; 32]) -> Result<bool, anyhow::Error> {
+        Ok(self.receipt(tx_hash).await?.is_some())
+    }
+
+    /// broadcast the submitted tx `tx_hash` again while it is not included, e.g. once dropped
+    /// from the mempool
+    async fn rebroadcast(&self, tx_hash: [u8; 32]) -> Result<(), anyhow::Error> {
+        Err(anyhow!(
+            "{:?} txs can not be broadcast again, 0x{}",
+            self.network(),
+            hex::encode(tx_hash)
+        ))
+    }
+
+    /// gas price of the low fee tier when `block_number` was built, only for chains with fee tiers
+    async fn low_tier_gas_price(&self, _block_number: u64) -> Result<Option<u128>, anyhow::Error> {
+        Ok(None)
+    }
+
+    /// fee the tx of `receipt` would have paid at the low tier of its block
+    async fn low_tier_fee(&self, receipt: &ChainReceipt) -> Result<Option<u128>, anyhow::Error> {
+        let gas_price = self.low_tier_gas_price(receipt.block_number).await?;
+        Ok(gas_price.and_then(|gas_price| receipt.fee_at(gas_price)))
+    }
+
+    /// suggested fee tiers of the next block, only for chains with an EIP-1559 fee market
+    async fn fee_tiers(&self) -> Result<FeeTiers, anyhow::Error> {
+        Err(anyhow!("{:?} has no fee tiers", self.network()))
+    }
+
+    /// whether the chain provider answers, checked by the watchdog
+    async fn health_check(&self) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    /// max fee per gas the node accepts to build txs with, in wei
+    fn max_fee_cap(&self) -> Option<u128> {
+        None
+    }
+
+    /// whether `SubmissionRoute::PrivateRelay` txs can be submitted
+    fn has_private_relay(&self) -> bool {
+        false
+    }
+
+    /// whether `faucet` funds accounts, true for dev chains only
+    fn has_faucet(&self) -> bool {
+        false
+    }
+
+    /// balance of `token` held by `account`, `None` when the client can not read it
+    async fn spendable_balance(
+        &self,
+        _account: &VaneAddress,
+        _token: Token,
+    ) -> Result<Option<u128>, anyhow::Error> {
+        Ok(None)
+    }
+
+    /// on chain findings about the receiver, e.g. it is a contract rejecting native value
+    async fn recipient_warnings(
+        &self,
+        _tx: &TxStateMachine,
+    ) -> Result<Vec<TxWarning>, anyhow::Error> {
+        Ok(vec![])
+    }
+
+    /// EIP-2612 permit of `amount` of `token` for `spender`, valid until `deadline`
+    async fn permit_payload(
+        &self,
+        _owner: &str,
+        _spender: &str,
+        token: Token,
+        _amount: u128,
+        _deadline: u64,
+    ) -> Result<PermitPayload, anyhow::Error> {
+        Err(anyhow!(
+            "{token:?} permits are not supported on {:?}",
+            self.network()
+        ))
+    }
+
+    /// credit `account` with `amount`, returns its balance. only dev chains have a faucet, an
+    /// `amount` of zero reads the balance
+    async fn faucet(&self, _account: &str, _amount: u128) -> Result<u128, anyhow::Error> {
+        Err(anyhow!(
+            "{:?} has no faucet, only dev chains do",
+            self.network()
+        ))
+    }
+}
+
+// ===================================== NONCE ======================================== //
+
+/// hands out account nonces per chain, txs prepared before the previous one is mined get consecutive
+/// nonces. a reservation unused for `RESERVATION_TTL` falls back to the chain nonce, so an abandoned
+/// tx does not leave a gap blocking the following ones
+#[derive(Clone, Default)]
+pub struct NonceManager {
+    reserved: Arc<std::sync::Mutex<HashMap<String, (u64, Instant)>>>,
+}
+
+/// nonce reserved for a tx being built, released when dropped before it is kept, e.g. once building
+/// the tx failed
+pub struct NonceReservation {
+    manager: NonceManager,
+    account: String,
+    nonce: u64,
+    kept: bool,
+}
+
+impl NonceReservation {
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// the tx was built with the nonce, it stays reserved for `NonceManager::RESERVATION_TTL`
+    pub fn keep(mut self) -> u64 {
+        self.kept = true;
+        self.nonce
+    }
+}
+
+impl Drop for NonceReservation {
+    fn drop(&mut self) {
+        if !self.kept {
+            self.manager.release(&self.account, self.nonce);
+        }
+    }
+}
+
+impl NonceManager {
+    /// same as the staged tx time to live, an unconfirmed tx cannot be submitted after it
+    pub const RESERVATION_TTL: Duration = Duration::from_secs(600);
+
+    fn reserved(&self) -> std::sync::MutexGuard<'_, HashMap<String, (u64, Instant)>> {
+        self.reserved
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// reserve the next nonce of `account`, `chain_nonce` being the account nonce reported by the chain
+    pub fn reserve(&self, account: impl Into<String>, chain_nonce: u64) -> NonceReservation {
+        let account = account.into();
+        let mut reserved = self.reserved();
+        let next = match reserved.get(&account) {
+            Some((next, at)) if at.elapsed() < Self::RESERVATION_TTL => (*next).max(chain_nonce),
+            _ => chain_nonce,
+        };
+        reserved.insert(account.clone(), (next + 1, Instant::now()));
+        NonceReservation {
+            manager: self.clone(),
+            account,
+            nonce: next,
+            kept: false,
+        }
+    }
+
+    /// hand `nonce` out again, unless a later nonce of `account` was reserved since
+    fn release(&self, account: &str, nonce: u64) {
+        if let Some((next, _)) = self.reserved().get_mut(account) {
+            if *next == nonce + 1 {
+                *next = nonce;
+            }
+        }
+    }
+}
+
+// ===================================== EVM ========================================== //
+
+/// ethereum & bnb client, unsigned txs are kept by signing hash until the sender signs them
+#[derive(Clone)]
+pub struct EvmChainClient {
+    network: ChainSupported,
+    provider: ReqwestProvider,
+    nonces: NonceManager,
+    gas_oracle: GasOracle,
+    max_fee_cap: Option<u128>,
+    /// private relay accepting `eth_sendRawTransaction`, used by `SubmissionRoute::PrivateRelay` txs
+    private_relay: Option<ReqwestProvider>,
+    /// chain id of the environment the network runs on, txs are not built against another chain
+    expected_chain_id: Option<u64>,
+    unsigned_txs: Arc<Mutex<HashMap<[u8; 32], TxEip1559>>>,
+    /// route and raw bytes of the submitted txs by hash, until they are seen in a block
+    submitted_txs: Arc<Mutex<HashMap<[u8; 32], (SubmissionRoute, Vec<u8>)>>>,
+    /// bundler the user operations of smart account senders are sent to
+    bundler: Option<Bundler>,
+    /// user operations by hash, unsigned until submitted and signed until included
+    user_ops: Arc<Mutex<HashMap<[u8; 32], UserOperation>>>,
+    /// paymaster service asked to sponsor the gas of the user operations
+    paymaster: Option<Paymaster>,
+    /// terms of the sponsored user operations by hash, until they are included
+    sponsorships: Arc<Mutex<HashMap<[u8; 32], Sponsorship>>>,
+}
+
+impl EvmChainClient {
+    pub fn new(network: ChainSupported, rpc_url: &str) -> Result<Self, anyhow::Error> {
+        let rpc_url = rpc_url
+            .parse()
+            .map_err(|err| anyhow!("{network:?} rpc url parse error: {err}"))?;
+        // Create a provider with the HTTP transport using the `reqwest` crate.
+        let provider = ProviderBuilder::new().on_http(rpc_url);
+        Ok(Self {
+            network,
+            gas_oracle: GasOracle::new(provider.clone()),
+            provider,
+            nonces: NonceManager::default(),
+            max_fee_cap: None,
+            private_relay: None,
+            expected_chain_id: None,
+            unsigned_txs: Arc::new(Default::default()),
+            submitted_txs: Arc::new(Default::default()),
+            bundler: None,
+            user_ops: Arc::new(Default::default()),
+            paymaster: None,
+            sponsorships: Arc::new(Default::default()),
+        })
+    }
+
+    /// send transfers of smart account senders as user operations to the bundler at `bundler_url`
+    pub fn with_bundler(mut self, bundler_url: &str) -> Result<Self, anyhow::Error> {
+        let bundler = Bundler::new(bundler_url)
+            .map_err(|err| anyhow!("{:?} bundler; caused by: {err}", self.network))?;
+        self.bundler = Some(bundler);
+        Ok(self)
+    }
+
+    /// have the paymaster service at `paymaster_url` sponsor the gas of the user operations, under
+    /// the policy of `context`
+    pub fn with_paymaster(
+        mut self,
+        paymaster_url: &str,
+        context: serde_json::Value,
+    ) -> Result<Self, anyhow::Error> {
+        let paymaster = Paymaster::new(paymaster_url, context)
+            .map_err(|err| anyhow!("{:?} paymaster; caused by: {err}", self.network))?;
+        self.paymaster = Some(paymaster);
+        Ok(self)
+    }
+
+    /// submit private route txs to the relay at `relay_url` instead of the public mempool
+    pub fn with_private_relay(mut self, relay_url: &str) -> Result<Self, anyhow::Error> {
+        let relay_url = relay_url
+            .parse()
+            .map_err(|err| anyhow!("{:?} private relay url parse error: {err}", self.network))?;
+        self.private_relay = Some(ProviderBuilder::new().on_http(relay_url));
+        Ok(self)
+    }
+
+    /// refuse to build txs with a max fee per gas above `cap` wei, whatever the sender tier
+    pub fn with_max_fee_cap(mut self, cap: u128) -> Self {
+        self.max_fee_cap = Some(cap);
+        self
+    }
+
+    /// refuse to build txs when the rpc endpoint serves another chain than the network in
+    /// `environment`, e.g. a mainnet endpoint configured for sepolia
+    pub fn with_environment(mut self, environment: NetworkEnvironment) -> Self {
+        self.expected_chain_id = environment.chain_id(self.network);
+        self
+    }
+
+    /// provider txs of `route` are submitted to
+    fn route_provider(&self, route: SubmissionRoute) -> Result<&ReqwestProvider, anyhow::Error> {
+        match route {
+            SubmissionRoute::Public => Ok(&self.provider),
+            SubmissionRoute::PrivateRelay => self.private_relay.as_ref().ok_or(anyhow!(
+                "no private relay configured for {:?}",
+                self.network
+            )),
+        }
+    }
+
+    async fn chain_id(&self) -> Result<u64, anyhow::Error> {
+        let chain_id = self
+            .provider
+            .get_chain_id()
+            .await
+            .map_err(|err| anyhow!("failed to fetch chain id; caused by: {err}"))?;
+        match self.expected_chain_id {
+            Some(expected) if expected != chain_id => Err(anyhow!(
+                "{:?} rpc endpoint serves chain {chain_id}, expected chain {expected}",
+                self.network
+            )),
+            _ => Ok(chain_id),
+        }
+    }
+
+    /// bundler to send the transfers of `sender` through, when it is a smart account
+    async fn smart_account_bundler(
+        &self,
+        sender: Address,
+    ) -> Result<Option<&Bundler>, anyhow::Error> {
+        let Some(bundler) = &self.bundler else {
+            return Ok(None);
+        };
+        let code = self
+            .provider
+            .get_code_at(sender)
+            .await
+            .map_err(|err| anyhow!("failed to fetch sender code; caused by: {err}"))?;
+        Ok((!code.is_empty()).then_some(bundler))
+    }
+
+    /// user operation calling `execute` on the smart account `sender`, sponsored by the paymaster
+    /// when one is configured, returns its hash
+    async fn create_user_op(
+        &self,
+        bundler: &Bundler,
+        (sender, receiver): (Address, Address),
+        chain_id: u64,
+        (to, value, input): (Address, U256, Vec<u8>),
+        fees: FeeEstimate,
+    ) -> Result<[u8; 32], anyhow::Error> {
+        let read = TransactionRequest::default()
+            .with_to(bundler.entry_point())
+            .with_input(
+                IEntryPoint::getNonceCall {
+                    sender,
+                    key: Default::default(),
+                }
+                .abi_encode(),
+            );
+        let nonce = self
+            .provider
+            .call(&read)
+            .await
+            .map_err(|err| anyhow!("failed to read the account nonce; caused by: {err}"))?;
+        let nonce = IEntryPoint::getNonceCall::abi_decode_returns(&nonce, true)?._0;
+        let call_data = ISmartAccount::executeCall {
+            dest: to,
+            value,
+            func: input.into(),
+        }
+        .abi_encode();
+        let mut user_op = UserOperation {
+            sender,
+            nonce,
+            call_data: call_data.into(),
+            call_gas_limit: U256::ZERO,
+            verification_gas_limit: U256::ZERO,
+            pre_verification_gas: U256::ZERO,
+            max_fee_per_gas: U256::from(fees.max_fee_per_gas),
+            max_priority_fee_per_gas: U256::from(fees.max_priority_fee_per_gas),
+            paymaster: None,
+            paymaster_verification_gas_limit: None,
+            paymaster_post_op_gas_limit: None,
+            paymaster_data: None,
+            signature: Default::default(),
+        };
+        let entry_point = bundler.entry_point();
+        let stub = match &self.paymaster {
+            Some(paymaster) => {
+                let stub = paymaster
+                    .stub_data(&user_op, entry_point, chain_id, receiver)
+                    .await?;
+                user_op.sponsored_by(&stub);
+                Some(stub)
+            }
+            None => None,
+        };
+        let gas = bundler.estimate_gas(&user_op).await?;
+        user_op.call_gas_limit = gas.call_gas_limit;
+        user_op.verification_gas_limit = gas.verification_gas_limit;
+        user_op.pre_verification_gas = gas.pre_verification_gas;
+        if gas.paymaster_verification_gas_limit.is_some() {
+            user_op.paymaster_verification_gas_limit = gas.paymaster_verification_gas_limit;
+        }
+        // the stub only stood in for the estimation, the service signs off the estimated operation
+        let sponsorship = match (&self.paymaster, stub) {
+            (Some(paymaster), Some(stub)) => {
+                let data = paymaster
+                    .data(&user_op, entry_point, chain_id, receiver)
+                    .await?;
+                user_op.sponsored_by(&data);
+                let sponsor = data.sponsor.or(stub.sponsor).map(|sponsor| sponsor.name);
+                Some(Sponsorship::new(
+                    data.paymaster.to_string(),
+                    sponsor,
+                    user_op.gas_limit().saturating_to(),
+                    fees.max_fee_per_gas,
+                ))
+            }
+            _ => None,
+        };
+
+        let hash = user_op.hash(entry_point, chain_id).0;
+        self.user_ops.lock().await.insert(hash, user_op);
+        if let Some(sponsorship) = sponsorship {
+            self.sponsorships.lock().await.insert(hash, sponsorship);
+        }
+        Ok(hash)
+    }
+
+    /// send the user operation signed by the owner of the smart account, the operation hash stands
+    /// for the tx hash until a bundle includes it
+    async fn submit_user_op(
+        &self,
+        mut user_op: UserOperation,
+        signature: Vec<u8>,
+    ) -> Result<[u8; 32], anyhow::Error> {
+        let bundler = self
+            .bundler
+            .as_ref()
+            .ok_or(anyhow!("no bundler configured for {:?}", self.network))?;
+        user_op.signature = signature.into();
+        let hash = bundler.send(&user_op).await?.0;
+        self.user_ops.lock().await.insert(hash, user_op);
+        Ok(hash)
+    }
+
+    async fn user_op_receipt(
+        &self,
+        bundler: &Bundler,
+        hash: [u8; 32],
+    ) -> Result<Option<ChainReceipt>, anyhow::Error> {
+        let Some(receipt) = bundler.receipt(hash.into()).await? else {
+            return Ok(None);
+        };
+        self.user_ops.lock().await.remove(&hash);
+        self.sponsorships.lock().await.remove(&hash);
+        let fee_paid = receipt.actual_gas_cost.saturating_to::<u128>();
+        let gas_used = receipt.actual_gas_used.saturating_to::<u128>();
+        Ok(Some(ChainReceipt {
+            block_number: receipt.receipt.block_number.to(),
+            effective_gas_price: fee_paid.checked_div(gas_used),
+            fee_paid,
+            success: receipt.success,
+        }))
+    }
+}
+
+#[async_trait]
+impl ChainClient for EvmChainClient {
+    fn network(&self) -> ChainSupported {
+        self.network
+    }
+
+    async fn health_check(&self) -> Result<(), anyhow::Error> {
+        self.provider
+            .get_block_number()
+            .await
+            .map_err(|err| anyhow!("failed to fetch block number; caused by: {err}"))?;
+        Ok(())
+    }
+
+    async fn create_tx(&self, tx: &TxStateMachine) -> Result<[u8; 32], anyhow::Error> {
+        let from_address = evm_address(&tx.sender_address)?;
+        let to_address = evm_address(&tx.receiver_address)?;
+
+        let chain_id = self.chain_id().await?;
+        let fees = self
+            .gas_oracle
+            .fee_tiers()
+            .await?
+            .select(tx.fee_preference.unwrap_or_default(), self.max_fee_cap)?;
+
+        // contract token transfers call the token contract without value
+        let (to_address, value, gas_limit, input) = match &tx.call_data {
+            Some(call_data) => {
+                let token = tx.token.unwrap_or(Token::native(self.network));
+                let contract = token_contract(token, self.network)
+                    .ok_or(anyhow!("no {token:?} contract known on {:?}", self.network))?;
+                (contract, U256::ZERO, ERC20_TRANSFER_GAS, call_data.clone())
+            }
+            None => {
+                let memo = tx.memo.clone().unwrap_or_default().into_bytes();
+                (
+                    to_address,
+                    U256::from(tx.transfer_amount()),
+                    NATIVE_TRANSFER_GAS + MEMO_BYTE_GAS * memo.len() as u128,
+                    memo,
+                )
+            }
+        };
+
+        // smart accounts do not sign txs, they are sent the transfer by the entry point
+        if let Some(bundler) = self.smart_account_bundler(from_address).await? {
+            let accounts = (from_address, evm_address(&tx.receiver_address)?);
+            let call = (to_address, value, input);
+            return self
+                .create_user_op(bundler, accounts, chain_id, call, fees)
+                .await;
+        }
+        let chain_nonce = self
+            .provider
+            .get_transaction_count(from_address)
+            .await
+            .map_err(|err| anyhow!("failed to fetch sender nonce; caused by: {err}"))?;
+        // released when building the tx fails below
+        let reservation = self.nonces.reserve(from_address.to_string(), chain_nonce);
+
+        let unsigned_tx = TransactionRequest::default()
+            .with_from(from_address)
+            .with_to(to_address)
+            .with_value(value)
+            .with_input(input)
+            .with_nonce(reservation.nonce())
+            .with_chain_id(chain_id)
+            .with_gas_limit(gas_limit)
+            .with_max_priority_fee_per_gas(fees.max_priority_fee_per_gas)
+            .with_max_fee_per_gas(fees.max_fee_per_gas)
+            .build_unsigned()
+            .map_err(|err| {
+                anyhow!("cannot build unsigned tx to be signed by EOA; caused by: {err:?}")
+            })?
+            .eip1559()
+            .ok_or(anyhow!("failed to convert to EIP 1559"))?
+            .clone();
+
+        let signing_hash = <[u8; 32]>::from(unsigned_tx.signature_hash());
+        self.unsigned_txs
+            .lock()
+            .await
+            .insert(signing_hash, unsigned_tx);
+        reservation.keep();
+        Ok(signing_hash)
+    }
+
+    async fn submit_tx(&self, tx: &TxStateMachine) -> Result<[u8; 32], anyhow::Error> {
+        let signature = tx
+            .signed_call_payload
+            .as_ref()
+            .ok_or(anyhow!("sender did not signed the tx payload"))?;
+        let call_payload = tx.call_payload.ok_or(anyhow!("call payload not found"))?;
+        let user_op = self.user_ops.lock().await.get(&call_payload).cloned();
+        if let Some(user_op) = user_op {
+            return self.submit_user_op(user_op, signature.clone()).await;
+        }
+        let provider = self.route_provider(tx.submission_route)?;
+
+        // kept until the chain accepts the tx, a failed submission can be retried
+        let unsigned_tx = self
+            .unsigned_txs
+            .lock()
+            .await
+            .get(&call_payload)
+            .cloned()
+            .ok_or(anyhow!("no unsigned tx created for the call payload"))?;
+
+        // wallets signing the whole tx pick its type, the transfer must be the one the node built
+        let raw_tx = if is_raw_tx(signature) {
+            let signed_tx = SignedEvmTx::decode(signature)?;
+            signed_tx.ensure_matches(&unsigned_tx, self.max_fee_cap)?;
+            if signed_tx.signer != evm_address(&tx.sender_address)? {
+                Err(anyhow!("the signed tx is not signed by the sender"))?
+            }
+            signed_tx.raw
+        } else {
+            let signature = Signature::try_from(signature.as_slice())
+                .map_err(|err| anyhow!("failed to parse signature: {err}"))?;
+            TxEnvelope::from(unsigned_tx.into_signed(signature)).encoded_2718()
+        };
+        let tx_hash = *provider
+            .send_raw_transaction(&raw_tx)
+            .await
+            .map_err(|err| {
+                anyhow!(
+                    "failed to submit {:?} raw tx; caused by: {err}",
+                    self.network
+                )
+            })?
+            .tx_hash();
+
+        self.unsigned_txs.lock().await.remove(&call_payload);
+        self.submitted_txs
+            .lock()
+            .await
+            .insert(tx_hash.0, (tx.submission_route, raw_tx));
+        Ok(tx_hash.0)
+    }
+
+    async fn validated_by_account(&self, call_payload: [u8; 32]) -> bool {
+        self.user_ops.lock().await.contains_key(&call_payload)
+    }
+
+    async fn sponsorship(&self, call_payload: [u8; 32]) -> Option<Sponsorship> {
+        self.sponsorships.lock().await.get(&call_payload).cloned()
+    }
+
+    async fn rebroadcast(&self, tx_hash: [u8; 32]) -> Result<(), anyhow::Error> {
+        let user_op = self.user_ops.lock().await.get(&tx_hash).cloned();
+        if let (Some(bundler), Some(user_op)) = (&self.bundler, user_op) {
+            return match bundler.send(&user_op).await {
+                Ok(_) => Ok(()),
+                Err(err) if err.to_string().contains("already known") => Ok(()),
+                Err(err) => Err(err),
+            };
+        }
+        let (route, raw_tx) = self
+            .submitted_txs
+            .lock()
+            .await
+            .get(&tx_hash)
+            .cloned()
+            .ok_or(anyhow!(
+                "tx 0x{} was not submitted by this node or is already included",
+                hex::encode(tx_hash)
+            ))?;
+        match self
+            .route_provider(route)?
+            .send_raw_transaction(&raw_tx)
+            .await
+        {
+            Ok(_) => Ok(()),
+            // still in the mempool of the provider
+            Err(err) if err.to_string().contains("already known") => Ok(()),
+            Err(err) => Err(anyhow!(
+                "failed to broadcast {:?} raw tx again; caused by: {err}",
+                self.network
+            )),
+        }
+    }
+
+    async fn receipt(&self, tx_hash: [u8; 32]) -> Result<Option<ChainReceipt>, anyhow::Error> {
+        if let Some(bundler) = &self.bundler {
+            if self.user_ops.lock().await.contains_key(&tx_hash) {
+                return self.user_op_receipt(bundler, tx_hash).await;
+            }
+        }
+        let receipt = self
+            .provider
+            .get_transaction_receipt(tx_hash.into())
+            .await
+            .map_err(|err| anyhow!("failed to fetch tx receipt; caused by: {err}"))?;
+        // pending txs have no receipt, nor a block number on some providers
+        let Some((receipt, block_number)) =
+            receipt.and_then(|receipt| receipt.block_number.map(|number| (receipt, number)))
+        else {
+            return Ok(None);
+        };
+        self.submitted_txs.lock().await.remove(&tx_hash);
+        Ok(Some(ChainReceipt {
+            block_number,
+            effective_gas_price: Some(receipt.effective_gas_price),
+            fee_paid: receipt.gas_used.saturating_mul(receipt.effective_gas_price),
+            success: receipt.status(),
+        }))
+    }
+
+    /// pending txs are known too, unlike their receipt
+    async fn knows_tx(&self, tx_hash: [u8; 32]) -> Result<bool, anyhow::Error> {
+        if let Some(bundler) = &self.bundler {
+            if self.user_ops.lock().await.contains_key(&tx_hash) {
+                return bundler.knows(tx_hash.into()).await;
+            }
+        }
+        let tx = self
+            .provider
+            .get_transaction_by_hash(tx_hash.into())
+            .await
+            .map_err(|err| anyhow!("failed to fetch tx; caused by: {err}"))?;
+        Ok(tx.is_some())
+    }
+
+    async fn low_tier_gas_price(&self, block_number: u64) -> Result<Option<u128>, anyhow::Error> {
+        let tiers = self.gas_oracle.fee_tiers_at(block_number).await?;
+        Ok(Some(tiers.low.max_fee_per_gas))
+    }
+
+    async fn fee_tiers(&self) -> Result<FeeTiers, anyhow::Error> {
+        self.gas_oracle.fee_tiers().await
+    }
+
+    fn max_fee_cap(&self) -> Option<u128> {
+        self.max_fee_cap
+    }
+
+    fn has_private_relay(&self) -> bool {
+        self.private_relay.is_some()
+    }
+
+    async fn spendable_balance(
+        &self,
+        account: &VaneAddress,
+        token: Token,
+    ) -> Result<Option<u128>, anyhow::Error> {
+        let owner = evm_address(account)?;
+        let balance = match token_contract(token, self.network) {
+            Some(contract) => {
+                let read = TransactionRequest::default()
+                    .with_to(contract)
+                    .with_input(IERC20::balanceOfCall { account: owner }.abi_encode());
+                let balance = self
+                    .provider
+                    .call(&read)
+                    .await
+                    .map_err(|err| anyhow!("failed to read token balance; caused by: {err}"))?;
+                IERC20::balanceOfCall::abi_decode_returns(&balance, true)?._0
+            }
+            None => self
+                .provider
+                .get_balance(owner)
+                .await
+                .map_err(|err| anyhow!("failed to fetch balance; caused by: {err}"))?,
+        };
+        Ok(Some(balance.saturating_to()))
+    }
+
+    async fn recipient_warnings(
+        &self,
+        tx: &TxStateMachine,
+    ) -> Result<Vec<TxWarning>, anyhow::Error> {
+        let receiver = evm_address(&tx.receiver_address)?;
+        let code = self
+            .provider
+            .get_code_at(receiver)
+            .await
+            .map_err(|err| anyhow!("failed to fetch receiver code; caused by: {err}"))?;
+        if code.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut warnings = vec![TxWarning::ContractRecipient];
+        let token = tx.token.unwrap_or(Token::native(self.network));
+        if token_contract(token, self.network).is_none() {
+            // simulate the native transfer, the contract needs a payable receive or fallback
+            let sender = evm_address(&tx.sender_address)?;
+            let transfer = TransactionRequest::default()
+                .with_from(sender)
+                .with_to(receiver)
+                .with_value(U256::from(tx.transfer_amount()));
+            if self.provider.call(&transfer).await.is_err() {
+                warnings.push(TxWarning::ContractRejectsValue);
+            }
+        }
+        Ok(warnings)
+    }
+
+    async fn permit_payload(
+        &self,
+        owner: &str,
+        spender: &str,
+        token: Token,
+        amount: u128,
+        deadline: u64,
+    ) -> Result<PermitPayload, anyhow::Error> {
+        let contract = token_contract(token, self.network)
+            .and_then(|address| known_contract(self.network, address))
+            .ok_or(anyhow!("no {token:?} contract known on {:?}", self.network))?;
+        let owner_address: Address = owner
+            .parse()
+            .map_err(|err| anyhow!("invalid owner address; caused by: {err}"))?;
+        let spender_address: Address = spender
+            .parse()
+            .map_err(|err| anyhow!("invalid spender address; caused by: {err}"))?;
+
+        let read = |input: Vec<u8>| {
+            TransactionRequest::default()
+                .with_to(contract.address)
+                .with_input(input)
+        };
+        let name = self
+            .provider
+            .call(&read(IERC20Permit::nameCall {}.abi_encode()))
+            .await
+            .map_err(|err| anyhow!("failed to read token name; caused by: {err}"))?;
+        let name = IERC20Permit::nameCall::abi_decode_returns(&name, true)?._0;
+        let nonce = self
+            .provider
+            .call(&read(
+                IERC20Permit::noncesCall {
+                    owner: owner_address,
+                }
+                .abi_encode(),
+            ))
+            .await
+            .map_err(|err| anyhow!("failed to read permit nonce; caused by: {err}"))?;
+        let nonce = IERC20Permit::noncesCall::abi_decode_returns(&nonce, true)?._0;
+        let chain_id = self.chain_id().await?;
+
+        let permit = Permit {
+            owner: owner_address,
+            spender: spender_address,
+            value: U256::from(amount),
+            nonce,
+            deadline: U256::from(deadline),
+        };
+        let signing_hash = permit_signing_hash(contract, name, chain_id, &permit)?;
+        Ok(PermitPayload {
+            owner: owner.to_string(),
+            spender: spender.to_string(),
+            token,
+            network: self.network,
+            amount,
+            nonce: nonce.saturating_to::<u128>(),
+            deadline,
+            signing_hash: signing_hash.0,
+        })
+    }
+}
+
+// ===================================== MOCK ========================================= //
+
+/// gas price of the txs of a `MockChain`, 1 gwei
+#[cfg(feature = "dev")]
+pub const MOCK_GAS_PRICE: u128 = 1_000_000_000;
+
+/// tx accepted by a `MockChain`
+#[cfg(feature = "dev")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MockSubmission {
+    pub tx_hash: [u8; 32],
+    pub sender: VaneAddress,
+    pub receiver: VaneAddress,
+    pub amount: u128,
+    pub route: SubmissionRoute,
+}
+
+#[cfg(feature = "dev")]
+#[derive(Default)]
+struct MockChainState {
+    nonces: HashMap<VaneAddress, u64>,
+    /// senders need funds from the faucet, dev chains only
+    enforce_balances: bool,
+    balances: HashMap<VaneAddress, u128>,
+    reverting: Vec<VaneAddress>,
+    /// contract addresses and whether they accept native value
+    contracts: HashMap<VaneAddress, bool>,
+    submissions: Vec<MockSubmission>,
+}
+
+/// deterministic in-memory chain, signing hashes and tx hashes only depend on the tx and sender nonce
+#[cfg(feature = "dev")]
+#[derive(Clone)]
+pub struct MockChain {
+    network: ChainSupported,
+    state: Arc<Mutex<MockChainState>>,
+}
+
+#[cfg(feature = "dev")]
+impl MockChain {
+    pub fn new(network: ChainSupported) -> Self {
+        Self {
+            network,
+            state: Arc::new(Default::default()),
+        }
+    }
+
+    /// chain of the dev mode, submissions move balances and senders get funds from the faucet
+    pub fn dev(network: ChainSupported) -> Self {
+        Self {
+            network,
+            state: Arc::new(Mutex::new(MockChainState {
+                enforce_balances: true,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// balance of `account`, zero for invalid addresses
+    pub async fn balance(&self, account: &str) -> u128 {
+        let Ok(account) = VaneAddress::parse(account) else {
+            return 0;
+        };
+        self.state
+            .lock()
+            .await
+            .balances
+            .get(&account)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// txs sent to `address` revert on submission, e.g. a contract rejecting transfers
+    pub async fn revert_to(&self, address: impl Into<String>) {
+        let address = VaneAddress::parse(&address.into()).expect("valid mock address");
+        self.state.lock().await.reverting.push(address);
+    }
+
+    /// treat `address` as a contract, `accepts_value` false makes native transfers to it revert
+    pub async fn deploy_contract(&self, address: impl Into<String>, accepts_value: bool) {
+        let address = VaneAddress::parse(&address.into()).expect("valid mock address");
+        let mut state = self.state.lock().await;
+        state.contracts.insert(address.clone(), accepts_value);
+        if !accepts_value {
+            state.reverting.push(address);
+        }
+    }
+
+    /// txs accepted so far, in submission order
+    pub async fn submissions(&self) -> Vec<MockSubmission> {
+        self.state.lock().await.submissions.clone()
+    }
+
+    fn signing_hash(&self, tx: &TxStateMachine, nonce: u64) -> [u8; 32] {
+        use codec::Encode;
+        let payload = (
+            String::from(self.network),
+            tx.sender_address,
+            tx.receiver_address,
+            tx.transfer_amount(),
+            nonce,
+        );
+        sp_core::keccak_256(&payload.encode())
+    }
+}
+
+#[cfg(feature = "dev")]
+#[async_trait]
+impl ChainClient for MockChain {
+    fn network(&self) -> ChainSupported {
+        self.network
+    }
+
+    async fn create_tx(&self, tx: &TxStateMachine) -> Result<[u8; 32], anyhow::Error> {
+        let nonce = self
+            .state
+            .lock()
+            .await
+            .nonces
+            .get(&tx.sender_address)
+            .copied()
+            .unwrap_or_default();
+        Ok(self.signing_hash(tx, nonce))
+    }
+
+    async fn submit_tx(&self, tx: &TxStateMachine) -> Result<[u8; 32], anyhow::Error> {
+        let signature = tx
+            .signed_call_payload
+            .as_ref()
+            .ok_or(anyhow!("sender did not signed the tx payload"))?;
+        let call_payload = tx.call_payload.ok_or(anyhow!("call payload not found"))?;
+
+        let mut state = self.state.lock().await;
+        let nonce = state
+            .nonces
+            .get(&tx.sender_address)
+            .copied()
+            .unwrap_or_default();
+        if call_payload != self.signing_hash(tx, nonce) {
+            Err(anyhow!(
+                "call payload does not match the sender nonce {nonce}"
+            ))?
+        }
+        if state.reverting.contains(&tx.receiver_address) {
+            Err(anyhow!("execution reverted: {}", tx.receiver_address))?
+        }
+        let balance = state
+            .balances
+            .get(&tx.sender_address)
+            .copied()
+            .unwrap_or_default();
+        let amount = tx.transfer_amount();
+        if state.enforce_balances && balance < amount {
+            Err(anyhow!(
+                "insufficient funds: {} holds {balance}, sends {amount}",
+                tx.sender_address
+            ))?
+        }
+
+        let mut signed_payload = call_payload.to_vec();
+        signed_payload.extend_from_slice(signature);
+        let tx_hash = sp_core::keccak_256(&signed_payload);
+
+        state.nonces.insert(tx.sender_address, nonce + 1);
+        state
+            .balances
+            .insert(tx.sender_address, balance.saturating_sub(amount));
+        *state.balances.entry(tx.receiver_address).or_default() += amount;
+        state.submissions.push(MockSubmission {
+            tx_hash,
+            sender: tx.sender_address,
+            receiver: tx.receiver_address,
+            amount,
+            route: tx.submission_route,
+        });
+        Ok(tx_hash)
+    }
+
+    /// txs are included right away, one per block, at `MOCK_GAS_PRICE`
+    async fn receipt(&self, tx_hash: [u8; 32]) -> Result<Option<ChainReceipt>, anyhow::Error> {
+        let state = self.state.lock().await;
+        let receipt = state
+            .submissions
+            .iter()
+            .position(|submission| submission.tx_hash == tx_hash)
+            .map(|index| ChainReceipt {
+                block_number: index as u64 + 1,
+                effective_gas_price: Some(MOCK_GAS_PRICE),
+                fee_paid: NATIVE_TRANSFER_GAS * MOCK_GAS_PRICE,
+                success: true,
+            });
+        Ok(receipt)
+    }
+
+    /// submitted txs are included right away, broadcasting them again changes nothing
+    async fn rebroadcast(&self, tx_hash: [u8; 32]) -> Result<(), anyhow::Error> {
+        let state = self.state.lock().await;
+        if !state
+            .submissions
+            .iter()
+            .any(|submission| submission.tx_hash == tx_hash)
+        {
+            Err(anyhow!("unknown tx 0x{}", hex::encode(tx_hash)))?
+        }
+        Ok(())
+    }
+
+    /// every tier pays `MOCK_GAS_PRICE`
+    async fn low_tier_gas_price(&self, _block_number: u64) -> Result<Option<u128>, anyhow::Error> {
+        Ok(Some(MOCK_GAS_PRICE))
+    }
+
+    /// private submissions are accepted and recorded with their route
+    fn has_private_relay(&self) -> bool {
+        true
+    }
+
+    fn has_faucet(&self) -> bool {
+        true
+    }
+
+    /// balances are only tracked by dev chains, every token shares the native one
+    async fn spendable_balance(
+        &self,
+        account: &VaneAddress,
+        _token: Token,
+    ) -> Result<Option<u128>, anyhow::Error> {
+        let state = self.state.lock().await;
+        if !state.enforce_balances {
+            return Ok(None);
+        }
+        Ok(Some(
+            state.balances.get(account).copied().unwrap_or_default(),
+        ))
+    }
+
+    async fn recipient_warnings(
+        &self,
+        tx: &TxStateMachine,
+    ) -> Result<Vec<TxWarning>, anyhow::Error> {
+        let warnings = match self.state.lock().await.contracts.get(&tx.receiver_address) {
+            Some(true) => vec![TxWarning::ContractRecipient],
+            Some(false) => vec![
+                TxWarning::ContractRecipient,
+                TxWarning::ContractRejectsValue,
+            ],
+            None => vec![],
+        };
+        Ok(warnings)
+    }
+
+    async fn faucet(&self, account: &str, amount: u128) -> Result<u128, anyhow::Error> {
+        let address = VaneAddress::parse(account)?;
+        let mut state = self.state.lock().await;
+        let balance = state.balances.entry(address).or_default();
+        *balance = balance
+            .checked_add(amount)
+            .ok_or(anyhow!("{account} balance overflows"))?;
+        Ok(*balance)
+    }
+}
@@ -0,0 +1,120 @@
+// per chain circuit breakers
+// a chain whose provider rejects `FAILURES_BEFORE_OPEN` submissions in a row is paused instead of
+// burning every tx against a dead endpoint. its submission worker keeps the queued txs and probes the
+// provider every `PROBE_INTERVAL`, a passed probe half opens the breaker for one submission which
+// closes it again or opens it for another round. the state is served by the `chainHealth` rpc and
+// the `/metrics` endpoint
+
+use log::{info, warn};
+use primitives::data_structure::{BreakerState, ChainSupported};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Clone, Copy, Default)]
+struct Breaker {
+    state: BreakerState,
+    /// rejected submissions in a row
+    failures: u32,
+    /// times the breaker opened since the node started
+    opened: u64,
+}
+
+#[derive(Clone, Default)]
+pub struct CircuitBreakers {
+    breakers: Arc<Mutex<HashMap<ChainSupported, Breaker>>>,
+}
+
+impl CircuitBreakers {
+    pub const FAILURES_BEFORE_OPEN: u32 = 5;
+    pub const PROBE_INTERVAL: Duration = Duration::from_secs(15);
+
+    fn update<T>(&self, network: ChainSupported, update: impl FnOnce(&mut Breaker) -> T) -> T {
+        let mut breakers = self
+            .breakers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        update(breakers.entry(network).or_default())
+    }
+
+    pub fn state(&self, network: ChainSupported) -> BreakerState {
+        self.update(network, |breaker| breaker.state)
+    }
+
+    /// the provider accepted a submission
+    pub fn record_success(&self, network: ChainSupported) {
+        let previous = self.update(network, |breaker| {
+            breaker.failures = 0;
+            std::mem::replace(&mut breaker.state, BreakerState::Closed)
+        });
+        if previous != BreakerState::Closed {
+            info!(target: "CircuitBreaker", "{network:?} submissions resumed");
+        }
+    }
+
+    /// the provider rejected a submission
+    pub fn record_failure(&self, network: ChainSupported) {
+        let opened = self.update(network, |breaker| {
+            breaker.failures += 1;
+            let trips = breaker.state == BreakerState::HalfOpen
+                || breaker.failures >= Self::FAILURES_BEFORE_OPEN;
+            if trips && breaker.state != BreakerState::Open {
+                breaker.state = BreakerState::Open;
+                breaker.opened += 1;
+            }
+            trips
+        });
+        if opened {
+            warn!(target: "CircuitBreaker", "{network:?} submissions paused until the provider answers a probe");
+        }
+    }
+
+    /// the provider answered a probe while the breaker was open
+    pub fn half_open(&self, network: ChainSupported) {
+        self.update(network, |breaker| {
+            if breaker.state == BreakerState::Open {
+                breaker.state = BreakerState::HalfOpen;
+            }
+        });
+    }
+
+    /// breaker states in the prometheus text format
+    pub fn render_metrics(&self) -> String {
+        let breakers: Vec<_> = self
+            .breakers
+            .lock()
+            .map(|breakers| breakers.iter().map(|(n, b)| (*n, *b)).collect())
+            .unwrap_or_default();
+        let mut metrics = String::new();
+        let _ = writeln!(
+            metrics,
+            "# HELP vane_chain_breaker_state 0 closed, 1 open and 2 half open"
+        );
+        let _ = writeln!(metrics, "# TYPE vane_chain_breaker_state gauge");
+        for (network, breaker) in &breakers {
+            let state = match breaker.state {
+                BreakerState::Closed => 0,
+                BreakerState::Open => 1,
+                BreakerState::HalfOpen => 2,
+            };
+            let _ = writeln!(
+                metrics,
+                "vane_chain_breaker_state{{network=\"{network:?}\"}} {state}"
+            );
+        }
+        let _ = writeln!(
+            metrics,
+            "# HELP vane_chain_breaker_opened_total times the breaker paused the submissions"
+        );
+        let _ = writeln!(metrics, "# TYPE vane_chain_breaker_opened_total counter");
+        for (network, breaker) in &breakers {
+            let _ = writeln!(
+                metrics,
+                "vane_chain_breaker_opened_total{{network=\"{network:?}\"}} {}",
+                breaker.opened
+            );
+        }
+        metrics
+    }
+}
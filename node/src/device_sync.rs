@@ -0,0 +1,627 @@
+// device pairing and sync
+// a paired device shows a short single-use pairing code, as a QR code along with its address or to
+// enter by hand, and the new device of the user proves it got the code. both then store the other
+// along with its x25519 key and derive their session key from the x25519 exchange. paired devices
+// send each other their contacts, policies and pending transfers sealed with the session key when
+// they connect and on every sync tick, the latest update of a record wins. the encryption key is
+// derived from the node identity so pairings survive restarts
+
+use crate::locks::timed_lock;
+use crate::p2p::VaneBehaviour;
+use crate::relay::{decrypt, encrypt};
+use anyhow::anyhow;
+use codec::{Decode, Encode};
+use curve25519_dalek::MontgomeryPoint;
+use db::{DbWorker, DbWorkerInterface};
+use libp2p::futures::channel::oneshot;
+use libp2p::identity::Keypair;
+use libp2p::multiaddr::Protocol;
+use libp2p::request_response::{Event, Message, OutboundRequestId};
+use libp2p::{Multiaddr, PeerId, Swarm};
+use log::{debug, info, warn};
+use moka::future::Cache as AsyncCache;
+use primitives::data_structure::{PeerRecord, TxStateMachine};
+use primitives::device_sync::{
+    pairing_proof, DevicePairingCode, PairedDevice, SealedRecords, SyncKind, SyncRecord,
+    SyncRequest, SyncResponse,
+};
+use primitives::envelope;
+use primitives::policy::parse_policies;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+/// protocol of the pairing and sync requests between the devices of a user
+pub const SYNC_PROTOCOL: &str = "/vane-sync/1.0.0";
+/// how long a pairing code is accepted
+pub const PAIRING_CODE_VALIDITY: Duration = Duration::from_secs(300);
+/// how often connected paired devices sync, disconnected ones are dialed
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+/// wrong codes before the pairing code is withdrawn
+const MAX_PAIRING_ATTEMPTS: u32 = 5;
+/// most records accepted in one sync
+const MAX_SYNCED_RECORDS: usize = 1024;
+
+type PairReply = oneshot::Sender<Result<PairedDevice, String>>;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+struct PairingCode {
+    code: String,
+    expires_at: u64,
+    attempts: u32,
+}
+
+#[derive(Default)]
+struct DeviceSyncState {
+    /// code shown to the new device, single use
+    pairing_code: Option<PairingCode>,
+    /// pairings waiting for the device showing the code to answer
+    pairing: HashMap<OutboundRequestId, PairReply>,
+    /// pending txs of this node, synced as its pending transfers
+    pending_transfers: Option<AsyncCache<u64, TxStateMachine>>,
+}
+
+#[derive(Clone)]
+pub struct DeviceSync {
+    node_id: PeerId,
+    multi_addr: Multiaddr,
+    encryption_secret: [u8; 32],
+    db_worker: Arc<Mutex<DbWorker>>,
+    state: Arc<std::sync::Mutex<DeviceSyncState>>,
+}
+
+impl DeviceSync {
+    pub fn new(
+        keypair: &Keypair,
+        multi_addr: Multiaddr,
+        db_worker: Arc<Mutex<DbWorker>>,
+    ) -> Result<Self, anyhow::Error> {
+        let identity = keypair
+            .to_protobuf_encoding()
+            .map_err(|err| anyhow!("failed to encode keypair; caused by: {err}"))?;
+        let encryption_secret = sp_core::hashing::blake2_256(
+            &[b"vane device sync encryption".as_slice(), &identity].concat(),
+        );
+        Ok(Self {
+            node_id: keypair.public().to_peer_id(),
+            multi_addr,
+            encryption_secret,
+            db_worker,
+            state: Default::default(),
+        })
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, DeviceSyncState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn encryption_key(&self) -> [u8; 32] {
+        MontgomeryPoint::mul_base_clamped(self.encryption_secret).to_bytes()
+    }
+
+    /// share the pending txs of `cache` with the paired devices
+    pub fn use_pending_transfers(&self, cache: AsyncCache<u64, TxStateMachine>) {
+        self.state().pending_transfers = Some(cache);
+    }
+
+    /// new pairing code, replacing the one shown before
+    pub fn pairing_code(&self) -> DevicePairingCode {
+        let code = format!("{:08}", rand::random::<u32>() % 100_000_000);
+        let expires_at = now() + PAIRING_CODE_VALIDITY.as_secs();
+        self.state().pairing_code = Some(PairingCode {
+            code: code.clone(),
+            expires_at,
+            attempts: 0,
+        });
+        DevicePairingCode {
+            uri: DevicePairingCode::uri(&code, &self.multi_addr.to_string()),
+            code,
+            expires_at,
+        }
+    }
+
+    fn session_key(&self, device: &PairedDevice) -> Result<[u8; 32], anyhow::Error> {
+        let shared = MontgomeryPoint(device.encryption_key).mul_clamped(self.encryption_secret);
+        if shared.0 == [0; 32] {
+            Err(anyhow!("low order encryption key"))?
+        }
+        let own = self.encryption_key();
+        let (first, second) = if own < device.encryption_key {
+            (own, device.encryption_key)
+        } else {
+            (device.encryption_key, own)
+        };
+        Ok(sp_core::hashing::blake2_256(
+            &[b"vane device sync".as_slice(), &shared.0, &first, &second].concat(),
+        ))
+    }
+
+    fn seal(
+        &self,
+        device: &PairedDevice,
+        records: &[SyncRecord],
+    ) -> Result<SealedRecords, anyhow::Error> {
+        let (nonce, ciphertext) = encrypt(&self.session_key(device)?, &records.encode())?;
+        Ok(SealedRecords { nonce, ciphertext })
+    }
+
+    fn open(
+        &self,
+        device: &PairedDevice,
+        sealed: &SealedRecords,
+    ) -> Result<Vec<SyncRecord>, anyhow::Error> {
+        let records = decrypt(
+            &self.session_key(device)?,
+            &sealed.nonce,
+            &sealed.ciphertext,
+        )?;
+        Vec::<SyncRecord>::decode(&mut &records[..])
+            .map_err(|err| anyhow!("undecodable sync records: {err:?}"))
+    }
+
+    async fn paired_device(&self, peer: &PeerId) -> Option<PairedDevice> {
+        let devices = match timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_paired_devices()
+            .await
+        {
+            Ok(devices) => devices,
+            Err(err) => {
+                warn!(target: "device_sync", "failed to read the paired devices; caused by: {err}");
+                return None;
+            }
+        };
+        devices
+            .into_iter()
+            .find(|device| device.peer_id == peer.to_base58())
+    }
+
+    /// records of this node, a record that changed since the last sync gets the current time
+    async fn local_records(&self) -> Result<Vec<SyncRecord>, anyhow::Error> {
+        let pending_transfers = self.state().pending_transfers.clone();
+        let db = timed_lock(&self.db_worker, "db_worker").await;
+        let stored = db.get_sync_records().await?;
+
+        let mut current = vec![];
+        for contact in db.get_all_saved_user_peers().await? {
+            let Some(peer_id) = contact.peer_id.clone() else {
+                continue;
+            };
+            // only the fields every db keeps, so a synced contact reads back unchanged
+            let contact = PeerRecord {
+                record_id: String::new(),
+                peer_id: Some(peer_id.clone()),
+                account_id1: contact.account_id1,
+                account_id2: None,
+                account_id3: None,
+                account_id4: None,
+                multi_addr: contact.multi_addr,
+                keypair: None,
+            };
+            current.push((SyncKind::Contact, peer_id, contact.encode()));
+        }
+        if let Some(document) = db.get_policies().await? {
+            current.push((SyncKind::Policies, String::new(), document.into_bytes()));
+        }
+        if let Some(cache) = pending_transfers {
+            let mut pending = cache.iter().map(|(_, tx)| tx).collect::<Vec<_>>();
+            pending.sort_by_key(|tx| tx.tx_nonce);
+            current.push((
+                SyncKind::PendingTransfers,
+                self.node_id.to_base58(),
+                pending.encode(),
+            ));
+        }
+
+        let mut records = vec![];
+        for (kind, key, value) in current {
+            let mut record = SyncRecord {
+                kind,
+                key,
+                value,
+                updated_at: 0,
+            };
+            match stored.iter().find(|stored| stored.id() == record.id()) {
+                Some(stored) if stored.value == record.value => records.push(stored.clone()),
+                stored => {
+                    record.updated_at = now().max(stored.map_or(0, |stored| stored.updated_at + 1));
+                    db.record_sync_record(record.clone()).await?;
+                    records.push(record);
+                }
+            }
+        }
+        // pending transfers of the other devices are passed on, so devices paired with this one
+        // only also see them
+        records.extend(stored.into_iter().filter(|stored| {
+            stored.kind == SyncKind::PendingTransfers && stored.key != self.node_id.to_base58()
+        }));
+        Ok(records)
+    }
+
+    /// store and apply the records of `peer` newer than the stored ones, returns how many were new
+    async fn accept(&self, peer: PeerId, records: Vec<SyncRecord>) -> usize {
+        let db = timed_lock(&self.db_worker, "db_worker").await;
+        let stored = match db.get_sync_records().await {
+            Ok(stored) => stored,
+            Err(err) => {
+                warn!(target: "device_sync", "failed to read the sync records; caused by: {err}");
+                return 0;
+            }
+        };
+
+        let mut accepted = 0;
+        for record in records.into_iter().take(MAX_SYNCED_RECORDS) {
+            // the own pending transfers are only updated by this node
+            if record.kind == SyncKind::PendingTransfers && record.key == self.node_id.to_base58() {
+                continue;
+            }
+            if stored
+                .iter()
+                .any(|stored| stored.id() == record.id() && !record.supersedes(stored))
+            {
+                continue;
+            }
+            let applied = match record.kind {
+                SyncKind::Contact => match PeerRecord::decode(&mut &record.value[..]) {
+                    Ok(contact)
+                        if contact.peer_id.as_ref() == Some(&record.key)
+                            && contact.account_id1.is_some()
+                            && contact.multi_addr.is_some() =>
+                    {
+                        let saved = db.get_all_saved_user_peers().await.map(|saved| {
+                            saved
+                                .iter()
+                                .any(|saved| saved.peer_id.as_ref() == Some(&record.key))
+                        });
+                        match saved {
+                            Ok(true) => Ok(()),
+                            Ok(false) => db.record_saved_user_peers(contact).await,
+                            Err(err) => Err(err),
+                        }
+                    }
+                    Ok(_) => Err(anyhow!("incomplete contact")),
+                    Err(err) => Err(anyhow!("undecodable contact: {err:?}")),
+                },
+                SyncKind::Policies => match String::from_utf8(record.value.clone()) {
+                    Ok(document) => match parse_policies(&document) {
+                        Ok(_) => db.set_policies(document).await,
+                        Err(err) => Err(anyhow!("invalid policies: {err}")),
+                    },
+                    Err(err) => Err(anyhow!("invalid policies: {err}")),
+                },
+                SyncKind::PendingTransfers => Vec::<TxStateMachine>::decode(&mut &record.value[..])
+                    .map(|_| ())
+                    .map_err(|err| anyhow!("undecodable pending transfers: {err:?}")),
+            };
+            if let Err(err) = applied {
+                warn!(target: "device_sync", "dropping the {:?} record {} from {peer}: {err}", record.kind, record.key);
+                continue;
+            }
+            if let Err(err) = db.record_sync_record(record).await {
+                warn!(target: "device_sync", "failed to store a record from {peer}; caused by: {err}");
+                continue;
+            }
+            accepted += 1;
+        }
+        if accepted > 0 {
+            info!(target: "device_sync", "synced {accepted} records from {peer}");
+        }
+        accepted
+    }
+
+    /// pending transfers of the paired devices, as last synced
+    pub async fn device_transfers(&self) -> Result<Vec<TxStateMachine>, anyhow::Error> {
+        let records = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_sync_records()
+            .await?;
+        let mut transfers = vec![];
+        for record in records.into_iter().filter(|record| {
+            record.kind == SyncKind::PendingTransfers && record.key != self.node_id.to_base58()
+        }) {
+            transfers.extend(
+                Vec::<TxStateMachine>::decode(&mut &record.value[..])
+                    .map_err(|err| anyhow!("undecodable pending transfers: {err:?}"))?,
+            );
+        }
+        Ok(transfers)
+    }
+
+    /// prove the pairing code to the device `peer` at `multi_addr` showing it
+    pub fn pair(
+        &self,
+        swarm: &mut Swarm<VaneBehaviour>,
+        peer: PeerId,
+        multi_addr: Multiaddr,
+        code: String,
+        reply: PairReply,
+    ) {
+        if !swarm.is_connected(&peer) {
+            if let Err(err) = swarm.dial(multi_addr) {
+                let _ = reply.send(Err(format!("failed to dial {peer}: {err}")));
+                return;
+            }
+        }
+        let encryption_key = self.encryption_key();
+        let request = SyncRequest::Pair {
+            proof: pairing_proof(&code, &self.node_id.to_base58(), &encryption_key),
+            encryption_key,
+            multi_addr: self.multi_addr.to_string(),
+        };
+        let request_id = swarm
+            .behaviour_mut()
+            .device_sync
+            .send_request(&peer, envelope::seal(&request));
+        self.state().pairing.insert(request_id, reply);
+    }
+
+    /// check the pairing proof of `peer` against the code shown and store it as paired
+    async fn accept_pairing(
+        &self,
+        peer: PeerId,
+        proof: [u8; 32],
+        encryption_key: [u8; 32],
+        multi_addr: String,
+    ) -> Result<PairedDevice, String> {
+        {
+            let mut state = self.state();
+            let Some(pairing) = state.pairing_code.as_mut() else {
+                return Err("no pairing code shown".to_string());
+            };
+            if pairing.expires_at < now() {
+                state.pairing_code = None;
+                return Err("pairing code expired".to_string());
+            }
+            if pairing_proof(&pairing.code, &peer.to_base58(), &encryption_key) != proof {
+                pairing.attempts += 1;
+                if pairing.attempts >= MAX_PAIRING_ATTEMPTS {
+                    state.pairing_code = None;
+                }
+                return Err("wrong pairing code".to_string());
+            }
+            state.pairing_code = None;
+        }
+        let device = self.paired(peer, encryption_key, multi_addr)?;
+        info!(target: "device_sync", "paired with the device {peer}");
+        self.record_paired(device).await
+    }
+
+    fn paired(
+        &self,
+        peer: PeerId,
+        encryption_key: [u8; 32],
+        multi_addr: String,
+    ) -> Result<PairedDevice, String> {
+        let address = Multiaddr::from_str(&multi_addr)
+            .map_err(|err| format!("invalid device multi addr; caused by: {err}"))?;
+        if address
+            .iter()
+            .any(|protocol| matches!(protocol, Protocol::P2p(addr_peer) if addr_peer != peer))
+        {
+            return Err(format!("{multi_addr} is not the address of {peer}"));
+        }
+        let device = PairedDevice {
+            peer_id: peer.to_base58(),
+            multi_addr,
+            encryption_key,
+            paired_at: now(),
+        };
+        self.session_key(&device).map_err(|err| err.to_string())?;
+        Ok(device)
+    }
+
+    async fn record_paired(&self, device: PairedDevice) -> Result<PairedDevice, String> {
+        timed_lock(&self.db_worker, "db_worker")
+            .await
+            .record_paired_device(device.clone())
+            .await
+            .map_err(|err| format!("failed to store the paired device; caused by: {err}"))?;
+        Ok(device)
+    }
+
+    async fn sealed_records(&self, device: &PairedDevice) -> Result<SealedRecords, anyhow::Error> {
+        let records = self.local_records().await?;
+        self.seal(device, &records)
+    }
+
+    /// send the records of this node to `peer` when it is a paired device, its response carries its
+    /// own
+    pub async fn start(&self, swarm: &mut Swarm<VaneBehaviour>, peer: PeerId) {
+        let Some(device) = self.paired_device(&peer).await else {
+            return;
+        };
+        match self.sealed_records(&device).await {
+            Ok(sealed) => {
+                swarm
+                    .behaviour_mut()
+                    .device_sync
+                    .send_request(&peer, envelope::seal(&SyncRequest::Records(sealed)));
+            }
+            Err(err) => {
+                warn!(target: "device_sync", "failed to seal the records for {peer}: {err}")
+            }
+        }
+    }
+
+    /// sync with the connected paired devices and dial the others, they sync once identified
+    pub async fn sync_all(&self, swarm: &mut Swarm<VaneBehaviour>) {
+        let devices = match timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_paired_devices()
+            .await
+        {
+            Ok(devices) => devices,
+            Err(err) => {
+                warn!(target: "device_sync", "failed to read the paired devices; caused by: {err}");
+                return;
+            }
+        };
+        for device in devices {
+            let Ok(peer) = PeerId::from_str(&device.peer_id) else {
+                continue;
+            };
+            if swarm.is_connected(&peer) {
+                self.start(swarm, peer).await;
+            } else if let Ok(address) = Multiaddr::from_str(&device.multi_addr) {
+                if let Err(err) = swarm.dial(address) {
+                    debug!(target: "device_sync", "failed to dial the device {peer}: {err}");
+                }
+            }
+        }
+    }
+
+    async fn respond(&self, peer: PeerId, request: &[u8]) -> SyncResponse {
+        match envelope::decode::<SyncRequest>(request) {
+            Ok(SyncRequest::Pair {
+                proof,
+                encryption_key,
+                multi_addr,
+            }) => match self
+                .accept_pairing(peer, proof, encryption_key, multi_addr)
+                .await
+            {
+                Ok(_) => SyncResponse::Paired {
+                    encryption_key: self.encryption_key(),
+                    multi_addr: self.multi_addr.to_string(),
+                },
+                Err(reason) => {
+                    warn!(target: "device_sync", "refused pairing {peer}: {reason}");
+                    SyncResponse::Refused(reason)
+                }
+            },
+            Ok(SyncRequest::Records(sealed)) => {
+                let Some(device) = self.paired_device(&peer).await else {
+                    return SyncResponse::Refused("not paired".to_string());
+                };
+                match self.open(&device, &sealed) {
+                    Ok(records) => {
+                        self.accept(peer, records).await;
+                    }
+                    Err(err) => {
+                        return SyncResponse::Refused(format!("failed to open the records: {err}"))
+                    }
+                }
+                match self.sealed_records(&device).await {
+                    Ok(sealed) => SyncResponse::Records(sealed),
+                    Err(err) => SyncResponse::Refused(format!("failed to seal the records: {err}")),
+                }
+            }
+            Err(err) => SyncResponse::Refused(format!("undecodable request: {err:?}")),
+        }
+    }
+
+    pub async fn handle_event(
+        &self,
+        swarm: &mut Swarm<VaneBehaviour>,
+        event: Event<Vec<u8>, Result<Vec<u8>, anyhow::Error>>,
+    ) {
+        match event {
+            Event::Message {
+                peer,
+                message:
+                    Message::Request {
+                        request, channel, ..
+                    },
+            } => {
+                let response = self.respond(peer, &request).await;
+                if swarm
+                    .behaviour_mut()
+                    .device_sync
+                    .send_response(channel, Ok(envelope::seal(&response)))
+                    .is_err()
+                {
+                    debug!(target: "device_sync", "{peer} closed the sync before the response");
+                }
+            }
+            Event::Message {
+                peer,
+                message:
+                    Message::Response {
+                        request_id,
+                        response,
+                    },
+            } => {
+                let pairing = self.state().pairing.remove(&request_id);
+                let response = response.and_then(|response| {
+                    envelope::decode::<SyncResponse>(&response)
+                        .map_err(|err| anyhow!("undecodable sync response: {err:?}"))
+                });
+                match (pairing, response) {
+                    (
+                        Some(reply),
+                        Ok(SyncResponse::Paired {
+                            encryption_key,
+                            multi_addr,
+                        }),
+                    ) => {
+                        let paired = match self.paired(peer, encryption_key, multi_addr) {
+                            Ok(device) => self.record_paired(device).await,
+                            Err(reason) => Err(reason),
+                        };
+                        let synced = paired.is_ok();
+                        if synced {
+                            info!(target: "device_sync", "paired with the device {peer}");
+                        }
+                        let _ = reply.send(paired);
+                        if synced {
+                            self.start(swarm, peer).await;
+                        }
+                    }
+                    (Some(reply), Ok(SyncResponse::Refused(reason))) => {
+                        let _ = reply.send(Err(reason));
+                    }
+                    (Some(reply), Ok(_)) => {
+                        let _ = reply.send(Err("unexpected pairing response".to_string()));
+                    }
+                    (Some(reply), Err(err)) => {
+                        let _ = reply.send(Err(err.to_string()));
+                    }
+                    (None, Ok(SyncResponse::Records(sealed))) => {
+                        let Some(device) = self.paired_device(&peer).await else {
+                            return;
+                        };
+                        match self.open(&device, &sealed) {
+                            Ok(records) => {
+                                self.accept(peer, records).await;
+                            }
+                            Err(err) => {
+                                warn!(target: "device_sync", "failed to open the records of {peer}: {err}")
+                            }
+                        }
+                    }
+                    (None, Ok(SyncResponse::Refused(reason))) => {
+                        warn!(target: "device_sync", "{peer} refused to sync: {reason}")
+                    }
+                    (None, Ok(SyncResponse::Paired { .. })) => {}
+                    (None, Err(err)) => {
+                        debug!(target: "device_sync", "sync with {peer} failed: {err}")
+                    }
+                }
+            }
+            Event::OutboundFailure {
+                peer,
+                request_id,
+                error,
+            } => {
+                if let Some(reply) = self.state().pairing.remove(&request_id) {
+                    let _ = reply.send(Err(format!("pairing request failed: {error:?}")));
+                }
+                debug!(target: "device_sync", "sync with {peer} failed: {error:?}")
+            }
+            Event::InboundFailure { peer, error, .. } => {
+                debug!(target: "device_sync", "sync from {peer} failed: {error:?}")
+            }
+            Event::ResponseSent { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,295 @@
+// peer discovery backends
+// peers register their peer id, multi address and accounts so senders can find the receiver's node,
+// `Airtable` is the hosted backend, `LocalDiscovery` keeps records in process for tests and local networks
+
+use crate::rpc::Airtable;
+use alloc::sync::Arc;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use primitives::accepted_assets::{decode_accepted_assets, AcceptedAssets};
+use primitives::attestations::{decode_attestations, IdentityAttestation};
+use primitives::data_structure::{AirtableRequestBody, Discovery, Fields, PostRecord, Record};
+use primitives::revocation::{decode_revocations, AccountRevocation};
+use std::sync::Mutex;
+
+/// discovery backend shared by the p2p and rpc layers
+#[async_trait]
+pub trait PeerDiscovery: Send + Sync {
+    /// all registered peers
+    async fn list_all_peers(&self) -> Result<Vec<Discovery>, anyhow::Error>;
+
+    /// peers registered with `account`, e.g. the devices of a receiver
+    async fn peers_of(&self, account: &str) -> Result<Vec<Discovery>, anyhow::Error> {
+        let peers = self.list_all_peers().await?;
+        Ok(peers
+            .into_iter()
+            .filter(|peer| peer.account_ids.iter().any(|id| id == account))
+            .collect())
+    }
+
+    /// register a peer, returns the created record
+    async fn create_peer(&self, record: AirtableRequestBody) -> Result<Record, anyhow::Error>;
+
+    /// update the primary account of the peer record
+    async fn update_peer(
+        &self,
+        record: PostRecord,
+        record_id: String,
+    ) -> Result<Record, anyhow::Error>;
+
+    /// replace the third party attestations stored with the peer record
+    async fn update_attestations(
+        &self,
+        record_id: String,
+        attestations: Vec<IdentityAttestation>,
+    ) -> Result<Record, anyhow::Error>;
+
+    /// replace the account revocations stored with the peer record
+    async fn update_revocations(
+        &self,
+        record_id: String,
+        revocations: Vec<AccountRevocation>,
+    ) -> Result<Record, anyhow::Error>;
+
+    /// replace the accepted assets advertised with the peer record
+    async fn update_accepted_assets(
+        &self,
+        record_id: String,
+        accepted_assets: Vec<AcceptedAssets>,
+    ) -> Result<Record, anyhow::Error>;
+
+    /// move the peer record to the rotated identity of the node, its accounts are kept
+    async fn update_identity(
+        &self,
+        record_id: String,
+        peer_id: String,
+        multi_addr: String,
+    ) -> Result<Record, anyhow::Error>;
+
+    #[cfg(feature = "e2e")]
+    async fn delete_all(&self) -> Result<(), anyhow::Error>;
+}
+
+#[async_trait]
+impl PeerDiscovery for Airtable {
+    async fn list_all_peers(&self) -> Result<Vec<Discovery>, anyhow::Error> {
+        Airtable::list_all_peers(self).await
+    }
+
+    async fn peers_of(&self, account: &str) -> Result<Vec<Discovery>, anyhow::Error> {
+        Airtable::peers_of(self, account).await
+    }
+
+    async fn create_peer(&self, record: AirtableRequestBody) -> Result<Record, anyhow::Error> {
+        Airtable::create_peer(self, record).await
+    }
+
+    async fn update_peer(
+        &self,
+        record: PostRecord,
+        record_id: String,
+    ) -> Result<Record, anyhow::Error> {
+        Airtable::update_peer(self, record, record_id).await
+    }
+
+    async fn update_attestations(
+        &self,
+        record_id: String,
+        attestations: Vec<IdentityAttestation>,
+    ) -> Result<Record, anyhow::Error> {
+        Airtable::update_attestations(self, record_id, attestations).await
+    }
+
+    async fn update_revocations(
+        &self,
+        record_id: String,
+        revocations: Vec<AccountRevocation>,
+    ) -> Result<Record, anyhow::Error> {
+        Airtable::update_revocations(self, record_id, revocations).await
+    }
+
+    async fn update_accepted_assets(
+        &self,
+        record_id: String,
+        accepted_assets: Vec<AcceptedAssets>,
+    ) -> Result<Record, anyhow::Error> {
+        Airtable::update_accepted_assets(self, record_id, accepted_assets).await
+    }
+
+    async fn update_identity(
+        &self,
+        record_id: String,
+        peer_id: String,
+        multi_addr: String,
+    ) -> Result<Record, anyhow::Error> {
+        Airtable::update_identity(self, record_id, peer_id, multi_addr).await
+    }
+
+    #[cfg(feature = "e2e")]
+    async fn delete_all(&self) -> Result<(), anyhow::Error> {
+        Airtable::delete_all(self).await
+    }
+}
+
+/// in-process discovery, clones share the same records so nodes in one process can find each other
+#[derive(Clone, Default)]
+pub struct LocalDiscovery {
+    records: Arc<Mutex<Vec<Record>>>,
+}
+
+impl LocalDiscovery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn records(&self) -> Result<std::sync::MutexGuard<'_, Vec<Record>>, anyhow::Error> {
+        self.records
+            .lock()
+            .map_err(|_| anyhow!("local discovery lock poisoned"))
+    }
+}
+
+#[async_trait]
+impl PeerDiscovery for LocalDiscovery {
+    async fn list_all_peers(&self) -> Result<Vec<Discovery>, anyhow::Error> {
+        let peers = self
+            .records()?
+            .iter()
+            .cloned()
+            .map(|record| {
+                let Fields {
+                    multi_addr,
+                    peer_id,
+                    account_id1,
+                    account_id2,
+                    account_id3,
+                    account_id4,
+                    attestations,
+                    revocations,
+                    accepted_assets,
+                } = record.fields;
+                Discovery {
+                    id: record.id,
+                    peer_id,
+                    multi_addr,
+                    account_ids: [account_id1, account_id2, account_id3, account_id4]
+                        .into_iter()
+                        .flatten()
+                        .collect(),
+                    attestations: decode_attestations(attestations.as_deref()),
+                    revocations: decode_revocations(revocations.as_deref()),
+                    accepted_assets: decode_accepted_assets(accepted_assets.as_deref()),
+                }
+            })
+            .collect();
+        Ok(peers)
+    }
+
+    async fn create_peer(&self, record: AirtableRequestBody) -> Result<Record, anyhow::Error> {
+        let fields = record
+            .records
+            .into_iter()
+            .next()
+            .ok_or(anyhow!("no peer record to create"))?
+            .fields;
+
+        let mut records = self.records()?;
+        let record = Record {
+            id: format!("rec{}", records.len()),
+            created_time: String::new(),
+            fields,
+        };
+        records.push(record.clone());
+        Ok(record)
+    }
+
+    // same as airtable, only `accountId1` is patched
+    async fn update_peer(
+        &self,
+        record: PostRecord,
+        record_id: String,
+    ) -> Result<Record, anyhow::Error> {
+        let mut records = self.records()?;
+        let stored = records
+            .iter_mut()
+            .find(|stored| stored.id == record_id)
+            .ok_or(anyhow!("peer record not found: {record_id}"))?;
+        // only the accounts present are updated, like an airtable patch
+        let fields = record.fields;
+        for (slot, account) in [
+            (&mut stored.fields.account_id1, fields.account_id1),
+            (&mut stored.fields.account_id2, fields.account_id2),
+            (&mut stored.fields.account_id3, fields.account_id3),
+            (&mut stored.fields.account_id4, fields.account_id4),
+        ] {
+            if account.is_some() {
+                *slot = account;
+            }
+        }
+        Ok(stored.clone())
+    }
+
+    async fn update_attestations(
+        &self,
+        record_id: String,
+        attestations: Vec<IdentityAttestation>,
+    ) -> Result<Record, anyhow::Error> {
+        let mut records = self.records()?;
+        let stored = records
+            .iter_mut()
+            .find(|stored| stored.id == record_id)
+            .ok_or(anyhow!("peer record not found: {record_id}"))?;
+        stored.fields.attestations = Some(serde_json::to_string(&attestations)?);
+        Ok(stored.clone())
+    }
+
+    async fn update_revocations(
+        &self,
+        record_id: String,
+        revocations: Vec<AccountRevocation>,
+    ) -> Result<Record, anyhow::Error> {
+        let mut records = self.records()?;
+        let stored = records
+            .iter_mut()
+            .find(|stored| stored.id == record_id)
+            .ok_or(anyhow!("peer record not found: {record_id}"))?;
+        stored.fields.revocations = Some(serde_json::to_string(&revocations)?);
+        Ok(stored.clone())
+    }
+
+    async fn update_accepted_assets(
+        &self,
+        record_id: String,
+        accepted_assets: Vec<AcceptedAssets>,
+    ) -> Result<Record, anyhow::Error> {
+        let mut records = self.records()?;
+        let stored = records
+            .iter_mut()
+            .find(|stored| stored.id == record_id)
+            .ok_or(anyhow!("peer record not found: {record_id}"))?;
+        stored.fields.accepted_assets = Some(serde_json::to_string(&accepted_assets)?);
+        Ok(stored.clone())
+    }
+
+    async fn update_identity(
+        &self,
+        record_id: String,
+        peer_id: String,
+        multi_addr: String,
+    ) -> Result<Record, anyhow::Error> {
+        let mut records = self.records()?;
+        let stored = records
+            .iter_mut()
+            .find(|stored| stored.id == record_id)
+            .ok_or(anyhow!("peer record not found: {record_id}"))?;
+        stored.fields.peer_id = Some(peer_id);
+        stored.fields.multi_addr = Some(multi_addr);
+        Ok(stored.clone())
+    }
+
+    #[cfg(feature = "e2e")]
+    async fn delete_all(&self) -> Result<(), anyhow::Error> {
+        self.records()?.clear();
+        Ok(())
+    }
+}
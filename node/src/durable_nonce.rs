@@ -0,0 +1,103 @@
+// durable nonces of the solana senders
+// the operator assigns a nonce account to a solana sender, its transfers are then signed against
+// the nonce stored in the account rather than a recent blockhash and do not expire while the
+// receiver attests or a hardware signer is slow to sign. the nonce is read at genesis and checked
+// again when the sender confirms, a nonce advanced in between means the signed tx can not land
+
+use alloy::providers::{Provider, ProviderBuilder, ReqwestProvider};
+use anyhow::anyhow;
+use base58::FromBase58;
+use primitives::durable_nonce::{DurableNonce, SYSTEM_PROGRAM_ID};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// `getAccountInfo` result, `None` for an account that does not exist
+#[derive(Deserialize)]
+struct AccountInfo {
+    value: Option<Account>,
+}
+
+#[derive(Deserialize)]
+struct Account {
+    owner: String,
+    /// data and its encoding
+    data: (String, String),
+}
+
+#[derive(Clone)]
+pub struct DurableNonces {
+    provider: ReqwestProvider,
+    /// nonce account of each sender, base58
+    accounts: HashMap<String, String>,
+}
+
+impl DurableNonces {
+    pub fn new(rpc_url: &str) -> Result<Self, anyhow::Error> {
+        let rpc_url = rpc_url
+            .parse()
+            .map_err(|err| anyhow!("Solana rpc url parse error: {err}"))?;
+        Ok(Self {
+            provider: ProviderBuilder::new().on_http(rpc_url),
+            accounts: HashMap::new(),
+        })
+    }
+
+    /// sign the transfers of `sender` against the nonce of `nonce_account`, the sender has to be
+    /// its authority
+    pub fn with_nonce_account(mut self, sender: &str, nonce_account: &str) -> Self {
+        self.accounts.insert(sender.into(), nonce_account.into());
+        self
+    }
+
+    /// nonce the transfers of `sender` are signed against, `None` without a nonce account
+    pub async fn nonce_of(&self, sender: &str) -> Result<Option<DurableNonce>, anyhow::Error> {
+        let Some(nonce_account) = self.accounts.get(sender) else {
+            return Ok(None);
+        };
+        let nonce = self.fetch(nonce_account).await?;
+        if nonce.authority != sender {
+            Err(anyhow!(
+                "the nonce account {nonce_account} of {sender} is advanced by {}, not by the sender",
+                nonce.authority
+            ))?
+        }
+        Ok(Some(nonce))
+    }
+
+    /// the nonce `tx_nonce` is still the one stored in its account, the signed tx can land
+    pub async fn ensure_current(&self, tx_nonce: &DurableNonce) -> Result<(), anyhow::Error> {
+        let nonce = self.fetch(&tx_nonce.nonce_account).await?;
+        if nonce.nonce != tx_nonce.nonce {
+            Err(anyhow!(
+                "the nonce account {} advanced since the tx was built, send the transfer again",
+                tx_nonce.nonce_account
+            ))?
+        }
+        Ok(())
+    }
+
+    async fn fetch(&self, nonce_account: &str) -> Result<DurableNonce, anyhow::Error> {
+        let params = (nonce_account, serde_json::json!({ "encoding": "base58" }));
+        let info: AccountInfo = self
+            .provider
+            .raw_request("getAccountInfo".into(), params)
+            .await
+            .map_err(|err| anyhow!("failed to fetch the nonce account; caused by: {err}"))?;
+        let account = info
+            .value
+            .ok_or(anyhow!("the nonce account {nonce_account} does not exist"))?;
+        if account.owner != SYSTEM_PROGRAM_ID {
+            Err(anyhow!(
+                "{nonce_account} is not owned by the system program"
+            ))?
+        }
+        let data = account
+            .data
+            .0
+            .from_base58()
+            .map_err(|err| anyhow!("nonce account data is not base58: {err:?}"))?;
+        DurableNonce::from_account_data(nonce_account, &data)?.ok_or(anyhow!(
+            "the nonce account {nonce_account} is not initialized"
+        ))
+    }
+}
@@ -0,0 +1,168 @@
+// tx lifecycle event bus
+// mirrors every tx state transition the node records onto a nats subject or a kafka topic as a
+// `TxLifecycleEvent`, keyed by the tx id. publishing runs on a task of its own behind a bounded
+// queue, a slow or unreachable bus delays and in the end drops events, never the transfers
+
+use alloc::sync::Arc;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use log::warn;
+use primitives::data_structure::TxEvent;
+use primitives::lifecycle::TxLifecycleEvent;
+use rskafka::client::partition::{Compression, PartitionClient, UnknownTopicHandling};
+use rskafka::client::ClientBuilder;
+use rskafka::record::Record;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::sync::Mutex;
+
+/// subject or topic the events are published on by default
+pub const DEFAULT_TX_EVENTS_TOPIC: &str = "vane.tx.events";
+
+/// events waiting to be published, further events are dropped
+const QUEUE_CAPACITY: usize = 1024;
+
+/// attempts at publishing an event before it is dropped
+const PUBLISH_ATTEMPTS: u32 = 3;
+
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// message bus the tx lifecycle events are published on
+#[async_trait]
+pub trait EventBus: Send + Sync {
+    /// publish `payload` under `key`, the events of a key keep their order
+    async fn publish(&self, key: &str, payload: Vec<u8>) -> Result<(), anyhow::Error>;
+}
+
+/// connect to the bus at `url`, `nats://host:4222` or `kafka://host:9092,host2:9092`
+pub async fn connect(url: &str, topic: &str) -> Result<Arc<dyn EventBus>, anyhow::Error> {
+    if url.starts_with("nats://") || url.starts_with("tls://") {
+        Ok(Arc::new(NatsBus::connect(url, topic).await?))
+    } else if let Some(brokers) = url.strip_prefix("kafka://") {
+        let brokers = brokers.split(',').map(str::to_string).collect();
+        Ok(Arc::new(KafkaBus::connect(brokers, topic).await?))
+    } else {
+        Err(anyhow!(
+            "event bus url {url} is neither nats:// nor kafka://"
+        ))
+    }
+}
+
+/// nats subject, the tx id is sent as the `Vane-Tx-Id` header
+pub struct NatsBus {
+    client: async_nats::Client,
+    subject: String,
+}
+
+impl NatsBus {
+    pub async fn connect(url: &str, subject: &str) -> Result<Self, anyhow::Error> {
+        let client = async_nats::connect(url)
+            .await
+            .map_err(|err| anyhow!("failed to connect to nats at {url}; caused by: {err}"))?;
+        Ok(Self {
+            client,
+            subject: subject.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl EventBus for NatsBus {
+    async fn publish(&self, key: &str, payload: Vec<u8>) -> Result<(), anyhow::Error> {
+        let mut headers = async_nats::HeaderMap::new();
+        headers.insert("Vane-Tx-Id", key);
+        self.client
+            .publish_with_headers(self.subject.clone(), headers, payload.into())
+            .await?;
+        Ok(())
+    }
+}
+
+/// first partition of a kafka topic, so the events of the node keep their order
+pub struct KafkaBus {
+    partition: PartitionClient,
+}
+
+impl KafkaBus {
+    pub async fn connect(brokers: Vec<String>, topic: &str) -> Result<Self, anyhow::Error> {
+        let client = ClientBuilder::new(brokers)
+            .build()
+            .await
+            .map_err(|err| anyhow!("failed to connect to kafka; caused by: {err}"))?;
+        let partition = client
+            .partition_client(topic, 0, UnknownTopicHandling::Retry)
+            .await?;
+        Ok(Self { partition })
+    }
+}
+
+#[async_trait]
+impl EventBus for KafkaBus {
+    async fn publish(&self, key: &str, payload: Vec<u8>) -> Result<(), anyhow::Error> {
+        let record = Record {
+            key: Some(key.as_bytes().to_vec()),
+            value: Some(payload),
+            headers: BTreeMap::new(),
+            timestamp: chrono::Utc::now(),
+        };
+        self.partition
+            .produce(vec![record], Compression::NoCompression)
+            .await?;
+        Ok(())
+    }
+}
+
+/// queue of the recorded tx events, drained onto the bus by `run`
+#[derive(Clone)]
+pub struct TxEventPublisher {
+    bus: Arc<dyn EventBus>,
+    /// peer id the node started with, stable across identity rotations
+    node: String,
+    queue: Sender<TxEvent>,
+    queued: Arc<Mutex<Receiver<TxEvent>>>,
+}
+
+impl TxEventPublisher {
+    pub fn new(bus: Arc<dyn EventBus>, node: impl Into<String>) -> Self {
+        let (queue, queued) = mpsc::channel(QUEUE_CAPACITY);
+        Self {
+            bus,
+            node: node.into(),
+            queue,
+            queued: Arc::new(Mutex::new(queued)),
+        }
+    }
+
+    /// queue `event` for publishing without waiting on the bus
+    pub(crate) fn publish(&self, event: &TxEvent) {
+        if self.queue.try_send(event.clone()).is_err() {
+            warn!(target: "EventBus", "event bus queue full, dropped a {:?} event of tx {:?}", event.status, event.tx_id);
+        }
+    }
+
+    /// publish the queued events in order until the node stops
+    pub async fn run(self) {
+        let mut queued = self.queued.lock().await;
+        while let Some(event) = queued.recv().await {
+            let event = TxLifecycleEvent::new(self.node.clone(), &event);
+            let key = format!("{:?}", event.tx_id);
+            let payload = match serde_json::to_vec(&event) {
+                Ok(payload) => payload,
+                Err(err) => {
+                    warn!(target: "EventBus", "failed to encode the event of tx {key}; caused by: {err}");
+                    continue;
+                }
+            };
+            for attempt in 1..=PUBLISH_ATTEMPTS {
+                match self.bus.publish(&key, payload.clone()).await {
+                    Ok(()) => break,
+                    Err(err) if attempt == PUBLISH_ATTEMPTS => {
+                        warn!(target: "EventBus", "dropped the {} event of tx {key}; caused by: {err}", event.status)
+                    }
+                    Err(_) => tokio::time::sleep(RETRY_DELAY * attempt).await,
+                }
+            }
+        }
+    }
+}
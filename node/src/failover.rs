@@ -0,0 +1,146 @@
+// p2p failover
+// redundant nodes share one identity, loaded from their keystore, and a store holding the lease of
+// that identity. only the instance holding the lease, the leader, runs the swarm while the standbys
+// serve the rpc and wait. a standby claiming the lease once it expired announces its own address in
+// discovery and starts the swarm. a leader unable to renew stops its swarm before the lease expires,
+// so the identity never runs on two instances at once
+
+use crate::cluster::{unix_now, ClusterStore, P2pLease};
+use crate::locks::timed_lock;
+use crate::MainServiceWorker;
+use alloc::sync::Arc;
+use anyhow::anyhow;
+use log::{info, warn};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// leader election of the p2p identity between redundant nodes
+#[derive(Clone)]
+pub struct Failover {
+    store: Arc<dyn ClusterStore>,
+    /// id of this instance, a restarted instance joins under a new one
+    instance: String,
+    lease: Duration,
+    leading: watch::Sender<bool>,
+}
+
+impl Failover {
+    pub const DEFAULT_LEASE: Duration = Duration::from_secs(15);
+
+    /// `lease` is how long the standbys wait on a crashed leader before taking over
+    pub fn new(store: Arc<dyn ClusterStore>, lease: Duration) -> Self {
+        Self {
+            store,
+            instance: format!("{:016x}", rand::random::<u64>()),
+            lease,
+            leading: watch::channel(false).0,
+        }
+    }
+
+    pub fn instance(&self) -> &str {
+        &self.instance
+    }
+
+    /// whether this instance runs the swarm
+    pub fn is_leader(&self) -> bool {
+        *self.leading.borrow()
+    }
+
+    /// wait until this instance leads
+    pub(crate) async fn elected(&self) {
+        let _ = self.leading.subscribe().wait_for(|leading| *leading).await;
+    }
+
+    /// wait until this instance stops leading
+    pub(crate) async fn deposed(&self) {
+        let _ = self.leading.subscribe().wait_for(|leading| !*leading).await;
+    }
+
+    /// claim and renew the p2p lease until the node stops
+    pub async fn run(self, worker: MainServiceWorker) {
+        let renewal = self.lease / 3;
+        let mut interval = tokio::time::interval(renewal);
+        // end of the lease last taken, a leader failing to renew steps down before it
+        let mut held_until = 0;
+        // whether discovery lists the address of this instance since it leads
+        let mut announced = false;
+        loop {
+            interval.tick().await;
+            let now = unix_now();
+            match self.claim(&worker, now).await {
+                Ok(true) => {
+                    held_until = now + self.lease.as_secs();
+                    if !self.is_leader() {
+                        info!(target: "Failover", "instance {} leads the p2p identity", self.instance);
+                        announced = false;
+                        self.leading.send_replace(true);
+                    }
+                    if !announced {
+                        match self.announce(&worker).await {
+                            Ok(()) => announced = true,
+                            Err(err) => {
+                                warn!(target: "Failover", "failed to announce the p2p address; caused by: {err}")
+                            }
+                        }
+                    }
+                }
+                Ok(false) => {
+                    if self.is_leader() {
+                        warn!(target: "Failover", "another instance took the p2p identity over");
+                        self.leading.send_replace(false);
+                    }
+                }
+                Err(err) => {
+                    warn!(target: "Failover", "failed to renew the p2p lease; caused by: {err}");
+                    if self.is_leader() && now + renewal.as_secs() >= held_until {
+                        warn!(target: "Failover", "p2p lease expiring, stepping down");
+                        self.leading.send_replace(false);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn claim(&self, worker: &MainServiceWorker, now: u64) -> Result<bool, anyhow::Error> {
+        let identity = timed_lock(&worker.tx_rpc_worker, "tx_rpc_worker")
+            .await
+            .identity
+            .clone();
+        let lease = P2pLease {
+            peer_id: identity.peer_id().to_string(),
+            owner: self.instance.clone(),
+            multi_addr: identity.multi_addr().to_string(),
+            expires_at: now + self.lease.as_secs(),
+        };
+        self.store.claim_p2p(lease, now).await
+    }
+
+    /// point the discovery records of the identity to the address of this instance, every
+    /// instance registered one on start and the accounts may be listed on any of them
+    async fn announce(&self, worker: &MainServiceWorker) -> Result<(), anyhow::Error> {
+        let identity = timed_lock(&worker.tx_rpc_worker, "tx_rpc_worker")
+            .await
+            .identity
+            .clone();
+        let peer_id = identity.peer_id().to_string();
+        let multi_addr = identity.multi_addr().to_string();
+        let records = worker
+            .discovery
+            .list_all_peers()
+            .await?
+            .into_iter()
+            .filter(|peer| peer.peer_id.as_deref() == Some(peer_id.as_str()))
+            .collect::<Vec<_>>();
+        if records.is_empty() {
+            Err(anyhow!("no discovery record of {peer_id}"))?
+        }
+        for record in records {
+            worker
+                .discovery
+                .update_identity(record.id, peer_id.clone(), multi_addr.clone())
+                .await?;
+        }
+        info!(target: "Failover", "announced the p2p address {multi_addr}");
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+// gas price oracle for evm chains
+// tracks the base fees and paid priority fees of recent blocks through `eth_feeHistory` and turns them
+// into low / medium / high fee tiers, refreshed at most once per block time
+
+use alloc::sync::Arc;
+use alloy::eips::BlockNumberOrTag;
+use alloy::providers::{Provider, ReqwestProvider};
+use anyhow::anyhow;
+use primitives::fees::FeeTiers;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// number of recent blocks the tiers are computed from
+pub const FEE_HISTORY_BLOCKS: u64 = 10;
+/// priority fee percentiles of the low, medium and high tiers
+pub const REWARD_PERCENTILES: [f64; 3] = [10.0, 50.0, 90.0];
+
+#[derive(Clone)]
+pub struct GasOracle {
+    provider: ReqwestProvider,
+    refresh_interval: Duration,
+    latest: Arc<Mutex<Option<(Instant, FeeTiers)>>>,
+}
+
+impl GasOracle {
+    /// ethereum block time, tiers do not change in between blocks
+    pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(12);
+
+    pub fn new(provider: ReqwestProvider) -> Self {
+        Self {
+            provider,
+            refresh_interval: Self::DEFAULT_REFRESH_INTERVAL,
+            latest: Arc::new(Default::default()),
+        }
+    }
+
+    pub fn with_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    /// fee tiers of the next block
+    pub async fn fee_tiers(&self) -> Result<FeeTiers, anyhow::Error> {
+        let mut latest = self.latest.lock().await;
+        if let Some((fetched_at, tiers)) = latest.as_ref() {
+            if fetched_at.elapsed() < self.refresh_interval {
+                return Ok(*tiers);
+            }
+        }
+
+        let tiers = self.fee_history_tiers(BlockNumberOrTag::Latest).await?;
+        *latest = Some((Instant::now(), tiers));
+        Ok(tiers)
+    }
+
+    /// fee tiers as they were for `block_number`, from the history up to its parent block
+    pub async fn fee_tiers_at(&self, block_number: u64) -> Result<FeeTiers, anyhow::Error> {
+        self.fee_history_tiers(BlockNumberOrTag::Number(block_number.saturating_sub(1)))
+            .await
+    }
+
+    async fn fee_history_tiers(&self, newest: BlockNumberOrTag) -> Result<FeeTiers, anyhow::Error> {
+        let history = self
+            .provider
+            .get_fee_history(FEE_HISTORY_BLOCKS, newest, &REWARD_PERCENTILES)
+            .await
+            .map_err(|err| anyhow!("failed to fetch fee history; caused by: {err}"))?;
+        FeeTiers::from_fee_history(
+            &history.base_fee_per_gas,
+            &history.reward.unwrap_or_default(),
+        )
+        .ok_or(anyhow!("fee history returned no base fees"))
+    }
+}
@@ -0,0 +1,438 @@
+// graphql api
+// an optional async-graphql endpoint at `POST /graphql` on the rpc port for dashboard builders, a
+// single query fetches the transfers, counterparties, fee stats and peers a view needs with their
+// filters instead of several rpc calls. the fields resolve through the `TransactionRpcServer`
+// methods of the rpc worker and each requires the role of the json-rpc method it calls.
+// `GET /graphql` serves the schema in the graphql sdl
+
+use crate::rest::json_response;
+use crate::rpc::{vane_error, TransactionRpcServer, TransactionRpcWorker};
+use crate::rpc_middleware::{read_body, AccessControl, Role};
+use alloy::hex;
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, ErrorExtensions, InputObject, Object, Schema,
+    SimpleObject,
+};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context as TaskContext, Poll};
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use primitives::counterparty::CounterpartyProfile;
+use primitives::data_structure::{ChainSupported, DbTxStateMachine, Discovery, PeerStats};
+use primitives::errors::VaneRpcError;
+use primitives::fees::FeeStats;
+use primitives::history::HistorySearch;
+use tower::{Layer, Service};
+
+pub type VaneSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+/// deepest selection a query may make
+const MAX_QUERY_DEPTH: usize = 8;
+
+/// schema resolving through `worker`
+pub fn schema(worker: TransactionRpcWorker) -> VaneSchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .data(worker)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .finish()
+}
+
+/// graphql error of a vane error, the error json goes in the `data` extension
+fn graphql_error(err: VaneRpcError) -> async_graphql::Error {
+    let data = serde_json::to_value(&err)
+        .ok()
+        .and_then(|data| async_graphql::Value::from_json(data).ok());
+    async_graphql::Error::new(err.to_string()).extend_with(|_, extensions| {
+        extensions.set("code", err.code());
+        if let Some(data) = data {
+            extensions.set("data", data);
+        }
+    })
+}
+
+/// the rpc worker, once the caller holds the role `method` requires
+fn authorize<'a>(
+    ctx: &Context<'a>,
+    method: &str,
+) -> async_graphql::Result<&'a TransactionRpcWorker> {
+    let role = ctx.data::<Role>()?;
+    let required = Role::required_for(method);
+    if *role < required {
+        Err(graphql_error(VaneRpcError::AccessDenied {
+            method: method.to_string(),
+            role: required.to_string(),
+        }))?
+    }
+    ctx.data::<TransactionRpcWorker>()
+}
+
+fn invalid(reason: String) -> async_graphql::Error {
+    graphql_error(VaneRpcError::InvalidParams { reason })
+}
+
+/// filters of the transfers, all of them must match
+#[derive(Default, InputObject)]
+pub struct TransferFilter {
+    /// counterparty address in any spelling, part of the sender name or memo, a reference or a tag
+    pub text: Option<String>,
+    /// `Polkadot`, `Ethereum`, `Bnb` or `Solana`
+    pub network: Option<String>,
+    /// inclusive amount range in the smallest unit of the token, as decimal strings
+    pub min_amount: Option<String>,
+    pub max_amount: Option<String>,
+    /// only succeeded or only failed transfers
+    pub success: Option<bool>,
+    /// most transfers returned, newest first
+    pub limit: Option<u32>,
+}
+
+impl TryFrom<TransferFilter> for HistorySearch {
+    type Error = async_graphql::Error;
+
+    fn try_from(filter: TransferFilter) -> Result<Self, Self::Error> {
+        let amount = |amount: Option<String>| {
+            amount
+                .map(|amount| {
+                    amount
+                        .parse()
+                        .map_err(|_| invalid(format!("invalid amount {amount}")))
+                })
+                .transpose()
+        };
+        let network = filter
+            .network
+            .map(|network| {
+                serde_json::from_value::<ChainSupported>(network.clone().into())
+                    .map_err(|_| invalid(format!("unsupported network {network}")))
+            })
+            .transpose()?;
+        Ok(HistorySearch {
+            text: filter.text,
+            network,
+            min_amount: amount(filter.min_amount)?,
+            max_amount: amount(filter.max_amount)?,
+            success: filter.success,
+            limit: filter.limit,
+        })
+    }
+}
+
+/// submitted transfer of the history
+#[derive(SimpleObject)]
+pub struct Transfer {
+    /// 0x hex hash of the chain tx
+    pub tx_hash: String,
+    pub network: String,
+    pub amount: String,
+    pub success: bool,
+    pub sender: String,
+    pub receiver: String,
+    pub sender_name: Option<String>,
+    pub memo: Option<String>,
+    pub reference: Option<String>,
+    pub tags: Vec<String>,
+    pub explorer_url: Option<String>,
+    pub block_number: Option<u64>,
+    /// fee paid in the smallest unit of the native token, once included
+    pub fee_paid: Option<String>,
+}
+
+impl From<DbTxStateMachine> for Transfer {
+    fn from(record: DbTxStateMachine) -> Self {
+        Transfer {
+            tx_hash: format!("0x{}", hex::encode(&record.tx_hash)),
+            network: record.network.into(),
+            amount: record.amount.to_string(),
+            success: record.success,
+            sender: record.sender_address,
+            receiver: record.receiver_address,
+            sender_name: record.sender_name,
+            memo: record.memo,
+            reference: record.reference,
+            tags: record.tags,
+            explorer_url: record.explorer_url,
+            block_number: record.receipt.as_ref().map(|receipt| receipt.block_number),
+            fee_paid: record.receipt.map(|receipt| receipt.fee_paid.to_string()),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Volume {
+    pub token: String,
+    pub amount: String,
+}
+
+#[derive(SimpleObject)]
+pub struct Counterparty {
+    pub address: String,
+    pub sent: u32,
+    pub received: u32,
+    pub succeeded: u32,
+    pub failed: u32,
+    /// amount of the succeeded transfers per token
+    pub volumes: Vec<Volume>,
+    pub first_seen: Option<u64>,
+    pub last_seen: Option<u64>,
+    pub attestations: u32,
+    pub avg_attestation_secs: Option<u64>,
+    /// codes of the warnings raised about transfers to the address, most frequent first
+    pub warnings: Vec<String>,
+}
+
+impl From<CounterpartyProfile> for Counterparty {
+    fn from(profile: CounterpartyProfile) -> Self {
+        Counterparty {
+            address: profile.address,
+            sent: profile.sent,
+            received: profile.received,
+            succeeded: profile.succeeded,
+            failed: profile.failed,
+            volumes: profile
+                .volumes
+                .into_iter()
+                .map(|volume| Volume {
+                    token: volume.token.into(),
+                    amount: volume.amount.to_string(),
+                })
+                .collect(),
+            first_seen: profile.first_seen,
+            last_seen: profile.last_seen,
+            attestations: profile.attestations,
+            avg_attestation_secs: profile.avg_attestation_secs,
+            warnings: profile
+                .warnings
+                .into_iter()
+                .map(|warning| warning.code)
+                .collect(),
+        }
+    }
+}
+
+/// fees paid on a network, in the smallest unit of its native token
+#[derive(SimpleObject)]
+pub struct NetworkFees {
+    pub network: String,
+    pub transfers: u32,
+    pub total_fee: String,
+    pub average_fee: String,
+}
+
+#[derive(SimpleObject)]
+pub struct Stats {
+    pub fees: Vec<NetworkFees>,
+    /// codes of the fee warnings, e.g. a tier consistently overpaying
+    pub warnings: Vec<String>,
+}
+
+impl From<FeeStats> for Stats {
+    fn from(stats: FeeStats) -> Self {
+        Stats {
+            fees: stats
+                .networks
+                .into_iter()
+                .map(|fees| NetworkFees {
+                    network: fees.network.into(),
+                    transfers: fees.transfers,
+                    total_fee: fees.total_fee.to_string(),
+                    average_fee: fees.average_fee.to_string(),
+                })
+                .collect(),
+            warnings: stats
+                .warnings
+                .into_iter()
+                .map(|warning| warning.code)
+                .collect(),
+        }
+    }
+}
+
+/// p2p stats of a peer since the node started
+#[derive(SimpleObject)]
+pub struct PeerTraffic {
+    pub connected: bool,
+    pub rtt_ms: Option<u64>,
+    pub requests_sent: u64,
+    pub requests_failed: u64,
+    pub success_rate: Option<f64>,
+    pub last_seen: Option<u64>,
+    pub agent_version: Option<String>,
+}
+
+impl From<PeerStats> for PeerTraffic {
+    fn from(stats: PeerStats) -> Self {
+        PeerTraffic {
+            connected: stats.connected,
+            rtt_ms: stats.rtt_ms,
+            requests_sent: stats.requests_sent,
+            requests_failed: stats.requests_failed,
+            success_rate: stats.success_rate,
+            last_seen: stats.last_seen,
+            agent_version: stats.agent_version,
+        }
+    }
+}
+
+/// peer registered to discovery, with its p2p stats once the node talked to it
+#[derive(SimpleObject)]
+pub struct Peer {
+    pub record_id: String,
+    pub peer_id: Option<String>,
+    pub multi_addr: Option<String>,
+    pub account_ids: Vec<String>,
+    pub traffic: Option<PeerTraffic>,
+}
+
+impl Peer {
+    fn of(peer: Discovery, stats: &[PeerStats]) -> Self {
+        let traffic = stats
+            .iter()
+            .find(|stats| Some(&stats.peer_id) == peer.peer_id.as_ref())
+            .cloned()
+            .map(PeerTraffic::from);
+        Peer {
+            record_id: peer.id,
+            peer_id: peer.peer_id,
+            multi_addr: peer.multi_addr,
+            account_ids: peer.account_ids,
+            traffic,
+        }
+    }
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// submitted transfers matching the filter, newest first
+    async fn transfers(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<TransferFilter>,
+    ) -> async_graphql::Result<Vec<Transfer>> {
+        let worker = authorize(ctx, "searchHistory")?;
+        let search = HistorySearch::try_from(filter.unwrap_or_default())?;
+        let history = worker.search_history(search).await;
+        let history = history.map_err(|err| graphql_error(vane_error(err)))?;
+        Ok(history.into_iter().map(Transfer::from).collect())
+    }
+
+    /// transfer history with each address
+    async fn counterparties(
+        &self,
+        ctx: &Context<'_>,
+        addresses: Vec<String>,
+    ) -> async_graphql::Result<Vec<Counterparty>> {
+        let worker = authorize(ctx, "getCounterparty")?;
+        let mut counterparties = vec![];
+        for address in addresses {
+            let profile = worker.get_counterparty(address).await;
+            let profile = profile.map_err(|err| graphql_error(vane_error(err)))?;
+            counterparties.push(profile.into());
+        }
+        Ok(counterparties)
+    }
+
+    /// network fees paid by the submitted transfers
+    async fn stats(&self, ctx: &Context<'_>) -> async_graphql::Result<Stats> {
+        let worker = authorize(ctx, "feeStats")?;
+        let stats = worker.fee_stats().await;
+        Ok(stats.map_err(|err| graphql_error(vane_error(err)))?.into())
+    }
+
+    /// peers registered to discovery, with their p2p stats
+    async fn peers(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Peer>> {
+        let worker = authorize(ctx, "listPeers")?;
+        authorize(ctx, "peerStats")?;
+        let peers = worker.list_peers().await;
+        let peers = peers.map_err(|err| graphql_error(vane_error(err)))?;
+        let stats = worker.peer_stats().await;
+        let stats = stats.map_err(|err| graphql_error(vane_error(err)))?;
+        Ok(peers
+            .into_iter()
+            .map(|peer| Peer::of(peer, &stats))
+            .collect())
+    }
+}
+
+/// tower layer answering `/graphql`, behind the access control so only valid api keys reach it.
+/// without a schema the endpoint is off and the requests go on to the json-rpc server
+#[derive(Clone)]
+pub struct GraphqlLayer {
+    schema: Option<VaneSchema>,
+    access: AccessControl,
+}
+
+impl GraphqlLayer {
+    pub fn new(schema: Option<VaneSchema>, access: AccessControl) -> Self {
+        Self { schema, access }
+    }
+}
+
+impl<S> Layer<S> for GraphqlLayer {
+    type Service = GraphqlService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GraphqlService {
+            inner,
+            schema: self.schema.clone(),
+            access: self.access.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GraphqlService<S> {
+    inner: S,
+    schema: Option<VaneSchema>,
+    access: AccessControl,
+}
+
+impl<S> Service<Request<Body>> for GraphqlService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let schema = match &self.schema {
+            Some(schema) if request.uri().path() == "/graphql" => schema.clone(),
+            _ => return Box::pin(self.inner.call(request)),
+        };
+        if request.method() == Method::GET {
+            let mut response = Response::new(Body::from(schema.sdl()));
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+            return Box::pin(async move { Ok(response) });
+        }
+        // the access control in front turned away the requests without a valid key
+        let role = match self.access.is_enabled() {
+            true => self.access.role(request.headers()).unwrap_or(Role::Viewer),
+            false => Role::Admin,
+        };
+        Box::pin(async move {
+            let Some(body) = read_body(request.into_body()).await else {
+                let message = "request body too large";
+                return Ok(json_response(StatusCode::PAYLOAD_TOO_LARGE, &message));
+            };
+            let query = match serde_json::from_slice::<async_graphql::Request>(&body) {
+                Ok(query) => query,
+                Err(err) => {
+                    let message = format!("invalid graphql request; {err}");
+                    return Ok(json_response(StatusCode::BAD_REQUEST, &message));
+                }
+            };
+            let answer = schema.execute(query.data(role)).await;
+            Ok(json_response(StatusCode::OK, &answer))
+        })
+    }
+}
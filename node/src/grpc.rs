@@ -0,0 +1,418 @@
+// grpc api
+// a tonic service mirroring the transaction and peer methods of the json-rpc api for backend
+// integrators preferring protobuf contracts and http/2 multiplexing. every call goes through the
+// `TransactionRpcServer` methods of the rpc worker, so the rate limits, checks and errors are the
+// json-rpc ones, and the api keys and roles of the json-rpc server apply. calls are rate limited
+// per api key, or per peer address without one
+
+use crate::rpc::{
+    vane_error, InitiateTransactionParams, TransactionRpcServer, TransactionRpcWorker,
+};
+use crate::rpc_middleware::{as_caller, bearer_key, AccessControl, Role};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use jsonrpsee::core::Error;
+use log::{info, warn};
+use primitives::data_structure::{
+    Discovery, PeerStats, PendingReceive, Token, TxPriority, TxStateMachine,
+};
+use primitives::errors::VaneRpcError;
+use primitives::rpc_tx::RpcTx;
+use std::net::SocketAddr;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::transport::server::TcpConnectInfo;
+use tonic::{Request, Response, Status};
+use tower::{Layer, Service};
+
+pub mod proto {
+    tonic::include_proto!("vane.v1");
+}
+
+use proto::vane_server::{Vane, VaneServer};
+
+/// updates buffered per tx update stream
+const STREAM_CAPACITY: usize = 64;
+
+/// the `Vane` grpc service of a node
+pub struct GrpcService {
+    worker: TransactionRpcWorker,
+    access_control: AccessControl,
+}
+
+impl GrpcService {
+    pub fn new(worker: TransactionRpcWorker, access_control: AccessControl) -> Self {
+        Self {
+            worker,
+            access_control,
+        }
+    }
+
+    /// serve on `address` in the background, returns the address listened on
+    pub async fn serve(self, address: SocketAddr) -> Result<SocketAddr, anyhow::Error> {
+        let listener = tokio::net::TcpListener::bind(address).await?;
+        let address = listener.local_addr()?;
+        let server = tonic::transport::Server::builder()
+            .layer(CallerLayer(self.access_control.clone()))
+            .add_service(VaneServer::new(self))
+            .serve_with_incoming(TcpListenerStream::new(listener));
+        tokio::spawn(async move {
+            if let Err(err) = server.await {
+                warn!(target: "grpc", "grpc server stopped; caused by: {err}");
+            }
+        });
+        info!(target: "grpc", "listening to grpc address: {address}");
+        Ok(address)
+    }
+
+    /// the api key of `request` has the role `method` requires
+    fn authorize<T>(&self, request: &Request<T>, method: &str) -> Result<(), Status> {
+        if !self.access_control.is_enabled() {
+            return Ok(());
+        }
+        let headers = request.metadata().clone().into_headers();
+        let role = self
+            .access_control
+            .role(&headers)
+            .ok_or_else(|| Status::unauthenticated("missing or unknown api key"))?;
+        let required = Role::required_for(method);
+        if role < required {
+            warn!(target: "grpc", "access denied: method={method} role={role}");
+            return Err(error_status(VaneRpcError::AccessDenied {
+                method: method.to_string(),
+                role: required.to_string(),
+            }));
+        }
+        Ok(())
+    }
+}
+
+/// tower layer running each grpc call as its caller for the rate limits and account scope
+#[derive(Clone)]
+struct CallerLayer(AccessControl);
+
+impl<S> Layer<S> for CallerLayer {
+    type Service = CallerService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CallerService {
+            inner,
+            access_control: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct CallerService<S> {
+    inner: S,
+    access_control: AccessControl,
+}
+
+impl<S, B> Service<hyper::Request<B>> for CallerService<S>
+where
+    S: Service<hyper::Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: hyper::Request<B>) -> Self::Future {
+        let peer = request
+            .extensions()
+            .get::<TcpConnectInfo>()
+            .and_then(TcpConnectInfo::remote_addr);
+        let caller = match (bearer_key(request.headers()), peer) {
+            (Some(key), _) => format!("key:{key}"),
+            (None, Some(peer)) => format!("peer:{}", peer.ip()),
+            (None, None) => "peer:unknown".to_string(),
+        };
+        let scope = self.access_control.scope(request.headers());
+        Box::pin(as_caller(caller, scope, self.inner.call(request)))
+    }
+}
+
+/// grpc status of a json-rpc error
+fn rpc_status(err: Error) -> Status {
+    error_status(vane_error(err))
+}
+
+/// grpc status of a vane error, its json goes in the details
+fn error_status(err: VaneRpcError) -> Status {
+    let code = match err {
+        VaneRpcError::AccessDenied { .. } => tonic::Code::PermissionDenied,
+        VaneRpcError::RateLimited { .. } => tonic::Code::ResourceExhausted,
+        VaneRpcError::ReceiverOffline { .. } => tonic::Code::Unavailable,
+        VaneRpcError::Internal { .. } => tonic::Code::Internal,
+        VaneRpcError::InvalidParams { .. }
+        | VaneRpcError::InvalidAddress { .. }
+        | VaneRpcError::UnsupportedNetwork { .. }
+        | VaneRpcError::WrongNetworkSuspected { .. }
+        | VaneRpcError::AmbiguousNetwork { .. } => tonic::Code::InvalidArgument,
+        _ => tonic::Code::FailedPrecondition,
+    };
+    let details = serde_json::to_vec(&err).unwrap_or_default();
+    Status::with_details(code, err.to_string(), details.into())
+}
+
+fn invalid(reason: String) -> Status {
+    error_status(VaneRpcError::InvalidParams { reason })
+}
+
+impl From<TxStateMachine> for proto::Tx {
+    fn from(tx: TxStateMachine) -> Self {
+        let tx_id = format!("{:?}", tx.tx_id());
+        let token = tx.token.unwrap_or(Token::native(tx.network));
+        let rpc_tx = RpcTx::from(tx);
+        proto::Tx {
+            tx_id,
+            tx_nonce: rpc_tx.tx_nonce,
+            sender_address: rpc_tx.sender_address.clone(),
+            receiver_address: rpc_tx.receiver_address.clone(),
+            network: rpc_tx.network.into(),
+            token: token.into(),
+            amount: rpc_tx.amount.clone(),
+            status: rpc_tx.status.clone(),
+            status_detail: rpc_tx.status_detail.clone(),
+            kind: format!("{:?}", rpc_tx.kind),
+            memo: rpc_tx.memo.clone(),
+            reference: rpc_tx.reference.clone(),
+            tags: rpc_tx.tags.clone(),
+            warnings: rpc_tx
+                .warnings
+                .iter()
+                .map(|warning| proto::Warning {
+                    code: warning.code.clone(),
+                    severity: format!("{:?}", warning.severity),
+                    message: warning.message.clone(),
+                })
+                .collect(),
+            call_payload: rpc_tx.call_payload.map(|payload| payload.to_vec()),
+            signed_call_payload: rpc_tx.signed_call_payload.clone(),
+            recv_signature: rpc_tx.recv_signature.clone(),
+            rpc_tx: serde_json::to_string(&rpc_tx).unwrap_or_default(),
+        }
+    }
+}
+
+impl TryFrom<proto::Tx> for TxStateMachine {
+    type Error = Status;
+
+    fn try_from(tx: proto::Tx) -> Result<Self, Self::Error> {
+        let mut rpc_tx: RpcTx = serde_json::from_str(&tx.rpc_tx)
+            .map_err(|err| invalid(format!("invalid rpc tx; {err}")))?;
+        if tx.signed_call_payload.is_some() {
+            rpc_tx.signed_call_payload = tx.signed_call_payload;
+        }
+        if tx.recv_signature.is_some() {
+            rpc_tx.recv_signature = tx.recv_signature;
+        }
+        TxStateMachine::try_from(rpc_tx).map_err(error_status)
+    }
+}
+
+fn required_tx(tx: Option<proto::Tx>) -> Result<TxStateMachine, Status> {
+    tx.ok_or_else(|| invalid("tx is missing".to_string()))?
+        .try_into()
+}
+
+impl From<Discovery> for proto::Peer {
+    fn from(peer: Discovery) -> Self {
+        proto::Peer {
+            record_id: peer.id,
+            peer_id: peer.peer_id,
+            multi_addr: peer.multi_addr,
+            account_ids: peer.account_ids,
+        }
+    }
+}
+
+impl From<PeerStats> for proto::PeerStats {
+    fn from(stats: PeerStats) -> Self {
+        proto::PeerStats {
+            peer_id: stats.peer_id,
+            connected: stats.connected,
+            rtt_ms: stats.rtt_ms,
+            requests_sent: stats.requests_sent,
+            requests_failed: stats.requests_failed,
+            responses_received: stats.responses_received,
+            requests_received: stats.requests_received,
+            success_rate: stats.success_rate,
+            last_seen: stats.last_seen,
+            bytes_sent: stats.bytes_sent,
+            bytes_received: stats.bytes_received,
+            agent_version: stats.agent_version,
+            protocol_version: stats.protocol_version,
+        }
+    }
+}
+
+impl From<PendingReceive> for proto::PendingReceive {
+    fn from(receive: PendingReceive) -> Self {
+        proto::PendingReceive {
+            tx: Some(receive.tx.into()),
+            received_at: receive.received_at,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Vane for GrpcService {
+    async fn register(
+        &self,
+        request: Request<proto::RegisterRequest>,
+    ) -> Result<Response<proto::Empty>, Status> {
+        self.authorize(&request, "register")?;
+        let request = request.into_inner();
+        self.worker
+            .register_vane_web3(request.name, request.account_id, request.network)
+            .await
+            .map_err(rpc_status)?;
+        Ok(Response::new(proto::Empty {}))
+    }
+
+    async fn initiate_transaction(
+        &self,
+        request: Request<proto::InitiateTransactionRequest>,
+    ) -> Result<Response<proto::TxId>, Status> {
+        self.authorize(&request, "initiateTransaction")?;
+        let request = request.into_inner();
+        let amount = request
+            .amount
+            .parse()
+            .map_err(|_| invalid(format!("invalid amount {}", request.amount)))?;
+        let tags = (!request.tags.is_empty()).then_some(request.tags);
+        let priority = match request.priority.as_deref() {
+            None => None,
+            Some("Low") => Some(TxPriority::Low),
+            Some("Normal") => Some(TxPriority::Normal),
+            Some("Urgent") => Some(TxPriority::Urgent),
+            Some(other) => Err(invalid(format!("invalid priority {other}")))?,
+        };
+        let tx_id = self
+            .worker
+            .initiate(InitiateTransactionParams {
+                sender: request.sender,
+                receiver: request.receiver,
+                amount,
+                token: request.token,
+                network: request.network,
+                idempotency_key: request.idempotency_key,
+                memo: request.memo,
+                reference: request.reference,
+                tags,
+                ttl_secs: request.ttl_secs,
+                priority,
+                ..Default::default()
+            })
+            .await
+            .map_err(rpc_status)?;
+        Ok(Response::new(proto::TxId {
+            tx_id: format!("{tx_id:?}"),
+        }))
+    }
+
+    async fn sender_confirm(
+        &self,
+        request: Request<proto::SenderConfirmRequest>,
+    ) -> Result<Response<proto::Empty>, Status> {
+        self.authorize(&request, "senderConfirm")?;
+        let request = request.into_inner();
+        let tx = required_tx(request.tx)?;
+        self.worker
+            .sender_confirm(tx, Some(request.override_warnings))
+            .await
+            .map_err(rpc_status)?;
+        Ok(Response::new(proto::Empty {}))
+    }
+
+    async fn receiver_confirm(
+        &self,
+        request: Request<proto::ReceiverConfirmRequest>,
+    ) -> Result<Response<proto::Empty>, Status> {
+        self.authorize(&request, "receiverConfirm")?;
+        let tx = required_tx(request.into_inner().tx)?;
+        self.worker.receiver_confirm(tx).await.map_err(rpc_status)?;
+        Ok(Response::new(proto::Empty {}))
+    }
+
+    async fn fetch_pending_tx_updates(
+        &self,
+        request: Request<proto::Empty>,
+    ) -> Result<Response<proto::TxList>, Status> {
+        self.authorize(&request, "fetchPendingTxUpdates")?;
+        let txs = self
+            .worker
+            .fetch_pending_tx_updates()
+            .await
+            .map_err(rpc_status)?;
+        Ok(Response::new(proto::TxList {
+            txs: txs.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    type SubscribeTxUpdatesStream = ReceiverStream<Result<proto::Tx, Status>>;
+
+    async fn subscribe_tx_updates(
+        &self,
+        request: Request<proto::Empty>,
+    ) -> Result<Response<Self::SubscribeTxUpdatesStream>, Status> {
+        self.authorize(&request, "subscribeTxUpdates")?;
+        let scope = self
+            .access_control
+            .scope(&request.metadata().clone().into_headers());
+        let mut updates = self.worker.tx_updates.subscribe(scope);
+        let (sender, receiver) = tokio::sync::mpsc::channel(STREAM_CAPACITY);
+        tokio::spawn(async move {
+            // the stream takes the updates of the accounts of the api key until the client leaves
+            while let Some(tx) = updates.next().await {
+                if sender.send(Ok(tx.into())).await.is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn list_pending_receives(
+        &self,
+        request: Request<proto::ListPendingReceivesRequest>,
+    ) -> Result<Response<proto::PendingReceiveList>, Status> {
+        self.authorize(&request, "listPendingReceives")?;
+        let request = request.into_inner();
+        let receives = self
+            .worker
+            .list_pending_receives(request.network, request.min_age_secs, request.max_age_secs)
+            .await
+            .map_err(rpc_status)?;
+        Ok(Response::new(proto::PendingReceiveList {
+            receives: receives.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn list_peers(
+        &self,
+        request: Request<proto::Empty>,
+    ) -> Result<Response<proto::PeerList>, Status> {
+        self.authorize(&request, "listPeers")?;
+        let peers = self.worker.list_peers().await.map_err(rpc_status)?;
+        Ok(Response::new(proto::PeerList {
+            peers: peers.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn peer_stats(
+        &self,
+        request: Request<proto::Empty>,
+    ) -> Result<Response<proto::PeerStatsList>, Status> {
+        self.authorize(&request, "peerStats")?;
+        let peers = self.worker.peer_stats().await.map_err(rpc_status)?;
+        Ok(Response::new(proto::PeerStatsList {
+            peers: peers.into_iter().map(Into::into).collect(),
+        }))
+    }
+}
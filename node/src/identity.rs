@@ -0,0 +1,195 @@
+// counterparty identity shown during transfers
+// the sender proves it owns the address it sends from with an EIP-191 signature over its display name,
+// the receiver node checks the proof again and adds how often it attested for the sender before.
+// third party attestations stored in the registry become badges once they verify and their issuer is
+// trusted by the node operator, the registry is read again every few minutes. a receiver rejecting a transfer sent to the wrong chain or an old
+// address proves the address it proposes instead with the key of the one the sender picked
+
+use crate::calldata::evm_address;
+use crate::discovery::PeerDiscovery;
+use alloy::primitives::{Address, Signature as EcdsaSignature};
+use anyhow::anyhow;
+use db::{DbWorker, DbWorkerInterface};
+use moka::future::Cache as AsyncCache;
+use primitives::address::VaneAddress;
+use primitives::attestations::{IdentityAttestation, VerificationBadge};
+use primitives::data_structure::{
+    AddressCorrection, Discovery, SenderProfile, TxStateMachine, TxStatus,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// how long the registry records are reused for badges before they are fetched again
+pub const REGISTRY_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// check the profile signature recovers to `sender_address`, evm senders only
+pub fn verify_sender_profile(
+    profile: &SenderProfile,
+    sender_address: &VaneAddress,
+) -> Result<(), anyhow::Error> {
+    let sender = evm_address(sender_address).map_err(|err| {
+        anyhow!("sender profiles are only supported for evm senders; caused by: {err}")
+    })?;
+    let signature = EcdsaSignature::try_from(profile.signature.as_slice())
+        .map_err(|err| anyhow!("invalid sender profile signature; caused by: {err}"))?;
+    let recovered = signature
+        .recover_address_from_msg(SenderProfile::message(
+            &profile.display_name,
+            &sender_address.to_string(),
+        ))
+        .map_err(|err| anyhow!("sender profile signature recovery failed; caused by: {err}"))?;
+    if recovered != sender {
+        Err(anyhow!("sender profile was not signed by {sender}"))?
+    }
+    Ok(())
+}
+
+/// check the correction proposed by the receiver of `tx` is signed with the key of the address the
+/// sender picked and names an address of the proposed network, evm receivers only
+pub fn verify_address_correction(
+    correction: &AddressCorrection,
+    tx: &TxStateMachine,
+) -> Result<(), anyhow::Error> {
+    if !correction.address.networks().contains(&correction.network) {
+        Err(anyhow!(
+            "{} is not an address of {:?}",
+            correction.address,
+            correction.network
+        ))?
+    }
+    let receiver = evm_address(&tx.receiver_address).map_err(|err| {
+        anyhow!("address corrections are only supported for evm receivers; caused by: {err}")
+    })?;
+    let signature = EcdsaSignature::try_from(correction.signature.as_slice())
+        .map_err(|err| anyhow!("invalid address correction signature; caused by: {err}"))?;
+    let recovered = signature
+        .recover_address_from_msg(AddressCorrection::message(
+            tx.tx_id(),
+            correction.network,
+            &correction.address,
+        ))
+        .map_err(|err| anyhow!("address correction signature recovery failed; caused by: {err}"))?;
+    if recovered != receiver {
+        Err(anyhow!("address correction was not signed by {receiver}"))?
+    }
+    Ok(())
+}
+
+/// fill in the receiver side of the sender profile, whatever the sender claimed there is overwritten
+pub async fn attest_sender(db: &DbWorker, tx: &mut TxStateMachine) -> Result<(), anyhow::Error> {
+    let Some(profile) = tx.sender_profile.as_mut() else {
+        return Ok(());
+    };
+    profile.verified = verify_sender_profile(profile, &tx.sender_address).is_ok();
+    let attested = db
+        .get_sender_tx_events(tx.sender_address.to_string(), None)
+        .await?
+        .into_iter()
+        .filter(|event| event.status == TxStatus::RecvAddrConfirmed)
+        .map(|event| event.tx_id)
+        .collect::<HashSet<_>>();
+    profile.past_interactions = attested.len() as u32;
+    Ok(())
+}
+
+/// check `attestation` is signed by its issuer
+pub fn verify_attestation(attestation: &IdentityAttestation) -> Result<(), anyhow::Error> {
+    let issuer: Address = attestation
+        .issuer
+        .parse()
+        .map_err(|err| anyhow!("invalid attestation issuer; caused by: {err}"))?;
+    let signature = EcdsaSignature::try_from(attestation.signature.as_slice())
+        .map_err(|err| anyhow!("invalid attestation signature; caused by: {err}"))?;
+    let recovered = signature
+        .recover_address_from_msg(attestation.message())
+        .map_err(|err| anyhow!("attestation signature recovery failed; caused by: {err}"))?;
+    if recovered != issuer {
+        Err(anyhow!("attestation was not signed by {issuer}"))?
+    }
+    Ok(())
+}
+
+/// issuers the node operator trusts, by evm account, with the name shown on their badges
+#[derive(Clone, Debug)]
+pub struct TrustedIssuers {
+    issuers: HashMap<Address, String>,
+    /// registry records the badges were last read from, one entry
+    registry: AsyncCache<(), Arc<Vec<Discovery>>>,
+}
+
+impl Default for TrustedIssuers {
+    fn default() -> Self {
+        Self {
+            issuers: HashMap::new(),
+            registry: AsyncCache::builder()
+                .max_capacity(1)
+                .time_to_live(REGISTRY_CACHE_TTL)
+                .build(),
+        }
+    }
+}
+
+impl TrustedIssuers {
+    pub fn insert(&mut self, account: &str, name: String) -> Result<(), anyhow::Error> {
+        let account: Address = account
+            .parse()
+            .map_err(|err| anyhow!("invalid trusted issuer {account}; caused by: {err}"))?;
+        self.issuers.insert(account, name);
+        Ok(())
+    }
+
+    /// badges of `account` from the attestations of trusted issuers that verify and did not expire
+    pub fn badges(
+        &self,
+        account: &str,
+        attestations: &[IdentityAttestation],
+        now: u64,
+    ) -> Vec<VerificationBadge> {
+        attestations
+            .iter()
+            .filter(|attestation| {
+                attestation.account.eq_ignore_ascii_case(account) && !attestation.is_expired(now)
+            })
+            .filter_map(|attestation| {
+                let issuer: Address = attestation.issuer.parse().ok()?;
+                let issuer_name = self.issuers.get(&issuer)?;
+                verify_attestation(attestation).ok()?;
+                Some(VerificationBadge {
+                    issuer: issuer.to_string(),
+                    issuer_name: issuer_name.clone(),
+                    claim: attestation.claim.clone(),
+                    expires_at: attestation.expires_at,
+                })
+            })
+            .collect()
+    }
+
+    /// badges of `account` as registered in discovery, none when the account is not registered
+    pub async fn lookup(
+        &self,
+        discovery: &dyn PeerDiscovery,
+        account: &str,
+    ) -> Result<Vec<VerificationBadge>, anyhow::Error> {
+        if self.issuers.is_empty() {
+            return Ok(vec![]);
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let registry = self
+            .registry
+            .try_get_with((), async { discovery.list_all_peers().await.map(Arc::new) })
+            .await
+            .map_err(|err| anyhow!("failed to read the registry; caused by: {err}"))?;
+        let badges = registry
+            .iter()
+            .filter(|peer| peer.account_ids.iter().any(|id| id == account))
+            .flat_map(|peer| self.badges(account, &peer.attestations, now))
+            .collect();
+        Ok(badges)
+    }
+
+    /// read the registry again on the next lookup, after this node stored an attestation
+    pub async fn forget_registry(&self) {
+        self.registry.invalidate(&()).await;
+    }
+}
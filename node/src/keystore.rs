@@ -0,0 +1,99 @@
+// node keystore
+// keeps the keys the node encrypts its db records with in files of their own, outside the db, so
+// a copied db file alone does not reveal the sealed records
+
+use anyhow::anyhow;
+use db::StorageKey;
+use libp2p::identity::Keypair;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// file of the key call payloads, signed call payloads and memos are sealed with at rest
+pub const STORAGE_KEY_FILE: &str = "storage.key";
+
+/// file of the p2p identity keypair, protobuf encoded
+pub const NODE_KEY_FILE: &str = "node.key";
+
+/// directory of the node keys
+#[derive(Clone, Debug)]
+pub struct Keystore {
+    dir: PathBuf,
+}
+
+impl Keystore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// keystore next to the db file, `db/dev.db` keeps its keys in `db/keystore`
+    pub fn for_db(db_url: &str) -> Self {
+        let dir = Path::new(db_url)
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join("keystore");
+        Self::new(dir)
+    }
+
+    /// the storage key, generated on first use
+    pub fn storage_key(&self) -> Result<StorageKey, anyhow::Error> {
+        let path = self.dir.join(STORAGE_KEY_FILE);
+        match fs::read(&path) {
+            Ok(key) => {
+                let key: [u8; 32] = key
+                    .try_into()
+                    .map_err(|_| anyhow!("storage key {} is not 32 bytes long", path.display()))?;
+                Ok(StorageKey::new(key))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let key: [u8; 32] = rand::random();
+                write_private(&self.dir, &path, &key)?;
+                Ok(StorageKey::new(key))
+            }
+            Err(err) => Err(anyhow!(
+                "failed to read storage key {}; caused by: {err}",
+                path.display()
+            )),
+        }
+    }
+
+    /// the p2p identity keypair, generated on first use, so the nodes sharing the keystore run
+    /// the same identity
+    pub fn node_key(&self) -> Result<Keypair, anyhow::Error> {
+        let path = self.dir.join(NODE_KEY_FILE);
+        match fs::read(&path) {
+            Ok(key) => Keypair::from_protobuf_encoding(&key)
+                .map_err(|err| anyhow!("invalid node key {}; caused by: {err}", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let keypair = Keypair::generate_ed25519();
+                let encoded = keypair
+                    .to_protobuf_encoding()
+                    .map_err(|err| anyhow!("failed to encode the node key; caused by: {err}"))?;
+                write_private(&self.dir, &path, &encoded)?;
+                Ok(keypair)
+            }
+            Err(err) => Err(anyhow!(
+                "failed to read node key {}; caused by: {err}",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// write `contents` to a new file only the node user can read
+fn write_private(dir: &Path, path: &Path, contents: &[u8]) -> Result<(), anyhow::Error> {
+    fs::create_dir_all(dir).map_err(|err| {
+        anyhow!(
+            "failed to create keystore {}; caused by: {err}",
+            dir.display()
+        )
+    })?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents))
+        .map_err(|err| anyhow!("failed to write {}; caused by: {err}", path.display()))
+}
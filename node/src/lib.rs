@@ -1,24 +1,89 @@
 extern crate alloc;
 extern crate core;
 
+pub mod amount_limits;
+pub mod anchors;
+pub mod approval_webhook;
+pub mod builder;
+pub mod calldata;
+pub mod chain;
+pub mod circuit_breaker;
+pub mod cluster;
 mod cryptography;
+pub mod device_sync;
+pub mod discovery;
+pub mod durable_nonce;
+pub mod event_bus;
+pub mod failover;
+pub mod gas_oracle;
+pub mod graphql;
+pub mod grpc;
+pub mod identity;
+pub mod keystore;
 mod light_clients;
+pub mod locks;
+pub mod openrpc;
 pub mod p2p;
+pub mod pairing;
+pub mod peer_exchange;
+pub mod peer_metrics;
+pub mod plugins;
+pub mod policy;
+pub mod pricing;
+pub mod priority_fees;
+pub mod push;
+pub mod receipts;
+pub mod relay;
+pub mod rest;
+pub mod revocation;
+pub mod risk;
+pub mod rotation;
 pub mod rpc;
+pub mod rpc_middleware;
+pub mod send_lock;
+pub mod spam;
+pub mod submission;
 pub mod telemetry;
+pub mod tenants;
+pub mod token_accounts;
+pub mod travel_rule;
+pub mod ts_client;
 pub mod tx_processing;
-
-use crate::p2p::P2pNetworkService;
-use crate::rpc::{Airtable, TransactionRpcServer};
+pub mod tx_updates;
+pub mod typed_tx;
+pub mod user_operation;
+pub mod watchdog;
+
+pub use crate::builder::{MainServiceWorkerBuilder, NodeHandle};
+use crate::cluster::Cluster;
+use crate::discovery::PeerDiscovery;
+use crate::event_bus::TxEventPublisher;
+use crate::failover::Failover;
+use crate::graphql::GraphqlLayer;
+use crate::grpc::GrpcService;
+use crate::identity::{verify_address_correction, TrustedIssuers};
+use crate::locks::timed_lock;
+use crate::p2p::{is_compatible, DialOutcome, P2pNetworkService, DIAL_TIMEOUT};
+use crate::policy::{requires_second_approval, verify_second_approval, SecondApprover};
+use crate::pricing::FiatPricing;
+use crate::rest::RestLayer;
+use crate::rpc::TransactionRpcServer;
+use crate::rpc_middleware::{
+    AccessControl, AccessControlLayer, MetricsLayer, RpcLogger, MAX_REQUEST_BODY_SIZE,
+    MAX_RESPONSE_BODY_SIZE,
+};
+use crate::spam::SpamFilter;
+use crate::submission::{SubmissionOutcome, SubmissionQueues, VERIFY_TIMEOUT};
+use crate::telemetry::TelemetryWorker;
+use crate::watchdog::Watchdog;
 use alloc::sync::Arc;
 use alloy::hex;
 use anyhow::{anyhow, Error};
-use codec::Decode;
 use core::str::FromStr;
-use db::db::saved_peers::Data;
 use db::DbWorker;
 use jsonrpsee::server::ServerBuilder;
-use libp2p::futures::{FutureExt, StreamExt};
+use libp2p::futures::future::join_all;
+use libp2p::futures::StreamExt;
 use libp2p::request_response::{InboundRequestId, Message, ResponseChannel};
 use libp2p::{Multiaddr, PeerId};
 use local_ip_address::local_ip;
@@ -26,13 +91,18 @@ use log::{error, info, warn};
 use moka::future::Cache as AsyncCache;
 use p2p::P2pWorker;
 use primitives::data_structure::{
-    ChainSupported, DbTxStateMachine, HashId, NetworkCommand, PeerRecord, SwarmMessage,
-    TxStateMachine, TxStatus,
+    ChainSupported, DbTxStateMachine, HashId, PeerRecord, SwarmMessage, TxEvent, TxStateMachine,
+    TxStatus, TxWarning, Warning,
+};
+use primitives::envelope;
+use primitives::environment::Environments;
+use primitives::tx_state::{
+    GenesisTx, NetConfirmedTx, RecvConfirmedTx, SenderConfirmedTx, UnsubmittedTx,
 };
-use rand::Rng;
 use rpc::TransactionRpcWorker;
 use std::hash::{DefaultHasher, Hash, Hasher};
 use std::net::SocketAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::Mutex;
 use tx_processing::TxProcessingWorker;
@@ -40,6 +110,19 @@ use db::DbWorkerInterface;
 extern crate rcgen;
 use rcgen::{generate_simple_self_signed, CertifiedKey};
 
+/// how often the txs in flight are checked against the deadline of their sender
+pub const DEADLINE_SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+/// wait before an unreachable receiver of an urgent transfer is dialed again
+pub const DELIVERY_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// how sending an attestation request to the receiver devices went
+enum Delivery {
+    /// at least one device got the request
+    Sent,
+    Incompatible(String),
+    Unreachable(String),
+}
+
 /// Main thread to be spawned by the application
 /// this encompasses all node's logic and processing flow
 #[derive(Clone)]
@@ -47,9 +130,9 @@ pub struct MainServiceWorker {
     pub db_worker: Arc<Mutex<DbWorker>>,
     pub tx_rpc_worker: Arc<Mutex<TransactionRpcWorker>>,
     pub tx_processing_worker: Arc<Mutex<TxProcessingWorker>>,
-    pub airtable_client: Airtable,
+    pub discovery: Arc<dyn PeerDiscovery>,
     // for swarm events
-    pub p2p_worker: Arc<Mutex<P2pWorker>>, //telemetry_worker: TelemetryWorker,
+    pub p2p_worker: Arc<Mutex<P2pWorker>>,
     pub p2p_network_service: Arc<Mutex<P2pNetworkService>>,
     // channels for layers communication
     /// sender channel to propagate transaction state to rpc layer
@@ -59,112 +142,133 @@ pub struct MainServiceWorker {
     pub user_rpc_update_recv_channel: Arc<Mutex<Receiver<Arc<Mutex<TxStateMachine>>>>>,
     // moka cache
     pub moka_cache: AsyncCache<u64, TxStateMachine>,
+    /// per chain submission queues
+    pub submission_queues: SubmissionQueues,
+    /// outcomes reported by the submission workers
+    pub submission_outcomes: Arc<Mutex<Receiver<SubmissionOutcome>>>,
+    /// approver sender confirmed txs are held for
+    pub second_approver: Option<SecondApprover>,
+    /// usd prices the fiat denominated transfers are checked against before submission
+    pub fiat_pricing: FiatPricing,
+    /// api keys the rpc server checks per method
+    pub access_control: AccessControl,
+    /// controls declining unwanted attestation requests
+    pub spam_filter: SpamFilter,
+    /// environments the networks run on, requests of another environment are declined
+    pub environments: Environments,
+    /// issuers whose attestations are shown as verification badges
+    pub trusted_issuers: TrustedIssuers,
+    /// anonymized stats reporter, none when the operator opted out
+    pub telemetry: Option<TelemetryWorker>,
+    /// health monitor restarting wedged subsystems
+    pub watchdog: Watchdog,
+    /// instances sharing the state and the accounts of the node, none when it runs alone
+    pub cluster: Option<Cluster>,
+    /// election of the instance running the p2p identity, none when the node has no standby
+    pub failover: Option<Failover>,
+    /// publisher mirroring the tx state transitions onto the message bus of the operator
+    pub event_publisher: Option<TxEventPublisher>,
+    /// address the grpc api is served on, none when it is not
+    pub grpc_address: Option<SocketAddr>,
+    /// serve the graphql endpoint on the rpc port
+    pub graphql: bool,
 }
 
-impl MainServiceWorker {
-    pub(crate) async fn new(db_url_path: Option<String>) -> Result<Self, anyhow::Error> {
-        // CHANNELS
-        // ===================================================================================== //
-        // for rpc messages back and forth propagation
-        let (rpc_sender_channel, rpc_recv_channel) = tokio::sync::mpsc::channel(10);
-        let (user_rpc_update_sender_channel, user_rpc_update_recv_channel) =
-            tokio::sync::mpsc::channel(10);
-
-        // for p2p network commands
-        let (p2p_command_tx, p2p_command_recv) = tokio::sync::mpsc::channel::<NetworkCommand>(10);
-
-        // DATABASE WORKER (LOCAL AND REMOTE )
-        // ===================================================================================== //
-        let mut db_url = String::new();
-        if let Some(url) = db_url_path {
-            db_url = url
-        } else {
-            db_url = String::from("db/dev.db")
-        }
-        let db = DbWorker::initialize_db_client(db_url.as_str()).await?;
+/// aborts the task when dropped, so a cancelled loop does not leave its spawned task behind
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
 
-        let mut rpc_port: u16 = 0;
-        let mut p2p_port: u16 = 0;
+/// peer id and address of a receiver device from its peer record
+fn receiver_device(record: &PeerRecord) -> Result<(PeerId, Multiaddr), Error> {
+    let multi_addr = record
+        .multi_addr
+        .as_ref()
+        .ok_or(anyhow!("saved peer has no multi addr"))?
+        .parse::<Multiaddr>()
+        .map_err(|err| anyhow!("failed to parse multi addr, caused by: {err}"))?;
+    let peer_id = record
+        .peer_id
+        .as_ref()
+        .ok_or(anyhow!("saved peer has no peer id"))?;
+    Ok((PeerId::from_str(peer_id)?, multi_addr))
+}
 
-        let returned_pots = db.get_ports().await?;
-        if let Some(ports) = returned_pots {
-            rpc_port = ports.rpc_port as u16;
-            p2p_port = ports.p_2_p_port as u16;
-        } else {
-            let (rp_port, p2_port) = {
-                let port = rand::thread_rng().gen_range(0..=u16::MAX);
-                (port, port - 541)
+/// why the receiver device is not sent the request when dialing it failed, a device with a relay
+/// queuing its requests still gets it
+fn unreachable_receiver(
+    p2p_network_service: &P2pNetworkService,
+    dialed: DialOutcome,
+    peer_id: &PeerId,
+) -> Option<String> {
+    let reason = dialed.failure(peer_id)?;
+    if p2p_network_service
+        .p2p_worker
+        .relay
+        .route(peer_id)
+        .is_some()
+    {
+        info!(target: "MainServiceWorker","{reason}, sending through the receiver relay");
+        return None;
+    }
+    Some(reason)
+}
+
+/// why the receiver device is not sent the request when it identified itself with an incompatible
+/// vane protocol, a device not identified yet still gets it
+fn incompatible_receiver(p2p_worker: &P2pWorker, peer_id: &PeerId) -> Option<String> {
+    let protocol_version = p2p_worker.peer_metrics.protocol_version(peer_id)?;
+    let local = p2p_worker.protocol_version;
+    if is_compatible(local, &protocol_version) {
+        return None;
+    }
+    Some(format!(
+        "the receiver node speaks {protocol_version} and this node {local}, \
+         the node on the older vane release has to be updated"
+    ))
+}
+
+impl MainServiceWorker {
+    /// run the swarm handling, started over whenever the watchdog finds the swarm loop wedged. a
+    /// node with standbys runs it only while it leads the p2p identity
+    pub(crate) async fn supervise_swarm_event_messages(
+        &self,
+        p2p_worker: Arc<Mutex<P2pWorker>>,
+        txn_processing_worker: TxProcessingWorker,
+    ) -> Result<(), Error> {
+        loop {
+            let deposed = async {
+                match &self.failover {
+                    Some(failover) => failover.deposed().await,
+                    None => std::future::pending().await,
+                }
             };
-            {
-                db.set_ports(rp_port, p2_port).await?
+            if let Some(failover) = &self.failover {
+                failover.elected().await;
+            }
+            tokio::select! {
+                res = self.handle_swarm_event_messages(
+                    p2p_worker.clone(),
+                    txn_processing_worker.clone(),
+                ) => {
+                    return res;
+                }
+                _ = self.watchdog.swarm_restart_requested() => {
+                    warn!(target:"MainServiceWorker","restarting swarm handling");
+                }
+                _ = deposed => {
+                    warn!(target:"MainServiceWorker","lost the p2p identity, swarm handling stopped");
+                    timed_lock(&p2p_worker, "p2p_worker")
+                        .await
+                        .disconnect_all()
+                        .await;
+                }
             }
-            rpc_port = rp_port;
-            p2p_port = p2_port
         }
-
-        let db_worker = Arc::new(Mutex::new(db));
-
-        // fetch to the db, if not then set one
-        let airtable_client = Airtable::new()
-            .await
-            .map_err(|err| anyhow!("failed to instantiate airtable client, caused by: {err}"))?;
-
-        let moka_cache = AsyncCache::builder()
-            .max_capacity(10)
-            .name("TxStateMachine rpc tracker")
-            .time_to_live(tokio::time::Duration::from_secs(600))
-            .build();
-
-        // PEER TO PEER NETWORKING WORKER
-        // ===================================================================================== //
-
-        let p2p_worker = P2pWorker::new(
-            Arc::new(Mutex::new(airtable_client.clone())),
-            db_worker.clone(),
-            p2p_port,
-            p2p_command_recv,
-        )
-        .await?;
-
-        let p2p_network_service =
-            P2pNetworkService::new(Arc::new(p2p_command_tx), p2p_worker.clone())?;
-
-        // TRANSACTION RPC WORKER
-        // ===================================================================================== //
-
-        let txn_rpc_worker = TransactionRpcWorker::new(
-            airtable_client.clone(),
-            db_worker.clone(),
-            Arc::new(Mutex::new(rpc_recv_channel)),
-            Arc::new(Mutex::new(user_rpc_update_sender_channel)),
-            rpc_port,
-            p2p_worker.node_id,
-            moka_cache.clone(),
-        )
-        .await?;
-
-        // TRANSACTION PROCESSING LAYER
-        // ===================================================================================== //
-
-        let tx_processing_worker = TxProcessingWorker::new((
-            ChainSupported::Bnb,
-            ChainSupported::Ethereum,
-            ChainSupported::Solana,
-        ))
-        .await?;
-        // ===================================================================================== //
-
-        Ok(Self {
-            db_worker,
-            tx_rpc_worker: Arc::new(Mutex::new(txn_rpc_worker)),
-            tx_processing_worker: Arc::new(Mutex::new(tx_processing_worker)),
-            airtable_client,
-            p2p_worker: Arc::new(Mutex::new(p2p_worker)),
-            p2p_network_service: Arc::new(Mutex::new(p2p_network_service)),
-            rpc_sender_channel: Arc::new(Mutex::new(rpc_sender_channel)),
-            user_rpc_update_recv_channel: Arc::new(Mutex::new(user_rpc_update_recv_channel)),
-            moka_cache,
-        })
     }
 
     /// handle swarm events; this includes
@@ -177,13 +281,15 @@ impl MainServiceWorker {
     ) -> Result<(), Error> {
         let (sender_channel, mut recv_channel) = tokio::sync::mpsc::channel(256);
 
-        // Start swarm first and keep it running infinitely
-        tokio::spawn(async move {
+        // Start swarm first and keep it running infinitely, it stops with this loop on a restart
+        let swarm = tokio::spawn(async move {
+            // held for the lifetime of the swarm, not timed as it would count as one long hold
             let res = p2p_worker.lock().await.start_swarm(sender_channel).await;
             if res.is_err() {
                 error!("start swarm failed");
             }
         });
+        let _abort_swarm = AbortOnDrop(swarm);
 
         // This loop should never end - it continuously processes messages
         loop {
@@ -191,16 +297,113 @@ impl MainServiceWorker {
                 match swarm_msg_result {
                     Ok(swarm_msg) => match swarm_msg {
                         SwarmMessage::Request { data, inbound_id } => {
-                            let mut decoded_req: TxStateMachine = Decode::decode(&mut &data[..])
-                                .expect("failed to decode request body");
+                            let decoded_req = match envelope::decode_tx(&data) {
+                                Ok(tx) => tx,
+                                Err(err) => {
+                                    error!(target:"MainServiceWorker","dropping undecodable swarm request: {err:?}");
+                                    continue;
+                                }
+                            };
+                            // only genesis txs are sent as requests
+                            let mut decoded_req = match GenesisTx::try_from(decoded_req) {
+                                Ok(tx) => tx.into_inner(),
+                                Err(err) => {
+                                    error!(target:"MainServiceWorker","dropping swarm request: {err}");
+                                    continue;
+                                }
+                            };
 
                             let inbound_req_id = inbound_id.get_hash_id();
                             println!("inbound req id: {inbound_req_id}");
                             decoded_req.inbound_req_id = Some(inbound_req_id);
+                            // test funds are never attested as real ones and the other way around
+                            let environment = self.environments.of(decoded_req.network);
+                            if decoded_req.environment != environment {
+                                let declined = GenesisTx::try_from(decoded_req)?.recv_declined();
+                                warn!(target:"MainServiceWorker","declined attestation request of tx {}, {:?} runs on {} here but the tx on {}",declined.tx_nonce,declined.network,environment.name(declined.network),declined.environment.name(declined.network));
+                                self.record_tx_event(&declined).await?;
+                                self.handle_recv_addr_confirmed_tx_state(
+                                    inbound_req_id,
+                                    Arc::new(Mutex::new(declined)),
+                                )
+                                .await?;
+                                continue;
+                            }
+                            // a revoked account is never attested, whoever holds its key takes the funds
+                            let revoked = revocation::stored_revocation(
+                                &*timed_lock(&self.db_worker, "db_worker").await,
+                                &decoded_req.receiver_address.to_string(),
+                            )
+                            .await;
+                            match revoked {
+                                Ok(Some(_)) => {
+                                    let mut declined =
+                                        GenesisTx::try_from(decoded_req)?.recv_declined();
+                                    // the sender node may have warned about it already
+                                    let warning = Warning::from(TxWarning::RevokedAccount);
+                                    if !declined.warnings.contains(&warning) {
+                                        declined.warnings.push(warning);
+                                    }
+                                    warn!(target:"MainServiceWorker","declined attestation request of tx {}, receiver account {} is revoked",declined.tx_nonce,declined.receiver_address);
+                                    self.record_tx_event(&declined).await?;
+                                    self.handle_recv_addr_confirmed_tx_state(
+                                        inbound_req_id,
+                                        Arc::new(Mutex::new(declined)),
+                                    )
+                                    .await?;
+                                    continue;
+                                }
+                                Ok(None) => {}
+                                Err(err) => {
+                                    error!(target:"MainServiceWorker","failed to check the receiver account revocation, reason: {err}")
+                                }
+                            }
+                            // unwanted requests are answered right away and never reach the user
+                            let spam = self
+                                .spam_filter
+                                .check(
+                                    &*timed_lock(&self.db_worker, "db_worker").await,
+                                    &decoded_req,
+                                )
+                                .await;
+                            match spam {
+                                Ok(Some(reason)) => {
+                                    let declined =
+                                        GenesisTx::try_from(decoded_req)?.recv_declined();
+                                    warn!(target:"MainServiceWorker","declined attestation request of tx {}, reason: {reason}",declined.tx_nonce);
+                                    self.record_tx_event(&declined).await?;
+                                    self.handle_recv_addr_confirmed_tx_state(
+                                        inbound_req_id,
+                                        Arc::new(Mutex::new(declined)),
+                                    )
+                                    .await?;
+                                    continue;
+                                }
+                                Ok(None) => {}
+                                Err(err) => {
+                                    error!(target:"MainServiceWorker","failed to check attestation request for spam, reason: {err}")
+                                }
+                            }
+                            if let Err(err) = identity::attest_sender(
+                                &*timed_lock(&self.db_worker, "db_worker").await,
+                                &mut decoded_req,
+                            )
+                            .await
+                            {
+                                error!(target:"MainServiceWorker","failed to attest the sender profile, reason: {err}")
+                            }
+                            decoded_req.sender_badges = self
+                                .trusted_issuers
+                                .lookup(&*self.discovery, &decoded_req.sender_address.to_string())
+                                .await
+                                .unwrap_or_else(|err| {
+                                    error!(target:"MainServiceWorker","sender badges lookup failed, reason: {err}");
+                                    vec![]
+                                });
+                            self.record_tx_event(&decoded_req).await?;
                             // ===================================================================== //
                             // propagate transaction state to rpc layer for user updating (receiver updating)
-                            self.rpc_sender_channel
-                                .lock()
+                            timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
                                 .await
                                 .send(decoded_req.clone())
                                 .await?;
@@ -211,43 +414,147 @@ impl MainServiceWorker {
                             info!(target: "MainServiceWorker","propagating txn msg as a request to rpc layer for user interaction: {decoded_req:?}");
                         }
                         SwarmMessage::Response { data, outbound_id } => {
-                            let mut decoded_resp: TxStateMachine = Decode::decode(&mut &data[..])
-                                .expect("failed to decode request body");
+                            let mut decoded_resp = match envelope::decode_tx(&data) {
+                                Ok(tx) => tx,
+                                Err(err) => {
+                                    error!(target:"MainServiceWorker","dropping undecodable swarm response: {err:?}");
+                                    continue;
+                                }
+                            };
 
                             let outbound_req_id = outbound_id.get_hash_id();
                             decoded_resp.outbound_req_id = Some(outbound_req_id);
+                            // the first device of the receiver to answer validly wins, the request
+                            // is withdrawn from the other devices then
+                            let pending = self
+                                .moka_cache
+                                .get(&u64::from(decoded_resp.tx_nonce))
+                                .await
+                                .and_then(|tx| GenesisTx::try_from(tx).ok());
+                            let Some(pending) = pending else {
+                                warn!(target:"MainServiceWorker","dropping the answer to tx {}, it was answered already or expired",decoded_resp.tx_nonce);
+                                continue;
+                            };
+                            // receiver badges are looked up by this node, not taken from the receiver
+                            decoded_resp.receiver_badges = pending.receiver_badges.clone();
+                            // a receiver settles for less than the initiated amount, never more
+                            if let Some(accepted) = decoded_resp
+                                .accepted_amount
+                                .filter(|accepted| !pending.can_accept(*accepted))
+                            {
+                                warn!(target:"MainServiceWorker","dropping the accepted amount {accepted} of tx {}, not a lower nonzero amount",decoded_resp.tx_nonce);
+                                decoded_resp.accepted_amount = None;
+                            }
                             // ===================================================================== //
+                            // the receiver declined, the tx fails from our own copy as the response
+                            // could carry anything but the reject reason
+                            let declined = match &decoded_resp.status {
+                                TxStatus::RecvAddrFailed => Some(pending.recv_declined()),
+                                TxStatus::RecvRejected(reason) => {
+                                    let mut rejected = pending.recv_rejected(*reason);
+                                    // a proposed correction is only offered once it verifies
+                                    rejected.address_correction = decoded_resp
+                                        .address_correction
+                                        .clone()
+                                        .filter(|correction| {
+                                            match verify_address_correction(correction, &rejected) {
+                                                Ok(()) => true,
+                                                Err(err) => {
+                                                    warn!(target:"MainServiceWorker","dropping the address correction of tx {}: {err}",rejected.tx_nonce);
+                                                    false
+                                                }
+                                            }
+                                        });
+                                    // kept in the history with the reason, unlike a silent decline
+                                    let db_tx = DbTxStateMachine::of(&rejected, vec![], false);
+                                    timed_lock(&self.db_worker, "db_worker")
+                                        .await
+                                        .update_failed_tx(db_tx)
+                                        .await?;
+                                    Some(rejected)
+                                }
+                                _ => None,
+                            };
+                            if let Some(declined) = declined {
+                                warn!(target:"MainServiceWorker","receiver declined tx {}: {:?}",declined.tx_nonce,declined.status);
+                                timed_lock(&self.p2p_network_service, "p2p_network_service")
+                                    .await
+                                    .cancel_requests(declined.tx_nonce)
+                                    .await?;
+                                self.record_tx_event(&declined).await?;
+                                timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
+                                    .await
+                                    .send(declined.clone())
+                                    .await?;
+                                self.moka_cache
+                                    .insert(declined.tx_nonce.into(), declined)
+                                    .await;
+                                continue;
+                            }
+                            // only receiver attested txs are sent as responses
+                            let recv_confirmed = match RecvConfirmedTx::try_from(decoded_resp) {
+                                Ok(tx) => tx,
+                                Err(err) => {
+                                    error!(target:"MainServiceWorker","dropping swarm response: {err}");
+                                    continue;
+                                }
+                            };
                             // handle error, by returning the tx status to the sender
-                            match txn_processing_worker
-                                .validate_receiver_sender_address(&decoded_resp, "Receiver")
+                            let mut decoded_resp = match txn_processing_worker
+                                .validate_receiver_sender_address(&recv_confirmed, "Receiver")
                             {
                                 Ok(_) => {
-                                    decoded_resp.recv_confirmation_passed();
+                                    // a malformed or forged answer leaves the others pending
+                                    timed_lock(&self.p2p_network_service, "p2p_network_service")
+                                        .await
+                                        .cancel_requests(recv_confirmed.tx_nonce)
+                                        .await?;
+                                    let mut net_confirmed = recv_confirmed.confirmation_passed();
                                     info!(target:"MainServiceWorker","receiver confirmation passed");
                                     // create a signable tx for sender to sign upon confirmation
-                                    let mut tx_processing =
-                                        self.tx_processing_worker.lock().await.clone();
-                                    tx_processing.create_tx(&mut decoded_resp).await?;
-
-                                    info!(target:"MainServiceWorker","created a signable transaction");
+                                    let mut tx_processing = timed_lock(
+                                        &self.tx_processing_worker,
+                                        "tx_processing_worker",
+                                    )
+                                    .await
+                                    .clone();
+                                    match tx_processing.create_tx(&mut net_confirmed).await {
+                                        Ok(_) => {
+                                            info!(target:"MainServiceWorker","created a signable transaction");
+                                            net_confirmed.into_inner()
+                                        }
+                                        Err(err) => {
+                                            // e.g. the fee is above the sender or node cap
+                                            error!(target:"MainServiceWorker","failed to create a signable transaction, reason: {err}");
+                                            let failed_tx =
+                                                net_confirmed.creation_failed(err.to_string());
+                                            let db_tx =
+                                                DbTxStateMachine::of(&failed_tx, vec![], false);
+                                            timed_lock(&self.db_worker, "db_worker")
+                                                .await
+                                                .update_failed_tx(db_tx)
+                                                .await?;
+                                            failed_tx
+                                        }
+                                    }
                                 }
                                 Err(err) => {
-                                    decoded_resp.recv_confirmation_failed();
+                                    let failed_tx = recv_confirmed.confirmation_failed();
                                     error!(target:"MainServiceWorker","receiver confirmation failed, reason: {err}");
                                     // record failed txn in local db
-                                    let db_tx = DbTxStateMachine {
-                                        tx_hash: vec![],
-                                        amount: decoded_resp.amount,
-                                        network: decoded_resp.network,
-                                        success: false,
-                                    };
-                                    self.db_worker.lock().await.update_failed_tx(db_tx).await?;
+                                    let db_tx = DbTxStateMachine::of(&failed_tx, vec![], false);
+                                    timed_lock(&self.db_worker, "db_worker")
+                                        .await
+                                        .update_failed_tx(db_tx)
+                                        .await?;
+                                    failed_tx
                                 }
-                            }
+                            };
+                            self.apply_policies(&mut decoded_resp).await;
+                            self.record_tx_event(&decoded_resp).await?;
 
                             // propagate transaction state to rpc layer for user updating ( this time sender verification)
-                            self.rpc_sender_channel
-                                .lock()
+                            timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
                                 .await
                                 .send(decoded_resp.clone())
                                 .await?;
@@ -258,6 +565,58 @@ impl MainServiceWorker {
 
                             info!(target: "MainServiceWorker","propagating txn msg as a response to rpc layer for user interaction: {decoded_resp:?}");
                         }
+                        // the receiver node got the request, the tx now waits on the receiver user
+                        SwarmMessage::Delivered { tx_nonce } => {
+                            let pending = self
+                                .moka_cache
+                                .get(&u64::from(tx_nonce))
+                                .await
+                                .filter(|tx| tx.status == TxStatus::Genesis)
+                                .and_then(|tx| GenesisTx::try_from(tx).ok());
+                            if let Some(pending) = pending {
+                                let delivered = pending.delivered().into_inner();
+                                info!(target:"MainServiceWorker","tx {tx_nonce} delivered to the receiver node");
+                                self.record_tx_event(&delivered).await?;
+                                timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
+                                    .await
+                                    .send(delivered.clone())
+                                    .await?;
+                                self.moka_cache.insert(tx_nonce.into(), delivered).await;
+                            }
+                        }
+                        // another device of the receiver answered, the request is no longer
+                        // waiting on this one
+                        SwarmMessage::Cancelled { inbound_req_id } => {
+                            let pending_request =
+                                timed_lock(&self.p2p_network_service, "p2p_network_service")
+                                    .await
+                                    .p2p_worker
+                                    .pending_request
+                                    .clone();
+                            let unanswered = pending_request
+                                .lock()
+                                .await
+                                .remove(&inbound_req_id)
+                                .is_some();
+                            let pending = self
+                                .moka_cache
+                                .iter()
+                                .map(|(_, tx)| tx)
+                                .find(|tx| tx.inbound_req_id == Some(inbound_req_id))
+                                .and_then(|tx| GenesisTx::try_from(tx).ok());
+                            if let (true, Some(pending)) = (unanswered, pending) {
+                                let cancelled = pending.answered_on_other_device();
+                                info!(target:"MainServiceWorker","tx {} answered on another device",cancelled.tx_nonce);
+                                self.record_tx_event(&cancelled).await?;
+                                timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
+                                    .await
+                                    .send(cancelled.clone())
+                                    .await?;
+                                self.moka_cache
+                                    .insert(cancelled.tx_nonce.into(), cancelled)
+                                    .await;
+                            }
+                        }
                     },
                     Err(err) => {
                         info!("no new messages from swarm: {err}");
@@ -270,9 +629,9 @@ impl MainServiceWorker {
     }
 
     /// genesis state of initialized tx is being handled by the following stages
-    /// 1. check if the receiver address peer id is saved in local db if not then search in remote db
-    /// 2. getting the recv peer-id then dial the target peer-id (receiver)
-    /// 3. send the tx-state-machine object to receiver target id via p2p swarm for receiver to sign and attest ownership and correctness of the address
+    /// 1. collect the receiver devices, the node bound or saved in local db and every node the receiver registered from in remote db
+    /// 2. dial all the devices at once
+    /// 3. send the tx-state-machine object to every reachable device for the receiver to sign and attest ownership and correctness of the address, the first answer wins
     pub(crate) async fn handle_genesis_tx_state(
         &self,
         txn: Arc<Mutex<TxStateMachine>>,
@@ -280,138 +639,315 @@ impl MainServiceWorker {
         // dial to target peer id from tx receiver
         let target_id = {
             let tx = txn.lock().await;
-            tx.receiver_address.clone()
+            tx.receiver_address.to_string()
         };
         // check if the acc is present in local db
         // First try local DB
         let target_peer_result = {
             // Release DB lock immediately after query
-            self.db_worker
-                .lock()
-                .await
-                .get_saved_user_peers(target_id.clone())
-                .await
+            let db = timed_lock(&self.db_worker, "db_worker").await;
+            // a binding signed by the receiver, learned over peer exchange, wins over a saved peer
+            match db.get_peer_binding(target_id.clone()).await? {
+                Some(binding) => Ok(binding.into()),
+                None => db.get_saved_user_peers(target_id.clone()).await,
+            }
         };
 
-        match target_peer_result {
-            Ok(acc) => {
-                info!(target:"MainServiceWorker","target peer found in local db");
-                // dial the target
-                let multi_addr = acc.multi_addr.parse::<Multiaddr>()?;
-                let peer_id = PeerId::from_str(&acc.node_id)?;
+        let mut devices = vec![];
+        let found_locally = target_peer_result.is_ok();
+        if let Ok(acc) = target_peer_result {
+            info!(target:"MainServiceWorker","target peer found in local db");
+            devices.push(receiver_device(&acc)?);
+        }
 
-                // ========================================================================= //
-                let mut p2p_network_service = self.p2p_network_service.lock().await;
+        // the receiver may be registered from several nodes, e.g. a laptop and a phone
+        match self.discovery.peers_of(&target_id).await {
+            Ok(acc_ids) => {
+                for peer_record in acc_ids.into_iter().map(PeerRecord::from) {
+                    let device = match receiver_device(&peer_record) {
+                        Ok(device) => device,
+                        Err(err) => {
+                            warn!(target:"MainServiceWorker","skipping a receiver device in remote db; {err}");
+                            continue;
+                        }
+                    };
+                    if devices.iter().any(|(peer_id, _)| *peer_id == device.0) {
+                        continue;
+                    }
+                    info!(target:"MainServiceWorker","target peer found in remote db: {}", device.0);
+                    if !found_locally && devices.is_empty() {
+                        // save the target peer id to local db
+                        info!(target: "MainServiceWorker","recording target peer id to local db");
+                        timed_lock(&self.db_worker, "db_worker")
+                            .await
+                            .record_saved_user_peers(peer_record)
+                            .await?;
+                    }
+                    devices.push(device);
+                }
+            }
+            // the devices known locally still get the request
+            Err(err) if found_locally => {
+                warn!(target:"MainServiceWorker","failed to list the receiver devices in remote db; {err}")
+            }
+            Err(err) => Err(err)?,
+        }
 
-                {
-                    p2p_network_service
-                        .dial_to_peer_id(multi_addr.clone(), &peer_id)
-                        .await?;
+        if devices.is_empty() {
+            // return tx state as error on sender rpc
+            self.fail_genesis_tx(&txn, GenesisTx::recv_not_registered)
+                .await?;
+            error!(target: "MainServiceWorker","target peer not found in remote db,tell the user is missing out on safety transaction");
+            return Ok(());
+        }
+        self.send_to_receiver_devices(&txn, devices).await
+    }
+
+    /// dial every device of the receiver and send the attestation request to the reachable ones
+    /// speaking a compatible vane protocol. the tx fails when no device gets the request, an
+    /// unreachable receiver of a transfer with delivery retries is dialed again in the background
+    pub(crate) async fn send_to_receiver_devices(
+        &self,
+        txn: &Arc<Mutex<TxStateMachine>>,
+        devices: Vec<(PeerId, Multiaddr)>,
+    ) -> Result<(), Error> {
+        let retries = txn.lock().await.priority.delivery_retries();
+        match self.deliver_attestation_request(txn, &devices).await? {
+            Delivery::Unreachable(reason) if retries > 0 => {
+                warn!(target: "MainServiceWorker","receiver unreachable, dialing again in {DELIVERY_RETRY_DELAY:?}; {reason}");
+                tokio::spawn(self.clone().retry_delivery(txn.clone(), devices, retries));
+                Ok(())
+            }
+            delivery => self.settle_delivery(txn, delivery).await,
+        }
+    }
+
+    /// dial the receiver devices up to `retries` more times, the tx fails as unreachable after
+    async fn retry_delivery(
+        self,
+        txn: Arc<Mutex<TxStateMachine>>,
+        devices: Vec<(PeerId, Multiaddr)>,
+        retries: u32,
+    ) {
+        for retry in 1..=retries {
+            tokio::time::sleep(DELIVERY_RETRY_DELAY).await;
+            let tx_nonce = txn.lock().await.tx_nonce;
+            // the tx moved on meanwhile, e.g. withdrawn by the sender
+            if self.moka_cache.contains_key(&u64::from(tx_nonce)) {
+                return;
+            }
+            let delivery = match self.deliver_attestation_request(&txn, &devices).await {
+                Ok(Delivery::Unreachable(_)) if retry < retries => continue,
+                Ok(delivery) => delivery,
+                Err(err) => {
+                    error!(target: "MainServiceWorker","failed to retry the delivery of tx {tx_nonce}; caused by: {err}");
+                    return;
                 }
+            };
+            if let Err(err) = self.settle_delivery(&txn, delivery).await {
+                error!(target: "MainServiceWorker","failed to settle the delivery of tx {tx_nonce}; caused by: {err}");
+            }
+            return;
+        }
+    }
 
-                // wait for dialing to complete
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+    /// fail the tx when no device got the request. an incompatible device tells the user what to
+    /// update, an unreachable one only to retry
+    async fn settle_delivery(
+        &self,
+        txn: &Arc<Mutex<TxStateMachine>>,
+        delivery: Delivery,
+    ) -> Result<(), Error> {
+        match delivery {
+            Delivery::Sent => Ok(()),
+            Delivery::Incompatible(reason) => {
+                self.fail_genesis_tx(txn, |tx| tx.recv_incompatible(reason))
+                    .await
+            }
+            Delivery::Unreachable(reason) => {
+                self.fail_genesis_tx(txn, |tx| tx.recv_unreachable(reason))
+                    .await
+            }
+        }
+    }
 
-                {
-                    p2p_network_service
-                        .send_request(txn.clone(), peer_id, multi_addr)
-                        .await?;
+    /// one round of dialing the receiver devices and sending the request to the reachable ones
+    async fn deliver_attestation_request(
+        &self,
+        txn: &Arc<Mutex<TxStateMachine>>,
+        devices: &[(PeerId, Multiaddr)],
+    ) -> Result<Delivery, Error> {
+        // the service lock is taken after the dials, they take up to `DIAL_TIMEOUT`
+        let p2p_command_tx = timed_lock(&self.p2p_network_service, "p2p_network_service")
+            .await
+            .p2p_command_tx
+            .clone();
+        let dials = devices.iter().map(|(peer_id, multi_addr)| {
+            P2pNetworkService::dial_to_peer_id(
+                &p2p_command_tx,
+                multi_addr.clone(),
+                peer_id,
+                DIAL_TIMEOUT,
+            )
+        });
+        let dialed = join_all(dials).await;
+        let mut p2p_network_service =
+            timed_lock(&self.p2p_network_service, "p2p_network_service").await;
+
+        let (mut unreachable, mut incompatible, mut sent) = (None, None, 0);
+        for ((peer_id, multi_addr), dialed) in devices.iter().cloned().zip(dialed) {
+            if let Some(reason) = unreachable_receiver(&p2p_network_service, dialed?, &peer_id) {
+                warn!(target: "MainServiceWorker","not sending the attestation request; {reason}");
+                unreachable.get_or_insert(reason);
+                continue;
+            }
+            if let Some(reason) = incompatible_receiver(&p2p_network_service.p2p_worker, &peer_id) {
+                warn!(target: "MainServiceWorker","not sending the attestation request; {reason}");
+                incompatible.get_or_insert(reason);
+                continue;
+            }
+            // the risk assessment of the receiver stays with the sender node
+            let mut request = txn.lock().await.clone();
+            request.risk_assessment = None;
+            // every device gets the travel rule data sealed to its own identity
+            if let Some(attachment) = &request.travel_rule {
+                let keypair = p2p_network_service.p2p_worker.identity.keypair();
+                match travel_rule::for_receiver(attachment, &keypair, &peer_id) {
+                    Ok(attachment) => request.travel_rule = Some(attachment),
+                    Err(err) => {
+                        let reason =
+                            format!("travel rule data can't be sealed to {peer_id}; {err}");
+                        warn!(target: "MainServiceWorker","not sending the attestation request; {reason}");
+                        incompatible.get_or_insert(reason);
+                        continue;
+                    }
                 }
             }
-            Err(_err) => {
-                // fetch from remote db
-                info!(target:"MainServiceWorker","target peer not found in local db, fetching from remote db");
-
-                let acc_ids = self.airtable_client.list_all_peers().await?;
-
-                let target_id_addr = {
-                    let tx = txn.lock().await;
-                    tx.receiver_address.clone()
-                };
-
-                if !acc_ids.is_empty() {
-                    let result_peer = acc_ids.into_iter().find_map(|discovery| {
-                        match discovery
-                            .clone()
-                            .account_ids
-                            .into_iter()
-                            .find(|addr| addr == &target_id_addr)
-                        {
-                            Some(_) => {
-                                let peer_record: PeerRecord = discovery.clone().into();
-                                Some((discovery.peer_id, discovery.multi_addr, peer_record))
-                            }
-                            None => None,
-                        }
-                    });
-
-                    if result_peer.is_some() {
-                        // dial the target
-                        info!(target:"MainServiceWorker","target peer found in remote db: {result_peer:?} \n");
-                        let multi_addr = result_peer
-                            .clone()
-                            .expect("failed to get multi addr")
-                            .1
-                            .unwrap()
-                            .parse::<Multiaddr>()
-                            .map_err(|err| {
-                                anyhow!("failed to parse multi addr, caused by: {err}")
-                            })?;
-                        let peer_id = PeerId::from_str(
-                            &*result_peer
-                                .clone()
-                                .expect("failed to parse peer id")
-                                .0
-                                .expect("failed to parse peerId"),
-                        )?;
+            p2p_network_service
+                .send_attestation_request(Arc::new(Mutex::new(request)), peer_id, multi_addr)
+                .await?;
+            sent += 1;
+        }
+        drop(p2p_network_service);
 
-                        // save the target peer id to local db
-                        let peer_record = result_peer.clone().unwrap().2;
-                        info!(target: "MainServiceWorker","recording target peer id to local db");
+        Ok(match (sent, incompatible, unreachable) {
+            (0, Some(reason), _) => Delivery::Incompatible(reason),
+            (0, None, Some(reason)) => Delivery::Unreachable(reason),
+            _ => Delivery::Sent,
+        })
+    }
 
-                        // ========================================================================= //
-                        {
-                            self.db_worker
-                                .lock()
-                                .await
-                                .record_saved_user_peers(peer_record)
-                                .await?;
-                        }
+    /// abort the txs in flight whose deadline passed and tell the user. the sender node withdraws
+    /// the attestation request from the receiver devices and the receiver node drops it, each node
+    /// expires its own copy
+    pub(crate) async fn expire_overdue_txs(&self) -> Result<(), Error> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let overdue: Vec<_> = self
+            .moka_cache
+            .iter()
+            .map(|(_, tx)| tx)
+            // sender confirmed txs are on their way to the chain, checked once more before
+            .filter(|tx| tx.is_overdue(now) && tx.status != TxStatus::SenderConfirmed)
+            .filter_map(|tx| UnsubmittedTx::try_from(tx).ok())
+            .collect();
+        for tx in overdue {
+            let network_service =
+                timed_lock(&self.p2p_network_service, "p2p_network_service").await;
+            match tx.inbound_req_id {
+                Some(inbound_req_id) => {
+                    let pending_request = network_service.p2p_worker.pending_request.clone();
+                    pending_request.lock().await.remove(&inbound_req_id);
+                }
+                None => network_service.cancel_requests(tx.tx_nonce).await?,
+            }
+            drop(network_service);
+            self.expire_tx(tx).await?;
+        }
+        Ok(())
+    }
 
-                        // ========================================================================= //
-                        let mut p2p_network_service = self.p2p_network_service.lock().await;
+    /// expire the overdue txs every `DEADLINE_SWEEP_INTERVAL` until the node stops
+    pub(crate) async fn sweep_deadlines(self) {
+        let mut interval = tokio::time::interval(DEADLINE_SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.expire_overdue_txs().await {
+                error!(target: "MainServiceWorker","failed to expire overdue txs; caused by: {err}");
+            }
+        }
+    }
 
-                        {
-                            p2p_network_service
-                                .dial_to_peer_id(multi_addr.clone(), &peer_id)
-                                .await?;
-                        }
+    /// abort `tx` as past its deadline and report it to the user
+    async fn expire_tx(&self, tx: UnsubmittedTx) -> Result<(), Error> {
+        let expired = tx.expired();
+        info!(target: "MainServiceWorker","tx {} passed its deadline",expired.tx_nonce);
+        self.record_tx_event(&expired).await?;
+        timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
+            .await
+            .send(expired.clone())
+            .await?;
+        self.moka_cache
+            .insert(expired.tx_nonce.into(), expired)
+            .await;
+        Ok(())
+    }
+
+    /// fail the genesis tx with `failed` and report it to the sender
+    pub(crate) async fn fail_genesis_tx(
+        &self,
+        txn: &Arc<Mutex<TxStateMachine>>,
+        failed: impl FnOnce(GenesisTx) -> TxStateMachine,
+    ) -> Result<(), Error> {
+        let txn = failed(GenesisTx::try_from(txn.lock().await.clone())?);
+        self.record_tx_event(&txn).await?;
+        timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
+            .await
+            .send(txn.clone())
+            .await?;
+        self.moka_cache.insert(txn.tx_nonce.into(), txn).await;
+        Ok(())
+    }
 
-                        // wait for dialing to complete
-                        tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+    /// append the tx current state to the db event log
+    pub(crate) async fn record_tx_event(&self, tx: &TxStateMachine) -> Result<(), Error> {
+        let recorded_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let event = TxEvent::new(tx.clone(), recorded_at);
+        timed_lock(&self.db_worker, "db_worker")
+            .await
+            .record_tx_event(event.clone())
+            .await?;
+        self.publish_tx_event(event).await
+    }
 
-                        {
-                            p2p_network_service
-                                .send_request(txn.clone(), peer_id, multi_addr)
-                                .await?;
-                        }
-                    } else {
-                        // return tx state as error on sender rpc
-                        let mut txn = txn.lock().await.clone();
-                        txn.recv_not_registered();
-                        self.rpc_sender_channel
-                            .lock()
-                            .await
-                            .send(txn.clone())
-                            .await?;
-                        self.moka_cache.insert(txn.tx_nonce.into(), txn).await;
+    /// hand a recorded tx event to the event publisher and the other instances of the cluster
+    pub(crate) async fn publish_tx_event(&self, event: TxEvent) -> Result<(), Error> {
+        if let Some(publisher) = &self.event_publisher {
+            publisher.publish(&event);
+        }
+        if let Some(cluster) = &self.cluster {
+            let storage_key = timed_lock(&self.db_worker, "db_worker")
+                .await
+                .storage_key()
+                .cloned();
+            // the payloads and memo leave the node sealed, the instances share the keystore
+            let event = match storage_key {
+                Some(key) => key.seal_event(event)?,
+                None => event,
+            };
+            cluster.publish_tx_event(event).await;
+        }
+        Ok(())
+    }
 
-                        error!(target: "MainServiceWorker","target peer not found in remote db,tell the user is missing out on safety transaction");
-                    }
-                }
+    /// record the operator policies applying to `tx` for the sender to review, blocking outcomes are
+    /// enforced when the sender confirms
+    pub(crate) async fn apply_policies(&self, tx: &mut TxStateMachine) {
+        match policy::policy_outcomes(&*timed_lock(&self.db_worker, "db_worker").await, tx).await {
+            Ok(outcomes) => tx.policy_outcomes = outcomes,
+            Err(err) => {
+                error!(target:"MainServiceWorker","failed to evaluate policies, reason: {err}")
             }
         }
-        Ok(())
     }
 
     /// send the response to the sender via p2p swarm
@@ -421,8 +957,7 @@ impl MainServiceWorker {
         id: u64,
         txn: Arc<Mutex<TxStateMachine>>,
     ) -> Result<(), Error> {
-        self.p2p_network_service
-            .lock()
+        timed_lock(&self.p2p_network_service, "p2p_network_service")
             .await
             .send_response(id, txn)
             .await?;
@@ -435,121 +970,318 @@ impl MainServiceWorker {
         &self,
         txn: Arc<Mutex<TxStateMachine>>,
     ) -> Result<(), Error> {
-        let mut txn_inner = txn.lock().await.clone();
+        let txn_inner = SenderConfirmedTx::try_from(txn.lock().await.clone())?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        if txn_inner.is_overdue(now) {
+            return self
+                .expire_tx(UnsubmittedTx::try_from(txn_inner.into_inner())?)
+                .await;
+        }
 
         // verify sender
-        self.tx_processing_worker
-            .lock()
+        timed_lock(&self.tx_processing_worker, "tx_processing_worker")
             .await
-            .validate_receiver_sender_address(&txn_inner, "Sender")?;
+            .validate_sender(&txn_inner)
+            .await?;
         // verify multi id
-        if self
-            .tx_processing_worker
-            .lock()
+        let multi_id = timed_lock(&self.tx_processing_worker, "tx_processing_worker")
             .await
-            .validate_multi_id(&txn_inner)
-        {
-            // TODO! handle submission errors
-            // signed and ready to be submitted to target chain
-            match self
-                .tx_processing_worker
-                .lock()
-                .await
-                .submit_tx(txn_inner.clone())
+            .validate_multi_id(&txn_inner);
+        if let Err(err) = &multi_id {
+            error!(target: "MainServiceWorker","{err}");
+        }
+        if multi_id.is_ok() {
+            // an approval counts only with the signature of the designated approver over this tx
+            let approved = match (&self.second_approver, &txn_inner.second_approval) {
+                (Some(approver), Some(approval)) => {
+                    verify_second_approval(approver, &txn_inner, &approval.signature).is_ok()
+                }
+                _ => false,
+            };
+            if requires_second_approval(self.second_approver.as_ref(), &txn_inner) && !approved {
+                // held until the designated approver signs off through `approverConfirm`
+                let held_tx = txn_inner.awaiting_second_approval().into_inner();
+                self.record_tx_event(&held_tx).await?;
+                info!(target: "MainServiceWorker","tx {} waiting for the second approver", held_tx.tx_nonce);
+                timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
+                    .await
+                    .send(held_tx.clone())
+                    .await?;
+                self.moka_cache
+                    .insert(held_tx.tx_nonce.into(), held_tx)
+                    .await;
+                return Ok(());
+            }
+            // a fiat transfer is aborted rather than submitted once its token price moved too far
+            if let Err(err) = self.fiat_pricing.check(&txn_inner).await {
+                let failed_tx = txn_inner.submission_failed(err.to_string());
+                self.record_tx_event(&failed_tx).await?;
+                error!(target: "MainServiceWorker","tx {} not submitted, reason: {err}", failed_tx.tx_nonce);
+                timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
+                    .await
+                    .send(failed_tx)
+                    .await?;
+                return Ok(());
+            }
+            // signed and ready to be submitted to target chain, queued on its network submission worker
+            self.submission_queues.submit(txn_inner).await?;
+        } else {
+            // non original sender confirmed, return error, send to rpc
+            let failed_tx = txn_inner.sender_confirmation_failed();
+            self.record_tx_event(&failed_tx).await?;
+            error!(target: "MainServiceWorker","Non original sender signed");
+            timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
                 .await
-            {
+                .send(failed_tx)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// report the submission workers outcomes to the user and record them in the db
+    pub(crate) async fn handle_submission_outcomes(&self) -> Result<(), Error> {
+        while let Some(SubmissionOutcome {
+            tx,
+            result,
+            unverified,
+        }) = self.submission_outcomes.lock().await.recv().await
+        {
+            match result {
                 Ok(tx_hash) => {
+                    let network = tx.network;
                     // update user via rpc on tx success
-                    txn_inner.tx_submission_passed(tx_hash);
-                    self.rpc_sender_channel
-                        .lock()
+                    let mut submitted_tx = tx.submission_passed(tx_hash).into_inner();
+                    // the hash is kept, the tx may still be mined
+                    if unverified {
+                        submitted_tx
+                            .warnings
+                            .push(Warning::from(TxWarning::UnverifiedBroadcast {
+                                timeout_secs: VERIFY_TIMEOUT.as_secs(),
+                            }));
+                    }
+                    // update local db on success tx
+                    let db_tx = DbTxStateMachine::of(&submitted_tx, tx_hash.to_vec(), true);
+                    self.record_tx_event(&submitted_tx).await?;
+                    timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
                         .await
-                        .send(txn_inner.clone())
+                        .send(submitted_tx)
                         .await?;
-                    // update local db on success tx
-                    let db_tx = DbTxStateMachine {
-                        tx_hash: tx_hash.to_vec(),
-                        amount: txn_inner.amount.clone(),
-                        network: txn_inner.network.clone(),
-                        success: true,
-                    };
-                    self.db_worker.lock().await.update_success_tx(db_tx).await?;
+                    timed_lock(&self.db_worker, "db_worker")
+                        .await
+                        .update_success_tx(db_tx)
+                        .await?;
+                    self.enrich_with_receipt(network, tx_hash).await;
                 }
                 Err(err) => {
-                    txn_inner.tx_submission_failed(format!(
+                    // TODO! handle submission errors
+                    let failed_tx = tx.submission_failed(format!(
                         "{err:?}: the tx will be resubmitted rest assured"
                     ));
-                    self.rpc_sender_channel.lock().await.send(txn_inner).await?;
+                    self.record_tx_event(&failed_tx).await?;
+                    timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
+                        .await
+                        .send(failed_tx)
+                        .await?;
                 }
             }
-        } else {
-            // non original sender confirmed, return error, send to rpc
-            txn_inner.sender_confirmation_failed();
-            error!(target: "MainServiceWorker","Non original sender signed");
-            self.rpc_sender_channel.lock().await.send(txn_inner).await?;
         }
-
         Ok(())
     }
 
-    /// this for now is same as `handle_addr_confirmed_tx_state`
+    /// record the chain receipt of a submitted tx in its history record once the tx is included,
+    /// in the background as inclusion takes blocks
+    async fn enrich_with_receipt(&self, network: ChainSupported, tx_hash: [u8; 32]) {
+        let tx_processing = timed_lock(&self.tx_processing_worker, "tx_processing_worker")
+            .await
+            .clone();
+        let db_worker = self.db_worker.clone();
+        tokio::spawn(async move {
+            let receipt = match tx_processing.await_receipt(network, tx_hash).await {
+                Ok(Some(receipt)) => receipt,
+                Ok(None) => {
+                    warn!(target: "MainServiceWorker", "no receipt of {network:?} tx 0x{}", hex::encode(tx_hash));
+                    return;
+                }
+                Err(err) => {
+                    error!(target: "MainServiceWorker", "failed to fetch a tx receipt, reason: {err}");
+                    return;
+                }
+            };
+            // the fee stats do without the overpay of this tx when the fee history is unavailable
+            let low_tier_fee = match tx_processing.low_tier_fee(network, &receipt).await {
+                Ok(low_tier_fee) => low_tier_fee,
+                Err(err) => {
+                    warn!(target: "MainServiceWorker", "failed to price the low fee tier, reason: {err}");
+                    None
+                }
+            };
+            let recorded = timed_lock(&db_worker, "db_worker")
+                .await
+                .record_tx_receipt(tx_hash.to_vec(), receipt, low_tier_fee)
+                .await;
+            if let Err(err) = recorded {
+                error!(target: "MainServiceWorker", "failed to record a tx receipt, reason: {err}");
+            }
+        });
+    }
+
+    /// txs without a receiver to attest, i.e. token approvals, enter here and directly get a signable tx
+    /// this will be executed on sender's end
     pub(crate) async fn handle_net_confirmed_tx_state(
         &self,
-        _txn: Arc<Mutex<TxStateMachine>>,
+        txn: Arc<Mutex<TxStateMachine>>,
     ) -> Result<(), anyhow::Error> {
-        todo!()
+        let mut net_confirmed = NetConfirmedTx::try_from(txn.lock().await.clone())?;
+        let mut tx_processing = timed_lock(&self.tx_processing_worker, "tx_processing_worker")
+            .await
+            .clone();
+        let mut tx = match tx_processing.create_tx(&mut net_confirmed).await {
+            Ok(_) => {
+                info!(target:"MainServiceWorker","created a signable transaction");
+                net_confirmed.into_inner()
+            }
+            Err(err) => {
+                error!(target:"MainServiceWorker","failed to create a signable transaction, reason: {err}");
+                let failed_tx = net_confirmed.creation_failed(err.to_string());
+                let db_tx = DbTxStateMachine::of(&failed_tx, vec![], false);
+                timed_lock(&self.db_worker, "db_worker")
+                    .await
+                    .update_failed_tx(db_tx)
+                    .await?;
+                failed_tx
+            }
+        };
+        self.apply_policies(&mut tx).await;
+        self.record_tx_event(&tx).await?;
+        timed_lock(&self.rpc_sender_channel, "rpc_sender_channel")
+            .await
+            .send(tx.clone())
+            .await?;
+        self.moka_cache.insert(tx.tx_nonce.into(), tx).await;
+        Ok(())
     }
 
     /// all user interactions are done via rpc, after user sends rpc as updated (`tx-state-machine`) as argument,
     /// the tx object will be send to channel to be handled depending on its current state
     pub(crate) async fn handle_incoming_rpc_tx_updates(&self) -> Result<(), anyhow::Error> {
         while let Some(txn) = self.user_rpc_update_recv_channel.lock().await.recv().await {
-            // handle the incoming transaction per its state
-            let status = txn.lock().await.clone().status;
-            match status {
-                TxStatus::Genesis => {
-                    info!(target:"MainServiceWorker","handling incoming genesis tx updates: {:?} \n",txn.lock().await.clone());
-                    self.handle_genesis_tx_state(txn.clone()).await?;
+            let tx = txn.lock().await.clone();
+            // the parts of a split payment are recorded together when the payment is staged
+            let staged = tx.status == TxStatus::Genesis
+                && tx.parent_payment.is_some()
+                && timed_lock(&self.db_worker, "db_worker")
+                    .await
+                    .get_tx_events(tx.tx_id())
+                    .await?
+                    .iter()
+                    .any(|event| event.status == TxStatus::Genesis);
+            if staged {
+                let recorded_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                self.publish_tx_event(TxEvent::new(tx, recorded_at)).await?;
+            } else {
+                self.record_tx_event(&tx).await?;
+            }
+            // in a cluster the instance owning the account of the tx handles it
+            if let Some(cluster) = &self.cluster {
+                if cluster.forward(&*txn.lock().await).await {
+                    continue;
                 }
+            }
+            self.handle_user_tx_update(txn).await?;
+        }
+        Ok(())
+    }
 
-                TxStatus::RecvAddrConfirmed => {
-                    info!(target:"MainServiceWorker","handling incoming receiver addr-confirmation tx updates: {:?} \n",txn.lock().await.clone());
+    /// handle the tx updated by the user per its state
+    pub(crate) async fn handle_user_tx_update(
+        &self,
+        txn: Arc<Mutex<TxStateMachine>>,
+    ) -> Result<(), anyhow::Error> {
+        // handle the incoming transaction per its state
+        let status = txn.lock().await.clone().status;
+        match status {
+            TxStatus::Genesis => {
+                info!(target:"MainServiceWorker","handling incoming genesis tx updates: {:?} \n",txn.lock().await.clone());
+                self.handle_genesis_tx_state(txn.clone()).await?;
+            }
 
-                    let inbound_id = txn
-                        .lock()
-                        .await
-                        .inbound_req_id
-                        .expect("no inbound req id found");
-                    self.handle_recv_addr_confirmed_tx_state(inbound_id, txn.clone())
-                        .await?;
-                }
+            TxStatus::RecvAddrConfirmed => {
+                info!(target:"MainServiceWorker","handling incoming receiver addr-confirmation tx updates: {:?} \n",txn.lock().await.clone());
 
-                TxStatus::NetConfirmed => {
-                    todo!()
-                }
+                let inbound_id = txn
+                    .lock()
+                    .await
+                    .inbound_req_id
+                    .expect("no inbound req id found");
+                self.handle_recv_addr_confirmed_tx_state(inbound_id, txn.clone())
+                    .await?;
+            }
 
-                TxStatus::SenderConfirmed => {
-                    info!(target:"MainServiceWorker","handling incoming sender addr-confirmed tx updates: {:?} \n",txn.lock().await.clone());
+            // receiver declined or rejected the request, answered like a confirmation
+            TxStatus::RecvAddrFailed | TxStatus::RecvRejected(_) => {
+                info!(target:"MainServiceWorker","handling incoming receiver declined tx updates: {:?} \n",txn.lock().await.clone());
+
+                let inbound_id = txn
+                    .lock()
+                    .await
+                    .inbound_req_id
+                    .expect("no inbound req id found");
+                self.handle_recv_addr_confirmed_tx_state(inbound_id, txn.clone())
+                    .await?;
+            }
 
-                    self.handle_sender_confirmed_tx_state(txn.clone()).await?;
-                }
-                _ => {}
-            };
-        }
+            TxStatus::NetConfirmed => {
+                info!(target:"MainServiceWorker","handling incoming net-confirmed tx updates: {:?} \n",txn.lock().await.clone());
+
+                self.handle_net_confirmed_tx_state(txn.clone()).await?;
+            }
+
+            TxStatus::SenderConfirmed => {
+                info!(target:"MainServiceWorker","handling incoming sender addr-confirmed tx updates: {:?} \n",txn.lock().await.clone());
+
+                self.handle_sender_confirmed_tx_state(txn.clone()).await?;
+            }
+            _ => {}
+        };
         Ok(())
     }
 
     /// Start rpc server with default url
     pub(crate) async fn start_rpc_server(&self) -> Result<SocketAddr, anyhow::Error> {
-        let server_builder = ServerBuilder::new();
+        let rpc_handler = timed_lock(&self.tx_rpc_worker, "tx_rpc_worker")
+            .await
+            .clone();
+        let graphql = self
+            .graphql
+            .then(|| crate::graphql::schema(rpc_handler.clone()));
+        let server_builder = ServerBuilder::new()
+            .max_request_body_size(MAX_REQUEST_BODY_SIZE)
+            .max_response_body_size(MAX_RESPONSE_BODY_SIZE)
+            .set_logger(RpcLogger::default())
+            .set_middleware(
+                tower::ServiceBuilder::new()
+                    .layer(AccessControlLayer::new(
+                        self.access_control.clone(),
+                        rpc_handler.rate_limiter.clone(),
+                    ))
+                    .layer(MetricsLayer::new(
+                        rpc_handler.peer_metrics.clone(),
+                        rpc_handler.circuit_breakers.clone(),
+                    ))
+                    .layer(RestLayer::new(
+                        rpc_handler.clone(),
+                        self.access_control.clone(),
+                    ))
+                    .layer(GraphqlLayer::new(graphql, self.access_control.clone())),
+            );
 
         // --------------------------- TLS CERT---------------------------------- //
         let url_names = vec!["197.168.1.177".to_string(), "localhost".to_string()];
         let CertifiedKey { cert, key_pair } = generate_simple_self_signed(url_names)
             .map_err(|err| anyhow!("failed to generate tsl cert; {err:?}"))?;
 
-        let url = self.tx_rpc_worker.lock().await.rpc_url.clone();
-        let rpc_handler = self.tx_rpc_worker.clone().lock().await.clone();
+        let url = rpc_handler.rpc_url.clone();
 
         let server = server_builder.build(url).await?;
         let address = server
@@ -563,8 +1295,24 @@ impl MainServiceWorker {
         Ok(address)
     }
 
+    /// start the grpc server when a grpc address is set, returns the address listened on
+    pub(crate) async fn start_grpc_server(&self) -> Result<Option<SocketAddr>, anyhow::Error> {
+        let Some(address) = self.grpc_address else {
+            return Ok(None);
+        };
+        let rpc_handler = timed_lock(&self.tx_rpc_worker, "tx_rpc_worker")
+            .await
+            .clone();
+        GrpcService::new(rpc_handler, self.access_control.clone())
+            .serve(address)
+            .await
+            .map(Some)
+            .map_err(|err| anyhow!("failed to start grpc server, caused by: {err}"))
+    }
+
     /// compose all workers and run logically, the p2p swarm worker will be running indefinately on background same as rpc worker
-    pub async fn run(db_url: Option<String>) -> Result<(), anyhow::Error> {
+    /// `builder` carries the node configuration, e.g. the db path, api keys or watch-only mode. returns once a node task exits
+    pub async fn run(builder: MainServiceWorkerBuilder) -> Result<(), anyhow::Error> {
         info!(
             "\n🔥 =========== Vane Web3 =========== 🔥\n\
              A safety layer for web3 transactions, allows you to feel secure when sending and receiving \n\
@@ -572,215 +1320,111 @@ impl MainServiceWorker {
              It provides a safety net, giving you room to make mistakes without losing all your funds.\n"
         );
 
-        // ====================================================================================== //
-        let main_worker = Self::new(db_url).await?;
-        // start rpc server
-        let rpc_address = main_worker
+        builder.build().await?.start().await?.exited().await
+    }
+
+    /// start the rpc server and node tasks on the current tokio runtime and return without blocking,
+    /// for hosts embedding the node in their own runtime. `run` and `e2e_run` start the node here too
+    pub async fn start(self) -> Result<NodeHandle, anyhow::Error> {
+        let rpc_address = self
             .start_rpc_server()
             .await
             .map_err(|err| anyhow!("failed to start rpc server, caused by: {err}"))?;
-
         info!(target: "RpcServer","listening to rpc url: {rpc_address}");
-        // ====================================================================================== //
+        let grpc_address = self.start_grpc_server().await?;
 
-        let p2p_worker = main_worker.p2p_worker.clone();
-        let txn_processing_worker = main_worker
-            .tx_processing_worker
-            .clone()
-            .lock()
+        let p2p_worker = self.p2p_worker.clone();
+        let txn_processing_worker = timed_lock(&self.tx_processing_worker, "tx_processing_worker")
             .await
             .clone();
 
-        // ====================================================================================== //
+        let cloned_main_worker = self.clone();
+        let tx_handling_task = tokio::spawn(async move {
+            // watch tx messages from tx rpc worker and pass it to p2p to be verified by receiver
+            let res = cloned_main_worker.handle_incoming_rpc_tx_updates().await;
+            if let Err(err) = res {
+                error!("rpc handle encountered error: caused by {err}");
+            }
+        });
 
-        let tokio_handle = tokio::runtime::Handle::current();
-        let mut task_manager = sc_service::TaskManager::new(tokio_handle, None)?;
+        let cloned_main_worker = self.clone();
+        let submission_task = tokio::spawn(async move {
+            let res = cloned_main_worker.handle_submission_outcomes().await;
+            if let Err(err) = res {
+                error!("submission outcome handle encountered error: caused by {err}");
+            }
+        });
 
-        // ====================================================================================== //
+        let cloned_main_worker = self.clone();
+        let swarm_task = tokio::spawn(async move {
+            let res = cloned_main_worker
+                .supervise_swarm_event_messages(p2p_worker, txn_processing_worker)
+                .await;
+            if let Err(err) = res {
+                error!("swarm handle encountered error; caused by {err}");
+            }
+        });
 
-        {
-            let cloned_main_worker = main_worker.clone();
-            let task_name = "transaction-handling-task".to_string();
-            task_manager.spawn_essential_handle().spawn_blocking(
-                Box::leak(Box::new(task_name)),
-                "transaction-handling",
-                async move {
-                    // watch tx messages from tx rpc worker and pass it to p2p to be verified by receiver
-                    let res = cloned_main_worker.handle_incoming_rpc_tx_updates().await;
-                    if let Err(err) = res {
-                        error!("rpc handle encountered error: caused by {err}");
-                    }
-                }
-                .boxed(),
-            )
+        let watchdog_task = tokio::spawn(self.watchdog.clone().run(self.clone()));
+        let tx_updates = timed_lock(&self.tx_rpc_worker, "tx_rpc_worker")
+            .await
+            .tx_updates
+            .clone();
+        let tx_updates_task = tokio::spawn(tx_updates.run());
+        let deadline_task = tokio::spawn(self.clone().sweep_deadlines());
+        let mut tasks = vec![
+            tx_handling_task,
+            submission_task,
+            swarm_task,
+            watchdog_task,
+            tx_updates_task,
+            deadline_task,
+        ];
+        if let Some(telemetry) = self.telemetry.clone() {
+            tasks.push(tokio::spawn(telemetry.run()));
         }
-
-        {
-            let task_name = "swarm-p2p-task".to_string();
-            task_manager.spawn_essential_handle().spawn_blocking(
-                Box::leak(Box::new(task_name)),
-                "swarm",
-                async move {
-                    let res = main_worker
-                        .handle_swarm_event_messages(p2p_worker, txn_processing_worker)
-                        .await;
-                    if let Err(err) = res {
-                        error!("swarm handle encountered error; caused by {err}");
-                    }
-                }
-                .boxed(),
-            )
+        if let Some(cluster) = self.cluster.clone() {
+            tasks.push(tokio::spawn(cluster.run(self.clone())));
+        }
+        if let Some(publisher) = self.event_publisher.clone() {
+            tasks.push(tokio::spawn(publisher.run()));
+        }
+        if let Some(failover) = self.failover.clone() {
+            tasks.push(tokio::spawn(failover.run(self.clone())));
         }
 
-        task_manager.future().await?;
-
-        Ok(())
+        Ok(NodeHandle {
+            rpc_address,
+            grpc_address,
+            worker: self,
+            tasks,
+        })
     }
 
     // =================================== E2E ====================================== //
 
+    /// hermetic node with an in-memory db, nodes sharing `discovery` can find each other
     #[cfg(feature = "e2e")]
-    pub async fn e2e_new(port: u16, db: &str) -> Result<Self, Error> {
-        // CHANNELS
-        // ===================================================================================== //
-        // for rpc messages back and forth propagation
-        let (rpc_sender_channel, rpc_recv_channel) = tokio::sync::mpsc::channel(10);
-        let (user_rpc_update_sender_channel, user_rpc_update_recv_channel) =
-            tokio::sync::mpsc::channel(10);
-
-        // for p2p network commands
-        let (p2p_command_tx, p2p_command_recv) = tokio::sync::mpsc::channel::<NetworkCommand>(10);
-
-        // DATABASE WORKER (LOCAL AND REMOTE )
-        // ===================================================================================== //
-        let db_worker = Arc::new(Mutex::new(DbWorker::initialize_db_client(db).await?));
-
-        // fetch to the db, if not then set one
-        let airtable_client = Airtable::new()
+    pub async fn e2e_new(
+        port: u16,
+        discovery: crate::discovery::LocalDiscovery,
+    ) -> Result<Self, Error> {
+        MainServiceWorkerBuilder::new()
+            .db_url(db::IN_MEMORY_DB_URL)
+            .ports(
+                port.checked_sub(100)
+                    .ok_or(anyhow!("rpc port below {port} out of range"))?,
+                port.checked_sub(589)
+                    .ok_or(anyhow!("p2p port below {port} out of range"))?,
+            )
+            .discovery(Arc::new(discovery))
+            .no_telemetry()
+            .build()
             .await
-            .map_err(|err| anyhow!("failed to instantiate airtable client, caused by: {err}"))?;
-
-        let moka_cache = AsyncCache::builder()
-            .max_capacity(10)
-            .name("TxStateMachine rpc tracker")
-            .time_to_live(tokio::time::Duration::from_secs(600))
-            .build();
-
-        // PEER TO PEER NETWORKING WORKER
-        // ===================================================================================== //
-        let (rpc_port, p2p_port) = (port - 100, port - 589);
-        let p2p_worker = P2pWorker::new(
-            Arc::new(Mutex::new(airtable_client.clone())),
-            db_worker.clone(),
-            p2p_port,
-            p2p_command_recv,
-        )
-        .await?;
-
-        let p2p_network_service =
-            P2pNetworkService::new(Arc::new(p2p_command_tx), p2p_worker.clone())?;
-
-        // TRANSACTION RPC WORKER
-        // ===================================================================================== //
-
-        let txn_rpc_worker = TransactionRpcWorker::new(
-            airtable_client.clone(),
-            db_worker.clone(),
-            Arc::new(Mutex::new(rpc_recv_channel)),
-            Arc::new(Mutex::new(user_rpc_update_sender_channel)),
-            rpc_port,
-            p2p_worker.node_id,
-            moka_cache.clone(),
-        )
-        .await?;
-
-        // TRANSACTION PROCESSING LAYER
-        // ===================================================================================== //
-
-        let tx_processing_worker = TxProcessingWorker::new((
-            ChainSupported::Bnb,
-            ChainSupported::Ethereum,
-            ChainSupported::Solana,
-        ))
-        .await?;
-        // ===================================================================================== //
-
-        Ok(Self {
-            db_worker,
-            tx_rpc_worker: Arc::new(Mutex::new(txn_rpc_worker)),
-            tx_processing_worker: Arc::new(Mutex::new(tx_processing_worker)),
-            airtable_client,
-            p2p_worker: Arc::new(Mutex::new(p2p_worker)),
-            p2p_network_service: Arc::new(Mutex::new(p2p_network_service)),
-            rpc_sender_channel: Arc::new(Mutex::new(rpc_sender_channel)),
-            user_rpc_update_recv_channel: Arc::new(Mutex::new(user_rpc_update_recv_channel)),
-            moka_cache,
-        })
     }
 
     #[cfg(feature = "e2e")]
     pub async fn e2e_run(main_worker: MainServiceWorker) -> Result<(), anyhow::Error> {
-        // ====================================================================================== //
-        // start rpc server
-        let rpc_address = main_worker
-            .start_rpc_server()
-            .await
-            .map_err(|err| anyhow!("failed to start rpc server, caused by: {err}"))?;
-
-        info!(target: "RpcServer","listening to rpc url: {rpc_address}");
-        // ====================================================================================== //
-
-        let p2p_worker = main_worker.p2p_worker.clone();
-        let txn_processing_worker = main_worker
-            .tx_processing_worker
-            .clone()
-            .lock()
-            .await
-            .clone();
-
-        // ====================================================================================== //
-
-        let tokio_handle = tokio::runtime::Handle::current();
-        let mut task_manager = sc_service::TaskManager::new(tokio_handle, None)?;
-
-        // ====================================================================================== //
-
-        {
-            let cloned_main_worker = main_worker.clone();
-            let task_name = "transaction-handling-task".to_string();
-            task_manager.spawn_essential_handle().spawn_blocking(
-                Box::leak(Box::new(task_name)),
-                "transaction-handling",
-                async move {
-                    // watch tx messages from tx rpc worker and pass it to p2p to be verified by receiver
-                    let res = cloned_main_worker.handle_incoming_rpc_tx_updates().await;
-                    if let Err(err) = res {
-                        error!("rpc handle encountered error: caused by {err}");
-                    }
-                }
-                .boxed(),
-            )
-        }
-
-        {
-            let task_name = "swarm-p2p-task".to_string();
-            task_manager.spawn_essential_handle().spawn_blocking(
-                Box::leak(Box::new(task_name)),
-                "swarm",
-                async move {
-                    let res = main_worker
-                        .handle_swarm_event_messages(p2p_worker, txn_processing_worker)
-                        .await;
-                    if let Err(err) = res {
-                        error!("swarm handle encountered error; caused by {err}");
-                    }
-                }
-                .boxed(),
-            )
-        }
-
-        task_manager.future().await?;
-
-        Ok(())
+        main_worker.start().await?.exited().await
     }
 }
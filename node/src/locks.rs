@@ -0,0 +1,154 @@
+// lock contention instrumentation
+// shared workers sit behind tokio mutexes, `timed_lock` records per component how long callers waited
+// for the lock and how long they held it. holding a lock longer than `LONG_HOLD_THRESHOLD` nearly always
+// means it was held across an await and is logged. the stats are served by the `/metrics` endpoint
+
+use log::warn;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex as StdMutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, MutexGuard};
+
+/// holds longer than this are logged
+pub const LONG_HOLD_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// wait and hold times of the locks of a component
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LockStats {
+    pub acquisitions: u64,
+    pub wait_total: Duration,
+    pub wait_max: Duration,
+    pub hold_total: Duration,
+    pub hold_max: Duration,
+    /// holds longer than `LONG_HOLD_THRESHOLD`
+    pub long_holds: u64,
+}
+
+fn registry() -> &'static StdMutex<BTreeMap<&'static str, LockStats>> {
+    static REGISTRY: OnceLock<StdMutex<BTreeMap<&'static str, LockStats>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+fn record(component: &'static str, update: impl FnOnce(&mut LockStats)) {
+    if let Ok(mut registry) = registry().lock() {
+        update(registry.entry(component).or_default());
+    }
+}
+
+/// stats of every component locked so far
+pub fn lock_stats() -> BTreeMap<&'static str, LockStats> {
+    registry()
+        .lock()
+        .map(|registry| registry.clone())
+        .unwrap_or_default()
+}
+
+/// lock stats in the prometheus text format
+pub fn render_metrics() -> String {
+    let stats = lock_stats();
+    let mut metrics = String::new();
+    let mut family = |name: &str, kind: &str, help: &str, value: fn(&LockStats) -> String| {
+        let _ = writeln!(metrics, "# HELP {name} {help}");
+        let _ = writeln!(metrics, "# TYPE {name} {kind}");
+        for (component, stats) in &stats {
+            let _ = writeln!(
+                metrics,
+                "{name}{{component=\"{component}\"}} {}",
+                value(stats)
+            );
+        }
+    };
+    family(
+        "vane_lock_acquisitions_total",
+        "counter",
+        "locks acquired",
+        |stats| stats.acquisitions.to_string(),
+    );
+    family(
+        "vane_lock_wait_seconds_total",
+        "counter",
+        "time spent waiting for the lock",
+        |stats| stats.wait_total.as_secs_f64().to_string(),
+    );
+    family(
+        "vane_lock_wait_seconds_max",
+        "gauge",
+        "longest wait for the lock",
+        |stats| stats.wait_max.as_secs_f64().to_string(),
+    );
+    family(
+        "vane_lock_hold_seconds_total",
+        "counter",
+        "time the lock was held",
+        |stats| stats.hold_total.as_secs_f64().to_string(),
+    );
+    family(
+        "vane_lock_hold_seconds_max",
+        "gauge",
+        "longest hold of the lock",
+        |stats| stats.hold_max.as_secs_f64().to_string(),
+    );
+    family(
+        "vane_lock_long_holds_total",
+        "counter",
+        "locks held longer than the long hold threshold",
+        |stats| stats.long_holds.to_string(),
+    );
+    metrics
+}
+
+/// guard recording the hold time of the lock when dropped
+pub struct TimedGuard<'a, T> {
+    guard: MutexGuard<'a, T>,
+    component: &'static str,
+    acquired_at: Instant,
+}
+
+impl<T> Deref for TimedGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for TimedGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for TimedGuard<'_, T> {
+    fn drop(&mut self) {
+        let held = self.acquired_at.elapsed();
+        let component = self.component;
+        if held > LONG_HOLD_THRESHOLD {
+            warn!(target: "Locks", "{component} lock held for {held:?}, likely across an await");
+        }
+        record(component, |stats| {
+            stats.hold_total += held;
+            stats.hold_max = stats.hold_max.max(held);
+            stats.long_holds += u64::from(held > LONG_HOLD_THRESHOLD);
+        });
+    }
+}
+
+/// `mutex.lock()` recording wait and hold times under `component`
+pub async fn timed_lock<'a, T>(mutex: &'a Mutex<T>, component: &'static str) -> TimedGuard<'a, T> {
+    let started = Instant::now();
+    let guard = mutex.lock().await;
+    let acquired_at = Instant::now();
+    let waited = acquired_at - started;
+    record(component, |stats| {
+        stats.acquisitions += 1;
+        stats.wait_total += waited;
+        stats.wait_max = stats.wait_max.max(waited);
+    });
+    TimedGuard {
+        guard,
+        component,
+        acquired_at,
+    }
+}
@@ -0,0 +1,2114 @@
+// OpenRPC description of the `TransactionRpc` api
+// served at `rpc.discover` and used for client sdk generation and contract testing
+// the served method list is generated from the methods registered on the rpc module,
+// `rpc_methods` describes their params, results and errors
+
+use crate::rpc_middleware::Role;
+use primitives::errors::{
+    ACCESS_DENIED_CODE, AMBIGUOUS_NETWORK_CODE, APPROVAL_DENIED_CODE, ASSET_NOT_ACCEPTED_CODE,
+    ATTESTATION_EXPIRED_CODE, BINDING_NOT_ANCHORED_CODE, COMPUTE_UNIT_PRICE_CAP_EXCEEDED_CODE,
+    DEADLINE_EXCEEDED_CODE, ENVIRONMENT_MISMATCH_CODE, FEE_CAP_EXCEEDED_CODE,
+    INSUFFICIENT_FUNDS_CODE, INTERNAL_ERROR_CODE, INVALID_ADDRESS_CODE, INVALID_PARAMS_CODE,
+    INVALID_TX_STATE_CODE, NODE_LOCKED_CODE, PLUGIN_DENIED_CODE, POLICY_VIOLATION_CODE,
+    PRICE_MOVED_CODE, RATE_LIMITED_CODE, RECEIVER_NOT_REGISTERED_CODE, RECEIVER_OFFLINE_CODE,
+    RISK_CHECK_FAILED_CODE, SIGNATURE_MISSING_CODE, SIGNER_NOT_PAIRED_CODE, STANDBY_NODE_CODE,
+    UNSUPPORTED_NETWORK_CODE, WARNING_OVERRIDE_REQUIRED_CODE, WRONG_NETWORK_SUSPECTED_CODE,
+};
+use serde_json::{json, Value};
+
+pub const OPENRPC_VERSION: &str = "1.2.6";
+
+/// method parameter or result description
+#[derive(Clone, Debug)]
+pub struct ContentDescriptor {
+    pub name: &'static str,
+    pub schema: Value,
+    pub required: bool,
+}
+
+impl ContentDescriptor {
+    pub fn new(name: &'static str, schema: Value) -> Self {
+        Self {
+            name,
+            schema,
+            required: true,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "required": self.required,
+            "schema": self.schema,
+        })
+    }
+}
+
+/// rpc method description
+#[derive(Clone, Debug)]
+pub struct MethodDescriptor {
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub params: Vec<ContentDescriptor>,
+    pub result: Option<ContentDescriptor>,
+    /// subscriptions are paired with an unsubscribe method and stream `result` items
+    pub unsubscribe: Option<&'static str>,
+    pub errors: Vec<i32>,
+}
+
+impl MethodDescriptor {
+    /// registered method `rpc_methods` does not describe yet
+    pub fn undocumented(name: &'static str) -> Self {
+        Self {
+            name,
+            summary: "",
+            params: Vec::new(),
+            result: None,
+            unsubscribe: None,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let result = self
+            .result
+            .as_ref()
+            .map(|r| r.to_json())
+            .unwrap_or(json!({"name": "null", "schema": {"type": "null"}}));
+
+        let mut method = json!({
+            "name": self.name,
+            "summary": self.summary,
+            "paramStructure": "by-position",
+            "params": self.params.iter().map(|p| p.to_json()).collect::<Vec<Value>>(),
+            "result": result,
+            "errors": self.errors.iter().map(|code| json!({"$ref": format!("#/components/errors/{code}")})).collect::<Vec<Value>>(),
+            "x-role": Role::required_for(self.name).to_string(),
+        });
+        if let Some(unsubscribe) = self.unsubscribe {
+            method["x-subscription"] = json!({ "unsubscribe": unsubscribe });
+        }
+        method
+    }
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+fn string_schema() -> Value {
+    json!({ "type": "string" })
+}
+
+fn bytes_schema(len: Option<usize>) -> Value {
+    let mut schema =
+        json!({ "type": "array", "items": { "type": "integer", "minimum": 0, "maximum": 255 } });
+    if let Some(len) = len {
+        schema["minItems"] = json!(len);
+        schema["maxItems"] = json!(len);
+    }
+    schema
+}
+
+/// all methods exposed by the node
+pub fn rpc_methods() -> Vec<MethodDescriptor> {
+    vec![
+        MethodDescriptor {
+            name: "register",
+            summary: "register user profile and push the account binding to vane discovery",
+            params: vec![
+                ContentDescriptor::new("name", string_schema()),
+                ContentDescriptor::new("accountId", string_schema()),
+                ContentDescriptor::new("network", schema_ref("ChainSupported")),
+            ],
+            result: None,
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "addAccount",
+            summary: "add crypto address accounts to the user profile, binding them to the node peer record",
+            params: vec![
+                ContentDescriptor::new("name", string_schema()),
+                ContentDescriptor::new(
+                    "accounts",
+                    json!({
+                        "type": "array",
+                        "items": {
+                            "type": "array",
+                            "prefixItems": [string_schema(), schema_ref("ChainSupported")],
+                            "minItems": 2,
+                            "maxItems": 2
+                        }
+                    }),
+                ),
+            ],
+            result: None,
+            unsubscribe: None,
+            errors: vec![
+                INVALID_ADDRESS_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "initiateTransaction",
+            summary: "initiate tx to be verified by the receiver on address and network choice, retries with the same idempotency key return the first tx id, reusing the key for another transfer is refused",
+            params: vec![
+                ContentDescriptor::new("sender", string_schema()),
+                ContentDescriptor::new("receiver", string_schema()),
+                ContentDescriptor::new("amount", json!({ "type": "integer", "minimum": 0 })),
+                ContentDescriptor::new("token", schema_ref("Token")),
+                ContentDescriptor::new("network", schema_ref("ChainSupported")),
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("idempotencyKey", string_schema())
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("feePreference", schema_ref("FeePreference"))
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("submissionRoute", schema_ref("SubmissionRoute"))
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("memo", string_schema())
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("senderProfile", schema_ref("SenderProfile"))
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("environment", schema_ref("NetworkEnvironment"))
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("reference", string_schema())
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new(
+                        "tags",
+                        json!({ "type": "array", "items": string_schema() }),
+                    )
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("parentPayment", string_schema())
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("travelRule", schema_ref("TravelRuleData"))
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("ttlSecs", json!({ "type": "integer", "minimum": 1 }))
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("priority", schema_ref("TxPriority"))
+                },
+            ],
+            result: Some(ContentDescriptor::new("txId", string_schema())),
+            unsubscribe: None,
+            errors: vec![
+                INVALID_ADDRESS_CODE,
+                WRONG_NETWORK_SUSPECTED_CODE,
+                UNSUPPORTED_NETWORK_CODE,
+                ENVIRONMENT_MISMATCH_CODE,
+                INSUFFICIENT_FUNDS_CODE,
+                FEE_CAP_EXCEEDED_CODE,
+                COMPUTE_UNIT_PRICE_CAP_EXCEEDED_CODE,
+                ASSET_NOT_ACCEPTED_CODE,
+                POLICY_VIOLATION_CODE,
+                PLUGIN_DENIED_CODE,
+                RISK_CHECK_FAILED_CODE,
+                BINDING_NOT_ANCHORED_CODE,
+                SIGNER_NOT_PAIRED_CODE,
+                NODE_LOCKED_CODE,
+                STANDBY_NODE_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "initiateFiatTransaction",
+            summary: "initiate a transfer worth a usd value, the amount is computed at the current token price and refused once the price moves past the slippage",
+            params: vec![
+                ContentDescriptor::new("sender", string_schema()),
+                ContentDescriptor::new("receiver", string_schema()),
+                ContentDescriptor::new("usdCents", json!({ "type": "integer", "minimum": 1 })),
+                ContentDescriptor::new("token", schema_ref("Token")),
+                ContentDescriptor::new("network", schema_ref("ChainSupported")),
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new(
+                        "maxSlippageBps",
+                        json!({ "type": "integer", "minimum": 0, "maximum": 9999 }),
+                    )
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("idempotencyKey", string_schema())
+                },
+            ],
+            result: Some(ContentDescriptor::new("txId", string_schema())),
+            unsubscribe: None,
+            errors: vec![
+                INVALID_ADDRESS_CODE,
+                WRONG_NETWORK_SUSPECTED_CODE,
+                UNSUPPORTED_NETWORK_CODE,
+                INSUFFICIENT_FUNDS_CODE,
+                ASSET_NOT_ACCEPTED_CODE,
+                POLICY_VIOLATION_CODE,
+                PLUGIN_DENIED_CODE,
+                RISK_CHECK_FAILED_CODE,
+                BINDING_NOT_ANCHORED_CODE,
+                SIGNER_NOT_PAIRED_CODE,
+                NODE_LOCKED_CODE,
+                STANDBY_NODE_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "initiateSplitPayment",
+            summary: "split one payment into transfers to several receivers, tokens or networks, each attested and confirmed on its own, returns the payment id",
+            params: vec![
+                ContentDescriptor::new("sender", string_schema()),
+                ContentDescriptor::new(
+                    "parts",
+                    json!({ "type": "array", "items": schema_ref("PaymentPart") }),
+                ),
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("reference", string_schema())
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new(
+                        "tags",
+                        json!({ "type": "array", "items": string_schema() }),
+                    )
+                },
+            ],
+            result: Some(ContentDescriptor::new("paymentId", string_schema())),
+            unsubscribe: None,
+            errors: vec![
+                INVALID_ADDRESS_CODE,
+                WRONG_NETWORK_SUSPECTED_CODE,
+                UNSUPPORTED_NETWORK_CODE,
+                INSUFFICIENT_FUNDS_CODE,
+                FEE_CAP_EXCEEDED_CODE,
+                COMPUTE_UNIT_PRICE_CAP_EXCEEDED_CODE,
+                ASSET_NOT_ACCEPTED_CODE,
+                POLICY_VIOLATION_CODE,
+                PLUGIN_DENIED_CODE,
+                RISK_CHECK_FAILED_CODE,
+                BINDING_NOT_ANCHORED_CODE,
+                SIGNER_NOT_PAIRED_CODE,
+                NODE_LOCKED_CODE,
+                STANDBY_NODE_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "getPayment",
+            summary: "transfers of a split payment and whether it settled, in full or in part",
+            params: vec![ContentDescriptor::new("paymentId", string_schema())],
+            result: Some(ContentDescriptor::new(
+                "payment",
+                schema_ref("PaymentStatus"),
+            )),
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "senderConfirm",
+            summary:
+                "sender confirms the attested tx with a signed call payload, triggering submission",
+            params: vec![
+                ContentDescriptor::new("tx", schema_ref("TxStateMachine")),
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("overrideWarnings", json!({ "type": "boolean" }))
+                },
+            ],
+            result: None,
+            unsubscribe: None,
+            errors: vec![
+                INVALID_TX_STATE_CODE,
+                SIGNATURE_MISSING_CODE,
+                ATTESTATION_EXPIRED_CODE,
+                DEADLINE_EXCEEDED_CODE,
+                PRICE_MOVED_CODE,
+                WARNING_OVERRIDE_REQUIRED_CODE,
+                POLICY_VIOLATION_CODE,
+                PLUGIN_DENIED_CODE,
+                APPROVAL_DENIED_CODE,
+                SIGNER_NOT_PAIRED_CODE,
+                NODE_LOCKED_CODE,
+                STANDBY_NODE_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "approverConfirm",
+            summary: "designated approver signs off a tx held after the sender confirmation",
+            params: vec![
+                ContentDescriptor::new("tx", schema_ref("TxStateMachine")),
+                ContentDescriptor::new("signature", bytes_schema(None)),
+            ],
+            result: None,
+            unsubscribe: None,
+            errors: vec![
+                INVALID_TX_STATE_CODE,
+                ATTESTATION_EXPIRED_CODE,
+                INVALID_PARAMS_CODE,
+                STANDBY_NODE_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "subscribeTxUpdates",
+            summary: "watch tx state machine updates",
+            params: vec![],
+            result: Some(ContentDescriptor::new("tx", schema_ref("TxStateMachine"))),
+            unsubscribe: Some("unsubscribeTxUpdates"),
+            errors: vec![],
+        },
+        MethodDescriptor {
+            name: "fetchPendingTxUpdates",
+            summary: "fetch pending tx state machines, an alternative to `subscribeTxUpdates`",
+            params: vec![],
+            result: Some(ContentDescriptor::new(
+                "txs",
+                json!({ "type": "array", "items": schema_ref("TxStateMachine") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE],
+        },
+        MethodDescriptor {
+            name: "receiverConfirm",
+            summary:
+                "receiver attests address ownership and network by signing the receiver address",
+            params: vec![ContentDescriptor::new("tx", schema_ref("TxStateMachine"))],
+            result: None,
+            unsubscribe: None,
+            errors: vec![
+                INVALID_TX_STATE_CODE,
+                SIGNATURE_MISSING_CODE,
+                ATTESTATION_EXPIRED_CODE,
+                DEADLINE_EXCEEDED_CODE,
+                RECEIVER_NOT_REGISTERED_CODE,
+                RECEIVER_OFFLINE_CODE,
+                STANDBY_NODE_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "listPendingReceives",
+            summary: "attestation requests waiting for this node's receiver, oldest first",
+            params: vec![
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("network", schema_ref("ChainSupported"))
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("minAgeSecs", json!({ "type": "integer", "minimum": 0 }))
+                },
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("maxAgeSecs", json!({ "type": "integer", "minimum": 0 }))
+                },
+            ],
+            result: Some(ContentDescriptor::new(
+                "receives",
+                json!({ "type": "array", "items": schema_ref("PendingReceive") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "confirmPendingReceives",
+            summary: "receiver confirmation of several attestation requests, each carrying its receiver signature",
+            params: vec![ContentDescriptor::new(
+                "txs",
+                json!({ "type": "array", "items": schema_ref("TxStateMachine") }),
+            )],
+            result: Some(ContentDescriptor::new(
+                "outcomes",
+                json!({ "type": "array", "items": schema_ref("BulkOutcome") }),
+            )),
+            unsubscribe: None,
+            errors: vec![STANDBY_NODE_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "rejectPendingReceives",
+            summary: "decline attestation requests, the senders see their tx as RecvAddrFailed, or as RecvRejected with the reason when one is given",
+            params: vec![
+                ContentDescriptor::new(
+                    "txNonces",
+                    json!({ "type": "array", "items": { "type": "integer", "minimum": 0 } }),
+                ),
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("reason", schema_ref("RejectReason"))
+                },
+            ],
+            result: Some(ContentDescriptor::new(
+                "outcomes",
+                json!({ "type": "array", "items": schema_ref("BulkOutcome") }),
+            )),
+            unsubscribe: None,
+            errors: vec![STANDBY_NODE_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "rejectWithCorrection",
+            summary: "reject an attestation request sent to the wrong chain or an old address, proposing the address and network to send to, signed with the key of the address the sender picked",
+            params: vec![
+                ContentDescriptor::new("txNonce", json!({ "type": "integer", "minimum": 0 })),
+                ContentDescriptor::new("reason", schema_ref("RejectReason")),
+                ContentDescriptor::new("correction", schema_ref("AddressCorrection")),
+            ],
+            result: None,
+            unsubscribe: None,
+            errors: vec![
+                INVALID_PARAMS_CODE,
+                STANDBY_NODE_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "restartWithCorrection",
+            summary: "send a rejected transfer again to the address and network the receiver proposed, returns the new tx id",
+            params: vec![ContentDescriptor::new("txId", string_schema())],
+            result: Some(ContentDescriptor::new("txId", string_schema())),
+            unsubscribe: None,
+            errors: vec![
+                UNSUPPORTED_NETWORK_CODE,
+                INSUFFICIENT_FUNDS_CODE,
+                FEE_CAP_EXCEEDED_CODE,
+                COMPUTE_UNIT_PRICE_CAP_EXCEEDED_CODE,
+                ASSET_NOT_ACCEPTED_CODE,
+                POLICY_VIOLATION_CODE,
+                PLUGIN_DENIED_CODE,
+                RISK_CHECK_FAILED_CODE,
+                BINDING_NOT_ANCHORED_CODE,
+                SIGNER_NOT_PAIRED_CODE,
+                NODE_LOCKED_CODE,
+                STANDBY_NODE_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "suggestRoutes",
+            summary: "routes of a pending transfer on the other chains both peers registered an account on and the sender balance covers",
+            params: vec![ContentDescriptor::new(
+                "txNonce",
+                json!({ "type": "integer", "minimum": 0 }),
+            )],
+            result: Some(ContentDescriptor::new(
+                "routes",
+                json!({ "type": "array", "items": schema_ref("RouteSuggestion") }),
+            )),
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "rerouteTransaction",
+            summary: "send a pending transfer again on one of its suggested routes, failing the original one, returns the new tx id",
+            params: vec![
+                ContentDescriptor::new("txNonce", json!({ "type": "integer", "minimum": 0 })),
+                ContentDescriptor::new("network", schema_ref("ChainSupported")),
+            ],
+            result: Some(ContentDescriptor::new("txId", string_schema())),
+            unsubscribe: None,
+            errors: vec![
+                INVALID_TX_STATE_CODE,
+                UNSUPPORTED_NETWORK_CODE,
+                ASSET_NOT_ACCEPTED_CODE,
+                POLICY_VIOLATION_CODE,
+                PLUGIN_DENIED_CODE,
+                RISK_CHECK_FAILED_CODE,
+                BINDING_NOT_ANCHORED_CODE,
+                SIGNER_NOT_PAIRED_CODE,
+                NODE_LOCKED_CODE,
+                STANDBY_NODE_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "listPeers",
+            summary: "list peers registered to vane discovery",
+            params: vec![],
+            result: Some(ContentDescriptor::new(
+                "peers",
+                json!({ "type": "array", "items": schema_ref("Discovery") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "addAttestation",
+            summary: "store a third party attestation of one of this node's accounts with its registry record",
+            params: vec![ContentDescriptor::new(
+                "attestation",
+                schema_ref("IdentityAttestation"),
+            )],
+            result: None,
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "listBadges",
+            summary: "verification badges of an account from the attestations of the issuers this node trusts",
+            params: vec![ContentDescriptor::new("account", string_schema())],
+            result: Some(ContentDescriptor::new(
+                "badges",
+                json!({ "type": "array", "items": schema_ref("VerificationBadge") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "setAcceptedAssets",
+            summary: "advertise the tokens one of this node's accounts accepts with its registry record, senders are refused other tokens, an empty list accepts any",
+            params: vec![
+                ContentDescriptor::new("account", string_schema()),
+                ContentDescriptor::new(
+                    "tokens",
+                    json!({ "type": "array", "items": schema_ref("Token") }),
+                ),
+            ],
+            result: None,
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "acceptedAssets",
+            summary: "tokens an address accepts as advertised by its node, empty when it accepts any",
+            params: vec![ContentDescriptor::new("address", string_schema())],
+            result: Some(ContentDescriptor::new(
+                "tokens",
+                json!({ "type": "array", "items": schema_ref("Token") }),
+            )),
+            unsubscribe: None,
+            errors: vec![INVALID_ADDRESS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "anchorCall",
+            summary: "binding registry contract call an evm account of the node sends from its wallet to anchor its binding to the node on chain",
+            params: vec![ContentDescriptor::new("account", string_schema())],
+            result: Some(ContentDescriptor::new("call", schema_ref("AnchorCall"))),
+            unsubscribe: None,
+            errors: vec![
+                INVALID_ADDRESS_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "verifyBinding",
+            summary: "binding of an account to a node as anchored in the binding registry contract, to the node the registry serves it from by default",
+            params: vec![
+                ContentDescriptor::new("account", string_schema()),
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("peerId", string_schema())
+                },
+            ],
+            result: Some(ContentDescriptor::new("anchor", schema_ref("BindingAnchor"))),
+            unsubscribe: None,
+            errors: vec![
+                INVALID_ADDRESS_CODE,
+                RECEIVER_NOT_REGISTERED_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "chainHealth",
+            summary: "reachability and latency of each supported chain rpc endpoint",
+            params: vec![],
+            result: Some(ContentDescriptor::new(
+                "health",
+                json!({ "type": "array", "items": schema_ref("ChainHealth") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE],
+        },
+        MethodDescriptor {
+            name: "peerStats",
+            summary: "rtt, request success rate, last seen and bytes exchanged of every peer since the node started",
+            params: vec![],
+            result: Some(ContentDescriptor::new(
+                "stats",
+                json!({ "type": "array", "items": schema_ref("PeerStats") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE],
+        },
+        MethodDescriptor {
+            name: "detectChains",
+            summary: "supported networks an address can live on going by its format, pick the network when one is returned",
+            params: vec![ContentDescriptor::new("address", string_schema())],
+            result: Some(ContentDescriptor::new(
+                "networks",
+                json!({ "type": "array", "items": schema_ref("ChainSupported") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE],
+        },
+        MethodDescriptor {
+            name: "getCounterparty",
+            summary: "past transfers, volumes, first and last seen, attestation response times and warnings of an address, as seen by this node",
+            params: vec![ContentDescriptor::new("address", string_schema())],
+            result: Some(ContentDescriptor::new(
+                "profile",
+                schema_ref("CounterpartyProfile"),
+            )),
+            unsubscribe: None,
+            errors: vec![INVALID_ADDRESS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "feeTiers",
+            summary: "low, medium and high fee suggestions for the next block of an evm network",
+            params: vec![ContentDescriptor::new("network", schema_ref("ChainSupported"))],
+            result: Some(ContentDescriptor::new("tiers", schema_ref("FeeTiers"))),
+            unsubscribe: None,
+            errors: vec![UNSUPPORTED_NETWORK_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "computeBudgetTiers",
+            summary: "low, medium and high compute unit prices for the next solana slots, raised while the network is congested",
+            params: vec![],
+            result: Some(ContentDescriptor::new("tiers", schema_ref("ComputeBudgetTiers"))),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "parseAmount",
+            summary: "read a human amount like 1,234.56 or 1 234,56 in a locale into the token smallest unit, english by default",
+            params: vec![
+                ContentDescriptor::new("amount", string_schema()),
+                ContentDescriptor::new("token", schema_ref("Token")),
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("locale", schema_ref("AmountLocale"))
+                },
+            ],
+            result: Some(ContentDescriptor::new("amount", schema_ref("LocalizedAmount"))),
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE],
+        },
+        MethodDescriptor {
+            name: "formatAmount",
+            summary: "render an amount in the token smallest unit with the separators of a locale, english by default",
+            params: vec![
+                ContentDescriptor::new("units", json!({ "type": "integer", "minimum": 0 })),
+                ContentDescriptor::new("token", schema_ref("Token")),
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("locale", schema_ref("AmountLocale"))
+                },
+            ],
+            result: Some(ContentDescriptor::new("amount", schema_ref("LocalizedAmount"))),
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE],
+        },
+        MethodDescriptor {
+            name: "faucet",
+            summary: "credit an account on the dev chain of a network, returns its balance; dev mode only",
+            params: vec![
+                ContentDescriptor::new("account", string_schema()),
+                ContentDescriptor::new("amount", json!({ "type": "integer", "minimum": 0 })),
+                ContentDescriptor::new("network", schema_ref("ChainSupported")),
+            ],
+            result: Some(ContentDescriptor::new(
+                "balance",
+                json!({ "type": "integer", "minimum": 0 }),
+            )),
+            unsubscribe: None,
+            errors: vec![
+                UNSUPPORTED_NETWORK_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "provisionTestAccounts",
+            summary: "generate evm test accounts registered to the node, not to discovery, funded on dev chains; refused on mainnet",
+            params: vec![
+                ContentDescriptor::new(
+                    "count",
+                    json!({ "type": "integer", "minimum": 1, "maximum": 4 }),
+                ),
+                ContentDescriptor::new("network", schema_ref("ChainSupported")),
+                ContentDescriptor::new("amount", json!({ "type": "integer", "minimum": 0 })),
+            ],
+            result: Some(ContentDescriptor::new(
+                "accounts",
+                json!({ "type": "array", "items": schema_ref("TestAccount") }),
+            )),
+            unsubscribe: None,
+            errors: vec![
+                UNSUPPORTED_NETWORK_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "approveToken",
+            summary: "approve a spender for a bounded amount of a contract token, unlimited approvals need the explicit flag",
+            params: vec![
+                ContentDescriptor::new("owner", string_schema()),
+                ContentDescriptor::new("spender", string_schema()),
+                ContentDescriptor::new("amount", json!({ "type": "integer", "minimum": 1 })),
+                ContentDescriptor::new("token", schema_ref("Token")),
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("unlimited", json!({ "type": "boolean" }))
+                },
+            ],
+            result: Some(ContentDescriptor::new("txId", string_schema())),
+            unsubscribe: None,
+            errors: vec![
+                INVALID_ADDRESS_CODE,
+                UNSUPPORTED_NETWORK_CODE,
+                POLICY_VIOLATION_CODE,
+                PLUGIN_DENIED_CODE,
+                RISK_CHECK_FAILED_CODE,
+                SIGNER_NOT_PAIRED_CODE,
+                NODE_LOCKED_CODE,
+                STANDBY_NODE_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "revokeApproval",
+            summary: "revoke an allowance by approving zero",
+            params: vec![
+                ContentDescriptor::new("owner", string_schema()),
+                ContentDescriptor::new("spender", string_schema()),
+                ContentDescriptor::new("token", schema_ref("Token")),
+            ],
+            result: Some(ContentDescriptor::new("txId", string_schema())),
+            unsubscribe: None,
+            errors: vec![
+                INVALID_ADDRESS_CODE,
+                UNSUPPORTED_NETWORK_CODE,
+                POLICY_VIOLATION_CODE,
+                PLUGIN_DENIED_CODE,
+                RISK_CHECK_FAILED_CODE,
+                SIGNER_NOT_PAIRED_CODE,
+                NODE_LOCKED_CODE,
+                STANDBY_NODE_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "listApprovals",
+            summary: "outstanding allowances granted through this node",
+            params: vec![ContentDescriptor {
+                required: false,
+                ..ContentDescriptor::new("owner", string_schema())
+            }],
+            result: Some(ContentDescriptor::new(
+                "approvals",
+                json!({ "type": "array", "items": schema_ref("Approval") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "buildPermit",
+            summary: "EIP-2612 permit for the owner to sign, bounded and expiring within a day",
+            params: vec![
+                ContentDescriptor::new("owner", string_schema()),
+                ContentDescriptor::new("spender", string_schema()),
+                ContentDescriptor::new("amount", json!({ "type": "integer", "minimum": 1 })),
+                ContentDescriptor::new("token", schema_ref("Token")),
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new(
+                        "validitySecs",
+                        json!({ "type": "integer", "minimum": 1, "maximum": 86400 }),
+                    )
+                },
+            ],
+            result: Some(ContentDescriptor::new("permit", schema_ref("PermitPayload"))),
+            unsubscribe: None,
+            errors: vec![
+                UNSUPPORTED_NETWORK_CODE,
+                INVALID_PARAMS_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "setPolicies",
+            summary: "replace the operator policies evaluated at each tx transition",
+            params: vec![ContentDescriptor::new(
+                "rules",
+                json!({ "type": "array", "items": schema_ref("PolicyRule") }),
+            )],
+            result: None,
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "listPolicies",
+            summary: "operator policies in force",
+            params: vec![],
+            result: Some(ContentDescriptor::new(
+                "rules",
+                json!({ "type": "array", "items": schema_ref("PolicyRule") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "pairingMessage",
+            summary: "one-time challenge message a signing device signs to pair an account",
+            params: vec![ContentDescriptor::new("account", string_schema())],
+            result: Some(ContentDescriptor::new("message", string_schema())),
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE],
+        },
+        MethodDescriptor {
+            name: "pairSigner",
+            summary: "pair a signing device, watch-only nodes only send for paired accounts",
+            params: vec![
+                ContentDescriptor::new("account", string_schema()),
+                ContentDescriptor::new("signature", bytes_schema(Some(65))),
+            ],
+            result: None,
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "unpairSigner",
+            summary: "remove a paired signing device",
+            params: vec![ContentDescriptor::new("account", string_schema())],
+            result: None,
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "listPairedSigners",
+            summary: "accounts of the paired signing devices",
+            params: vec![],
+            result: Some(ContentDescriptor::new(
+                "accounts",
+                json!({ "type": "array", "items": string_schema() }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "unlock",
+            summary: "unlock the send operations with the send passphrase for the unlock window",
+            params: vec![ContentDescriptor::new("passphrase", string_schema())],
+            result: Some(ContentDescriptor::new(
+                "unlockedUntil",
+                json!({ "type": "integer", "minimum": 0 }),
+            )),
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE],
+        },
+        MethodDescriptor {
+            name: "lock",
+            summary: "lock the send operations before the unlock window passed",
+            params: vec![],
+            result: None,
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE],
+        },
+        MethodDescriptor {
+            name: "peerBindingMessage",
+            summary: "message an account signs to bind itself to this node",
+            params: vec![
+                ContentDescriptor::new("account", string_schema()),
+                ContentDescriptor::new("signedAt", json!({ "type": "integer", "minimum": 0 })),
+            ],
+            result: Some(ContentDescriptor::new("message", string_schema())),
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE],
+        },
+        MethodDescriptor {
+            name: "addPeerBinding",
+            summary: "bind an account registered by this node to it, shared with connected peers",
+            params: vec![
+                ContentDescriptor::new("account", string_schema()),
+                ContentDescriptor::new("signedAt", json!({ "type": "integer", "minimum": 0 })),
+                ContentDescriptor::new("signature", bytes_schema(Some(65))),
+            ],
+            result: None,
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "listPeerBindings",
+            summary: "account to node bindings this node verified, learned over peer exchange included",
+            params: vec![],
+            result: Some(ContentDescriptor::new(
+                "bindings",
+                json!({ "type": "array", "items": schema_ref("PeerBinding") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "devicePairingCode",
+            summary: "single-use code linking another device of the user, shown as a QR code",
+            params: vec![],
+            result: Some(ContentDescriptor::new(
+                "pairingCode",
+                schema_ref("DevicePairingCode"),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE],
+        },
+        MethodDescriptor {
+            name: "pairDevice",
+            summary: "pair with the device showing a pairing code and sync with it",
+            params: vec![ContentDescriptor::new("uri", string_schema())],
+            result: Some(ContentDescriptor::new("device", schema_ref("PairedDevice"))),
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "unpairDevice",
+            summary: "stop syncing with a paired device",
+            params: vec![ContentDescriptor::new("peerId", string_schema())],
+            result: None,
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "listPairedDevices",
+            summary: "devices of the user this node syncs with",
+            params: vec![],
+            result: Some(ContentDescriptor::new(
+                "devices",
+                json!({ "type": "array", "items": schema_ref("PairedDevice") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "listDeviceTransfers",
+            summary: "pending transfers of the paired devices, as last synced",
+            params: vec![],
+            result: Some(ContentDescriptor::new(
+                "txs",
+                json!({ "type": "array", "items": schema_ref("TxStateMachine") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "rotateNodeIdentity",
+            summary: "rotate the node p2p identity, the old one answers for the grace period",
+            params: vec![ContentDescriptor {
+                required: false,
+                ..ContentDescriptor::new(
+                    "graceSecs",
+                    json!({ "type": "integer", "minimum": 0, "maximum": 604800 }),
+                )
+            }],
+            result: Some(ContentDescriptor::new(
+                "rotated",
+                schema_ref("RotatedIdentity"),
+            )),
+            unsubscribe: None,
+            errors: vec![
+                INVALID_PARAMS_CODE,
+                STANDBY_NODE_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "revocationMessage",
+            summary: "message the key of a compromised account signs to revoke it",
+            params: vec![
+                ContentDescriptor::new("account", string_schema()),
+                ContentDescriptor::new("revokedAt", json!({ "type": "integer", "minimum": 0 })),
+            ],
+            result: Some(ContentDescriptor::new("message", string_schema())),
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE],
+        },
+        MethodDescriptor {
+            name: "revokeAccount",
+            summary: "mark an account as compromised, gossiped to peers and kept in the registry",
+            params: vec![
+                ContentDescriptor::new("account", string_schema()),
+                ContentDescriptor::new("revokedAt", json!({ "type": "integer", "minimum": 0 })),
+                ContentDescriptor::new("signature", bytes_schema(Some(65))),
+            ],
+            result: None,
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "listRevocations",
+            summary: "account revocations this node verified, learned from peers included",
+            params: vec![],
+            result: Some(ContentDescriptor::new(
+                "revocations",
+                json!({ "type": "array", "items": schema_ref("AccountRevocation") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "txHistory",
+            summary: "submitted txs, both succeeded and failed, optionally only the ones whose reference or a tag is the query or whose memo contains it",
+            params: vec![ContentDescriptor {
+                required: false,
+                ..ContentDescriptor::new("query", string_schema())
+            }],
+            result: Some(ContentDescriptor::new(
+                "txs",
+                json!({ "type": "array", "items": schema_ref("DbTxStateMachine") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "txReceipt",
+            summary: "history record of a submitted tx with its explorer link, block number, effective gas price and fee paid once included",
+            params: vec![ContentDescriptor::new("txHash", string_schema())],
+            result: Some(ContentDescriptor::new("tx", schema_ref("DbTxStateMachine"))),
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "getTravelRule",
+            summary: "originator and beneficiary data attached to a transfer, opened on the sender and the receiver node",
+            params: vec![ContentDescriptor::new("txId", string_schema())],
+            result: Some(ContentDescriptor::new(
+                "travelRule",
+                json!({ "oneOf": [{ "type": "null" }, schema_ref("TravelRuleData")] }),
+            )),
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "feeStats",
+            summary: "network fees paid per network and fee tier, from the receipts of the submitted txs, with a warning per consistently overpaying tier",
+            params: vec![],
+            result: Some(ContentDescriptor::new("stats", schema_ref("FeeStats"))),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "diagnoseTx",
+            summary: "stage a transfer waits at, since when and the suggested actions, e.g. peer unreachable, awaiting the receiver or pending in the mempool",
+            params: vec![ContentDescriptor::new("txId", string_schema())],
+            result: Some(ContentDescriptor::new("diagnosis", schema_ref("TxDiagnosis"))),
+            unsubscribe: None,
+            errors: vec![INVALID_PARAMS_CODE, RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "retryTx",
+            summary: "drive a stuck transfer again from its persisted state by resending the attestation request, simulating the chain tx again or broadcasting it again",
+            params: vec![
+                ContentDescriptor::new("txId", string_schema()),
+                ContentDescriptor {
+                    required: false,
+                    ..ContentDescriptor::new("stage", schema_ref("RetryStage"))
+                },
+            ],
+            result: Some(ContentDescriptor::new("stage", schema_ref("RetryStage"))),
+            unsubscribe: None,
+            errors: vec![
+                INVALID_PARAMS_CODE,
+                NODE_LOCKED_CODE,
+                STANDBY_NODE_CODE,
+                RATE_LIMITED_CODE,
+                INTERNAL_ERROR_CODE,
+            ],
+        },
+        MethodDescriptor {
+            name: "searchHistory",
+            summary: "history records matching a counterparty address, sender name, memo, reference or tag, an amount range, a network and an outcome, newest first",
+            params: vec![ContentDescriptor::new("search", schema_ref("HistorySearch"))],
+            result: Some(ContentDescriptor::new(
+                "txs",
+                json!({ "type": "array", "items": schema_ref("DbTxStateMachine") }),
+            )),
+            unsubscribe: None,
+            errors: vec![RATE_LIMITED_CODE, INTERNAL_ERROR_CODE],
+        },
+        MethodDescriptor {
+            name: "rpc.discover",
+            summary: "returns the OpenRPC document of this node",
+            params: vec![],
+            result: Some(ContentDescriptor::new(
+                "openrpcDocument",
+                json!({ "type": "object" }),
+            )),
+            unsubscribe: None,
+            errors: vec![],
+        },
+    ]
+}
+
+pub(crate) fn schemas() -> Value {
+    json!({
+        "ChainSupported": {
+            "type": "string",
+            "enum": ["Polkadot", "Ethereum", "Bnb", "Solana"]
+        },
+        "Token": {
+            "type": "string",
+            "enum": ["Dot", "Bnb", "Sol", "Eth", "UsdtSol", "UsdcSol", "UsdtEth", "UsdcEth", "UsdtDot"]
+        },
+        "TxStatus": {
+            "type": "string",
+            "enum": [
+                "Genesis",
+                "RecvAddrConfirmed",
+                "RecvAddrConfirmationPassed",
+                "NetConfirmed",
+                "SenderConfirmed",
+                "SenderConfirmationfailed",
+                "RecvAddrFailed",
+                "FailedToSubmitTxn",
+                "TxSubmissionPassed",
+                "ReceiverNotRegistered",
+                "AwaitingSecondApproval",
+                "ReceiverIncompatible",
+                "ReceiverUnreachable",
+                "DeliveredToReceiver",
+                "AnsweredOnOtherDevice",
+                "RecvRejected",
+                "Expired",
+                "Withdrawn"
+            ]
+        },
+        "RejectReason": {
+            "type": "string",
+            "enum": ["NotMyAddress", "WrongChain", "UnexpectedAmount", "SuspectedPhishing"]
+        },
+        "Severity": {
+            "type": "string",
+            "enum": ["Low", "Medium", "High"]
+        },
+        "Warning": {
+            "type": "object",
+            "properties": {
+                "code": {
+                    "type": "string",
+                    "enum": [
+                        "BURN_ADDRESS",
+                        "CONTRACT_RECIPIENT",
+                        "CONTRACT_REJECTS_VALUE",
+                        "CROSS_CHAIN_DEPOSIT",
+                        "EXCHANGE_NOT_CREDITED",
+                        "REVOKED_ACCOUNT",
+                        "PLUGIN_WARNING",
+                        "CONFLICTING_TRANSFERS",
+                        "ROUND_AMOUNT",
+                        "EXCESS_PRECISION",
+                        "ALTERNATIVE_ROUTE",
+                        "MISSING_TOKEN_ACCOUNT",
+                        "UNVERIFIED_BROADCAST"
+                    ]
+                },
+                "severity": schema_ref("Severity"),
+                "message": string_schema()
+            },
+            "required": ["code", "severity", "message"]
+        },
+        "SubmissionRoute": {
+            "type": "string",
+            "enum": ["Public", "PrivateRelay"]
+        },
+        "TxPriority": {
+            "type": "string",
+            "enum": ["Low", "Normal", "Urgent"]
+        },
+        "NetworkEnvironment": {
+            "type": "string",
+            "enum": ["Mainnet", "Testnet"]
+        },
+        "TestAccount": {
+            "type": "object",
+            "properties": {
+                "account": string_schema(),
+                "privateKey": { "type": "string", "pattern": "^0x[0-9a-fA-F]{64}$" },
+                "network": schema_ref("ChainSupported"),
+                "balance": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["account", "privateKey", "network", "balance"]
+        },
+        "TxKind": {
+            "type": "string",
+            "enum": ["Transfer", "Approval"]
+        },
+        "Approval": {
+            "type": "object",
+            "properties": {
+                "owner": string_schema(),
+                "spender": string_schema(),
+                "token": schema_ref("Token"),
+                "network": schema_ref("ChainSupported"),
+                "amount": { "type": "integer", "minimum": 1 },
+                "txId": string_schema(),
+                "txHash": bytes_schema(Some(32))
+            },
+            "required": ["owner", "spender", "token", "network", "amount", "txId", "txHash"]
+        },
+        "AnchorCall": {
+            "type": "object",
+            "properties": {
+                "network": schema_ref("ChainSupported"),
+                "registry": string_schema(),
+                "account": string_schema(),
+                "peerId": string_schema(),
+                "binding": string_schema(),
+                "data": bytes_schema(None)
+            },
+            "required": ["network", "registry", "account", "peerId", "binding", "data"]
+        },
+        "BindingAnchor": {
+            "type": "object",
+            "properties": {
+                "network": schema_ref("ChainSupported"),
+                "registry": string_schema(),
+                "account": string_schema(),
+                "peerId": string_schema(),
+                "anchored": { "type": ["string", "null"] },
+                "verified": { "type": "boolean" }
+            },
+            "required": ["network", "registry", "account", "peerId", "verified"]
+        },
+        "PermitPayload": {
+            "type": "object",
+            "properties": {
+                "owner": string_schema(),
+                "spender": string_schema(),
+                "token": schema_ref("Token"),
+                "network": schema_ref("ChainSupported"),
+                "amount": { "type": "integer", "minimum": 1 },
+                "nonce": { "type": "integer", "minimum": 0 },
+                "deadline": { "type": "integer", "minimum": 0 },
+                "signingHash": bytes_schema(Some(32))
+            },
+            "required": [
+                "owner",
+                "spender",
+                "token",
+                "network",
+                "amount",
+                "nonce",
+                "deadline",
+                "signingHash"
+            ]
+        },
+        "PolicyAction": {
+            "type": "string",
+            "enum": ["Block", "RequireSecondApprover", "Warn"]
+        },
+        "Condition": {
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": {
+                        "type": { "const": "amountAbove" },
+                        "token": schema_ref("Token"),
+                        "amount": { "type": "integer", "minimum": 0 }
+                    },
+                    "required": ["type", "token", "amount"]
+                },
+                {
+                    "type": "object",
+                    "properties": { "type": { "enum": ["notContact", "newRecipient"] } },
+                    "required": ["type"]
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "type": { "const": "onNetworks" },
+                        "networks": { "type": "array", "items": schema_ref("ChainSupported") }
+                    },
+                    "required": ["type", "networks"]
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "type": { "const": "hoursUtc" },
+                        "from": { "type": "integer", "minimum": 0, "maximum": 23 },
+                        "to": { "type": "integer", "minimum": 0, "maximum": 24 }
+                    },
+                    "required": ["type", "from", "to"]
+                }
+            ]
+        },
+        "PolicyRule": {
+            "type": "object",
+            "properties": {
+                "id": string_schema(),
+                "description": string_schema(),
+                "conditions": { "type": "array", "items": schema_ref("Condition") },
+                "action": schema_ref("PolicyAction")
+            },
+            "required": ["id", "description", "conditions", "action"]
+        },
+        "PendingReceive": {
+            "type": "object",
+            "properties": {
+                "tx": schema_ref("TxStateMachine"),
+                "receivedAt": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["tx", "receivedAt"]
+        },
+        "BulkOutcome": {
+            "type": "object",
+            "properties": {
+                "txNonce": { "type": "integer", "minimum": 0 },
+                "error": {
+                    "oneOf": [
+                        { "type": "null" },
+                        { "type": "object", "properties": { "kind": string_schema() }, "required": ["kind"] }
+                    ]
+                }
+            },
+            "required": ["txNonce", "error"]
+        },
+        "SecondApproval": {
+            "type": "object",
+            "properties": {
+                "approver": string_schema(),
+                "signature": bytes_schema(None),
+                "approvedAt": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["approver", "signature", "approvedAt"]
+        },
+        "AddressCorrection": {
+            "type": "object",
+            "properties": {
+                "network": schema_ref("ChainSupported"),
+                "address": string_schema(),
+                "signature": bytes_schema(None)
+            },
+            "required": ["network", "address", "signature"]
+        },
+        "SenderProfile": {
+            "type": "object",
+            "properties": {
+                "displayName": string_schema(),
+                "signature": bytes_schema(None),
+                "verified": { "type": "boolean" },
+                "pastInteractions": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["displayName", "signature"]
+        },
+        "RuleOutcome": {
+            "type": "object",
+            "properties": {
+                "ruleId": string_schema(),
+                "action": schema_ref("PolicyAction"),
+                "description": string_schema()
+            },
+            "required": ["ruleId", "action", "description"]
+        },
+        "RiskAssessment": {
+            "type": "object",
+            "properties": {
+                "provider": string_schema(),
+                "address": string_schema(),
+                "score": { "type": "integer", "minimum": 0, "maximum": 100 },
+                "labels": { "type": "array", "items": string_schema() },
+                "jurisdiction": { "type": ["string", "null"] },
+                "assessedAt": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["provider", "address", "score", "labels", "assessedAt"]
+        },
+        "FiatQuote": {
+            "type": "object",
+            "properties": {
+                "usdCents": { "type": "integer", "minimum": 1 },
+                "lockedPrice": { "type": "integer", "minimum": 1 },
+                "maxSlippageBps": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["usdCents", "lockedPrice", "maxSlippageBps"]
+        },
+        "Sponsorship": {
+            "type": "object",
+            "properties": {
+                "paymaster": string_schema(),
+                "sponsor": { "type": ["string", "null"] },
+                "maxGasCost": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["paymaster", "maxGasCost"]
+        },
+        "DurableNonce": {
+            "type": "object",
+            "properties": {
+                "nonceAccount": string_schema(),
+                "authority": string_schema(),
+                "nonce": string_schema()
+            },
+            "required": ["nonceAccount", "authority", "nonce"]
+        },
+        "TokenAccountCreation": {
+            "type": "object",
+            "properties": {
+                "tokenAccount": string_schema(),
+                "mint": string_schema(),
+                "rent": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["tokenAccount", "mint", "rent"]
+        },
+        "RouteSuggestion": {
+            "type": "object",
+            "properties": {
+                "network": schema_ref("ChainSupported"),
+                "token": schema_ref("Token"),
+                "sender": string_schema(),
+                "receiver": string_schema(),
+                "amount": { "type": "integer", "minimum": 0 },
+                "balance": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["network", "token", "sender", "receiver", "amount", "balance"]
+        },
+        "FeeTier": {
+            "type": "string",
+            "enum": ["Low", "Medium", "High"]
+        },
+        "FeePreference": {
+            "type": "object",
+            "properties": {
+                "tier": schema_ref("FeeTier"),
+                "maxFeePerGas": { "type": ["integer", "null"], "minimum": 1 }
+            }
+        },
+        "FeeEstimate": {
+            "type": "object",
+            "properties": {
+                "maxFeePerGas": { "type": "integer", "minimum": 0 },
+                "maxPriorityFeePerGas": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["maxFeePerGas", "maxPriorityFeePerGas"]
+        },
+        "AmountLocale": {
+            "oneOf": [
+                { "type": "string", "description": "BCP 47 locale tag, e.g. de or de-CH" },
+                {
+                    "type": "object",
+                    "properties": {
+                        "decimal": { "type": "string", "enum": [".", ","] },
+                        "group": { "type": ["string", "null"], "maxLength": 1 }
+                    },
+                    "required": ["decimal"]
+                }
+            ]
+        },
+        "LocalizedAmount": {
+            "type": "object",
+            "properties": {
+                "token": schema_ref("Token"),
+                "units": { "type": "integer", "minimum": 0 },
+                "display": string_schema(),
+                "symbol": string_schema()
+            },
+            "required": ["token", "units", "display", "symbol"]
+        },
+        "FeeTiers": {
+            "type": "object",
+            "properties": {
+                "baseFeePerGas": { "type": "integer", "minimum": 0 },
+                "peakBaseFeePerGas": { "type": "integer", "minimum": 0 },
+                "low": schema_ref("FeeEstimate"),
+                "medium": schema_ref("FeeEstimate"),
+                "high": schema_ref("FeeEstimate")
+            },
+            "required": ["baseFeePerGas", "peakBaseFeePerGas", "low", "medium", "high"]
+        },
+        "ComputeBudgetTiers": {
+            "type": "object",
+            "properties": {
+                "recentComputeUnitPrice": { "type": "integer", "minimum": 0 },
+                "congested": { "type": "boolean" },
+                "low": { "type": "integer", "minimum": 0 },
+                "medium": { "type": "integer", "minimum": 0 },
+                "high": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["recentComputeUnitPrice", "congested", "low", "medium", "high"]
+        },
+        "ChainHealth": {
+            "type": "object",
+            "properties": {
+                "network": schema_ref("ChainSupported"),
+                "environment": schema_ref("NetworkEnvironment"),
+                "reachable": { "type": "boolean" },
+                "latencyMs": { "type": "integer", "minimum": 0 },
+                "error": { "type": ["string", "null"] },
+                "breaker": schema_ref("BreakerState")
+            },
+            "required": ["network", "environment", "reachable", "latencyMs"]
+        },
+        "BreakerState": {
+            "type": "string",
+            "enum": ["Closed", "Open", "HalfOpen"]
+        },
+        "PeerBinding": {
+            "type": "object",
+            "properties": {
+                "account": string_schema(),
+                "peerId": string_schema(),
+                "multiAddr": string_schema(),
+                "signedAt": { "type": "integer", "minimum": 0 },
+                "signature": bytes_schema(Some(65))
+            },
+            "required": ["account", "peerId", "multiAddr", "signedAt", "signature"]
+        },
+        "DevicePairingCode": {
+            "type": "object",
+            "properties": {
+                "uri": string_schema(),
+                "code": string_schema(),
+                "expiresAt": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["uri", "code", "expiresAt"]
+        },
+        "PairedDevice": {
+            "type": "object",
+            "properties": {
+                "peerId": string_schema(),
+                "multiAddr": string_schema(),
+                "encryptionKey": bytes_schema(Some(32)),
+                "pairedAt": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["peerId", "multiAddr", "encryptionKey", "pairedAt"]
+        },
+        "IdentityRotation": {
+            "type": "object",
+            "properties": {
+                "oldPeerId": string_schema(),
+                "newPeerId": string_schema(),
+                "newMultiAddr": string_schema(),
+                "oldPublicKey": bytes_schema(None),
+                "newPublicKey": bytes_schema(None),
+                "rotatedAt": { "type": "integer", "minimum": 0 },
+                "graceUntil": { "type": "integer", "minimum": 0 },
+                "oldSignature": bytes_schema(None),
+                "newSignature": bytes_schema(None)
+            },
+            "required": [
+                "oldPeerId",
+                "newPeerId",
+                "newMultiAddr",
+                "oldPublicKey",
+                "newPublicKey",
+                "rotatedAt",
+                "graceUntil",
+                "oldSignature",
+                "newSignature"
+            ]
+        },
+        "RotatedIdentity": {
+            "type": "object",
+            "properties": {
+                "rotation": schema_ref("IdentityRotation"),
+                "rebindAccounts": { "type": "array", "items": string_schema() }
+            },
+            "required": ["rotation", "rebindAccounts"]
+        },
+        "AccountRevocation": {
+            "type": "object",
+            "properties": {
+                "account": string_schema(),
+                "revokedAt": { "type": "integer", "minimum": 0 },
+                "signature": bytes_schema(Some(65))
+            },
+            "required": ["account", "revokedAt", "signature"]
+        },
+        "PeerStats": {
+            "type": "object",
+            "properties": {
+                "peerId": string_schema(),
+                "connected": { "type": "boolean" },
+                "rttMs": { "type": ["integer", "null"], "minimum": 0 },
+                "requestsSent": { "type": "integer", "minimum": 0 },
+                "requestsFailed": { "type": "integer", "minimum": 0 },
+                "responsesReceived": { "type": "integer", "minimum": 0 },
+                "requestsReceived": { "type": "integer", "minimum": 0 },
+                "successRate": { "type": ["number", "null"], "minimum": 0, "maximum": 1 },
+                "lastSeen": { "type": ["integer", "null"], "minimum": 0 },
+                "bytesSent": { "type": "integer", "minimum": 0 },
+                "bytesReceived": { "type": "integer", "minimum": 0 },
+                "agentVersion": { "type": ["string", "null"] },
+                "protocolVersion": { "type": ["string", "null"] }
+            },
+            "required": [
+                "peerId",
+                "connected",
+                "requestsSent",
+                "requestsFailed",
+                "responsesReceived",
+                "requestsReceived",
+                "bytesSent",
+                "bytesReceived"
+            ]
+        },
+        "DbTxStateMachine": {
+            "type": "object",
+            "properties": {
+                "tx_hash": bytes_schema(None),
+                "amount": { "type": "integer", "minimum": 0 },
+                "network": schema_ref("ChainSupported"),
+                "success": { "type": "boolean" },
+                "memo": { "type": ["string", "null"] },
+                "reference": { "type": ["string", "null"] },
+                "tags": { "type": "array", "items": string_schema() },
+                "sender_address": string_schema(),
+                "receiver_address": string_schema(),
+                "sender_name": { "type": ["string", "null"] },
+                "explorer_url": { "type": ["string", "null"] },
+                "receipt": {
+                    "oneOf": [{ "type": "null" }, schema_ref("ChainReceipt")]
+                },
+                "fee_tier": {
+                    "oneOf": [{ "type": "null" }, schema_ref("FeeTier")]
+                },
+                "low_tier_fee": { "type": ["integer", "null"], "minimum": 0 },
+                "reject_reason": {
+                    "oneOf": [{ "type": "null" }, schema_ref("RejectReason")]
+                },
+                "travel_rule": {
+                    "oneOf": [{ "type": "null" }, schema_ref("TravelRuleData")]
+                }
+            },
+            "required": ["tx_hash", "amount", "network", "success"]
+        },
+        "TravelRuleData": {
+            "type": "object",
+            "properties": {
+                "originator": schema_ref("TravelRuleParty"),
+                "beneficiary": schema_ref("TravelRuleParty"),
+                "originatingVasp": { "oneOf": [{ "type": "null" }, schema_ref("Vasp")] },
+                "beneficiaryVasp": { "oneOf": [{ "type": "null" }, schema_ref("Vasp")] }
+            },
+            "required": ["originator", "beneficiary"]
+        },
+        "TravelRuleParty": {
+            "type": "object",
+            "properties": {
+                "name": string_schema(),
+                "account": string_schema(),
+                "geographicAddress": { "type": ["string", "null"] },
+                "nationalIdentification": { "type": ["string", "null"] },
+                "dateOfBirth": { "type": ["string", "null"], "format": "date" },
+                "countryOfResidence": {
+                    "type": ["string", "null"],
+                    "minLength": 2,
+                    "maxLength": 2
+                }
+            },
+            "required": ["name", "account"]
+        },
+        "Vasp": {
+            "type": "object",
+            "properties": {
+                "name": string_schema(),
+                "lei": { "type": ["string", "null"] }
+            },
+            "required": ["name"]
+        },
+        "CounterpartyProfile": {
+            "type": "object",
+            "properties": {
+                "address": string_schema(),
+                "sent": { "type": "integer", "minimum": 0 },
+                "received": { "type": "integer", "minimum": 0 },
+                "succeeded": { "type": "integer", "minimum": 0 },
+                "failed": { "type": "integer", "minimum": 0 },
+                "volumes": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "token": schema_ref("Token"),
+                            "amount": { "type": "integer", "minimum": 0 }
+                        },
+                        "required": ["token", "amount"]
+                    }
+                },
+                "firstSeen": { "type": ["integer", "null"] },
+                "lastSeen": { "type": ["integer", "null"] },
+                "attestations": { "type": "integer", "minimum": 0 },
+                "avgAttestationSecs": { "type": ["integer", "null"] },
+                "maxAttestationSecs": { "type": ["integer", "null"] },
+                "warnings": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "code": string_schema(),
+                            "severity": schema_ref("Severity"),
+                            "count": { "type": "integer", "minimum": 0 },
+                            "lastSeen": { "type": "integer" }
+                        },
+                        "required": ["code", "severity", "count", "lastSeen"]
+                    }
+                }
+            },
+            "required": ["address", "sent", "received", "succeeded", "failed", "attestations"]
+        },
+        "ChainReceipt": {
+            "type": "object",
+            "properties": {
+                "blockNumber": { "type": "integer", "minimum": 0 },
+                "effectiveGasPrice": { "type": ["integer", "null"], "minimum": 0 },
+                "feePaid": { "type": "integer", "minimum": 0 },
+                "success": { "type": "boolean" }
+            },
+            "required": ["blockNumber", "feePaid", "success"]
+        },
+        "TxDiagnosis": {
+            "type": "object",
+            "properties": {
+                "txId": string_schema(),
+                "stage": {
+                    "enum": [
+                        "AwaitingDelivery",
+                        "PeerUnreachable",
+                        "AwaitingReceiver",
+                        "AwaitingYourConfirmation",
+                        "AwaitingSenderSignature",
+                        "AwaitingSecondApproval",
+                        "Submitting",
+                        "MempoolPending",
+                        "ProviderError",
+                        "Included",
+                        "Reverted",
+                        "Ended"
+                    ]
+                },
+                "stuck": { "type": "boolean" },
+                "since": { "type": "integer", "minimum": 0 },
+                "waitingSecs": { "type": "integer", "minimum": 0 },
+                "detail": string_schema(),
+                "actions": { "type": "array", "items": string_schema() },
+                "explorerUrl": { "type": ["string", "null"] }
+            },
+            "required": ["txId", "stage", "stuck", "since", "waitingSecs", "detail", "actions"]
+        },
+        "RetryStage": {
+            "type": "string",
+            "enum": ["Attestation", "Simulation", "Broadcast"]
+        },
+        "PaymentPart": {
+            "type": "object",
+            "properties": {
+                "receiver": string_schema(),
+                "amount": { "type": "integer", "minimum": 1 },
+                "token": schema_ref("Token"),
+                "memo": { "type": ["string", "null"] }
+            },
+            "required": ["receiver", "amount", "token"]
+        },
+        "PaymentState": {
+            "type": "string",
+            "enum": ["Pending", "Settled", "PartiallySettled", "Failed"]
+        },
+        "PaymentStatus": {
+            "type": "object",
+            "properties": {
+                "paymentId": string_schema(),
+                "children": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "txId": string_schema(),
+                            "state": schema_ref("PaymentState"),
+                            "tx": schema_ref("TxStateMachine")
+                        },
+                        "required": ["txId", "state", "tx"]
+                    }
+                },
+                "state": schema_ref("PaymentState")
+            },
+            "required": ["paymentId", "children", "state"]
+        },
+        "FeeStats": {
+            "type": "object",
+            "properties": {
+                "networks": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "network": schema_ref("ChainSupported"),
+                            "transfers": { "type": "integer", "minimum": 0 },
+                            "totalFee": { "type": "integer", "minimum": 0 },
+                            "averageFee": { "type": "integer", "minimum": 0 },
+                            "tiers": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "tier": schema_ref("FeeTier"),
+                                        "transfers": { "type": "integer", "minimum": 0 },
+                                        "totalFee": { "type": "integer", "minimum": 0 },
+                                        "averageFee": { "type": "integer", "minimum": 0 },
+                                        "overpaid": { "type": "integer", "minimum": 0 },
+                                        "overpayStreak": { "type": "integer", "minimum": 0 }
+                                    }
+                                }
+                            }
+                        },
+                        "required": ["network", "transfers", "totalFee", "averageFee", "tiers"]
+                    }
+                },
+                "warnings": { "type": "array", "items": schema_ref("Warning") }
+            },
+            "required": ["networks", "warnings"]
+        },
+        "HistorySearch": {
+            "type": "object",
+            "properties": {
+                "text": { "type": ["string", "null"] },
+                "network": schema_ref("ChainSupported"),
+                "minAmount": { "type": ["integer", "null"], "minimum": 0 },
+                "maxAmount": { "type": ["integer", "null"], "minimum": 0 },
+                "success": { "type": ["boolean", "null"] },
+                "limit": { "type": ["integer", "null"], "minimum": 0 }
+            }
+        },
+        "Discovery": {
+            "type": "object",
+            "properties": {
+                "id": string_schema(),
+                "peer_id": { "type": ["string", "null"] },
+                "multi_addr": { "type": ["string", "null"] },
+                "account_ids": { "type": "array", "items": string_schema() },
+                "attestations": { "type": "array", "items": schema_ref("IdentityAttestation") },
+                "revocations": { "type": "array", "items": schema_ref("AccountRevocation") },
+                "accepted_assets": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "account": string_schema(),
+                            "tokens": { "type": "array", "items": schema_ref("Token") }
+                        },
+                        "required": ["account", "tokens"]
+                    }
+                }
+            },
+            "required": ["id", "account_ids"]
+        },
+        "IdentityAttestation": {
+            "type": "object",
+            "properties": {
+                "account": string_schema(),
+                "issuer": string_schema(),
+                "claim": string_schema(),
+                "issuedAt": { "type": "integer", "minimum": 0 },
+                "expiresAt": { "type": ["integer", "null"], "minimum": 0 },
+                "signature": bytes_schema(None)
+            },
+            "required": ["account", "issuer", "claim", "issuedAt", "signature"]
+        },
+        "VerificationBadge": {
+            "type": "object",
+            "properties": {
+                "issuer": string_schema(),
+                "issuerName": string_schema(),
+                "claim": string_schema(),
+                "expiresAt": { "type": ["integer", "null"], "minimum": 0 }
+            },
+            "required": ["issuer", "issuerName", "claim"]
+        },
+        "TxStateMachine": {
+            "type": "object",
+            "properties": {
+                "senderAddress": string_schema(),
+                "receiverAddress": string_schema(),
+                "multiId": { "type": "string", "pattern": "^0x[0-9a-fA-F]{64}$" },
+                "multiIdVersion": { "type": "integer", "minimum": 0, "maximum": 255 },
+                "recvSignature": { "oneOf": [bytes_schema(None), { "type": "null" }] },
+                "network": schema_ref("ChainSupported"),
+                "status": schema_ref("TxStatus"),
+                "statusDetail": { "type": ["string", "null"] },
+                "amount": { "type": "string", "pattern": "^[0-9]+$" },
+                "signedCallPayload": { "oneOf": [bytes_schema(None), { "type": "null" }] },
+                "callPayload": { "oneOf": [bytes_schema(Some(32)), { "type": "null" }] },
+                "inboundReqId": { "type": ["string", "null"] },
+                "outboundReqId": { "type": ["string", "null"] },
+                "txNonce": { "type": "integer", "minimum": 0 },
+                "feePreference": { "oneOf": [schema_ref("FeePreference"), { "type": "null" }] },
+                "submissionRoute": schema_ref("SubmissionRoute"),
+                "token": { "oneOf": [schema_ref("Token"), { "type": "null" }] },
+                "callData": { "oneOf": [bytes_schema(None), { "type": "null" }] },
+                "intent": { "type": ["string", "null"] },
+                "warnings": { "type": "array", "items": schema_ref("Warning") },
+                "kind": schema_ref("TxKind"),
+                "memo": { "type": ["string", "null"] },
+                "reference": { "type": ["string", "null"] },
+                "tags": { "type": "array", "items": string_schema() },
+                "policyOutcomes": { "type": "array", "items": schema_ref("RuleOutcome") },
+                "secondApproval": { "oneOf": [schema_ref("SecondApproval"), { "type": "null" }] },
+                "senderProfile": { "oneOf": [schema_ref("SenderProfile"), { "type": "null" }] },
+                "receiverBadges": { "type": "array", "items": schema_ref("VerificationBadge") },
+                "senderBadges": { "type": "array", "items": schema_ref("VerificationBadge") },
+                "environment": schema_ref("NetworkEnvironment"),
+                "addressCorrection": {
+                    "oneOf": [schema_ref("AddressCorrection"), { "type": "null" }]
+                },
+                "acceptedAmount": { "type": ["string", "null"], "pattern": "^[0-9]+$" },
+                "parentPayment": { "type": ["string", "null"] },
+                "riskAssessment": {
+                    "oneOf": [schema_ref("RiskAssessment"), { "type": "null" }]
+                },
+                "deadline": { "type": ["integer", "null"], "minimum": 0 },
+                "priority": schema_ref("TxPriority"),
+                "fiatQuote": {
+                    "oneOf": [schema_ref("FiatQuote"), { "type": "null" }]
+                },
+                "sponsorship": {
+                    "oneOf": [schema_ref("Sponsorship"), { "type": "null" }]
+                },
+                "durableNonce": {
+                    "oneOf": [schema_ref("DurableNonce"), { "type": "null" }]
+                },
+                "receiverTokenAccount": {
+                    "oneOf": [schema_ref("TokenAccountCreation"), { "type": "null" }]
+                }
+            },
+            "required": [
+                "senderAddress",
+                "receiverAddress",
+                "multiId",
+                "network",
+                "status",
+                "amount",
+                "txNonce"
+            ]
+        }
+    })
+}
+
+fn errors() -> Value {
+    let errors = [
+        (INVALID_ADDRESS_CODE, "INVALID_ADDRESS"),
+        (WRONG_NETWORK_SUSPECTED_CODE, "WRONG_NETWORK_SUSPECTED"),
+        (UNSUPPORTED_NETWORK_CODE, "UNSUPPORTED_NETWORK"),
+        (ENVIRONMENT_MISMATCH_CODE, "ENVIRONMENT_MISMATCH"),
+        (AMBIGUOUS_NETWORK_CODE, "AMBIGUOUS_NETWORK"),
+        (ASSET_NOT_ACCEPTED_CODE, "ASSET_NOT_ACCEPTED"),
+        (INSUFFICIENT_FUNDS_CODE, "INSUFFICIENT_FUNDS"),
+        (RECEIVER_NOT_REGISTERED_CODE, "RECEIVER_NOT_REGISTERED"),
+        (RECEIVER_OFFLINE_CODE, "RECEIVER_OFFLINE"),
+        (ATTESTATION_EXPIRED_CODE, "ATTESTATION_EXPIRED"),
+        (INVALID_TX_STATE_CODE, "INVALID_TX_STATE"),
+        (SIGNATURE_MISSING_CODE, "SIGNATURE_MISSING"),
+        (RATE_LIMITED_CODE, "RATE_LIMITED"),
+        (FEE_CAP_EXCEEDED_CODE, "FEE_CAP_EXCEEDED"),
+        (WARNING_OVERRIDE_REQUIRED_CODE, "WARNING_OVERRIDE_REQUIRED"),
+        (POLICY_VIOLATION_CODE, "POLICY_VIOLATION"),
+        (PLUGIN_DENIED_CODE, "PLUGIN_DENIED"),
+        (APPROVAL_DENIED_CODE, "APPROVAL_DENIED"),
+        (RISK_CHECK_FAILED_CODE, "RISK_CHECK_FAILED"),
+        (DEADLINE_EXCEEDED_CODE, "DEADLINE_EXCEEDED"),
+        (PRICE_MOVED_CODE, "PRICE_MOVED"),
+        (BINDING_NOT_ANCHORED_CODE, "BINDING_NOT_ANCHORED"),
+        (
+            COMPUTE_UNIT_PRICE_CAP_EXCEEDED_CODE,
+            "COMPUTE_UNIT_PRICE_CAP_EXCEEDED",
+        ),
+        (ACCESS_DENIED_CODE, "ACCESS_DENIED"),
+        (SIGNER_NOT_PAIRED_CODE, "SIGNER_NOT_PAIRED"),
+        (NODE_LOCKED_CODE, "NODE_LOCKED"),
+        (STANDBY_NODE_CODE, "STANDBY_NODE"),
+        (INVALID_PARAMS_CODE, "INVALID_PARAMS"),
+        (INTERNAL_ERROR_CODE, "INTERNAL"),
+    ];
+    let mut components = serde_json::Map::new();
+    for (code, kind) in errors {
+        components.insert(
+            code.to_string(),
+            json!({
+                "code": code,
+                "message": kind,
+                "data": { "type": "object", "properties": { "kind": { "const": kind } } }
+            }),
+        );
+    }
+    Value::Object(components)
+}
+
+/// build the OpenRPC document describing the node api
+pub fn openrpc_document() -> Value {
+    document(rpc_methods())
+}
+
+/// build the OpenRPC document of the methods `registered` on the rpc module. a registered method
+/// missing from `rpc_methods` is listed by its name so the served document never leaves it out
+pub fn registered_document(registered: impl IntoIterator<Item = &'static str>) -> Value {
+    let mut registered = registered.into_iter().collect::<Vec<_>>();
+    let described = rpc_methods();
+    // unsubscribe methods are part of the description of their subscription
+    registered.retain(|name| !described.iter().any(|m| m.unsubscribe == Some(*name)));
+    let undocumented = registered
+        .iter()
+        .filter(|name| !described.iter().any(|m| m.name == **name))
+        .map(|name| MethodDescriptor::undocumented(name))
+        .collect::<Vec<_>>();
+    let mut methods = described
+        .into_iter()
+        .filter(|m| registered.contains(&m.name))
+        .collect::<Vec<_>>();
+    methods.extend(undocumented);
+    document(methods)
+}
+
+fn document(methods: Vec<MethodDescriptor>) -> Value {
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": "vane_web3 node",
+            "description": "risk-free transaction sending, receiver attestation and network confirmation",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "methods": methods.iter().map(|m| m.to_json()).collect::<Vec<Value>>(),
+        "components": {
+            "schemas": schemas(),
+            "errors": errors(),
+        }
+    })
+}
@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Error};
 use core::pin::Pin;
 use core::str::FromStr;
-use log::{debug, error, info, trace};
+use log::{debug, error, info, trace, warn};
 use std::collections::{HashMap, VecDeque};
 use std::hash::{DefaultHasher, Hash, Hasher};
 use std::io;
@@ -9,17 +9,31 @@ use std::sync::Arc;
 use std::time::Duration;
 // peer discovery
 // app to app communication (i.e sending the tx to be verified by the receiver) and back
-use crate::rpc::Airtable;
-use codec::Encode;
+use crate::device_sync::{DeviceSync, SYNC_INTERVAL, SYNC_PROTOCOL};
+use crate::discovery::PeerDiscovery;
+use crate::locks::timed_lock;
+use crate::peer_exchange::{PeerExchange, PEX_PROTOCOL};
+use crate::peer_metrics::PeerMetrics;
+use crate::receipts::{Receipts, RECEIPT_PROTOCOL};
+use crate::relay::{Relay, RELAY_PROTOCOL, RELAY_TIMEOUT};
+use crate::rotation::{
+    rotated_multi_addr, sign_rotation, IdentityRotations, NodeIdentity, ROTATION_PROTOCOL,
+};
 use db::DbWorker;
 use libp2p::futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, Stream};
+use libp2p::multiaddr::Protocol;
 use libp2p::request_response::{Behaviour, Event, InboundRequestId, Message, OutboundRequestId};
 use libp2p::request_response::{Codec, ProtocolSupport, ResponseChannel};
-use libp2p::swarm::SwarmEvent;
+use libp2p::swarm::dial_opts::{DialOpts, PeerCondition};
+use libp2p::swarm::{NetworkBehaviour, SwarmEvent};
+use libp2p::{identify, ping};
 use libp2p::{Multiaddr, PeerId, Swarm, SwarmBuilder};
 use local_ip_address::local_ip;
 use primitives::data_structure::{AirtableRequestBody, Fields, HashId, PeerRecord};
 use primitives::data_structure::{NetworkCommand, SwarmMessage, TxStateMachine};
+use primitives::envelope;
+use primitives::identity_rotation::IdentityRotation;
+use primitives::relay::SEALED_REQUEST_PREFIX;
 use sp_core::H256;
 use tokio::select;
 use tokio::sync::mpsc::{Receiver, Sender};
@@ -177,11 +191,137 @@ impl Codec for GenericCodec {
 
 type BlockStream<T> = Pin<Box<dyn Stream<Item = Result<T, anyhow::Error>> + Send>>;
 type BlockStreamRes<T> = Result<BlockStream<T>, anyhow::Error>;
+type DialReply = libp2p::futures::channel::oneshot::Sender<Result<(), String>>;
+
+/// vane protocol spoken over request response and announced over identify, nodes of another major
+/// version can not decode each other's messages while minor versions only append fields, decoded
+/// across versions through the wire version of their envelopes
+pub const PROTOCOL_VERSION: &str = "/vane-web3/1.0.0";
+/// protocol of the dev mode nodes, incompatible with `PROTOCOL_VERSION` so they never exchange
+/// requests with the nodes moving real funds
+pub const DEV_PROTOCOL_VERSION: &str = "/vane-web3-dev/1.0.0";
+/// node implementation and release announced over identify
+pub const AGENT_VERSION: &str = concat!("vane/", env!("CARGO_PKG_VERSION"));
+/// how long a sender waits for the receiver node connection before failing the tx
+pub const DIAL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// how dialing a peer ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DialOutcome {
+    Connected,
+    /// the dial failed, with the connection error
+    Failed(String),
+    /// no connection nor dial error within the timeout
+    TimedOut(Duration),
+}
+
+impl DialOutcome {
+    /// why the peer is unreachable, `None` once connected
+    pub fn failure(&self, peer_id: &PeerId) -> Option<String> {
+        match self {
+            DialOutcome::Connected => None,
+            DialOutcome::Failed(error) => Some(format!("failed to dial {peer_id}: {error}")),
+            DialOutcome::TimedOut(timeout) => {
+                Some(format!("no connection to {peer_id} within {timeout:?}"))
+            }
+        }
+    }
+}
+
+/// whether a peer announcing `protocol_version` over identify speaks the major vane protocol of
+/// `local`, in the same namespace
+pub fn is_compatible(local: &str, protocol_version: &str) -> bool {
+    fn major(version: &str) -> Option<(&str, &str)> {
+        let (namespace, version) = version.strip_prefix('/')?.split_once('/')?;
+        let major = version
+            .split('.')
+            .next()
+            .filter(|major| !major.is_empty())?;
+        Some((namespace, major))
+    }
+    major(protocol_version).is_some() && major(protocol_version) == major(local)
+}
+
+/// attestation requests and responses, along with libp2p ping for the round trip time of every peer
+/// and identify exchanging agent and protocol versions at connect time, peer exchange then shares
+/// the account to node bindings with identified peers. the relay protocol carries the sealed
+/// requests to mobile nodes through their always-on node, receipts acknowledge the requests,
+/// device sync pairs the devices of a user and syncs their records and identity rotation announces
+/// a new node identity to its contacts
+#[derive(NetworkBehaviour)]
+pub struct VaneBehaviour {
+    pub request_response: Behaviour<GenericCodec>,
+    pub ping: ping::Behaviour,
+    pub identify: identify::Behaviour,
+    pub peer_exchange: Behaviour<GenericCodec>,
+    pub relay: Behaviour<GenericCodec>,
+    pub receipts: Behaviour<GenericCodec>,
+    pub device_sync: Behaviour<GenericCodec>,
+    pub identity_rotation: Behaviour<GenericCodec>,
+}
+
+/// swarm of `keypair` speaking the vane protocols, requests go over `protocol_version`
+fn build_swarm(
+    keypair: libp2p::identity::Keypair,
+    protocol_version: &'static str,
+) -> Result<Swarm<VaneBehaviour>, Error> {
+    let request_response_config = libp2p::request_response::Config::default()
+        .with_request_timeout(tokio::time::Duration::from_secs(600)); // 10 minutes waiting time for a response
+
+    let transport_tcp = libp2p::tcp::Config::new().nodelay(true).port_reuse(true);
+
+    let swarm = SwarmBuilder::with_existing_identity(keypair)
+        .with_tokio()
+        .with_tcp(
+            transport_tcp,
+            libp2p::tls::Config::new,
+            libp2p::yamux::Config::default,
+        )?
+        .with_behaviour(|key| VaneBehaviour {
+            request_response: Behaviour::new(
+                vec![(protocol_version, ProtocolSupport::Full)].into_iter(),
+                request_response_config,
+            ),
+            ping: ping::Behaviour::new(ping::Config::new()),
+            identify: identify::Behaviour::new(
+                identify::Config::new(protocol_version.to_string(), key.public())
+                    .with_agent_version(AGENT_VERSION.to_string()),
+            ),
+            peer_exchange: Behaviour::new(
+                vec![(PEX_PROTOCOL, ProtocolSupport::Full)].into_iter(),
+                libp2p::request_response::Config::default(),
+            ),
+            relay: Behaviour::new(
+                vec![(RELAY_PROTOCOL, ProtocolSupport::Full)].into_iter(),
+                libp2p::request_response::Config::default().with_request_timeout(RELAY_TIMEOUT),
+            ),
+            receipts: Behaviour::new(
+                vec![(RECEIPT_PROTOCOL, ProtocolSupport::Full)].into_iter(),
+                libp2p::request_response::Config::default(),
+            ),
+            device_sync: Behaviour::new(
+                vec![(SYNC_PROTOCOL, ProtocolSupport::Full)].into_iter(),
+                libp2p::request_response::Config::default(),
+            ),
+            identity_rotation: Behaviour::new(
+                vec![(ROTATION_PROTOCOL, ProtocolSupport::Full)].into_iter(),
+                libp2p::request_response::Config::default(),
+            ),
+        })?
+        .with_swarm_config(|cfg| {
+            cfg.with_idle_connection_timeout(tokio::time::Duration::from_secs(300))
+        })
+        .build();
+    Ok(swarm)
+}
 
 #[derive(Clone)]
 pub struct P2pWorker {
+    // identity the node started with, the relay, peer exchange and device sync stay on it
     pub node_id: PeerId,
-    pub swarm: Arc<Mutex<Swarm<Behaviour<GenericCodec>>>>,
+    // vane protocol of the node, the dev protocol in dev mode
+    pub protocol_version: &'static str,
+    pub swarm: Arc<Mutex<Swarm<VaneBehaviour>>>,
     pub url: Multiaddr,
     // for receiving network commands
     pub p2p_command_recv: Arc<Mutex<Receiver<NetworkCommand>>>,
@@ -189,17 +329,30 @@ pub struct P2pWorker {
     pub pending_request: Arc<Mutex<HashMap<u64, ResponseChannel<Result<Vec<u8>, Error>>>>>,
     // for storing current ongoing request data
     pub current_req: VecDeque<SwarmMessage>,
+    // rtt, requests and bytes exchanged per peer
+    pub peer_metrics: PeerMetrics,
+    pub peer_exchange: PeerExchange,
+    // requests relayed to and from mobile nodes
+    pub relay: Relay,
+    // receipts of the requests sent by this node
+    pub receipts: Receipts,
+    // pairing and sync with the other devices of the user
+    pub device_sync: DeviceSync,
+    // current identity, moves on every rotation
+    pub identity: NodeIdentity,
+    pub rotations: IdentityRotations,
 }
 
 impl P2pWorker {
-    /// generate new ed25519 keypair for node identity and register the peer record in  the db
+    /// register the node identity keypair as the peer record in the db and discovery
     pub async fn new(
-        airtable_client: Arc<Mutex<Airtable>>,
+        discovery: Arc<dyn PeerDiscovery>,
         db_worker: Arc<Mutex<DbWorker>>,
         port: u16,
         command_recv_channel: Receiver<NetworkCommand>,
+        self_peer_id: libp2p::identity::Keypair,
+        protocol_version: &'static str,
     ) -> Result<Self, Error> {
-        let self_peer_id = libp2p::identity::Keypair::generate_ed25519();
         let peer_id = self_peer_id.public().to_peer_id().to_base58();
         let mut p2p_url = String::new();
 
@@ -230,12 +383,11 @@ impl P2pWorker {
 
         let field: Fields = user_peer_id.clone().into();
         let req_body = AirtableRequestBody::new(field);
-        let record_data = airtable_client.lock().await.create_peer(req_body).await?;
+        let record_data = discovery.create_peer(req_body).await?;
 
-        // store in the local db and airtable db
+        // store in the local db and discovery
         user_peer_id.record_id = record_data.id;
-        db_worker
-            .lock()
+        timed_lock(&db_worker, "db_worker")
             .await
             .record_user_peer_id(user_peer_id.clone())
             .await?;
@@ -251,47 +403,98 @@ impl P2pWorker {
         let secret_bytes = user_peer_id.keypair.ok_or(anyhow!("keyPair is not set"))?;
         let keypair = libp2p::identity::Keypair::from_protobuf_encoding(&secret_bytes[..])
             .map_err(|_| anyhow!("failed to decode keypair ed25519"))?;
-
-        let request_response_config = libp2p::request_response::Config::default()
-            .with_request_timeout(tokio::time::Duration::from_secs(600)); // 10 minutes waiting time for a response
-
-        let behaviour = Behaviour::new(
-            vec![("/vane-web3/1.0.0", ProtocolSupport::Full)].into_iter(),
-            request_response_config,
-        );
-        let transport_tcp = libp2p::tcp::Config::new().nodelay(true).port_reuse(true);
-
-        let swarm = SwarmBuilder::with_existing_identity(keypair)
-            .with_tokio()
-            .with_tcp(
-                transport_tcp,
-                libp2p::tls::Config::new,
-                libp2p::yamux::Config::default,
-            )?
-            .with_behaviour(|_| behaviour)?
-            .with_swarm_config(|cfg| {
-                cfg.with_idle_connection_timeout(tokio::time::Duration::from_secs(300))
-            })
-            .build();
+        let relay = Relay::new(keypair.clone())?;
+        let device_sync = DeviceSync::new(&keypair, multi_addr.clone(), db_worker.clone())?;
+        let identity = NodeIdentity::new(keypair.clone(), multi_addr.clone());
+        let swarm = build_swarm(keypair, protocol_version)?;
 
         Ok(Self {
             node_id: peer_id,
+            protocol_version,
             swarm: Arc::new(Mutex::new(swarm)),
             url: multi_addr,
             p2p_command_recv: Arc::new(Mutex::new(command_recv_channel)),
             pending_request: Default::default(),
             current_req: Default::default(),
+            peer_metrics: Default::default(),
+            peer_exchange: PeerExchange::new(peer_id, db_worker.clone(), relay.clone()),
+            relay,
+            receipts: Default::default(),
+            device_sync,
+            identity,
+            rotations: IdentityRotations::new(db_worker),
         })
     }
 
+    /// close the connections of the swarm, peers dial the identity again at its announced address
+    pub async fn disconnect_all(&self) {
+        let mut swarm = self.swarm.lock().await;
+        let peers = swarm.connected_peers().cloned().collect::<Vec<_>>();
+        for peer in peers {
+            let _ = swarm.disconnect_peer_id(peer);
+        }
+    }
+
+    /// swap the swarm for one of `keypair` listening on a new port, returns the rotation and the
+    /// old swarm to keep polling until the grace period ends
+    fn rotate(
+        &self,
+        swarm: &mut Swarm<VaneBehaviour>,
+        keypair: libp2p::identity::Keypair,
+        grace: Duration,
+    ) -> Result<(IdentityRotation, Swarm<VaneBehaviour>), Error> {
+        if self.relay.pins_identity() {
+            Err(anyhow!(
+                "the node relays through or for another node, its identity can not rotate"
+            ))?
+        }
+        let old_multi_addr = self.identity.multi_addr();
+        let ip = old_multi_addr
+            .iter()
+            .find_map(|protocol| match protocol {
+                Protocol::Ip4(ip) => Some(std::net::IpAddr::from(ip)),
+                Protocol::Ip6(ip) => Some(std::net::IpAddr::from(ip)),
+                _ => None,
+            })
+            .ok_or(anyhow!("no ip address in {old_multi_addr}"))?;
+        let port = std::net::TcpListener::bind((ip, 0))
+            .and_then(|listener| listener.local_addr())
+            .map_err(|err| anyhow!("failed to find a free port; caused by: {err}"))?
+            .port();
+        let multi_addr = rotated_multi_addr(&old_multi_addr, port, keypair.public().to_peer_id());
+        let rotation = sign_rotation(&self.identity.keypair(), &keypair, &multi_addr, grace)?;
+
+        let mut rotated = build_swarm(keypair.clone(), self.protocol_version)?;
+        rotated.listen_on(multi_addr.clone())?;
+        self.identity.set(keypair, multi_addr);
+        Ok((rotation, std::mem::replace(swarm, rotated)))
+    }
+
     pub async fn handle_swarm_events(
         pending_request: Arc<Mutex<HashMap<u64, ResponseChannel<Result<Vec<u8>, Error>>>>>,
-        events: SwarmEvent<Event<Vec<u8>, Result<Vec<u8>, Error>>>,
+        peer_metrics: PeerMetrics,
+        relay: Relay,
+        events: SwarmEvent<VaneBehaviourEvent>,
         sender: Sender<Result<SwarmMessage, Error>>,
     ) {
         match events {
-            SwarmEvent::Behaviour(behaviour_event) => match behaviour_event {
-                Event::Message { message, .. } => {
+            SwarmEvent::Behaviour(VaneBehaviourEvent::Ping(ping::Event {
+                peer, result, ..
+            })) => match result {
+                Ok(rtt) => peer_metrics.ping_succeeded(peer, rtt),
+                Err(err) => debug!(target: "p2p","ping to {peer} failed: {err}"),
+            },
+            SwarmEvent::Behaviour(VaneBehaviourEvent::Identify(identify::Event::Received {
+                peer_id,
+                info,
+            })) => {
+                peer_metrics.identified(peer_id, info.agent_version, info.protocol_version);
+            }
+            SwarmEvent::Behaviour(VaneBehaviourEvent::Identify(event)) => {
+                debug!(target: "p2p","identify: {event:?}")
+            }
+            SwarmEvent::Behaviour(VaneBehaviourEvent::RequestResponse(event)) => match event {
+                Event::Message { message, peer } => {
                     info!(target: "p2p","received message: {message:?}");
 
                     // update pending request for requests messages
@@ -301,12 +504,21 @@ impl P2pWorker {
                             request_id,
                             request,
                         } => {
+                            peer_metrics.request_received(peer, request.len());
+                            let req_id_hash = request_id.get_hash_id();
+                            // requests delivered by a relay are sealed to this node
+                            let request = match relay.open(req_id_hash, request) {
+                                Ok(request) => request,
+                                Err(err) => {
+                                    error!(target: "p2p","dropping relayed request: {err}");
+                                    return;
+                                }
+                            };
                             let req_msg = SwarmMessage::Request {
                                 data: request,
                                 inbound_id: request_id,
                             };
 
-                            let req_id_hash = request_id.get_hash_id();
                             info!(target: "p2p","stored response channel, with key: {req_id_hash}");
                             pending_request.lock().await.insert(req_id_hash, channel);
 
@@ -320,6 +532,7 @@ impl P2pWorker {
                             request_id,
                         } => {
                             if let Ok(data) = response {
+                                peer_metrics.response_received(peer, data.len());
                                 let resp_msg = SwarmMessage::Response {
                                     data,
                                     outbound_id: request_id,
@@ -337,6 +550,7 @@ impl P2pWorker {
                     peer,
                     request_id,
                 } => {
+                    peer_metrics.request_failed(peer);
                     let req_id_hash = request_id.get_hash_id();
                     error!(target:"p2p","outbound error: {error:?} peerId: {peer}  request id: {req_id_hash}")
                 }
@@ -348,6 +562,7 @@ impl P2pWorker {
                 }
                 Event::ResponseSent { peer, request_id } => {
                     let req_id_hash = request_id.get_hash_id();
+                    peer_metrics.response_sent(peer, req_id_hash);
                     info!(target: "p2p","response sent to: {peer:?}: req_id: {req_id_hash}")
                 }
             },
@@ -357,6 +572,7 @@ impl P2pWorker {
                 num_established,
                 ..
             } => {
+                peer_metrics.connection_changed(peer_id, true);
                 info!(target:"p2p","connection established: peer_id:{peer_id:?} endpoint:{endpoint:?} num_established:{num_established:?}")
             }
             SwarmEvent::IncomingConnection {
@@ -376,8 +592,12 @@ impl P2pWorker {
                 peer_id,
                 endpoint,
                 cause,
+                num_established,
                 ..
             } => {
+                if num_established == 0 {
+                    peer_metrics.connection_changed(peer_id, false);
+                }
                 info!(target:"p2p","connection closed peer_id:{peer_id:?} endpoint:{endpoint:?} cause:{cause:?}")
             }
             SwarmEvent::IncomingConnectionError { error, .. } => {
@@ -405,34 +625,170 @@ impl P2pWorker {
         sender_channel: Sender<Result<SwarmMessage, Error>>,
     ) -> Result<(), Error> {
         let multi_addr = &self.url;
-        let _listening_id = self.swarm.lock().await.listen_on(multi_addr.clone())?;
-        trace!(target:"p2p","listening to: {:?}",multi_addr);
-
         let sender = sender_channel;
         let mut swarm = self.swarm.lock().await;
+        // a restarted swarm loop keeps listening on the listener of the previous one
+        if swarm.listeners().next().is_none() {
+            let _listening_id = swarm.listen_on(multi_addr.clone())?;
+        }
+        trace!(target:"p2p","listening to: {:?}",multi_addr);
+        self.relay.start(&mut swarm);
+
         let mut p2p_command_recv = self.p2p_command_recv.lock().await;
+        let pex_actions_recv = self.peer_exchange.actions_recv.clone();
+        let mut pex_actions_recv = pex_actions_recv.lock().await;
+        let peer_metrics = self.peer_metrics.clone();
+        // dial results waited on, per dialed peer
+        let mut pending_dials: HashMap<PeerId, Vec<DialReply>> = HashMap::new();
+        let mut sync_interval = tokio::time::interval(SYNC_INTERVAL);
+        // swarm of the identity rotated from, answering until its grace period ends
+        let mut retired: Option<(Swarm<VaneBehaviour>, tokio::time::Instant)> = None;
 
         loop {
+            if retired
+                .as_ref()
+                .is_some_and(|(_, until)| *until <= tokio::time::Instant::now())
+            {
+                retired = None;
+                info!(target: "p2p","grace period of the rotated identity ended");
+            }
+
             // Create futures before select to ensure they're polled fairly
             let next_event = swarm.next();
             let next_command = p2p_command_recv.recv();
+            let next_pex_action = pex_actions_recv.recv();
+            let next_sync = sync_interval.tick();
+            let next_retired_event = async {
+                match retired.as_mut() {
+                    Some((retired, _)) => retired.next().await,
+                    None => std::future::pending().await,
+                }
+            };
 
             select! {
                 event = next_event => {
 
-                    if let Some(event) = event {
-                        Self::handle_swarm_events(self.clone().pending_request, event, sender.clone()).await
-                    } else {
-                        info!("no current swarm event")
+                    match event {
+                        Some(SwarmEvent::Behaviour(VaneBehaviourEvent::PeerExchange(event))) => {
+                            self.peer_exchange.handle_event(event)
+                        }
+                        Some(SwarmEvent::Behaviour(VaneBehaviourEvent::Relay(event))) => {
+                            self.relay.handle_event(&mut swarm, event, &sender).await
+                        }
+                        Some(SwarmEvent::Behaviour(VaneBehaviourEvent::DeviceSync(event))) => {
+                            self.device_sync.handle_event(&mut swarm, event).await
+                        }
+                        Some(SwarmEvent::Behaviour(VaneBehaviourEvent::IdentityRotation(event))) => {
+                            self.rotations.handle_event(&mut swarm, event).await
+                        }
+                        Some(SwarmEvent::Behaviour(VaneBehaviourEvent::Receipts(event))) => {
+                            if let Some(message) = self.receipts.handle_event(&mut swarm, event) {
+                                if let Err(err) = sender.send(Ok(message)).await {
+                                    error!("Failed to send message: {err}");
+                                }
+                            }
+                        }
+                        Some(SwarmEvent::Behaviour(VaneBehaviourEvent::RequestResponse(event))) => {
+                            // replies to the requests this node relayed go back to their sender
+                            if let Some(event) = self.relay.handle_delivery(&mut swarm, event) {
+                                // requests are acknowledged before the user confirms, unless a
+                                // relay delivered them
+                                if let Event::Message {
+                                    peer,
+                                    message: Message::Request { request, request_id, .. },
+                                } = &event
+                                {
+                                    if !request.starts_with(SEALED_REQUEST_PREFIX) {
+                                        let inbound_req_id = request_id.get_hash_id();
+                                        self.receipts.acknowledge(
+                                            &mut swarm,
+                                            *peer,
+                                            inbound_req_id,
+                                            request,
+                                        );
+                                    }
+                                }
+                                let event = VaneBehaviourEvent::RequestResponse(event);
+                                Self::handle_swarm_events(
+                                    self.clone().pending_request,
+                                    self.peer_metrics.clone(),
+                                    self.relay.clone(),
+                                    SwarmEvent::Behaviour(event),
+                                    sender.clone(),
+                                ).await
+                            }
+                        }
+                        Some(event) => {
+                            match &event {
+                                SwarmEvent::Behaviour(VaneBehaviourEvent::Identify(
+                                    identify::Event::Received { peer_id, info },
+                                )) if is_compatible(
+                                    self.protocol_version,
+                                    &info.protocol_version,
+                                ) => {
+                                    self.peer_exchange.start(*peer_id);
+                                    self.device_sync.start(&mut swarm, *peer_id).await;
+                                }
+                                SwarmEvent::Behaviour(VaneBehaviourEvent::Identify(
+                                    identify::Event::Received { peer_id, info },
+                                )) => {
+                                    warn!(
+                                        target: "p2p",
+                                        "peer {peer_id} runs {} speaking {}, incompatible with {}",
+                                        info.agent_version,
+                                        info.protocol_version,
+                                        self.protocol_version
+                                    );
+                                }
+                                SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                                    self.relay.connected(&mut swarm, *peer_id);
+                                    for reply in pending_dials.remove(peer_id).unwrap_or_default() {
+                                        let _ = reply.send(Ok(()));
+                                    }
+                                }
+                                SwarmEvent::OutgoingConnectionError {
+                                    peer_id: Some(peer_id),
+                                    error,
+                                    ..
+                                } => {
+                                    for reply in pending_dials.remove(peer_id).unwrap_or_default() {
+                                        let _ = reply.send(Err(error.to_string()));
+                                    }
+                                }
+                                _ => {}
+                            }
+                            Self::handle_swarm_events(
+                                self.clone().pending_request,
+                                self.peer_metrics.clone(),
+                                self.relay.clone(),
+                                event,
+                                sender.clone(),
+                            ).await
+                        }
+                        None => info!("no current swarm event"),
                     }
 
                 },
+                Some(event) = next_retired_event => {
+                    // the old identity only completes the requests still addressed to it
+                    if let SwarmEvent::Behaviour(VaneBehaviourEvent::RequestResponse(event)) = event {
+                        Self::handle_swarm_events(
+                            self.clone().pending_request,
+                            self.peer_metrics.clone(),
+                            self.relay.clone(),
+                            SwarmEvent::Behaviour(VaneBehaviourEvent::RequestResponse(event)),
+                            sender.clone(),
+                        ).await
+                    }
+                },
                 cmd = next_command => {
 
                     match cmd {
                         Some(NetworkCommand::SendResponse {response,channel}) => {
                             if channel.is_open() {
-                                swarm.behaviour_mut().send_response(channel,Ok(response))
+                                swarm.behaviour_mut()
+                                    .request_response
+                                    .send_response(channel,Ok(response))
                                     .map_err(|err|anyhow!("failed to send response; {err:?}"))?;
                             } else {
                                 error!("response channel is closed");
@@ -440,28 +796,114 @@ impl P2pWorker {
                         },
                         Some(NetworkCommand::SendRequest {request,peer_id,target_multi_addr}) => {
                             if swarm.is_connected(&peer_id) {
-                                swarm.behaviour_mut().send_request(&peer_id,request);
+                                peer_metrics.request_sent(peer_id, request.len());
+                                swarm.behaviour_mut()
+                                    .request_response
+                                    .send_request(&peer_id,request);
                                 info!("request sent to peer: {peer_id:?}");
                             } else {
                                 info!("re dialing");
                                 swarm.dial(target_multi_addr).map_err(|err|anyhow!("failed to re dial: {err}"))?;
-                                swarm.behaviour_mut().send_request(&peer_id,request);
+                                peer_metrics.request_sent(peer_id, request.len());
+                                swarm.behaviour_mut()
+                                    .request_response
+                                    .send_request(&peer_id,request);
                                 info!("request sent to peer: {peer_id:?}");
                             }
                         },
-                        Some(NetworkCommand::Dial {target_multi_addr,target_peer_id}) => {
+                        Some(NetworkCommand::Dial {target_multi_addr,target_peer_id,reply}) => {
                             // check first if the peer communication is already connected
                             if swarm.is_connected(&target_peer_id){
-                                info!("peer already connected: {target_peer_id}")
+                                info!("peer already connected: {target_peer_id}");
+                                let _ = reply.send(Ok(()));
                             }else{
-                                info!("dialing peer: {target_peer_id} ");
-                                swarm.dial(target_multi_addr).map_err(|err|anyhow!("failed to dial: {err}"))?;
+                                // replies the dialer stopped waiting for are dropped
+                                pending_dials.retain(|_, replies| {
+                                    replies.retain(|reply| !reply.is_canceled());
+                                    !replies.is_empty()
+                                });
+                                if let Some(replies) = pending_dials.get_mut(&target_peer_id) {
+                                    // answered with the dial already in progress
+                                    replies.push(reply);
+                                } else {
+                                    info!("dialing peer: {target_peer_id} ");
+                                    // by peer id so a failed dial reports its peer, a dial
+                                    // nobody waits on anymore does not hold the next one back
+                                    let dial = DialOpts::peer_id(target_peer_id)
+                                        .condition(PeerCondition::Disconnected)
+                                        .addresses(vec![target_multi_addr])
+                                        .build();
+                                    match swarm.dial(dial) {
+                                        Ok(()) => {
+                                            pending_dials.insert(target_peer_id, vec![reply]);
+                                        }
+                                        Err(err) => {
+                                            error!("failed to dial {target_peer_id}: {err}");
+                                            let _ = reply.send(Err(err.to_string()));
+                                        }
+                                    }
+                                }
+                            }
+                        },
+                        Some(NetworkCommand::SendRelayedRequest {request,peer_id}) => {
+                            peer_metrics.request_sent(peer_id, request.len());
+                            if let Err(err) = self.relay.forward(&mut swarm, peer_id, request) {
+                                error!("failed to relay the request to {peer_id}: {err}");
+                            }
+                        },
+                        Some(NetworkCommand::Ping {reply}) => {
+                            let _ = reply.send(());
+                        },
+                        Some(NetworkCommand::CancelRequests {tx_nonce}) => {
+                            self.receipts.cancel(&mut swarm, tx_nonce);
+                        },
+                        Some(NetworkCommand::PairDevice {
+                            target_multi_addr,
+                            target_peer_id,
+                            code,
+                            reply,
+                        }) => {
+                            self.device_sync
+                                .pair(&mut swarm, target_peer_id, target_multi_addr, code, reply);
+                        },
+                        Some(NetworkCommand::ExchangePeers) => {
+                            self.peer_exchange.start_all(&swarm, None);
+                        },
+                        Some(NetworkCommand::RotateIdentity {keypair, grace, reply}) => {
+                            if retired.is_some() {
+                                let _ = reply.send(Err(
+                                    "the previous identity is still in its grace period".to_string()
+                                ));
+                                continue;
+                            }
+                            match self.rotate(&mut swarm, keypair, grace) {
+                                Ok((rotation, old)) => {
+                                    info!(
+                                        "rotated the node identity to {}, {} answers until {}",
+                                        rotation.new_peer_id,
+                                        rotation.old_peer_id,
+                                        rotation.grace_until
+                                    );
+                                    self.rotations.notify(&mut swarm, &rotation).await;
+                                    retired = Some((old, tokio::time::Instant::now() + grace));
+                                    let _ = reply.send(Ok(rotation));
+                                }
+                                Err(err) => {
+                                    error!("failed to rotate the node identity: {err}");
+                                    let _ = reply.send(Err(err.to_string()));
+                                }
                             }
                         },
                         None => {
                             info!("command channel closed");
                         }
                     }
+                },
+                Some(action) = next_pex_action => {
+                    self.peer_exchange.act(&mut swarm, action);
+                },
+                _ = next_sync => {
+                    self.device_sync.sync_all(&mut swarm).await;
                 }
             }
 
@@ -490,23 +932,32 @@ impl P2pNetworkService {
         })
     }
 
-    // dialing the target peer_id
+    // dialing the target peer_id, resolves once it is connected, the dial failed or after
+    // `timeout`. takes the command sender alone so the service lock is not held while dialing
     pub async fn dial_to_peer_id(
-        &mut self,
+        p2p_command_tx: &Sender<NetworkCommand>,
         target_url: Multiaddr,
         peer_id: &PeerId,
-    ) -> Result<(), anyhow::Error> {
+        timeout: Duration,
+    ) -> Result<DialOutcome, anyhow::Error> {
+        let (reply, dialed) = libp2p::futures::channel::oneshot::channel();
         let dial_command = NetworkCommand::Dial {
             target_multi_addr: target_url.clone(),
             target_peer_id: peer_id.clone(),
+            reply,
         };
 
-        self.p2p_command_tx
+        p2p_command_tx
             .send(dial_command)
             .await
             .map_err(|err| anyhow!("failed to send dial command; {err}"))?;
 
-        Ok(())
+        match tokio::time::timeout(timeout, dialed).await {
+            Ok(Ok(Ok(()))) => Ok(DialOutcome::Connected),
+            Ok(Ok(Err(error))) => Ok(DialOutcome::Failed(error)),
+            Ok(Err(_)) => Err(anyhow!("swarm loop dropped the dial")),
+            Err(_) => Ok(DialOutcome::TimedOut(timeout)),
+        }
     }
 
     pub async fn send_request(
@@ -516,7 +967,10 @@ impl P2pNetworkService {
         target_multi_addr: Multiaddr,
     ) -> Result<(), Error> {
         let request = request.lock().await;
-        let encoded_req = request.encode();
+        let encoded_req = envelope::seal(&*request);
+        self.p2p_worker
+            .receipts
+            .expect(target_peer_id, request.tx_nonce, &encoded_req);
         let req_command = NetworkCommand::SendRequest {
             request: encoded_req,
             peer_id: target_peer_id,
@@ -531,13 +985,79 @@ impl P2pNetworkService {
         Ok(())
     }
 
+    /// send the attestation request to the receiver node, through its relay when it is offline
+    pub async fn send_attestation_request(
+        &mut self,
+        request: Arc<Mutex<TxStateMachine>>,
+        target_peer_id: PeerId,
+        target_multi_addr: Multiaddr,
+    ) -> Result<(), Error> {
+        let offline = !self.p2p_worker.peer_metrics.is_connected(&target_peer_id);
+        if offline && self.p2p_worker.relay.route(&target_peer_id).is_some() {
+            info!(target: "p2p","{target_peer_id} is offline, sending through its relay");
+            self.send_relayed_request(request, target_peer_id).await
+        } else {
+            self.send_request(request, target_peer_id, target_multi_addr)
+                .await
+        }
+    }
+
+    /// hand the request to the relay of the offline mobile node `target_peer_id`
+    pub async fn send_relayed_request(
+        &mut self,
+        request: Arc<Mutex<TxStateMachine>>,
+        target_peer_id: PeerId,
+    ) -> Result<(), Error> {
+        let req_command = NetworkCommand::SendRelayedRequest {
+            request: envelope::seal(&*request.lock().await),
+            peer_id: target_peer_id,
+        };
+        self.p2p_command_tx
+            .send(req_command)
+            .await
+            .map_err(|err| anyhow!("failed to send relayed req command; {err}"))?;
+        Ok(())
+    }
+
+    /// withdraw the attestation requests of the tx `tx_nonce` from the devices it was sent to
+    pub async fn cancel_requests(&self, tx_nonce: u32) -> Result<(), anyhow::Error> {
+        self.p2p_command_tx
+            .send(NetworkCommand::CancelRequests { tx_nonce })
+            .await
+            .map_err(|err| anyhow!("failed to send cancel command; {err}"))?;
+        Ok(())
+    }
+
+    /// whether the swarm loop answers within `timeout`
+    pub async fn ping(&self, timeout: Duration) -> Result<(), anyhow::Error> {
+        let (reply, replied) = libp2p::futures::channel::oneshot::channel();
+        tokio::time::timeout(timeout, async {
+            self.p2p_command_tx
+                .send(NetworkCommand::Ping { reply })
+                .await
+                .map_err(|err| anyhow!("failed to send ping command; {err}"))?;
+            replied
+                .await
+                .map_err(|_| anyhow!("swarm loop dropped the ping"))
+        })
+        .await
+        .map_err(|_| anyhow!("swarm loop did not answer within {timeout:?}"))?
+    }
+
     pub async fn send_response(
         &mut self,
         outbound_id: u64,
         response: Arc<Mutex<TxStateMachine>>,
     ) -> Result<(), anyhow::Error> {
         let txn_state = response.lock().await.clone();
-        let encoded_resp = txn_state.encode();
+        // replies to relayed requests are sealed for the sender
+        let encoded_resp = self
+            .p2p_worker
+            .relay
+            .seal(outbound_id, envelope::seal(&txn_state))?;
+        self.p2p_worker
+            .peer_metrics
+            .response_queued(outbound_id, encoded_resp.len());
 
         let channel = self
             .clone()
@@ -558,3 +1078,22 @@ impl P2pNetworkService {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // only nodes of the same major vane protocol talk to each other
+    #[test]
+    fn protocol_versions_are_compatible_within_a_major_version() {
+        assert!(is_compatible(PROTOCOL_VERSION, PROTOCOL_VERSION));
+        assert!(is_compatible(PROTOCOL_VERSION, "/vane-web3/1.4.2"));
+        assert!(!is_compatible(PROTOCOL_VERSION, "/vane-web3/2.0.0"));
+        assert!(!is_compatible(PROTOCOL_VERSION, "/vane-web3/"));
+        assert!(!is_compatible(PROTOCOL_VERSION, "/ipfs/0.1.0"));
+        // dev nodes and the nodes moving real funds ignore each other
+        assert!(!is_compatible(PROTOCOL_VERSION, DEV_PROTOCOL_VERSION));
+        assert!(!is_compatible(DEV_PROTOCOL_VERSION, PROTOCOL_VERSION));
+        assert!(is_compatible(DEV_PROTOCOL_VERSION, "/vane-web3-dev/1.2.0"));
+    }
+}
@@ -0,0 +1,64 @@
+// signing device pairing for watch-only nodes
+// a watch-only node holds no keys and only sends for the accounts of paired devices, the device proves
+// it holds the account key by signing a message bound to the node peer id and to a one-time
+// challenge, so a signature seen once can not pair the account again
+
+use alloy::primitives::{Address, Signature as EcdsaSignature};
+use anyhow::anyhow;
+use core::time::Duration;
+use libp2p::PeerId;
+
+/// time the signing device has to sign a pairing message
+pub const PAIRING_CHALLENGE_VALIDITY: Duration = Duration::from_secs(300);
+
+/// challenge a pairing message is bound to, handed out with the message and spent by the pairing
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PairingChallenge {
+    pub nonce: u64,
+    /// unix timestamp in seconds
+    pub expires_at: u64,
+}
+
+impl PairingChallenge {
+    pub fn new(now: u64) -> Self {
+        Self {
+            nonce: rand::random(),
+            expires_at: now + PAIRING_CHALLENGE_VALIDITY.as_secs(),
+        }
+    }
+}
+
+/// message the signing device signs to pair `account` with the node
+pub fn pairing_message(
+    account: &Address,
+    node_id: &PeerId,
+    challenge: &PairingChallenge,
+) -> String {
+    format!(
+        "vane pair signing device for {account} with node {node_id}, \
+         challenge {:016x} valid until {}",
+        challenge.nonce, challenge.expires_at
+    )
+}
+
+/// check `signature` is the EIP-191 signature of the pairing message of `challenge` by `account`,
+/// returns the checksummed account
+pub fn verify_pairing(
+    account: &str,
+    node_id: &PeerId,
+    challenge: &PairingChallenge,
+    signature: &[u8],
+) -> Result<String, anyhow::Error> {
+    let account: Address = account
+        .parse()
+        .map_err(|err| anyhow!("invalid signer account; caused by: {err}"))?;
+    let signature = EcdsaSignature::try_from(signature)
+        .map_err(|err| anyhow!("invalid pairing signature; caused by: {err}"))?;
+    let recovered = signature
+        .recover_address_from_msg(pairing_message(&account, node_id, challenge))
+        .map_err(|err| anyhow!("pairing signature recovery failed; caused by: {err}"))?;
+    if recovered != account {
+        Err(anyhow!("pairing message was not signed by {account}"))?
+    }
+    Ok(account.to_string())
+}
@@ -0,0 +1,378 @@
+// peer exchange (PEX)
+// once a connected peer identified itself with a compatible protocol, both nodes send each other the
+// account to node bindings they verified. a binding is only kept when the account signature checks out
+// and it is newer than the stored one. transfers to a bound account dial its node without asking the
+// registry. the relay routes of mobile nodes and the account revocations are exchanged along with
+// the bindings, a peer sending revocations this node did not know is followed by an exchange with
+// every other connected peer so revocations spread through the network
+// the db reads and the signature checks run in tasks of their own, the swarm task only sends what
+// they hand back over the action channel
+
+use crate::locks::timed_lock;
+use crate::p2p::VaneBehaviour;
+use crate::relay::Relay;
+use crate::revocation::verify_revocation;
+use alloy::primitives::{Address, Signature as EcdsaSignature};
+use anyhow::anyhow;
+use db::{DbWorker, DbWorkerInterface};
+use libp2p::multiaddr::Protocol;
+use libp2p::request_response::{Event, Message, ResponseChannel};
+use libp2p::{Multiaddr, PeerId, Swarm};
+use log::{debug, info, warn};
+use moka::future::Cache as AsyncCache;
+use primitives::envelope;
+use primitives::peer_exchange::{PeerBinding, PeerExchangeMessage};
+use primitives::revocation::AccountRevocation;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::Mutex;
+
+/// protocol of the peer exchange requests, next to the attestation request protocol
+pub const PEX_PROTOCOL: &str = "/vane-pex/1.0.0";
+/// most bindings sent or accepted in one exchange, the latest signed first
+pub const MAX_EXCHANGED_BINDINGS: usize = 256;
+/// most bindings of one peer checked within `BINDING_QUOTA_WINDOW`, the rest wait for a later
+/// exchange
+pub const MAX_BINDINGS_PER_PEER: usize = 512;
+const BINDING_QUOTA_WINDOW: Duration = Duration::from_secs(600);
+/// bindings signed further ahead of the local clock are refused
+const MAX_CLOCK_DRIFT: Duration = Duration::from_secs(300);
+/// swarm actions waiting to be sent, the exchange tasks wait when the swarm falls behind
+const MAX_PENDING_ACTIONS: usize = 64;
+
+/// check the binding is signed by its account, returns the checksummed account
+pub fn verify_peer_binding(binding: &PeerBinding) -> Result<String, anyhow::Error> {
+    let account: Address = binding
+        .account
+        .parse()
+        .map_err(|err| anyhow!("invalid binding account; caused by: {err}"))?;
+    let peer_id = PeerId::from_str(&binding.peer_id)
+        .map_err(|err| anyhow!("invalid binding peer id; caused by: {err}"))?;
+    let multi_addr = Multiaddr::from_str(&binding.multi_addr)
+        .map_err(|err| anyhow!("invalid binding multi addr; caused by: {err}"))?;
+    if multi_addr
+        .iter()
+        .any(|protocol| matches!(protocol, Protocol::P2p(addr_peer) if addr_peer != peer_id))
+    {
+        Err(anyhow!(
+            "binding multi addr {multi_addr} is not the address of {peer_id}"
+        ))?
+    }
+    let signature = EcdsaSignature::try_from(binding.signature.as_slice())
+        .map_err(|err| anyhow!("invalid binding signature; caused by: {err}"))?;
+    let recovered = signature
+        .recover_address_from_msg(binding.signed_message())
+        .map_err(|err| anyhow!("binding signature recovery failed; caused by: {err}"))?;
+    if recovered != account {
+        Err(anyhow!("peer binding was not signed by {account}"))?
+    }
+    Ok(account.to_string())
+}
+
+/// what an exchange task leaves for the swarm task to send
+pub enum PexAction {
+    /// start an exchange with `peer`
+    Request {
+        peer: PeerId,
+        shared: PeerExchangeMessage,
+    },
+    /// answer the exchange `peer` started
+    Respond {
+        peer: PeerId,
+        channel: ResponseChannel<Result<Vec<u8>, anyhow::Error>>,
+        shared: PeerExchangeMessage,
+    },
+    /// exchange with every connected peer but `except`, once `except` shared new revocations
+    ExchangeAll { except: PeerId },
+}
+
+#[derive(Clone)]
+pub struct PeerExchange {
+    node_id: PeerId,
+    db_worker: Arc<Mutex<DbWorker>>,
+    /// relay routes are exchanged along with the bindings
+    relay: Relay,
+    /// bindings checked per peer within the quota window
+    checked: AsyncCache<PeerId, usize>,
+    actions_tx: Sender<PexAction>,
+    pub actions_recv: Arc<Mutex<Receiver<PexAction>>>,
+}
+
+impl PeerExchange {
+    pub fn new(node_id: PeerId, db_worker: Arc<Mutex<DbWorker>>, relay: Relay) -> Self {
+        let (actions_tx, actions_recv) = channel(MAX_PENDING_ACTIONS);
+        Self {
+            node_id,
+            db_worker,
+            relay,
+            checked: AsyncCache::builder()
+                .max_capacity(1024)
+                .time_to_live(BINDING_QUOTA_WINDOW)
+                .build(),
+            actions_tx,
+            actions_recv: Arc::new(Mutex::new(actions_recv)),
+        }
+    }
+
+    /// latest signed bindings this node verified, its own included
+    pub async fn shared_bindings(&self) -> Result<Vec<PeerBinding>, anyhow::Error> {
+        let mut bindings = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_peer_bindings()
+            .await?;
+        bindings.sort_by(|a, b| b.signed_at.cmp(&a.signed_at));
+        bindings.truncate(MAX_EXCHANGED_BINDINGS);
+        Ok(bindings)
+    }
+
+    /// store the bindings received from `peer` that verify, returns how many were new
+    pub async fn accept(&self, peer: PeerId, bindings: Vec<PeerBinding>) -> usize {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut checked = self.checked.get(&peer).await.unwrap_or_default();
+
+        let mut accepted = 0;
+        for binding in bindings.into_iter().take(MAX_EXCHANGED_BINDINGS) {
+            if binding.peer_id == self.node_id.to_base58()
+                || binding.signed_at > now + MAX_CLOCK_DRIFT.as_secs()
+            {
+                continue;
+            }
+            let stored = timed_lock(&self.db_worker, "db_worker")
+                .await
+                .get_peer_binding(binding.account.clone())
+                .await;
+            match stored {
+                Ok(Some(stored)) if !binding.supersedes(&stored) => continue,
+                Ok(_) => {}
+                Err(err) => {
+                    warn!(target: "pex", "failed to read a stored binding; caused by: {err}");
+                    continue;
+                }
+            }
+            if checked >= MAX_BINDINGS_PER_PEER {
+                debug!(target: "pex", "{peer} is over its binding quota, dropping the rest");
+                break;
+            }
+            checked += 1;
+            match verify_peer_binding(&binding) {
+                Ok(account) if account == binding.account => {}
+                Ok(_) | Err(_) => {
+                    debug!(target: "pex", "dropping unverified binding of {} from {peer}", binding.account);
+                    continue;
+                }
+            }
+            if let Err(err) = timed_lock(&self.db_worker, "db_worker")
+                .await
+                .record_peer_binding(binding)
+                .await
+            {
+                warn!(target: "pex", "failed to store a binding from {peer}; caused by: {err}");
+                continue;
+            }
+            accepted += 1;
+        }
+        self.checked.insert(peer, checked).await;
+        if accepted > 0 {
+            info!(target: "pex", "accepted {accepted} peer bindings from {peer}");
+        }
+        accepted
+    }
+
+    /// store the revocations received from `peer` that verify, returns how many were new
+    pub async fn accept_revocations(
+        &self,
+        peer: PeerId,
+        revocations: Vec<AccountRevocation>,
+    ) -> usize {
+        let stored = match timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_revocations()
+            .await
+        {
+            Ok(stored) => stored,
+            Err(err) => {
+                warn!(target: "pex", "failed to read the stored revocations; caused by: {err}");
+                return 0;
+            }
+        };
+
+        let mut accepted = 0;
+        for revocation in revocations.into_iter().take(MAX_EXCHANGED_BINDINGS) {
+            if stored
+                .iter()
+                .any(|stored| stored.revokes(&revocation.account))
+            {
+                continue;
+            }
+            match verify_revocation(&revocation) {
+                Ok(account) if account == revocation.account => {}
+                Ok(_) | Err(_) => {
+                    let account = &revocation.account;
+                    debug!(target: "pex", "dropping unverified revocation of {account} from {peer}");
+                    continue;
+                }
+            }
+            if let Err(err) = timed_lock(&self.db_worker, "db_worker")
+                .await
+                .record_revocation(revocation)
+                .await
+            {
+                warn!(target: "pex", "failed to store a revocation from {peer}; caused by: {err}");
+                continue;
+            }
+            accepted += 1;
+        }
+        if accepted > 0 {
+            warn!(target: "pex", "{peer} shared {accepted} revoked accounts");
+        }
+        accepted
+    }
+
+    async fn shared(&self) -> PeerExchangeMessage {
+        let bindings = self.shared_bindings().await.unwrap_or_else(|err| {
+            warn!(target: "pex", "failed to read the shared bindings; caused by: {err}");
+            vec![]
+        });
+        let mut relay_routes = self.relay.routes();
+        relay_routes.truncate(MAX_EXCHANGED_BINDINGS);
+        let mut revocations = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_revocations()
+            .await
+            .unwrap_or_else(|err| {
+                warn!(target: "pex", "failed to read the revocations; caused by: {err}");
+                vec![]
+            });
+        revocations.sort_by(|a, b| b.revoked_at.cmp(&a.revoked_at));
+        revocations.truncate(MAX_EXCHANGED_BINDINGS);
+        PeerExchangeMessage {
+            bindings,
+            relay_routes,
+            revocations,
+        }
+    }
+
+    async fn send_action(&self, action: PexAction) {
+        if self.actions_tx.send(action).await.is_err() {
+            warn!(target: "pex", "the swarm stopped taking peer exchange actions");
+        }
+    }
+
+    /// exchange with every connected peer but `except`, e.g. once a revocation was learned
+    pub fn start_all(&self, swarm: &Swarm<VaneBehaviour>, except: Option<PeerId>) {
+        for peer in swarm.connected_peers() {
+            if Some(*peer) != except {
+                self.start(*peer);
+            }
+        }
+    }
+
+    /// send the shared bindings and relay routes to a newly identified peer, its response carries
+    /// its own
+    pub fn start(&self, peer: PeerId) {
+        let exchange = self.clone();
+        tokio::spawn(async move {
+            let shared = exchange.shared().await;
+            exchange
+                .send_action(PexAction::Request { peer, shared })
+                .await;
+        });
+    }
+
+    /// send what an exchange task handed over, on the swarm task
+    pub fn act(&self, swarm: &mut Swarm<VaneBehaviour>, action: PexAction) {
+        match action {
+            PexAction::Request { peer, shared } => {
+                swarm
+                    .behaviour_mut()
+                    .peer_exchange
+                    .send_request(&peer, envelope::seal(&shared));
+            }
+            PexAction::Respond {
+                peer,
+                channel,
+                shared,
+            } => {
+                if swarm
+                    .behaviour_mut()
+                    .peer_exchange
+                    .send_response(channel, Ok(envelope::seal(&shared)))
+                    .is_err()
+                {
+                    debug!(target: "pex", "{peer} closed the exchange before the response");
+                }
+            }
+            PexAction::ExchangeAll { except } => self.start_all(swarm, Some(except)),
+        }
+    }
+
+    pub fn handle_event(&self, event: Event<Vec<u8>, Result<Vec<u8>, anyhow::Error>>) {
+        match event {
+            Event::Message {
+                peer,
+                message:
+                    Message::Request {
+                        request, channel, ..
+                    },
+            } => {
+                let exchange = self.clone();
+                tokio::spawn(async move {
+                    let shared = exchange.shared().await;
+                    exchange
+                        .send_action(PexAction::Respond {
+                            peer,
+                            channel,
+                            shared,
+                        })
+                        .await;
+                    exchange.accept_encoded(peer, &request).await;
+                });
+            }
+            Event::Message {
+                peer,
+                message:
+                    Message::Response {
+                        response: Ok(response),
+                        ..
+                    },
+            } => {
+                let exchange = self.clone();
+                tokio::spawn(async move { exchange.accept_encoded(peer, &response).await });
+            }
+            Event::Message { .. } | Event::ResponseSent { .. } => {}
+            Event::OutboundFailure { peer, error, .. } => {
+                debug!(target: "pex", "exchange with {peer} failed: {error:?}")
+            }
+            Event::InboundFailure { peer, error, .. } => {
+                debug!(target: "pex", "exchange from {peer} failed: {error:?}")
+            }
+        }
+    }
+
+    async fn accept_encoded(&self, peer: PeerId, encoded: &[u8]) {
+        match envelope::decode::<PeerExchangeMessage>(encoded) {
+            Ok(message) => {
+                self.accept(peer, message.bindings).await;
+                if self.accept_revocations(peer, message.revocations).await > 0 {
+                    self.send_action(PexAction::ExchangeAll { except: peer })
+                        .await;
+                }
+                let learned = message
+                    .relay_routes
+                    .into_iter()
+                    .take(MAX_EXCHANGED_BINDINGS)
+                    .filter(|route| self.relay.learn(route.clone()))
+                    .count();
+                if learned > 0 {
+                    debug!(target: "pex", "learned {learned} relay routes from {peer}");
+                }
+            }
+            Err(err) => warn!(target: "pex", "undecodable peer exchange from {peer}: {err:?}"),
+        }
+    }
+}
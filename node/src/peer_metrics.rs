@@ -0,0 +1,211 @@
+// per peer connection metrics
+// the swarm loop records the agent and protocol versions peers announce over identify, the ping round
+// trip time, the attestation requests and responses exchanged with every peer, their size and when the
+// peer was last heard from. the metrics are served by the `peerStats` rpc and the `/metrics` endpoint
+// and only live as long as the node runs
+
+use libp2p::PeerId;
+use primitives::data_structure::PeerStats;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// how long the size of a queued response is kept, the swarm never reports a response it failed
+/// to send as sent
+const QUEUED_RESPONSE_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Default)]
+pub struct PeerMetrics {
+    peers: Arc<Mutex<HashMap<PeerId, PeerStats>>>,
+    /// size of the responses handed to the swarm loop and when, by inbound request id hash
+    queued_responses: Arc<Mutex<HashMap<u64, (u64, Instant)>>>,
+}
+
+impl PeerMetrics {
+    fn update(&self, peer: PeerId, heard_from: bool, update: impl FnOnce(&mut PeerStats)) {
+        let Ok(mut peers) = self.peers.lock() else {
+            return;
+        };
+        let stats = peers.entry(peer).or_insert_with(|| PeerStats {
+            peer_id: peer.to_base58(),
+            ..Default::default()
+        });
+        update(stats);
+        if heard_from {
+            stats.last_seen = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|now| now.as_secs());
+        }
+        let outcomes = stats.responses_received + stats.requests_failed;
+        stats.success_rate =
+            (outcomes > 0).then(|| stats.responses_received as f64 / outcomes as f64);
+    }
+
+    pub fn connection_changed(&self, peer: PeerId, connected: bool) {
+        self.update(peer, connected, |stats| stats.connected = connected);
+    }
+
+    pub fn identified(&self, peer: PeerId, agent_version: String, protocol_version: String) {
+        self.update(peer, true, |stats| {
+            stats.agent_version = Some(agent_version);
+            stats.protocol_version = Some(protocol_version);
+        });
+    }
+
+    /// vane protocol version `peer` announced over identify, none before it identified itself
+    pub fn protocol_version(&self, peer: &PeerId) -> Option<String> {
+        self.peers
+            .lock()
+            .ok()?
+            .get(peer)
+            .and_then(|stats| stats.protocol_version.clone())
+    }
+
+    pub fn ping_succeeded(&self, peer: PeerId, rtt: Duration) {
+        self.update(peer, true, |stats| {
+            stats.rtt_ms = Some(rtt.as_millis() as u64)
+        });
+    }
+
+    pub fn request_sent(&self, peer: PeerId, bytes: usize) {
+        self.update(peer, false, |stats| {
+            stats.requests_sent += 1;
+            stats.bytes_sent += bytes as u64;
+        });
+    }
+
+    pub fn request_failed(&self, peer: PeerId) {
+        self.update(peer, false, |stats| stats.requests_failed += 1);
+    }
+
+    pub fn response_received(&self, peer: PeerId, bytes: usize) {
+        self.update(peer, true, |stats| {
+            stats.responses_received += 1;
+            stats.bytes_received += bytes as u64;
+        });
+    }
+
+    pub fn request_received(&self, peer: PeerId, bytes: usize) {
+        self.update(peer, true, |stats| {
+            stats.requests_received += 1;
+            stats.bytes_received += bytes as u64;
+        });
+    }
+
+    /// remember the size of a response until the swarm reports it sent
+    pub fn response_queued(&self, request_id_hash: u64, bytes: usize) {
+        if let Ok(mut queued) = self.queued_responses.lock() {
+            queued.retain(|_, (_, queued_at)| queued_at.elapsed() < QUEUED_RESPONSE_TTL);
+            queued.insert(request_id_hash, (bytes as u64, Instant::now()));
+        }
+    }
+
+    pub fn response_sent(&self, peer: PeerId, request_id_hash: u64) {
+        let bytes = self
+            .queued_responses
+            .lock()
+            .ok()
+            .and_then(|mut queued| queued.remove(&request_id_hash))
+            .map(|(bytes, _)| bytes)
+            .unwrap_or_default();
+        self.update(peer, false, |stats| stats.bytes_sent += bytes);
+    }
+
+    pub fn is_connected(&self, peer: &PeerId) -> bool {
+        self.peers
+            .lock()
+            .ok()
+            .and_then(|peers| peers.get(peer).map(|stats| stats.connected))
+            .unwrap_or_default()
+    }
+
+    /// stats of every peer exchanged with, ordered by peer id
+    pub fn stats(&self) -> Vec<PeerStats> {
+        let mut stats = self
+            .peers
+            .lock()
+            .map(|peers| peers.values().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        stats.sort_by(|a, b| a.peer_id.cmp(&b.peer_id));
+        stats
+    }
+
+    /// peer stats in the prometheus text format
+    pub fn render_metrics(&self) -> String {
+        let stats = self.stats();
+        let mut metrics = String::new();
+        let mut family =
+            |name: &str, kind: &str, help: &str, value: fn(&PeerStats) -> Option<String>| {
+                let _ = writeln!(metrics, "# HELP {name} {help}");
+                let _ = writeln!(metrics, "# TYPE {name} {kind}");
+                for stats in &stats {
+                    if let Some(value) = value(stats) {
+                        let _ = writeln!(metrics, "{name}{{peer=\"{}\"}} {value}", stats.peer_id);
+                    }
+                }
+            };
+        family(
+            "vane_peer_connected",
+            "gauge",
+            "whether the peer is connected",
+            |stats| Some(u8::from(stats.connected).to_string()),
+        );
+        family(
+            "vane_peer_rtt_seconds",
+            "gauge",
+            "round trip time of the last ping",
+            |stats| stats.rtt_ms.map(|rtt| (rtt as f64 / 1000.0).to_string()),
+        );
+        family(
+            "vane_peer_requests_sent_total",
+            "counter",
+            "requests sent to the peer",
+            |stats| Some(stats.requests_sent.to_string()),
+        );
+        family(
+            "vane_peer_requests_failed_total",
+            "counter",
+            "requests sent to the peer that failed",
+            |stats| Some(stats.requests_failed.to_string()),
+        );
+        family(
+            "vane_peer_responses_received_total",
+            "counter",
+            "responses received from the peer",
+            |stats| Some(stats.responses_received.to_string()),
+        );
+        family(
+            "vane_peer_requests_received_total",
+            "counter",
+            "requests received from the peer",
+            |stats| Some(stats.requests_received.to_string()),
+        );
+        family(
+            "vane_peer_request_success_ratio",
+            "gauge",
+            "answered over failed and answered requests",
+            |stats| stats.success_rate.map(|rate| rate.to_string()),
+        );
+        family(
+            "vane_peer_last_seen_timestamp_seconds",
+            "gauge",
+            "unix time the peer was last heard from",
+            |stats| stats.last_seen.map(|seen| seen.to_string()),
+        );
+        family(
+            "vane_peer_sent_bytes_total",
+            "counter",
+            "bytes sent to the peer",
+            |stats| Some(stats.bytes_sent.to_string()),
+        );
+        family(
+            "vane_peer_received_bytes_total",
+            "counter",
+            "bytes received from the peer",
+            |stats| Some(stats.bytes_received.to_string()),
+        );
+        metrics
+    }
+}
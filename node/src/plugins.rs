@@ -0,0 +1,166 @@
+// operator validation plugins, wasm modules run at the pre-attestation and pre-submission hooks
+// every call instantiates the module in a fresh store with a fuel budget and a memory cap, so a
+// plugin keeps no state between transfers and a looping or growing one can't stall the node. the
+// calls run on the blocking pool, off the rpc workers. a plugin failing to answer denies the
+// transfer, compliance checks fail closed
+
+use anyhow::anyhow;
+use log::{error, info};
+use primitives::data_structure::TxStateMachine;
+use primitives::plugins::{PluginDecision, PluginHook};
+use std::path::Path;
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// wasm instructions a plugin may run per call
+pub const PLUGIN_FUEL: u64 = 50_000_000;
+/// linear memory a plugin may grow to per call, in bytes
+pub const PLUGIN_MEMORY: usize = 16 * 1024 * 1024;
+/// longest decision json read back from a plugin
+const MAX_DECISION_LEN: usize = 64 * 1024;
+
+#[derive(Clone)]
+struct Plugin {
+    /// file stem of the module
+    name: String,
+    module: Module,
+    hooks: Vec<PluginHook>,
+}
+
+/// plugins loaded at start, in load order
+#[derive(Clone)]
+pub struct Plugins {
+    engine: Engine,
+    plugins: Vec<Plugin>,
+}
+
+impl Default for Plugins {
+    fn default() -> Self {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        Self {
+            engine: Engine::new(&config),
+            plugins: vec![],
+        }
+    }
+}
+
+impl Plugins {
+    /// compile the module at `path`, named after its file stem
+    pub fn load(&mut self, path: &Path) -> Result<(), anyhow::Error> {
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or(anyhow!("invalid plugin path {}", path.display()))?
+            .to_string();
+        let wasm = std::fs::read(path)
+            .map_err(|err| anyhow!("failed to read plugin {name}; caused by: {err}"))?;
+        let module = Module::new(&self.engine, &wasm[..])
+            .map_err(|err| anyhow!("invalid plugin {name}; caused by: {err}"))?;
+        let exports: Vec<&str> = module.exports().map(|export| export.name()).collect();
+        for required in ["memory", "alloc"] {
+            if !exports.contains(&required) {
+                Err(anyhow!("plugin {name} does not export {required}"))?
+            }
+        }
+        let hooks: Vec<PluginHook> = PluginHook::ALL
+            .into_iter()
+            .filter(|hook| exports.contains(&hook.export()))
+            .collect();
+        if hooks.is_empty() {
+            Err(anyhow!("plugin {name} exports no hook"))?
+        }
+        info!("loaded plugin {name} for {hooks:?}");
+        self.plugins.push(Plugin {
+            name,
+            module,
+            hooks,
+        });
+        Ok(())
+    }
+
+    /// decisions of the plugins exporting `hook` about `tx`, named with their plugin
+    pub async fn decide(
+        &self,
+        hook: PluginHook,
+        tx: &TxStateMachine,
+    ) -> Result<Vec<(String, PluginDecision)>, anyhow::Error> {
+        if !self
+            .plugins
+            .iter()
+            .any(|plugin| plugin.hooks.contains(&hook))
+        {
+            return Ok(vec![]);
+        }
+        let (plugins, tx) = (self.clone(), tx.clone());
+        tokio::task::spawn_blocking(move || plugins.decide_blocking(hook, &tx))
+            .await
+            .map_err(|err| anyhow!("plugins failed to run at {hook:?}; caused by: {err}"))
+    }
+
+    fn decide_blocking(
+        &self,
+        hook: PluginHook,
+        tx: &TxStateMachine,
+    ) -> Vec<(String, PluginDecision)> {
+        let plugins = self
+            .plugins
+            .iter()
+            .filter(|plugin| plugin.hooks.contains(&hook));
+        let input = serde_json::to_vec(tx).unwrap_or_default();
+        plugins
+            .map(|plugin| {
+                let decision = self.call(plugin, hook, &input).unwrap_or_else(|err| {
+                    error!(
+                        "plugin {} failed at {hook:?}; caused by: {err}",
+                        plugin.name
+                    );
+                    PluginDecision::Deny {
+                        reason: format!("plugin failed to answer, {err}"),
+                    }
+                });
+                (plugin.name.clone(), decision)
+            })
+            .collect()
+    }
+
+    fn call(
+        &self,
+        plugin: &Plugin,
+        hook: PluginHook,
+        input: &[u8],
+    ) -> Result<PluginDecision, anyhow::Error> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(PLUGIN_MEMORY)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store
+            .add_fuel(PLUGIN_FUEL)
+            .map_err(|err| anyhow!("fuel metering disabled; caused by: {err}"))?;
+        let instance = Linker::<StoreLimits>::new(&self.engine)
+            .instantiate(&mut store, &plugin.module)?
+            .start(&mut store)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or(anyhow!("no memory export"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc")?;
+        let decide = instance.get_typed_func::<(i32, i32), i64>(&store, hook.export())?;
+
+        let len = i32::try_from(input.len())?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input)
+            .map_err(|err| anyhow!("tx out of plugin memory; caused by: {err}"))?;
+        let packed = decide.call(&mut store, (ptr, len))? as u64;
+        let (ptr, len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        if len > MAX_DECISION_LEN {
+            Err(anyhow!("decision of {len} bytes"))?
+        }
+        let mut decision = vec![0; len];
+        memory
+            .read(&store, ptr, &mut decision)
+            .map_err(|err| anyhow!("decision out of plugin memory; caused by: {err}"))?;
+        Ok(serde_json::from_slice(&decision)?)
+    }
+}
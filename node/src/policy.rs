@@ -0,0 +1,85 @@
+// operator policy evaluation and second approver sign-off
+// the rules and the facts they need are read from the db on every evaluation, so a policy update
+// also applies to txs already in flight
+
+use alloy::primitives::{Address, Signature as EcdsaSignature};
+use anyhow::anyhow;
+use db::{DbWorker, DbWorkerInterface};
+use primitives::data_structure::{TxStateMachine, TxStatus};
+use primitives::policy::{evaluate, parse_policies, PolicyAction, PolicyContext, RuleOutcome};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// outcomes of the operator policies applying to `tx` at the current time
+pub async fn policy_outcomes(
+    db: &DbWorker,
+    tx: &TxStateMachine,
+) -> Result<Vec<RuleOutcome>, anyhow::Error> {
+    let Some(document) = db.get_policies().await? else {
+        return Ok(vec![]);
+    };
+    let rules = parse_policies(&document)
+        .map_err(|err| anyhow!("stored policies are invalid; caused by: {err}"))?;
+    if rules.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let is_contact = db
+        .get_saved_user_peers(tx.receiver_address.to_string())
+        .await
+        .is_ok();
+    let is_new_recipient = !db
+        .get_sender_tx_events(
+            tx.sender_address.to_string(),
+            Some(tx.receiver_address.to_string()),
+        )
+        .await?
+        .iter()
+        .any(|event| matches!(event.status, TxStatus::TxSubmissionPassed(_)));
+    let hour_utc = (SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() / 3600 % 24) as u8;
+
+    let context = PolicyContext {
+        is_contact,
+        is_new_recipient,
+        hour_utc,
+    };
+    Ok(evaluate(&rules, tx, &context))
+}
+
+/// designated approver signing off sender confirmed txs before they are submitted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecondApprover {
+    /// evm account of the approver
+    pub account: String,
+    /// every tx needs the sign-off, otherwise only those a `RequireSecondApprover` rule applied to
+    pub all_txs: bool,
+}
+
+/// whether `tx` is held for the approver once the sender confirmed
+pub fn requires_second_approval(approver: Option<&SecondApprover>, tx: &TxStateMachine) -> bool {
+    approver.is_some_and(|approver| approver.all_txs)
+        || tx
+            .policy_outcomes
+            .iter()
+            .any(|outcome| outcome.action == PolicyAction::RequireSecondApprover)
+}
+
+/// check `signature` is the approver EIP-191 signature of the tx second approval message
+pub fn verify_second_approval(
+    approver: &SecondApprover,
+    tx: &TxStateMachine,
+    signature: &[u8],
+) -> Result<(), anyhow::Error> {
+    let account: Address = approver
+        .account
+        .parse()
+        .map_err(|err| anyhow!("invalid approver account; caused by: {err}"))?;
+    let signature = EcdsaSignature::try_from(signature)
+        .map_err(|err| anyhow!("invalid approver signature; caused by: {err}"))?;
+    let recovered = signature
+        .recover_address_from_msg(tx.second_approval_message())
+        .map_err(|err| anyhow!("approver signature recovery failed; caused by: {err}"))?;
+    if recovered != account {
+        Err(anyhow!("tx was not signed off by the designated approver"))?
+    }
+    Ok(())
+}
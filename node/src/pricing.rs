@@ -0,0 +1,118 @@
+// usd prices of the tokens for fiat denominated transfers
+// the amount of a fiat transfer is computed from the price of its token when it is initiated and the
+// price is looked up again when the sender confirms and right before the tx is queued for submission,
+// the transfer is refused once the price moved past the slippage the sender allowed
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use primitives::data_structure::{Token, TxStateMachine};
+use primitives::errors::VaneRpcError;
+use primitives::fiat::{FiatQuote, DEFAULT_MAX_SLIPPAGE_BPS};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+/// current usd price of a whole token
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    /// price of `token` in usd, `None` when the source does not price it
+    async fn usd_price(&self, token: Token) -> Result<Option<f64>, anyhow::Error>;
+}
+
+/// prices set by the operator, e.g. from `--usd-price`, and updated in place
+#[derive(Clone, Default)]
+pub struct StaticPrices {
+    prices: Arc<RwLock<HashMap<Token, f64>>>,
+}
+
+impl StaticPrices {
+    pub fn new(prices: impl IntoIterator<Item = (Token, f64)>) -> Self {
+        Self {
+            prices: Arc::new(RwLock::new(prices.into_iter().collect())),
+        }
+    }
+
+    /// prices keyed by the token names of `usd_value`
+    pub fn from_names(prices: &BTreeMap<String, f64>) -> Self {
+        Self::new(
+            prices
+                .iter()
+                .map(|(token, price)| (Token::from(token.as_str()), *price)),
+        )
+    }
+
+    pub fn set(&self, token: Token, price: f64) {
+        if let Ok(mut prices) = self.prices.write() {
+            prices.insert(token, price);
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for StaticPrices {
+    async fn usd_price(&self, token: Token) -> Result<Option<f64>, anyhow::Error> {
+        let prices = self
+            .prices
+            .read()
+            .map_err(|_| anyhow!("usd prices lock poisoned"))?;
+        Ok(prices.get(&token).copied())
+    }
+}
+
+/// locks and checks the prices of the fiat denominated transfers
+#[derive(Clone)]
+pub struct FiatPricing {
+    source: Arc<dyn PriceSource>,
+    /// slippage of the transfers whose sender picks none, in basis points
+    default_slippage_bps: u32,
+}
+
+impl Default for FiatPricing {
+    fn default() -> Self {
+        Self::new(Arc::new(StaticPrices::default()), DEFAULT_MAX_SLIPPAGE_BPS)
+    }
+}
+
+impl FiatPricing {
+    pub fn new(source: Arc<dyn PriceSource>, default_slippage_bps: u32) -> Self {
+        Self {
+            source,
+            default_slippage_bps,
+        }
+    }
+
+    async fn price(&self, token: Token) -> Result<f64, VaneRpcError> {
+        match self.source.usd_price(token).await {
+            Ok(Some(price)) => Ok(price),
+            Ok(None) => Err(VaneRpcError::InvalidParams {
+                reason: format!("no usd price of {token:?}, fiat transfers are unavailable"),
+            }),
+            Err(err) => Err(VaneRpcError::Internal {
+                reason: format!("usd price of {token:?} unavailable; caused by: {err}"),
+            }),
+        }
+    }
+
+    /// lock the current price of `token` for a transfer worth `usd_cents`, returns the quote and the
+    /// token amount it is worth
+    pub async fn quote(
+        &self,
+        usd_cents: u64,
+        token: Token,
+        max_slippage_bps: Option<u32>,
+    ) -> Result<(FiatQuote, u128), VaneRpcError> {
+        let price = self.price(token).await?;
+        let slippage = max_slippage_bps.unwrap_or(self.default_slippage_bps);
+        let quote = FiatQuote::lock(usd_cents, price, slippage)?;
+        let amount = quote.token_amount(token)?;
+        Ok((quote, amount))
+    }
+
+    /// refuse a fiat denominated `tx` whose token price moved past its slippage
+    pub async fn check(&self, tx: &TxStateMachine) -> Result<(), VaneRpcError> {
+        let Some(quote) = tx.fiat_quote else {
+            return Ok(());
+        };
+        let token = tx.token.unwrap_or(Token::native(tx.network));
+        quote.check(self.price(token).await?)
+    }
+}
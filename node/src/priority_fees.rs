@@ -0,0 +1,107 @@
+// priority fee oracle for solana
+// tracks the lowest compute unit price that landed in each recent slot through
+// `getRecentPrioritizationFees` and turns them into low / medium / high compute budget tiers, raised
+// while the network is congested. the compute unit price cap of the node bounds every tier
+
+use alloc::sync::Arc;
+use alloy::providers::{Provider, ProviderBuilder, ReqwestProvider};
+use anyhow::anyhow;
+use primitives::compute_budget::{transfer_compute_units, ComputeBudget, ComputeBudgetTiers};
+use primitives::data_structure::Token;
+use primitives::fees::FeeTier;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// price that landed in a recent slot, in micro lamports per compute unit
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SlotPrioritizationFee {
+    prioritization_fee: u64,
+}
+
+#[derive(Clone)]
+pub struct PriorityFeeOracle {
+    provider: ReqwestProvider,
+    refresh_interval: Duration,
+    /// compute unit limit of every transfer, the presets of the token otherwise
+    compute_unit_limit: Option<u32>,
+    /// most micro lamports per compute unit the node accepts to build txs with
+    max_compute_unit_price: Option<u64>,
+    latest: Arc<Mutex<Option<(Instant, ComputeBudgetTiers)>>>,
+}
+
+impl PriorityFeeOracle {
+    /// a few slots, the recent prices barely move in between
+    pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+    pub fn new(rpc_url: &str) -> Result<Self, anyhow::Error> {
+        let rpc_url = rpc_url
+            .parse()
+            .map_err(|err| anyhow!("Solana rpc url parse error: {err}"))?;
+        Ok(Self {
+            provider: ProviderBuilder::new().on_http(rpc_url),
+            refresh_interval: Self::DEFAULT_REFRESH_INTERVAL,
+            compute_unit_limit: None,
+            max_compute_unit_price: None,
+            latest: Arc::new(Default::default()),
+        })
+    }
+
+    pub fn with_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    /// request `limit` compute units for every transfer instead of the presets of its token
+    pub fn with_compute_unit_limit(mut self, limit: u32) -> Self {
+        self.compute_unit_limit = Some(limit);
+        self
+    }
+
+    /// refuse to build txs with a compute unit price above `cap` micro lamports, whatever the tier
+    pub fn with_max_compute_unit_price(mut self, cap: u64) -> Self {
+        self.max_compute_unit_price = Some(cap);
+        self
+    }
+
+    pub fn max_compute_unit_price(&self) -> Option<u64> {
+        self.max_compute_unit_price
+    }
+
+    /// compute budget tiers of the next slots
+    pub async fn compute_budget_tiers(&self) -> Result<ComputeBudgetTiers, anyhow::Error> {
+        let mut latest = self.latest.lock().await;
+        if let Some((fetched_at, tiers)) = latest.as_ref() {
+            if fetched_at.elapsed() < self.refresh_interval {
+                return Ok(*tiers);
+            }
+        }
+
+        let fees: Vec<SlotPrioritizationFee> = self
+            .provider
+            .raw_request("getRecentPrioritizationFees".into(), ())
+            .await
+            .map_err(|err| anyhow!("failed to fetch prioritization fees; caused by: {err}"))?;
+        let fees = fees
+            .iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect::<Vec<u64>>();
+        let tiers = ComputeBudgetTiers::from_recent_prioritization_fees(&fees);
+        *latest = Some((Instant::now(), tiers));
+        Ok(tiers)
+    }
+
+    /// compute budget instructions of a transfer of `token` at `tier`
+    pub async fn compute_budget(
+        &self,
+        token: Token,
+        tier: FeeTier,
+    ) -> Result<ComputeBudget, anyhow::Error> {
+        let limit = self
+            .compute_unit_limit
+            .unwrap_or(transfer_compute_units(token));
+        let tiers = self.compute_budget_tiers().await?;
+        Ok(tiers.select(tier, limit, self.max_compute_unit_price)?)
+    }
+}
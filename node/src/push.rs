@@ -0,0 +1,52 @@
+// push notification bridge
+// a relay queuing a request for an offline mobile node calls the configured push gateway, a webhook
+// in front of FCM or APNs, with the device token the mobile node registered with. the notification
+// only tells a request is waiting, the sealed request never leaves the relay
+
+use anyhow::anyhow;
+use serde::Serialize;
+use std::time::Duration;
+
+/// body posted to the push gateway
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushNotification {
+    /// device token of the mobile app
+    pub token: String,
+    pub peer_id: String,
+    /// requests waiting for the mobile node, this one included
+    pub queued: usize,
+}
+
+#[derive(Clone)]
+pub struct PushGateway {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl PushGateway {
+    /// the relay does not wait on the gateway, a slow one only delays the notification
+    pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn new(url: impl Into<String>) -> Result<Self, anyhow::Error> {
+        let client = reqwest::ClientBuilder::new()
+            .timeout(Self::TIMEOUT)
+            .build()
+            .map_err(|err| anyhow!("failed to build the push gateway client; caused by: {err}"))?;
+        Ok(Self {
+            url: url.into(),
+            client,
+        })
+    }
+
+    pub async fn notify(&self, notification: &PushNotification) -> Result<(), anyhow::Error> {
+        self.client
+            .post(&self.url)
+            .json(notification)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| anyhow!("push gateway call failed; caused by: {err}"))?;
+        Ok(())
+    }
+}
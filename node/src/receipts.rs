@@ -0,0 +1,149 @@
+// delivery receipts
+// a receiver node acknowledges every attestation request right away, before its user confirms,
+// with the hash of the request. the sender marks the tx as delivered, telling a receiver waiting on
+// its user apart from an unreachable one. only the node a request was sent to can acknowledge it,
+// requests a relay delivered are not acknowledged as the relay is not their sender. a receiver
+// registered from several devices gets the request on each of them, once one answered the sender
+// withdraws it from the others over the same protocol
+
+use crate::p2p::VaneBehaviour;
+use libp2p::request_response::{Event, Message};
+use libp2p::{PeerId, Swarm};
+use log::{debug, warn};
+use primitives::data_structure::{ReceiptMessage, SwarmMessage};
+use primitives::envelope;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// protocol of the receipts and cancellations of attestation requests
+pub const RECEIPT_PROTOCOL: &str = "/vane-receipt/1.0.0";
+/// most requests tracked each way, the oldest are forgotten first
+const MAX_TRACKED_REQUESTS: usize = 1024;
+
+#[derive(Default)]
+struct ReceiptsState {
+    /// request hash, receiver node and tx nonce of the requests sent, until the tx is answered
+    sent: VecDeque<([u8; 32], PeerId, u32)>,
+    /// request hash, sender node and inbound request id hash of the requests received
+    received: VecDeque<([u8; 32], PeerId, u64)>,
+}
+
+#[derive(Clone, Default)]
+pub struct Receipts {
+    state: Arc<Mutex<ReceiptsState>>,
+}
+
+fn track<T>(requests: &mut VecDeque<T>, request: T) {
+    if requests.len() >= MAX_TRACKED_REQUESTS {
+        requests.pop_front();
+    }
+    requests.push_back(request);
+}
+
+impl Receipts {
+    fn state(&self) -> std::sync::MutexGuard<'_, ReceiptsState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// wait for the receipt of the encoded `request` of the tx `tx_nonce` sent to `receiver`
+    pub fn expect(&self, receiver: PeerId, tx_nonce: u32, request: &[u8]) {
+        let hash = sp_core::hashing::blake2_256(request);
+        track(&mut self.state().sent, (hash, receiver, tx_nonce));
+    }
+
+    /// acknowledge the `request` just received from `sender` as `inbound_req_id`
+    pub fn acknowledge(
+        &self,
+        swarm: &mut Swarm<VaneBehaviour>,
+        sender: PeerId,
+        inbound_req_id: u64,
+        request: &[u8],
+    ) {
+        let hash = sp_core::hashing::blake2_256(request);
+        track(&mut self.state().received, (hash, sender, inbound_req_id));
+        swarm
+            .behaviour_mut()
+            .receipts
+            .send_request(&sender, envelope::seal(&ReceiptMessage::Delivered(hash)));
+    }
+
+    /// withdraw the requests of the tx `tx_nonce` from every receiver node it was sent to, the one
+    /// that answered ignores it
+    pub fn cancel(&self, swarm: &mut Swarm<VaneBehaviour>, tx_nonce: u32) {
+        let cancelled: VecDeque<_> = {
+            let mut state = self.state();
+            let (cancelled, kept) = state
+                .sent
+                .drain(..)
+                .partition(|(_, _, nonce)| *nonce == tx_nonce);
+            state.sent = kept;
+            cancelled
+        };
+        for (hash, receiver, _) in cancelled {
+            swarm
+                .behaviour_mut()
+                .receipts
+                .send_request(&receiver, envelope::seal(&ReceiptMessage::Cancelled(hash)));
+        }
+    }
+
+    /// the delivery or cancellation a receipt request carries, `None` when not expected from `peer`
+    pub fn handle_event(
+        &self,
+        swarm: &mut Swarm<VaneBehaviour>,
+        event: Event<Vec<u8>, Result<Vec<u8>, anyhow::Error>>,
+    ) -> Option<SwarmMessage> {
+        match event {
+            Event::Message {
+                peer,
+                message:
+                    Message::Request {
+                        request, channel, ..
+                    },
+            } => {
+                let _ = swarm
+                    .behaviour_mut()
+                    .receipts
+                    .send_response(channel, Ok(vec![]));
+                let message = match envelope::decode::<ReceiptMessage>(&request) {
+                    Ok(message) => message,
+                    Err(err) => {
+                        warn!(target: "receipts", "undecodable receipt from {peer}: {err:?}");
+                        return None;
+                    }
+                };
+                let mut state = self.state();
+                let message = match message {
+                    ReceiptMessage::Delivered(hash) => state
+                        .sent
+                        .iter()
+                        .find(|(sent, receiver, _)| *sent == hash && *receiver == peer)
+                        .map(|(_, _, tx_nonce)| SwarmMessage::Delivered {
+                            tx_nonce: *tx_nonce,
+                        }),
+                    ReceiptMessage::Cancelled(hash) => state
+                        .received
+                        .iter()
+                        .position(|(received, sender, _)| *received == hash && *sender == peer)
+                        .and_then(|position| state.received.remove(position))
+                        .map(|(_, _, inbound_req_id)| SwarmMessage::Cancelled { inbound_req_id }),
+                };
+                if message.is_none() {
+                    debug!(target: "receipts", "dropping a receipt not expected from {peer}");
+                }
+                message
+            }
+            Event::Message { .. } | Event::ResponseSent { .. } => None,
+            Event::OutboundFailure { peer, error, .. } => {
+                debug!(target: "receipts", "receipt to {peer} failed: {error:?}");
+                None
+            }
+            Event::InboundFailure { peer, error, .. } => {
+                debug!(target: "receipts", "receipt from {peer} failed: {error:?}");
+                None
+            }
+        }
+    }
+}
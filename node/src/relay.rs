@@ -0,0 +1,772 @@
+// relay assisted delivery
+// a mobile node designates an always-on node of the same user as its relay and registers with it
+// each time they connect. senders that can't reach the mobile node seal the attestation request to
+// the mobile encryption key and forward it to the relay, which queues it until the mobile node
+// connects and then delivers it over the attestation protocol. the reply travels back sealed with
+// the key of the request, so the relay only ever sees ciphertext. routes are signed by the mobile
+// node identity and shared over peer exchange, routes and queued requests only live as long as the
+// node runs. a route expires a week after it was signed and only a later signed route replaces it,
+// the mobile node signs a fresh one each time it registers. a relay with a push gateway wakes the
+// mobile app up for each queued request
+
+use crate::p2p::VaneBehaviour;
+use crate::push::{PushGateway, PushNotification};
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
+use anyhow::anyhow;
+use curve25519_dalek::MontgomeryPoint;
+use libp2p::identity::{Keypair, PublicKey};
+use libp2p::multiaddr::Protocol;
+use libp2p::request_response::{Event, Message, OutboundRequestId, ResponseChannel};
+use libp2p::{Multiaddr, PeerId, Swarm};
+use log::{debug, info, warn};
+use primitives::data_structure::SwarmMessage;
+use primitives::envelope;
+use primitives::relay::{
+    RelayMessage, RelayRoute, SealedReply, SealedRequest, SEALED_REQUEST_PREFIX,
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::Sender;
+
+/// protocol of the requests between senders, relays and mobile nodes
+pub const RELAY_PROTOCOL: &str = "/vane-relay/1.0.0";
+/// how long a sender waits for the mobile node to connect and reply through the relay
+pub const RELAY_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+/// most requests a relay queues for one mobile node
+pub const MAX_QUEUED_REQUESTS: usize = 64;
+/// routes signed longer ago are dropped
+pub const ROUTE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// most routes learned over peer exchange, the earliest signed are dropped first
+pub const MAX_ROUTES: usize = 4096;
+/// most requests in flight per direction, the oldest are dropped first
+pub const MAX_PENDING_REQUESTS: usize = 1024;
+/// routes signed further ahead of the local clock are refused
+const MAX_CLOCK_DRIFT: Duration = Duration::from_secs(300);
+
+type Channel = ResponseChannel<Result<Vec<u8>, anyhow::Error>>;
+
+/// aes key both ends of a sealed request derive from their x25519 keys
+pub(crate) fn shared_key(
+    secret: [u8; 32],
+    public: [u8; 32],
+    ephemeral_key: [u8; 32],
+    encryption_key: [u8; 32],
+) -> Result<[u8; 32], anyhow::Error> {
+    let shared = MontgomeryPoint(public).mul_clamped(secret);
+    if shared.0 == [0; 32] {
+        Err(anyhow!("low order encryption key"))?
+    }
+    Ok(sp_core::hashing::blake2_256(
+        &[shared.0, ephemeral_key, encryption_key].concat(),
+    ))
+}
+
+pub(crate) fn encrypt(
+    key: &[u8; 32],
+    plaintext: &[u8],
+) -> Result<([u8; 12], Vec<u8>), anyhow::Error> {
+    let nonce: [u8; 12] = rand::random();
+    let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|err| anyhow!("failed to seal; caused by: {err}"))?;
+    Ok((nonce, ciphertext))
+}
+
+pub(crate) fn decrypt(
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, anyhow::Error> {
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|err| anyhow!("failed to open; caused by: {err}"))
+}
+
+/// seal `request` to the mobile node of `route`, returns the key its reply is sealed with
+pub fn seal_request(
+    route: &RelayRoute,
+    request: &[u8],
+) -> Result<(SealedRequest, [u8; 32]), anyhow::Error> {
+    let ephemeral_secret: [u8; 32] = rand::random();
+    let ephemeral_key = MontgomeryPoint::mul_base_clamped(ephemeral_secret).to_bytes();
+    let key = shared_key(
+        ephemeral_secret,
+        route.encryption_key,
+        ephemeral_key,
+        route.encryption_key,
+    )?;
+    let (nonce, ciphertext) = encrypt(&key, request)?;
+    let sealed = SealedRequest {
+        mobile_peer_id: route.mobile_peer_id.clone(),
+        ephemeral_key,
+        nonce,
+        ciphertext,
+    };
+    Ok((sealed, key))
+}
+
+/// open a request sealed to the x25519 `secret`, returns the key to seal the reply with
+pub fn open_request(
+    secret: [u8; 32],
+    sealed: &SealedRequest,
+) -> Result<(Vec<u8>, [u8; 32]), anyhow::Error> {
+    let encryption_key = MontgomeryPoint::mul_base_clamped(secret).to_bytes();
+    let key = shared_key(
+        secret,
+        sealed.ephemeral_key,
+        sealed.ephemeral_key,
+        encryption_key,
+    )?;
+    Ok((decrypt(&key, &sealed.nonce, &sealed.ciphertext)?, key))
+}
+
+pub fn seal_reply(key: &[u8; 32], reply: &[u8]) -> Result<SealedReply, anyhow::Error> {
+    let (nonce, ciphertext) = encrypt(key, reply)?;
+    Ok(SealedReply { nonce, ciphertext })
+}
+
+pub fn open_reply(key: &[u8; 32], sealed: &SealedReply) -> Result<Vec<u8>, anyhow::Error> {
+    decrypt(key, &sealed.nonce, &sealed.ciphertext)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// check the route is signed by its mobile node and has not expired, returns the mobile peer id
+pub fn verify_relay_route(route: &RelayRoute) -> Result<PeerId, anyhow::Error> {
+    let now = unix_now();
+    if route.signed_at > now + MAX_CLOCK_DRIFT.as_secs() {
+        Err(anyhow!("relay route is signed ahead of the local clock"))?
+    }
+    if route.signed_at + ROUTE_TTL.as_secs() < now {
+        Err(anyhow!("relay route has expired"))?
+    }
+    let public_key = PublicKey::try_decode_protobuf(&route.public_key)
+        .map_err(|err| anyhow!("invalid route public key; caused by: {err}"))?;
+    let mobile = PeerId::from_str(&route.mobile_peer_id)
+        .map_err(|err| anyhow!("invalid route mobile peer id; caused by: {err}"))?;
+    if public_key.to_peer_id() != mobile {
+        Err(anyhow!("route public key is not the key of {mobile}"))?
+    }
+    PeerId::from_str(&route.relay_peer_id)
+        .map_err(|err| anyhow!("invalid route relay peer id; caused by: {err}"))?;
+    Multiaddr::from_str(&route.relay_multi_addr)
+        .map_err(|err| anyhow!("invalid route relay multi addr; caused by: {err}"))?;
+    if !public_key.verify(route.message().as_bytes(), &route.signature) {
+        Err(anyhow!("relay route was not signed by {mobile}"))?
+    }
+    Ok(mobile)
+}
+
+/// requests in flight, dropped once the relay timeout passed or `MAX_PENDING_REQUESTS` newer ones
+/// are pending
+struct Pending<K, V> {
+    entries: HashMap<K, (V, Instant)>,
+}
+
+impl<K, V> Default for Pending<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Copy + Eq + Hash, V> Pending<K, V> {
+    fn insert(&mut self, key: K, value: V) {
+        let now = Instant::now();
+        self.entries
+            .retain(|_, (_, at)| now.duration_since(*at) < RELAY_TIMEOUT);
+        if self.entries.len() >= MAX_PENDING_REQUESTS {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, at))| *at)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (value, now));
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.entries.remove(key).map(|(value, _)| value)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+}
+
+#[derive(Default)]
+struct RelayState {
+    /// relay this node registers with, along with its address
+    designated: Option<(PeerId, Multiaddr)>,
+    /// mobile nodes this node relays for
+    relay_for: HashSet<PeerId>,
+    /// verified routes, registered with this node, learned over peer exchange or its own
+    routes: HashMap<PeerId, RelayRoute>,
+    /// forwarded requests waiting for their mobile node to connect
+    queued: HashMap<PeerId, VecDeque<(SealedRequest, Channel)>>,
+    /// requests delivered to a mobile node, keyed by the delivery request id
+    delivered: Pending<OutboundRequestId, Channel>,
+    /// reply keys of the requests this node forwarded to a relay
+    forwarded: Pending<OutboundRequestId, [u8; 32]>,
+    /// reply keys of the relayed requests this node received, keyed by the inbound request id hash
+    received: Pending<u64, [u8; 32]>,
+    /// gateway notifying the mobile apps of their queued requests
+    push_gateway: Option<PushGateway>,
+    /// device token this node registers with its relay
+    push_token: Option<String>,
+    /// device tokens of the registered mobile nodes
+    push_tokens: HashMap<PeerId, String>,
+}
+
+#[derive(Clone)]
+pub struct Relay {
+    node_id: PeerId,
+    keypair: Keypair,
+    encryption_secret: [u8; 32],
+    state: Arc<Mutex<RelayState>>,
+}
+
+impl Relay {
+    /// the encryption key is derived from the node identity, so it survives restarts
+    pub fn new(keypair: Keypair) -> Result<Self, anyhow::Error> {
+        let identity = keypair
+            .to_protobuf_encoding()
+            .map_err(|err| anyhow!("failed to encode keypair; caused by: {err}"))?;
+        let encryption_secret = sp_core::hashing::blake2_256(
+            &[b"vane relay encryption".as_slice(), &identity].concat(),
+        );
+        Ok(Self {
+            node_id: keypair.public().to_peer_id(),
+            keypair,
+            encryption_secret,
+            state: Default::default(),
+        })
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, RelayState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// route the requests to this node through the always-on node at `relay`, an address ending
+    /// with its /p2p peer id
+    pub fn use_relay(&self, relay: Multiaddr) -> Result<(), anyhow::Error> {
+        let Some(Protocol::P2p(relay_peer_id)) = relay.iter().last() else {
+            Err(anyhow!(
+                "relay address {relay} should end with /p2p/<peer id>"
+            ))?
+        };
+        let route = self.signed_route(relay_peer_id, &relay, unix_now())?;
+        let mut state = self.state();
+        state.designated = Some((relay_peer_id, relay));
+        state.routes.insert(self.node_id, route);
+        Ok(())
+    }
+
+    fn signed_route(
+        &self,
+        relay_peer_id: PeerId,
+        relay: &Multiaddr,
+        signed_at: u64,
+    ) -> Result<RelayRoute, anyhow::Error> {
+        let mut route = RelayRoute {
+            mobile_peer_id: self.node_id.to_base58(),
+            relay_peer_id: relay_peer_id.to_base58(),
+            relay_multi_addr: relay.to_string(),
+            public_key: self.keypair.public().encode_protobuf(),
+            encryption_key: MontgomeryPoint::mul_base_clamped(self.encryption_secret).to_bytes(),
+            signed_at,
+            signature: vec![],
+        };
+        route.signature = self
+            .keypair
+            .sign(route.message().as_bytes())
+            .map_err(|err| anyhow!("failed to sign the relay route; caused by: {err}"))?;
+        Ok(route)
+    }
+
+    /// queue the requests sent to the mobile node `mobile` while it is offline
+    pub fn relay_for(&self, mobile: PeerId) {
+        self.state().relay_for.insert(mobile);
+    }
+
+    /// notify the registered mobile apps through `gateway` when a request is queued for them
+    pub fn use_push_gateway(&self, gateway: PushGateway) {
+        self.state().push_gateway = Some(gateway);
+    }
+
+    /// device token of the app running this node, sent to the relay along with the route
+    pub fn use_push_token(&self, token: impl Into<String>) {
+        self.state().push_token = Some(token.into());
+    }
+
+    /// whether the node relays through or for another node, both pin its identity in signed routes
+    pub fn pins_identity(&self) -> bool {
+        let state = self.state();
+        state.designated.is_some() || !state.relay_for.is_empty()
+    }
+
+    pub fn route(&self, mobile: &PeerId) -> Option<RelayRoute> {
+        let expired_at = unix_now().saturating_sub(ROUTE_TTL.as_secs());
+        self.state()
+            .routes
+            .get(mobile)
+            .filter(|route| route.signed_at >= expired_at)
+            .cloned()
+    }
+
+    /// routes shared over peer exchange
+    pub fn routes(&self) -> Vec<RelayRoute> {
+        let expired_at = unix_now().saturating_sub(ROUTE_TTL.as_secs());
+        self.state()
+            .routes
+            .values()
+            .filter(|route| route.signed_at >= expired_at)
+            .cloned()
+            .collect()
+    }
+
+    /// store a route learned over peer exchange, returns whether it verified and is later than the
+    /// stored one
+    pub fn learn(&self, route: RelayRoute) -> bool {
+        let Ok(mobile) = verify_relay_route(&route) else {
+            return false;
+        };
+        // the own route and the routes registered with this relay are authoritative
+        let mut state = self.state();
+        if mobile == self.node_id || state.relay_for.contains(&mobile) {
+            return false;
+        }
+        if state
+            .routes
+            .get(&mobile)
+            .is_some_and(|stored| stored.signed_at >= route.signed_at)
+        {
+            return false;
+        }
+        state.routes.insert(mobile, route);
+
+        if state.routes.len() > MAX_ROUTES {
+            let expired_at = unix_now().saturating_sub(ROUTE_TTL.as_secs());
+            state
+                .routes
+                .retain(|_, route| route.signed_at >= expired_at);
+        }
+        while state.routes.len() > MAX_ROUTES {
+            let earliest = state
+                .routes
+                .iter()
+                .filter(|(peer, _)| **peer != self.node_id && !state.relay_for.contains(peer))
+                .min_by_key(|(_, route)| route.signed_at)
+                .map(|(peer, _)| *peer);
+            match earliest {
+                Some(earliest) => state.routes.remove(&earliest),
+                None => break,
+            };
+        }
+        true
+    }
+
+    /// dial the designated relay, the registration follows once connected
+    pub fn start(&self, swarm: &mut Swarm<VaneBehaviour>) {
+        let designated = self.state().designated.clone();
+        if let Some((relay, address)) = designated {
+            if !swarm.is_connected(&relay) {
+                if let Err(err) = swarm.dial(address) {
+                    warn!(target: "relay", "failed to dial the relay {relay}; caused by: {err}");
+                }
+            }
+        }
+    }
+
+    /// register with the designated relay under a freshly signed route and deliver the requests
+    /// queued for `peer`
+    pub fn connected(&self, swarm: &mut Swarm<VaneBehaviour>, peer: PeerId) {
+        let designated = self.state().designated.clone();
+        let register = match designated {
+            Some((relay, address)) if relay == peer => {
+                match self.signed_route(relay, &address, unix_now()) {
+                    Ok(route) => {
+                        let mut state = self.state();
+                        state.routes.insert(self.node_id, route.clone());
+                        Some(RelayMessage::Register {
+                            route,
+                            push_token: state.push_token.clone(),
+                        })
+                    }
+                    Err(err) => {
+                        warn!(target: "relay", "failed to renew the relay route; caused by: {err}");
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        if let Some(register) = register {
+            swarm
+                .behaviour_mut()
+                .relay
+                .send_request(&peer, envelope::seal(&register));
+        }
+
+        let queued = self.state().queued.remove(&peer).unwrap_or_default();
+        for (sealed, channel) in queued {
+            self.deliver(swarm, peer, sealed, channel);
+        }
+    }
+
+    /// seal `request` to the offline mobile node and hand it to its relay
+    pub fn forward(
+        &self,
+        swarm: &mut Swarm<VaneBehaviour>,
+        mobile: PeerId,
+        request: Vec<u8>,
+    ) -> Result<(), anyhow::Error> {
+        let route = self
+            .route(&mobile)
+            .ok_or(anyhow!("no relay route to {mobile}"))?;
+        let relay = PeerId::from_str(&route.relay_peer_id)?;
+        let (sealed, key) = seal_request(&route, &request)?;
+        if !swarm.is_connected(&relay) {
+            swarm
+                .dial(Multiaddr::from_str(&route.relay_multi_addr)?)
+                .map_err(|err| anyhow!("failed to dial the relay {relay}; caused by: {err}"))?;
+        }
+        let request_id = swarm
+            .behaviour_mut()
+            .relay
+            .send_request(&relay, envelope::seal(&RelayMessage::Forward(sealed)));
+        self.state().forwarded.insert(request_id, key);
+        info!(target: "relay", "request to {mobile} forwarded to its relay {relay}");
+        Ok(())
+    }
+
+    fn deliver(
+        &self,
+        swarm: &mut Swarm<VaneBehaviour>,
+        mobile: PeerId,
+        sealed: SealedRequest,
+        channel: Channel,
+    ) {
+        let request_id = swarm.behaviour_mut().request_response.send_request(
+            &mobile,
+            [SEALED_REQUEST_PREFIX, &envelope::seal(&sealed)].concat(),
+        );
+        self.state().delivered.insert(request_id, channel);
+        info!(target: "relay", "relayed request delivered to {mobile}");
+    }
+
+    fn refuse(
+        &self,
+        swarm: &mut Swarm<VaneBehaviour>,
+        peer: PeerId,
+        channel: Channel,
+        reason: &str,
+    ) {
+        warn!(target: "relay", "refused relay request of {peer}: {reason}");
+        // an error response closes the stream, the requester sees the request failed
+        let _ = swarm
+            .behaviour_mut()
+            .relay
+            .send_response(channel, Err(anyhow!("{reason}")));
+    }
+
+    pub async fn handle_event(
+        &self,
+        swarm: &mut Swarm<VaneBehaviour>,
+        event: Event<Vec<u8>, Result<Vec<u8>, anyhow::Error>>,
+        sender: &Sender<Result<SwarmMessage, anyhow::Error>>,
+    ) {
+        match event {
+            Event::Message {
+                peer,
+                message:
+                    Message::Request {
+                        request, channel, ..
+                    },
+            } => match envelope::decode::<RelayMessage>(&request) {
+                Ok(RelayMessage::Register { route, push_token }) => {
+                    let allowed = self.state().relay_for.contains(&peer);
+                    match verify_relay_route(&route) {
+                        Ok(mobile)
+                            if allowed
+                                && mobile == peer
+                                && route.relay_peer_id == self.node_id.to_base58() =>
+                        {
+                            let mut state = self.state();
+                            state.routes.insert(mobile, route);
+                            match push_token {
+                                Some(token) => state.push_tokens.insert(mobile, token),
+                                None => state.push_tokens.remove(&mobile),
+                            };
+                            drop(state);
+                            let _ = swarm
+                                .behaviour_mut()
+                                .relay
+                                .send_response(channel, Ok(vec![]));
+                            info!(target: "relay", "relaying the requests to {mobile}");
+                        }
+                        Ok(_) => self.refuse(swarm, peer, channel, "not relaying for this node"),
+                        Err(err) => self.refuse(swarm, peer, channel, &err.to_string()),
+                    }
+                }
+                Ok(RelayMessage::Forward(sealed)) => {
+                    let mobile = match PeerId::from_str(&sealed.mobile_peer_id) {
+                        Ok(mobile) if self.state().relay_for.contains(&mobile) => mobile,
+                        _ => {
+                            return self.refuse(
+                                swarm,
+                                peer,
+                                channel,
+                                "not relaying for the receiver",
+                            )
+                        }
+                    };
+                    if swarm.is_connected(&mobile) {
+                        self.deliver(swarm, mobile, sealed, channel);
+                        return;
+                    }
+                    let mut state = self.state();
+                    let queue = state.queued.entry(mobile).or_default();
+                    if queue.len() >= MAX_QUEUED_REQUESTS {
+                        drop(state);
+                        return self.refuse(swarm, peer, channel, "relay queue is full");
+                    }
+                    queue.push_back((sealed, channel));
+                    let queued = queue.len();
+                    info!(target: "relay", "request from {peer} queued until {mobile} connects");
+                    let gateway = state.push_gateway.clone();
+                    let token = state.push_tokens.get(&mobile).cloned();
+                    if let (Some(gateway), Some(token)) = (gateway, token) {
+                        let notification = PushNotification {
+                            token,
+                            peer_id: mobile.to_base58(),
+                            queued,
+                        };
+                        tokio::spawn(async move {
+                            if let Err(err) = gateway.notify(&notification).await {
+                                warn!(target: "relay", "failed to notify {mobile}: {err}");
+                            }
+                        });
+                    }
+                }
+                Err(err) => self.refuse(
+                    swarm,
+                    peer,
+                    channel,
+                    &format!("undecodable request: {err:?}"),
+                ),
+            },
+            Event::Message {
+                peer,
+                message:
+                    Message::Response {
+                        request_id,
+                        response,
+                    },
+            } => {
+                let Some(key) = self.state().forwarded.remove(&request_id) else {
+                    match response {
+                        Ok(_) => info!(target: "relay", "registered with the relay {peer}"),
+                        Err(err) => warn!(target: "relay", "relay {peer} refused to relay: {err}"),
+                    }
+                    return;
+                };
+                let reply = response.and_then(|response| {
+                    let sealed = envelope::decode::<SealedReply>(&response)
+                        .map_err(|err| anyhow!("undecodable relayed reply: {err:?}"))?;
+                    open_reply(&key, &sealed)
+                });
+                match reply {
+                    Ok(data) => {
+                        let resp_msg = SwarmMessage::Response {
+                            data,
+                            outbound_id: request_id,
+                        };
+                        if let Err(err) = sender.send(Ok(resp_msg)).await {
+                            warn!(target: "relay", "failed to propagate the relayed reply: {err}");
+                        }
+                    }
+                    Err(err) => warn!(target: "relay", "relay {peer} returned no reply: {err}"),
+                }
+            }
+            Event::OutboundFailure {
+                peer,
+                request_id,
+                error,
+            } => {
+                self.state().forwarded.remove(&request_id);
+                warn!(target: "relay", "relay request to {peer} failed: {error:?}")
+            }
+            Event::InboundFailure { peer, error, .. } => {
+                debug!(target: "relay", "relay request from {peer} failed: {error:?}")
+            }
+            Event::ResponseSent { .. } => {}
+        }
+    }
+
+    /// answer the requester with the reply of a delivered request, events of other requests are
+    /// passed through
+    pub fn handle_delivery(
+        &self,
+        swarm: &mut Swarm<VaneBehaviour>,
+        event: Event<Vec<u8>, Result<Vec<u8>, anyhow::Error>>,
+    ) -> Option<Event<Vec<u8>, Result<Vec<u8>, anyhow::Error>>> {
+        match event {
+            Event::Message {
+                peer,
+                message:
+                    Message::Response {
+                        request_id,
+                        response,
+                    },
+            } if self.state().delivered.contains_key(&request_id) => {
+                let channel = self.state().delivered.remove(&request_id)?;
+                let _ = swarm.behaviour_mut().relay.send_response(channel, response);
+                info!(target: "relay", "reply of {peer} returned to the requester");
+                None
+            }
+            Event::OutboundFailure {
+                peer,
+                request_id,
+                error,
+            } if self.state().delivered.contains_key(&request_id) => {
+                let channel = self.state().delivered.remove(&request_id)?;
+                self.refuse(
+                    swarm,
+                    peer,
+                    channel,
+                    &format!("mobile node failed: {error:?}"),
+                );
+                None
+            }
+            event => Some(event),
+        }
+    }
+
+    /// open a request delivered by a relay, other requests are returned as is
+    pub fn open(&self, inbound_req_id: u64, request: Vec<u8>) -> Result<Vec<u8>, anyhow::Error> {
+        let Some(sealed) = request.strip_prefix(SEALED_REQUEST_PREFIX) else {
+            return Ok(request);
+        };
+        let sealed = envelope::decode::<SealedRequest>(sealed)
+            .map_err(|err| anyhow!("undecodable sealed request: {err:?}"))?;
+        let (request, key) = open_request(self.encryption_secret, &sealed)?;
+        self.state().received.insert(inbound_req_id, key);
+        Ok(request)
+    }
+
+    /// seal the reply of a request delivered by a relay, other replies are returned as is
+    pub fn seal(&self, inbound_req_id: u64, reply: Vec<u8>) -> Result<Vec<u8>, anyhow::Error> {
+        match self.state().received.remove(&inbound_req_id) {
+            Some(key) => Ok(envelope::seal(&seal_reply(&key, &reply)?)),
+            None => Ok(reply),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mobile_with_route() -> Result<(Relay, RelayRoute), anyhow::Error> {
+        let mobile_keypair = Keypair::generate_ed25519();
+        let mobile = Relay::new(mobile_keypair.clone())?;
+        let relay_peer_id = Keypair::generate_ed25519().public().to_peer_id();
+        mobile.use_relay(format!("/ip4/127.0.0.1/tcp/30333/p2p/{relay_peer_id}").parse()?)?;
+        let route = mobile
+            .route(&mobile_keypair.public().to_peer_id())
+            .ok_or(anyhow!("own route missing"))?;
+        Ok((mobile, route))
+    }
+
+    // the relay only sees the sealed request and reply, the mobile node opens and answers it
+    #[test]
+    fn relayed_requests_are_sealed_to_the_mobile_node() -> Result<(), anyhow::Error> {
+        let (mobile, route) = mobile_with_route()?;
+        assert_eq!(verify_relay_route(&route)?, mobile.node_id);
+
+        let request = b"attestation request".to_vec();
+        let (sealed, key) = seal_request(&route, &request)?;
+        assert_ne!(sealed.ciphertext, request);
+        let delivered = [SEALED_REQUEST_PREFIX, &envelope::seal(&sealed)].concat();
+        assert_eq!(mobile.open(7, delivered)?, request);
+        let reply = mobile.seal(7, b"receiver reply".to_vec())?;
+        let reply = envelope::decode::<SealedReply>(&reply).map_err(|err| anyhow!("{err:?}"))?;
+        assert_eq!(open_reply(&key, &reply)?, b"receiver reply".to_vec());
+
+        // direct requests and replies pass through untouched
+        assert_eq!(mobile.open(8, request.clone())?, request);
+        assert_eq!(mobile.seal(8, request.clone())?, request);
+        Ok(())
+    }
+
+    // a route is only learned when signed by the mobile node it routes to
+    #[test]
+    fn tampered_relay_routes_are_refused() -> Result<(), anyhow::Error> {
+        let (_, route) = mobile_with_route()?;
+        let sender = Relay::new(Keypair::generate_ed25519())?;
+        let rerouted = RelayRoute {
+            relay_peer_id: Keypair::generate_ed25519()
+                .public()
+                .to_peer_id()
+                .to_base58(),
+            ..route.clone()
+        };
+        assert!(verify_relay_route(&rerouted).is_err());
+        assert!(!sender.learn(rerouted));
+        let rekeyed = RelayRoute {
+            encryption_key: [9; 32],
+            ..route.clone()
+        };
+        assert!(!sender.learn(rekeyed));
+        assert!(sender.learn(route));
+        Ok(())
+    }
+
+    // a replayed earlier route does not replace a later one and expired routes are refused
+    #[test]
+    fn only_later_unexpired_routes_are_learned() -> Result<(), anyhow::Error> {
+        let (mobile, route) = mobile_with_route()?;
+        let relay_peer_id = PeerId::from_str(&route.relay_peer_id)?;
+        let relay = Multiaddr::from_str(&route.relay_multi_addr)?;
+        let earlier = mobile.signed_route(relay_peer_id, &relay, route.signed_at - 60)?;
+        let expired = mobile.signed_route(
+            relay_peer_id,
+            &relay,
+            route.signed_at - ROUTE_TTL.as_secs() - 60,
+        )?;
+        assert!(verify_relay_route(&expired).is_err());
+
+        let sender = Relay::new(Keypair::generate_ed25519())?;
+        assert!(!sender.learn(expired));
+        assert!(sender.learn(route.clone()));
+        assert!(!sender.learn(earlier));
+        assert_eq!(sender.route(&mobile.node_id), Some(route));
+        Ok(())
+    }
+
+    #[test]
+    fn pending_requests_are_capped() {
+        let mut pending = Pending::default();
+        for id in 0..=MAX_PENDING_REQUESTS as u64 {
+            pending.insert(id, [0u8; 32]);
+        }
+        assert_eq!(pending.entries.len(), MAX_PENDING_REQUESTS);
+        assert!(pending.contains_key(&(MAX_PENDING_REQUESTS as u64)));
+    }
+}
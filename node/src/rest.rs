@@ -0,0 +1,439 @@
+// rest gateway
+// a json over http facade of the most common operations, for web integrators not speaking
+// json-rpc: `POST /transfers` initiates a transfer, `GET /transfers/{id}` returns its latest state
+// with a diagnosis and `GET /history` the submitted txs. it is served on the rpc port, behind the
+// access control, and its routes call the `TransactionRpcServer` methods of the rpc worker, so the
+// roles, rate limits and errors are the json-rpc ones. `GET /tx-updates` streams the tx updates of
+// the accounts of the api key as server-sent events. `GET /openapi.json` describes the routes
+
+use crate::locks::timed_lock;
+use crate::openrpc::schemas;
+use crate::rpc::{
+    vane_error, InitiateTransactionParams, TransactionRpcServer, TransactionRpcWorker,
+};
+use crate::rpc_middleware::{caller_scope, read_body, AccessControl, Role};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use db::DbWorkerInterface;
+use hyper::header::{HeaderValue, CONTENT_TYPE, LOCATION};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use log::warn;
+use primitives::data_structure::{ChainSupported, Token, TxPriority, H256};
+use primitives::errors::VaneRpcError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tower::{Layer, Service};
+
+pub const OPENAPI_VERSION: &str = "3.1.0";
+
+/// body of `POST /transfers`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferRequest {
+    pub sender: String,
+    pub receiver: String,
+    /// decimal amount in the smallest unit of the token
+    pub amount: String,
+    pub token: Token,
+    pub network: ChainSupported,
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default)]
+    pub reference: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// seconds the transfer may take until it is submitted
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+    #[serde(default)]
+    pub priority: Option<TxPriority>,
+}
+
+enum Route {
+    Initiate,
+    Transfer(String),
+    History,
+    TxUpdates,
+    OpenApi,
+}
+
+impl Route {
+    fn of(method: &Method, path: &str) -> Option<Self> {
+        match (method, path.trim_end_matches('/')) {
+            (&Method::POST, "/transfers") => Some(Route::Initiate),
+            (&Method::GET, "/history") => Some(Route::History),
+            (&Method::GET, "/tx-updates") => Some(Route::TxUpdates),
+            (&Method::GET, "/openapi.json") => Some(Route::OpenApi),
+            (&Method::GET, path) => path
+                .strip_prefix("/transfers/")
+                .map(|tx_id| Route::Transfer(tx_id.to_string())),
+            _ => None,
+        }
+    }
+
+    /// json-rpc method the route serves, its role is the one required
+    fn rpc_method(&self) -> &'static str {
+        match self {
+            Route::Initiate => "initiateTransaction",
+            Route::Transfer(_) => "diagnoseTx",
+            Route::History => "txHistory",
+            Route::TxUpdates => "subscribeTxUpdates",
+            Route::OpenApi => "rpc.discover",
+        }
+    }
+}
+
+#[derive(Clone)]
+struct RestGateway {
+    worker: TransactionRpcWorker,
+    access: AccessControl,
+}
+
+impl RestGateway {
+    async fn answer(&self, route: Route, request: Request<Body>) -> Response<Body> {
+        let method = route.rpc_method();
+        let required = Role::required_for(method);
+        let role = self.access.role(request.headers());
+        if self.access.is_enabled() && !role.is_some_and(|role| role >= required) {
+            warn!(target: "rpc","access denied: method={method} role={role:?}");
+            return error_response(VaneRpcError::AccessDenied {
+                method: method.to_string(),
+                role: required.to_string(),
+            });
+        }
+        let answer = match route {
+            Route::Initiate => self.initiate(request).await,
+            Route::Transfer(tx_id) => self.transfer(&tx_id).await,
+            Route::History => self.history(&request).await,
+            Route::TxUpdates => Ok(self.tx_updates(&request)),
+            Route::OpenApi => Ok(json_response(StatusCode::OK, &openapi_document())),
+        };
+        answer.unwrap_or_else(error_response)
+    }
+
+    async fn initiate(&self, request: Request<Body>) -> Result<Response<Body>, VaneRpcError> {
+        let (parts, body) = request.into_parts();
+        let body = read_body(body).await.ok_or(VaneRpcError::InvalidParams {
+            reason: "request body too large".to_string(),
+        })?;
+        let transfer: TransferRequest =
+            serde_json::from_slice(&body).map_err(|err| VaneRpcError::InvalidParams {
+                reason: format!("invalid transfer; {err}"),
+            })?;
+        let amount = transfer
+            .amount
+            .parse()
+            .map_err(|_| VaneRpcError::InvalidParams {
+                reason: format!("invalid amount {}", transfer.amount),
+            })?;
+        let idempotency_key = parts
+            .headers
+            .get("idempotency-key")
+            .and_then(|key| key.to_str().ok())
+            .map(str::to_string);
+        let tags = (!transfer.tags.is_empty()).then_some(transfer.tags);
+        let tx_id = self
+            .worker
+            .initiate(InitiateTransactionParams {
+                sender: transfer.sender,
+                receiver: transfer.receiver,
+                amount,
+                token: transfer.token.into(),
+                network: transfer.network.into(),
+                idempotency_key,
+                memo: transfer.memo,
+                reference: transfer.reference,
+                tags,
+                ttl_secs: transfer.ttl_secs,
+                priority: transfer.priority,
+                ..Default::default()
+            })
+            .await
+            .map_err(vane_error)?;
+        let mut response = json_response(StatusCode::CREATED, &json!({ "txId": tx_id }));
+        if let Ok(location) = HeaderValue::from_str(&format!("/transfers/{tx_id:?}")) {
+            response.headers_mut().insert(LOCATION, location);
+        }
+        Ok(response)
+    }
+
+    async fn transfer(&self, tx_id: &str) -> Result<Response<Body>, VaneRpcError> {
+        let tx_id: H256 =
+            serde_json::from_value(Value::String(tx_id.to_string())).map_err(|_| {
+                VaneRpcError::InvalidParams {
+                    reason: format!("invalid tx id {tx_id}"),
+                }
+            })?;
+        let events = timed_lock(&self.worker.db_worker, "db_worker")
+            .await
+            .get_tx_events(tx_id)
+            .await
+            .map_err(VaneRpcError::from)?;
+        // a scoped api key is told the txs of the other accounts do not exist
+        let Some(latest) = events
+            .last()
+            .filter(|event| caller_scope().allows(&event.tx))
+        else {
+            let err = VaneRpcError::InvalidParams {
+                reason: format!("no tx with id {tx_id:?}"),
+            };
+            return Ok(json_response(StatusCode::NOT_FOUND, &error_body(err)));
+        };
+        let diagnosis = self.worker.diagnose_tx(tx_id).await.map_err(vane_error)?;
+        let transfer = json!({ "tx": latest.tx, "diagnosis": diagnosis });
+        Ok(json_response(StatusCode::OK, &transfer))
+    }
+
+    async fn history(&self, request: &Request<Body>) -> Result<Response<Body>, VaneRpcError> {
+        let query = request.uri().query().and_then(|query| {
+            form_urlencoded::parse(query.as_bytes())
+                .find(|(name, _)| name == "query")
+                .map(|(_, value)| value.into_owned())
+        });
+        let history = self.worker.tx_history(query).await.map_err(vane_error)?;
+        Ok(json_response(StatusCode::OK, &history))
+    }
+
+    /// server-sent events of the updates in the scope of the api key until the client leaves
+    fn tx_updates(&self, request: &Request<Body>) -> Response<Body> {
+        let mut updates = self
+            .worker
+            .tx_updates
+            .subscribe(self.access.scope(request.headers()));
+        let (mut sender, body) = Body::channel();
+        tokio::spawn(async move {
+            while let Some(tx) = updates.next().await {
+                let tx = serde_json::to_string(&tx).unwrap_or_default();
+                let event = format!("data: {tx}\n\n");
+                if sender.send_data(event.into()).await.is_err() {
+                    return;
+                }
+            }
+        });
+        let mut response = Response::new(body);
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+        response
+    }
+}
+
+pub(crate) fn json_response(status: StatusCode, body: &impl Serialize) -> Response<Body> {
+    let mut response = Response::new(Body::from(serde_json::to_vec(body).unwrap_or_default()));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+/// the json-rpc error object of `err`
+fn error_body(err: VaneRpcError) -> Value {
+    json!({ "code": err.code(), "message": err.to_string(), "data": err })
+}
+
+fn error_response(err: VaneRpcError) -> Response<Body> {
+    let status = match err {
+        VaneRpcError::AccessDenied { .. } => StatusCode::FORBIDDEN,
+        VaneRpcError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        VaneRpcError::ReceiverOffline { .. } => StatusCode::SERVICE_UNAVAILABLE,
+        VaneRpcError::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        VaneRpcError::InvalidParams { .. }
+        | VaneRpcError::InvalidAddress { .. }
+        | VaneRpcError::UnsupportedNetwork { .. }
+        | VaneRpcError::WrongNetworkSuspected { .. }
+        | VaneRpcError::AmbiguousNetwork { .. } => StatusCode::BAD_REQUEST,
+        _ => StatusCode::UNPROCESSABLE_ENTITY,
+    };
+    json_response(status, &error_body(err))
+}
+
+/// build the OpenAPI document describing the rest gateway
+pub fn openapi_document() -> Value {
+    let mut schemas = schemas();
+    schemas["TransferRequest"] = json!({
+        "type": "object",
+        "required": ["sender", "receiver", "amount", "token", "network"],
+        "properties": {
+            "sender": { "type": "string" },
+            "receiver": { "type": "string" },
+            "amount": { "type": "string", "description": "decimal amount in the smallest unit of the token" },
+            "token": { "$ref": "#/components/schemas/Token" },
+            "network": { "$ref": "#/components/schemas/ChainSupported" },
+            "memo": { "type": "string" },
+            "reference": { "type": "string" },
+            "tags": { "type": "array", "items": { "type": "string" } },
+            "ttlSecs": { "type": "integer", "minimum": 1 },
+            "priority": { "type": "string", "enum": ["Low", "Normal", "Urgent"] }
+        }
+    });
+    schemas["Transfer"] = json!({
+        "type": "object",
+        "properties": {
+            "tx": { "$ref": "#/components/schemas/TxStateMachine" },
+            "diagnosis": { "$ref": "#/components/schemas/TxDiagnosis" }
+        }
+    });
+    schemas["Error"] = json!({
+        "type": "object",
+        "properties": {
+            "code": { "type": "integer" },
+            "message": { "type": "string" },
+            "data": { "type": "object", "properties": { "kind": { "type": "string" } } }
+        }
+    });
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": "vane_web3 node rest gateway",
+            "description": "the most common operations of the json-rpc api over plain http",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": {
+            "/transfers": {
+                "post": {
+                    "summary": "initiate a transfer, the receiver is asked to attest its address and network",
+                    "parameters": [{
+                        "name": "Idempotency-Key",
+                        "in": "header",
+                        "required": false,
+                        "description": "retries with the same key return the first tx id",
+                        "schema": { "type": "string" }
+                    }],
+                    "requestBody": {
+                        "required": true,
+                        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/TransferRequest" } } }
+                    },
+                    "responses": {
+                        "default": { "$ref": "#/components/responses/Error" },
+                        "201": {
+                            "description": "id of the initiated transfer",
+                            "content": { "application/json": { "schema": {
+                                "type": "object",
+                                "properties": { "txId": { "type": "string" } }
+                            } } }
+                        }
+                    }
+                }
+            },
+            "/transfers/{txId}": {
+                "get": {
+                    "summary": "latest state of a transfer and where it waits",
+                    "parameters": [{
+                        "name": "txId",
+                        "in": "path",
+                        "required": true,
+                        "schema": { "type": "string" }
+                    }],
+                    "responses": {
+                        "default": { "$ref": "#/components/responses/Error" },
+                        "200": {
+                            "description": "the transfer",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Transfer" } } }
+                        },
+                        "404": { "$ref": "#/components/responses/Error" }
+                    }
+                }
+            },
+            "/history": {
+                "get": {
+                    "summary": "submitted txs, optionally only the ones whose reference or a tag is the query or whose memo contains it",
+                    "parameters": [{
+                        "name": "query",
+                        "in": "query",
+                        "required": false,
+                        "schema": { "type": "string" }
+                    }],
+                    "responses": {
+                        "default": { "$ref": "#/components/responses/Error" },
+                        "200": {
+                            "description": "the submitted txs",
+                            "content": { "application/json": { "schema": {
+                                "type": "array",
+                                "items": { "$ref": "#/components/schemas/DbTxStateMachine" }
+                            } } }
+                        }
+                    }
+                }
+            },
+            "/tx-updates": {
+                "get": {
+                    "summary": "server-sent events of the tx updates, only the ones of the accounts of an account scoped api key",
+                    "responses": {
+                        "default": { "$ref": "#/components/responses/Error" },
+                        "200": {
+                            "description": "a `data` event per tx update",
+                            "content": { "text/event-stream": { "schema": { "$ref": "#/components/schemas/TxStateMachine" } } }
+                        }
+                    }
+                }
+            }
+        },
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": { "apiKey": { "type": "http", "scheme": "bearer" } },
+            "responses": {
+                "Error": {
+                    "description": "vane error, as the json-rpc error object",
+                    "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+                }
+            },
+        },
+        "security": [{ "apiKey": [] }],
+    })
+}
+
+/// tower layer answering the rest routes, behind the access control so only valid api keys reach
+/// it, other requests go on to the json-rpc server
+#[derive(Clone)]
+pub struct RestLayer {
+    gateway: RestGateway,
+}
+
+impl RestLayer {
+    pub fn new(worker: TransactionRpcWorker, access: AccessControl) -> Self {
+        Self {
+            gateway: RestGateway { worker, access },
+        }
+    }
+}
+
+impl<S> Layer<S> for RestLayer {
+    type Service = RestService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RestService {
+            inner,
+            gateway: self.gateway.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RestService<S> {
+    inner: S,
+    gateway: RestGateway,
+}
+
+impl<S> Service<Request<Body>> for RestService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let Some(route) = Route::of(request.method(), request.uri().path()) else {
+            return Box::pin(self.inner.call(request));
+        };
+        let gateway = self.gateway.clone();
+        Box::pin(async move { Ok(gateway.answer(route, request).await) })
+    }
+}
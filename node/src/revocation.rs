@@ -0,0 +1,67 @@
+// account revocations
+// an account owner marks a compromised account by signing its revocation with the leaked key. the
+// node refuses to attest transfers to a revoked account and warns senders about transfers to one.
+// revocations are gossiped over peer exchange, a node learning a new one passes it on to its other
+// connected peers, and kept in the registry with the record of the node serving the account
+
+use crate::discovery::PeerDiscovery;
+use alloy::primitives::{Address, Signature as EcdsaSignature};
+use anyhow::anyhow;
+use db::{DbWorker, DbWorkerInterface};
+use primitives::revocation::AccountRevocation;
+
+/// check the revocation is signed by its account, returns the checksummed account
+pub fn verify_revocation(revocation: &AccountRevocation) -> Result<String, anyhow::Error> {
+    let account: Address = revocation
+        .account
+        .parse()
+        .map_err(|err| anyhow!("invalid revoked account; caused by: {err}"))?;
+    let signature = EcdsaSignature::try_from(revocation.signature.as_slice())
+        .map_err(|err| anyhow!("invalid revocation signature; caused by: {err}"))?;
+    let recovered = signature
+        .recover_address_from_msg(revocation.signed_message())
+        .map_err(|err| anyhow!("revocation signature recovery failed; caused by: {err}"))?;
+    if recovered != account {
+        Err(anyhow!("revocation was not signed by {account}"))?
+    }
+    Ok(account.to_string())
+}
+
+/// revocation of `account` this node stored, its own or gossiped to it
+pub async fn stored_revocation(
+    db: &DbWorker,
+    account: &str,
+) -> Result<Option<AccountRevocation>, anyhow::Error> {
+    Ok(db
+        .get_revocations()
+        .await?
+        .into_iter()
+        .find(|revocation| revocation.revokes(account)))
+}
+
+/// revocation of `account` stored by this node or else kept in the registry, a verified registry
+/// revocation is stored for the next lookups
+pub async fn revocation_of(
+    db: &DbWorker,
+    discovery: &dyn PeerDiscovery,
+    account: &str,
+) -> Result<Option<AccountRevocation>, anyhow::Error> {
+    if let Some(revocation) = stored_revocation(db, account).await? {
+        return Ok(Some(revocation));
+    }
+    let revocation = discovery
+        .list_all_peers()
+        .await?
+        .into_iter()
+        .filter(|peer| {
+            peer.account_ids
+                .iter()
+                .any(|id| id.eq_ignore_ascii_case(account))
+        })
+        .flat_map(|peer| peer.revocations)
+        .find(|revocation| revocation.revokes(account) && verify_revocation(revocation).is_ok());
+    if let Some(revocation) = &revocation {
+        db.record_revocation(revocation.clone()).await?;
+    }
+    Ok(revocation)
+}
@@ -0,0 +1,183 @@
+// receiver risk screening
+// the risk provider of the operator scores the receiver of every transfer the node initiates, at
+// genesis. the assessment is recorded with the tx in the event log and checked against the
+// operator requirements. a provider failing to answer refuses the transfer once something is
+// required of the receivers, and is only logged otherwise
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use log::warn;
+use primitives::address::VaneAddress;
+use primitives::data_structure::{ChainSupported, TxStateMachine};
+use primitives::errors::VaneRpcError;
+use primitives::risk::{AddressRisk, RiskAssessment, RiskRequirements, MAX_RISK_SCORE};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// scores addresses and labels the clusters they belong to
+#[async_trait]
+pub trait RiskProvider: Send + Sync {
+    /// name recorded with the assessments
+    fn name(&self) -> String;
+
+    /// risk of `address` on `network`, `None` when the provider does not score it
+    async fn assess(
+        &self,
+        address: &VaneAddress,
+        network: ChainSupported,
+    ) -> Result<Option<AddressRisk>, anyhow::Error>;
+}
+
+/// scores nothing, used when the operator plugged no provider
+pub struct NoRiskProvider;
+
+#[async_trait]
+impl RiskProvider for NoRiskProvider {
+    fn name(&self) -> String {
+        "none".to_string()
+    }
+
+    async fn assess(
+        &self,
+        _address: &VaneAddress,
+        _network: ChainSupported,
+    ) -> Result<Option<AddressRisk>, anyhow::Error> {
+        Ok(None)
+    }
+}
+
+#[derive(Serialize)]
+struct RiskQuery<'a> {
+    address: String,
+    network: &'a ChainSupported,
+}
+
+/// provider behind an http api, posted `{"address":…,"network":…}` and answering an
+/// `AddressRisk` json or 404 for the addresses it does not score
+pub struct HttpRiskProvider {
+    name: String,
+    url: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl HttpRiskProvider {
+    /// `api_key` is sent as a bearer token
+    pub fn new(
+        url: impl Into<String>,
+        api_key: Option<String>,
+        timeout: Duration,
+    ) -> Result<Self, anyhow::Error> {
+        let url: String = url.into();
+        let name = reqwest::Url::parse(&url)
+            .map_err(|err| anyhow!("invalid risk provider url; caused by: {err}"))?
+            .host_str()
+            .ok_or(anyhow!("the risk provider url has no host"))?
+            .to_string();
+        let client = reqwest::ClientBuilder::new()
+            .timeout(timeout)
+            .build()
+            .map_err(|err| anyhow!("failed to build the risk provider client; caused by: {err}"))?;
+        Ok(Self {
+            name,
+            url,
+            api_key,
+            client,
+        })
+    }
+}
+
+#[async_trait]
+impl RiskProvider for HttpRiskProvider {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn assess(
+        &self,
+        address: &VaneAddress,
+        network: ChainSupported,
+    ) -> Result<Option<AddressRisk>, anyhow::Error> {
+        let query = RiskQuery {
+            address: address.to_string(),
+            network: &network,
+        };
+        let mut request = self.client.post(&self.url).json(&query);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|err| anyhow!("risk provider call failed; caused by: {err}"))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let risk: AddressRisk = response
+            .error_for_status()
+            .map_err(|err| anyhow!("risk provider call failed; caused by: {err}"))?
+            .json()
+            .await
+            .map_err(|err| anyhow!("invalid risk provider answer; caused by: {err}"))?;
+        if risk.score > MAX_RISK_SCORE {
+            Err(anyhow!("risk score {} above {MAX_RISK_SCORE}", risk.score))?
+        }
+        Ok(Some(risk))
+    }
+}
+
+/// provider of the node and the requirements its assessments are checked against
+#[derive(Clone)]
+pub struct RiskScreening {
+    provider: Arc<dyn RiskProvider>,
+    requirements: RiskRequirements,
+}
+
+impl Default for RiskScreening {
+    fn default() -> Self {
+        Self::new(Arc::new(NoRiskProvider), RiskRequirements::default())
+    }
+}
+
+impl RiskScreening {
+    pub fn new(provider: Arc<dyn RiskProvider>, requirements: RiskRequirements) -> Self {
+        Self {
+            provider,
+            requirements,
+        }
+    }
+
+    /// assess the receiver of `tx`, the assessment is returned to be recorded with the tx
+    pub async fn screen(
+        &self,
+        tx: &TxStateMachine,
+    ) -> Result<Option<RiskAssessment>, VaneRpcError> {
+        let address = tx.receiver_address.to_string();
+        let risk = match self.provider.assess(&tx.receiver_address, tx.network).await {
+            Ok(risk) => risk,
+            Err(err) if self.requirements.is_empty() => {
+                warn!("risk screening of {address} failed; caused by: {err}");
+                None
+            }
+            Err(err) => Err(VaneRpcError::RiskCheckFailed {
+                address,
+                reason: format!("no answer from the risk provider, {err}"),
+            })?,
+        };
+        let assessed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| anyhow!("system clock before unix epoch; caused by: {err}"))?
+            .as_secs();
+        let assessment = risk.map(|risk| RiskAssessment {
+            provider: self.provider.name(),
+            address: address.clone(),
+            score: risk.score,
+            labels: risk.labels,
+            jurisdiction: risk.jurisdiction,
+            assessed_at,
+        });
+        self.requirements.check(&address, assessment.as_ref())?;
+        Ok(assessment)
+    }
+}
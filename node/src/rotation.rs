@@ -0,0 +1,316 @@
+// node identity rotation
+// a node rotating its libp2p identity swaps its swarm for one with the new keypair, listening on a
+// new port, and keeps the old swarm answering until the grace period ends so requests in flight to
+// the old peer id still complete. the rotation is signed by both keys and sent to the saved contacts
+// and paired devices, which move the node to its new peer id once both signatures verify
+
+use crate::locks::timed_lock;
+use crate::p2p::VaneBehaviour;
+use anyhow::anyhow;
+use db::{DbWorker, DbWorkerInterface};
+use libp2p::identity::{Keypair, PublicKey};
+use libp2p::multiaddr::Protocol;
+use libp2p::request_response::{Event, Message};
+use libp2p::{Multiaddr, PeerId, Swarm};
+use log::{debug, info, warn};
+use primitives::device_sync::PairedDevice;
+use primitives::envelope;
+use primitives::identity_rotation::IdentityRotation;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+/// protocol of the rotation announcements to the contacts of a node
+pub const ROTATION_PROTOCOL: &str = "/vane-rotation/1.0.0";
+/// how long the old identity keeps answering by default
+pub const DEFAULT_ROTATION_GRACE: Duration = Duration::from_secs(24 * 3600);
+/// longest grace period, the old key stays usable to the node until it ends
+pub const MAX_ROTATION_GRACE: Duration = Duration::from_secs(7 * 24 * 3600);
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// sign the rotation from `old` to `new` listening on `new_multi_addr` with both keys
+pub fn sign_rotation(
+    old: &Keypair,
+    new: &Keypair,
+    new_multi_addr: &Multiaddr,
+    grace: Duration,
+) -> Result<IdentityRotation, anyhow::Error> {
+    if grace > MAX_ROTATION_GRACE {
+        Err(anyhow!(
+            "grace period exceeds the maximum of {MAX_ROTATION_GRACE:?}"
+        ))?
+    }
+    let rotated_at = now();
+    let mut rotation = IdentityRotation {
+        old_peer_id: old.public().to_peer_id().to_base58(),
+        new_peer_id: new.public().to_peer_id().to_base58(),
+        new_multi_addr: new_multi_addr.to_string(),
+        old_public_key: old.public().encode_protobuf(),
+        new_public_key: new.public().encode_protobuf(),
+        rotated_at,
+        grace_until: rotated_at + grace.as_secs(),
+        old_signature: vec![],
+        new_signature: vec![],
+    };
+    let message = rotation.signed_message();
+    rotation.old_signature = old
+        .sign(message.as_bytes())
+        .map_err(|err| anyhow!("failed to sign the rotation; caused by: {err}"))?;
+    rotation.new_signature = new
+        .sign(message.as_bytes())
+        .map_err(|err| anyhow!("failed to sign the rotation; caused by: {err}"))?;
+    Ok(rotation)
+}
+
+/// check the rotation is signed by both identities, returns the old and the new peer id
+pub fn verify_rotation(rotation: &IdentityRotation) -> Result<(PeerId, PeerId), anyhow::Error> {
+    let key = |encoded: &[u8], peer_id: &str| -> Result<(PeerId, PublicKey), anyhow::Error> {
+        let public_key = PublicKey::try_decode_protobuf(encoded)
+            .map_err(|err| anyhow!("invalid rotation public key; caused by: {err}"))?;
+        let peer = public_key.to_peer_id();
+        if peer.to_base58() != peer_id {
+            Err(anyhow!("rotation public key is not the key of {peer_id}"))?
+        }
+        Ok((peer, public_key))
+    };
+    let (old_peer, old_key) = key(&rotation.old_public_key, &rotation.old_peer_id)?;
+    let (new_peer, new_key) = key(&rotation.new_public_key, &rotation.new_peer_id)?;
+    if old_peer == new_peer {
+        Err(anyhow!("rotation to the same identity"))?
+    }
+    if rotation.grace_until < rotation.rotated_at
+        || rotation.grace_until - rotation.rotated_at > MAX_ROTATION_GRACE.as_secs()
+    {
+        Err(anyhow!("invalid rotation grace period"))?
+    }
+    let multi_addr = Multiaddr::from_str(&rotation.new_multi_addr)
+        .map_err(|err| anyhow!("invalid rotated multi addr; caused by: {err}"))?;
+    if multi_addr
+        .iter()
+        .any(|protocol| matches!(protocol, Protocol::P2p(addr_peer) if addr_peer != new_peer))
+    {
+        Err(anyhow!("{multi_addr} is not the address of {new_peer}"))?
+    }
+    let message = rotation.signed_message();
+    if !old_key.verify(message.as_bytes(), &rotation.old_signature) {
+        Err(anyhow!("invalid old key rotation signature"))?
+    }
+    if !new_key.verify(message.as_bytes(), &rotation.new_signature) {
+        Err(anyhow!("invalid new key rotation signature"))?
+    }
+    Ok((old_peer, new_peer))
+}
+
+/// `multi_addr` moved to `port` and ending with `peer_id`
+pub fn rotated_multi_addr(multi_addr: &Multiaddr, port: u16, peer_id: PeerId) -> Multiaddr {
+    multi_addr
+        .iter()
+        .map(|protocol| match protocol {
+            Protocol::Tcp(_) => Protocol::Tcp(port),
+            Protocol::P2p(_) => Protocol::P2p(peer_id),
+            protocol => protocol,
+        })
+        .collect()
+}
+
+/// the current identity of the node, shared with the rpc worker
+#[derive(Clone)]
+pub struct NodeIdentity(Arc<RwLock<(Keypair, Multiaddr)>>);
+
+impl NodeIdentity {
+    pub fn new(keypair: Keypair, multi_addr: Multiaddr) -> Self {
+        Self(Arc::new(RwLock::new((keypair, multi_addr))))
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, (Keypair, Multiaddr)> {
+        self.0
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn keypair(&self) -> Keypair {
+        self.read().0.clone()
+    }
+
+    pub fn peer_id(&self) -> PeerId {
+        self.read().0.public().to_peer_id()
+    }
+
+    pub fn multi_addr(&self) -> Multiaddr {
+        self.read().1.clone()
+    }
+
+    pub(crate) fn set(&self, keypair: Keypair, multi_addr: Multiaddr) {
+        *self
+            .0
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = (keypair, multi_addr);
+    }
+}
+
+/// announces the rotations of this node and applies the rotations of its contacts
+#[derive(Clone)]
+pub struct IdentityRotations {
+    db_worker: Arc<Mutex<DbWorker>>,
+}
+
+impl IdentityRotations {
+    pub fn new(db_worker: Arc<Mutex<DbWorker>>) -> Self {
+        Self { db_worker }
+    }
+
+    /// send `rotation` to the saved contacts and paired devices, dialing the disconnected ones
+    pub async fn notify(&self, swarm: &mut Swarm<VaneBehaviour>, rotation: &IdentityRotation) {
+        let db = timed_lock(&self.db_worker, "db_worker").await;
+        let contacts = match db.get_all_saved_user_peers().await {
+            Ok(contacts) => contacts
+                .into_iter()
+                .filter_map(|contact| Some((contact.peer_id?, contact.multi_addr?)))
+                .collect::<Vec<_>>(),
+            Err(err) => {
+                warn!(target: "rotation", "failed to read the saved contacts; caused by: {err}");
+                vec![]
+            }
+        };
+        let devices = match db.get_paired_devices().await {
+            Ok(devices) => devices
+                .into_iter()
+                .map(|device| (device.peer_id, device.multi_addr))
+                .collect::<Vec<_>>(),
+            Err(err) => {
+                warn!(target: "rotation", "failed to read the paired devices; caused by: {err}");
+                vec![]
+            }
+        };
+        drop(db);
+
+        let mut notified = vec![];
+        for (peer_id, multi_addr) in contacts.into_iter().chain(devices) {
+            let Ok(peer) = PeerId::from_str(&peer_id) else {
+                continue;
+            };
+            if notified.contains(&peer) || peer.to_base58() == rotation.old_peer_id {
+                continue;
+            }
+            if !swarm.is_connected(&peer) {
+                let Ok(address) = Multiaddr::from_str(&multi_addr) else {
+                    continue;
+                };
+                if let Err(err) = swarm.dial(address) {
+                    debug!(target: "rotation", "failed to dial {peer}: {err}");
+                    continue;
+                }
+            }
+            swarm
+                .behaviour_mut()
+                .identity_rotation
+                .send_request(&peer, envelope::seal(rotation));
+            notified.push(peer);
+        }
+        info!(target: "rotation", "announced the rotation to {} contacts", notified.len());
+    }
+
+    /// move the contact `peer` rotated from to its new identity
+    async fn apply(&self, peer: PeerId, request: &[u8]) -> Result<(), String> {
+        let rotation = envelope::decode::<IdentityRotation>(request)
+            .map_err(|err| format!("undecodable rotation: {err:?}"))?;
+        // only the new identity announces the rotation, so it is reachable at its address
+        if peer.to_base58() != rotation.new_peer_id {
+            return Err(format!("{peer} announced the rotation of another node"));
+        }
+        verify_rotation(&rotation).map_err(|err| err.to_string())?;
+
+        let db = timed_lock(&self.db_worker, "db_worker").await;
+        db.rotate_saved_peer_id(
+            rotation.old_peer_id.clone(),
+            rotation.new_peer_id.clone(),
+            rotation.new_multi_addr.clone(),
+        )
+        .await
+        .map_err(|err| format!("failed to move the saved contact; caused by: {err}"))?;
+
+        let devices = db
+            .get_paired_devices()
+            .await
+            .map_err(|err| format!("failed to read the paired devices; caused by: {err}"))?;
+        if let Some(device) = devices
+            .into_iter()
+            .find(|device| device.peer_id == rotation.old_peer_id)
+        {
+            db.record_paired_device(PairedDevice {
+                peer_id: rotation.new_peer_id.clone(),
+                multi_addr: rotation.new_multi_addr.clone(),
+                ..device
+            })
+            .await
+            .map_err(|err| format!("failed to move the paired device; caused by: {err}"))?;
+            db.unpair_device(rotation.old_peer_id.clone())
+                .await
+                .map_err(|err| format!("failed to move the paired device; caused by: {err}"))?;
+        }
+        info!(
+            target: "rotation",
+            "contact {} rotated to {}", rotation.old_peer_id, rotation.new_peer_id
+        );
+        Ok(())
+    }
+
+    pub async fn handle_event(
+        &self,
+        swarm: &mut Swarm<VaneBehaviour>,
+        event: Event<Vec<u8>, Result<Vec<u8>, anyhow::Error>>,
+    ) {
+        match event {
+            Event::Message {
+                peer,
+                message:
+                    Message::Request {
+                        request, channel, ..
+                    },
+            } => {
+                let applied = self.apply(peer, &request).await;
+                if let Err(reason) = &applied {
+                    warn!(target: "rotation", "refused the rotation from {peer}: {reason}");
+                }
+                if swarm
+                    .behaviour_mut()
+                    .identity_rotation
+                    .send_response(channel, Ok(envelope::seal(&applied)))
+                    .is_err()
+                {
+                    debug!(target: "rotation", "{peer} closed the rotation before the response");
+                }
+            }
+            Event::Message {
+                peer,
+                message: Message::Response { response, .. },
+            } => {
+                let applied = response.and_then(|response| {
+                    envelope::decode::<Result<(), String>>(&response)
+                        .map_err(|err| anyhow!("undecodable rotation response: {err:?}"))
+                });
+                match applied {
+                    Ok(Ok(())) => debug!(target: "rotation", "{peer} applied the rotation"),
+                    Ok(Err(reason)) => {
+                        warn!(target: "rotation", "{peer} refused the rotation: {reason}")
+                    }
+                    Err(err) => debug!(target: "rotation", "rotation to {peer} failed: {err}"),
+                }
+            }
+            Event::OutboundFailure { peer, error, .. } => {
+                debug!(target: "rotation", "rotation to {peer} failed: {error:?}")
+            }
+            Event::InboundFailure { peer, error, .. } => {
+                debug!(target: "rotation", "rotation from {peer} failed: {error:?}")
+            }
+            Event::ResponseSent { .. } => {}
+        }
+    }
+}
@@ -11,29 +11,94 @@
 // ========================================
 
 extern crate alloc;
-use crate::cryptography::verify_public_bytes;
+use crate::amount_limits::AmountLimits;
+use crate::anchors::{served_by, BindingAnchors};
+use crate::approval_webhook::ApprovalWebhook;
+use crate::calldata::token_contract;
+use crate::chain::ChainClient;
+use crate::circuit_breaker::CircuitBreakers;
+use crate::device_sync::DeviceSync;
+use crate::discovery::PeerDiscovery;
+use crate::durable_nonce::DurableNonces;
+use crate::failover::Failover;
+use crate::identity::{
+    verify_address_correction, verify_attestation, verify_sender_profile, TrustedIssuers,
+};
+use crate::locks::timed_lock;
+use crate::openrpc::registered_document;
+use crate::pairing::{
+    pairing_message, verify_pairing, PairingChallenge, PAIRING_CHALLENGE_VALIDITY,
+};
+use crate::peer_exchange::verify_peer_binding;
+use crate::peer_metrics::PeerMetrics;
+use crate::plugins::Plugins;
+use crate::policy::{policy_outcomes, verify_second_approval, SecondApprover};
+use crate::pricing::FiatPricing;
+use crate::priority_fees::PriorityFeeOracle;
+use crate::revocation::{revocation_of, verify_revocation};
+use crate::risk::RiskScreening;
+use crate::rotation::{NodeIdentity, DEFAULT_ROTATION_GRACE, MAX_ROTATION_GRACE};
+use crate::rpc_middleware::{caller_scope, MethodRateLimiter};
+use crate::send_lock::SendLock;
+use crate::token_accounts::TokenAccounts;
+use crate::travel_rule::{encryption_secret, open, seal};
+use crate::tx_updates::{AccountScope, TxUpdates};
 use alloc::sync::Arc;
 use alloy::primitives::private::serde::{Deserialize, Serialize};
+use alloy::signers::local::PrivateKeySigner;
 use anyhow::anyhow;
+use curve25519_dalek::MontgomeryPoint;
 use db::DbWorker;
 use jsonrpsee::core::Error;
+use jsonrpsee::types::error::{CallError, ErrorObject};
 use jsonrpsee::{
     core::{async_trait, RpcResult, SubscriptionResult},
     proc_macros::rpc,
     PendingSubscriptionSink, SubscriptionMessage,
 };
-use libp2p::PeerId;
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
 use local_ip_address;
 use local_ip_address::local_ip;
-use log::{info, trace};
+use log::{error, info, trace, warn};
 use moka::future::Cache as AsyncCache;
+use primitives::accepted_assets::{check_accepted, decode_accepted_assets, AcceptedAssets};
+use primitives::address::VaneAddress;
+use primitives::anchors::{AnchorCall, BindingAnchor};
+use primitives::approvals::{Approval, PermitPayload, UNLIMITED_APPROVAL};
+use primitives::attestations::{decode_attestations, IdentityAttestation, VerificationBadge};
+use primitives::compute_budget::{transfer_compute_units, ComputeBudgetTiers};
+use primitives::counterparty::CounterpartyProfile;
 use primitives::data_structure::{
-    AirtableRequestBody, AirtableResponse, ChainSupported, Discovery, Fields, PeerRecord,
-    PostRecord, Record, Token, TxStateMachine, TxStatus, UserAccount,
+    AddressCorrection, AirtableRequestBody, AirtableResponse, BreakerState, BulkOutcome,
+    ChainHealth, ChainSupported, DbTxStateMachine, Discovery, Fields, NetworkCommand, PeerRecord,
+    PeerStats, PendingReceive, PostRecord, Record, RejectReason, SecondApproval, SenderProfile,
+    SubmissionRoute, Token, TxEvent, TxKind, TxPriority, TxStateMachine, TxStatus, TxWarning,
+    UserAccount, Warning,
 };
+use primitives::device_sync::{DevicePairingCode, PairedDevice, PAIRING_URI_SCHEME};
+use primitives::diagnosis::{ChainLookup, RetryStage, TxDiagnosis};
+use primitives::environment::{Environments, NetworkEnvironment, TestAccount};
+use primitives::errors::VaneRpcError;
+use primitives::fees::{FeePreference, FeeStats, FeeTiers, OVERPAY_STREAK};
+use primitives::history::HistorySearch;
+use primitives::identity_rotation::RotatedIdentity;
+use primitives::locale::{parse_amount, AmountLocale, LocalizedAmount, Separators};
+use primitives::payments::{payment_id, PaymentPart, PaymentStatus, MAX_PAYMENT_PARTS};
+use primitives::peer_exchange::PeerBinding;
+use primitives::plugins::{apply_decisions, PluginHook};
+use primitives::policy::{enforce, parse_policies, PolicyAction, PolicyRule, RuleOutcome};
+use primitives::revocation::{decode_revocations, AccountRevocation};
+use primitives::routes::{candidate_routes, RouteSuggestion};
+use primitives::travel_rule::{TravelRuleAttachment, TravelRuleData};
+use primitives::tx_builder::{derive_idempotency_key, TxStateMachineBuilder};
+use primitives::tx_state::{AwaitingApprovalTx, GenesisTx, NetConfirmedTx};
+use primitives::validation::{candidate_chains, validate_transfer};
 use reqwest::{ClientBuilder, Url};
-use sp_core::{Blake2Hasher, Hasher};
+use sp_core::H256;
 use sp_runtime::traits::Zero;
+use std::collections::HashMap;
+use std::sync::OnceLock;
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::{Mutex, MutexGuard};
 use db::DbWorkerInterface;
@@ -76,8 +141,29 @@ impl Airtable {
     }
 
     pub async fn list_all_peers(&self) -> Result<Vec<Discovery>, anyhow::Error> {
+        self.list_peers(None).await
+    }
+
+    /// peers registered with `account`, filtered by airtable instead of listing every peer
+    pub async fn peers_of(&self, account: &str) -> Result<Vec<Discovery>, anyhow::Error> {
+        // accounts are hex, base58 or ss58, nothing else may reach the formula
+        if !account.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Ok(vec![]);
+        }
+        let formula = format!(
+            "OR({{accountId1}}='{account}',{{accountId2}}='{account}',{{accountId3}}='{account}',{{accountId4}}='{account}')"
+        );
+        self.list_peers(Some(formula)).await
+    }
+
+    async fn list_peers(&self, formula: Option<String>) -> Result<Vec<Discovery>, anyhow::Error> {
         let url = Url::parse(AIRTABLE_URL)?;
-        let list_record_url = url.join(&(BASE_ID.to_string() + "/" + TABLE_ID))?;
+        let mut list_record_url = url.join(&(BASE_ID.to_string() + "/" + TABLE_ID))?;
+        if let Some(formula) = formula {
+            list_record_url
+                .query_pairs_mut()
+                .append_pair("filterByFormula", &formula);
+        }
 
         let req = self.client.get(list_record_url).build()?;
         let resp = self.client.execute(req).await?;
@@ -113,6 +199,9 @@ impl Airtable {
                 peer_id: record.fields.peer_id,
                 multi_addr: record.fields.multi_addr,
                 account_ids: accounts,
+                attestations: decode_attestations(record.fields.attestations.as_deref()),
+                revocations: decode_revocations(record.fields.revocations.as_deref()),
+                accepted_assets: decode_accepted_assets(record.fields.accepted_assets.as_deref()),
             };
             peers.push(disc)
         });
@@ -156,12 +245,20 @@ impl Airtable {
         let patch_record_url =
             url.join(&(BASE_ID.to_string() + "/" + "peer_discovery" + "/" + record_id.as_str()))?;
 
-        let acc_id = record.fields.account_id1.unwrap();
-        let patch_value = serde_json::json!({
-            "fields":{
-                "accountId1":acc_id
+        // only the accounts present are patched, the others keep their value
+        let fields = record.fields;
+        let mut patched = serde_json::Map::new();
+        for (name, account) in [
+            ("accountId1", fields.account_id1),
+            ("accountId2", fields.account_id2),
+            ("accountId3", fields.account_id3),
+            ("accountId4", fields.account_id4),
+        ] {
+            if let Some(account) = account {
+                patched.insert(name.to_string(), account.into());
             }
-        });
+        }
+        let patch_value = serde_json::json!({ "fields": patched });
         let resp = self
             .client
             .patch(patch_record_url)
@@ -183,6 +280,140 @@ impl Airtable {
         Ok(resp)
     }
 
+    // a patch request of the attestations field only
+    pub async fn update_attestations(
+        &self,
+        record_id: String,
+        attestations: Vec<IdentityAttestation>,
+    ) -> Result<Record, anyhow::Error> {
+        let url = Url::parse(AIRTABLE_URL)?;
+        let patch_record_url =
+            url.join(&(BASE_ID.to_string() + "/" + "peer_discovery" + "/" + record_id.as_str()))?;
+
+        let patch_value = serde_json::json!({
+            "fields":{
+                "attestations":serde_json::to_string(&attestations)?
+            }
+        });
+        let resp = self
+            .client
+            .patch(patch_record_url)
+            .json(&patch_value)
+            .send()
+            .await?;
+
+        if resp.status().is_server_error() {
+            Err(anyhow!("server error, update attestations"))?
+        }
+        if resp.status().is_client_error() {
+            Err(anyhow!("client error, update attestations"))?
+        }
+
+        let resp = resp.json::<Record>().await?;
+        Ok(resp)
+    }
+
+    // a patch request of the revocations field only
+    pub async fn update_revocations(
+        &self,
+        record_id: String,
+        revocations: Vec<AccountRevocation>,
+    ) -> Result<Record, anyhow::Error> {
+        let url = Url::parse(AIRTABLE_URL)?;
+        let patch_record_url =
+            url.join(&(BASE_ID.to_string() + "/" + "peer_discovery" + "/" + record_id.as_str()))?;
+
+        let patch_value = serde_json::json!({
+            "fields":{
+                "revocations":serde_json::to_string(&revocations)?
+            }
+        });
+        let resp = self
+            .client
+            .patch(patch_record_url)
+            .json(&patch_value)
+            .send()
+            .await?;
+
+        if resp.status().is_server_error() {
+            Err(anyhow!("server error, update revocations"))?
+        }
+        if resp.status().is_client_error() {
+            Err(anyhow!("client error, update revocations"))?
+        }
+
+        let resp = resp.json::<Record>().await?;
+        Ok(resp)
+    }
+
+    // a patch request of the accepted assets field only
+    pub async fn update_accepted_assets(
+        &self,
+        record_id: String,
+        accepted_assets: Vec<AcceptedAssets>,
+    ) -> Result<Record, anyhow::Error> {
+        let url = Url::parse(AIRTABLE_URL)?;
+        let patch_record_url =
+            url.join(&(BASE_ID.to_string() + "/" + "peer_discovery" + "/" + record_id.as_str()))?;
+
+        let patch_value = serde_json::json!({
+            "fields":{
+                "acceptedAssets":serde_json::to_string(&accepted_assets)?
+            }
+        });
+        let resp = self
+            .client
+            .patch(patch_record_url)
+            .json(&patch_value)
+            .send()
+            .await?;
+
+        if resp.status().is_server_error() {
+            Err(anyhow!("server error, update accepted assets"))?
+        }
+        if resp.status().is_client_error() {
+            Err(anyhow!("client error, update accepted assets"))?
+        }
+
+        let resp = resp.json::<Record>().await?;
+        Ok(resp)
+    }
+
+    // a patch request of the peer id and multi addr fields only
+    pub async fn update_identity(
+        &self,
+        record_id: String,
+        peer_id: String,
+        multi_addr: String,
+    ) -> Result<Record, anyhow::Error> {
+        let url = Url::parse(AIRTABLE_URL)?;
+        let patch_record_url =
+            url.join(&(BASE_ID.to_string() + "/" + "peer_discovery" + "/" + record_id.as_str()))?;
+
+        let patch_value = serde_json::json!({
+            "fields":{
+                "peerId":peer_id,
+                "multiAddr":multi_addr
+            }
+        });
+        let resp = self
+            .client
+            .patch(patch_record_url)
+            .json(&patch_value)
+            .send()
+            .await?;
+
+        if resp.status().is_server_error() {
+            Err(anyhow!("server error, update identity"))?
+        }
+        if resp.status().is_client_error() {
+            Err(anyhow!("client error, update identity"))?
+        }
+
+        let resp = resp.json::<Record>().await?;
+        Ok(resp)
+    }
+
     #[cfg(feature = "e2e")]
     pub async fn delete_all(&self) -> Result<(), anyhow::Error> {
         let url = Url::parse(AIRTABLE_URL)?;
@@ -223,6 +454,7 @@ impl Airtable {
 }
 
 /// Trait
+/// NOTE: methods added here are served by `rpc.discover`, describe them in `openrpc::rpc_methods`
 #[rpc(server, client)]
 pub trait TransactionRpc {
     /// register user profile, generate node peer id and push the profile for vane discovery server
@@ -252,13 +484,32 @@ pub trait TransactionRpc {
         accounts: Vec<(String, ChainSupported)>,
     ) -> RpcResult<()>;
 
-    /// initiate tx to be verified recv address and network choice
+    /// initiate tx to be verified recv address and network choice, returns the tx id
     /// params:
     ///
     /// - `sender address`,
     /// - `receiver_address`,
     /// - `amount`,
     /// - `networkId`
+    /// - `idempotencyKey` optional, derived from the transfer when missing. requests with the same
+    ///   key within `IDEMPOTENCY_WINDOW` return the first tx id instead of starting a new flow, a
+    ///   key reused for another transfer is refused
+    /// - `feePreference` optional fee tier and max fee per gas, medium tier under the node cap when missing
+    /// - `submissionRoute` optional, `PrivateRelay` keeps the signed tx out of the public mempool
+    /// - `memo` optional memo or destination tag, e.g. the reference an exchange credits by. a
+    ///   solana transfer carries it on chain as a memo instruction, e.g. its invoice reference
+    /// - `senderProfile` optional display name and sender signature of `SenderProfile::message`,
+    ///   shown to the receiver with the proof checked, evm senders only
+    /// - `environment` optional, refused when the node runs the network on another environment
+    /// - `reference` optional invoice or order id and `tags` optional labels, kept in the history
+    ///   for reconciliation and not signed
+    /// - `parentPayment` optional id of the split payment the transfer is a part of
+    /// - `travelRule` optional originator and beneficiary data, sealed to the sender and receiver
+    ///   nodes
+    /// - `ttlSecs` optional seconds the transfer may take until it is submitted, both nodes abort
+    ///   it as `Expired` once they pass
+    /// - `priority` optional, `Urgent` transfers are submitted ahead of the queued ones and redial
+    ///   an unreachable receiver, the fee tier follows the priority when `feePreference` is missing
     #[method(name = "initiateTransaction")]
     async fn initiate_transaction(
         &self,
@@ -267,11 +518,303 @@ pub trait TransactionRpc {
         amount: u128,
         token: String,
         network: String,
+        idempotency_key: Option<String>,
+        fee_preference: Option<FeePreference>,
+        submission_route: Option<SubmissionRoute>,
+        memo: Option<String>,
+        sender_profile: Option<SenderProfile>,
+        environment: Option<NetworkEnvironment>,
+        reference: Option<String>,
+        tags: Option<Vec<String>>,
+        parent_payment: Option<H256>,
+        travel_rule: Option<TravelRuleData>,
+        ttl_secs: Option<u64>,
+        priority: Option<TxPriority>,
+    ) -> RpcResult<H256>;
+
+    /// initiate a transfer worth `usdCents` of `token`, e.g. $100 of ETH. the amount is computed
+    /// from the current usd price of the token, locked with the tx. the sender confirmation and
+    /// the submission are refused with `PRICE_MOVED` once the price moved more than
+    /// `maxSlippageBps`, the slippage of the node when missing
+    #[method(name = "initiateFiatTransaction")]
+    async fn initiate_fiat_transaction(
+        &self,
+        sender: String,
+        receiver: String,
+        usd_cents: u64,
+        token: String,
+        network: String,
+        max_slippage_bps: Option<u32>,
+        idempotency_key: Option<String>,
+    ) -> RpcResult<H256>;
+
+    /// split one payment into transfers to several receivers, tokens or networks, returns the
+    /// payment id. every part is attested and confirmed like a transfer of its own, the parts
+    /// share the `reference` and `tags` and are recorded together or not at all
+    #[method(name = "initiateSplitPayment")]
+    async fn initiate_split_payment(
+        &self,
+        sender: String,
+        parts: Vec<PaymentPart>,
+        reference: Option<String>,
+        tags: Option<Vec<String>>,
+    ) -> RpcResult<H256>;
+
+    /// transfers of a split payment and whether it settled, in full or in part
+    #[method(name = "getPayment")]
+    async fn get_payment(&self, payment_id: H256) -> RpcResult<PaymentStatus>;
+
+    /// networks of the node `address` can live on going by its format, the network to pick when
+    /// a single one is returned. empty when the format is unknown or on no supported network
+    #[method(name = "detectChains")]
+    async fn detect_chains(&self, address: String) -> RpcResult<Vec<ChainSupported>>;
+
+    /// past interactions of this node with `address`: transfers and volumes each way, first and
+    /// last seen, attestation response times and the warnings raised about it. shown to the
+    /// sender before confirming
+    #[method(name = "getCounterparty")]
+    async fn get_counterparty(&self, address: String) -> RpcResult<CounterpartyProfile>;
+
+    /// low, medium and high fee suggestions for the next block of an evm network
+    #[method(name = "feeTiers")]
+    async fn fee_tiers(&self, network: String) -> RpcResult<FeeTiers>;
+
+    /// low, medium and high compute unit price suggestions for the next solana slots, raised while
+    /// the network is congested
+    #[method(name = "computeBudgetTiers")]
+    async fn compute_budget_tiers(&self) -> RpcResult<ComputeBudgetTiers>;
+
+    /// read a human `amount`, e.g. `1,234.56` or `1 234,56`, into the token smallest unit with
+    /// the separators of `locale`, english when `None`. returned along with its rendering
+    #[method(name = "parseAmount")]
+    async fn parse_amount(
+        &self,
+        amount: String,
+        token: Token,
+        locale: Option<AmountLocale>,
+    ) -> RpcResult<LocalizedAmount>;
+
+    /// render `units` of the token smallest unit with the separators of `locale`, english when
+    /// `None`
+    #[method(name = "formatAmount")]
+    async fn format_amount(
+        &self,
+        units: u128,
+        token: Token,
+        locale: Option<AmountLocale>,
+    ) -> RpcResult<LocalizedAmount>;
+
+    /// credit `account` with `amount` in the smallest unit on the dev chain of `network`, returns
+    /// its balance. only nodes in dev mode have a faucet
+    #[method(name = "faucet")]
+    async fn faucet(&self, account: String, amount: u128, network: String) -> RpcResult<u128>;
+
+    /// generate `count` evm test accounts, register them to the node peer record, kept out of
+    /// discovery, and fund each with `amount` on dev chains. refused for networks on mainnet, the
+    /// secret keys are returned in the clear
+    #[method(name = "provisionTestAccounts")]
+    async fn provision_test_accounts(
+        &self,
+        count: u8,
+        network: String,
+        amount: u128,
+    ) -> RpcResult<Vec<TestAccount>>;
+
+    /// approve a spender for a bounded amount of a contract token, returns the tx id. the approval
+    /// skips receiver attestation and goes straight to the sender confirmation
+    /// params:
+    ///
+    /// - `owner`
+    /// - `spender`
+    /// - `amount`
+    /// - `token`
+    /// - `unlimited` optional, required to approve `UNLIMITED_APPROVAL`
+    #[method(name = "approveToken")]
+    async fn approve_token(
+        &self,
+        owner: String,
+        spender: String,
+        amount: u128,
+        token: String,
+        unlimited: Option<bool>,
+    ) -> RpcResult<H256>;
+
+    /// revoke an allowance by approving zero, returns the tx id
+    #[method(name = "revokeApproval")]
+    async fn revoke_approval(
+        &self,
+        owner: String,
+        spender: String,
+        token: String,
+    ) -> RpcResult<H256>;
+
+    /// outstanding allowances granted through this node, optionally of a single owner
+    #[method(name = "listApprovals")]
+    async fn list_approvals(&self, owner: Option<String>) -> RpcResult<Vec<Approval>>;
+
+    /// EIP-2612 permit for the owner to sign, always bounded and expiring
+    /// params:
+    ///
+    /// - `owner`
+    /// - `spender`
+    /// - `amount`
+    /// - `token`
+    /// - `validitySecs` optional, `DEFAULT_PERMIT_VALIDITY` when missing, at most `MAX_PERMIT_VALIDITY`
+    #[method(name = "buildPermit")]
+    async fn build_permit(
+        &self,
+        owner: String,
+        spender: String,
+        amount: u128,
+        token: String,
+        validity_secs: Option<u64>,
+    ) -> RpcResult<PermitPayload>;
+
+    /// replace the operator policies, evaluated at each tx transition from then on
+    #[method(name = "setPolicies")]
+    async fn set_policies(&self, rules: Vec<PolicyRule>) -> RpcResult<()>;
+
+    /// operator policies in force
+    #[method(name = "listPolicies")]
+    async fn list_policies(&self) -> RpcResult<Vec<PolicyRule>>;
+
+    /// message a signing device signs to pair `account` with this node, bound to a one-time
+    /// challenge valid for 5 minutes. a new message replaces the previous one
+    #[method(name = "pairingMessage")]
+    async fn pairing_message(&self, account: String) -> RpcResult<String>;
+
+    /// pair a signing device, a watch-only node only sends for the accounts of paired devices
+    /// params:
+    ///
+    /// - `account`
+    /// - `signature` EIP-191 signature of the latest pairing message of `account`, it pairs once
+    #[method(name = "pairSigner")]
+    async fn pair_signer(&self, account: String, signature: Vec<u8>) -> RpcResult<()>;
+
+    #[method(name = "unpairSigner")]
+    async fn unpair_signer(&self, account: String) -> RpcResult<()>;
+
+    /// accounts of the paired signing devices
+    #[method(name = "listPairedSigners")]
+    async fn list_paired_signers(&self) -> RpcResult<Vec<String>>;
+
+    /// unlock the send operations of a node started with a send passphrase, the node locks itself
+    /// again once the unlock window passed. returns the unix timestamp in seconds it locks at
+    #[method(name = "unlock")]
+    async fn unlock(&self, passphrase: String) -> RpcResult<u64>;
+
+    /// lock the send operations again before the unlock window passed
+    #[method(name = "lock")]
+    async fn lock(&self) -> RpcResult<()>;
+
+    /// message `account` signs to bind itself to this node, shared with connected peers
+    /// params:
+    ///
+    /// - `account` registered by this node
+    /// - `signedAt` unix timestamp in seconds, the latest binding of an account wins
+    #[method(name = "peerBindingMessage")]
+    async fn peer_binding_message(&self, account: String, signed_at: u64) -> RpcResult<String>;
+
+    /// bind `account` to this node, peer exchange shares the binding with connected peers so
+    /// senders dial this node without asking the registry
+    /// params:
+    ///
+    /// - `account` registered by this node
+    /// - `signedAt` the timestamp in the signed message
+    /// - `signature` EIP-191 signature of the peer binding message by `account`
+    #[method(name = "addPeerBinding")]
+    async fn add_peer_binding(
+        &self,
+        account: String,
+        signed_at: u64,
+        signature: Vec<u8>,
+    ) -> RpcResult<()>;
+
+    /// account to node bindings this node verified, its own and the ones learned over peer exchange
+    #[method(name = "listPeerBindings")]
+    async fn list_peer_bindings(&self) -> RpcResult<Vec<PeerBinding>>;
+
+    /// single-use code linking another device of the user to this node, its uri is shown as a QR
+    /// code. a new code replaces the previous one
+    #[method(name = "devicePairingCode")]
+    async fn device_pairing_code(&self) -> RpcResult<DevicePairingCode>;
+
+    /// pair with the device showing a pairing code, contacts, policies and pending transfers then
+    /// sync between both over an encrypted channel
+    /// params:
+    ///
+    /// - `uri` scanned from the QR code, or `vane-pair:<code>@<multi addr>` entered by hand
+    #[method(name = "pairDevice")]
+    async fn pair_device(&self, uri: String) -> RpcResult<PairedDevice>;
+
+    /// stop syncing with a paired device, the records synced so far are kept
+    #[method(name = "unpairDevice")]
+    async fn unpair_device(&self, peer_id: String) -> RpcResult<()>;
+
+    #[method(name = "listPairedDevices")]
+    async fn list_paired_devices(&self) -> RpcResult<Vec<PairedDevice>>;
+
+    /// pending transfers of the paired devices, as last synced
+    #[method(name = "listDeviceTransfers")]
+    async fn list_device_transfers(&self) -> RpcResult<Vec<TxStateMachine>>;
+
+    /// rotate the node to a new libp2p identity, re-registered in discovery and announced to the
+    /// saved contacts and paired devices. the old identity keeps answering for the grace period,
+    /// the accounts bound to it sign the peer binding of the new one
+    /// params:
+    ///
+    /// - `graceSecs` optional, how long the old identity keeps answering, one day by default
+    #[method(name = "rotateNodeIdentity")]
+    async fn rotate_node_identity(&self, grace_secs: Option<u64>) -> RpcResult<RotatedIdentity>;
+
+    /// message the key of a compromised `account` signs to revoke it
+    /// params:
+    ///
+    /// - `account` compromised account
+    /// - `revokedAt` unix timestamp in seconds
+    #[method(name = "revocationMessage")]
+    async fn revocation_message(&self, account: String, revoked_at: u64) -> RpcResult<String>;
+
+    /// mark `account` as compromised, the revocation is gossiped to connected peers and kept in the
+    /// registry when this node registered the account. transfers to it are then refused by its
+    /// receiver nodes and warned about to senders. a revocation cannot be withdrawn
+    /// params:
+    ///
+    /// - `account` compromised account
+    /// - `revokedAt` the timestamp in the signed message
+    /// - `signature` EIP-191 signature of the revocation message by `account`
+    #[method(name = "revokeAccount")]
+    async fn revoke_account(
+        &self,
+        account: String,
+        revoked_at: u64,
+        signature: Vec<u8>,
     ) -> RpcResult<()>;
 
+    /// account revocations this node verified, its own and the ones learned from peers or the
+    /// registry
+    #[method(name = "listRevocations")]
+    async fn list_revocations(&self) -> RpcResult<Vec<AccountRevocation>>;
+
     /// confirm sender signifying agreeing all tx state after verification and this will trigger actual submission
+    /// params:
+    ///
+    /// - `tx` with the signed call payload
+    /// - `overrideWarnings` optional, required to confirm a tx with high severity warnings
     #[method(name = "senderConfirm")]
-    async fn sender_confirm(&self, tx: TxStateMachine) -> RpcResult<()>;
+    async fn sender_confirm(
+        &self,
+        tx: TxStateMachine,
+        override_warnings: Option<bool>,
+    ) -> RpcResult<()>;
+
+    /// designated approver signs off a tx held after the sender confirmation, the tx is then submitted
+    /// params:
+    ///
+    /// - `tx` at `AwaitingSecondApproval`
+    /// - `signature` EIP-191 signature of the tx second approval message by the approver account
+    #[method(name = "approverConfirm")]
+    async fn approver_confirm(&self, tx: TxStateMachine, signature: Vec<u8>) -> RpcResult<()>;
 
     /// watch tx update stream
     #[subscription(name ="subscribeTxUpdates",item = TxStateMachine )]
@@ -284,6 +827,147 @@ pub trait TransactionRpc {
     /// receiver confirmation on address and ownership of account ( network ) signifying correct token to the network choice
     #[method(name = "receiverConfirm")]
     async fn receiver_confirm(&self, tx: TxStateMachine) -> RpcResult<()>;
+
+    /// attestation requests waiting for this node's receiver, oldest first
+    /// params:
+    ///
+    /// - `network` optional, only requests on this network
+    /// - `minAgeSecs` optional, only requests received at least this long ago
+    /// - `maxAgeSecs` optional, only requests received at most this long ago
+    #[method(name = "listPendingReceives")]
+    async fn list_pending_receives(
+        &self,
+        network: Option<String>,
+        min_age_secs: Option<u64>,
+        max_age_secs: Option<u64>,
+    ) -> RpcResult<Vec<PendingReceive>>;
+
+    /// receiver confirmation of several attestation requests, each tx carrying its receiver signature.
+    /// a failing tx does not stop the others
+    #[method(name = "confirmPendingReceives")]
+    async fn confirm_pending_receives(
+        &self,
+        txs: Vec<TxStateMachine>,
+    ) -> RpcResult<Vec<BulkOutcome>>;
+
+    /// decline attestation requests, the senders see their tx as `RecvAddrFailed`, or as
+    /// `RecvRejected` with the `reason` when one is given
+    #[method(name = "rejectPendingReceives")]
+    async fn reject_pending_receives(
+        &self,
+        tx_nonces: Vec<u32>,
+        reason: Option<RejectReason>,
+    ) -> RpcResult<Vec<BulkOutcome>>;
+
+    /// reject the attestation request `tx_nonce` sent to the wrong chain or an old address,
+    /// proposing the address and network to send to instead. the correction is signed with the key
+    /// of the address the sender picked, see `AddressCorrection::message`
+    #[method(name = "rejectWithCorrection")]
+    async fn reject_with_correction(
+        &self,
+        tx_nonce: u32,
+        reason: RejectReason,
+        correction: AddressCorrection,
+    ) -> RpcResult<()>;
+
+    /// send the transfer `tx_id` the receiver rejected again, to the address and network it
+    /// proposed, returns the id of the new transfer
+    #[method(name = "restartWithCorrection")]
+    async fn restart_with_correction(&self, tx_id: H256) -> RpcResult<H256>;
+
+    /// the transfer `tx_nonce` on the other chains the sender and the receiver both registered an
+    /// account on, where the sender balance covers it, the largest balance first
+    #[method(name = "suggestRoutes")]
+    async fn suggest_routes(&self, tx_nonce: u32) -> RpcResult<Vec<RouteSuggestion>>;
+
+    /// send the transfer `tx_nonce` again on `network` between the accounts bound to the same
+    /// peers, returns the id of the new transfer. the original one fails before the sender signs
+    #[method(name = "rerouteTransaction")]
+    async fn reroute_transaction(&self, tx_nonce: u32, network: ChainSupported) -> RpcResult<H256>;
+
+    /// list peers registered to vane discovery
+    #[method(name = "listPeers")]
+    async fn list_peers(&self) -> RpcResult<Vec<Discovery>>;
+
+    /// store a third party attestation of one of this node's accounts with its registry record,
+    /// an attestation of the same issuer for the account is replaced
+    #[method(name = "addAttestation")]
+    async fn add_attestation(&self, attestation: IdentityAttestation) -> RpcResult<()>;
+
+    /// verification badges of `account`, from the registry attestations of the issuers this node trusts
+    #[method(name = "listBadges")]
+    async fn list_badges(&self, account: String) -> RpcResult<Vec<VerificationBadge>>;
+
+    /// advertise the tokens one of this node's accounts accepts with its registry record, in order
+    /// of preference. senders are refused transfers of other tokens, an empty list accepts any
+    #[method(name = "setAcceptedAssets")]
+    async fn set_accepted_assets(&self, account: String, tokens: Vec<Token>) -> RpcResult<()>;
+
+    /// tokens `address` accepts as advertised by its node, empty when it accepts any
+    #[method(name = "acceptedAssets")]
+    async fn accepted_assets(&self, address: String) -> RpcResult<Vec<Token>>;
+
+    /// call of the binding registry contract the wallet of `account`, an evm account of this node,
+    /// sends to anchor its binding to this node on chain
+    #[method(name = "anchorCall")]
+    async fn anchor_call(&self, account: String) -> RpcResult<AnchorCall>;
+
+    /// the binding of `account` to the node `peerId` as anchored in the binding registry contract,
+    /// to the node the off-chain registry serves it from when `peerId` is missing
+    #[method(name = "verifyBinding")]
+    async fn verify_binding(
+        &self,
+        account: String,
+        peer_id: Option<String>,
+    ) -> RpcResult<BindingAnchor>;
+
+    /// reachability and latency of each supported chain rpc endpoint
+    #[method(name = "chainHealth")]
+    async fn chain_health(&self) -> RpcResult<Vec<ChainHealth>>;
+
+    /// rtt, request success rate, last seen and bytes exchanged per peer since the node started
+    #[method(name = "peerStats")]
+    async fn peer_stats(&self) -> RpcResult<Vec<PeerStats>>;
+
+    /// submitted txs, both succeeded and failed. with a `query` only the txs whose reference or a
+    /// tag is the query or whose memo contains it, ignoring case
+    #[method(name = "txHistory")]
+    async fn tx_history(&self, query: Option<String>) -> RpcResult<Vec<DbTxStateMachine>>;
+
+    /// history records matching every filter of `search`, newest first. the text is a counterparty
+    /// address in any spelling, part of the sender name or memo, a reference or a tag
+    #[method(name = "searchHistory")]
+    async fn search_history(&self, search: HistorySearch) -> RpcResult<Vec<DbTxStateMachine>>;
+
+    /// history record of the submitted tx `tx_hash` with its explorer link and, once the tx is
+    /// included, its block number, effective gas price and fee paid
+    #[method(name = "txReceipt")]
+    async fn tx_receipt(&self, tx_hash: H256) -> RpcResult<DbTxStateMachine>;
+
+    /// travel rule data attached to the tx `tx_id`, on the sender and the receiver node alike.
+    /// `None` when the transfer carried none
+    #[method(name = "getTravelRule")]
+    async fn get_travel_rule(&self, tx_id: H256) -> RpcResult<Option<TravelRuleData>>;
+
+    /// network fees paid by the submitted txs with a receipt, in total and on average per network
+    /// and fee tier, with a warning for each tier that consistently overpays
+    #[method(name = "feeStats")]
+    async fn fee_stats(&self) -> RpcResult<FeeStats>;
+
+    /// where the transfer `tx_id` waits, since when and what to do about it: peer unreachable,
+    /// awaiting the receiver or the sender, pending in the mempool or a chain provider error
+    #[method(name = "diagnoseTx")]
+    async fn diagnose_tx(&self, tx_id: H256) -> RpcResult<TxDiagnosis>;
+
+    /// drive the stuck transfer `tx_id` again from its persisted state: resend the attestation
+    /// request, simulate the chain tx again or broadcast the signed one again. `stage` defaults to
+    /// the one the transfer waits at, the stage retried is returned
+    #[method(name = "retryTx")]
+    async fn retry_tx(&self, tx_id: H256, stage: Option<RetryStage>) -> RpcResult<RetryStage>;
+
+    /// OpenRPC document describing all methods, params, results and error codes
+    #[method(name = "rpc.discover")]
+    async fn discover(&self) -> RpcResult<serde_json::Value>;
 }
 
 /// handling tx submission & tx confirmation & tx simulation interactions
@@ -293,31 +977,233 @@ pub struct TransactionRpcWorker {
     /// local database worker
     pub db_worker: Arc<Mutex<DbWorker>>,
     /// central server to get peer data
-    pub airtable_client: Arc<Mutex<Airtable>>,
+    pub discovery: Arc<dyn PeerDiscovery>,
     /// rpc server url
     pub rpc_url: String,
-    /// receiving end of transaction which will be polled in websocket , updating state of tx to end user
-    pub rpc_receiver_channel: Arc<Mutex<Receiver<TxStateMachine>>>,
+    /// tx updates to the end user, fanned out to the subscriptions
+    pub tx_updates: TxUpdates,
     /// sender channel when user updates the transaction state, propagating to main service worker
     pub user_rpc_update_sender_channel: Arc<Mutex<Sender<Arc<Mutex<TxStateMachine>>>>>,
-    /// P2p peerId
-    pub peer_id: PeerId,
+    /// current p2p identity, moves on every rotation
+    pub identity: NodeIdentity,
     // txn_counter
     // HashMap<txn_counter,Integrity hash>
     /// tx pending store
     pub moka_cache: AsyncCache<u64, TxStateMachine>, // initial fees, after dry running tx initialy without optimization
+    /// nonces of the staged transfers per sender, network and token
+    pub in_flight: InFlightTransfers,
+    /// idempotency key to tx id and transfer digest of recently initiated transfers
+    pub idempotency_keys: AsyncCache<String, (H256, String)>,
+    /// pending pairing challenge per lowercase signer account, spent by the pairing
+    pub pairing_challenges: AsyncCache<String, PairingChallenge>,
+    /// per method call quotas
+    pub rate_limiter: MethodRateLimiter,
+    /// networks this node accepts transactions for
+    pub supported_chains: Vec<ChainSupported>,
+    /// chain clients per network, queried for fee tiers
+    pub chain_clients: HashMap<ChainSupported, Arc<dyn ChainClient>>,
+    /// breakers pausing the submissions to chains whose provider rejects them
+    pub circuit_breakers: CircuitBreakers,
+    /// approver sender confirmed txs are held for
+    pub second_approver: Option<SecondApprover>,
+    /// the node holds no signing capability, sending needs a paired signing device
+    pub watch_only: bool,
+    /// initiated transfers are screened after their tx id is returned
+    pub optimistic_send: bool,
+    /// passphrase lock of the send operations, `None` when the node has no send passphrase
+    pub send_lock: Option<SendLock>,
+    /// minimum and precision of the transfer amounts per token
+    pub amount_limits: AmountLimits,
+    /// usd prices of the fiat denominated transfers
+    pub fiat_pricing: FiatPricing,
+    /// compute budget of the solana transfers
+    pub priority_fees: PriorityFeeOracle,
+    /// nonce accounts the solana transfers are signed against
+    pub durable_nonces: DurableNonces,
+    /// token accounts of the solana receivers
+    pub token_accounts: TokenAccounts,
+    /// environments and rpc endpoints the networks run on
+    pub environments: Environments,
+    /// issuers whose attestations are shown as verification badges
+    pub trusted_issuers: TrustedIssuers,
+    /// operator validation plugins run before attestation and before submission
+    pub plugins: Plugins,
+    /// approval system sender confirmed transfers are submitted to before submission
+    pub approval_webhook: Option<ApprovalWebhook>,
+    /// risk provider screening the receivers at genesis and the requirements of the operator
+    pub risk_screening: RiskScreening,
+    /// on chain registry of the account to node bindings
+    pub binding_anchors: BindingAnchors,
+    /// connection metrics the swarm loop records per peer
+    pub peer_metrics: PeerMetrics,
+    /// pairing and sync with the other devices of the user
+    pub device_sync: DeviceSync,
+    /// for pairing commands to the swarm loop
+    pub p2p_command_tx: Arc<Sender<NetworkCommand>>,
+    /// election of the instance running the p2p identity, a standby refuses the transfer updates
+    pub failover: Option<Failover>,
+    /// OpenRPC document of the registered methods, built once at startup
+    openrpc_document: Arc<OnceLock<serde_json::Value>>,
+}
+
+/// what `TransactionRpcWorker::new` starts the worker with, the node builder fills it in
+pub struct TransactionRpcWorkerParams {
+    pub discovery: Arc<dyn PeerDiscovery>,
+    pub db_worker: Arc<Mutex<DbWorker>>,
+    /// tx updates of the main service worker, fanned out to the subscriptions
+    pub rpc_recv_channel: Arc<Mutex<Receiver<TxStateMachine>>>,
+    pub user_rpc_update_sender_channel: Arc<Mutex<Sender<Arc<Mutex<TxStateMachine>>>>>,
+    /// port of the rpc server
+    pub port: u16,
+    pub identity: NodeIdentity,
+    pub moka_cache: AsyncCache<u64, TxStateMachine>,
+    pub supported_chains: Vec<ChainSupported>,
+    /// chain clients, one per network
+    pub chain_clients: Vec<Arc<dyn ChainClient>>,
+    pub circuit_breakers: CircuitBreakers,
+    pub second_approver: Option<SecondApprover>,
+    pub watch_only: bool,
+    pub optimistic_send: bool,
+    pub send_lock: Option<SendLock>,
+    pub amount_limits: AmountLimits,
+    pub fiat_pricing: FiatPricing,
+    pub priority_fees: PriorityFeeOracle,
+    pub durable_nonces: DurableNonces,
+    pub token_accounts: TokenAccounts,
+    pub environments: Environments,
+    pub trusted_issuers: TrustedIssuers,
+    pub plugins: Plugins,
+    pub approval_webhook: Option<ApprovalWebhook>,
+    pub risk_screening: RiskScreening,
+    pub binding_anchors: BindingAnchors,
+    pub peer_metrics: PeerMetrics,
+    pub device_sync: DeviceSync,
+    pub p2p_command_tx: Arc<Sender<NetworkCommand>>,
+    pub failover: Option<Failover>,
+}
+
+/// what `TransactionRpcWorker::initiate` starts a transfer with, the optional fields are the
+/// optional `initiateTransaction` params
+#[derive(Clone, Default)]
+pub struct InitiateTransactionParams {
+    pub sender: String,
+    pub receiver: String,
+    pub amount: u128,
+    /// token and network names as `initiateTransaction` takes them
+    pub token: String,
+    pub network: String,
+    /// derived from the transfer when missing
+    pub idempotency_key: Option<String>,
+    pub fee_preference: Option<FeePreference>,
+    pub submission_route: Option<SubmissionRoute>,
+    pub memo: Option<String>,
+    pub sender_profile: Option<SenderProfile>,
+    pub environment: Option<NetworkEnvironment>,
+    pub reference: Option<String>,
+    pub tags: Option<Vec<String>>,
+    /// split payment the transfer is a part of
+    pub parent_payment: Option<H256>,
+    pub travel_rule: Option<TravelRuleData>,
+    /// seconds the transfer may take until it is submitted
+    pub ttl_secs: Option<u64>,
+    pub priority: Option<TxPriority>,
+}
+
+/// nonces of the transfers staged per sender, network and token. the balance check of a new
+/// transfer reads the pending store for these only, settled ones are forgotten as it finds them
+#[derive(Clone, Default)]
+pub struct InFlightTransfers {
+    nonces: Arc<std::sync::Mutex<HashMap<(VaneAddress, ChainSupported, Token), Vec<u32>>>>,
+}
+
+impl InFlightTransfers {
+    fn update<T>(&self, tx: &TxStateMachine, update: impl FnOnce(&mut Vec<u32>) -> T) -> T {
+        let token = tx.token.unwrap_or(Token::native(tx.network));
+        let key = (tx.sender_address, tx.network, token);
+        let mut nonces = self
+            .nonces
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let outcome = update(nonces.entry(key).or_default());
+        if nonces.get(&key).is_some_and(Vec::is_empty) {
+            nonces.remove(&key);
+        }
+        outcome
+    }
+
+    pub fn track(&self, tx: &TxStateMachine) {
+        if tx.kind != TxKind::Transfer {
+            return;
+        }
+        self.update(tx, |nonces| {
+            if !nonces.contains(&tx.tx_nonce) {
+                nonces.push(tx.tx_nonce);
+            }
+        });
+    }
+
+    /// nonces of the other transfers of the sender of `tx` on its network and token
+    pub fn others(&self, tx: &TxStateMachine) -> Vec<u32> {
+        self.update(tx, |nonces| {
+            nonces
+                .iter()
+                .copied()
+                .filter(|nonce| *nonce != tx.tx_nonce)
+                .collect()
+        })
+    }
+
+    /// `tx_nonce` of the sender of `tx` is no longer in flight
+    pub fn forget(&self, tx: &TxStateMachine, tx_nonce: u32) {
+        self.update(tx, |nonces| nonces.retain(|nonce| *nonce != tx_nonce));
+    }
 }
 
 impl TransactionRpcWorker {
-    pub async fn new(
-        airtable_client: Airtable,
-        db_worker: Arc<Mutex<DbWorker>>,
-        rpc_recv_channel: Arc<Mutex<Receiver<TxStateMachine>>>,
-        user_rpc_update_sender_channel: Arc<Mutex<Sender<Arc<Mutex<TxStateMachine>>>>>,
-        port: u16,
-        peer_id: PeerId,
-        moka_cache: AsyncCache<u64, TxStateMachine>,
-    ) -> Result<Self, anyhow::Error> {
+    const CHAIN_PROBE_TIMEOUT: core::time::Duration = core::time::Duration::from_secs(5);
+    /// how long a transfer request is remembered to dedupe client retries
+    pub const IDEMPOTENCY_WINDOW: core::time::Duration = core::time::Duration::from_secs(120);
+    pub const DEFAULT_PERMIT_VALIDITY: core::time::Duration = core::time::Duration::from_secs(3600);
+    /// permits outliving a day are refused, a leaked signature stays usable until the deadline
+    pub const MAX_PERMIT_VALIDITY: core::time::Duration =
+        core::time::Duration::from_secs(24 * 3600);
+    /// how long pairing waits for the device showing the code to answer
+    const PAIRING_TIMEOUT: core::time::Duration = core::time::Duration::from_secs(30);
+    /// how long the swarm loop gets to swap the identity and announce it to the contacts
+    const ROTATION_TIMEOUT: core::time::Duration = core::time::Duration::from_secs(30);
+
+    pub async fn new(params: TransactionRpcWorkerParams) -> Result<Self, anyhow::Error> {
+        let TransactionRpcWorkerParams {
+            discovery,
+            db_worker,
+            rpc_recv_channel,
+            user_rpc_update_sender_channel,
+            port,
+            identity,
+            moka_cache,
+            supported_chains,
+            chain_clients,
+            circuit_breakers,
+            second_approver,
+            watch_only,
+            optimistic_send,
+            send_lock,
+            amount_limits,
+            fiat_pricing,
+            priority_fees,
+            durable_nonces,
+            token_accounts,
+            environments,
+            trusted_issuers,
+            plugins,
+            approval_webhook,
+            risk_screening,
+            binding_anchors,
+            peer_metrics,
+            device_sync,
+            p2p_command_tx,
+            failover,
+        } = params;
         let local_ip = local_ip()
             .map_err(|err| anyhow!("failed to get local ip address; caused by: {err}"))?;
 
@@ -328,67 +1214,959 @@ impl TransactionRpcWorker {
         } else {
             rpc_url = format!("{}:{}", local_ip.to_string(), port);
         }
-        Ok(Self {
+        // the transfers restored from the event log are in flight as well
+        let in_flight = InFlightTransfers::default();
+        for (_, tx) in moka_cache.iter() {
+            in_flight.track(&tx);
+        }
+        let worker = Self {
             db_worker,
-            airtable_client: Arc::new(Mutex::new(airtable_client)),
+            discovery,
             rpc_url,
-            rpc_receiver_channel: rpc_recv_channel,
+            tx_updates: TxUpdates::new(rpc_recv_channel),
             user_rpc_update_sender_channel,
-            peer_id,
+            identity,
             moka_cache,
-        })
+            in_flight,
+            idempotency_keys: AsyncCache::builder()
+                .name("initiated transfers by idempotency key")
+                .time_to_live(Self::IDEMPOTENCY_WINDOW)
+                .build(),
+            pairing_challenges: AsyncCache::builder()
+                .name("pairing challenges by signer account")
+                .time_to_live(PAIRING_CHALLENGE_VALIDITY)
+                .build(),
+            rate_limiter: MethodRateLimiter::default(),
+            supported_chains,
+            chain_clients: chain_clients
+                .into_iter()
+                .map(|client| (client.network(), client))
+                .collect(),
+            circuit_breakers,
+            second_approver,
+            watch_only,
+            optimistic_send,
+            send_lock,
+            amount_limits,
+            fiat_pricing,
+            priority_fees,
+            durable_nonces,
+            token_accounts,
+            environments,
+            trusted_issuers,
+            plugins,
+            approval_webhook,
+            risk_screening,
+            binding_anchors,
+            peer_metrics,
+            device_sync,
+            p2p_command_tx,
+            failover,
+            openrpc_document: Arc::default(),
+        };
+        worker.openrpc_document();
+        Ok(worker)
     }
 
-    /// first dry tx, returns the projected fees
-    pub async fn dry_run_tx(
-        network: ChainSupported,
-        _sender: String,
-        _recv: String,
-        _token: Token,
-        _amount: u64,
-    ) -> Result<u64, anyhow::Error> {
-        let _fees = match network {
-            ChainSupported::Polkadot => {}
-            ChainSupported::Ethereum => {}
-            ChainSupported::Bnb => {}
-            ChainSupported::Solana => {}
-        };
-        todo!()
+    /// `rpc.discover` document, the methods are listed from the rpc module registrations
+    fn openrpc_document(&self) -> &serde_json::Value {
+        self.openrpc_document
+            .get_or_init(|| registered_document(self.clone().into_rpc().method_names()))
     }
-}
 
-#[async_trait]
-impl TransactionRpcServer for TransactionRpcWorker {
-    async fn register_vane_web3(
+    fn peer_id(&self) -> PeerId {
+        self.identity.peer_id()
+    }
+
+    /// `initiateTransaction` of in-process callers, e.g. a transfer restarted to another address
+    pub async fn initiate(&self, params: InitiateTransactionParams) -> RpcResult<H256> {
+        self.rate_limiter.check("initiateTransaction")?;
+        let (idempotency_key, transfer_digest, tx_builder) = self.prepare_transfer(params).await?;
+        // retries share the entry, concurrent ones wait for the first to be staged
+        self.initiate_once(idempotency_key, transfer_digest, tx_builder)
+            .await
+    }
+
+    /// checks of a transfer to initiate, returned as its idempotency key, the digest of the
+    /// transfer and the builder of the genesis tx
+    async fn prepare_transfer(
+        &self,
+        params: InitiateTransactionParams,
+    ) -> RpcResult<(String, String, TxStateMachineBuilder)> {
+        let InitiateTransactionParams {
+            sender,
+            receiver,
+            amount,
+            token,
+            network,
+            idempotency_key,
+            fee_preference,
+            submission_route,
+            memo,
+            sender_profile,
+            environment,
+            reference,
+            tags,
+            parent_payment,
+            travel_rule,
+            ttl_secs,
+            priority,
+        } = params;
+        info!("initiated sending transaction");
+        let token: Token = token.parse().map_err(rpc_error)?;
+
+        let network: ChainSupported = network.parse().map_err(rpc_error)?;
+        if !self.supported_chains.contains(&network) {
+            Err(rpc_error(VaneRpcError::UnsupportedNetwork {
+                network: network.into(),
+            }))?
+        }
+        // one transfer never mixes environments, the receiver node refuses it as well
+        let node_environment = self.environments.of(network);
+        if let Some(environment) = environment.filter(|env| *env != node_environment) {
+            Err(rpc_error(VaneRpcError::EnvironmentMismatch {
+                network: format!("{network:?}"),
+                expected: node_environment.name(network).to_string(),
+                found: environment.name(network).to_string(),
+            }))?
+        }
+        // the in-memory chains of dev mode hold every token, real testnets only the native one
+        let dev_chain = self
+            .chain_clients
+            .get(&network)
+            .is_some_and(|client| client.has_faucet());
+        if node_environment == NetworkEnvironment::Testnet
+            && !dev_chain
+            && token_contract(token, network).is_some()
+        {
+            Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!(
+                    "{token:?} is a mainnet token contract, {} transfers are native token only",
+                    node_environment.name(network)
+                ),
+            }))?
+        }
+        let submission_route = submission_route.unwrap_or_default();
+        let priority = priority.unwrap_or_default();
+        // the priority picks the fee tier when the sender picks none
+        let fee_preference = fee_preference.or_else(|| {
+            (priority != TxPriority::Normal).then(|| FeePreference {
+                tier: priority.fee_tier(),
+                max_fee_per_gas: None,
+            })
+        });
+        // fail fast when the current fees are already above the cap, enforced again when the tx is built
+        if let Some(client) = self.chain_clients.get(&network) {
+            if submission_route == SubmissionRoute::PrivateRelay && !client.has_private_relay() {
+                Err(rpc_error(VaneRpcError::InvalidParams {
+                    reason: format!("no private relay configured for {network:?}"),
+                }))?
+            }
+            if let Ok(tiers) = client.fee_tiers().await {
+                tiers
+                    .select(fee_preference.unwrap_or_default(), client.max_fee_cap())
+                    .map_err(rpc_error)?;
+            }
+        }
+        if network == ChainSupported::Solana {
+            if let Ok(tiers) = self.priority_fees.compute_budget_tiers().await {
+                let tier = fee_preference.unwrap_or_default().tier;
+                let (limit, cap) = (
+                    transfer_compute_units(token),
+                    self.priority_fees.max_compute_unit_price(),
+                );
+                tiers.select(tier, limit, cap).map_err(rpc_error)?;
+            }
+        }
+        // a client key is only reused by retries of the same transfer
+        let transfer_digest = derive_idempotency_key(&sender, &receiver, amount, token, network);
+        let idempotency_key = idempotency_key.unwrap_or_else(|| transfer_digest.clone());
+        // a bad proof fails here rather than showing up as unverified to the receiver
+        if let Some(profile) = &sender_profile {
+            let sender = VaneAddress::parse(&sender).map_err(rpc_error)?;
+            verify_sender_profile(profile, &sender).map_err(|err| {
+                rpc_error(VaneRpcError::InvalidParams {
+                    reason: err.to_string(),
+                })
+            })?;
+        }
+        // the receiver copy is sealed per device when the attestation requests go out
+        let travel_rule = match travel_rule {
+            Some(data) => {
+                let (sender, receiver) = (
+                    VaneAddress::parse(&sender).map_err(rpc_error)?,
+                    VaneAddress::parse(&receiver).map_err(rpc_error)?,
+                );
+                data.check(&sender, &receiver).map_err(rpc_error)?;
+                let secret = encryption_secret(&self.identity.keypair()).map_err(rpc_error)?;
+                let encryption_key = MontgomeryPoint::mul_base_clamped(secret).to_bytes();
+                Some(TravelRuleAttachment {
+                    sender_copy: seal(&data, encryption_key).map_err(rpc_error)?,
+                    receiver_copy: None,
+                })
+            }
+            None => None,
+        };
+        let mut tx_builder = TxStateMachine::builder()
+            .sender(sender)
+            .receiver(receiver)
+            .network(network)
+            .environment(self.environments.of(network))
+            .token(token)
+            .amount(amount)
+            .submission_route(submission_route)
+            .priority(priority);
+        if let Some(fee_preference) = fee_preference {
+            tx_builder = tx_builder.fee_preference(fee_preference);
+        }
+        if let Some(memo) = memo {
+            tx_builder = tx_builder.memo(memo);
+        }
+        if let Some(reference) = reference {
+            tx_builder = tx_builder.reference(reference);
+        }
+        if let Some(tags) = tags {
+            tx_builder = tx_builder.tags(tags);
+        }
+        if let Some(payment_id) = parent_payment {
+            tx_builder = tx_builder.parent_payment(payment_id);
+        }
+        if let Some(profile) = sender_profile {
+            tx_builder = tx_builder.sender_profile(profile.display_name, profile.signature);
+        }
+        if let Some(attachment) = travel_rule {
+            tx_builder = tx_builder.travel_rule(attachment);
+        }
+        if let Some(ttl_secs) = ttl_secs {
+            if ttl_secs == 0 {
+                Err(rpc_error(VaneRpcError::InvalidParams {
+                    reason: "the ttl of a transfer should be above zero".to_string(),
+                }))?
+            }
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_err(|err| {
+                    rpc_error(anyhow!("system clock before unix epoch; caused by: {err}"))
+                })?
+                .as_secs();
+            tx_builder = tx_builder.deadline(now.saturating_add(ttl_secs));
+        }
+        Ok((idempotency_key, transfer_digest, tx_builder))
+    }
+
+    /// stage the transfer once per idempotency key, a key reused for another transfer is refused
+    async fn initiate_once(
+        &self,
+        idempotency_key: String,
+        transfer_digest: String,
+        tx_builder: TxStateMachineBuilder,
+    ) -> RpcResult<H256> {
+        let digest = transfer_digest.clone();
+        let staged = async {
+            self.stage_transaction(tx_builder)
+                .await
+                .map(|tx_id| (tx_id, digest))
+        };
+        let entry = self
+            .idempotency_keys
+            .entry(idempotency_key)
+            .or_try_insert_with(staged)
+            .await
+            .map_err(|err| rpc_error((*err).clone()))?;
+        let (tx_id, staged_digest) = entry.value();
+        if !entry.is_fresh() {
+            if *staged_digest != transfer_digest {
+                Err(rpc_error(VaneRpcError::InvalidParams {
+                    reason: "the idempotency key was used for another transfer".to_string(),
+                }))?
+            }
+            info!("duplicate transfer request, returning the already initiated tx");
+        }
+        Ok(*tx_id)
+    }
+
+    /// the sender transfers of the same token still in flight, when together with `tx` they exceed
+    /// the sender balance. the second one would otherwise fail on chain once the first went through
+    async fn conflicting_transfers(
+        &self,
+        client: &dyn ChainClient,
+        tx: &TxStateMachine,
+    ) -> Result<Option<TxWarning>, anyhow::Error> {
+        let token = tx.token.unwrap_or(Token::native(tx.network));
+        let mut in_flight = Vec::new();
+        for tx_nonce in self.in_flight.others(tx) {
+            match self.moka_cache.get(&u64::from(tx_nonce)).await {
+                Some(pending) if !pending.status.is_final() => in_flight.push(pending),
+                // settled, failed or evicted from the pending store
+                _ => self.in_flight.forget(tx, tx_nonce),
+            }
+        }
+        if in_flight.is_empty() {
+            return Ok(None);
+        }
+        let Some(balance) = client.spendable_balance(&tx.sender_address, token).await? else {
+            return Ok(None);
+        };
+        let committed = in_flight.iter().fold(0u128, |committed, pending| {
+            committed.saturating_add(pending.transfer_amount())
+        });
+        if committed.saturating_add(tx.transfer_amount()) <= balance {
+            return Ok(None);
+        }
+        Ok(Some(TxWarning::ConflictingTransfers {
+            in_flight: in_flight.len() as u32,
+            committed,
+            balance,
+        }))
+    }
+
+    /// routes of `tx` on the other chains the sender balance covers, the largest balance first
+    async fn alternative_routes(
+        &self,
+        tx: &TxStateMachine,
+    ) -> Result<Vec<RouteSuggestion>, anyhow::Error> {
+        let peers = self.discovery.list_all_peers().await?;
+        let mut routes = Vec::new();
+        for mut route in candidate_routes(&peers, tx, &self.supported_chains) {
+            let Some(client) = self.chain_clients.get(&route.network) else {
+                continue;
+            };
+            let sender = VaneAddress::parse(&route.sender)?;
+            if let Some(balance) = client.spendable_balance(&sender, route.token).await? {
+                route.balance = balance;
+                if balance >= route.amount {
+                    routes.push(route);
+                }
+            }
+        }
+        routes.sort_by(|a, b| b.balance.cmp(&a.balance));
+        Ok(routes)
+    }
+
+    /// validate the transfer, assign the vane tx nonce and hand the genesis tx to the main service worker
+    async fn stage_transaction(
+        &self,
+        tx_builder: TxStateMachineBuilder,
+    ) -> Result<H256, VaneRpcError> {
+        let tx_state_machine = self.build_transaction(tx_builder).await?;
+        if !self.optimistic_send {
+            let tx_state_machine = self.screen_transaction(tx_state_machine).await?;
+            return self.propagate_transaction(tx_state_machine).await;
+        }
+
+        let tx_id = tx_state_machine.tx_id();
+        let worker = self.clone();
+        tokio::spawn(async move {
+            let screened = worker.screen_transaction(tx_state_machine.clone()).await;
+            let outcome = match screened {
+                Ok(tx) => {
+                    // the subscriptions see the warnings and badges before the receiver answers
+                    worker.tx_updates.publish(tx.clone());
+                    worker.propagate_transaction(tx).await.map(|_| ())
+                }
+                Err(err) => {
+                    let mut failed = tx_state_machine;
+                    failed.status = TxStatus::Withdrawn(err.to_string());
+                    worker.tx_updates.publish(failed.clone());
+                    // recorded in the event log by the main service worker
+                    worker.propagate_transaction(failed).await.map(|_| ())
+                }
+            };
+            if let Err(err) = outcome {
+                error!("tx {tx_id:?} screening outcome not propagated; caused by: {err}");
+            }
+        });
+        Ok(tx_id)
+    }
+
+    /// stage the parts of a split payment. their genesis events are written in one db write
+    /// before any part is propagated, a crash never leaves part of a payment recorded
+    async fn stage_payment(
+        &self,
+        tx_builders: Vec<TxStateMachineBuilder>,
+    ) -> Result<(), VaneRpcError> {
+        let mut parts = Vec::with_capacity(tx_builders.len());
+        for tx_builder in tx_builders {
+            let tx_state_machine = self.build_transaction(tx_builder).await?;
+            // screened before the payment is recorded, optimistic send or not
+            parts.push(self.screen_transaction(tx_state_machine).await?);
+        }
+        let recorded_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|err| anyhow!("system clock before unix epoch; caused by: {err}"))?
+            .as_secs();
+        let events = parts
+            .iter()
+            .map(|tx| TxEvent::new(tx.clone(), recorded_at))
+            .collect();
+        timed_lock(&self.db_worker, "db_worker")
+            .await
+            .record_tx_events(events)
+            .await?;
+        // the main service worker finds the genesis events recorded and does not write them again
+        for tx_state_machine in parts {
+            self.propagate_transaction(tx_state_machine).await?;
+        }
+        Ok(())
+    }
+
+    /// genesis tx of `tx_builder` with its nonce reserved, refused while the node cannot send
+    async fn build_transaction(
+        &self,
+        tx_builder: TxStateMachineBuilder,
+    ) -> Result<TxStateMachine, VaneRpcError> {
+        self.ensure_leader()?;
+        self.ensure_unlocked()?;
+        // checked before the nonce is taken, a refused send leaves no gap in the nonce sequence
+        let unnumbered = tx_builder.clone().build()?;
+        let warning = self.amount_limits.check(&unnumbered)?;
+        let sender = unnumbered.sender_address.to_string();
+        self.ensure_paired_signer(&sender).await?;
+        let durable_nonce = match unnumbered.network {
+            ChainSupported::Solana => self.durable_nonces.nonce_of(&sender).await?,
+            _ => None,
+        };
+
+        // taken in one db write, redundant nodes on the shared db never assign the same nonce
+        let nonce = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .reserve_nonce()
+            .await?;
+
+        // construct the tx
+        let mut tx_state_machine = tx_builder.tx_nonce(nonce).build()?;
+        tx_state_machine.durable_nonce = durable_nonce;
+        tx_state_machine.warnings.extend(warning.map(Warning::from));
+        self.in_flight.track(&tx_state_machine);
+        Ok(tx_state_machine)
+    }
+
+    /// registry, chain and risk checks of the genesis tx and the operator policies and plugins
+    async fn screen_transaction(
+        &self,
+        mut tx_state_machine: TxStateMachine,
+    ) -> Result<TxStateMachine, VaneRpcError> {
+        let receiver = tx_state_machine.receiver_address.to_string();
+        match self
+            .trusted_issuers
+            .lookup(&*self.discovery, &receiver)
+            .await
+        {
+            Ok(badges) => tx_state_machine.receiver_badges = badges,
+            Err(err) => warn!("receiver badges lookup failed; caused by: {err}"),
+        }
+        // a token the receiver does not take is refused before it is asked to attest, as is a
+        // receiver whose binding to its node is not anchored when the operator requires it
+        if tx_state_machine.kind == TxKind::Transfer {
+            let token = tx_state_machine
+                .token
+                .unwrap_or(Token::native(tx_state_machine.network));
+            match self.discovery.list_all_peers().await {
+                Ok(peers) => {
+                    let preferences: Vec<AcceptedAssets> = peers
+                        .iter()
+                        .flat_map(|peer| peer.accepted_assets.clone())
+                        .collect();
+                    check_accepted(&preferences, &tx_state_machine.receiver_address, token)?;
+                    self.binding_anchors
+                        .check(&peers, &tx_state_machine)
+                        .await?
+                }
+                Err(err) => warn!("receiver accepted assets lookup failed; caused by: {err}"),
+            }
+        }
+        let revoked = revocation_of(
+            &*timed_lock(&self.db_worker, "db_worker").await,
+            &*self.discovery,
+            &receiver,
+        )
+        .await;
+        match revoked {
+            Ok(Some(_)) => tx_state_machine
+                .warnings
+                .push(Warning::from(TxWarning::RevokedAccount)),
+            Ok(None) => {}
+            Err(err) => warn!("receiver revocation lookup failed; caused by: {err}"),
+        }
+        info!("successfully initially verified sender and receiver and related network bytes");
+
+        // on chain receiver checks before attestation, the transfer is not blocked when they fail.
+        // approval spenders are contracts by design
+        let client = self.chain_clients.get(&tx_state_machine.network);
+        if let (Some(client), TxKind::Transfer) = (client, tx_state_machine.kind) {
+            match client.recipient_warnings(&tx_state_machine).await {
+                Ok(warnings) => tx_state_machine
+                    .warnings
+                    .extend(warnings.into_iter().map(Warning::from)),
+                Err(err) => warn!("receiver checks failed; caused by: {err}"),
+            }
+            match self
+                .conflicting_transfers(&**client, &tx_state_machine)
+                .await
+            {
+                Ok(Some(warning)) => tx_state_machine.warnings.push(Warning::from(warning)),
+                Ok(None) => {}
+                Err(err) => warn!("sender balance check failed; caused by: {err}"),
+            }
+            // a sender short on the chosen chain is pointed at another one it holds the asset on
+            let token = tx_state_machine
+                .token
+                .unwrap_or(Token::native(tx_state_machine.network));
+            let balance = client
+                .spendable_balance(&tx_state_machine.sender_address, token)
+                .await;
+            let amount = tx_state_machine.transfer_amount();
+            let routes = match balance {
+                Ok(Some(balance)) if balance < amount => {
+                    self.alternative_routes(&tx_state_machine).await
+                }
+                _ => Ok(vec![]),
+            };
+            match routes {
+                Ok(routes) => tx_state_machine
+                    .warnings
+                    .extend(routes.first().map(|route| {
+                        Warning::from(TxWarning::AlternativeRoute {
+                            network: route.network,
+                            token: route.token,
+                            balance: route.balance,
+                        })
+                    })),
+                Err(err) => warn!("alternative routes lookup failed; caused by: {err}"),
+            }
+        }
+        // an spl transfer to a receiver without a token account fails on chain, unless the tx
+        // creates it at the expense of the sender
+        let solana_transfer = tx_state_machine.network == ChainSupported::Solana
+            && tx_state_machine.kind == TxKind::Transfer;
+        if solana_transfer {
+            match self.token_accounts.missing_account(&tx_state_machine).await {
+                Ok(Some(token_account)) => {
+                    let created = self.token_accounts.creates_missing();
+                    let token = tx_state_machine.token.unwrap_or(Token::Sol);
+                    tx_state_machine
+                        .warnings
+                        .push(Warning::from(TxWarning::MissingTokenAccount {
+                            token,
+                            rent: token_account.rent,
+                            created,
+                        }));
+                    if created {
+                        tx_state_machine.receiver_token_account = Some(token_account);
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => warn!("receiver token account lookup failed; caused by: {err}"),
+            }
+        }
+        // the sender may pick a lower tier when the chosen one kept paying well above the low tier
+        let network = tx_state_machine.network;
+        if matches!(network, ChainSupported::Ethereum | ChainSupported::Bnb) {
+            let tier = tx_state_machine.fee_preference.unwrap_or_default().tier;
+            // only the latest transfers of the tier decide its overpay streak
+            let history = timed_lock(&self.db_worker, "db_worker")
+                .await
+                .get_tier_fee_txs(network, tier, OVERPAY_STREAK as usize)
+                .await;
+            match history {
+                Ok(history) => tx_state_machine
+                    .warnings
+                    .extend(FeeStats::fold(history.into_iter().rev()).warning(network, tier)),
+                Err(err) => warn!("fee stats lookup failed; caused by: {err}"),
+            }
+        }
+        for warning in &tx_state_machine.warnings {
+            let (tx_nonce, code, message) =
+                (tx_state_machine.tx_nonce, &warning.code, &warning.message);
+            if warning.requires_override() {
+                error!("tx {tx_nonce}: {code}, {message}");
+            } else {
+                warn!("tx {tx_nonce}: {code}, {message}");
+            }
+        }
+        // the assessment is kept with the genesis tx in the event log
+        let assessment = self.risk_screening.screen(&tx_state_machine).await;
+        if let Err(err) = &assessment {
+            error!(
+                "tx {} refused by the risk screening; {err}",
+                tx_state_machine.tx_nonce
+            );
+        }
+        tx_state_machine.risk_assessment = assessment?;
+        // a blocking policy refuses the transfer before the receiver is asked to attest
+        tx_state_machine.policy_outcomes = self.enforce_policies(&tx_state_machine).await?;
+        let decisions = self
+            .plugins
+            .decide(PluginHook::PreAttestation, &tx_state_machine)
+            .await?;
+        apply_decisions(&mut tx_state_machine, decisions)?;
+        Ok(tx_state_machine)
+    }
+
+    /// hand the genesis tx to the main service worker
+    async fn propagate_transaction(
+        &self,
+        tx_state_machine: TxStateMachine,
+    ) -> Result<H256, VaneRpcError> {
+        self.ensure_leader()?;
+        // dry run the tx
+
+        //let fees = self::dry_run_tx().map_err(|err|anyhow!("{}",err))?;
+
+        // propagate the tx to lower layer (Main service worker layer)
+        let tx_id = tx_state_machine.tx_id();
+        let sender_channel = timed_lock(
+            &self.user_rpc_update_sender_channel,
+            "user_rpc_update_sender_channel",
+        )
+        .await;
+
+        let sender = sender_channel.clone();
+        sender
+            .send(Arc::from(Mutex::new(tx_state_machine)))
+            .await
+            .map_err(|_| anyhow!("failed to send initial tx state to sender channel"))?;
+        info!("propagated initiated transaction to tx handling layer");
+        Ok(tx_id)
+    }
+
+    /// travel rule data of the first of the tx `events` carrying it, opened with the copy sealed
+    /// to this node
+    fn open_travel_rule(&self, events: &[TxEvent]) -> Result<Option<TravelRuleData>, VaneRpcError> {
+        let Some(attachment) = events
+            .iter()
+            .find_map(|event| event.tx.travel_rule.as_ref())
+        else {
+            return Ok(None);
+        };
+        let secret = encryption_secret(&self.identity.keypair())?;
+        // the receiver copy is only set on the receiver node and on the sender once attested
+        [
+            attachment.receiver_copy.as_ref(),
+            Some(&attachment.sender_copy),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|sealed| open(secret, sealed).ok())
+        .map(Some)
+        .ok_or(VaneRpcError::InvalidParams {
+            reason: "the travel rule data is not sealed to the current node identity".into(),
+        })
+    }
+
+    /// evaluate the operator policies against `tx`, a blocking outcome refuses the transition
+    async fn enforce_policies(
+        &self,
+        tx: &TxStateMachine,
+    ) -> Result<Vec<RuleOutcome>, VaneRpcError> {
+        let outcomes =
+            policy_outcomes(&*timed_lock(&self.db_worker, "db_worker").await, tx).await?;
+        enforce(&outcomes)?;
+        Ok(outcomes)
+    }
+
+    /// pending attestation request the receiver acts on, requests are the txs a sender node asked
+    /// this node to attest
+    async fn pending_receive(&self, tx_nonce: u32) -> Result<TxStateMachine, VaneRpcError> {
+        let pending = self
+            .moka_cache
+            .get(&u64::from(tx_nonce))
+            .await
+            .ok_or(VaneRpcError::AttestationExpired { tx_nonce })?;
+        if pending.inbound_req_id.is_none() {
+            Err(VaneRpcError::InvalidTxState {
+                status: format!("{:?}", pending.status),
+                reason: "not an attestation request to this node".to_string(),
+            })?
+        }
+        Ok(pending)
+    }
+
+    /// pending transfer `tx_nonce` this node sends
+    async fn pending_send(&self, tx_nonce: u32) -> Result<TxStateMachine, VaneRpcError> {
+        let pending = self
+            .moka_cache
+            .get(&u64::from(tx_nonce))
+            .await
+            .filter(|pending| pending.inbound_req_id.is_none())
+            .ok_or(VaneRpcError::InvalidParams {
+                reason: format!("no pending transfer {tx_nonce} sent by this node"),
+            })?;
+        Ok(pending)
+    }
+
+    /// receiver attestation of a pending request, `tx` carries the receiver signature
+    async fn confirm_receive(&self, tx: TxStateMachine) -> Result<(), VaneRpcError> {
+        self.ensure_leader()?;
+        // return error as we do not accept any other TxStatus at this api and the receiver should have signed for confirmation
+        let recv_signature = tx
+            .recv_signature
+            .clone()
+            .ok_or(VaneRpcError::SignatureMissing {
+                who: "receiver".to_string(),
+            })?;
+        // the sender checks the accepted amount again against its own copy of the tx
+        if let Some(accepted) = tx
+            .accepted_amount
+            .filter(|accepted| !tx.can_accept(*accepted))
+        {
+            Err(VaneRpcError::InvalidParams {
+                reason: format!(
+                    "the accepted amount {accepted} should be above zero and below {}",
+                    tx.amount
+                ),
+            })?
+        }
+        let tx = GenesisTx::try_from(tx)?;
+        self.ensure_before_deadline(tx.tx_nonce).await?;
+        // the warnings are for the sender, the receiver only attests its address
+        self.take_pending_tx(&tx, true).await?;
+        // verify the tx-state-machine integrity
+        // TODO
+        // tx status to TxStatus::RecvAddrConfirmed
+        let tx = tx.recv_signed(recv_signature);
+        timed_lock(
+            &self.user_rpc_update_sender_channel,
+            "user_rpc_update_sender_channel",
+        )
+        .await
+        .send(Arc::from(Mutex::new(tx.into_inner())))
+        .await
+        .map_err(|_| anyhow!("failed to send recv confirmation tx state to sender channel"))?;
+        Ok(())
+    }
+
+    /// decline a pending attestation request, the node copy is answered so the sender gets its own
+    /// tx back. with a `reason` the sender sees the request rejected on purpose rather than failed
+    async fn reject_receive(
+        &self,
+        tx_nonce: u32,
+        reason: Option<RejectReason>,
+        correction: Option<AddressCorrection>,
+    ) -> Result<(), VaneRpcError> {
+        self.ensure_leader()?;
+        let pending = self.pending_receive(tx_nonce).await?;
+        let tx = GenesisTx::try_from(pending)?;
+        self.take_pending_tx(&tx, true).await?;
+        let mut answer = match reason {
+            Some(reason) => tx.recv_rejected(reason),
+            None => tx.recv_declined(),
+        };
+        answer.address_correction = correction;
+        timed_lock(
+            &self.user_rpc_update_sender_channel,
+            "user_rpc_update_sender_channel",
+        )
+        .await
+        .send(Arc::from(Mutex::new(answer)))
+        .await
+        .map_err(|_| anyhow!("failed to send recv rejection tx state to sender channel"))?;
+        Ok(())
+    }
+
+    /// a tx is confirmed only before the deadline of its sender, checked on the node copy
+    async fn ensure_before_deadline(&self, tx_nonce: u32) -> Result<(), VaneRpcError> {
+        match self.moka_cache.get(&u64::from(tx_nonce)).await {
+            Some(tx) => ensure_not_overdue(&tx),
+            None => Ok(()),
+        }
+    }
+
+    /// a standby runs no swarm, the transfer updates it took would wait on it until they expire
+    fn ensure_leader(&self) -> Result<(), VaneRpcError> {
+        match &self.failover {
+            Some(failover) if !failover.is_leader() => Err(VaneRpcError::StandbyNode),
+            _ => Ok(()),
+        }
+    }
+
+    /// a node with a send passphrase only sends once unlocked with it
+    fn ensure_unlocked(&self) -> Result<(), VaneRpcError> {
+        match &self.send_lock {
+            Some(send_lock) => send_lock.ensure_unlocked(),
+            None => Ok(()),
+        }
+    }
+
+    /// a watch-only node only sends for the accounts of paired signing devices
+    async fn ensure_paired_signer(&self, sender: &str) -> Result<(), VaneRpcError> {
+        if !self.watch_only {
+            return Ok(());
+        }
+        let paired = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_paired_signers()
+            .await?;
+        if !paired
+            .iter()
+            .any(|account| account.eq_ignore_ascii_case(sender))
+        {
+            Err(VaneRpcError::SignerNotPaired {
+                account: sender.to_string(),
+            })?
+        }
+        Ok(())
+    }
+
+    /// network of a token the node submits to
+    fn token_network(&self, token: Token) -> Result<ChainSupported, VaneRpcError> {
+        let network = ChainSupported::from(token);
+        if !self.supported_chains.contains(&network) {
+            Err(VaneRpcError::UnsupportedNetwork {
+                network: network.into(),
+            })?
+        }
+        Ok(network)
+    }
+
+    /// stage an approval of `spender`, a zero amount revokes
+    async fn stage_approval(
+        &self,
+        owner: String,
+        spender: String,
+        amount: u128,
+        token: Token,
+    ) -> Result<H256, VaneRpcError> {
+        let network = self.token_network(token)?;
+        let tx_builder = TxStateMachine::builder()
+            .sender(owner)
+            .receiver(spender)
+            .network(network)
+            .environment(self.environments.of(network))
+            .token(token)
+            .amount(amount)
+            .approval()
+            .status(TxStatus::NetConfirmed);
+        self.stage_transaction(tx_builder).await
+    }
+
+    /// remove the pending tx the update refers to, the update is only accepted if it carries the
+    /// same status as the node copy so a client cannot skip or replay a stage.
+    /// `override_warnings` is checked against the warnings of the tx as staged by this node, not as
+    /// sent back by the client
+    async fn take_pending_tx(
+        &self,
+        tx: &TxStateMachine,
+        override_warnings: bool,
+    ) -> Result<(), VaneRpcError> {
+        let pending = self.moka_cache.get(&u64::from(tx.tx_nonce)).await.ok_or(
+            VaneRpcError::AttestationExpired {
+                tx_nonce: tx.tx_nonce,
+            },
+        )?;
+        if pending.status != tx.status {
+            Err(VaneRpcError::InvalidTxState {
+                status: format!("{:?}", pending.status),
+                reason: format!("tx update is at {:?}", tx.status),
+            })?
+        }
+        let unacknowledged: Vec<String> = pending
+            .warnings
+            .iter()
+            .filter(|warning| warning.requires_override())
+            .map(|warning| warning.code.clone())
+            .collect();
+        if !override_warnings && !unacknowledged.is_empty() {
+            Err(VaneRpcError::WarningOverrideRequired {
+                codes: unacknowledged,
+            })?
+        }
+        // remove from cache
+        self.moka_cache.remove(&u64::from(tx.tx_nonce)).await;
+        Ok(())
+    }
+
+    /// time a lightweight json-rpc call against the chain rpc endpoint at `url`
+    pub async fn probe_chain(
+        network: ChainSupported,
+        environment: NetworkEnvironment,
+        url: String,
+    ) -> ChainHealth {
+        let method = match network {
+            ChainSupported::Polkadot => "system_health",
+            ChainSupported::Ethereum | ChainSupported::Bnb => "eth_blockNumber",
+            ChainSupported::Solana => "getHealth",
+        };
+        // substrate endpoints are configured as websocket, the same node serves http
+        let url = url
+            .replacen("wss://", "https://", 1)
+            .replacen("ws://", "http://", 1);
+        let body = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": []});
+
+        let started = std::time::Instant::now();
+        let resp = reqwest::Client::new()
+            .post(url)
+            .timeout(Self::CHAIN_PROBE_TIMEOUT)
+            .json(&body)
+            .send()
+            .await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let error = match resp {
+            Ok(resp) if resp.status().is_success() => None,
+            Ok(resp) => Some(format!("chain rpc responded with {}", resp.status())),
+            Err(err) => Some(err.to_string()),
+        };
+        ChainHealth {
+            network,
+            environment,
+            reachable: error.is_none(),
+            latency_ms,
+            error,
+            breaker: BreakerState::Closed,
+        }
+    }
+
+    /// first dry tx, returns the projected fees
+    pub async fn dry_run_tx(
+        network: ChainSupported,
+        _sender: String,
+        _recv: String,
+        _token: Token,
+        _amount: u64,
+    ) -> Result<u64, anyhow::Error> {
+        let _fees = match network {
+            ChainSupported::Polkadot => {}
+            ChainSupported::Ethereum => {}
+            ChainSupported::Bnb => {}
+            ChainSupported::Solana => {}
+        };
+        todo!()
+    }
+}
+
+#[async_trait]
+impl TransactionRpcServer for TransactionRpcWorker {
+    async fn register_vane_web3(
         &self,
         name: String,
         account_id: String,
         network: String,
     ) -> RpcResult<()> {
+        self.rate_limiter.check("register")?;
         // TODO verify the account id as it belongs to the registerer
-        let network = network.as_str().into();
+        let network: ChainSupported = network.parse().map_err(rpc_error)?;
         let user_account = UserAccount {
             user_name: name,
             account_id: account_id.clone(),
             network,
         };
-        self.db_worker
-            .lock()
+        timed_lock(&self.db_worker, "db_worker")
             .await
             .set_user_account(user_account)
-            .await?;
+            .await
+            .map_err(rpc_error)?;
 
         // NOTE: the peer-record is already registered, the following is only updating account details of the record
         // update: account address related to peer id
         // ========================================================================================//
 
         // fetch the record
-        let record = self
-            .db_worker
-            .lock()
+        let record = timed_lock(&self.db_worker, "db_worker")
             .await
-            .get_user_peer_id(None, Some(self.peer_id.to_string()))
-            .await?;
+            .get_user_peer_id(None, Some(self.peer_id().to_string()))
+            .await
+            .map_err(rpc_error)?;
 
         let peer_account = PeerRecord {
             record_id: record.record_id.clone(),
@@ -402,33 +2180,103 @@ impl TransactionRpcServer for TransactionRpcWorker {
         };
         info!("updated user peer record to be stored in local db");
 
-        self.db_worker
-            .lock()
+        timed_lock(&self.db_worker, "db_worker")
             .await
             .update_user_peer_id_accounts(peer_account.clone())
-            .await?;
+            .await
+            .map_err(rpc_error)?;
 
-        // update to airtable
+        // update to discovery
         let field: Fields = peer_account.into();
         let req_body = PostRecord::new(field);
 
-        self.airtable_client
-            .lock()
-            .await
+        self.discovery
             .update_peer(req_body, record.record_id)
-            .await?;
+            .await
+            .map_err(rpc_error)?;
 
-        info!("updated airtable db with user peer id");
+        info!("updated discovery with user peer id");
 
         Ok(())
     }
 
     async fn add_account(
         &self,
-        _name: String,
-        _accounts: Vec<(String, ChainSupported)>,
+        name: String,
+        accounts: Vec<(String, ChainSupported)>,
     ) -> RpcResult<()> {
-        todo!()
+        self.rate_limiter.check("addAccount")?;
+        for (account, network) in &accounts {
+            if !candidate_chains(account).contains(network) {
+                Err(VaneRpcError::InvalidAddress {
+                    address: account.clone(),
+                })?
+            }
+        }
+        let record = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_user_peer_id(None, Some(self.peer_id().to_string()))
+            .await
+            .map_err(rpc_error)?;
+
+        // the accounts of the peer record bind the user accounts on every chain to this node, the
+        // routes across chains are looked up from them
+        let mut bound: Vec<String> = [
+            record.account_id1,
+            record.account_id2,
+            record.account_id3,
+            record.account_id4,
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        for (account, _) in &accounts {
+            if !bound.contains(account) {
+                bound.push(account.clone());
+            }
+        }
+        if bound.len() > 4 {
+            Err(VaneRpcError::InvalidParams {
+                reason: format!("a peer binds at most 4 accounts, {} requested", bound.len()),
+            })?
+        }
+        let mut bound = bound.into_iter();
+        let peer_account = PeerRecord {
+            record_id: record.record_id.clone(),
+            peer_id: None,
+            account_id1: bound.next(),
+            account_id2: bound.next(),
+            account_id3: bound.next(),
+            account_id4: bound.next(),
+            multi_addr: None,
+            keypair: None,
+        };
+        timed_lock(&self.db_worker, "db_worker")
+            .await
+            .update_user_peer_id_accounts(peer_account.clone())
+            .await
+            .map_err(rpc_error)?;
+        for (account_id, network) in accounts {
+            let user_account = UserAccount {
+                user_name: name.clone(),
+                account_id,
+                network,
+            };
+            timed_lock(&self.db_worker, "db_worker")
+                .await
+                .set_user_account(user_account)
+                .await
+                .map_err(rpc_error)?;
+        }
+
+        let req_body = PostRecord::new(Fields::from(peer_account));
+        self.discovery
+            .update_peer(req_body, record.record_id)
+            .await
+            .map_err(rpc_error)?;
+        info!("updated discovery with the added accounts");
+
+        Ok(())
     }
 
     async fn initiate_transaction(
@@ -438,121 +2286,555 @@ impl TransactionRpcServer for TransactionRpcWorker {
         amount: u128,
         token: String,
         network: String,
-    ) -> RpcResult<()> {
-        info!("initiated sending transaction");
-        let token = token.as_str().into();
-
-        let network = network.as_str().into();
-        if let (Ok(net_sender), Ok(net_recv)) = (
-            verify_public_bytes(sender.as_str(), token, network),
-            verify_public_bytes(receiver.as_str(), token, network),
-        ) {
-            if net_sender != net_recv {
-                Err(anyhow!("sender and receiver should be same network"))?
-            }
-
-            info!("successfully initially verified sender and receiver and related network bytes");
-            // construct the tx
-            let mut sender_recv = sender.as_bytes().to_vec();
-            sender_recv.extend_from_slice(receiver.as_bytes());
-            let multi_addr = Blake2Hasher::hash(&sender_recv[..]);
-
-            let mut nonce = 0;
-            nonce = self.db_worker.lock().await.get_nonce().await? + 1;
-            // update the db on nonce
-            self.db_worker.lock().await.increment_nonce().await?;
-
-            let tx_state_machine = TxStateMachine {
-                sender_address: sender,
-                receiver_address: receiver,
-                multi_id: multi_addr,
-                recv_signature: None,
-                network: net_sender,
-                status: TxStatus::default(),
-                amount,
-                signed_call_payload: None,
-                call_payload: None,
-                inbound_req_id: None,
-                outbound_req_id: None,
-                tx_nonce: nonce,
-            };
-
-            // dry run the tx
-
-            //let fees = self::dry_run_tx().map_err(|err|anyhow!("{}",err))?;
-
-            // propagate the tx to lower layer (Main service worker layer)
-            let sender_channel = self.user_rpc_update_sender_channel.lock().await;
+        idempotency_key: Option<String>,
+        fee_preference: Option<FeePreference>,
+        submission_route: Option<SubmissionRoute>,
+        memo: Option<String>,
+        sender_profile: Option<SenderProfile>,
+        environment: Option<NetworkEnvironment>,
+        reference: Option<String>,
+        tags: Option<Vec<String>>,
+        parent_payment: Option<H256>,
+        travel_rule: Option<TravelRuleData>,
+        ttl_secs: Option<u64>,
+        priority: Option<TxPriority>,
+    ) -> RpcResult<H256> {
+        self.initiate(InitiateTransactionParams {
+            sender,
+            receiver,
+            amount,
+            token,
+            network,
+            idempotency_key,
+            fee_preference,
+            submission_route,
+            memo,
+            sender_profile,
+            environment,
+            reference,
+            tags,
+            parent_payment,
+            travel_rule,
+            ttl_secs,
+            priority,
+        })
+        .await
+    }
 
-            let sender = sender_channel.clone();
-            sender
-                .send(Arc::from(Mutex::new(tx_state_machine)))
-                .await
-                .map_err(|_| anyhow!("failed to send initial tx state to sender channel"))?;
-            info!("propagated initiated transaction to tx handling layer")
-        } else {
-            Err(anyhow!(
-                "sender and receiver should be correct accounts for the specified token"
-            ))?
+    async fn initiate_fiat_transaction(
+        &self,
+        sender: String,
+        receiver: String,
+        usd_cents: u64,
+        token: String,
+        network: String,
+        max_slippage_bps: Option<u32>,
+        idempotency_key: Option<String>,
+    ) -> RpcResult<H256> {
+        self.rate_limiter.check("initiateFiatTransaction")?;
+        let token: Token = token.parse().map_err(rpc_error)?;
+        let network: ChainSupported = network.parse().map_err(rpc_error)?;
+        if !self.supported_chains.contains(&network) {
+            Err(rpc_error(VaneRpcError::UnsupportedNetwork {
+                network: network.into(),
+            }))?
         }
-        Ok(())
+        let (quote, amount) = self
+            .fiat_pricing
+            .quote(usd_cents, token, max_slippage_bps)
+            .await
+            .map_err(rpc_error)?;
+        info!(
+            "initiated ${}.{:02} transfer of {amount} {token:?}",
+            usd_cents / 100,
+            usd_cents % 100
+        );
+        let tx_builder = TxStateMachine::builder()
+            .sender(sender.clone())
+            .receiver(receiver.clone())
+            .network(network)
+            .environment(self.environments.of(network))
+            .token(token)
+            .amount(amount)
+            .fiat_quote(quote);
+        // retries of the same usd value share the entry whatever the price did in between
+        let transfer_digest = format!(
+            "usd:{}",
+            derive_idempotency_key(&sender, &receiver, usd_cents.into(), token, network)
+        );
+        let idempotency_key = idempotency_key.unwrap_or_else(|| transfer_digest.clone());
+        self.initiate_once(idempotency_key, transfer_digest, tx_builder)
+            .await
     }
 
-    /// sender confirms by updating TxStatus to SenderConfirmed
+    async fn initiate_split_payment(
+        &self,
+        sender: String,
+        parts: Vec<PaymentPart>,
+        reference: Option<String>,
+        tags: Option<Vec<String>>,
+    ) -> RpcResult<H256> {
+        self.rate_limiter.check("initiateSplitPayment")?;
+        if parts.len() < 2 || parts.len() > MAX_PAYMENT_PARTS {
+            Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("a split payment has 2 to {MAX_PAYMENT_PARTS} parts"),
+            }))?
+        }
+        // a bad part fails the payment before any transfer is initiated
+        for part in &parts {
+            let network = self.token_network(part.token).map_err(rpc_error)?;
+            validate_transfer(&sender, &part.receiver, part.token, network).map_err(rpc_error)?;
+        }
+        let window = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|err| rpc_error(anyhow!("system clock before unix epoch; caused by: {err}")))?
+            .as_secs()
+            / Self::IDEMPOTENCY_WINDOW.as_secs();
+        let payment_id = payment_id(&sender, &parts, window);
+        let mut tx_builders = Vec::with_capacity(parts.len());
+        for part in parts {
+            self.rate_limiter.check("initiateTransaction")?;
+            let network = ChainSupported::from(part.token);
+            let (_, _, tx_builder) = self
+                .prepare_transfer(InitiateTransactionParams {
+                    sender: sender.clone(),
+                    receiver: part.receiver,
+                    amount: part.amount,
+                    token: part.token.into(),
+                    network: network.into(),
+                    memo: part.memo,
+                    reference: reference.clone(),
+                    tags: tags.clone(),
+                    parent_payment: Some(payment_id),
+                    ..Default::default()
+                })
+                .await?;
+            tx_builders.push(tx_builder);
+        }
+        // a retry in the same window gets the same payment id and is not staged again
+        let payment_key = format!("{payment_id:?}");
+        let staged = async {
+            self.stage_payment(tx_builders)
+                .await
+                .map(|()| (payment_id, payment_key.clone()))
+        };
+        let entry = self
+            .idempotency_keys
+            .entry(payment_key.clone())
+            .or_try_insert_with(staged)
+            .await
+            .map_err(|err| rpc_error((*err).clone()))?;
+        if !entry.is_fresh() {
+            info!("duplicate split payment request, returning the already initiated payment");
+        }
+        info!("initiated split payment {payment_id:?}");
+        Ok(payment_id)
+    }
+
+    async fn get_payment(&self, payment_id: H256) -> RpcResult<PaymentStatus> {
+        self.rate_limiter.check("getPayment")?;
+        let events = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_payment_tx_events(payment_id)
+            .await
+            .map_err(rpc_error)?;
+        let scope = caller_scope();
+        let events = events.into_iter().filter(|event| scope.allows(&event.tx));
+        PaymentStatus::fold(payment_id, events).ok_or_else(|| {
+            rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("no payment with id {payment_id:?}"),
+            })
+        })
+    }
+
+    /// sender confirms by updating TxStatus to SenderConfirmed
     /// at this stage receiver should have confirmed and sender should also have confirmed
     /// sender cannot confirm if TxStatus is RecvAddrFailed
-    async fn sender_confirm(&self, mut tx: TxStateMachine) -> RpcResult<()> {
-        let sender_channel = self.user_rpc_update_sender_channel.lock().await;
-        if tx.signed_call_payload.is_none() && tx.status != TxStatus::RecvAddrConfirmationPassed {
-            // return error as receiver hasnt confirmed yet or sender hasnt confirmed on his turn
-            Err(Error::Custom(
-                "Wait for Receiver to confirm or sender should confirm".to_string(),
-            ))?
-        } else {
-            // remove from cache
-            self.moka_cache.remove(&tx.tx_nonce.into()).await;
-            // verify the tx-state-machine integrity
-            // TODO
-            // update the TxStatus to TxStatus::SenderConfirmed
-            tx.sender_confirmation();
-            let sender = sender_channel.clone();
-            sender.send(Arc::from(Mutex::new(tx))).await.map_err(|_| {
-                anyhow!("failed to send sender confirmation tx state to sender-channel")
-            })?;
+    async fn sender_confirm(
+        &self,
+        tx: TxStateMachine,
+        override_warnings: Option<bool>,
+    ) -> RpcResult<()> {
+        self.rate_limiter.check("senderConfirm")?;
+        self.ensure_leader().map_err(rpc_error)?;
+        // return error as receiver hasnt confirmed yet
+        let tx = NetConfirmedTx::try_from(tx).map_err(rpc_error)?;
+        self.ensure_before_deadline(tx.tx_nonce)
+            .await
+            .map_err(rpc_error)?;
+        self.ensure_unlocked().map_err(rpc_error)?;
+        self.ensure_paired_signer(&tx.sender_address.to_string())
+            .await
+            .map_err(rpc_error)?;
+        if let Some(durable_nonce) = &tx.durable_nonce {
+            self.durable_nonces
+                .ensure_current(durable_nonce)
+                .await
+                .map_err(rpc_error)?;
+        }
+        let signed_call_payload =
+            tx.signed_call_payload
+                .clone()
+                .ok_or(rpc_error(VaneRpcError::SignatureMissing {
+                    who: "sender".to_string(),
+                }))?;
+        // policies are evaluated again on the node copy, they may have changed or the tx may have
+        // aged into a denied window since it was staged
+        let mut outcomes = vec![];
+        let mut plugin_warnings = vec![];
+        // the second approval is node state, whatever approval the client sent along is ignored
+        let mut second_approval = None;
+        if let Some(mut pending) = self.moka_cache.get(&u64::from(tx.tx_nonce)).await {
+            second_approval = pending.second_approval.clone();
+            // the sender signs the amount of a fiat transfer at the locked price only
+            self.fiat_pricing.check(&pending).await.map_err(rpc_error)?;
+            outcomes = self.enforce_policies(&pending).await.map_err(rpc_error)?;
+            let decisions = self
+                .plugins
+                .decide(PluginHook::PreSubmission, &pending)
+                .await
+                .map_err(rpc_error)?;
+            let staged_warnings = pending.warnings.len();
+            apply_decisions(&mut pending, decisions).map_err(rpc_error)?;
+            plugin_warnings = pending.warnings.split_off(staged_warnings);
+            // the approval system of the operator has the last word, waited on synchronously
+            if let Some(webhook) = &self.approval_webhook {
+                webhook.approve(&pending).await.map_err(rpc_error)?;
+            }
+        }
+        let needs_approver = outcomes
+            .iter()
+            .find(|outcome| outcome.action == PolicyAction::RequireSecondApprover);
+        if let (None, Some(outcome)) = (&self.second_approver, needs_approver) {
+            Err(rpc_error(VaneRpcError::PolicyViolation {
+                rule_id: outcome.rule_id.clone(),
+                reason: "no second approver is designated on this node".to_string(),
+            }))?
         }
+        self.take_pending_tx(&tx, override_warnings.unwrap_or_default())
+            .await
+            .map_err(rpc_error)?;
+        // verify the tx-state-machine integrity
+        // TODO
+        // update the TxStatus to TxStatus::SenderConfirmed
+        let mut tx = tx.sender_signed(signed_call_payload).into_inner();
+        tx.policy_outcomes = outcomes;
+        tx.second_approval = second_approval;
+        tx.warnings.extend(plugin_warnings);
+        // the channel is locked only once the webhook and the plugins are done, a slow approval
+        // system does not hold the other confirmations up
+        let sender = timed_lock(
+            &self.user_rpc_update_sender_channel,
+            "user_rpc_update_sender_channel",
+        )
+        .await
+        .clone();
+        sender.send(Arc::from(Mutex::new(tx))).await.map_err(|_| {
+            rpc_error(anyhow!(
+                "failed to send sender confirmation tx state to sender-channel"
+            ))
+        })?;
         Ok(())
     }
 
     /// receiver confirms by signing msg and updating TxStatus to RecvConfirmed
-    async fn receiver_confirm(&self, mut tx: TxStateMachine) -> RpcResult<()> {
-        let sender_channel = self.user_rpc_update_sender_channel.lock().await;
-        if tx.recv_signature.is_none() {
-            // return error as we do not accept any other TxStatus at this api and the receiver should have signed for confirmation
-            Err(Error::Custom("Receiver did not confirm".to_string()))?
-        } else {
-            // remove from cache
-            self.moka_cache.remove(&tx.tx_nonce.into()).await;
-            // verify the tx-state-machine integrity
-            // TODO
-            // tx status to TxStatus::RecvAddrConfirmed
-            tx.recv_confirmed();
-            let sender = sender_channel.clone();
-            sender.send(Arc::from(Mutex::new(tx))).await.map_err(|_| {
-                anyhow!("failed to send recv confirmation tx state to sender channel")
-            })?;
-            Ok(())
+    async fn receiver_confirm(&self, tx: TxStateMachine) -> RpcResult<()> {
+        self.rate_limiter.check("receiverConfirm")?;
+        Ok(self.confirm_receive(tx).await.map_err(rpc_error)?)
+    }
+
+    async fn list_pending_receives(
+        &self,
+        network: Option<String>,
+        min_age_secs: Option<u64>,
+        max_age_secs: Option<u64>,
+    ) -> RpcResult<Vec<PendingReceive>> {
+        self.rate_limiter.check("listPendingReceives")?;
+        let scope = caller_scope();
+        let network = network
+            .map(|network| network.parse::<ChainSupported>())
+            .transpose()
+            .map_err(rpc_error)?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|err| rpc_error(anyhow!("system clock before unix epoch; caused by: {err}")))?
+            .as_secs();
+
+        let txs = self
+            .moka_cache
+            .iter()
+            .map(|(_, tx)| tx)
+            .filter(|tx| tx.inbound_req_id.is_some() && tx.status == TxStatus::Genesis)
+            .filter(|tx| network.map_or(true, |network| tx.network == network))
+            .filter(|tx| scope.allows(tx))
+            .collect::<Vec<_>>();
+
+        // requests are recorded in the event log when they reach the node
+        let db = timed_lock(&self.db_worker, "db_worker").await;
+        let mut pending = Vec::with_capacity(txs.len());
+        for tx in txs {
+            let received_at = db
+                .get_tx_events(tx.tx_id())
+                .await
+                .map_err(rpc_error)?
+                .into_iter()
+                .find(|event| event.tx.inbound_req_id.is_some())
+                .map_or(now, |event| event.recorded_at);
+            pending.push(PendingReceive { received_at, tx });
         }
+        drop(db);
+        pending.retain(|receive| {
+            let age = now.saturating_sub(receive.received_at);
+            min_age_secs.map_or(true, |min| age >= min)
+                && max_age_secs.map_or(true, |max| age <= max)
+        });
+        pending.sort_by_key(|receive| receive.received_at);
+        Ok(pending)
+    }
+
+    async fn confirm_pending_receives(
+        &self,
+        txs: Vec<TxStateMachine>,
+    ) -> RpcResult<Vec<BulkOutcome>> {
+        self.rate_limiter.check("confirmPendingReceives")?;
+        let mut outcomes = Vec::with_capacity(txs.len());
+        for tx in txs {
+            let tx_nonce = tx.tx_nonce;
+            let result = match self.pending_receive(tx_nonce).await {
+                Ok(_) => self.confirm_receive(tx).await,
+                Err(err) => Err(err),
+            };
+            outcomes.push(BulkOutcome {
+                tx_nonce,
+                error: result.err(),
+            });
+        }
+        Ok(outcomes)
+    }
+
+    async fn reject_pending_receives(
+        &self,
+        tx_nonces: Vec<u32>,
+        reason: Option<RejectReason>,
+    ) -> RpcResult<Vec<BulkOutcome>> {
+        self.rate_limiter.check("rejectPendingReceives")?;
+        let mut outcomes = Vec::with_capacity(tx_nonces.len());
+        for tx_nonce in tx_nonces {
+            let result = self.reject_receive(tx_nonce, reason, None).await;
+            if result.is_ok() {
+                info!("declined attestation request of tx {tx_nonce}");
+            }
+            outcomes.push(BulkOutcome {
+                tx_nonce,
+                error: result.err(),
+            });
+        }
+        Ok(outcomes)
+    }
+
+    async fn reject_with_correction(
+        &self,
+        tx_nonce: u32,
+        reason: RejectReason,
+        correction: AddressCorrection,
+    ) -> RpcResult<()> {
+        self.rate_limiter.check("rejectWithCorrection")?;
+        let invalid = |reason: String| rpc_error(VaneRpcError::InvalidParams { reason });
+        if !reason.allows_correction() {
+            Err(invalid(format!(
+                "a {reason:?} rejection can not propose a correction"
+            )))?
+        }
+        let pending = self.pending_receive(tx_nonce).await.map_err(rpc_error)?;
+        if correction.network == pending.network && correction.address == pending.receiver_address {
+            Err(invalid(
+                "the correction proposes the address and network of the tx".into(),
+            ))?
+        }
+        verify_address_correction(&correction, &pending).map_err(|err| invalid(err.to_string()))?;
+        self.reject_receive(tx_nonce, Some(reason), Some(correction))
+            .await
+            .map_err(rpc_error)?;
+        info!("rejected attestation request of tx {tx_nonce} proposing a correction");
+        Ok(())
+    }
+
+    async fn restart_with_correction(&self, tx_id: H256) -> RpcResult<H256> {
+        self.rate_limiter.check("restartWithCorrection")?;
+        let invalid = |reason: String| rpc_error(VaneRpcError::InvalidParams { reason });
+        let tx = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_tx_events(tx_id)
+            .await
+            .map_err(rpc_error)?
+            .pop()
+            .ok_or_else(|| invalid(format!("no tx with id {tx_id:?}")))?
+            .tx;
+        let correction = match (&tx.status, tx.address_correction) {
+            (TxStatus::RecvRejected(_), Some(correction)) => correction,
+            _ => Err(invalid(format!(
+                "the receiver proposed no correction of tx {tx_id:?}"
+            )))?,
+        };
+        let token = tx.token.unwrap_or(Token::native(tx.network));
+        if token != Token::native(correction.network)
+            && token_contract(token, correction.network).is_none()
+        {
+            Err(invalid(format!(
+                "{token:?} is not on {:?}, send again picking one of its tokens",
+                correction.network
+            )))?
+        }
+        info!(
+            "restarting tx {} to the address the receiver proposed",
+            tx.tx_nonce
+        );
+        self.initiate(InitiateTransactionParams {
+            sender: tx.sender_address.to_string(),
+            receiver: correction.address.to_string(),
+            amount: tx.amount,
+            token: token.into(),
+            network: correction.network.into(),
+            fee_preference: tx.fee_preference,
+            submission_route: Some(tx.submission_route),
+            memo: tx.memo,
+            sender_profile: tx.sender_profile,
+            environment: Some(tx.environment),
+            reference: tx.reference,
+            tags: Some(tx.tags),
+            parent_payment: tx.parent_payment,
+            priority: Some(tx.priority),
+            // the beneficiary data names the refused address, attached again if needed, and the
+            // restarted transfer gets a deadline of its own
+            ..Default::default()
+        })
+        .await
+    }
+
+    async fn suggest_routes(&self, tx_nonce: u32) -> RpcResult<Vec<RouteSuggestion>> {
+        self.rate_limiter.check("suggestRoutes")?;
+        let tx = self.pending_send(tx_nonce).await.map_err(rpc_error)?;
+        if !caller_scope().allows(&tx) {
+            Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("no pending transfer {tx_nonce} sent by this node"),
+            }))?
+        }
+        self.alternative_routes(&tx).await.map_err(rpc_error)
+    }
+
+    async fn reroute_transaction(&self, tx_nonce: u32, network: ChainSupported) -> RpcResult<H256> {
+        self.rate_limiter.check("rerouteTransaction")?;
+        let mut tx = self.pending_send(tx_nonce).await.map_err(rpc_error)?;
+        if tx.status.is_final() || tx.status == TxStatus::SenderConfirmed {
+            Err(rpc_error(VaneRpcError::InvalidTxState {
+                status: format!("{:?}", tx.status),
+                reason: "only a transfer the sender did not sign yet is rerouted".to_string(),
+            }))?
+        }
+        let route = self
+            .alternative_routes(&tx)
+            .await
+            .map_err(rpc_error)?
+            .into_iter()
+            .find(|route| route.network == network)
+            .ok_or(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("no route of tx {tx_nonce} on {network:?} the sender can pay"),
+            }))?;
+        info!("rerouting tx {tx_nonce} to {network:?}");
+        let tx_id = self
+            .initiate(InitiateTransactionParams {
+                sender: route.sender,
+                receiver: route.receiver,
+                amount: route.amount,
+                token: route.token.into(),
+                network: network.into(),
+                fee_preference: tx.fee_preference,
+                submission_route: Some(tx.submission_route),
+                memo: tx.memo.clone(),
+                sender_profile: tx.sender_profile.clone(),
+                reference: tx.reference.clone(),
+                tags: Some(tx.tags.clone()),
+                parent_payment: tx.parent_payment,
+                priority: Some(tx.priority),
+                ..Default::default()
+            })
+            .await?;
+
+        // the original transfer is not sent once its route is taken
+        tx.status = TxStatus::Withdrawn(format!("rerouted to {network:?} as tx {tx_id:?}"));
+        self.tx_updates.publish(tx.clone());
+        // recorded in the event log by the main service worker
+        self.propagate_transaction(tx).await.map_err(rpc_error)?;
+        Ok(tx_id)
+    }
+
+    /// approver signs off by signing the second approval message, the node copy of the tx is
+    /// submitted so the approval covers what the sender signed
+    async fn approver_confirm(&self, tx: TxStateMachine, signature: Vec<u8>) -> RpcResult<()> {
+        self.rate_limiter.check("approverConfirm")?;
+        self.ensure_leader().map_err(rpc_error)?;
+        let approver =
+            self.second_approver
+                .as_ref()
+                .ok_or(rpc_error(VaneRpcError::InvalidTxState {
+                    status: format!("{:?}", tx.status),
+                    reason: "no second approver is designated on this node".to_string(),
+                }))?;
+        let tx = AwaitingApprovalTx::try_from(tx).map_err(rpc_error)?;
+        let pending = self
+            .moka_cache
+            .get(&u64::from(tx.tx_nonce))
+            .await
+            .ok_or(rpc_error(VaneRpcError::AttestationExpired {
+                tx_nonce: tx.tx_nonce,
+            }))?;
+        verify_second_approval(approver, &pending, &signature).map_err(|err| {
+            rpc_error(VaneRpcError::InvalidParams {
+                reason: err.to_string(),
+            })
+        })?;
+        self.take_pending_tx(&tx, true).await.map_err(rpc_error)?;
+
+        let approved_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|err| rpc_error(anyhow!("system clock before unix epoch; caused by: {err}")))?
+            .as_secs();
+        let approval = SecondApproval {
+            approver: approver.account.clone(),
+            signature,
+            approved_at,
+        };
+        let tx = AwaitingApprovalTx::try_from(pending)
+            .map_err(rpc_error)?
+            .approved(approval);
+        info!("tx {} signed off by the second approver", tx.tx_nonce);
+        timed_lock(
+            &self.user_rpc_update_sender_channel,
+            "user_rpc_update_sender_channel",
+        )
+        .await
+        .send(Arc::from(Mutex::new(tx.into_inner())))
+        .await
+        .map_err(|_| {
+            rpc_error(anyhow!(
+                "failed to send approver confirmation tx state to sender channel"
+            ))
+        })?;
+        Ok(())
     }
 
     async fn watch_tx_updates(
         &self,
         subscription_sink: PendingSubscriptionSink,
     ) -> SubscriptionResult {
+        // websocket sessions are not account scoped, the access control only opens them to
+        // unscoped keys
+        let mut updates = self.tx_updates.subscribe(AccountScope::all());
         let sink = subscription_sink
             .accept()
             .await
             .map_err(|_| anyhow!("failed to accept rpc ws channel"))?;
-        while let Some(tx_update) = self.rpc_receiver_channel.lock().await.recv().await {
+        while let Some(tx_update) = updates.next().await {
             trace!(target:"rpc","\n watching tx: {tx_update:?} \n");
 
             let subscription_msg = SubscriptionMessage::from_json(&tx_update)
@@ -565,14 +2847,1207 @@ impl TransactionRpcServer for TransactionRpcWorker {
     }
 
     async fn fetch_pending_tx_updates(&self) -> RpcResult<Vec<TxStateMachine>> {
+        self.rate_limiter.check("fetchPendingTxUpdates")?;
+        let scope = caller_scope();
         let tx_updates = self
             .moka_cache
             .iter()
             .map(|(_k, v)| v)
+            .filter(|tx| scope.allows(tx))
             .collect::<Vec<TxStateMachine>>();
         println!("moka: {tx_updates:?}");
         Ok(tx_updates)
     }
+
+    async fn list_peers(&self) -> RpcResult<Vec<Discovery>> {
+        self.rate_limiter.check("listPeers")?;
+        let peers = self.discovery.list_all_peers().await.map_err(rpc_error)?;
+        Ok(peers)
+    }
+
+    async fn add_attestation(&self, attestation: IdentityAttestation) -> RpcResult<()> {
+        self.rate_limiter.check("addAttestation")?;
+        verify_attestation(&attestation).map_err(|err| {
+            rpc_error(VaneRpcError::InvalidParams {
+                reason: err.to_string(),
+            })
+        })?;
+
+        let record = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_user_peer_id(None, Some(self.peer_id().to_string()))
+            .await
+            .map_err(rpc_error)?;
+        let own_account = [
+            &record.account_id1,
+            &record.account_id2,
+            &record.account_id3,
+            &record.account_id4,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|account| account.eq_ignore_ascii_case(&attestation.account));
+        if !own_account {
+            Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("{} is not registered by this node", attestation.account),
+            }))?
+        }
+
+        let mut attestations = self
+            .discovery
+            .list_all_peers()
+            .await
+            .map_err(rpc_error)?
+            .into_iter()
+            .find(|peer| peer.id == record.record_id)
+            .map(|peer| peer.attestations)
+            .unwrap_or_default();
+        attestations.retain(|stored| {
+            !(stored.issuer.eq_ignore_ascii_case(&attestation.issuer)
+                && stored.account.eq_ignore_ascii_case(&attestation.account))
+        });
+        attestations.push(attestation);
+        self.discovery
+            .update_attestations(record.record_id, attestations)
+            .await
+            .map_err(rpc_error)?;
+        self.trusted_issuers.forget_registry().await;
+        info!("stored attestation with the registry record");
+        Ok(())
+    }
+
+    async fn list_badges(&self, account: String) -> RpcResult<Vec<VerificationBadge>> {
+        self.rate_limiter.check("listBadges")?;
+        Ok(self
+            .trusted_issuers
+            .lookup(&*self.discovery, &account)
+            .await
+            .map_err(rpc_error)?)
+    }
+
+    async fn set_accepted_assets(&self, account: String, tokens: Vec<Token>) -> RpcResult<()> {
+        self.rate_limiter.check("setAcceptedAssets")?;
+        let networks = candidate_chains(&account);
+        if let Some(token) = tokens
+            .iter()
+            .find(|token| !networks.contains(&ChainSupported::from(**token)))
+        {
+            Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("{account} can not receive {token:?}"),
+            }))?
+        }
+
+        let record = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_user_peer_id(None, Some(self.peer_id().to_string()))
+            .await
+            .map_err(rpc_error)?;
+        let own_account = [
+            &record.account_id1,
+            &record.account_id2,
+            &record.account_id3,
+            &record.account_id4,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|registered| registered.eq_ignore_ascii_case(&account));
+        if !own_account {
+            Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("{account} is not registered by this node"),
+            }))?
+        }
+
+        let mut accepted_assets = self
+            .discovery
+            .list_all_peers()
+            .await
+            .map_err(rpc_error)?
+            .into_iter()
+            .find(|peer| peer.id == record.record_id)
+            .map(|peer| peer.accepted_assets)
+            .unwrap_or_default();
+        accepted_assets.retain(|stored| !stored.account.eq_ignore_ascii_case(&account));
+        if !tokens.is_empty() {
+            accepted_assets.push(AcceptedAssets { account, tokens });
+        }
+        self.discovery
+            .update_accepted_assets(record.record_id, accepted_assets)
+            .await
+            .map_err(rpc_error)?;
+        info!("advertised the accepted assets with the registry record");
+        Ok(())
+    }
+
+    async fn accepted_assets(&self, address: String) -> RpcResult<Vec<Token>> {
+        self.rate_limiter.check("acceptedAssets")?;
+        let address = VaneAddress::parse(&address).map_err(rpc_error)?;
+        Ok(self
+            .discovery
+            .list_all_peers()
+            .await
+            .map_err(rpc_error)?
+            .into_iter()
+            .flat_map(|peer| peer.accepted_assets)
+            .find(|preference| preference.covers(&address))
+            .map(|preference| preference.tokens)
+            .unwrap_or_default())
+    }
+
+    async fn anchor_call(&self, account: String) -> RpcResult<AnchorCall> {
+        self.rate_limiter.check("anchorCall")?;
+        let address = VaneAddress::parse(&account).map_err(rpc_error)?;
+        let record = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_user_peer_id(None, Some(self.peer_id().to_string()))
+            .await
+            .map_err(rpc_error)?;
+        let own = [
+            record.account_id1,
+            record.account_id2,
+            record.account_id3,
+            record.account_id4,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|id| VaneAddress::parse(&id).is_ok_and(|id| id == address));
+        if !own {
+            Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("{account} is not an account of this node"),
+            }))?
+        }
+        self.binding_anchors
+            .anchor_call(&address, &self.peer_id().to_string())
+            .map_err(rpc_error)
+    }
+
+    async fn verify_binding(
+        &self,
+        account: String,
+        peer_id: Option<String>,
+    ) -> RpcResult<BindingAnchor> {
+        self.rate_limiter.check("verifyBinding")?;
+        let address = VaneAddress::parse(&account).map_err(rpc_error)?;
+        let peer_id = match peer_id {
+            Some(peer_id) => peer_id,
+            None => {
+                let peers = self.discovery.list_all_peers().await.map_err(rpc_error)?;
+                served_by(&peers, &address).ok_or(rpc_error(
+                    VaneRpcError::ReceiverNotRegistered { address: account },
+                ))?
+            }
+        };
+        self.binding_anchors
+            .verify(&address, peer_id)
+            .await
+            .map_err(rpc_error)
+    }
+
+    async fn chain_health(&self) -> RpcResult<Vec<ChainHealth>> {
+        self.rate_limiter.check("chainHealth")?;
+        let probes = [
+            ChainSupported::Polkadot,
+            ChainSupported::Ethereum,
+            ChainSupported::Bnb,
+            ChainSupported::Solana,
+        ]
+        .map(|network| async move {
+            ChainHealth {
+                breaker: self.circuit_breakers.state(network),
+                ..Self::probe_chain(
+                    network,
+                    self.environments.of(network),
+                    self.environments.url(network),
+                )
+                .await
+            }
+        });
+        Ok(libp2p::futures::future::join_all(probes).await)
+    }
+
+    async fn peer_stats(&self) -> RpcResult<Vec<PeerStats>> {
+        self.rate_limiter.check("peerStats")?;
+        Ok(self.peer_metrics.stats())
+    }
+
+    async fn detect_chains(&self, address: String) -> RpcResult<Vec<ChainSupported>> {
+        self.rate_limiter.check("detectChains")?;
+        let candidates = candidate_chains(address.trim())
+            .into_iter()
+            .filter(|network| self.supported_chains.contains(network))
+            .collect();
+        Ok(candidates)
+    }
+
+    async fn parse_amount(
+        &self,
+        amount: String,
+        token: Token,
+        locale: Option<AmountLocale>,
+    ) -> RpcResult<LocalizedAmount> {
+        self.rate_limiter.check("parseAmount")?;
+        let separators = locale_separators(locale).map_err(rpc_error)?;
+        let units = parse_amount(&amount, token.decimals(), separators).map_err(rpc_error)?;
+        Ok(LocalizedAmount::new(token, units, separators))
+    }
+
+    async fn format_amount(
+        &self,
+        units: u128,
+        token: Token,
+        locale: Option<AmountLocale>,
+    ) -> RpcResult<LocalizedAmount> {
+        self.rate_limiter.check("formatAmount")?;
+        let separators = locale_separators(locale).map_err(rpc_error)?;
+        Ok(LocalizedAmount::new(token, units, separators))
+    }
+
+    async fn get_counterparty(&self, address: String) -> RpcResult<CounterpartyProfile> {
+        self.rate_limiter.check("getCounterparty")?;
+        let address = VaneAddress::parse(&address).map_err(rpc_error)?;
+        let events = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_account_tx_events(address.to_string())
+            .await
+            .map_err(rpc_error)?;
+        let scope = caller_scope();
+        let events = events.into_iter().filter(|event| scope.allows(&event.tx));
+        Ok(CounterpartyProfile::fold(&address, events))
+    }
+
+    async fn fee_tiers(&self, network: String) -> RpcResult<FeeTiers> {
+        self.rate_limiter.check("feeTiers")?;
+        let network: ChainSupported = network.parse().map_err(rpc_error)?;
+        let client = self.chain_clients.get(&network).ok_or(rpc_error(
+            VaneRpcError::UnsupportedNetwork {
+                network: network.into(),
+            },
+        ))?;
+        let tiers = client.fee_tiers().await.map_err(rpc_error)?;
+        Ok(tiers)
+    }
+
+    async fn compute_budget_tiers(&self) -> RpcResult<ComputeBudgetTiers> {
+        self.rate_limiter.check("computeBudgetTiers")?;
+        let tiers = self.priority_fees.compute_budget_tiers().await;
+        tiers.map_err(rpc_error)
+    }
+
+    async fn faucet(&self, account: String, amount: u128, network: String) -> RpcResult<u128> {
+        self.rate_limiter.check("faucet")?;
+        let network: ChainSupported = network.parse().map_err(rpc_error)?;
+        let client = self.chain_clients.get(&network).ok_or(rpc_error(
+            VaneRpcError::UnsupportedNetwork {
+                network: network.into(),
+            },
+        ))?;
+        let balance = client.faucet(&account, amount).await.map_err(|err| {
+            rpc_error(VaneRpcError::InvalidParams {
+                reason: err.to_string(),
+            })
+        })?;
+        info!("faucet credited {account} with {amount} on {network:?}, balance {balance}");
+        Ok(balance)
+    }
+
+    async fn provision_test_accounts(
+        &self,
+        count: u8,
+        network: String,
+        amount: u128,
+    ) -> RpcResult<Vec<TestAccount>> {
+        self.rate_limiter.check("provisionTestAccounts")?;
+        let invalid = |reason: String| rpc_error(VaneRpcError::InvalidParams { reason });
+        let network: ChainSupported = network.parse().map_err(rpc_error)?;
+        let client = self.chain_clients.get(&network).ok_or(rpc_error(
+            VaneRpcError::UnsupportedNetwork {
+                network: network.into(),
+            },
+        ))?;
+        let environment = self.environments.of(network);
+        // real funds never sit behind keys handed out over rpc, whatever chain client is plugged in
+        if environment == NetworkEnvironment::Mainnet {
+            Err(invalid(format!(
+                "test accounts need dev mode or a testnet, {network:?} runs on mainnet"
+            )))?
+        }
+        if amount > 0 && !client.has_faucet() {
+            Err(invalid(format!(
+                "no faucet for {}, fund the accounts from its public faucet",
+                environment.name(network)
+            )))?
+        }
+        if !matches!(network, ChainSupported::Ethereum | ChainSupported::Bnb) {
+            Err(invalid(format!(
+                "test accounts are evm only, found {network:?}"
+            )))?
+        }
+
+        let record = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_user_peer_id(None, Some(self.peer_id().to_string()))
+            .await
+            .map_err(rpc_error)?;
+        let mut registered = PeerRecord {
+            record_id: record.record_id.clone(),
+            peer_id: None,
+            account_id1: None,
+            account_id2: None,
+            account_id3: None,
+            account_id4: None,
+            multi_addr: None,
+            keypair: None,
+        };
+        let free_slots = [
+            (&record.account_id1, &mut registered.account_id1),
+            (&record.account_id2, &mut registered.account_id2),
+            (&record.account_id3, &mut registered.account_id3),
+            (&record.account_id4, &mut registered.account_id4),
+        ]
+        .into_iter()
+        .filter(|(stored, _)| stored.is_none())
+        .map(|(_, slot)| slot)
+        .collect::<Vec<_>>();
+        if count == 0 || free_slots.len() < usize::from(count) {
+            Err(invalid(format!(
+                "the node peer record has {} free account slots, {count} requested",
+                free_slots.len()
+            )))?
+        }
+
+        let mut provisioned = Vec::with_capacity(usize::from(count));
+        for slot in free_slots.into_iter().take(usize::from(count)) {
+            let signer = PrivateKeySigner::random();
+            let account = signer.address().to_string();
+            let balance = match amount {
+                0 => 0,
+                amount => client.faucet(&account, amount).await.map_err(rpc_error)?,
+            };
+            *slot = Some(account.clone());
+            provisioned.push(TestAccount {
+                account,
+                private_key: signer.to_bytes().to_string(),
+                network,
+                balance,
+            });
+        }
+
+        // the accounts stay local, the shared discovery registry only holds accounts of real users
+        timed_lock(&self.db_worker, "db_worker")
+            .await
+            .update_user_peer_id_accounts(registered)
+            .await
+            .map_err(rpc_error)?;
+        info!(
+            "provisioned {} test accounts on {}",
+            provisioned.len(),
+            environment.name(network)
+        );
+        Ok(provisioned)
+    }
+
+    async fn approve_token(
+        &self,
+        owner: String,
+        spender: String,
+        amount: u128,
+        token: String,
+        unlimited: Option<bool>,
+    ) -> RpcResult<H256> {
+        self.rate_limiter.check("approveToken")?;
+        let amount = match unlimited {
+            Some(true) => UNLIMITED_APPROVAL,
+            _ if amount == UNLIMITED_APPROVAL => Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: "unlimited approvals must be requested explicitly".to_string(),
+            }))?,
+            _ if amount.is_zero() => Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: "zero approvals revoke, use revokeApproval".to_string(),
+            }))?,
+            _ => amount,
+        };
+        if amount == UNLIMITED_APPROVAL {
+            warn!("unlimited {token} approval requested for {spender}");
+        }
+        let tx_id = self
+            .stage_approval(owner, spender, amount, token.parse().map_err(rpc_error)?)
+            .await
+            .map_err(rpc_error)?;
+        Ok(tx_id)
+    }
+
+    async fn revoke_approval(
+        &self,
+        owner: String,
+        spender: String,
+        token: String,
+    ) -> RpcResult<H256> {
+        self.rate_limiter.check("revokeApproval")?;
+        let tx_id = self
+            .stage_approval(owner, spender, 0, token.parse().map_err(rpc_error)?)
+            .await
+            .map_err(rpc_error)?;
+        Ok(tx_id)
+    }
+
+    async fn list_approvals(&self, owner: Option<String>) -> RpcResult<Vec<Approval>> {
+        self.rate_limiter.check("listApprovals")?;
+        let mut events = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_approval_tx_events(owner.clone())
+            .await
+            .map_err(rpc_error)?;
+        let scope = caller_scope();
+        events.retain(|event| scope.allows(&event.tx));
+        events.sort_by_key(|event| event.recorded_at);
+        let approvals = Approval::outstanding(events)
+            .into_iter()
+            .filter(|approval| {
+                owner
+                    .as_ref()
+                    .map_or(true, |owner| &approval.owner == owner)
+            })
+            .collect();
+        Ok(approvals)
+    }
+
+    async fn build_permit(
+        &self,
+        owner: String,
+        spender: String,
+        amount: u128,
+        token: String,
+        validity_secs: Option<u64>,
+    ) -> RpcResult<PermitPayload> {
+        self.rate_limiter.check("buildPermit")?;
+        if amount.is_zero() || amount == UNLIMITED_APPROVAL {
+            Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: "permits must be for a bounded non zero amount".to_string(),
+            }))?
+        }
+        let validity = validity_secs.unwrap_or(Self::DEFAULT_PERMIT_VALIDITY.as_secs());
+        if validity.is_zero() || validity > Self::MAX_PERMIT_VALIDITY.as_secs() {
+            Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!(
+                    "permit validity must be within 1..={} seconds",
+                    Self::MAX_PERMIT_VALIDITY.as_secs()
+                ),
+            }))?
+        }
+        let token: Token = token.parse().map_err(rpc_error)?;
+        let network = self.token_network(token).map_err(rpc_error)?;
+        let client = self.chain_clients.get(&network).ok_or(rpc_error(
+            VaneRpcError::UnsupportedNetwork {
+                network: network.into(),
+            },
+        ))?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|err| rpc_error(anyhow!("system clock before unix epoch; caused by: {err}")))?
+            .as_secs();
+        let permit = client
+            .permit_payload(&owner, &spender, token, amount, now + validity)
+            .await
+            .map_err(rpc_error)?;
+        Ok(permit)
+    }
+
+    async fn set_policies(&self, rules: Vec<PolicyRule>) -> RpcResult<()> {
+        self.rate_limiter.check("setPolicies")?;
+        let document = serde_json::to_string(&rules)
+            .map_err(|err| rpc_error(anyhow!("failed to encode policies; caused by: {err}")))?;
+        // same validation as when the document is loaded for evaluation
+        parse_policies(&document).map_err(rpc_error)?;
+        timed_lock(&self.db_worker, "db_worker")
+            .await
+            .set_policies(document)
+            .await
+            .map_err(rpc_error)?;
+        info!("operator policies updated, {} rules in force", rules.len());
+        Ok(())
+    }
+
+    async fn list_policies(&self) -> RpcResult<Vec<PolicyRule>> {
+        self.rate_limiter.check("listPolicies")?;
+        let document = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_policies()
+            .await
+            .map_err(rpc_error)?;
+        match document {
+            Some(document) => Ok(parse_policies(&document).map_err(rpc_error)?),
+            None => Ok(vec![]),
+        }
+    }
+
+    async fn pairing_message(&self, account: String) -> RpcResult<String> {
+        self.rate_limiter.check("pairingMessage")?;
+        let account = account.parse().map_err(|err| {
+            rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("invalid signer account {account}; caused by: {err}"),
+            })
+        })?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|err| rpc_error(anyhow!("system clock before unix epoch; caused by: {err}")))?
+            .as_secs();
+        let challenge = PairingChallenge::new(now);
+        self.pairing_challenges
+            .insert(account.to_string().to_lowercase(), challenge.clone())
+            .await;
+        Ok(pairing_message(&account, &self.peer_id(), &challenge))
+    }
+
+    async fn pair_signer(&self, account: String, signature: Vec<u8>) -> RpcResult<()> {
+        self.rate_limiter.check("pairSigner")?;
+        // taken on any attempt, so a signature pairs at most once
+        let challenge = self
+            .pairing_challenges
+            .remove(&account.to_lowercase())
+            .await
+            .ok_or(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!(
+                    "no pending pairing challenge for {account}, request a pairing message first"
+                ),
+            }))?;
+        let account =
+            verify_pairing(&account, &self.peer_id(), &challenge, &signature).map_err(|err| {
+                rpc_error(VaneRpcError::InvalidParams {
+                    reason: err.to_string(),
+                })
+            })?;
+        timed_lock(&self.db_worker, "db_worker")
+            .await
+            .pair_signer(account.clone())
+            .await
+            .map_err(rpc_error)?;
+        info!("signing device paired for {account}");
+        Ok(())
+    }
+
+    async fn unpair_signer(&self, account: String) -> RpcResult<()> {
+        self.rate_limiter.check("unpairSigner")?;
+        let db = timed_lock(&self.db_worker, "db_worker").await;
+        // stored checksummed, the caller may pass any casing
+        let paired = db.get_paired_signers().await.map_err(rpc_error)?;
+        for paired in paired
+            .into_iter()
+            .filter(|paired| paired.eq_ignore_ascii_case(&account))
+        {
+            db.unpair_signer(paired).await.map_err(rpc_error)?;
+        }
+        info!("signing device unpaired for {account}");
+        Ok(())
+    }
+
+    async fn list_paired_signers(&self) -> RpcResult<Vec<String>> {
+        self.rate_limiter.check("listPairedSigners")?;
+        Ok(timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_paired_signers()
+            .await
+            .map_err(rpc_error)?)
+    }
+
+    async fn unlock(&self, passphrase: String) -> RpcResult<u64> {
+        self.rate_limiter.check("unlock")?;
+        let send_lock = self
+            .send_lock
+            .as_ref()
+            .ok_or(rpc_error(VaneRpcError::InvalidParams {
+                reason: "the node has no send passphrase".to_string(),
+            }))?;
+        Ok(send_lock.unlock(&passphrase).map_err(rpc_error)?)
+    }
+
+    async fn lock(&self) -> RpcResult<()> {
+        self.rate_limiter.check("lock")?;
+        if let Some(send_lock) = &self.send_lock {
+            send_lock.lock();
+        }
+        Ok(())
+    }
+
+    async fn peer_binding_message(&self, account: String, signed_at: u64) -> RpcResult<String> {
+        self.rate_limiter.check("peerBindingMessage")?;
+        let account = checksummed_binding_account(&account)?;
+        Ok(PeerBinding::message(
+            &account,
+            &self.peer_id().to_base58(),
+            signed_at,
+        ))
+    }
+
+    async fn add_peer_binding(
+        &self,
+        account: String,
+        signed_at: u64,
+        signature: Vec<u8>,
+    ) -> RpcResult<()> {
+        self.rate_limiter.check("addPeerBinding")?;
+        // peers check a binding account is the checksummed one its signature recovers to
+        let account = checksummed_binding_account(&account)?;
+        let db = timed_lock(&self.db_worker, "db_worker").await;
+        let record = db
+            .get_user_peer_id(None, Some(self.peer_id().to_string()))
+            .await
+            .map_err(rpc_error)?;
+        let own_account = [
+            &record.account_id1,
+            &record.account_id2,
+            &record.account_id3,
+            &record.account_id4,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|registered| registered.eq_ignore_ascii_case(&account));
+        if !own_account {
+            Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("{account} is not registered by this node"),
+            }))?
+        }
+
+        let binding = PeerBinding {
+            account,
+            peer_id: self.peer_id().to_base58(),
+            multi_addr: record
+                .multi_addr
+                .ok_or(anyhow!("node peer record has no multi addr"))
+                .map_err(rpc_error)?,
+            signed_at,
+            signature,
+        };
+        verify_peer_binding(&binding).map_err(|err| {
+            rpc_error(VaneRpcError::InvalidParams {
+                reason: err.to_string(),
+            })
+        })?;
+        info!("{} bound to this node", binding.account);
+        db.record_peer_binding(binding).await.map_err(rpc_error)?;
+        Ok(())
+    }
+
+    async fn list_peer_bindings(&self) -> RpcResult<Vec<PeerBinding>> {
+        self.rate_limiter.check("listPeerBindings")?;
+        Ok(timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_peer_bindings()
+            .await
+            .map_err(rpc_error)?)
+    }
+
+    async fn device_pairing_code(&self) -> RpcResult<DevicePairingCode> {
+        self.rate_limiter.check("devicePairingCode")?;
+        Ok(self.device_sync.pairing_code())
+    }
+
+    async fn pair_device(&self, uri: String) -> RpcResult<PairedDevice> {
+        self.rate_limiter.check("pairDevice")?;
+        let invalid_uri = |reason: String| {
+            rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("invalid pairing uri; {reason}"),
+            })
+        };
+        let (code, multi_addr) = DevicePairingCode::parse_uri(&uri).ok_or_else(|| {
+            invalid_uri(format!("expected {PAIRING_URI_SCHEME}<code>@<multi addr>"))
+        })?;
+        let target_multi_addr = multi_addr
+            .parse::<Multiaddr>()
+            .map_err(|err| invalid_uri(format!("caused by: {err}")))?;
+        let Some(Protocol::P2p(target_peer_id)) = target_multi_addr.iter().last() else {
+            Err(invalid_uri(
+                "the multi addr should end with /p2p/<peer id>".to_string(),
+            ))?
+        };
+        if target_peer_id == self.peer_id() {
+            Err(invalid_uri("the code is shown by this node".to_string()))?
+        }
+
+        let (reply, paired) = libp2p::futures::channel::oneshot::channel();
+        self.p2p_command_tx
+            .send(NetworkCommand::PairDevice {
+                target_multi_addr,
+                target_peer_id,
+                code,
+                reply,
+            })
+            .await
+            .map_err(|err| rpc_error(anyhow!("failed to send pair command; {err}")))?;
+        let device = tokio::time::timeout(Self::PAIRING_TIMEOUT, paired)
+            .await
+            .map_err(|_| {
+                rpc_error(anyhow!(
+                    "{target_peer_id} did not answer within {:?}",
+                    Self::PAIRING_TIMEOUT
+                ))
+            })?
+            .map_err(|_| rpc_error(anyhow!("swarm loop dropped the pairing")))?
+            .map_err(|reason| rpc_error(VaneRpcError::InvalidParams { reason }))?;
+        info!("paired with the device {target_peer_id}");
+        Ok(device)
+    }
+
+    async fn unpair_device(&self, peer_id: String) -> RpcResult<()> {
+        self.rate_limiter.check("unpairDevice")?;
+        timed_lock(&self.db_worker, "db_worker")
+            .await
+            .unpair_device(peer_id.clone())
+            .await
+            .map_err(rpc_error)?;
+        info!("device {peer_id} unpaired");
+        Ok(())
+    }
+
+    async fn list_paired_devices(&self) -> RpcResult<Vec<PairedDevice>> {
+        self.rate_limiter.check("listPairedDevices")?;
+        Ok(timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_paired_devices()
+            .await
+            .map_err(rpc_error)?)
+    }
+
+    async fn list_device_transfers(&self) -> RpcResult<Vec<TxStateMachine>> {
+        self.rate_limiter.check("listDeviceTransfers")?;
+        let mut transfers = self
+            .device_sync
+            .device_transfers()
+            .await
+            .map_err(rpc_error)?;
+        let scope = caller_scope();
+        transfers.retain(|tx| scope.allows(tx));
+        Ok(transfers)
+    }
+
+    async fn rotate_node_identity(&self, grace_secs: Option<u64>) -> RpcResult<RotatedIdentity> {
+        self.rate_limiter.check("rotateNodeIdentity")?;
+        self.ensure_leader().map_err(rpc_error)?;
+        let grace = grace_secs
+            .map(core::time::Duration::from_secs)
+            .unwrap_or(DEFAULT_ROTATION_GRACE);
+        if grace > MAX_ROTATION_GRACE {
+            Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("graceSecs exceeds {}", MAX_ROTATION_GRACE.as_secs()),
+            }))?
+        }
+        let old_peer_id = self.peer_id();
+        let keypair = libp2p::identity::Keypair::generate_ed25519();
+        let encoded_keypair = keypair
+            .to_protobuf_encoding()
+            .map_err(|err| rpc_error(anyhow!("failed to encode keypair; caused by: {err}")))?;
+
+        let (reply, rotated) = libp2p::futures::channel::oneshot::channel();
+        self.p2p_command_tx
+            .send(NetworkCommand::RotateIdentity {
+                keypair,
+                grace,
+                reply,
+            })
+            .await
+            .map_err(|err| rpc_error(anyhow!("failed to send rotate command; {err}")))?;
+        let rotation = tokio::time::timeout(Self::ROTATION_TIMEOUT, rotated)
+            .await
+            .map_err(|_| {
+                rpc_error(anyhow!(
+                    "swarm loop did not rotate within {:?}",
+                    Self::ROTATION_TIMEOUT
+                ))
+            })?
+            .map_err(|_| rpc_error(anyhow!("swarm loop dropped the rotation")))?
+            .map_err(|reason| rpc_error(anyhow!("failed to rotate the node identity; {reason}")))?;
+
+        // the swarm already moved, the records follow
+        let db = timed_lock(&self.db_worker, "db_worker").await;
+        let record = db
+            .get_user_peer_id(None, Some(old_peer_id.to_string()))
+            .await
+            .map_err(rpc_error)?;
+        self.discovery
+            .update_identity(
+                record.record_id,
+                rotation.new_peer_id.clone(),
+                rotation.new_multi_addr.clone(),
+            )
+            .await
+            .map_err(rpc_error)?;
+        db.rotate_user_peer_id(
+            rotation.old_peer_id.clone(),
+            rotation.new_peer_id.clone(),
+            rotation.new_multi_addr.clone(),
+            encoded_keypair,
+        )
+        .await
+        .map_err(rpc_error)?;
+        let rebind_accounts = db
+            .get_peer_bindings()
+            .await
+            .map_err(rpc_error)?
+            .into_iter()
+            .filter(|binding| binding.peer_id == rotation.old_peer_id)
+            .map(|binding| binding.account)
+            .collect();
+
+        info!(
+            "node identity rotated from {} to {}",
+            rotation.old_peer_id, rotation.new_peer_id
+        );
+        Ok(RotatedIdentity {
+            rotation,
+            rebind_accounts,
+        })
+    }
+
+    async fn revocation_message(&self, account: String, revoked_at: u64) -> RpcResult<String> {
+        self.rate_limiter.check("revocationMessage")?;
+        let account = checksummed_binding_account(&account)?;
+        Ok(AccountRevocation::message(&account, revoked_at))
+    }
+
+    async fn revoke_account(
+        &self,
+        account: String,
+        revoked_at: u64,
+        signature: Vec<u8>,
+    ) -> RpcResult<()> {
+        self.rate_limiter.check("revokeAccount")?;
+        // peers check a revoked account is the checksummed one its signature recovers to
+        let revocation = AccountRevocation {
+            account: checksummed_binding_account(&account)?,
+            revoked_at,
+            signature,
+        };
+        verify_revocation(&revocation).map_err(|err| {
+            rpc_error(VaneRpcError::InvalidParams {
+                reason: err.to_string(),
+            })
+        })?;
+
+        let db = timed_lock(&self.db_worker, "db_worker").await;
+        db.record_revocation(revocation.clone())
+            .await
+            .map_err(rpc_error)?;
+        let record = db
+            .get_user_peer_id(None, Some(self.peer_id().to_string()))
+            .await
+            .map_err(rpc_error)?;
+        drop(db);
+        let own_account = [
+            &record.account_id1,
+            &record.account_id2,
+            &record.account_id3,
+            &record.account_id4,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|registered| revocation.revokes(registered));
+        // senders looking the account up in the registry find the revocation with its record
+        if own_account {
+            let mut revocations = self
+                .discovery
+                .list_all_peers()
+                .await
+                .map_err(rpc_error)?
+                .into_iter()
+                .find(|peer| peer.id == record.record_id)
+                .map(|peer| peer.revocations)
+                .unwrap_or_default();
+            if !revocations
+                .iter()
+                .any(|stored| stored.revokes(&revocation.account))
+            {
+                revocations.push(revocation.clone());
+                self.discovery
+                    .update_revocations(record.record_id, revocations)
+                    .await
+                    .map_err(rpc_error)?;
+            }
+        }
+        self.p2p_command_tx
+            .send(NetworkCommand::ExchangePeers)
+            .await
+            .map_err(|err| rpc_error(anyhow!("failed to send exchange command; {err}")))?;
+        warn!("{} revoked as compromised", revocation.account);
+        Ok(())
+    }
+
+    async fn list_revocations(&self) -> RpcResult<Vec<AccountRevocation>> {
+        self.rate_limiter.check("listRevocations")?;
+        Ok(timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_revocations()
+            .await
+            .map_err(rpc_error)?)
+    }
+
+    async fn tx_history(&self, query: Option<String>) -> RpcResult<Vec<DbTxStateMachine>> {
+        self.rate_limiter.check("txHistory")?;
+        let db = timed_lock(&self.db_worker, "db_worker").await;
+        let mut history = db.get_success_txs().await.map_err(rpc_error)?;
+        history.extend(db.get_failed_txs().await.map_err(rpc_error)?);
+        let scope = caller_scope();
+        history.retain(|tx| scope.allows_record(tx));
+        if let Some(query) = query {
+            history.retain(|tx| tx.matches(&query));
+        }
+        Ok(history)
+    }
+
+    async fn tx_receipt(&self, tx_hash: H256) -> RpcResult<DbTxStateMachine> {
+        self.rate_limiter.check("txReceipt")?;
+        let mut record = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_success_tx(tx_hash.as_bytes().to_vec())
+            .await
+            .map_err(rpc_error)?
+            .filter(|tx| caller_scope().allows_record(tx))
+            .ok_or(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("no submitted tx with hash {tx_hash:?}"),
+            }))?;
+        // the background poll gave up or did not get to the tx yet
+        let chain_client = self.chain_clients.get(&record.network);
+        if let (None, Some(chain_client)) = (record.receipt, chain_client) {
+            if let Some(receipt) = chain_client.receipt(tx_hash.0).await.map_err(rpc_error)? {
+                let low_tier_fee =
+                    chain_client
+                        .low_tier_fee(&receipt)
+                        .await
+                        .unwrap_or_else(|err| {
+                            warn!("failed to price the low fee tier; caused by: {err}");
+                            None
+                        });
+                timed_lock(&self.db_worker, "db_worker")
+                    .await
+                    .record_tx_receipt(tx_hash.0.to_vec(), receipt, low_tier_fee)
+                    .await
+                    .map_err(rpc_error)?;
+                record.receipt = Some(receipt);
+                record.low_tier_fee = low_tier_fee;
+            }
+        }
+        let events = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_account_tx_events(record.sender_address.clone())
+            .await
+            .map_err(rpc_error)?;
+        let submitted = events
+            .iter()
+            .find(|event| event.status == TxStatus::TxSubmissionPassed(tx_hash.0))
+            .map(|event| event.tx_id);
+        if let Some(tx_id) = submitted {
+            let events: Vec<TxEvent> = events
+                .into_iter()
+                .filter(|event| event.tx_id == tx_id)
+                .collect();
+            record.travel_rule = self.open_travel_rule(&events).map_err(rpc_error)?;
+        }
+        Ok(record)
+    }
+
+    async fn get_travel_rule(&self, tx_id: H256) -> RpcResult<Option<TravelRuleData>> {
+        self.rate_limiter.check("getTravelRule")?;
+        let events = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_tx_events(tx_id)
+            .await
+            .map_err(rpc_error)?;
+        if !events
+            .last()
+            .is_some_and(|event| caller_scope().allows(&event.tx))
+        {
+            Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("no tx with id {tx_id:?}"),
+            }))?
+        }
+        self.open_travel_rule(&events).map_err(rpc_error)
+    }
+
+    async fn fee_stats(&self) -> RpcResult<FeeStats> {
+        self.rate_limiter.check("feeStats")?;
+        let history = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_success_txs()
+            .await
+            .map_err(rpc_error)?;
+        Ok(FeeStats::fold(history))
+    }
+
+    async fn diagnose_tx(&self, tx_id: H256) -> RpcResult<TxDiagnosis> {
+        self.rate_limiter.check("diagnoseTx")?;
+        let events = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_tx_events(tx_id)
+            .await
+            .map_err(rpc_error)?;
+        let unknown = || {
+            rpc_error(VaneRpcError::InvalidParams {
+                reason: format!("no tx with id {tx_id:?}"),
+            })
+        };
+        let latest = events
+            .last()
+            .filter(|event| caller_scope().allows(&event.tx))
+            .ok_or_else(unknown)?;
+        // the chain is only asked about signed txs
+        let chain_client = self.chain_clients.get(&latest.tx.network);
+        let chain = match (&latest.status, chain_client) {
+            (TxStatus::TxSubmissionPassed(tx_hash), Some(chain_client)) => {
+                match chain_client.receipt(*tx_hash).await {
+                    Ok(Some(receipt)) => ChainLookup::Included(receipt),
+                    Ok(None) => ChainLookup::Pending,
+                    Err(err) => ChainLookup::ProviderError(err.to_string()),
+                }
+            }
+            (TxStatus::SenderConfirmed, Some(chain_client)) => {
+                match chain_client.health_check().await {
+                    Ok(()) => ChainLookup::Skipped,
+                    Err(err) => ChainLookup::ProviderError(err.to_string()),
+                }
+            }
+            _ => ChainLookup::Skipped,
+        };
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|err| rpc_error(anyhow!("system clock before unix epoch; caused by: {err}")))?
+            .as_secs();
+        TxDiagnosis::of(&events, chain, now).ok_or_else(unknown)
+    }
+
+    async fn retry_tx(&self, tx_id: H256, stage: Option<RetryStage>) -> RpcResult<RetryStage> {
+        self.rate_limiter.check("retryTx")?;
+        self.ensure_leader().map_err(rpc_error)?;
+        self.ensure_unlocked().map_err(rpc_error)?;
+        let events = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_tx_events(tx_id)
+            .await
+            .map_err(rpc_error)?;
+        let mut tx = events
+            .last()
+            .ok_or_else(|| {
+                rpc_error(VaneRpcError::InvalidParams {
+                    reason: format!("no tx with id {tx_id:?}"),
+                })
+            })?
+            .tx
+            .clone();
+        let stage = match stage {
+            Some(stage) if stage.applies_to_events(&events) => stage,
+            Some(stage) => Err(rpc_error(VaneRpcError::InvalidParams {
+                reason: format!(
+                    "tx {tx_id:?} at {:?} can not be retried at {stage:?}",
+                    tx.status
+                ),
+            }))?,
+            None => RetryStage::of(&events).ok_or_else(|| {
+                rpc_error(VaneRpcError::InvalidParams {
+                    reason: format!("tx {tx_id:?} at {:?} has nothing to retry", tx.status),
+                })
+            })?,
+        };
+        // a retried tx is sent like a new one, past its deadline or without a signer it is not
+        ensure_not_overdue(&tx).map_err(rpc_error)?;
+        self.ensure_paired_signer(&tx.sender_address.to_string())
+            .await
+            .map_err(rpc_error)?;
+
+        match (stage, tx.status.clone()) {
+            // still pending on chain, the submitted tx is sent to the provider again as it is
+            (RetryStage::Broadcast, TxStatus::TxSubmissionPassed(tx_hash)) => {
+                let chain_client = self
+                    .chain_clients
+                    .get(&tx.network)
+                    .ok_or_else(|| rpc_error(anyhow!("no chain client for {:?}", tx.network)))?;
+                chain_client.rebroadcast(tx_hash).await.map_err(rpc_error)?;
+                info!("tx {} broadcast again", tx.tx_nonce);
+                return Ok(stage);
+            }
+            (RetryStage::Attestation, _) => tx.status = TxStatus::Genesis,
+            (RetryStage::Simulation, _) => {
+                // a new chain tx is built, the previous signature does not cover it
+                tx.signed_call_payload = None;
+                tx.status = TxStatus::NetConfirmed;
+            }
+            (RetryStage::Broadcast, _) => tx.status = TxStatus::SenderConfirmed,
+        }
+        info!("retrying tx {} at {stage:?}", tx.tx_nonce);
+        timed_lock(
+            &self.user_rpc_update_sender_channel,
+            "user_rpc_update_sender_channel",
+        )
+        .await
+        .send(Arc::from(Mutex::new(tx)))
+        .await
+        .map_err(|_| rpc_error(anyhow!("failed to send retried tx state to sender channel")))?;
+        Ok(stage)
+    }
+
+    async fn search_history(&self, search: HistorySearch) -> RpcResult<Vec<DbTxStateMachine>> {
+        self.rate_limiter.check("searchHistory")?;
+        let mut history = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .search_txs(&search)
+            .await
+            .map_err(rpc_error)?;
+        let scope = caller_scope();
+        history.retain(|tx| scope.allows_record(tx));
+        Ok(history)
+    }
+
+    async fn discover(&self) -> RpcResult<serde_json::Value> {
+        self.rate_limiter.check("rpc.discover")?;
+        Ok(self.openrpc_document().clone())
+    }
+}
+
+/// `tx` is past the deadline of its sender
+fn ensure_not_overdue(tx: &TxStateMachine) -> Result<(), VaneRpcError> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|err| anyhow!("system clock before unix epoch; caused by: {err}"))?
+        .as_secs();
+    match tx.deadline {
+        Some(deadline) if tx.is_overdue(now) => Err(VaneRpcError::DeadlineExceeded {
+            tx_nonce: tx.tx_nonce,
+            deadline,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// checksummed form of a binding or revoked account
+fn checksummed_binding_account(account: &str) -> RpcResult<String> {
+    let account: alloy::primitives::Address = account.parse().map_err(|err| {
+        rpc_error(VaneRpcError::InvalidParams {
+            reason: format!("invalid binding account {account}; caused by: {err}"),
+        })
+    })?;
+    Ok(account.to_string())
+}
+
+/// separators of the amounts of `locale`, english ones when `None`
+fn locale_separators(locale: Option<AmountLocale>) -> Result<Separators, VaneRpcError> {
+    locale.map_or(Ok(Separators::default()), |locale| locale.separators())
+}
+
+/// convert a typed vane error into a json-rpc error object, the error kind and context are carried in `data`
+pub fn rpc_error(err: impl Into<VaneRpcError>) -> Error {
+    let err: VaneRpcError = err.into();
+    Error::Call(CallError::Custom(ErrorObject::owned(
+        err.code(),
+        err.to_string(),
+        Some(err),
+    )))
+}
+
+/// typed vane error carried by a json-rpc error, errors of the rpc server itself are internal
+pub(crate) fn vane_error(err: Error) -> VaneRpcError {
+    let carried = match &err {
+        Error::Call(CallError::Custom(object)) => object
+            .data()
+            .and_then(|data| serde_json::from_str(data.get()).ok()),
+        _ => None,
+    };
+    carried.unwrap_or(VaneRpcError::Internal {
+        reason: err.to_string(),
+    })
 }
 
 // -------------------------------------- WASM BINDGEN ----------------------------------------- //
@@ -0,0 +1,752 @@
+// rpc server middleware
+// request body size limits
+// per caller and method rate limiting ( state changing methods are stricter than reads ), callers
+// are told apart by their api key or else their connection
+// slow and failed call logging for debugging hosted deployments
+// role based access control, api keys are checked per method before the call reaches the rpc
+// module, websocket frames included
+// `GET /metrics` serving the lock contention, peer stats and chain circuit breakers in the
+// prometheus text format
+
+use crate::circuit_breaker::CircuitBreakers;
+use crate::peer_metrics::PeerMetrics;
+use crate::rpc::{rpc_error, vane_error};
+use crate::tx_updates::AccountScope;
+use alloc::sync::Arc;
+use anyhow::anyhow;
+use core::fmt::{Display, Formatter};
+use core::future::Future;
+use core::pin::Pin;
+use core::str::FromStr;
+use core::task::{Context, Poll};
+use hyper::body::HttpBody;
+use hyper::header::{
+    HeaderValue, AUTHORIZATION, CONNECTION, CONTENT_TYPE, SEC_WEBSOCKET_ACCEPT, SEC_WEBSOCKET_KEY,
+    UPGRADE,
+};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use jsonrpsee::core::Error;
+use jsonrpsee::server::logger::{HttpRequest, Logger, MethodKind, Params, TransportProtocol};
+use libp2p::futures::{SinkExt, StreamExt};
+use log::{debug, info, warn};
+use primitives::errors::VaneRpcError;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use tower::{Layer, Service};
+
+/// maximum size of an incoming rpc request body in bytes
+pub const MAX_REQUEST_BODY_SIZE: u32 = 512 * 1024;
+/// maximum size of an outgoing rpc response body in bytes
+pub const MAX_RESPONSE_BODY_SIZE: u32 = 10 * 1024 * 1024;
+/// calls taking longer than this are logged as slow
+pub const SLOW_CALL_THRESHOLD: Duration = Duration::from_millis(500);
+/// call windows kept before the expired ones are dropped
+const MAX_TRACKED_WINDOWS: usize = 10_000;
+
+/// ids of the rpc server connections, callers without an api key are rate limited per connection
+static NEXT_CONNECTION: AtomicU64 = AtomicU64::new(0);
+
+tokio::task_local! {
+    /// caller the rate limited calls of the current rpc request are counted against
+    static CALLER: String;
+    /// accounts the caller of the current rpc request sees the txs of
+    static CALLER_SCOPE: AccountScope;
+}
+
+/// run `call` counting the rate limited calls it makes against `caller`, its tx reads limited to
+/// the accounts of `scope`
+pub async fn as_caller<F: Future>(caller: String, scope: AccountScope, call: F) -> F::Output {
+    CALLER.scope(caller, CALLER_SCOPE.scope(scope, call)).await
+}
+
+/// accounts the caller of the current rpc request sees the txs of, calls made in process outside
+/// of an rpc request see every account
+pub fn caller_scope() -> AccountScope {
+    CALLER_SCOPE.try_with(Clone::clone).unwrap_or_default()
+}
+
+/// number of calls allowed for a method within a time window
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateQuota {
+    pub max_calls: u32,
+    pub window: Duration,
+}
+
+impl RateQuota {
+    pub const fn per_minute(max_calls: u32) -> Self {
+        Self {
+            max_calls,
+            window: Duration::from_secs(60),
+        }
+    }
+}
+
+/// sliding window rate limiter keyed by caller and rpc method name, one caller exhausting a quota
+/// does not throttle the others
+#[derive(Clone)]
+pub struct MethodRateLimiter {
+    quotas: Arc<HashMap<&'static str, RateQuota>>,
+    default_quota: RateQuota,
+    calls: Arc<std::sync::Mutex<HashMap<(String, String), VecDeque<Instant>>>>,
+}
+
+impl Default for MethodRateLimiter {
+    fn default() -> Self {
+        let mut quotas = HashMap::new();
+        // state changing methods, each call triggers db writes, remote registry calls or p2p traffic
+        quotas.insert("register", RateQuota::per_minute(3));
+        quotas.insert("addAccount", RateQuota::per_minute(10));
+        quotas.insert("initiateTransaction", RateQuota::per_minute(5));
+        quotas.insert("initiateFiatTransaction", RateQuota::per_minute(5));
+        // every part counts against the transfer quota as well
+        quotas.insert("initiateSplitPayment", RateQuota::per_minute(2));
+        quotas.insert("senderConfirm", RateQuota::per_minute(20));
+        quotas.insert("retryTx", RateQuota::per_minute(10));
+        quotas.insert("receiverConfirm", RateQuota::per_minute(20));
+        quotas.insert("confirmPendingReceives", RateQuota::per_minute(10));
+        quotas.insert("rejectPendingReceives", RateQuota::per_minute(10));
+        quotas.insert("rejectWithCorrection", RateQuota::per_minute(10));
+        quotas.insert("restartWithCorrection", RateQuota::per_minute(5));
+        quotas.insert("rerouteTransaction", RateQuota::per_minute(5));
+        quotas.insert("pairDevice", RateQuota::per_minute(5));
+        // passphrase guesses
+        quotas.insert("unlock", RateQuota::per_minute(5));
+        quotas.insert("rotateNodeIdentity", RateQuota::per_minute(1));
+        quotas.insert("revokeAccount", RateQuota::per_minute(5));
+        quotas.insert("provisionTestAccounts", RateQuota::per_minute(5));
+
+        // reads
+        Self::new(quotas, RateQuota::per_minute(120))
+    }
+}
+
+impl MethodRateLimiter {
+    pub fn new(quotas: HashMap<&'static str, RateQuota>, default_quota: RateQuota) -> Self {
+        Self {
+            quotas: Arc::new(quotas),
+            default_quota,
+            calls: Default::default(),
+        }
+    }
+
+    pub fn quota(&self, method: &str) -> RateQuota {
+        self.quotas
+            .get(method)
+            .copied()
+            .unwrap_or(self.default_quota)
+    }
+
+    /// record a call to `method` by the caller of the current rpc request, erroring if its quota
+    /// is exhausted in the current window. calls made in process, outside of an rpc request, are
+    /// not limited
+    pub fn check(&self, method: &str) -> Result<(), Error> {
+        match CALLER.try_with(Clone::clone) {
+            Ok(caller) => self.check_caller(&caller, method),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// record a call to `method` by `caller`, erroring if its quota is exhausted in the current
+    /// window
+    pub fn check_caller(&self, caller: &str, method: &str) -> Result<(), Error> {
+        let quota = self.quota(method);
+        let now = Instant::now();
+
+        let mut calls = self
+            .calls
+            .lock()
+            .map_err(|_| rpc_error(anyhow!("rate limiter state poisoned")))?;
+        if calls.len() >= MAX_TRACKED_WINDOWS {
+            calls.retain(|(_, method), window| {
+                window
+                    .back()
+                    .is_some_and(|last| now.duration_since(*last) < self.quota(method).window)
+            });
+        }
+        let window = calls
+            .entry((caller.to_string(), method.to_string()))
+            .or_default();
+
+        while let Some(oldest) = window.front() {
+            if now.duration_since(*oldest) >= quota.window {
+                window.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if window.len() as u32 >= quota.max_calls {
+            warn!(target: "rpc","rate limit exceeded: method={method} quota={} per {:?}",quota.max_calls,quota.window);
+            Err(rpc_error(VaneRpcError::RateLimited {
+                method: method.to_string(),
+                max_calls: quota.max_calls,
+                window_secs: quota.window.as_secs(),
+            }))?
+        }
+        window.push_back(now);
+        Ok(())
+    }
+}
+
+/// logs every rpc call with its timing, warning on slow and failed calls
+#[derive(Clone, Debug)]
+pub struct RpcLogger {
+    pub slow_call_threshold: Duration,
+}
+
+impl Default for RpcLogger {
+    fn default() -> Self {
+        Self {
+            slow_call_threshold: SLOW_CALL_THRESHOLD,
+        }
+    }
+}
+
+impl Logger for RpcLogger {
+    type Instant = Instant;
+
+    fn on_connect(&self, remote_addr: SocketAddr, _request: &HttpRequest, t: TransportProtocol) {
+        info!(target: "rpc","client connected: remote_addr={remote_addr} transport={t}");
+    }
+
+    fn on_request(&self, _transport: TransportProtocol) -> Self::Instant {
+        Instant::now()
+    }
+
+    fn on_call(&self, method_name: &str, params: Params, kind: MethodKind, t: TransportProtocol) {
+        debug!(target: "rpc","call: method={method_name} kind={kind} transport={t} params_bytes={}",params.len_bytes());
+    }
+
+    fn on_result(
+        &self,
+        method_name: &str,
+        success: bool,
+        started_at: Self::Instant,
+        t: TransportProtocol,
+    ) {
+        let elapsed = started_at.elapsed();
+        if !success {
+            warn!(target: "rpc","failed call: method={method_name} transport={t} elapsed_ms={}",elapsed.as_millis());
+        } else if elapsed > self.slow_call_threshold {
+            warn!(target: "rpc","slow call: method={method_name} transport={t} elapsed_ms={} threshold_ms={}",elapsed.as_millis(),self.slow_call_threshold.as_millis());
+        } else {
+            debug!(target: "rpc","call finished: method={method_name} transport={t} elapsed_ms={}",elapsed.as_millis());
+        }
+    }
+
+    fn on_response(&self, _result: &str, _started_at: Self::Instant, _t: TransportProtocol) {}
+
+    fn on_disconnect(&self, remote_addr: SocketAddr, t: TransportProtocol) {
+        info!(target: "rpc","client disconnected: remote_addr={remote_addr} transport={t}");
+    }
+}
+
+/// rpc caller role, a role can call every method of the roles below it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    /// reads only, e.g. a dashboard
+    Viewer,
+    /// initiates and confirms txs
+    Operator,
+    /// changes policies and manages accounts
+    Admin,
+}
+
+impl Role {
+    /// minimum role allowed to call `method`, methods not listed here are admin only
+    pub fn required_for(method: &str) -> Role {
+        match method {
+            "subscribeTxUpdates"
+            | "unsubscribeTxUpdates"
+            | "fetchPendingTxUpdates"
+            | "listPeers"
+            | "chainHealth"
+            | "peerStats"
+            | "feeTiers"
+            | "computeBudgetTiers"
+            | "detectChains"
+            | "parseAmount"
+            | "formatAmount"
+            | "getCounterparty"
+            | "listApprovals"
+            | "listPolicies"
+            | "pairingMessage"
+            | "listPairedSigners"
+            | "peerBindingMessage"
+            | "listPeerBindings"
+            | "revocationMessage"
+            | "listRevocations"
+            | "listPairedDevices"
+            | "listDeviceTransfers"
+            | "listPendingReceives"
+            | "listBadges"
+            | "txHistory"
+            | "searchHistory"
+            | "txReceipt"
+            | "getTravelRule"
+            | "feeStats"
+            | "diagnoseTx"
+            | "suggestRoutes"
+            | "getPayment"
+            | "acceptedAssets"
+            | "verifyBinding"
+            | "rpc.discover" => Role::Viewer,
+            "initiateTransaction"
+            | "initiateFiatTransaction"
+            | "initiateSplitPayment"
+            | "senderConfirm"
+            | "retryTx"
+            | "receiverConfirm"
+            | "approverConfirm"
+            | "confirmPendingReceives"
+            | "rejectPendingReceives"
+            | "rejectWithCorrection"
+            | "restartWithCorrection"
+            | "rerouteTransaction"
+            | "approveToken"
+            | "revokeApproval"
+            | "buildPermit"
+            | "anchorCall"
+            | "faucet"
+            | "unlock"
+            | "lock" => Role::Operator,
+            _ => Role::Admin,
+        }
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Role::Viewer => write!(f, "viewer"),
+            Role::Operator => write!(f, "operator"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(role: &str) -> Result<Self, Self::Err> {
+        match role {
+            "viewer" => Ok(Role::Viewer),
+            "operator" => Ok(Role::Operator),
+            "admin" => Ok(Role::Admin),
+            other => Err(anyhow!(
+                "unknown role: {other}, expected viewer, operator or admin"
+            )),
+        }
+    }
+}
+
+/// api keys and their roles, sent by clients as `Authorization: Bearer <key>`
+/// without any key the access control is disabled and every caller is admin
+#[derive(Clone, Debug, Default)]
+pub struct AccessControl {
+    keys: Arc<HashMap<String, Role>>,
+    /// accounts the tx updates and tx reads of a key are scoped to
+    scopes: Arc<HashMap<String, AccountScope>>,
+}
+
+impl AccessControl {
+    pub fn new(keys: HashMap<String, Role>) -> Self {
+        Self {
+            keys: Arc::new(keys),
+            scopes: Arc::default(),
+        }
+    }
+
+    /// scope the tx updates and reads of each key to its accounts, other keys see every tx
+    pub fn with_scopes(mut self, scopes: HashMap<String, AccountScope>) -> Self {
+        self.scopes = Arc::new(scopes);
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// role of the api key carried by the request headers
+    pub fn role(&self, headers: &hyper::HeaderMap) -> Option<Role> {
+        self.keys.get(bearer_key(headers)?).copied()
+    }
+
+    /// accounts the api key carried by the request headers sees the tx updates of
+    pub fn scope(&self, headers: &hyper::HeaderMap) -> AccountScope {
+        bearer_key(headers)
+            .and_then(|key| self.scopes.get(key))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// api key carried by the request headers as `Authorization: Bearer <key>`
+pub(crate) fn bearer_key(headers: &hyper::HeaderMap) -> Option<&str> {
+    let key = headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")?;
+    Some(key.trim())
+}
+
+/// tower layer enforcing `AccessControl` and the rate limits of each caller on the rpc server
+#[derive(Clone)]
+pub struct AccessControlLayer {
+    access: AccessControl,
+    rate_limiter: MethodRateLimiter,
+}
+
+impl AccessControlLayer {
+    pub fn new(access: AccessControl, rate_limiter: MethodRateLimiter) -> Self {
+        Self {
+            access,
+            rate_limiter,
+        }
+    }
+}
+
+impl<S> Layer<S> for AccessControlLayer {
+    type Service = AccessControlService<S>;
+
+    // the rpc server layers the service of each connection
+    fn layer(&self, inner: S) -> Self::Service {
+        AccessControlService {
+            inner,
+            access: self.access.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            connection: NEXT_CONNECTION.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+/// http requests are checked per called method, batches included, and run as their caller for the
+/// rate limits. the rpc server does not tell the calls of an upgraded websocket apart by key, so
+/// websockets are relayed and their frames checked and rate limited the same way
+#[derive(Clone)]
+pub struct AccessControlService<S> {
+    inner: S,
+    access: AccessControl,
+    rate_limiter: MethodRateLimiter,
+    connection: u64,
+}
+
+impl<S> Service<Request<Body>> for AccessControlService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // the ready service is the one to call, leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = core::mem::replace(&mut self.inner, clone);
+        let access = self.access.clone();
+        let rate_limiter = self.rate_limiter.clone();
+        // keys are shared by their clients, other callers are told apart by their connection
+        let caller = match bearer_key(request.headers()) {
+            Some(key) => format!("key:{key}"),
+            None => format!("connection:{}", self.connection),
+        };
+
+        Box::pin(async move {
+            let role = if access.is_enabled() {
+                let Some(role) = access.role(request.headers()) else {
+                    warn!(target: "rpc","rejected request without a valid api key");
+                    return Ok(plain_response(
+                        StatusCode::UNAUTHORIZED,
+                        "missing or unknown api key",
+                    ));
+                };
+                role
+            } else {
+                Role::Admin
+            };
+
+            let is_websocket = request
+                .headers()
+                .get(UPGRADE)
+                .and_then(|upgrade| upgrade.to_str().ok())
+                .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"));
+            if is_websocket {
+                // the subscriptions of a websocket session can not tell its key
+                if access.scope(request.headers()).is_scoped() {
+                    warn!(target: "rpc","rejected websocket connection for an account scoped key");
+                    return Ok(plain_response(
+                        StatusCode::FORBIDDEN,
+                        "account scoped keys subscribe over `GET /tx-updates` or grpc",
+                    ));
+                }
+                let gate = WebsocketGate {
+                    role,
+                    rate_limiter,
+                    caller,
+                };
+                return Ok(gated_websocket(inner, request, gate));
+            }
+
+            let request = if role < Role::Admin {
+                let (parts, body) = request.into_parts();
+                let Some(body) = read_body(body).await else {
+                    return Ok(plain_response(
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        "request body too large",
+                    ));
+                };
+                if let Some((id, method)) = called_methods(&body)
+                    .into_iter()
+                    .find(|(_, method)| role < Role::required_for(method))
+                {
+                    warn!(target: "rpc","access denied: method={method} role={role}");
+                    return Ok(access_denied(id, &method));
+                }
+                Request::from_parts(parts, Body::from(body))
+            } else {
+                request
+            };
+            let scope = access.scope(request.headers());
+            as_caller(caller, scope, inner.call(request)).await
+        })
+    }
+}
+
+/// tower layer answering `GET /metrics` with the lock, peer and circuit breaker metrics, behind the
+/// access control so any api key may scrape it
+#[derive(Clone, Default)]
+pub struct MetricsLayer {
+    peer_metrics: PeerMetrics,
+    circuit_breakers: CircuitBreakers,
+}
+
+impl MetricsLayer {
+    pub fn new(peer_metrics: PeerMetrics, circuit_breakers: CircuitBreakers) -> Self {
+        Self {
+            peer_metrics,
+            circuit_breakers,
+        }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            peer_metrics: self.peer_metrics.clone(),
+            circuit_breakers: self.circuit_breakers.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MetricsService<S> {
+    inner: S,
+    peer_metrics: PeerMetrics,
+    circuit_breakers: CircuitBreakers,
+}
+
+impl<S> Service<Request<Body>> for MetricsService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if request.method() == Method::GET && request.uri().path() == "/metrics" {
+            let metrics = crate::locks::render_metrics()
+                + &self.peer_metrics.render_metrics()
+                + &self.circuit_breakers.render_metrics();
+            let mut response = Response::new(Body::from(metrics));
+            response.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("text/plain; version=0.0.4"),
+            );
+            return Box::pin(async move { Ok(response) });
+        }
+        Box::pin(self.inner.call(request))
+    }
+}
+
+/// buffer the request body, `None` when it exceeds `MAX_REQUEST_BODY_SIZE` or the stream failed
+pub(crate) async fn read_body(mut body: Body) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.ok()?;
+        if bytes.len() + chunk.len() > MAX_REQUEST_BODY_SIZE as usize {
+            return None;
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Some(bytes)
+}
+
+/// request id and method of each call in a json-rpc request or batch, malformed requests are left
+/// to the server to reject
+fn called_methods(body: &[u8]) -> Vec<(Value, String)> {
+    let calls = match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Array(calls)) => calls,
+        Ok(call) => vec![call],
+        Err(_) => vec![],
+    };
+    calls
+        .into_iter()
+        .filter_map(|call| {
+            let method = call.get("method")?.as_str()?.to_string();
+            Some((call.get("id").cloned().unwrap_or(Value::Null), method))
+        })
+        .collect()
+}
+
+pub(crate) fn plain_response(status: StatusCode, message: &'static str) -> Response<Body> {
+    let mut response = Response::new(Body::from(message));
+    *response.status_mut() = status;
+    response
+}
+
+/// role and rate limits the frames of a relayed websocket are checked against
+struct WebsocketGate {
+    role: Role,
+    rate_limiter: MethodRateLimiter,
+    caller: String,
+}
+
+impl WebsocketGate {
+    /// json-rpc error answering a frame instead of the rpc server, for the first call above the
+    /// role or over the rate limits of the caller
+    fn refusal(&self, frame: &[u8]) -> Option<Value> {
+        called_methods(frame).into_iter().find_map(|(id, method)| {
+            if self.role < Role::required_for(&method) {
+                warn!(target: "rpc","access denied: method={method} role={}",self.role);
+                return Some(access_denied_body(id, &method));
+            }
+            let err = self
+                .rate_limiter
+                .check_caller(&self.caller, &method)
+                .err()?;
+            Some(error_body(id, vane_error(err)))
+        })
+    }
+}
+
+/// accept a websocket upgrade and relay the connection to the rpc server behind this layer, served
+/// over an in-memory connection
+fn gated_websocket<S>(inner: S, request: Request<Body>, gate: WebsocketGate) -> Response<Body>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Send + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    S::Future: Send + 'static,
+{
+    let Some(key) = request.headers().get(SEC_WEBSOCKET_KEY) else {
+        return plain_response(StatusCode::BAD_REQUEST, "missing websocket key");
+    };
+    let accept = derive_accept_key(key.as_bytes());
+    tokio::spawn(async move {
+        if let Err(err) = relay_websocket(inner, request, gate).await {
+            debug!(target: "rpc","websocket relay closed; caused by: {err}");
+        }
+    });
+    Response::builder()
+        .status(StatusCode::SWITCHING_PROTOCOLS)
+        .header(CONNECTION, "upgrade")
+        .header(UPGRADE, "websocket")
+        .header(SEC_WEBSOCKET_ACCEPT, accept)
+        .body(Body::empty())
+        .unwrap_or_default()
+}
+
+/// pass the frames both ways, a frame the gate refuses is answered with its error instead of
+/// reaching the rpc server
+async fn relay_websocket<S>(
+    inner: S,
+    request: Request<Body>,
+    gate: WebsocketGate,
+) -> Result<(), anyhow::Error>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Send + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    S::Future: Send + 'static,
+{
+    let upgraded = hyper::upgrade::on(request).await?;
+    let client = WebSocketStream::from_raw_socket(upgraded, protocol::Role::Server, None).await;
+    let (local, remote) = tokio::io::duplex(MAX_RESPONSE_BODY_SIZE as usize);
+    tokio::spawn(
+        hyper::server::conn::Http::new()
+            .serve_connection(remote, inner)
+            .with_upgrades(),
+    );
+    let (server, _) = tokio_tungstenite::client_async("ws://localhost/", local).await?;
+
+    let (mut client_tx, mut client_rx) = client.split();
+    let (mut server_tx, mut server_rx) = server.split();
+    loop {
+        tokio::select! {
+            message = client_rx.next() => {
+                let Some(message) = message.transpose()? else { break };
+                if message.is_text() || message.is_binary() {
+                    if let Some(refusal) = gate.refusal(&message.clone().into_data()) {
+                        client_tx.send(Message::Text(refusal.to_string())).await?;
+                        continue;
+                    }
+                }
+                server_tx.send(message).await?;
+            }
+            message = server_rx.next() => {
+                let Some(message) = message.transpose()? else { break };
+                client_tx.send(message).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// json-rpc error of a call above the role of the key, so clients get the typed error like any
+/// other vane error
+fn access_denied_body(id: Value, method: &str) -> Value {
+    let err = VaneRpcError::AccessDenied {
+        method: method.to_string(),
+        role: Role::required_for(method).to_string(),
+    };
+    error_body(id, err)
+}
+
+/// json-rpc response of the call `id` failing with `err`
+fn error_body(id: Value, err: VaneRpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code(), "message": err.to_string(), "data": err },
+    })
+}
+
+fn access_denied(id: Value, method: &str) -> Response<Body> {
+    let body = access_denied_body(id, method);
+    let mut response = Response::new(Body::from(body.to_string()));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
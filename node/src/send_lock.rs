@@ -0,0 +1,85 @@
+// passphrase lock of the send operations
+// a node started with a send passphrase refuses to initiate or sender confirm transfers until it is
+// unlocked with the passphrase. an unlock lasts for the unlock window and the node locks itself
+// again once it passed, like a wallet does. only a salted hash of the passphrase is kept in memory
+
+use alloy::primitives::{keccak256, B256};
+use log::{info, warn};
+use primitives::errors::VaneRpcError;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone)]
+pub struct SendLock {
+    salt: [u8; 32],
+    passphrase_hash: B256,
+    window: Duration,
+    unlocked_until: Arc<Mutex<Option<Instant>>>,
+}
+
+impl SendLock {
+    pub fn new(passphrase: &str, window: Duration) -> Self {
+        let salt: [u8; 32] = rand::random();
+        Self {
+            salt,
+            passphrase_hash: Self::hash(&salt, passphrase),
+            window,
+            unlocked_until: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn hash(salt: &[u8; 32], passphrase: &str) -> B256 {
+        keccak256([salt.as_slice(), passphrase.as_bytes()].concat())
+    }
+
+    fn unlocked_until(&self) -> std::sync::MutexGuard<'_, Option<Instant>> {
+        self.unlocked_until
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// unlock the send operations for the unlock window, returns the unix timestamp in seconds the
+    /// node locks itself again at
+    pub fn unlock(&self, passphrase: &str) -> Result<u64, VaneRpcError> {
+        let hash = Self::hash(&self.salt, passphrase);
+        // compared in constant time, the rate limiter bounds the guesses of each api key
+        let matches = hash
+            .iter()
+            .zip(self.passphrase_hash.iter())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0;
+        if !matches {
+            warn!("unlock with a wrong send passphrase");
+            Err(VaneRpcError::InvalidParams {
+                reason: "wrong send passphrase".to_string(),
+            })?
+        }
+        *self.unlocked_until() = Some(Instant::now() + self.window);
+        info!("send operations unlocked for {:?}", self.window);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Ok((now + self.window).as_secs())
+    }
+
+    /// lock the send operations before the unlock window passed
+    pub fn lock(&self) {
+        *self.unlocked_until() = None;
+        info!("send operations locked");
+    }
+
+    /// refuse the send operations unless unlocked within the unlock window
+    pub fn ensure_unlocked(&self) -> Result<(), VaneRpcError> {
+        let mut unlocked_until = self.unlocked_until();
+        match *unlocked_until {
+            Some(until) if Instant::now() < until => Ok(()),
+            _ => {
+                // relock once the window passed
+                *unlocked_until = None;
+                Err(VaneRpcError::NodeLocked {
+                    window_secs: self.window.as_secs(),
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+// receiver side spam controls
+// attestation requests from unknown senders, below a dust threshold or past a per sender frequency are
+// declined before they reach the user, the sender sees its tx as RecvAddrFailed
+
+use db::{DbWorker, DbWorkerInterface};
+use moka::future::Cache as AsyncCache;
+use primitives::address::VaneAddress;
+use primitives::data_structure::{Token, TxStateMachine, TxStatus};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+/// senders whose recent requests are counted, the least recent ones are forgotten past it
+const MAX_TRACKED_SENDERS: u64 = 10_000;
+
+/// why an attestation request was declined
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpamReason {
+    UnknownSender,
+    Dust,
+    TooFrequent,
+}
+
+impl Display for SpamReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpamReason::UnknownSender => write!(f, "sender is unknown"),
+            SpamReason::Dust => write!(f, "amount below the dust threshold"),
+            SpamReason::TooFrequent => write!(f, "too many requests from the sender"),
+        }
+    }
+}
+
+/// operator controls on incoming attestation requests, all disabled by default
+#[derive(Clone, Debug)]
+pub struct SpamFilter {
+    /// only senders in the peer book or that this node attested for before
+    pub reject_unknown_senders: bool,
+    /// per token amount, in the token smallest unit, requests below it are declined
+    pub dust_thresholds: HashMap<Token, u128>,
+    /// at most this many requests per sender within the window
+    max_requests_per_sender: Option<(u32, Duration)>,
+    /// recent request times per sender, a sender quiet for the window is forgotten
+    requests: AsyncCache<VaneAddress, VecDeque<Instant>>,
+}
+
+impl Default for SpamFilter {
+    fn default() -> Self {
+        Self {
+            reject_unknown_senders: false,
+            dust_thresholds: HashMap::new(),
+            max_requests_per_sender: None,
+            requests: AsyncCache::new(MAX_TRACKED_SENDERS),
+        }
+    }
+}
+
+impl SpamFilter {
+    /// decline the requests of a sender past `max_requests` within `window`
+    pub fn limit_requests_per_sender(&mut self, max_requests: u32, window: Duration) {
+        self.max_requests_per_sender = Some((max_requests, window));
+        self.requests = AsyncCache::builder()
+            .max_capacity(MAX_TRACKED_SENDERS)
+            .time_to_idle(window)
+            .build();
+    }
+
+    /// reason to decline `tx`, every request is counted toward the sender frequency even when declined
+    pub async fn check(
+        &self,
+        db: &DbWorker,
+        tx: &TxStateMachine,
+    ) -> Result<Option<SpamReason>, anyhow::Error> {
+        if let Some((max_requests, window)) = self.max_requests_per_sender {
+            let now = Instant::now();
+            let sender_requests = self
+                .requests
+                .entry(tx.sender_address)
+                .and_upsert_with(|entry| {
+                    let mut requests = entry.map(|entry| entry.into_value()).unwrap_or_default();
+                    while requests
+                        .front()
+                        .is_some_and(|at| now.duration_since(*at) > window)
+                    {
+                        requests.pop_front();
+                    }
+                    // one request past the limit is enough to decline the next ones
+                    requests.push_back(now);
+                    if requests.len() > max_requests as usize + 1 {
+                        requests.pop_front();
+                    }
+                    std::future::ready(requests)
+                })
+                .await;
+            if sender_requests.value().len() > max_requests as usize {
+                return Ok(Some(SpamReason::TooFrequent));
+            }
+        }
+
+        let token = tx.token.unwrap_or(Token::native(tx.network));
+        if self
+            .dust_thresholds
+            .get(&token)
+            .is_some_and(|threshold| tx.amount < *threshold)
+        {
+            return Ok(Some(SpamReason::Dust));
+        }
+
+        if self.reject_unknown_senders {
+            let is_contact = db
+                .get_saved_user_peers(tx.sender_address.to_string())
+                .await
+                .is_ok();
+            let attested_before = db
+                .get_sender_tx_events(tx.sender_address.to_string(), None)
+                .await?
+                .iter()
+                .any(|event| event.status == TxStatus::RecvAddrConfirmed);
+            if !is_contact && !attested_before {
+                return Ok(Some(SpamReason::UnknownSender));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,216 @@
+// per chain submission workers
+// each network gets its own queue and task submitting through its own chain client, so a slow chain rpc
+// only delays txs on that chain. outcomes are reported back to the main service worker on a shared channel
+// the txs waiting on a queue are submitted the most urgent first, in arrival order within a priority
+// and are held while the circuit breaker of the chain is open
+// chains with an independent provider check that provider sees the broadcast tx, catching a primary
+// provider silently dropping txs. the check runs off the queue and a tx it does not see in time is
+// still reported submitted, with a warning, as the primary provider accepted it
+
+use crate::chain::ChainClient;
+use crate::circuit_breaker::CircuitBreakers;
+use alloc::sync::Arc;
+use alloy::hex;
+use anyhow::anyhow;
+use log::{error, info, warn};
+use primitives::data_structure::{BreakerState, ChainSupported, TxPriority};
+use primitives::tx_state::SenderConfirmedTx;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+/// how long a broadcast tx has to show up at the independent provider before being reported
+/// unverified
+pub const VERIFY_TIMEOUT: Duration = Duration::from_secs(30);
+const VERIFY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// result of submitting a sender confirmed tx
+pub struct SubmissionOutcome {
+    pub tx: SenderConfirmedTx,
+    /// tx hash on success
+    pub result: Result<[u8; 32], anyhow::Error>,
+    /// the independent provider did not see the submitted tx in time
+    pub unverified: bool,
+}
+
+/// txs received while another one was submitting
+#[derive(Default)]
+struct WaitingTxs {
+    txs: BTreeMap<(Reverse<TxPriority>, u64), SenderConfirmedTx>,
+    arrivals: u64,
+}
+
+impl WaitingTxs {
+    fn push(&mut self, tx: SenderConfirmedTx) {
+        self.txs.insert((Reverse(tx.priority), self.arrivals), tx);
+        self.arrivals += 1;
+    }
+
+    /// the most urgent tx received first
+    fn pop(&mut self) -> Option<SenderConfirmedTx> {
+        self.txs.pop_first().map(|(_, tx)| tx)
+    }
+}
+
+/// drains the queue of a single network by priority
+pub struct SubmissionWorker {
+    chain_client: Arc<dyn ChainClient>,
+    queue: Receiver<SenderConfirmedTx>,
+    outcomes: Sender<SubmissionOutcome>,
+    breakers: CircuitBreakers,
+    /// independent provider of the same network confirming the broadcast
+    verifier: Option<Arc<dyn ChainClient>>,
+}
+
+impl SubmissionWorker {
+    pub async fn run(mut self) {
+        let network = self.chain_client.network();
+        let mut waiting = WaitingTxs::default();
+        loop {
+            if waiting.txs.is_empty() {
+                match self.queue.recv().await {
+                    Some(tx) => waiting.push(tx),
+                    None => break,
+                }
+            }
+            while let Ok(tx) = self.queue.try_recv() {
+                waiting.push(tx);
+            }
+            // the queued txs wait until the provider answers a probe again
+            if self.breakers.state(network) == BreakerState::Open {
+                tokio::time::sleep(CircuitBreakers::PROBE_INTERVAL).await;
+                let probe = tokio::time::timeout(
+                    CircuitBreakers::PROBE_INTERVAL,
+                    self.chain_client.health_check(),
+                );
+                if matches!(probe.await, Ok(Ok(()))) {
+                    self.breakers.half_open(network);
+                }
+                continue;
+            }
+            let Some(tx) = waiting.pop() else {
+                continue;
+            };
+            info!(target: "SubmissionWorker", "submitting {network:?} {:?} tx: {}", tx.priority, tx.tx_nonce);
+            let result = self.chain_client.submit_tx(&tx).await;
+            // only the primary provider answer counts towards the breaker
+            match &result {
+                Ok(_) => self.breakers.record_success(network),
+                Err(_) => self.breakers.record_failure(network),
+            }
+            if let (Ok(tx_hash), Some(verifier)) = (&result, &self.verifier) {
+                // the next txs of the queue are submitted meanwhile
+                let (tx_hash, verifier, outcomes) =
+                    (*tx_hash, verifier.clone(), self.outcomes.clone());
+                tokio::spawn(async move {
+                    let unverified = verify_broadcast(verifier.as_ref(), tx_hash).await.is_err();
+                    let outcome = SubmissionOutcome {
+                        tx,
+                        result: Ok(tx_hash),
+                        unverified,
+                    };
+                    if outcomes.send(outcome).await.is_err() {
+                        error!(target: "SubmissionWorker", "{network:?} outcome channel closed");
+                    }
+                });
+                continue;
+            }
+            let outcome = SubmissionOutcome {
+                tx,
+                result,
+                unverified: false,
+            };
+            if self.outcomes.send(outcome).await.is_err() {
+                error!(target: "SubmissionWorker", "{network:?} outcome channel closed, stopping");
+                break;
+            }
+        }
+    }
+}
+
+/// `tx_hash` once `verifier` knows it, polled until `VERIFY_TIMEOUT`
+async fn verify_broadcast(
+    verifier: &dyn ChainClient,
+    tx_hash: [u8; 32],
+) -> Result<[u8; 32], anyhow::Error> {
+    let seen = tokio::time::timeout(VERIFY_TIMEOUT, async {
+        loop {
+            match verifier.knows_tx(tx_hash).await {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(err) => {
+                    warn!(target: "SubmissionWorker", "failed to verify tx 0x{}: {err}", hex::encode(tx_hash))
+                }
+            }
+            tokio::time::sleep(VERIFY_POLL_INTERVAL).await;
+        }
+    });
+    seen.await.map(|_| tx_hash).map_err(|_| {
+        let err = anyhow!(
+            "{:?} tx 0x{} was not seen by the independent provider within {}s",
+            verifier.network(),
+            hex::encode(tx_hash),
+            VERIFY_TIMEOUT.as_secs()
+        );
+        warn!(target: "SubmissionWorker", "{err}");
+        err
+    })
+}
+
+/// handles to the per network submission queues
+#[derive(Clone)]
+pub struct SubmissionQueues {
+    queues: HashMap<ChainSupported, Sender<SenderConfirmedTx>>,
+    tasks: Arc<Vec<JoinHandle<()>>>,
+}
+
+impl SubmissionQueues {
+    /// spawn a submission worker per chain client, returns the queues and the outcomes of all workers
+    /// txs of the networks in `verifiers` are reported submitted once their verifier sees them
+    pub fn spawn(
+        chain_clients: impl IntoIterator<Item = Arc<dyn ChainClient>>,
+        capacity: usize,
+        breakers: CircuitBreakers,
+        verifiers: &HashMap<ChainSupported, Arc<dyn ChainClient>>,
+    ) -> (Self, Receiver<SubmissionOutcome>) {
+        let (outcomes, outcomes_recv) = tokio::sync::mpsc::channel(capacity);
+        let mut queues = HashMap::new();
+        let mut tasks = Vec::new();
+        for chain_client in chain_clients {
+            let (queue_sender, queue) = tokio::sync::mpsc::channel(capacity);
+            let network = chain_client.network();
+            queues.insert(network, queue_sender);
+            let worker = SubmissionWorker {
+                chain_client,
+                queue,
+                outcomes: outcomes.clone(),
+                breakers: breakers.clone(),
+                verifier: verifiers.get(&network).cloned(),
+            };
+            tasks.push(tokio::spawn(worker.run()));
+        }
+        let queues = Self {
+            queues,
+            tasks: Arc::new(tasks),
+        };
+        (queues, outcomes_recv)
+    }
+
+    /// enqueue the tx on its network queue
+    pub async fn submit(&self, tx: SenderConfirmedTx) -> Result<(), anyhow::Error> {
+        let network = tx.network;
+        self.queues
+            .get(&network)
+            .ok_or(anyhow!("no submission worker for {network:?}"))?
+            .send(tx)
+            .await
+            .map_err(|_| anyhow!("{network:?} submission worker stopped"))
+    }
+
+    /// stop all workers
+    pub fn abort(&self) {
+        self.tasks.iter().for_each(|task| task.abort());
+    }
+}
@@ -1,6 +1,225 @@
-// share number of transactions done
-// value of transactions (reverted and succeeded)
-// node id and number of chains network connected and used
-// revenue for vane
+// anonymized node telemetry, substrate style
+// a node configured with a telemetry server connects to it over websocket, announces itself with
+// `system.connected` and then sends `system.interval` stats. no account, address, amount or peer id leaves the node, it is only
+// known by a random session id drawn at every start. operators can opt in to also share the daily loss
+// prevention aggregates of `primitives::loss_prevention`. watchdog health events are forwarded as they come
 
-pub struct TelemetryWorker {}
+use crate::locks::timed_lock;
+use crate::watchdog::HealthEvent;
+use anyhow::anyhow;
+use db::{DbWorker, DbWorkerInterface};
+use libp2p::futures::SinkExt;
+use log::{info, warn};
+use primitives::data_structure::{ChainSupported, TxStatus};
+use primitives::loss_prevention::{self, LossPreventionReport, SECS_PER_DAY};
+use serde::Serialize;
+use sp_core::H256;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+/// latest status of every tx, read from the event log as it grows
+#[derive(Default)]
+struct TxTally {
+    /// record time of the latest event read, the next read starts from it
+    read_up_to: u64,
+    latest: HashMap<H256, TxStatus>,
+}
+
+/// stats shared with the telemetry server
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct NodeStats {
+    pub txs_initiated: u64,
+    pub txs_submitted: u64,
+    pub txs_failed: u64,
+    /// failed over finished txs, 0 before any finished
+    pub error_rate: f64,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "msg")]
+enum Payload<'a> {
+    #[serde(rename = "system.connected")]
+    Connected {
+        implementation: &'static str,
+        version: &'static str,
+        chains: &'a [ChainSupported],
+    },
+    #[serde(rename = "system.interval")]
+    Interval(&'a NodeStats),
+    #[serde(rename = "loss_prevention.daily")]
+    LossPrevention(&'a LossPreventionReport),
+    #[serde(rename = "system.health")]
+    Health(&'a HealthEvent),
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    id: u64,
+    /// unix timestamp in milliseconds
+    ts: u128,
+    payload: Payload<'a>,
+}
+
+#[derive(Clone)]
+pub struct TelemetryWorker {
+    url: String,
+    session_id: u64,
+    chains: Vec<ChainSupported>,
+    db_worker: Arc<Mutex<DbWorker>>,
+    /// usd price per whole token when the operator opted in to share loss prevention aggregates
+    loss_prevention: Option<BTreeMap<String, f64>>,
+    /// last day whose loss prevention aggregates were handled, each day is sent once per session
+    reported_day: Arc<Mutex<Option<u64>>>,
+    tally: Arc<Mutex<TxTally>>,
+    event_sender: Sender<HealthEvent>,
+    events: Arc<Mutex<Receiver<HealthEvent>>>,
+}
+
+impl TelemetryWorker {
+    pub const INTERVAL: Duration = Duration::from_secs(60);
+    const RECONNECT_DELAY: Duration = Duration::from_secs(30);
+    const MAX_QUEUED_EVENTS: usize = 64;
+
+    pub fn new(
+        url: impl Into<String>,
+        chains: Vec<ChainSupported>,
+        db_worker: Arc<Mutex<DbWorker>>,
+    ) -> Self {
+        let (event_sender, events) = tokio::sync::mpsc::channel(Self::MAX_QUEUED_EVENTS);
+        Self {
+            url: url.into(),
+            session_id: rand::random(),
+            chains,
+            db_worker,
+            loss_prevention: None,
+            reported_day: Arc::new(Mutex::new(None)),
+            tally: Arc::new(Mutex::new(TxTally::default())),
+            event_sender,
+            events: Arc::new(Mutex::new(events)),
+        }
+    }
+
+    /// also share the loss prevention aggregates of every complete day, values priced with `usd_prices`
+    pub fn with_loss_prevention(mut self, usd_prices: BTreeMap<String, f64>) -> Self {
+        self.loss_prevention = Some(usd_prices);
+        self
+    }
+
+    /// tx counts from the event log, a tx counts once with its latest status. only the events
+    /// recorded since the previous call are read
+    pub async fn stats(&self) -> Result<NodeStats, anyhow::Error> {
+        let mut tally = self.tally.lock().await;
+        let events = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_tx_events_since(tally.read_up_to)
+            .await?;
+        for event in events {
+            tally.read_up_to = tally.read_up_to.max(event.recorded_at);
+            tally.latest.insert(event.tx_id, event.status);
+        }
+        let latest = &tally.latest;
+        let txs_submitted = latest
+            .values()
+            .filter(|status| matches!(status, TxStatus::TxSubmissionPassed(_)))
+            .count() as u64;
+        let txs_failed = latest
+            .values()
+            .filter(|status| {
+                status.is_final() && !matches!(status, TxStatus::TxSubmissionPassed(_))
+            })
+            .count() as u64;
+        let finished = txs_submitted + txs_failed;
+        Ok(NodeStats {
+            txs_initiated: latest.len() as u64,
+            txs_submitted,
+            txs_failed,
+            error_rate: if finished == 0 {
+                0.0
+            } else {
+                txs_failed as f64 / finished as f64
+            },
+        })
+    }
+
+    /// queue a health event for the server, dropped when the queue is full
+    pub fn emit(&self, event: HealthEvent) {
+        if self.event_sender.try_send(event).is_err() {
+            warn!(target: "Telemetry", "telemetry event queue is full, dropping {event:?}");
+        }
+    }
+
+    /// aggregates of the last complete day not handled yet, none when already sent or too few to share
+    async fn loss_prevention_report(&self) -> Result<Option<LossPreventionReport>, anyhow::Error> {
+        let Some(usd_prices) = &self.loss_prevention else {
+            return Ok(None);
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let day = now / SECS_PER_DAY - 1;
+        let mut reported_day = self.reported_day.lock().await;
+        if *reported_day == Some(day) {
+            return Ok(None);
+        }
+        let events = timed_lock(&self.db_worker, "db_worker")
+            .await
+            .get_all_tx_events()
+            .await?;
+        *reported_day = Some(day);
+        Ok(loss_prevention::aggregate(events, day, now, usd_prices))
+    }
+
+    fn encode(&self, payload: Payload) -> Result<Message, anyhow::Error> {
+        let envelope = Envelope {
+            id: self.session_id,
+            ts: SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis(),
+            payload,
+        };
+        Ok(Message::Text(serde_json::to_string(&envelope)?))
+    }
+
+    /// report until the node stops, reconnecting when the server goes away
+    pub async fn run(self) {
+        loop {
+            if let Err(err) = self.report().await {
+                warn!(target: "Telemetry", "telemetry connection to {} lost; caused by: {err}", self.url);
+            }
+            tokio::time::sleep(Self::RECONNECT_DELAY).await;
+        }
+    }
+
+    async fn report(&self) -> Result<(), anyhow::Error> {
+        let (mut socket, _) = tokio_tungstenite::connect_async(self.url.as_str())
+            .await
+            .map_err(|err| anyhow!("failed to connect; caused by: {err}"))?;
+        info!(target: "Telemetry", "reporting to {}", self.url);
+        socket
+            .send(self.encode(Payload::Connected {
+                implementation: "vane",
+                version: env!("CARGO_PKG_VERSION"),
+                chains: &self.chains,
+            })?)
+            .await?;
+
+        let mut interval = tokio::time::interval(Self::INTERVAL);
+        let mut events = self.events.lock().await;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let stats = self.stats().await?;
+                    socket.send(self.encode(Payload::Interval(&stats))?).await?;
+                    if let Some(report) = self.loss_prevention_report().await? {
+                        socket
+                            .send(self.encode(Payload::LossPrevention(&report))?)
+                            .await?;
+                    }
+                }
+                Some(event) = events.recv() => {
+                    socket.send(self.encode(Payload::Health(&event))?).await?;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,388 @@
+// multi tenant hosted mode
+// one process runs a node per tenant, each keeping its db and keystore in a directory of its own,
+// so the account registrations, policies, pending txs and history of tenants never mix. a gateway
+// rpc server resolves the tenant of every request from its api key and forwards the request to the
+// tenant node, whose access control then checks the role of the key. `GET /metrics` on the gateway
+// serves the peer metrics of the tenants labelled with `tenant="<name>"`
+
+use crate::builder::{MainServiceWorkerBuilder, NodeHandle};
+use crate::locks::timed_lock;
+use crate::peer_metrics::PeerMetrics;
+use crate::rpc_middleware::{
+    bearer_key, plain_response, read_body, Role, MAX_REQUEST_BODY_SIZE, MAX_RESPONSE_BODY_SIZE,
+};
+use alloc::sync::Arc;
+use anyhow::anyhow;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use hyper::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE, UPGRADE};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use jsonrpsee::server::{ServerBuilder, ServerHandle};
+use jsonrpsee::RpcModule;
+use log::{info, warn};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use tower::{Layer, Service};
+
+struct Tenant {
+    name: String,
+    api_keys: HashMap<String, Role>,
+    builder: MainServiceWorkerBuilder,
+}
+
+/// node process serving several tenants, each tenant gets a node of its own behind one gateway
+pub struct HostedNode {
+    data_dir: PathBuf,
+    rpc_address: SocketAddr,
+    operator_key: Option<String>,
+    tenants: Vec<Tenant>,
+}
+
+impl HostedNode {
+    /// tenant directories are created in `data_dir`, the gateway listens on `rpc_address`
+    pub fn new(data_dir: impl Into<PathBuf>, rpc_address: SocketAddr) -> Self {
+        Self {
+            data_dir: data_dir.into(),
+            rpc_address,
+            operator_key: None,
+            tenants: vec![],
+        }
+    }
+
+    /// serve the tenant `name` to the holders of `api_keys`, `builder` carries the configuration
+    /// of its node but not its db, keystore or api keys
+    pub fn tenant(
+        mut self,
+        name: impl Into<String>,
+        api_keys: HashMap<String, Role>,
+        builder: MainServiceWorkerBuilder,
+    ) -> Self {
+        self.tenants.push(Tenant {
+            name: name.into(),
+            api_keys,
+            builder,
+        });
+        self
+    }
+
+    /// api key of the operator of the hosted node, the only one scraping the metrics of all tenants
+    pub fn operator_key(mut self, key: impl Into<String>) -> Self {
+        self.operator_key = Some(key.into());
+        self
+    }
+
+    /// start the tenant nodes and the gateway on the current tokio runtime
+    pub async fn start(self) -> Result<HostedHandle, anyhow::Error> {
+        if self.tenants.is_empty() {
+            Err(anyhow!("a hosted node needs at least one tenant"))?
+        }
+        let mut names = HashSet::new();
+        let mut keys = HashMap::new();
+        for tenant in &self.tenants {
+            let valid = tenant
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if tenant.name.is_empty() || !valid {
+                Err(anyhow!(
+                    "invalid tenant name {:?}, use letters, digits, - and _",
+                    tenant.name
+                ))?
+            }
+            if !names.insert(&tenant.name) {
+                Err(anyhow!("tenant {} is configured twice", tenant.name))?
+            }
+            for key in tenant.api_keys.keys() {
+                if keys.insert(key.clone(), tenant.name.clone()).is_some()
+                    || self.operator_key.as_ref() == Some(key)
+                {
+                    Err(anyhow!(
+                        "api keys can not be shared, tenant {} reuses one",
+                        tenant.name
+                    ))?
+                }
+            }
+        }
+
+        let mut nodes = BTreeMap::new();
+        let mut routes = HashMap::new();
+        for tenant in self.tenants {
+            let started = start_tenant(
+                self.data_dir.join(&tenant.name),
+                tenant.api_keys,
+                tenant.builder,
+            );
+            let node = match started.await {
+                Ok(node) => node,
+                Err(err) => {
+                    nodes.values().for_each(NodeHandle::stop);
+                    Err(anyhow!(
+                        "failed to start tenant {}; caused by: {err}",
+                        tenant.name
+                    ))?
+                }
+            };
+            let peer_metrics = timed_lock(&node.worker.tx_rpc_worker, "tx_rpc_worker")
+                .await
+                .peer_metrics
+                .clone();
+            let name = &tenant.name;
+            info!(target: "RpcServer","tenant {name} served by the node at {}", node.rpc_address);
+            routes.insert(
+                tenant.name.clone(),
+                TenantRoute {
+                    url: format!("http://{}", node.rpc_address),
+                    peer_metrics,
+                },
+            );
+            nodes.insert(tenant.name, node);
+        }
+
+        let router = TenantRouter {
+            routes: Arc::new(routes),
+            keys: Arc::new(keys),
+            operator_key: self.operator_key.map(Arc::from),
+            client: reqwest::Client::new(),
+        };
+        let server = ServerBuilder::new()
+            .max_request_body_size(MAX_REQUEST_BODY_SIZE)
+            .max_response_body_size(MAX_RESPONSE_BODY_SIZE)
+            .set_middleware(tower::ServiceBuilder::new().layer(TenantRouterLayer { router }))
+            .build(self.rpc_address)
+            .await?;
+        let rpc_address = server
+            .local_addr()
+            .map_err(|err| anyhow!("failed to get address: {}", err))?;
+        let server = server
+            .start(RpcModule::new(()))
+            .map_err(|err| anyhow!("rpc handler error: {}", err))?;
+        info!(target: "RpcServer","hosted gateway listening to rpc url: {rpc_address}");
+
+        Ok(HostedHandle {
+            rpc_address,
+            tenants: nodes,
+            server,
+        })
+    }
+}
+
+async fn start_tenant(
+    dir: PathBuf,
+    api_keys: HashMap<String, Role>,
+    builder: MainServiceWorkerBuilder,
+) -> Result<NodeHandle, anyhow::Error> {
+    std::fs::create_dir_all(&dir)
+        .map_err(|err| anyhow!("failed to create {}; caused by: {err}", dir.display()))?;
+    builder.tenant(&dir, api_keys)?.build().await?.start().await
+}
+
+/// handle to a node started with `HostedNode::start`
+pub struct HostedHandle {
+    /// address the gateway is listening on
+    pub rpc_address: SocketAddr,
+    /// node of each tenant by name
+    pub tenants: BTreeMap<String, NodeHandle>,
+    server: ServerHandle,
+}
+
+impl HostedHandle {
+    /// stop the gateway and abort the tenant node tasks
+    pub fn stop(&self) {
+        let _ = self.server.stop();
+        self.tenants.values().for_each(NodeHandle::stop);
+    }
+
+    /// wait until the gateway and all tenant node tasks exit
+    pub async fn stopped(self) {
+        self.server.stopped().await;
+        for node in self.tenants.into_values() {
+            node.stopped().await;
+        }
+    }
+}
+
+struct TenantRoute {
+    url: String,
+    peer_metrics: PeerMetrics,
+}
+
+#[derive(Clone)]
+struct TenantRouter {
+    routes: Arc<HashMap<String, TenantRoute>>,
+    /// tenant of each api key
+    keys: Arc<HashMap<String, String>>,
+    operator_key: Option<Arc<str>>,
+    client: reqwest::Client,
+}
+
+impl TenantRouter {
+    async fn route(&self, request: Request<Body>) -> Response<Body> {
+        let key = bearer_key(request.headers());
+        let tenant = key.and_then(|key| self.keys.get(key)).cloned();
+        let is_operator = key.is_some() && key == self.operator_key.as_deref();
+
+        if request.method() == Method::GET && request.uri().path() == "/metrics" {
+            return match tenant {
+                Some(tenant) => metrics_response(self.metrics(&[tenant])),
+                None if is_operator => {
+                    let tenants = self.routes.keys().cloned().collect::<Vec<_>>();
+                    metrics_response(crate::locks::render_metrics() + &self.metrics(&tenants))
+                }
+                None => plain_response(StatusCode::UNAUTHORIZED, "missing or unknown api key"),
+            };
+        }
+        let Some(tenant) = tenant else {
+            warn!(target: "rpc","rejected request without a valid tenant api key");
+            return plain_response(StatusCode::UNAUTHORIZED, "missing or unknown api key");
+        };
+        let is_websocket = request
+            .headers()
+            .get(UPGRADE)
+            .and_then(|upgrade| upgrade.to_str().ok())
+            .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"));
+        if is_websocket || request.method() != Method::POST {
+            return plain_response(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "hosted nodes serve json-rpc over http post only",
+            );
+        }
+        self.forward(&self.routes[&tenant], request)
+            .await
+            .unwrap_or_else(|err| {
+                warn!(target: "rpc","forwarding to tenant {tenant} failed; caused by: {err}");
+                plain_response(StatusCode::BAD_GATEWAY, "tenant node unavailable")
+            })
+    }
+
+    /// pass the request to the tenant node with the api key, its access control checks the role
+    async fn forward(
+        &self,
+        route: &TenantRoute,
+        request: Request<Body>,
+    ) -> Result<Response<Body>, anyhow::Error> {
+        let (parts, body) = request.into_parts();
+        let Some(body) = read_body(body).await else {
+            return Ok(plain_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "request body too large",
+            ));
+        };
+        let mut forwarded = self.client.post(&route.url).body(body);
+        for name in [AUTHORIZATION, CONTENT_TYPE] {
+            if let Some(value) = parts.headers.get(&name).and_then(|v| v.to_str().ok()) {
+                forwarded = forwarded.header(name.as_str(), value);
+            }
+        }
+        let answer = forwarded.send().await?;
+        let status = StatusCode::from_u16(answer.status().as_u16())?;
+        let content_type = answer
+            .headers()
+            .get(CONTENT_TYPE.as_str())
+            .and_then(|value| value.to_str().ok())
+            .map(HeaderValue::from_str)
+            .transpose()?;
+        let mut response = Response::new(Body::from(answer.bytes().await?));
+        *response.status_mut() = status;
+        if let Some(content_type) = content_type {
+            response.headers_mut().insert(CONTENT_TYPE, content_type);
+        }
+        Ok(response)
+    }
+
+    /// peer metrics of `tenants` with a `tenant` label, one family per metric name as prometheus
+    /// wants the samples of a family together under a single HELP and TYPE
+    fn metrics(&self, tenants: &[String]) -> String {
+        let mut families: Vec<(String, Vec<String>, Vec<String>)> = vec![];
+        for tenant in tenants {
+            let rendered = self.routes[tenant].peer_metrics.render_metrics();
+            let mut family = None;
+            for line in rendered.lines() {
+                if let Some(comment) = line.strip_prefix("# ") {
+                    let name = comment.split_whitespace().nth(1).unwrap_or_default();
+                    let index = match families.iter().position(|(family, ..)| family == name) {
+                        Some(index) => index,
+                        None => {
+                            families.push((name.to_string(), vec![], vec![]));
+                            families.len() - 1
+                        }
+                    };
+                    if !families[index].1.iter().any(|header| header == line) {
+                        families[index].1.push(line.to_string());
+                    }
+                    family = Some(index);
+                } else if let Some(index) = family {
+                    families[index].2.push(with_tenant_label(line, tenant));
+                }
+            }
+        }
+        families
+            .into_iter()
+            .flat_map(|(_, headers, samples)| headers.into_iter().chain(samples))
+            .map(|line| line + "\n")
+            .collect()
+    }
+}
+
+/// `name{labels} value` with the `tenant` label first
+fn with_tenant_label(sample: &str, tenant: &str) -> String {
+    match sample.split_once('{') {
+        Some((name, labels)) => format!("{name}{{tenant=\"{tenant}\",{labels}"),
+        None => match sample.split_once(' ') {
+            Some((name, value)) => format!("{name}{{tenant=\"{tenant}\"}} {value}"),
+            None => sample.to_string(),
+        },
+    }
+}
+
+fn metrics_response(metrics: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(metrics));
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; version=0.0.4"),
+    );
+    response
+}
+
+/// tower layer answering every request of the gateway, the rpc module behind it is empty
+#[derive(Clone)]
+struct TenantRouterLayer {
+    router: TenantRouter,
+}
+
+impl<S> Layer<S> for TenantRouterLayer {
+    type Service = TenantRouterService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TenantRouterService {
+            inner,
+            router: self.router.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TenantRouterService<S> {
+    inner: S,
+    router: TenantRouter,
+}
+
+impl<S> Service<Request<Body>> for TenantRouterService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let router = self.router.clone();
+        Box::pin(async move { Ok(router.route(request).await) })
+    }
+}
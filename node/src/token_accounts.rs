@@ -0,0 +1,87 @@
+// token accounts of the solana receivers
+// an spl transfer to a wallet without an associated token account for the mint fails on chain. the
+// sender node looks the account up before the receiver attests and warns the sender, the operator
+// may have the missing accounts created in the transfer tx with the sender paying their rent
+
+use alloy::providers::{Provider, ProviderBuilder, ReqwestProvider};
+use anyhow::anyhow;
+use primitives::data_structure::TxStateMachine;
+use primitives::token_account::{
+    associated_token_address, token_mint, TokenAccountCreation, TOKEN_ACCOUNT_LENGTH,
+};
+use serde::Deserialize;
+
+/// `getAccountInfo` result, `None` for an account that does not exist
+#[derive(Deserialize)]
+struct AccountInfo {
+    value: Option<serde_json::Value>,
+}
+
+#[derive(Clone)]
+pub struct TokenAccounts {
+    provider: ReqwestProvider,
+    /// missing token accounts are created in the transfer tx, warned about only otherwise
+    create_missing: bool,
+}
+
+impl TokenAccounts {
+    pub fn new(rpc_url: &str) -> Result<Self, anyhow::Error> {
+        let rpc_url = rpc_url
+            .parse()
+            .map_err(|err| anyhow!("Solana rpc url parse error: {err}"))?;
+        Ok(Self {
+            provider: ProviderBuilder::new().on_http(rpc_url),
+            create_missing: false,
+        })
+    }
+
+    /// create the missing token accounts of the receivers in the transfer txs
+    pub fn with_creation(mut self) -> Self {
+        self.create_missing = true;
+        self
+    }
+
+    pub fn creates_missing(&self) -> bool {
+        self.create_missing
+    }
+
+    /// token account the receiver of `tx` lacks along its rent, `None` when it exists or the
+    /// transfer is not of an spl token
+    pub async fn missing_account(
+        &self,
+        tx: &TxStateMachine,
+    ) -> Result<Option<TokenAccountCreation>, anyhow::Error> {
+        let Some(mint) = tx.token.and_then(token_mint) else {
+            return Ok(None);
+        };
+        let receiver = tx.receiver_address.to_string();
+        let token_account = associated_token_address(&receiver, mint)?;
+        let config = serde_json::json!({
+            "encoding": "base64",
+            "dataSlice": { "offset": 0, "length": 0 }
+        });
+        let info: AccountInfo = self
+            .provider
+            .raw_request("getAccountInfo".into(), (&token_account, config))
+            .await
+            .map_err(|err| {
+                anyhow!("failed to fetch the receiver token account; caused by: {err}")
+            })?;
+        if info.value.is_some() {
+            return Ok(None);
+        }
+        let rent: u64 = self
+            .provider
+            .raw_request(
+                "getMinimumBalanceForRentExemption".into(),
+                (TOKEN_ACCOUNT_LENGTH,),
+            )
+            .await
+            .map_err(|err| anyhow!("failed to fetch the token account rent; caused by: {err}"))?;
+        Ok(Some(TokenAccountCreation {
+            token_account,
+            mint: mint.into(),
+            rent,
+        }))
+    }
+}
@@ -0,0 +1,89 @@
+// travel rule data sealing
+// the x25519 key a copy is sealed to is the montgomery form of the ed25519 identity of the node,
+// so a sender reaches any receiver from its peer id alone. a node that rotates its identity can no
+// longer open the copies sealed to its previous one
+
+use crate::relay::{decrypt, encrypt, shared_key};
+use anyhow::anyhow;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::MontgomeryPoint;
+use libp2p::identity::{Keypair, PublicKey};
+use libp2p::PeerId;
+use primitives::travel_rule::{SealedTravelRule, TravelRuleAttachment, TravelRuleData};
+use sha2::{Digest, Sha512};
+
+/// x25519 secret of the node with the ed25519 `keypair`
+pub fn encryption_secret(keypair: &Keypair) -> Result<[u8; 32], anyhow::Error> {
+    let keypair = keypair
+        .clone()
+        .try_into_ed25519()
+        .map_err(|err| anyhow!("travel rule data needs an ed25519 identity; caused by: {err}"))?;
+    let hashed = Sha512::digest(keypair.secret().as_ref());
+    let mut secret = [0; 32];
+    secret.copy_from_slice(&hashed[..32]);
+    Ok(secret)
+}
+
+/// x25519 key of the node `peer_id`, recovered from the ed25519 key inlined in it
+pub fn encryption_key(peer_id: &PeerId) -> Result<[u8; 32], anyhow::Error> {
+    let multihash = peer_id.as_ref();
+    if multihash.code() != 0 {
+        Err(anyhow!("{peer_id} does not inline its public key"))?
+    }
+    let public_key = PublicKey::try_decode_protobuf(multihash.digest())
+        .map_err(|err| anyhow!("invalid public key of {peer_id}; caused by: {err}"))?
+        .try_into_ed25519()
+        .map_err(|err| anyhow!("{peer_id} has no ed25519 key; caused by: {err}"))?;
+    let point = CompressedEdwardsY(public_key.to_bytes())
+        .decompress()
+        .ok_or(anyhow!("{peer_id} key is not on the curve"))?;
+    Ok(point.to_montgomery().to_bytes())
+}
+
+/// seal `data` to the x25519 `encryption_key`
+pub fn seal(
+    data: &TravelRuleData,
+    encryption_key: [u8; 32],
+) -> Result<SealedTravelRule, anyhow::Error> {
+    let ephemeral_secret: [u8; 32] = rand::random();
+    let ephemeral_key = MontgomeryPoint::mul_base_clamped(ephemeral_secret).to_bytes();
+    let key = shared_key(
+        ephemeral_secret,
+        encryption_key,
+        ephemeral_key,
+        encryption_key,
+    )?;
+    let (nonce, ciphertext) = encrypt(&key, &serde_json::to_vec(data)?)?;
+    Ok(SealedTravelRule {
+        ephemeral_key,
+        nonce,
+        ciphertext,
+    })
+}
+
+/// `attachment` of the sender node with `keypair`, with the data sealed to the receiver node
+/// `peer_id` as well
+pub fn for_receiver(
+    attachment: &TravelRuleAttachment,
+    keypair: &Keypair,
+    peer_id: &PeerId,
+) -> Result<TravelRuleAttachment, anyhow::Error> {
+    let data = open(encryption_secret(keypair)?, &attachment.sender_copy)?;
+    Ok(TravelRuleAttachment {
+        sender_copy: attachment.sender_copy.clone(),
+        receiver_copy: Some(seal(&data, encryption_key(peer_id)?)?),
+    })
+}
+
+/// open a copy sealed to the x25519 `secret`
+pub fn open(secret: [u8; 32], sealed: &SealedTravelRule) -> Result<TravelRuleData, anyhow::Error> {
+    let encryption_key = MontgomeryPoint::mul_base_clamped(secret).to_bytes();
+    let key = shared_key(
+        secret,
+        sealed.ephemeral_key,
+        sealed.ephemeral_key,
+        encryption_key,
+    )?;
+    let plaintext = decrypt(&key, &sealed.nonce, &sealed.ciphertext)?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
@@ -0,0 +1,239 @@
+// typescript client generation from the OpenRPC description
+// emitted types follow the serde shape of `TxStateMachine` and friends so frontends stay in lockstep
+// regenerate with `app --emit-ts-client <path>` whenever the rpc api changes
+
+use crate::openrpc::{openrpc_document, rpc_methods, MethodDescriptor};
+use serde_json::Value;
+use std::fmt::Write;
+
+/// convert a json schema into a typescript type expression
+fn ts_type(schema: &Value) -> String {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or("unknown")
+            .to_string();
+    }
+    if let Some(variants) = schema.get("oneOf").and_then(Value::as_array) {
+        return variants.iter().map(ts_type).collect::<Vec<_>>().join(" | ");
+    }
+    if let Some(literals) = schema.get("enum").and_then(Value::as_array) {
+        return literals
+            .iter()
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+    if let Some(literal) = schema.get("const") {
+        return literal.to_string();
+    }
+    match schema.get("type") {
+        Some(Value::Array(types)) => types
+            .iter()
+            .map(|ty| ts_type(&serde_json::json!({ "type": ty })))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        Some(Value::String(ty)) => match ty.as_str() {
+            "string" => "string".to_string(),
+            "integer" | "number" => "number".to_string(),
+            "boolean" => "boolean".to_string(),
+            "null" => "null".to_string(),
+            "array" => {
+                if let Some(items) = schema.get("prefixItems").and_then(Value::as_array) {
+                    format!(
+                        "[{}]",
+                        items.iter().map(ts_type).collect::<Vec<_>>().join(", ")
+                    )
+                } else {
+                    let item = schema
+                        .get("items")
+                        .map(ts_type)
+                        .unwrap_or("unknown".to_string());
+                    if item.contains(' ') {
+                        format!("Array<{item}>")
+                    } else {
+                        format!("{item}[]")
+                    }
+                }
+            }
+            "object" => match schema.get("properties").and_then(Value::as_object) {
+                Some(properties) => {
+                    let required = required_fields(schema);
+                    let fields = properties
+                        .iter()
+                        .map(|(name, prop)| {
+                            let optional = if required.contains(&name.as_str()) {
+                                ""
+                            } else {
+                                "?"
+                            };
+                            format!("{name}{optional}: {}", ts_type(prop))
+                        })
+                        .collect::<Vec<_>>()
+                        .join("; ");
+                    format!("{{ {fields} }}")
+                }
+                None => "Record<string, unknown>".to_string(),
+            },
+            _ => "unknown".to_string(),
+        },
+        _ => "unknown".to_string(),
+    }
+}
+
+fn required_fields(schema: &Value) -> Vec<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// `rpc.discover` -> `rpcDiscover`
+fn ts_method_name(rpc_name: &str) -> String {
+    let mut name = String::new();
+    let mut upper = false;
+    for c in rpc_name.chars() {
+        if c == '.' || c == '_' {
+            upper = true;
+        } else if upper {
+            name.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            name.push(c);
+        }
+    }
+    name
+}
+
+fn write_method(out: &mut String, method: &MethodDescriptor) -> std::fmt::Result {
+    let params = method
+        .params
+        .iter()
+        .map(|p| format!("{}: {}", p.name, ts_type(&p.schema)))
+        .collect::<Vec<_>>();
+    let args = method.params.iter().map(|p| p.name).collect::<Vec<_>>();
+    let result = method
+        .result
+        .as_ref()
+        .map(|r| ts_type(&r.schema))
+        .unwrap_or("null".to_string());
+
+    writeln!(out, "  /** {} */", method.summary)?;
+    match method.unsubscribe {
+        Some(unsubscribe) => {
+            let mut sub_params = params.clone();
+            sub_params.push(format!("onItem: (item: {result}) => void"));
+            writeln!(
+                out,
+                "  {}({}): Promise<Unsubscribe> {{",
+                ts_method_name(method.name),
+                sub_params.join(", ")
+            )?;
+            writeln!(
+                out,
+                "    return this.transport.subscribe(\"{}\", \"{unsubscribe}\", [{}], (item) => onItem(item as {result}));",
+                method.name,
+                args.join(", ")
+            )?;
+        }
+        None => {
+            writeln!(
+                out,
+                "  async {}({}): Promise<{result}> {{",
+                ts_method_name(method.name),
+                params.join(", ")
+            )?;
+            writeln!(
+                out,
+                "    return (await this.transport.request(\"{}\", [{}])) as {result};",
+                method.name,
+                args.join(", ")
+            )?;
+        }
+    }
+    writeln!(out, "  }}")?;
+    writeln!(out)
+}
+
+/// generate a self contained typescript module with the api types, error codes and a transport agnostic client
+pub fn generate_ts_client() -> Result<String, anyhow::Error> {
+    let document = openrpc_document();
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "// generated from the vane node OpenRPC document, do not edit"
+    )?;
+    writeln!(
+        out,
+        "// node version: {}",
+        document["info"]["version"].as_str().unwrap_or_default()
+    )?;
+    writeln!(out)?;
+
+    // ------------------------------------- types ------------------------------------------- //
+    if let Some(schemas) = document["components"]["schemas"].as_object() {
+        for (name, schema) in schemas {
+            writeln!(out, "export type {name} = {};", ts_type(schema))?;
+            writeln!(out)?;
+        }
+    }
+
+    // ------------------------------------- errors ------------------------------------------ //
+    writeln!(out, "export const VaneRpcErrorCode = {{")?;
+    if let Some(errors) = document["components"]["errors"].as_object() {
+        for error in errors.values() {
+            writeln!(
+                out,
+                "  {}: {},",
+                error["message"].as_str().unwrap_or_default(),
+                error["code"]
+            )?;
+        }
+    }
+    writeln!(out, "}} as const;")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "export type VaneRpcErrorKind = keyof typeof VaneRpcErrorCode;"
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "export interface VaneRpcErrorData {{ kind: VaneRpcErrorKind; [field: string]: unknown }}"
+    )?;
+    writeln!(out)?;
+
+    // ------------------------------------- client ------------------------------------------ //
+    writeln!(out, "export type Unsubscribe = () => Promise<void>;")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "/** json-rpc transport, implement over websocket or http */"
+    )?;
+    writeln!(out, "export interface VaneTransport {{")?;
+    writeln!(
+        out,
+        "  request(method: string, params: unknown[]): Promise<unknown>;"
+    )?;
+    writeln!(
+        out,
+        "  subscribe(method: string, unsubscribe: string, params: unknown[], onItem: (item: unknown) => void): Promise<Unsubscribe>;"
+    )?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(out, "export class VaneClient {{")?;
+    writeln!(
+        out,
+        "  constructor(private readonly transport: VaneTransport) {{}}"
+    )?;
+    writeln!(out)?;
+    for method in rpc_methods() {
+        write_method(&mut out, &method)?;
+    }
+    writeln!(out, "}}")?;
+
+    Ok(out)
+}
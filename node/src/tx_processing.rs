@@ -6,31 +6,35 @@
 
 extern crate alloc;
 
+use crate::calldata::{contract_call, evm_address};
+use crate::chain::ChainClient;
+use crate::typed_tx::{is_raw_tx, SignedEvmTx};
 use alloc::sync::Arc;
-use alloy::consensus::{SignableTransaction, TxEip7702, TypedTransaction};
-use alloy::network::TransactionBuilder;
-use alloy::primitives::private::alloy_rlp::{Decodable, Encodable};
-use alloy::primitives::{keccak256, U256};
-use alloy::primitives::{Address, Signature as EcdsaSignature, Signature, SignatureError, B256};
-use alloy::providers::{Provider, ProviderBuilder, ReqwestProvider};
-use alloy::rpc::types::TransactionRequest;
-use alloy::signers::k256::sha2::digest::Mac;
+use alloy::primitives::{Signature as EcdsaSignature, B256};
 use anyhow::anyhow;
-use core::str::FromStr;
 use log::error;
 use primitives::data_structure::{ChainSupported, TxStateMachine, ETH_SIG_MSG_PREFIX};
+use primitives::explorer::ChainReceipt;
+use primitives::tx_builder::verify_multi_id;
+use primitives::tx_state::{NetConfirmedTx, SenderConfirmedTx};
 use sp_core::{
     ed25519::{Public as EdPublic, Signature as EdSignature},
-    keccak_256, Blake2Hasher, Hasher,
+    keccak_256,
 };
 use sp_core::{ByteArray, H256};
 use sp_runtime::traits::Verify;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
 use tokio::sync::mpsc::Receiver;
 use tokio::sync::Mutex;
 
 // use solana_client::rpc_client::RpcClient;
 
+/// interval between two receipt polls of a submitted tx
+pub const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(6);
+/// receipt polls before a submitted tx is left without one, about ten minutes
+pub const RECEIPT_POLLS: u32 = 100;
+
 /// handling tx processing, updating tx state machine, updating db and tx chain simulation processing
 /// & tx submission to specified and confirmed chain
 #[derive(Clone)]
@@ -41,45 +45,21 @@ pub struct TxProcessingWorker {
     pub sender_tx_pending: Arc<Mutex<Vec<TxStateMachine>>>,
     /// In-memory Db for to be confirmed tx on receiver
     pub receiver_tx_pending: Arc<Mutex<Vec<TxStateMachine>>>,
-    // /// substrate client
-    // sub_client: OnlineClient<PolkadotConfig>,
-    /// ethereum & bnb client
-    eth_client: ReqwestProvider,
-    bnb_client: ReqwestProvider,
-    // solana_client: RpcClient
+    /// chain clients per network, txs are created and submitted through them
+    chain_clients: HashMap<ChainSupported, Arc<dyn ChainClient>>,
 }
 
 impl TxProcessingWorker {
-    pub async fn new(
-        chain_networks: (ChainSupported, ChainSupported, ChainSupported),
-    ) -> Result<Self, anyhow::Error> {
-        let (_solana, eth, bnb) = chain_networks;
-        //let polkadot_url = polkadot.url();
-        let eth_url = eth.url();
-        let bnb_url = bnb.url().to_string();
-
-        // let sub_client = OnlineClient::from_url(polkadot_url)
-        //     .await
-        //     .map_err(|_| anyhow!("failed to connect polkadot url"))?;
-        let eth_rpc_url = eth_url
-            .parse()
-            .map_err(|err| anyhow!("eth rpc parse error: {err}"))?;
-        // Create a provider with the HTTP transport using the `reqwest` crate.
-        let eth_provider = ProviderBuilder::new().on_http(eth_rpc_url);
-
-        let bnb_rpc_url = bnb_url
-            .parse()
-            .map_err(|err| anyhow!("bnb rpc url parse error: {err}"))?;
-        let bnb_provider = ProviderBuilder::new().on_http(bnb_rpc_url);
-
-        Ok(Self {
+    pub fn new(chain_clients: impl IntoIterator<Item = Arc<dyn ChainClient>>) -> Self {
+        Self {
             tx_staging: Arc::new(Default::default()),
             sender_tx_pending: Arc::new(Default::default()),
             receiver_tx_pending: Arc::new(Default::default()),
-            //sub_client,
-            eth_client: eth_provider,
-            bnb_client: bnb_provider,
-        })
+            chain_clients: chain_clients
+                .into_iter()
+                .map(|client| (client.network(), client))
+                .collect(),
+        }
     }
     /// cryptographically verify the receiver address, validity and address ownership on receiver's end
     pub fn validate_receiver_sender_address(
@@ -96,8 +76,9 @@ impl TxProcessingWorker {
                 .recv_signature
                 .ok_or(anyhow!("receiver didnt signed"))?;
 
-            let recv_address = tx.receiver_address.clone();
-            let msg = tx.receiver_address.as_bytes().to_vec();
+            let recv_address = tx.receiver_address;
+            // receivers sign the canonical text of their address, the one wallets display
+            let msg = tx.receiver_address.to_string().into_bytes();
 
             (network, signature, msg, recv_address)
         } else {
@@ -112,7 +93,7 @@ impl TxProcessingWorker {
             let msg = tx
                 .call_payload
                 .expect("unexpected error, call payload should be available");
-            let sender_address = tx.sender_address.clone();
+            let sender_address = tx.sender_address;
 
             (network, signature, msg.to_vec(), sender_address)
         };
@@ -131,8 +112,16 @@ impl TxProcessingWorker {
                 // }
                 todo!()
             }
-            ChainSupported::Ethereum => {
-                let address: Address = address.parse().expect("Invalid address");
+            // both evm networks recover the same secp256k1 signatures
+            ChainSupported::Ethereum | ChainSupported::Bnb => {
+                let address = evm_address(&address)?;
+                // wallets signing the whole tx, its content is checked on submission
+                if who != "Receiver" && is_raw_tx(&signature) {
+                    if SignedEvmTx::decode(&signature)?.signer != address {
+                        Err(anyhow!("the signed tx is not signed by the sender"))?
+                    }
+                    return Ok(());
+                }
 
                 let hashed_msg = {
                     if who == "Receiver" {
@@ -166,11 +155,8 @@ impl TxProcessingWorker {
                     Err(err) => Err(anyhow!("ec signature verification failed: {err}"))?,
                 }
             }
-            ChainSupported::Bnb => {
-                todo!()
-            }
             ChainSupported::Solana => {
-                let ed_receiver_public = EdPublic::from_str(&tx.receiver_address)
+                let ed_receiver_public = EdPublic::try_from(tx.receiver_address.as_bytes())
                     .map_err(|_| anyhow!("failed to convert ed25519 recv addr bytes"))?;
                 let sig = EdSignature::from_slice(&signature[..])
                     .map_err(|_| anyhow!("failed to convert ed25519_signature"))?;
@@ -187,14 +173,22 @@ impl TxProcessingWorker {
         Ok(())
     }
 
-    pub fn validate_multi_id(&self, txn: &TxStateMachine) -> bool {
-        let post_multi_id = {
-            let mut sender_recv = txn.sender_address.as_bytes().to_vec();
-            sender_recv.extend_from_slice(txn.receiver_address.as_bytes());
-            Blake2Hasher::hash(&sender_recv[..])
-        };
+    /// verify the sender signed the call payload, the signature of a smart account sender follows
+    /// the validation scheme of its account and is checked by the account on submission
+    pub async fn validate_sender(&self, tx: &TxStateMachine) -> Result<(), anyhow::Error> {
+        if let Some(call_payload) = tx.call_payload {
+            let chain_client = self.chain_client(tx.network)?;
+            if chain_client.validated_by_account(call_payload).await {
+                return Ok(());
+            }
+        }
+        self.validate_receiver_sender_address(tx, "Sender")
+    }
 
-        post_multi_id == txn.multi_id
+    /// check the multi id binds the tx, txs of outdated clients are refused as such
+    pub fn validate_multi_id(&self, txn: &TxStateMachine) -> Result<(), anyhow::Error> {
+        verify_multi_id(txn)
+            .map_err(|err| anyhow!("multi id verification failed; caused by: {err}"))
     }
 
     /// simulate the recipient blockchain network for mitigating errors resulting to wrong network selection
@@ -202,214 +196,55 @@ impl TxProcessingWorker {
         Ok(())
     }
 
-    /// create the tx to be signed by externally owned account
-    pub async fn create_tx(&mut self, tx: &mut TxStateMachine) -> Result<(), anyhow::Error> {
-        let network = tx.network;
-        let to_signed_bytes = match network {
-            ChainSupported::Polkadot => {
-                // let transfer_value = dynamic::Value::primitive(U128(tx.data.amount as u128));
-                // let to_address = dynamic::Value::from_bytes(tx.data.receiver_address);
-                //
-                // // construct a dynamic extrinsic payload
-                // let extrinsic = dynamic(
-                //     "Balances",
-                //     "transferKeepAlive",
-                //     Named(vec![
-                //         ("dest".to_string(), to_address),
-                //         ("value".to_string(), transfer_value),
-                //     ]),
-                // );
-                // let ext_params = DefaultExtrinsicParamsBuilder::<PolkadotConfig>::new().build();
-                // let partial_ext = self
-                //     .sub_client
-                //     .tx()
-                //     .create_partial_signed_offline(&extrinsic, ext_params)
-                //     .map_err(|err| anyhow!("failed to create partial ext; caused by: {err:?}"))?;
-                // partial_ext.signer_payload()
-                todo!()
-            }
-
-            ChainSupported::Ethereum => {
-                let from_address: Address = tx.sender_address.parse().expect("Invalid address");
-                let to_address: Address = tx.receiver_address.parse().expect("Invalid address");
-                let value = U256::from(tx.amount);
-
-                // TODO upgrade to EIP7702
-                let tx_builder = TransactionRequest::default()
-                    .with_from(from_address)
-                    .with_to(to_address)
-                    .with_value(value)
-                    .with_nonce(0)
-                    .with_chain_id(56)
-                    .with_gas_limit(21_000)
-                    .with_max_priority_fee_per_gas(1_000_000_000)
-                    .with_max_fee_per_gas(20_000_000_000)
-                    .build_unsigned()
-                    .map_err(|err| {
-                        anyhow!("cannot build unsigned tx to be signed by EOA; caused by: {err:?}")
-                    })?;
-
-                let signing_hash = tx_builder
-                    .eip1559()
-                    .ok_or(anyhow!("failed to convert to EIP 7702"))?
-                    .signature_hash();
-
-                tx.call_payload = Some(<[u8; 32]>::from(signing_hash));
-            }
-
-            ChainSupported::Bnb => {
-                let to_address = Address::from_slice(&tx.receiver_address.as_bytes());
-                let value = U256::from(tx.amount);
-
-                let tx_builder = alloy::rpc::types::TransactionRequest::default()
-                    .with_to(to_address)
-                    .with_value(value)
-                    .with_chain_id(56)
-                    .build_unsigned()
-                    .map_err(|err| {
-                        anyhow!("cannot build unsigned tx to be signed by EOA; caused by: {err:?}")
-                    })?;
-
-                let signing_hash = tx_builder
-                    .eip7702()
-                    .ok_or(anyhow!("failed to convert to EIP 7702"))?
-                    .signature_hash();
-
-                tx.call_payload = Some(<[u8; 32]>::from(signing_hash));
-            }
+    /// client registered for the network
+    fn chain_client(&self, network: ChainSupported) -> Result<Arc<dyn ChainClient>, anyhow::Error> {
+        self.chain_clients
+            .get(&network)
+            .cloned()
+            .ok_or(anyhow!("no chain client configured for {network:?}"))
+    }
 
-            ChainSupported::Solana => {
-                todo!()
-            }
-        };
+    /// create the tx to be signed by externally owned account
+    pub async fn create_tx(&mut self, tx: &mut NetConfirmedTx) -> Result<(), anyhow::Error> {
+        // contract token transfers and approvals are calls to the token contract
+        if let Some((call_data, intent)) = contract_call(tx)? {
+            tx.set_contract_call(call_data, intent);
+        }
+        let chain_client = self.chain_client(tx.network)?;
+        let signing_hash = chain_client.create_tx(tx).await?;
+        tx.set_call_payload(signing_hash);
+        tx.set_sponsorship(chain_client.sponsorship(signing_hash).await);
         Ok(())
     }
 
     /// submit the externally signed tx, returns tx hash
-    pub async fn submit_tx(&mut self, tx: TxStateMachine) -> Result<[u8; 32], anyhow::Error> {
-        let network = tx.network;
-
-        let block_hash = match network {
-            ChainSupported::Polkadot => {
-                // let signature_payload = MultiSignature::Sr25519(<[u8; 64]>::from(
-                //     SrSignature::from_slice(
-                //         &tx.data
-                //             .signed_call_payload
-                //             .ok_or(anyhow!("call payload not signed"))?,
-                //     )
-                //     .map_err(|_| anyhow!("failed to convert sr signature"))?,
-                // ));
-                // let sender = MultiAddress::Address32(
-                //     SrPublic::from_slice(&tx.data.sender_address)
-                //         .map_err(|_| anyhow!("failed to convert acc id"))?
-                //         .0,
-                // );
-                //
-                // let transfer_value = dynamic::Value::primitive(U128(tx.data.amount as u128));
-                // let extrinsic = dynamic(
-                //     "Balances",
-                //     "transferKeepAlive",
-                //     Named(vec![("dest".to_string(), transfer_value)]),
-                // );
-                // let ext_params = DefaultExtrinsicParamsBuilder::<PolkadotConfig>::new().build();
-                // let partial_ext = self
-                //     .sub_client
-                //     .tx()
-                //     .create_partial_signed_offline(&extrinsic, ext_params)
-                //     .map_err(|_| anyhow!("failed to create partial ext"))?;
-                //
-                // let submittable_extrinsic =
-                //     partial_ext.sign_with_address_and_signature(&sender, &signature_payload.into());
-                //
-                // let tx_hash = submittable_extrinsic
-                //     .submit_and_watch()
-                //     .await?
-                //     .wait_for_finalized()
-                //     .await?
-                //     .block_hash()
-                //     .tx_hash();
-                //
-                // tx_hash
-                //     .to_vec()
-                //     .try_into()
-                //     .map_err(|err| anyhow!("failed to convert to 32 bytes array"))
-                todo!()
-            }
-            ChainSupported::Ethereum => {
-                let signature = tx
-                    .signed_call_payload
-                    .ok_or(anyhow!("sender did not signed the tx payload"))?;
-                let signature = Signature::try_from(signature.as_slice())
-                    .map_err(|err| anyhow!("failed to parse signature: {err}"))?;
-
-                let to_address: Address = tx.receiver_address.parse().expect("Invalid address");
-                let value = U256::from(tx.amount);
-
-                let tx_builder = TransactionRequest::default()
-                    .with_to(to_address)
-                    .with_value(value)
-                    .with_chain_id(56)
-                    .build_unsigned()
-                    .map_err(|err| {
-                        anyhow!("cannot build unsigned tx to be signed by EOA; caused by: {err:?}")
-                    })?
-                    .eip7702()
-                    .ok_or(anyhow!("failed to convert txn to eip7702"))?
-                    .clone();
-
-                let signed_tx = tx_builder.into_signed(signature);
-
-                let to_submit_tx: TransactionRequest = signed_tx.tx().clone().into();
-                let receipt = self
-                    .eth_client
-                    .send_transaction(to_submit_tx)
-                    .await
-                    .map_err(|err| anyhow!("failed to submit eth raw tx; caused by :{err}"))?
-                    .tx_hash()
-                    .clone();
+    pub async fn submit_tx(&mut self, tx: &SenderConfirmedTx) -> Result<[u8; 32], anyhow::Error> {
+        self.chain_client(tx.network)?.submit_tx(tx).await
+    }
 
-                receipt.to_vec().try_into().map_err(|err| {
-                    anyhow!("failed to convert to 32 bytes array; caused by: {err:?}")
-                })?
+    /// receipt of a submitted tx, polled every `RECEIPT_POLL_INTERVAL` until the tx is included or
+    /// `RECEIPT_POLLS` polls went unanswered
+    pub async fn await_receipt(
+        &self,
+        network: ChainSupported,
+        tx_hash: [u8; 32],
+    ) -> Result<Option<ChainReceipt>, anyhow::Error> {
+        let chain_client = self.chain_client(network)?;
+        for _ in 0..RECEIPT_POLLS {
+            if let Some(receipt) = chain_client.receipt(tx_hash).await? {
+                return Ok(Some(receipt));
             }
-            ChainSupported::Bnb => {
-                todo!();
-                let signature = tx
-                    .signed_call_payload
-                    .ok_or(anyhow!("sender did not signed the tx payload"))?;
-                let tx_payload = tx.call_payload.ok_or(anyhow!("call payload not found"))?;
-                let decoded_tx = TxEip7702::decode(&mut &tx_payload[..]).map_err(|err| {
-                    anyhow!("failed to decode eth EIP7702 tx payload; caused by: {err:?}")
-                })?;
-
-                let signed_tx =
-                    decoded_tx.into_signed(signature.as_slice().try_into().map_err(|err| {
-                        anyhow!("failed to decode tx siganture; caused by: {err}")
-                    })?);
-
-                let mut encoded_signed_tx = vec![];
-                signed_tx.tx().encode_with_signature(
-                    signed_tx.signature(),
-                    &mut encoded_signed_tx,
-                    false,
-                );
-
-                let receipt = self
-                    .bnb_client
-                    .send_raw_transaction(&encoded_signed_tx)
-                    .await
-                    .map_err(|err| anyhow!("failed to submit eth raw tx; caused by: {err}"))?
-                    .tx_hash()
-                    .clone();
+            tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+        }
+        Ok(None)
+    }
 
-                receipt.to_vec().try_into().map_err(|err| {
-                    anyhow!("failed to convert to 32 bytes array; caused by: {err:?}")
-                })?
-            }
-            ChainSupported::Solana => {
-                todo!()
-            }
-        };
-        Ok(block_hash)
+    /// fee the tx of `receipt` would have paid at the low tier of its block, see `FeeStats`
+    pub async fn low_tier_fee(
+        &self,
+        network: ChainSupported,
+        receipt: &ChainReceipt,
+    ) -> Result<Option<u128>, anyhow::Error> {
+        self.chain_client(network)?.low_tier_fee(receipt).await
     }
 }
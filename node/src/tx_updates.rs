@@ -0,0 +1,132 @@
+// tx update fan-out
+// the tx updates the node sends to its users are fanned out to every open subscription instead of
+// going to whichever subscriber polls first, and each subscription only sees the txs of the
+// accounts its api key is scoped to, so the sessions of a multi-account node never see each
+// other's transfers. a subscriber falling behind skips updates rather than holding the others back,
+// updates sent while nobody subscribes are only found through `fetchPendingTxUpdates`
+
+use alloc::sync::Arc;
+use log::warn;
+use primitives::address::VaneAddress;
+use primitives::data_structure::{DbTxStateMachine, TxStateMachine};
+use std::collections::HashSet;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::Mutex;
+
+/// updates buffered per subscription before a slow one skips some
+const SUBSCRIPTION_CAPACITY: usize = 256;
+
+/// accounts a session sees the txs of, all of them unless the api key is scoped
+#[derive(Clone, Debug, Default)]
+pub struct AccountScope {
+    /// canonical addresses, none for every account
+    accounts: Option<Arc<HashSet<String>>>,
+}
+
+impl AccountScope {
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// txs sent from or to `accounts` only
+    pub fn accounts(accounts: impl IntoIterator<Item = String>) -> Self {
+        let accounts = accounts
+            .into_iter()
+            .map(|account| canonical(&account))
+            .collect();
+        Self {
+            accounts: Some(Arc::new(accounts)),
+        }
+    }
+
+    pub fn is_scoped(&self) -> bool {
+        self.accounts.is_some()
+    }
+
+    pub fn allows(&self, tx: &TxStateMachine) -> bool {
+        match &self.accounts {
+            None => true,
+            Some(accounts) => {
+                accounts.contains(&tx.sender_address.to_string())
+                    || accounts.contains(&tx.receiver_address.to_string())
+            }
+        }
+    }
+
+    /// history records keep the addresses as they were sent, compared in canonical form
+    pub fn allows_record(&self, record: &DbTxStateMachine) -> bool {
+        match &self.accounts {
+            None => true,
+            Some(accounts) => {
+                accounts.contains(&canonical(&record.sender_address))
+                    || accounts.contains(&canonical(&record.receiver_address))
+            }
+        }
+    }
+}
+
+fn canonical(account: &str) -> String {
+    match VaneAddress::parse(account) {
+        Ok(address) => address.to_string(),
+        Err(_) => account.to_string(),
+    }
+}
+
+/// the tx updates of the node, drained from the rpc channel by `run` and fanned out
+#[derive(Clone)]
+pub struct TxUpdates {
+    source: Arc<Mutex<Receiver<TxStateMachine>>>,
+    fanout: broadcast::Sender<TxStateMachine>,
+}
+
+impl TxUpdates {
+    pub fn new(source: Arc<Mutex<Receiver<TxStateMachine>>>) -> Self {
+        let (fanout, _) = broadcast::channel(SUBSCRIPTION_CAPACITY);
+        Self { source, fanout }
+    }
+
+    /// updates of the txs in `scope` from now on
+    pub fn subscribe(&self, scope: AccountScope) -> TxUpdateSubscription {
+        TxUpdateSubscription {
+            updates: self.fanout.subscribe(),
+            scope,
+        }
+    }
+
+    /// fan out an update not going through the rpc channel
+    pub fn publish(&self, tx: TxStateMachine) {
+        // without subscribers the update is dropped
+        let _ = self.fanout.send(tx);
+    }
+
+    /// fan the updates out until the node stops
+    pub async fn run(self) {
+        let mut source = self.source.lock().await;
+        while let Some(tx) = source.recv().await {
+            // without subscribers the update is dropped
+            let _ = self.fanout.send(tx);
+        }
+    }
+}
+
+pub struct TxUpdateSubscription {
+    updates: broadcast::Receiver<TxStateMachine>,
+    scope: AccountScope,
+}
+
+impl TxUpdateSubscription {
+    /// next update in the scope, none once the node stopped
+    pub async fn next(&mut self) -> Option<TxStateMachine> {
+        loop {
+            match self.updates.recv().await {
+                Ok(tx) if self.scope.allows(&tx) => return Some(tx),
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(target: "rpc", "tx update subscriber fell behind, skipped {skipped} updates")
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
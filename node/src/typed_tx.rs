@@ -0,0 +1,168 @@
+// typed evm txs signed by the wallet of the sender
+// the sender signs the call payload of the tx the node built, a 65 bytes signature, or wallets only
+// exposing `eth_signTransaction` hand back the whole eip-2718 encoded tx. legacy, access list and
+// dynamic fee txs are accepted, blob txs and the types vane does not know are refused by their type
+// byte with a clear error rather than failing somewhere in the rlp decoding
+
+use alloy::consensus::{SignableTransaction, Signed, TxEip1559, TxEnvelope};
+use alloy::eips::eip2718::Decodable2718;
+use alloy::primitives::{Address, Bytes, Signature, TxKind, U256};
+use anyhow::anyhow;
+
+/// length of the signature of a call payload, longer payloads are raw txs
+pub const SIGNATURE_LEN: usize = 65;
+/// rlp list of a pre eip-2718 tx
+pub const LEGACY_TX_TYPE: u8 = 0x00;
+/// eip-2930 access list tx
+pub const EIP2930_TX_TYPE: u8 = 0x01;
+/// eip-1559 dynamic fee tx, the type the node builds
+pub const EIP1559_TX_TYPE: u8 = 0x02;
+/// eip-4844 blob tx
+pub const EIP4844_TX_TYPE: u8 = 0x03;
+/// eip-7702 set code tx
+pub const EIP7702_TX_TYPE: u8 = 0x04;
+
+/// the sender handed back the whole signed tx rather than the signature of the call payload
+pub fn is_raw_tx(signed_call_payload: &[u8]) -> bool {
+    signed_call_payload.len() != SIGNATURE_LEN
+}
+
+/// eip-2718 type of `raw`, legacy txs are rlp lists starting at 0xc0
+pub fn tx_type(raw: &[u8]) -> Result<u8, anyhow::Error> {
+    match raw.first() {
+        None => Err(anyhow!("the signed tx is empty")),
+        Some(byte) if *byte >= 0xc0 => Ok(LEGACY_TX_TYPE),
+        Some(byte) if *byte <= 0x7f => Ok(*byte),
+        Some(byte) => Err(anyhow!(
+            "the signed tx starts with 0x{byte:02x}, neither a tx type nor an rlp list"
+        )),
+    }
+}
+
+/// fields of a tx signed by the wallet of the sender checked against the tx the node built
+#[derive(Clone, Debug)]
+pub struct SignedEvmTx {
+    pub tx_type: u8,
+    /// `None` for legacy txs without replay protection
+    pub chain_id: Option<u64>,
+    pub nonce: u64,
+    pub to: TxKind,
+    pub value: U256,
+    pub input: Bytes,
+    /// gas price of legacy and access list txs
+    pub max_fee_per_gas: u128,
+    pub signer: Address,
+    /// eip-2718 encoding, broadcast as the wallet signed it
+    pub raw: Vec<u8>,
+}
+
+impl SignedEvmTx {
+    pub fn decode(raw: &[u8]) -> Result<Self, anyhow::Error> {
+        let tx_type = tx_type(raw)?;
+        match tx_type {
+            LEGACY_TX_TYPE | EIP2930_TX_TYPE | EIP1559_TX_TYPE => {}
+            EIP4844_TX_TYPE => Err(anyhow!(
+                "blob txs (type 0x03) are not supported, sign the transfer as a type 0x02 tx"
+            ))?,
+            EIP7702_TX_TYPE => Err(anyhow!(
+                "set code txs (type 0x04) are not supported, sign the transfer as a type 0x02 tx"
+            ))?,
+            other => Err(anyhow!(
+                "unknown tx type 0x{other:02x}, sign the transfer as a type 0x02 tx"
+            ))?,
+        }
+        let envelope = TxEnvelope::decode_2718(&mut &raw[..]).map_err(|err| {
+            anyhow!("failed to decode the type 0x{tx_type:02x} signed tx; caused by: {err}")
+        })?;
+        let (chain_id, nonce, to, value, input, max_fee_per_gas, signer) = match &envelope {
+            TxEnvelope::Legacy(signed) => {
+                let tx = signed.tx();
+                (
+                    tx.chain_id,
+                    tx.nonce,
+                    tx.to,
+                    tx.value,
+                    tx.input.clone(),
+                    tx.gas_price,
+                    signer(signed)?,
+                )
+            }
+            TxEnvelope::Eip2930(signed) => {
+                let tx = signed.tx();
+                (
+                    Some(tx.chain_id),
+                    tx.nonce,
+                    tx.to,
+                    tx.value,
+                    tx.input.clone(),
+                    tx.gas_price,
+                    signer(signed)?,
+                )
+            }
+            TxEnvelope::Eip1559(signed) => {
+                let tx = signed.tx();
+                (
+                    Some(tx.chain_id),
+                    tx.nonce,
+                    tx.to,
+                    tx.value,
+                    tx.input.clone(),
+                    tx.max_fee_per_gas,
+                    signer(signed)?,
+                )
+            }
+            _ => Err(anyhow!("unsupported tx type 0x{tx_type:02x}"))?,
+        };
+        Ok(Self {
+            tx_type,
+            chain_id,
+            nonce,
+            to,
+            value,
+            input,
+            max_fee_per_gas,
+            signer,
+            raw: raw.to_vec(),
+        })
+    }
+
+    /// the wallet signed the transfer the node built, it only picks the tx type, nonce and fees
+    pub fn ensure_matches(
+        &self,
+        unsigned_tx: &TxEip1559,
+        max_fee_cap: Option<u128>,
+    ) -> Result<(), anyhow::Error> {
+        match self.chain_id {
+            None => Err(anyhow!(
+                "the signed tx has no chain id, it could be replayed on every evm chain"
+            ))?,
+            Some(chain_id) if chain_id != unsigned_tx.chain_id => Err(anyhow!(
+                "the signed tx is for chain {chain_id}, the transfer for chain {}",
+                unsigned_tx.chain_id
+            ))?,
+            Some(_) => {}
+        }
+        if self.to != unsigned_tx.to
+            || self.value != unsigned_tx.value
+            || self.input != unsigned_tx.input
+        {
+            Err(anyhow!(
+                "the signed tx does not send the transfer the receiver confirmed"
+            ))?
+        }
+        if let Some(cap) = max_fee_cap.filter(|cap| self.max_fee_per_gas > *cap) {
+            Err(anyhow!(
+                "the signed tx pays up to {} wei per gas, above the cap of {cap}",
+                self.max_fee_per_gas
+            ))?
+        }
+        Ok(())
+    }
+}
+
+fn signer<T: SignableTransaction<Signature>>(signed: &Signed<T>) -> Result<Address, anyhow::Error> {
+    signed
+        .signature()
+        .recover_address_from_prehash(&signed.signature_hash())
+        .map_err(|err| anyhow!("failed to recover the signer of the signed tx: {err}"))
+}
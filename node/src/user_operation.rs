@@ -0,0 +1,315 @@
+// erc-4337 user operations of smart account senders
+// a sender whose account is a contract does not sign a tx, the node builds a user operation calling
+// `execute` on the account, the owner signs its hash following the validation scheme of the account
+// and a bundler submits it through the entry point. the account checks the signature itself when the
+// bundler simulates the operation, the node can not for a scheme it does not know. a paymaster may
+// pay the gas of the operation instead of the account, the node asks an erc-7677 paymaster service
+// for its data while building the operation
+
+use alloy::primitives::{address, hex, keccak256, Address, Bytes, B256, U256, U64};
+use alloy::providers::{Provider, ProviderBuilder, ReqwestProvider};
+use alloy::sol;
+use alloy::sol_types::SolValue;
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+sol! {
+    interface ISmartAccount {
+        function execute(address dest, uint256 value, bytes func) external;
+    }
+
+    interface IEntryPoint {
+        function getNonce(address sender, uint192 key) external view returns (uint256);
+    }
+}
+
+/// entry point v0.7, deployed at the same address on every evm chain
+pub const ENTRY_POINT: Address = address!("0000000071727De22E5E9d8BAf0edAc6f37da032");
+/// signature the gas of an operation is estimated with, accounts validate it without reverting
+pub const DUMMY_SIGNATURE: [u8; 65] = hex!(
+    "fffffffffffffffffffffffffffffff0000000000000000000000000000000007aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa1c"
+);
+
+/// user operation of the entry point v0.7 as bundlers take it over json rpc, smart accounts are
+/// deployed beforehand, without factory, and pay their own gas unless a paymaster sponsors it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub call_data: Bytes,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_verification_gas_limit: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_post_op_gas_limit: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_data: Option<Bytes>,
+    pub signature: Bytes,
+}
+
+impl UserOperation {
+    /// hash the owner of the account signs, over the packed operation, entry point and chain
+    pub fn hash(&self, entry_point: Address, chain_id: u64) -> B256 {
+        let packed = (
+            self.sender,
+            self.nonce,
+            // no init code
+            keccak256([]),
+            keccak256(&self.call_data),
+            pack(self.verification_gas_limit, self.call_gas_limit),
+            self.pre_verification_gas,
+            pack(self.max_priority_fee_per_gas, self.max_fee_per_gas),
+            keccak256(self.paymaster_and_data()),
+        )
+            .abi_encode();
+        keccak256((keccak256(packed), entry_point, U256::from(chain_id)).abi_encode())
+    }
+
+    /// paymaster, its gas limits and data packed as the entry point hashes them, empty without one
+    fn paymaster_and_data(&self) -> Vec<u8> {
+        let Some(paymaster) = self.paymaster else {
+            return Vec::new();
+        };
+        let gas_limit = |limit: Option<U256>| limit.unwrap_or_default().saturating_to::<u128>();
+        [
+            paymaster.as_slice(),
+            &gas_limit(self.paymaster_verification_gas_limit).to_be_bytes(),
+            &gas_limit(self.paymaster_post_op_gas_limit).to_be_bytes(),
+            self.paymaster_data.as_deref().unwrap_or_default(),
+        ]
+        .concat()
+    }
+
+    /// gas the operation may use, paid by the account or by its paymaster
+    pub fn gas_limit(&self) -> U256 {
+        self.call_gas_limit
+            + self.verification_gas_limit
+            + self.pre_verification_gas
+            + self.paymaster_verification_gas_limit.unwrap_or_default()
+            + self.paymaster_post_op_gas_limit.unwrap_or_default()
+    }
+
+    /// have the gas paid by the paymaster of `data`, keeping the gas limits it leaves out
+    pub fn sponsored_by(&mut self, data: &PaymasterData) {
+        self.paymaster = Some(data.paymaster);
+        self.paymaster_data = Some(data.paymaster_data.clone());
+        if data.paymaster_verification_gas_limit.is_some() {
+            self.paymaster_verification_gas_limit = data.paymaster_verification_gas_limit;
+        }
+        if data.paymaster_post_op_gas_limit.is_some() {
+            self.paymaster_post_op_gas_limit = data.paymaster_post_op_gas_limit;
+        }
+    }
+}
+
+/// two 128 bits values in one word, the first in the high half
+fn pack(high: U256, low: U256) -> B256 {
+    B256::from((high << 128) | low)
+}
+
+/// gas limits a bundler estimated for an operation
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationGas {
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    #[serde(default)]
+    pub paymaster_verification_gas_limit: Option<U256>,
+}
+
+/// inclusion of an operation, `success` is false when the call of the account reverted
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationReceipt {
+    pub success: bool,
+    pub actual_gas_cost: U256,
+    pub actual_gas_used: U256,
+    pub receipt: BundleReceipt,
+}
+
+/// receipt of the bundle tx including the operation
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleReceipt {
+    pub transaction_hash: B256,
+    pub block_number: U64,
+}
+
+/// json rpc endpoint of an erc-4337 bundler
+#[derive(Clone)]
+pub struct Bundler {
+    provider: ReqwestProvider,
+    entry_point: Address,
+}
+
+impl Bundler {
+    pub fn new(url: &str) -> Result<Self, anyhow::Error> {
+        let url = url
+            .parse()
+            .map_err(|err| anyhow!("bundler url parse error: {err}"))?;
+        Ok(Self {
+            provider: ProviderBuilder::new().on_http(url),
+            entry_point: ENTRY_POINT,
+        })
+    }
+
+    pub fn entry_point(&self) -> Address {
+        self.entry_point
+    }
+
+    /// gas limits of `user_op`, estimated with `DUMMY_SIGNATURE` before the owner signs it
+    pub async fn estimate_gas(
+        &self,
+        user_op: &UserOperation,
+    ) -> Result<UserOperationGas, anyhow::Error> {
+        let user_op = UserOperation {
+            signature: Bytes::from(DUMMY_SIGNATURE),
+            ..user_op.clone()
+        };
+        self.provider
+            .raw_request(
+                "eth_estimateUserOperationGas".into(),
+                (user_op, self.entry_point),
+            )
+            .await
+            .map_err(|err| anyhow!("failed to estimate the user operation gas; caused by: {err}"))
+    }
+
+    /// send the signed `user_op`, returns its hash. the bundler refuses it when the account
+    /// validation rejects the signature
+    pub async fn send(&self, user_op: &UserOperation) -> Result<B256, anyhow::Error> {
+        self.provider
+            .raw_request(
+                "eth_sendUserOperation".into(),
+                (user_op.clone(), self.entry_point),
+            )
+            .await
+            .map_err(|err| anyhow!("failed to send the user operation; caused by: {err}"))
+    }
+
+    /// receipt of the operation `hash`, `None` until a bundle includes it
+    pub async fn receipt(&self, hash: B256) -> Result<Option<UserOperationReceipt>, anyhow::Error> {
+        self.provider
+            .raw_request("eth_getUserOperationReceipt".into(), (hash,))
+            .await
+            .map_err(|err| anyhow!("failed to fetch the user operation receipt; caused by: {err}"))
+    }
+
+    /// whether the bundler knows the operation `hash`, pending or included
+    pub async fn knows(&self, hash: B256) -> Result<bool, anyhow::Error> {
+        let user_op: Option<serde_json::Value> = self
+            .provider
+            .raw_request("eth_getUserOperationByHash".into(), (hash,))
+            .await
+            .map_err(|err| anyhow!("failed to fetch the user operation; caused by: {err}"))?;
+        Ok(user_op.is_some())
+    }
+}
+
+/// paymaster fields of an operation as erc-7677 paymaster services return them
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymasterData {
+    pub paymaster: Address,
+    pub paymaster_data: Bytes,
+    #[serde(default)]
+    pub paymaster_verification_gas_limit: Option<U256>,
+    #[serde(default)]
+    pub paymaster_post_op_gas_limit: Option<U256>,
+    /// sponsor the service names for the operation, stub data only
+    #[serde(default)]
+    pub sponsor: Option<PaymasterSponsor>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PaymasterSponsor {
+    pub name: String,
+}
+
+/// json rpc endpoint of an erc-7677 paymaster service
+#[derive(Clone)]
+pub struct Paymaster {
+    provider: ReqwestProvider,
+    /// policy of the service the operations are sponsored under, e.g. `{"policyId": ".."}`
+    context: serde_json::Value,
+}
+
+impl Paymaster {
+    pub fn new(url: &str, context: serde_json::Value) -> Result<Self, anyhow::Error> {
+        let url = url
+            .parse()
+            .map_err(|err| anyhow!("paymaster url parse error: {err}"))?;
+        Ok(Self {
+            provider: ProviderBuilder::new().on_http(url),
+            context,
+        })
+    }
+
+    /// placeholder paymaster data the gas of `user_op` is estimated with
+    pub async fn stub_data(
+        &self,
+        user_op: &UserOperation,
+        entry_point: Address,
+        chain_id: u64,
+        receiver: Address,
+    ) -> Result<PaymasterData, anyhow::Error> {
+        self.request(
+            "pm_getPaymasterStubData",
+            user_op,
+            entry_point,
+            chain_id,
+            receiver,
+        )
+        .await
+    }
+
+    /// paymaster data of the estimated `user_op`, the service signs off the sponsorship
+    pub async fn data(
+        &self,
+        user_op: &UserOperation,
+        entry_point: Address,
+        chain_id: u64,
+        receiver: Address,
+    ) -> Result<PaymasterData, anyhow::Error> {
+        self.request(
+            "pm_getPaymasterData",
+            user_op,
+            entry_point,
+            chain_id,
+            receiver,
+        )
+        .await
+    }
+
+    /// the receiver goes in the context so services can sponsor the payments to a given merchant
+    async fn request(
+        &self,
+        method: &'static str,
+        user_op: &UserOperation,
+        entry_point: Address,
+        chain_id: u64,
+        receiver: Address,
+    ) -> Result<PaymasterData, anyhow::Error> {
+        let mut context = match &self.context {
+            serde_json::Value::Object(context) => context.clone(),
+            _ => Default::default(),
+        };
+        context.insert("receiver".into(), receiver.to_string().into());
+        let chain_id = format!("{chain_id:#x}");
+        self.provider
+            .raw_request(
+                method.into(),
+                (user_op.clone(), entry_point, chain_id, context),
+            )
+            .await
+            .map_err(|err| anyhow!("paymaster refused to sponsor the operation; caused by: {err}"))
+    }
+}
@@ -0,0 +1,275 @@
+// watchdog health monitor
+// periodically checks the swarm loop answers, the chain providers respond and the db is writable. a
+// subsystem failing `FAILURES_BEFORE_RESTART` checks in a row is restarted or re-initialized, every
+// change of health is logged and sent as a telemetry event
+
+use crate::chain::ChainClient;
+use crate::locks::timed_lock;
+use crate::MainServiceWorker;
+use anyhow::anyhow;
+use db::{DbWorker, DbWorkerInterface};
+use log::{error, info, warn};
+use primitives::data_structure::ChainSupported;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, Notify};
+
+/// part of the node the watchdog checks
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Subsystem {
+    Swarm,
+    Db,
+    Chain(ChainSupported),
+}
+
+impl Display for Subsystem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Subsystem::Swarm => write!(f, "swarm"),
+            Subsystem::Db => write!(f, "db"),
+            Subsystem::Chain(network) => write!(f, "{network:?} chain provider"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// first failed check
+    Unresponsive,
+    Restarted,
+    RestartFailed,
+    /// first passed check after failed ones
+    Recovered,
+}
+
+/// change of a subsystem health, the error itself stays in the node logs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct HealthEvent {
+    pub subsystem: Subsystem,
+    pub status: HealthStatus,
+}
+
+#[derive(Clone)]
+pub struct Watchdog {
+    interval: Duration,
+    db_url: String,
+    chain_clients: Vec<Arc<dyn ChainClient>>,
+    probe_timeout: Duration,
+    /// failed checks in a row per subsystem
+    failures: Arc<Mutex<HashMap<Subsystem, u32>>>,
+    /// latest change of health per subsystem
+    health: Arc<Mutex<HashMap<Subsystem, HealthStatus>>>,
+    swarm_restart: Arc<Notify>,
+}
+
+impl Watchdog {
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+    pub const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+    pub const FAILURES_BEFORE_RESTART: u32 = 3;
+
+    pub fn new(
+        interval: Duration,
+        db_url: impl Into<String>,
+        chain_clients: Vec<Arc<dyn ChainClient>>,
+    ) -> Self {
+        Self {
+            interval,
+            db_url: db_url.into(),
+            chain_clients,
+            probe_timeout: Self::PROBE_TIMEOUT,
+            failures: Default::default(),
+            health: Default::default(),
+            swarm_restart: Arc::new(Notify::new()),
+        }
+    }
+
+    /// time a subsystem has to answer a check, `PROBE_TIMEOUT` otherwise
+    pub fn with_probe_timeout(mut self, probe_timeout: Duration) -> Self {
+        self.probe_timeout = probe_timeout;
+        self
+    }
+
+    /// stop the swarm loop and start it over, pending requests keep their response channels. a
+    /// restart requested while no swarm loop runs is dropped, the next loop would otherwise be
+    /// restarted as soon as it started
+    pub fn restart_swarm(&self) {
+        self.swarm_restart.notify_waiters();
+    }
+
+    /// latest change of health of `subsystem`, `None` while it never failed a check
+    pub async fn health(&self, subsystem: Subsystem) -> Option<HealthStatus> {
+        self.health.lock().await.get(&subsystem).copied()
+    }
+
+    /// resolves when the swarm loop should be restarted
+    pub async fn swarm_restart_requested(&self) {
+        self.swarm_restart.notified().await
+    }
+
+    /// check every subsystem each interval until the node stops
+    pub async fn run(self, worker: MainServiceWorker) {
+        let mut interval = tokio::time::interval(self.interval);
+        // the first tick completes right away, let the node start first
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            self.check(&worker).await;
+        }
+    }
+
+    pub async fn check(&self, worker: &MainServiceWorker) {
+        let subsystems = [Subsystem::Swarm, Subsystem::Db].into_iter().chain(
+            self.chain_clients
+                .iter()
+                .map(|c| Subsystem::Chain(c.network())),
+        );
+        for subsystem in subsystems {
+            let result = self.probe(worker, subsystem).await;
+            let failures = {
+                let mut failures = self.failures.lock().await;
+                let count = failures.entry(subsystem).or_default();
+                match result {
+                    Ok(()) => std::mem::take(count),
+                    Err(_) => {
+                        *count += 1;
+                        *count
+                    }
+                }
+            };
+            // a restart resets the failures, the subsystem is still to recover from it
+            let recovering = self
+                .health(subsystem)
+                .await
+                .is_some_and(|status| status != HealthStatus::Recovered);
+            match result {
+                Ok(()) if failures > 0 || recovering => {
+                    info!(target: "Watchdog", "{subsystem} recovered");
+                    self.emit(worker, subsystem, HealthStatus::Recovered).await;
+                }
+                Ok(()) => {}
+                Err(err) => {
+                    warn!(target: "Watchdog", "{subsystem} failed health check {failures}; caused by: {err}");
+                    if failures == 1 {
+                        self.emit(worker, subsystem, HealthStatus::Unresponsive)
+                            .await;
+                    }
+                    if failures == Self::FAILURES_BEFORE_RESTART {
+                        self.restart(worker, subsystem).await;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn probe(
+        &self,
+        worker: &MainServiceWorker,
+        subsystem: Subsystem,
+    ) -> Result<(), anyhow::Error> {
+        match subsystem {
+            // a standby runs no swarm until it leads the p2p identity
+            Subsystem::Swarm if worker.failover.as_ref().is_some_and(|f| !f.is_leader()) => Ok(()),
+            Subsystem::Swarm => {
+                let network_service = self
+                    .within(
+                        timed_lock(&worker.p2p_network_service, "p2p_network_service"),
+                        "network service lock",
+                    )
+                    .await?;
+                network_service.ping(self.probe_timeout).await
+            }
+            Subsystem::Db => {
+                let db = self
+                    .within(timed_lock(&worker.db_worker, "db_worker"), "db lock")
+                    .await?;
+                self.within(probe_db(&db), "db check").await?
+            }
+            Subsystem::Chain(network) => {
+                let client = self
+                    .chain_clients
+                    .iter()
+                    .find(|client| client.network() == network)
+                    .ok_or(anyhow!("no client for {network:?}"))?;
+                self.within(client.health_check(), "provider").await?
+            }
+        }
+    }
+
+    async fn restart(&self, worker: &MainServiceWorker, subsystem: Subsystem) {
+        let status = match subsystem {
+            Subsystem::Swarm => {
+                warn!(target: "Watchdog", "restarting the swarm loop");
+                self.restart_swarm();
+                HealthStatus::Restarted
+            }
+            Subsystem::Db => match self.reinitialize_db(worker).await {
+                Ok(()) => {
+                    warn!(target: "Watchdog", "re-initialized the db client");
+                    HealthStatus::Restarted
+                }
+                Err(err) => {
+                    error!(target: "Watchdog", "failed to re-initialize the db client; caused by: {err}");
+                    HealthStatus::RestartFailed
+                }
+            },
+            // providers are reached over stateless http, there is nothing to re-initialize
+            Subsystem::Chain(_) => return,
+        };
+        // give the restarted subsystem as many checks again before the next restart
+        self.failures.lock().await.insert(subsystem, 0);
+        self.emit(worker, subsystem, status).await;
+    }
+
+    async fn emit(&self, worker: &MainServiceWorker, subsystem: Subsystem, status: HealthStatus) {
+        self.health.lock().await.insert(subsystem, status);
+        if let Some(telemetry) = &worker.telemetry {
+            telemetry.emit(HealthEvent { subsystem, status });
+        }
+    }
+
+    async fn within<T>(
+        &self,
+        future: impl Future<Output = T>,
+        what: &str,
+    ) -> Result<T, anyhow::Error> {
+        tokio::time::timeout(self.probe_timeout, future)
+            .await
+            .map_err(|_| anyhow!("{what} did not answer within {:?}", self.probe_timeout))
+    }
+
+    async fn reinitialize_db(&self, worker: &MainServiceWorker) -> Result<(), anyhow::Error> {
+        if self.db_url == db::IN_MEMORY_DB_URL {
+            Err(anyhow!("the in-memory db would lose its records"))?
+        }
+        let db = self
+            .within(
+                DbWorker::initialize_db_client(&self.db_url),
+                "db initialization",
+            )
+            .await??;
+        let mut db_worker = self
+            .within(timed_lock(&worker.db_worker, "db_worker"), "db lock")
+            .await?;
+        // keep sealing with the key of the replaced client
+        let db = match db_worker.storage_key() {
+            Some(key) => db.with_storage_key(key.clone()),
+            None => db,
+        };
+        *db_worker = db;
+        Ok(())
+    }
+}
+
+/// write the persisted ports back, a read when none are persisted yet
+async fn probe_db(db: &DbWorker) -> Result<(), anyhow::Error> {
+    if let Some(ports) = db.get_ports().await? {
+        db.set_ports(ports.rpc_port, ports.p_2_p_port).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! Accepted assets
+//!
+//! a receiver that only takes some tokens, e.g. usdc on ethereum or solana, advertises them with the
+//! peer record of its account. the sender node checks the token of a transfer against them before
+//! the tx is built, so a transfer the receiver would not credit is refused rather than sent. an
+//! account advertising nothing accepts every token of its network.
+extern crate alloc;
+use crate::address::VaneAddress;
+use crate::data_structure::Token;
+use crate::errors::VaneRpcError;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// tokens `account` accepts, in order of preference
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptedAssets {
+    pub account: String,
+    pub tokens: Vec<Token>,
+}
+
+impl AcceptedAssets {
+    /// the preference is advertised for `address`, in any spelling of it
+    pub fn covers(&self, address: &VaneAddress) -> bool {
+        VaneAddress::parse(&self.account).is_ok_and(|account| account == *address)
+    }
+
+    pub fn accepts(&self, token: Token) -> bool {
+        self.tokens.is_empty() || self.tokens.contains(&token)
+    }
+}
+
+/// preferences of the registry `acceptedAssets` field, stored as a json list
+pub fn decode_accepted_assets(field: Option<&str>) -> Vec<AcceptedAssets> {
+    field
+        .and_then(|field| serde_json::from_str(field).ok())
+        .unwrap_or_default()
+}
+
+/// refuse a transfer of `token` to `receiver` when the receiver advertised other tokens only
+pub fn check_accepted(
+    preferences: &[AcceptedAssets],
+    receiver: &VaneAddress,
+    token: Token,
+) -> Result<(), VaneRpcError> {
+    match preferences
+        .iter()
+        .find(|preference| preference.covers(receiver))
+    {
+        Some(preference) if !preference.accepts(token) => Err(VaneRpcError::AssetNotAccepted {
+            token: token.into(),
+            accepted: preference
+                .tokens
+                .iter()
+                .map(|token| (*token).into())
+                .collect(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn transfers_are_checked_against_the_advertised_tokens() {
+        let receiver = "0x63F9725f107358c9115BC9d86c72dD5823E9B1E6";
+        let preferences = vec![AcceptedAssets {
+            account: receiver.to_lowercase(),
+            tokens: vec![Token::UsdcEth, Token::UsdcSol],
+        }];
+        let address = VaneAddress::parse(receiver).unwrap();
+        assert!(check_accepted(&preferences, &address, Token::UsdcEth).is_ok());
+        assert_eq!(
+            check_accepted(&preferences, &address, Token::UsdtEth),
+            Err(VaneRpcError::AssetNotAccepted {
+                token: "UsdtEth".to_string(),
+                accepted: vec!["UsdcEth".to_string(), "UsdcSol".to_string()],
+            })
+        );
+
+        // other accounts and accounts advertising nothing take any token
+        let other = VaneAddress::parse("0x4690152131E5399dE5E76801Fc7742A087829F00").unwrap();
+        assert!(check_accepted(&preferences, &other, Token::UsdtEth).is_ok());
+        let anything = AcceptedAssets {
+            account: receiver.to_string(),
+            tokens: vec![],
+        };
+        assert!(anything.accepts(Token::Eth));
+        assert_eq!(
+            decode_accepted_assets(Some(&serde_json::to_string(&preferences).unwrap())),
+            preferences
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Typed account addresses
+//!
+//! the same account is spelled many ways, lower case or EIP-55 checksummed hex, with or without
+//! surrounding spaces. `VaneAddress` parses an address once into the bytes it stands for and
+//! prints it in a single canonical form: checksummed hex for evm accounts, base58 for ed25519 keys
+//! and SS58 with its network prefix for substrate accounts. txs carry the canonical form, multi ids
+//! hash the canonical bytes and receivers sign the canonical text, so two spellings of one account
+//! never hash or verify differently.
+extern crate alloc;
+use crate::data_structure::ChainSupported;
+use crate::errors::VaneRpcError;
+use crate::validation::{address_format, AddressFormat};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use base58::{FromBase58, ToBase58};
+use codec::{Decode, Encode, EncodeLike, Input, Output};
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// account address of a supported network
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum VaneAddress {
+    /// 20 bytes evm account, ethereum and bnb
+    Evm([u8; 20]),
+    /// ed25519 public key, solana
+    Ed25519([u8; 32]),
+    /// substrate public key with the SS58 network prefix it was encoded with, polkadot
+    Ss58 { prefix: u16, public: [u8; 32] },
+}
+
+impl Default for VaneAddress {
+    /// the evm zero address
+    fn default() -> Self {
+        VaneAddress::Evm([0; 20])
+    }
+}
+
+impl VaneAddress {
+    /// parse any spelling of an address, surrounding spaces and the hex case do not matter but a
+    /// mixed case evm address has to carry a valid checksum
+    pub fn parse(address: &str) -> Result<Self, VaneRpcError> {
+        let address = address.trim();
+        let invalid = || VaneRpcError::InvalidAddress {
+            address: address.to_string(),
+        };
+        match address_format(address).ok_or_else(invalid)? {
+            AddressFormat::Evm { .. } => {
+                let mut bytes = [0u8; 20];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = u8::from_str_radix(&address[2 + 2 * i..4 + 2 * i], 16)
+                        .map_err(|_| invalid())?;
+                }
+                Ok(VaneAddress::Evm(bytes))
+            }
+            AddressFormat::Base58Ed25519 => {
+                let bytes = address.from_base58().map_err(|_| invalid())?;
+                Ok(VaneAddress::Ed25519(
+                    bytes.try_into().map_err(|_| invalid())?,
+                ))
+            }
+            AddressFormat::Ss58 { prefix } => {
+                let bytes = address.from_base58().map_err(|_| invalid())?;
+                // prefix, 32 bytes public key and 2 bytes checksum
+                let start = bytes.len() - 34;
+                Ok(VaneAddress::Ss58 {
+                    prefix,
+                    public: bytes[start..start + 32].try_into().map_err(|_| invalid())?,
+                })
+            }
+            AddressFormat::Bech32 => Err(invalid()),
+        }
+    }
+
+    /// canonical bytes of the account, the evm account or the public key
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            VaneAddress::Evm(bytes) => bytes,
+            VaneAddress::Ed25519(public) | VaneAddress::Ss58 { public, .. } => public,
+        }
+    }
+
+    /// networks the account lives on
+    pub fn networks(&self) -> Vec<ChainSupported> {
+        match self {
+            VaneAddress::Evm(_) => alloc::vec![ChainSupported::Ethereum, ChainSupported::Bnb],
+            VaneAddress::Ed25519(_) => alloc::vec![ChainSupported::Solana],
+            VaneAddress::Ss58 { .. } => alloc::vec![ChainSupported::Polkadot],
+        }
+    }
+}
+
+/// EIP-55 checksummed hex of an evm account, without the 0x prefix
+pub(crate) fn checksummed_hex(bytes: &[u8; 20]) -> String {
+    let lower = bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    // a letter is upper case when the nibble of the hash at its position is 8 or above
+    let hash = sp_core::hashing::keccak_256(lower.as_bytes());
+    lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// SS58 checksum of the prefix and public key bytes
+pub(crate) fn ss58_checksum(body: &[u8]) -> [u8; 2] {
+    let hash = sp_core::hashing::blake2_512(&[b"SS58PRE".as_slice(), body].concat());
+    [hash[0], hash[1]]
+}
+
+impl Display for VaneAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let address = match self {
+            VaneAddress::Evm(bytes) => format!("0x{}", checksummed_hex(bytes)),
+            VaneAddress::Ed25519(public) => public.to_base58(),
+            VaneAddress::Ss58 { prefix, public } => {
+                let mut body = match *prefix {
+                    0..=63 => alloc::vec![*prefix as u8],
+                    _ => alloc::vec![
+                        ((*prefix & 0b1111_1100) >> 2) as u8 | 0b0100_0000,
+                        (*prefix >> 8) as u8 | ((*prefix & 0b11) << 6) as u8,
+                    ],
+                };
+                body.extend_from_slice(public);
+                let checksum = ss58_checksum(&body);
+                body.extend_from_slice(&checksum);
+                body.to_base58()
+            }
+        };
+        // padded so addresses line up in tables
+        f.pad(&address)
+    }
+}
+
+impl FromStr for VaneAddress {
+    type Err = VaneRpcError;
+
+    fn from_str(address: &str) -> Result<Self, Self::Err> {
+        VaneAddress::parse(address)
+    }
+}
+
+impl PartialEq<str> for VaneAddress {
+    /// `other` is a spelling of this address
+    fn eq(&self, other: &str) -> bool {
+        VaneAddress::parse(other).is_ok_and(|other| other == *self)
+    }
+}
+
+// the canonical text on the wire, in json and in scale, so rpc clients, peers and stored txs
+// keep reading addresses as strings
+
+impl Serialize for VaneAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for VaneAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let address = String::deserialize(deserializer)?;
+        VaneAddress::parse(&address).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Encode for VaneAddress {
+    fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+        self.to_string().encode_to(dest)
+    }
+}
+
+impl EncodeLike for VaneAddress {}
+
+impl Decode for VaneAddress {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        let address = String::decode(input)?;
+        VaneAddress::parse(&address).map_err(|_| codec::Error::from("invalid address"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spellings_of_an_address_parse_to_one_canonical_form() {
+        let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let lower = VaneAddress::parse(" 0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed ").unwrap();
+        assert_eq!(lower, VaneAddress::parse(checksummed).unwrap());
+        assert_eq!(lower.to_string(), checksummed);
+        assert_eq!(lower.as_bytes().len(), 20);
+        assert!(lower == *"0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED");
+        assert!(VaneAddress::parse("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD").is_err());
+
+        for address in [
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5",
+            "11111111111111111111111111111111",
+        ] {
+            let parsed = VaneAddress::parse(address).unwrap();
+            assert_eq!(parsed.to_string(), address);
+            assert_eq!(
+                VaneAddress::decode(&mut &parsed.encode()[..]).ok(),
+                Some(parsed)
+            );
+        }
+        assert!(VaneAddress::parse("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4").is_err());
+    }
+}
@@ -0,0 +1,106 @@
+//! On chain anchors of the account to node bindings
+//!
+//! an evm account may anchor the hash of its binding to the vane node serving it in a small registry
+//! contract, see `contracts/VaneBindingRegistry.sol`. the contract keys the hash by the sender of the
+//! anchoring tx, so only the account itself anchors its binding. anyone reading the chain checks an
+//! address is vane protected by the node the off-chain registry claims, without trusting it.
+extern crate alloc;
+use crate::address::VaneAddress;
+use crate::data_structure::{ChainSupported, H256};
+use crate::errors::VaneRpcError;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// hash anchored for the binding of `account` to the node `peer_id`, the keccak256 of the 20 bytes
+/// of the account followed by the peer id, like solidity `abi.encodePacked(account, peerId)`
+pub fn binding_hash(account: &VaneAddress, peer_id: &str) -> Result<H256, VaneRpcError> {
+    let VaneAddress::Evm(bytes) = account else {
+        return Err(VaneRpcError::InvalidAddress {
+            address: account.to_string(),
+        });
+    };
+    let mut packed = bytes.to_vec();
+    packed.extend_from_slice(peer_id.as_bytes());
+    Ok(H256(sp_core::hashing::keccak_256(&packed)))
+}
+
+/// registry contract call anchoring the binding, sent by `account` from its own wallet
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorCall {
+    pub network: ChainSupported,
+    /// registry contract the call is sent to
+    pub registry: String,
+    pub account: String,
+    pub peer_id: String,
+    pub binding: H256,
+    /// abi encoded `anchor(binding)` calldata
+    pub data: Vec<u8>,
+}
+
+/// the binding of `account` to `peer_id` as anchored on chain
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BindingAnchor {
+    pub network: ChainSupported,
+    pub registry: String,
+    pub account: String,
+    pub peer_id: String,
+    /// hash anchored by the account, `None` when it anchored none
+    pub anchored: Option<H256>,
+    /// the anchored hash is the one of the binding to `peer_id`
+    pub verified: bool,
+}
+
+impl BindingAnchor {
+    pub fn new(
+        network: ChainSupported,
+        registry: String,
+        account: &VaneAddress,
+        peer_id: String,
+        anchored: Option<H256>,
+    ) -> Result<Self, VaneRpcError> {
+        let verified = anchored == Some(binding_hash(account, &peer_id)?);
+        Ok(Self {
+            network,
+            registry,
+            account: account.to_string(),
+            peer_id,
+            anchored,
+            verified,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchors_verify_the_binding_to_its_peer_only() {
+        let account = VaneAddress::parse("0x63F9725f107358c9115BC9d86c72dD5823E9B1E6").unwrap();
+        let peer_id = "12D3KooWRgyvrHbHn1wXV4kMKyYqGtDdNHeuTn8Ef4RqjkmmkbW1";
+        let binding = binding_hash(&account, peer_id).unwrap();
+        assert_ne!(binding, binding_hash(&account, "12D3KooWOther").unwrap());
+        let other = VaneAddress::parse("0x9858EfFD232B4033E47d90003D41EC34EcaEda94").unwrap();
+        assert_ne!(binding, binding_hash(&other, peer_id).unwrap());
+
+        let anchor = |anchored| {
+            BindingAnchor::new(
+                ChainSupported::Ethereum,
+                "0x0000000000000000000000000000000000000001".to_string(),
+                &account,
+                peer_id.to_string(),
+                anchored,
+            )
+            .unwrap()
+        };
+        assert!(anchor(Some(binding)).verified);
+        assert!(!anchor(None).verified);
+        assert!(!anchor(Some(H256::zero())).verified);
+
+        let solana = VaneAddress::parse("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin").unwrap();
+        assert!(binding_hash(&solana, peer_id).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! ERC-20 allowances granted through vane
+//!
+//! approvals are regular txs of kind `TxKind::Approval`, the outstanding ones are folded from the tx
+//! event log: a submitted approval sets the allowance of its (owner, token, spender) and a submitted
+//! zero approval revokes it.
+extern crate alloc;
+use crate::data_structure::{ChainSupported, Token, TxEvent, TxKind, TxStatus, H256};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// approval amount standing for an unlimited allowance, only used when explicitly requested
+pub const UNLIMITED_APPROVAL: u128 = u128::MAX;
+
+/// allowance granted by a submitted approval and not revoked since
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Approval {
+    pub owner: String,
+    pub spender: String,
+    pub token: Token,
+    pub network: ChainSupported,
+    /// `UNLIMITED_APPROVAL` for an unlimited allowance
+    pub amount: u128,
+    /// tx that granted the allowance
+    pub tx_id: H256,
+    pub tx_hash: [u8; 32],
+}
+
+/// EIP-2612 permit for the owner to sign off chain, the spender submits it together with its first
+/// `transferFrom` so the owner pays no approval gas
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermitPayload {
+    pub owner: String,
+    pub spender: String,
+    pub token: Token,
+    pub network: ChainSupported,
+    pub amount: u128,
+    /// permit nonce of the owner on the token contract
+    pub nonce: u128,
+    /// unix timestamp in seconds after which the permit is rejected
+    pub deadline: u64,
+    /// EIP-712 signing hash the owner signs
+    pub signing_hash: [u8; 32],
+}
+
+impl Approval {
+    pub fn is_unlimited(&self) -> bool {
+        self.amount == UNLIMITED_APPROVAL
+    }
+
+    /// outstanding approvals folded from an event log in recorded order
+    pub fn outstanding(events: impl IntoIterator<Item = TxEvent>) -> Vec<Approval> {
+        let mut approvals = BTreeMap::new();
+        for event in events {
+            let tx = event.tx;
+            let (TxKind::Approval, TxStatus::TxSubmissionPassed(tx_hash)) = (tx.kind, &tx.status)
+            else {
+                continue;
+            };
+            let token = tx.token.unwrap_or(Token::native(tx.network));
+            let key = (
+                tx.sender_address.clone(),
+                String::from(token),
+                tx.receiver_address.clone(),
+            );
+            if tx.amount == 0 {
+                approvals.remove(&key);
+            } else {
+                approvals.insert(
+                    key,
+                    Approval {
+                        owner: tx.sender_address.to_string(),
+                        spender: tx.receiver_address.to_string(),
+                        token,
+                        network: tx.network,
+                        amount: tx.amount,
+                        tx_id: event.tx_id,
+                        tx_hash: *tx_hash,
+                    },
+                );
+            }
+        }
+        approvals.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::VaneAddress;
+    use crate::data_structure::TxStateMachine;
+
+    fn submitted_approval(amount: u128, tx_nonce: u32) -> TxEvent {
+        let tx = TxStateMachine {
+            sender_address: VaneAddress::Evm([1; 20]),
+            receiver_address: VaneAddress::Evm([2; 20]),
+            network: ChainSupported::Ethereum,
+            token: Some(Token::UsdcEth),
+            kind: TxKind::Approval,
+            status: TxStatus::TxSubmissionPassed([tx_nonce as u8; 32]),
+            amount,
+            tx_nonce,
+            ..Default::default()
+        };
+        TxEvent::new(tx, tx_nonce as u64)
+    }
+
+    #[test]
+    fn revoked_approvals_are_not_outstanding() {
+        let granted = Approval::outstanding([submitted_approval(100, 1)]);
+        assert_eq!(granted.len(), 1);
+        assert_eq!(granted[0].amount, 100);
+
+        let raised = Approval::outstanding([
+            submitted_approval(100, 1),
+            submitted_approval(UNLIMITED_APPROVAL, 2),
+        ]);
+        assert!(raised[0].is_unlimited());
+
+        let revoked = Approval::outstanding([submitted_approval(100, 1), submitted_approval(0, 2)]);
+        assert!(revoked.is_empty());
+    }
+}
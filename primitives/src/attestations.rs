@@ -0,0 +1,104 @@
+//! Third party identity attestations
+//!
+//! an issuer, e.g. an employer or an exchange, signs a claim binding an account to an identity and the
+//! account owner stores it with its peer record in the registry. anybody can sign a claim, so nodes only
+//! turn the attestations of the issuers their operator trusts into verification badges.
+extern crate alloc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// claim of `issuer` about `account`, signed by the issuer
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityAttestation {
+    pub account: String,
+    /// evm account of the issuer
+    pub issuer: String,
+    /// e.g. `employee of Acme` or `kyc verified`
+    pub claim: String,
+    /// unix timestamp in seconds
+    pub issued_at: u64,
+    /// unix timestamp in seconds, the attestation never expires when missing
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    /// EIP-191 signature of `IdentityAttestation::message` by the issuer
+    pub signature: Vec<u8>,
+}
+
+impl IdentityAttestation {
+    /// message the issuer signs
+    pub fn message(&self) -> String {
+        let expires_at = self.expires_at.map_or(String::from("never"), |expires_at| {
+            alloc::format!("{expires_at}")
+        });
+        alloc::format!(
+            "vane identity attestation by {} that {} is {}, issued at {} expires {}",
+            self.issuer,
+            self.account,
+            self.claim,
+            self.issued_at,
+            expires_at
+        )
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+/// verified attestation of a trusted issuer, shown next to the account during transfers
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationBadge {
+    pub issuer: String,
+    /// name the node operator gave the issuer, never taken from the attestation
+    pub issuer_name: String,
+    pub claim: String,
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+/// attestations of the registry `attestations` field, stored as a json list
+pub fn decode_attestations(field: Option<&str>) -> Vec<IdentityAttestation> {
+    field
+        .and_then(|field| serde_json::from_str(field).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attestation(expires_at: Option<u64>) -> IdentityAttestation {
+        IdentityAttestation {
+            account: "0x63F9725f107358c9115BC9d86c72dD5823E9B1E6".into(),
+            issuer: "0x4690152131E5399dE5E76801Fc7742A087829F00".into(),
+            claim: "employee of Acme".into(),
+            issued_at: 100,
+            expires_at,
+            signature: vec![],
+        }
+    }
+
+    #[test]
+    fn message_binds_every_field() {
+        let never = attestation(None);
+        let expiring = attestation(Some(200));
+        assert_ne!(never.message(), expiring.message());
+        assert!(never.message().contains("employee of Acme"));
+        assert!(!never.is_expired(u64::MAX));
+        assert!(!expiring.is_expired(199));
+        assert!(expiring.is_expired(200));
+    }
+
+    #[test]
+    fn registry_field_round_trips() {
+        let attestations = vec![attestation(None), attestation(Some(200))];
+        let field = serde_json::to_string(&attestations).unwrap();
+        assert_eq!(decode_attestations(Some(&field)), attestations);
+        assert!(decode_attestations(Some("not json")).is_empty());
+        assert!(decode_attestations(None).is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+//! Solana compute budget, the priority fee of a transfer
+//!
+//! solana txs set a compute unit limit and a compute unit price in micro lamports through two
+//! compute budget instructions, the priority fee is their product on top of the base fee. txs
+//! without one routinely fail to land while the network is congested, so every tier has a preset
+//! floor. the tiers follow the prices that landed in recent slots and are raised by one tier when
+//! most of those slots needed a priority fee.
+extern crate alloc;
+use crate::data_structure::Token;
+use crate::errors::VaneRpcError;
+use crate::fees::FeeTier;
+use serde::{Deserialize, Serialize};
+
+/// program the compute budget instructions are sent to
+pub const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+/// most compute units a tx may request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// a system transfer and the two compute budget instructions use about 450 units
+pub const NATIVE_TRANSFER_COMPUTE_UNITS: u32 = 1_000;
+/// an spl `transfer_checked` uses about 6 200 units, creating the receiver token account the rest
+pub const TOKEN_TRANSFER_COMPUTE_UNITS: u32 = 40_000;
+/// compute unit price floors of the low, medium and high tiers, in micro lamports
+pub const TIER_FLOORS: [u64; 3] = [1_000, 10_000, 100_000];
+/// percentiles of the recent slot prices the low, medium and high tiers follow
+pub const PRICE_PERCENTILES: [usize; 3] = [25, 50, 90];
+/// percent of the recent slots needing a priority fee from which the network counts as congested
+pub const CONGESTION_PERCENT: usize = 75;
+
+/// compute unit limit preset of a transfer of `token`
+pub fn transfer_compute_units(token: Token) -> u32 {
+    match token {
+        Token::Sol => NATIVE_TRANSFER_COMPUTE_UNITS,
+        _ => TOKEN_TRANSFER_COMPUTE_UNITS,
+    }
+}
+
+/// compute budget instructions of a tx
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComputeBudget {
+    pub compute_unit_limit: u32,
+    /// micro lamports per compute unit
+    pub compute_unit_price: u64,
+}
+
+impl ComputeBudget {
+    /// priority fee in lamports, paid on top of the base fee of the signatures
+    pub fn priority_fee(&self) -> u64 {
+        let micro_lamports = self.compute_unit_limit as u128 * self.compute_unit_price as u128;
+        micro_lamports.div_ceil(1_000_000) as u64
+    }
+
+    /// data of the `SetComputeUnitLimit` instruction
+    pub fn limit_instruction_data(&self) -> [u8; 5] {
+        let mut data = [2; 5];
+        data[1..].copy_from_slice(&self.compute_unit_limit.to_le_bytes());
+        data
+    }
+
+    /// data of the `SetComputeUnitPrice` instruction
+    pub fn price_instruction_data(&self) -> [u8; 9] {
+        let mut data = [3; 9];
+        data[1..].copy_from_slice(&self.compute_unit_price.to_le_bytes());
+        data
+    }
+}
+
+/// suggested compute unit prices for the next slots, in micro lamports
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComputeBudgetTiers {
+    /// median price that landed in the recent slots
+    pub recent_compute_unit_price: u64,
+    /// most recent slots needed a priority fee, the tiers are raised by one
+    pub congested: bool,
+    pub low: u64,
+    pub medium: u64,
+    pub high: u64,
+}
+
+impl ComputeBudgetTiers {
+    /// tiers from `getRecentPrioritizationFees`, the lowest price that landed in each recent slot
+    pub fn from_recent_prioritization_fees(fees: &[u64]) -> Self {
+        let mut fees = fees.to_vec();
+        fees.sort_unstable();
+        let percentile = |percent: usize| match fees.len() {
+            0 => 0,
+            len => fees[(len - 1) * percent / 100],
+        };
+        let paying = fees.iter().filter(|fee| **fee > 0).count();
+        let congested = !fees.is_empty() && paying * 100 >= fees.len() * CONGESTION_PERCENT;
+        let price = |tier: usize| percentile(PRICE_PERCENTILES[tier]).max(TIER_FLOORS[tier]);
+        let (low, medium, high) = (price(0), price(1), price(2));
+        let (low, medium, high) = if congested {
+            (medium, high, high.saturating_mul(2))
+        } else {
+            (low, medium, high)
+        };
+        Self {
+            recent_compute_unit_price: percentile(50),
+            congested,
+            low,
+            medium,
+            high,
+        }
+    }
+
+    pub fn tier(&self, tier: FeeTier) -> u64 {
+        match tier {
+            FeeTier::Low => self.low,
+            FeeTier::Medium => self.medium,
+            FeeTier::High => self.high,
+        }
+    }
+
+    /// compute budget of a tx using up to `compute_unit_limit` units at the price of `tier`,
+    /// refused above the node cap
+    pub fn select(
+        &self,
+        tier: FeeTier,
+        compute_unit_limit: u32,
+        node_cap: Option<u64>,
+    ) -> Result<ComputeBudget, VaneRpcError> {
+        let compute_unit_price = self.tier(tier);
+        match node_cap {
+            Some(cap) if compute_unit_price > cap => {
+                Err(VaneRpcError::ComputeUnitPriceCapExceeded {
+                    compute_unit_price,
+                    cap,
+                })
+            }
+            _ => Ok(ComputeBudget {
+                compute_unit_limit: compute_unit_limit.min(MAX_COMPUTE_UNIT_LIMIT),
+                compute_unit_price,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn idle_networks_get_the_tier_floors() {
+        let tiers = ComputeBudgetTiers::from_recent_prioritization_fees(&[0, 0, 0, 500]);
+        assert!(!tiers.congested);
+        assert_eq!(
+            (tiers.low, tiers.medium, tiers.high),
+            (1_000, 10_000, 100_000)
+        );
+        let empty = ComputeBudgetTiers::from_recent_prioritization_fees(&[]);
+        assert_eq!(empty.low, TIER_FLOORS[0]);
+    }
+
+    #[test]
+    fn congested_networks_raise_the_tiers() {
+        let fees = vec![20_000, 40_000, 60_000, 80_000, 300_000];
+        let tiers = ComputeBudgetTiers::from_recent_prioritization_fees(&fees);
+        assert!(tiers.congested);
+        assert_eq!(tiers.recent_compute_unit_price, 60_000);
+        assert_eq!(
+            (tiers.low, tiers.medium, tiers.high),
+            (60_000, 100_000, 200_000)
+        );
+    }
+
+    #[test]
+    fn compute_budgets_are_capped_and_encoded() {
+        let tiers = ComputeBudgetTiers::from_recent_prioritization_fees(&[]);
+        let limit = transfer_compute_units(Token::UsdcSol);
+        let budget = tiers.select(FeeTier::Medium, limit, None).unwrap();
+        assert_eq!(budget.priority_fee(), 400);
+        assert_eq!(budget.limit_instruction_data(), [2, 0x40, 0x9c, 0, 0]);
+        assert_eq!(budget.price_instruction_data()[..3], [3, 0x10, 0x27]);
+        let capped = tiers.select(FeeTier::High, 40_000, Some(50_000));
+        assert!(matches!(
+            capped,
+            Err(VaneRpcError::ComputeUnitPriceCapExceeded { .. })
+        ));
+        let limit = tiers.select(FeeTier::Low, u32::MAX, None).unwrap();
+        assert_eq!(limit.compute_unit_limit, MAX_COMPUTE_UNIT_LIMIT);
+    }
+}
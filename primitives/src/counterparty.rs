@@ -0,0 +1,259 @@
+//! Counterparty profiles
+//!
+//! before signing, a sender wants to know whether it dealt with the receiver before: how many
+//! transfers went each way and how much, since when, how fast the counterparty attested and what
+//! the safety checks flagged about it. the profile is folded from the tx event log of the node, so
+//! it only covers what this node saw and never leaves it.
+extern crate alloc;
+use crate::address::VaneAddress;
+use crate::data_structure::{Severity, Token, TxEvent, TxStatus};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+
+/// past interactions of this node with an address
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CounterpartyProfile {
+    /// canonical form of the address
+    pub address: String,
+    /// transfers to the address
+    pub sent: u32,
+    /// transfers from the address
+    pub received: u32,
+    /// transfers submitted on chain, either way
+    pub succeeded: u32,
+    /// transfers that ended without a chain tx, declined, unreachable or failed to submit
+    pub failed: u32,
+    /// amount of the succeeded transfers per token, in its smallest unit
+    pub volumes: Vec<CounterpartyVolume>,
+    /// unix timestamps in seconds of the first and latest event involving the address
+    pub first_seen: Option<u64>,
+    pub last_seen: Option<u64>,
+    /// attestation requests to the address it answered, and how long it took
+    pub attestations: u32,
+    pub avg_attestation_secs: Option<u64>,
+    pub max_attestation_secs: Option<u64>,
+    /// warnings raised about transfers to the address, most frequent first
+    pub warnings: Vec<WarningHistory>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CounterpartyVolume {
+    pub token: Token,
+    pub amount: u128,
+}
+
+/// how often a warning code was raised about the counterparty
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WarningHistory {
+    pub code: String,
+    pub severity: Severity,
+    /// transfers it was raised on
+    pub count: u32,
+    pub last_seen: u64,
+}
+
+impl CounterpartyProfile {
+    /// fold the events of the txs involving `address`, in recorded order
+    pub fn fold(address: &VaneAddress, events: impl IntoIterator<Item = TxEvent>) -> Self {
+        let mut txs: BTreeMap<H256, Vec<TxEvent>> = BTreeMap::new();
+        for event in events {
+            if event.tx.sender_address == *address || event.tx.receiver_address == *address {
+                txs.entry(event.tx_id).or_default().push(event);
+            }
+        }
+
+        let mut profile = CounterpartyProfile {
+            address: alloc::format!("{address}"),
+            ..Default::default()
+        };
+        let mut attestation_secs = Vec::new();
+        for events in txs.values() {
+            let (Some(first), Some(latest)) = (events.first(), events.last()) else {
+                continue;
+            };
+            let to_counterparty = latest.tx.receiver_address == *address;
+            if to_counterparty {
+                profile.sent += 1;
+            } else {
+                profile.received += 1;
+            }
+            profile.first_seen = Some(
+                profile
+                    .first_seen
+                    .map_or(first.recorded_at, |seen| seen.min(first.recorded_at)),
+            );
+            profile.last_seen = Some(
+                profile
+                    .last_seen
+                    .map_or(latest.recorded_at, |seen| seen.max(latest.recorded_at)),
+            );
+
+            match &latest.status {
+                TxStatus::TxSubmissionPassed(_) => {
+                    profile.succeeded += 1;
+                    let token = latest.tx.token.unwrap_or(Token::native(latest.tx.network));
+                    match profile
+                        .volumes
+                        .iter_mut()
+                        .find(|volume| volume.token == token)
+                    {
+                        Some(volume) => {
+                            volume.amount =
+                                volume.amount.saturating_add(latest.tx.transfer_amount())
+                        }
+                        None => profile.volumes.push(CounterpartyVolume {
+                            token,
+                            amount: latest.tx.transfer_amount(),
+                        }),
+                    }
+                }
+                TxStatus::AnsweredOnOtherDevice => {}
+                status if status.is_final() => profile.failed += 1,
+                _ => {}
+            }
+
+            if !to_counterparty {
+                continue;
+            }
+            // the first answer of the counterparty to the attestation request
+            let answer = events.iter().find(|event| {
+                matches!(
+                    event.status,
+                    TxStatus::RecvAddrConfirmed
+                        | TxStatus::RecvAddrConfirmationPassed
+                        | TxStatus::RecvAddrFailed
+                        | TxStatus::RecvRejected(_)
+                )
+            });
+            if let Some(answer) = answer {
+                attestation_secs.push(answer.recorded_at.saturating_sub(first.recorded_at));
+            }
+            // a warning counts once per transfer, at the latest event raising it
+            let mut raised: Vec<(&str, Severity, u64)> = Vec::new();
+            for event in events {
+                for warning in &event.tx.warnings {
+                    match raised.iter_mut().find(|(code, ..)| *code == warning.code) {
+                        Some(raised) => raised.2 = event.recorded_at,
+                        None => raised.push((&warning.code, warning.severity, event.recorded_at)),
+                    }
+                }
+            }
+            for (code, severity, seen) in raised {
+                match profile
+                    .warnings
+                    .iter_mut()
+                    .find(|history| history.code == code)
+                {
+                    Some(history) => {
+                        history.count += 1;
+                        history.last_seen = history.last_seen.max(seen);
+                    }
+                    None => profile.warnings.push(WarningHistory {
+                        code: code.into(),
+                        severity,
+                        count: 1,
+                        last_seen: seen,
+                    }),
+                }
+            }
+        }
+
+        profile.attestations = attestation_secs.len() as u32;
+        if !attestation_secs.is_empty() {
+            let total: u64 = attestation_secs.iter().sum();
+            profile.avg_attestation_secs = Some(total / attestation_secs.len() as u64);
+            profile.max_attestation_secs = attestation_secs.iter().max().copied();
+        }
+        profile
+            .warnings
+            .sort_by(|a, b| b.count.cmp(&a.count).then(b.last_seen.cmp(&a.last_seen)));
+        profile
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structure::{ChainSupported, TxStateMachine, Warning};
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn profiles_fold_the_transfers_with_an_address() {
+        let me = VaneAddress::Evm([1; 20]);
+        let them = VaneAddress::Evm([2; 20]);
+        let other = VaneAddress::Evm([3; 20]);
+        let tx = |nonce: u32, sender: VaneAddress, receiver: VaneAddress| TxStateMachine {
+            sender_address: sender,
+            receiver_address: receiver,
+            network: ChainSupported::Ethereum,
+            amount: 100,
+            tx_nonce: nonce,
+            ..Default::default()
+        };
+        let event = |tx: &TxStateMachine, status: TxStatus, at: u64| {
+            TxEvent::new(
+                TxStateMachine {
+                    status,
+                    ..tx.clone()
+                },
+                at,
+            )
+        };
+        let warned = TxStateMachine {
+            warnings: vec![Warning {
+                code: "NEW_RECEIVER".to_string(),
+                severity: Severity::Medium,
+                message: "first transfer to this receiver".to_string(),
+            }],
+            ..tx(1, me, them)
+        };
+        let paid = tx(2, me, them);
+        let declined = tx(3, them, me);
+        let unrelated = tx(4, me, other);
+        let events = vec![
+            event(&warned, TxStatus::Genesis, 10),
+            event(&warned, TxStatus::RecvAddrConfirmationPassed, 40),
+            event(&warned, TxStatus::TxSubmissionPassed([1; 32]), 50),
+            event(&paid, TxStatus::Genesis, 100),
+            event(&paid, TxStatus::RecvAddrConfirmationPassed, 110),
+            event(&paid, TxStatus::TxSubmissionPassed([2; 32]), 120),
+            event(&declined, TxStatus::Genesis, 200),
+            event(&declined, TxStatus::RecvAddrFailed, 210),
+            event(&unrelated, TxStatus::Genesis, 300),
+        ];
+
+        let profile = CounterpartyProfile::fold(&them, events);
+        assert_eq!((profile.sent, profile.received), (2, 1));
+        assert_eq!((profile.succeeded, profile.failed), (2, 1));
+        assert_eq!(
+            profile.volumes,
+            vec![CounterpartyVolume {
+                token: Token::Eth,
+                amount: 200
+            }]
+        );
+        assert_eq!(
+            (profile.first_seen, profile.last_seen),
+            (Some(10), Some(210))
+        );
+        // the answers to the attestation requests sent to it, not to the ones it sent
+        assert_eq!(profile.attestations, 2);
+        assert_eq!(profile.avg_attestation_secs, Some(20));
+        assert_eq!(profile.max_attestation_secs, Some(30));
+        assert_eq!(profile.warnings.len(), 1);
+        assert_eq!(
+            (profile.warnings[0].count, profile.warnings[0].last_seen),
+            (1, 50)
+        );
+
+        let stranger = CounterpartyProfile::fold(&VaneAddress::Evm([9; 20]), vec![]);
+        assert_eq!((stranger.sent, stranger.first_seen), (0, None));
+    }
+}
@@ -1,22 +1,44 @@
 //! All data structure related to transaction processing and updating
 extern crate alloc;
+use crate::accepted_assets::AcceptedAssets;
+use crate::address::VaneAddress;
+use crate::attestations::{IdentityAttestation, VerificationBadge};
+use crate::device_sync::PairedDevice;
+use crate::durable_nonce::DurableNonce;
+use crate::environment::NetworkEnvironment;
+use crate::errors::VaneRpcError;
+use crate::explorer::{explorer_url, ChainReceipt};
+use crate::fees::{FeePreference, FeeTier};
+use crate::fiat::FiatQuote;
+use crate::identity_rotation::IdentityRotation;
+use crate::policy::RuleOutcome;
+use crate::revocation::AccountRevocation;
+use crate::risk::RiskAssessment;
+use crate::rpc_tx::RpcTx;
+use crate::sponsorship::Sponsorship;
+use crate::token_account::TokenAccountCreation;
+use crate::travel_rule::{TravelRuleAttachment, TravelRuleData};
+use crate::tx_builder::format_units;
 use alloc::string::String;
 use alloc::vec::Vec;
 use anyhow::Error;
-use codec::{Decode, Encode};
+use codec::{Decode, Encode, Input};
 use core::hash::{Hash, Hasher};
+use core::str::FromStr;
 use libp2p::request_response::{InboundRequestId, OutboundRequestId, ResponseChannel};
 use libp2p::{Multiaddr, PeerId};
-use serde::de::Error as SerdeError;
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use serde_json::Value;
-use sp_core::H256;
+use serde::{Deserialize, Serialize};
+// re-exported so clients can name tx ids without depending on sp-core
+pub use sp_core::H256;
 use twox_hash::XxHash64;
 
 // Ethereum signature preimage prefix according to EIP-191
 // keccak256("\x19Ethereum Signed Message:\n" + len(message) + message))
 pub const ETH_SIG_MSG_PREFIX: &str = "\x19Ethereum Signed Message:\n";
 
+/// most txs staged again from the event log, the latest updated ones
+pub const MAX_REPLAYED_TXS: usize = 1_000;
+
 /// tx state
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize, Encode, Decode)]
 pub enum TxStatus {
@@ -40,6 +62,24 @@ pub enum TxStatus {
     TxSubmissionPassed([u8; 32]),
     /// if the receiver has not registered to vane yet
     ReceiverNotRegistered,
+    /// if the sender has confirmed and the designated approver has to sign off before submission
+    AwaitingSecondApproval,
+    /// if the receiver node speaks an incompatible vane protocol version, with the reason
+    ReceiverIncompatible(String),
+    /// if the receiver node can't be dialed and no relay queues the request, with the reason
+    ReceiverUnreachable(String),
+    /// if the receiver node acknowledged the request, waiting on the receiver to confirm
+    DeliveredToReceiver,
+    /// if another device of the receiver answered the attestation request first
+    AnsweredOnOtherDevice,
+    /// if the receiver rejected the request, with its reason. unlike `RecvAddrFailed` the
+    /// receiver answered on purpose, no signature failed to verify
+    RecvRejected(RejectReason),
+    /// if the deadline of the sender passed before the tx was submitted, both nodes abort it
+    Expired,
+    /// if the sender node withdrew the tx, screening refused it or it was rerouted, with the
+    /// reason. unlike `FailedToSubmitTxn` it is never retried
+    Withdrawn(String),
 }
 impl Default for TxStatus {
     fn default() -> Self {
@@ -47,60 +87,420 @@ impl Default for TxStatus {
     }
 }
 
-fn serialize_u64_as_string<S>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    match value {
-        Some(v) => serializer.serialize_str(&v.to_string()),
-        None => serializer.serialize_none(),
+impl TxStatus {
+    /// no further transition follows this status
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            TxStatus::SenderConfirmationfailed
+                | TxStatus::RecvAddrFailed
+                | TxStatus::FailedToSubmitTxn(_)
+                | TxStatus::TxSubmissionPassed(_)
+                | TxStatus::ReceiverNotRegistered
+                | TxStatus::ReceiverIncompatible(_)
+                | TxStatus::ReceiverUnreachable(_)
+                | TxStatus::AnsweredOnOtherDevice
+                | TxStatus::RecvRejected(_)
+                | TxStatus::Expired
+                | TxStatus::Withdrawn(_)
+        )
+    }
+
+    /// final, but the sender can drive the tx again, e.g. a submission the provider refused
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, TxStatus::FailedToSubmitTxn(_))
     }
 }
-fn deserialize_u64_flexible<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    // First try as string
-    let value = Value::deserialize(deserializer)?;
 
-    match value {
-        // Handle direct number
-        Value::Number(n) => {
-            if let Some(num) = n.as_u64() {
-                Ok(Some(num))
-            } else {
-                Err(D::Error::custom("Invalid number format for u64"))
-            }
+/// why the receiver rejected an attestation request, told to the sender
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+pub enum RejectReason {
+    /// the receiver does not own the address
+    NotMyAddress,
+    /// the receiver owns the address but not on the network of the transfer
+    WrongChain,
+    /// the receiver did not expect that amount
+    UnexpectedAmount,
+    /// the receiver does not know the sender and suspects phishing
+    SuspectedPhishing,
+}
+
+impl RejectReason {
+    pub const ALL: [RejectReason; 4] = [
+        RejectReason::NotMyAddress,
+        RejectReason::WrongChain,
+        RejectReason::UnexpectedAmount,
+        RejectReason::SuspectedPhishing,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RejectReason::NotMyAddress => "NotMyAddress",
+            RejectReason::WrongChain => "WrongChain",
+            RejectReason::UnexpectedAmount => "UnexpectedAmount",
+            RejectReason::SuspectedPhishing => "SuspectedPhishing",
         }
-        // Handle string (both normal and hex)
-        Value::String(s) => {
-            if let Some(stripped) = s.strip_prefix("0x") {
-                // Handle hex string
-                u64::from_str_radix(stripped, 16)
-                    .map(Some)
-                    .map_err(D::Error::custom)
-            } else {
-                // Handle decimal string
-                s.parse::<u64>().map(Some).map_err(D::Error::custom)
+    }
+
+    /// reason named `name`, ignoring case
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|reason| reason.name().eq_ignore_ascii_case(name))
+    }
+
+    /// the receiver can propose the address or network to send to instead
+    pub fn allows_correction(&self) -> bool {
+        matches!(self, RejectReason::NotMyAddress | RejectReason::WrongChain)
+    }
+}
+
+/// address and network the receiver asks to be paid on instead, attached to its rejection. signed
+/// by the receiver with the key of the address the sender picked, so the sender node can tell it
+/// comes from the receiver it dealt with
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressCorrection {
+    pub network: ChainSupported,
+    pub address: VaneAddress,
+    /// EIP-191 signature of `AddressCorrection::message`, evm receivers only
+    pub signature: Vec<u8>,
+}
+
+impl AddressCorrection {
+    /// message the receiver signs, bound to the rejected tx so it can not be replayed on another
+    pub fn message(tx_id: H256, network: ChainSupported, address: &VaneAddress) -> String {
+        alloc::format!("vane address correction of tx {tx_id:?}: pay {address} on {network:?}")
+    }
+}
+
+/// what the chain tx does
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+pub enum TxKind {
+    /// move `amount` to the receiver, attested by the receiver
+    #[default]
+    Transfer,
+    /// allow the receiver (spender contract) to move up to `amount` of the sender tokens, zero revokes.
+    /// there is no receiver to attest, the tx goes straight to the sender confirmation
+    Approval,
+}
+
+/// how bad it is to proceed despite a warning
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, Encode, Decode,
+)]
+pub enum Severity {
+    Low,
+    Medium,
+    /// the funds are as good as lost if the sender proceeds, confirming needs an explicit override
+    High,
+}
+
+/// sign-off of the designated approver on a sender confirmed tx
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct SecondApproval {
+    /// evm account of the approver
+    pub approver: String,
+    /// EIP-191 signature of `TxStateMachine::second_approval_message`
+    pub signature: Vec<u8>,
+    /// unix timestamp in seconds
+    pub approved_at: u64,
+}
+
+/// sender identity attached to an attestation request, so the receiver does not attest for a bare address.
+/// the sender fills in the name and signature, the receiver node the rest
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct SenderProfile {
+    pub display_name: String,
+    /// EIP-191 signature of `SenderProfile::message` by the sender account
+    pub signature: Vec<u8>,
+    /// the signature recovers to the sender address, checked by the receiver node
+    #[serde(default)]
+    pub verified: bool,
+    /// txs from the sender the receiver attested before, from the receiver node db
+    #[serde(default)]
+    pub past_interactions: u32,
+}
+
+impl SenderProfile {
+    /// message the sender signs to prove it owns the address it sends from, `sender_address` in its
+    /// canonical `VaneAddress` form
+    pub fn message(display_name: &str, sender_address: &str) -> String {
+        alloc::format!("vane sender profile {display_name} for {sender_address}")
+    }
+}
+
+/// safety finding about a transfer as carried in the tx, shown to the sender before confirming
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+pub struct Warning {
+    /// stable identifier clients can branch on, e.g. `BURN_ADDRESS`
+    pub code: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn requires_override(&self) -> bool {
+        self.severity == Severity::High
+    }
+}
+
+/// findings of the safety checks, graded into a `Warning`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+pub enum TxWarning {
+    /// receiver is a burn or system address no one controls, funds sent to it are lost
+    BurnAddress,
+    /// receiver is a contract, not an account the receiver controls with a key
+    ContractRecipient,
+    /// receiver contract reverts when receiving native value
+    ContractRejectsValue,
+    /// receiver is a bridge deposit contract deployed on another chain than the selected network
+    CrossChainDeposit { name: String, chain: String },
+    /// receiver is an exchange deposit address not crediting the token on the selected network
+    ExchangeNotCredited {
+        exchange: String,
+        token: Token,
+        network: ChainSupported,
+    },
+    /// receiver account was revoked by its owner as compromised
+    RevokedAccount,
+    /// a validation plugin of the operator flagged the transfer
+    Plugin { plugin: String, reason: String },
+    /// transfers of the same funds still in flight leave the sender short for this one
+    ConflictingTransfers {
+        in_flight: u32,
+        committed: u128,
+        balance: u128,
+    },
+    /// a large round amount, likely a smaller one typed without its decimal point
+    RoundAmount { token: Token, amount: u128 },
+    /// the amount carries more decimals than the operator allows for the token
+    ExcessPrecision {
+        token: Token,
+        decimals: u32,
+        max_decimals: u32,
+    },
+    /// the sender balance does not cover the transfer, it does on another chain both peers use
+    AlternativeRoute {
+        network: ChainSupported,
+        token: Token,
+        balance: u128,
+    },
+    /// the solana receiver has no token account for the token, `created` when the tx creates it
+    /// and the sender pays the `rent` lamports it is funded with
+    MissingTokenAccount {
+        token: Token,
+        rent: u64,
+        created: bool,
+    },
+    /// the independent provider did not see the broadcast tx within `timeout_secs`, the primary
+    /// provider accepted it and may have dropped it
+    UnverifiedBroadcast { timeout_secs: u64 },
+}
+
+impl TxWarning {
+    pub fn code(&self) -> &'static str {
+        match self {
+            TxWarning::BurnAddress => "BURN_ADDRESS",
+            TxWarning::ContractRecipient => "CONTRACT_RECIPIENT",
+            TxWarning::ContractRejectsValue => "CONTRACT_REJECTS_VALUE",
+            TxWarning::CrossChainDeposit { .. } => "CROSS_CHAIN_DEPOSIT",
+            TxWarning::ExchangeNotCredited { .. } => "EXCHANGE_NOT_CREDITED",
+            TxWarning::RevokedAccount => "REVOKED_ACCOUNT",
+            TxWarning::Plugin { .. } => "PLUGIN_WARNING",
+            TxWarning::ConflictingTransfers { .. } => "CONFLICTING_TRANSFERS",
+            TxWarning::RoundAmount { .. } => "ROUND_AMOUNT",
+            TxWarning::ExcessPrecision { .. } => "EXCESS_PRECISION",
+            TxWarning::AlternativeRoute { .. } => "ALTERNATIVE_ROUTE",
+            TxWarning::MissingTokenAccount { .. } => "MISSING_TOKEN_ACCOUNT",
+            TxWarning::UnverifiedBroadcast { .. } => "UNVERIFIED_BROADCAST",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            TxWarning::ExcessPrecision { .. } => Severity::Low,
+            TxWarning::ContractRecipient
+            | TxWarning::Plugin { .. }
+            | TxWarning::RoundAmount { .. }
+            | TxWarning::AlternativeRoute { .. }
+            | TxWarning::MissingTokenAccount { created: true, .. }
+            | TxWarning::UnverifiedBroadcast { .. } => Severity::Medium,
+            TxWarning::MissingTokenAccount { created: false, .. }
+            | TxWarning::BurnAddress
+            | TxWarning::ContractRejectsValue
+            | TxWarning::CrossChainDeposit { .. }
+            | TxWarning::ExchangeNotCredited { .. }
+            | TxWarning::RevokedAccount
+            | TxWarning::ConflictingTransfers { .. } => Severity::High,
+        }
+    }
+}
+
+impl From<TxWarning> for Warning {
+    fn from(warning: TxWarning) -> Self {
+        Warning {
+            code: warning.code().to_string(),
+            severity: warning.severity(),
+            message: warning.to_string(),
+        }
+    }
+}
+
+impl core::fmt::Display for TxWarning {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TxWarning::BurnAddress => write!(f, "receiver is a burn address, funds will be lost"),
+            TxWarning::ContractRecipient => write!(f, "receiver is a contract"),
+            TxWarning::ContractRejectsValue => {
+                write!(f, "receiver contract can not receive native tokens")
             }
+            TxWarning::CrossChainDeposit { name, chain } => write!(
+                f,
+                "receiver is the {name} on {chain}, it is not deployed on the selected network and funds will be lost"
+            ),
+            TxWarning::ExchangeNotCredited {
+                exchange,
+                token,
+                network,
+            } => write!(
+                f,
+                "receiver is a {exchange} deposit address, {exchange} does not credit {token:?} on {network:?}"
+            ),
+            TxWarning::RevokedAccount => write!(
+                f,
+                "receiver account was revoked as compromised, whoever holds its key can take the funds"
+            ),
+            TxWarning::Plugin { plugin, reason } => write!(f, "plugin {plugin}: {reason}"),
+            TxWarning::ConflictingTransfers {
+                in_flight,
+                committed,
+                balance,
+            } => write!(
+                f,
+                "{in_flight} transfers in flight already commit {committed} of the {balance} balance, this one would fail on chain"
+            ),
+            TxWarning::RoundAmount { token, amount } => write!(
+                f,
+                "{} {} is a suspiciously round amount, check it is not {} typed without its decimal point",
+                format_units(*amount, token.decimals()),
+                token.symbol(),
+                format_units(amount / 100, token.decimals()),
+            ),
+            TxWarning::ExcessPrecision {
+                token,
+                decimals,
+                max_decimals,
+            } => write!(
+                f,
+                "amount has {decimals} decimals, {} transfers are expected with at most {max_decimals}",
+                token.symbol()
+            ),
+            TxWarning::AlternativeRoute {
+                network,
+                token,
+                balance,
+            } => write!(
+                f,
+                "sender balance does not cover the transfer, it holds {} {} on {network:?} where the receiver is registered too",
+                format_units(*balance, token.decimals()),
+                token.symbol()
+            ),
+            TxWarning::MissingTokenAccount {
+                token,
+                rent,
+                created: true,
+            } => write!(
+                f,
+                "receiver has no {} token account, the tx creates it and the sender pays {} SOL of rent",
+                token.symbol(),
+                format_units(*rent as u128, Token::Sol.decimals())
+            ),
+            TxWarning::MissingTokenAccount { token, .. } => write!(
+                f,
+                "receiver has no {} token account, the transfer fails on chain until it opens one",
+                token.symbol()
+            ),
+            TxWarning::UnverifiedBroadcast { timeout_secs } => write!(
+                f,
+                "the independent provider did not see the tx within {timeout_secs}s, follow it on the explorer"
+            ),
+        }
+    }
+}
+
+/// where the signed tx is sent to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+pub enum SubmissionRoute {
+    /// chain rpc node, the tx is visible in the public mempool
+    #[default]
+    Public,
+    /// private relay (Flashbots Protect style) forwarding to block builders only, protecting
+    /// large transfers from sandwiching. ethereum only
+    PrivateRelay,
+}
+
+/// how time critical a transfer is, a payment isn't stuck behind a batch run of lower priority. the
+/// scale index of `Normal` is 0 so txs of nodes older than the field decode as normal ones
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Deserialize,
+    Serialize,
+    Encode,
+    Decode,
+)]
+pub enum TxPriority {
+    /// batch runs such as payroll, low fee tier
+    #[codec(index = 1)]
+    Low,
+    #[default]
+    #[codec(index = 0)]
+    Normal,
+    /// high fee tier, first in the submission queue and redialing an unreachable receiver
+    #[codec(index = 2)]
+    Urgent,
+}
+
+impl TxPriority {
+    /// fee tier of the transfer when the sender picks none
+    pub fn fee_tier(self) -> FeeTier {
+        match self {
+            TxPriority::Low => FeeTier::Low,
+            TxPriority::Normal => FeeTier::Medium,
+            TxPriority::Urgent => FeeTier::High,
+        }
+    }
+
+    /// times the receiver devices are dialed again before the tx fails as unreachable
+    pub fn delivery_retries(self) -> u32 {
+        match self {
+            TxPriority::Low | TxPriority::Normal => 0,
+            TxPriority::Urgent => 2,
         }
-        Value::Null => Ok(None),
-        _ => Err(D::Error::custom("Expected string, number, or null")),
     }
 }
 
-/// Transaction data structure state machine, passed in rpc and p2p swarm
+/// Transaction data structure state machine, passed in rpc and p2p swarm. scale encoded between
+/// nodes and serialized through the stable `RpcTx` shape over the rpc
 #[derive(Clone, Default, PartialEq, Debug, Deserialize, Serialize, Encode, Decode)]
+#[serde(into = "RpcTx", try_from = "RpcTx")]
 pub struct TxStateMachine {
-    #[serde(rename = "senderAddress")]
-    pub sender_address: String,
-    #[serde(rename = "receiverAddress")]
-    pub receiver_address: String,
-    /// hashed sender and receiver address to bind the addresses while sending
-    #[serde(rename = "multiId")]
+    pub sender_address: VaneAddress,
+    pub receiver_address: VaneAddress,
+    /// hash binding the addresses, network, chain id, amount and nonce while sending, see
+    /// `MultiIdPreimage`
     pub multi_id: H256,
     /// signature of the receiver id (Signature)
-    #[serde(rename = "recvSignature")]
     pub recv_signature: Option<Vec<u8>>,
     /// chain network
     pub network: ChainSupported,
@@ -109,62 +509,205 @@ pub struct TxStateMachine {
     /// amount to be sent
     pub amount: u128,
     /// signed call payload (signed hash of the transaction)
-    #[serde(rename = "signedCallPayload")]
     pub signed_call_payload: Option<Vec<u8>>,
     /// call payload (hash of transaction)
-    #[serde(rename = "callPayload")]
     pub call_payload: Option<[u8; 32]>,
     // /// used for simplifying tx identification
     // pub code_word: String,
     // pub sender_name: String,
     /// Inbound Request id for p2p
-    #[serde(rename = "inboundReqId")]
-    #[serde(serialize_with = "serialize_u64_as_string")]
-    #[serde(deserialize_with = "deserialize_u64_flexible")]
     pub inbound_req_id: Option<u64>,
     /// Outbound Request id for p2p
-    #[serde(rename = "outboundReqId")]
-    #[serde(serialize_with = "serialize_u64_as_string")]
-    #[serde(deserialize_with = "deserialize_u64_flexible")]
     pub outbound_req_id: Option<u64>,
     /// stores the current nonce of the transaction per vane not the nonce for the blockchain network
-    #[serde(rename = "txNonce")]
     pub tx_nonce: u32,
+    /// sender fee tier and max fee, evm networks only
+    pub fee_preference: Option<FeePreference>,
+    /// public mempool or private relay submission
+    pub submission_route: SubmissionRoute,
+    /// token sent, the network native token when missing
+    pub token: Option<Token>,
+    /// input of the chain tx when the transfer is a contract call, e.g. an erc20 `transfer`
+    pub call_data: Option<Vec<u8>>,
+    /// human readable summary of the contract call the sender signs
+    pub intent: Option<String>,
+    /// safety findings about the receiver, the sender should review them before confirming
+    pub warnings: Vec<Warning>,
+    pub kind: TxKind,
+    /// memo or destination tag identifying the receiver account at a shared deposit address
+    pub memo: Option<String>,
+    /// operator policy rules that applied at the latest transition
+    pub policy_outcomes: Vec<RuleOutcome>,
+    /// sign-off of the designated approver, set once it approved the sender confirmed tx
+    pub second_approval: Option<SecondApproval>,
+    /// sender identity shown to the receiver before attesting
+    pub sender_profile: Option<SenderProfile>,
+    /// trusted third party attestations of the receiver account, set by the sender node
+    pub receiver_badges: Vec<VerificationBadge>,
+    /// trusted third party attestations of the sender account, set by the receiver node
+    pub sender_badges: Vec<VerificationBadge>,
+    /// environment of the network the tx was initiated on, the receiver node refuses other ones
+    pub environment: NetworkEnvironment,
+    /// version of the `multi_id` preimage, zero for txs of clients older than the versioning
+    pub multi_id_version: u8,
+    /// invoice or order id the transfer settles, kept in the history for reconciliation. like the
+    /// memo and tags it is not part of the multi id nor of the signed chain tx
+    pub reference: Option<String>,
+    /// free form labels of the transfer, searchable in the history
+    pub tags: Vec<String>,
+    /// address and network the receiver proposed along its rejection, kept by the sender node only
+    /// once the receiver signature verified
+    pub address_correction: Option<AddressCorrection>,
+    /// lower amount the receiver accepted when attesting, the chain tx moves it instead of
+    /// `amount`. the multi id keeps binding the amount the sender initiated
+    pub accepted_amount: Option<u128>,
+    /// id of the split payment the transfer is a part of
+    pub parent_payment: Option<H256>,
+    /// originator and beneficiary data sealed to the nodes of both ends, never sent to clients
+    pub travel_rule: Option<TravelRuleAttachment>,
+    /// risk provider assessment of the receiver, recorded by the sender node at genesis and never
+    /// sent to the receiver
+    pub risk_assessment: Option<RiskAssessment>,
+    /// unix timestamp in seconds the sender wants the transfer submitted by, both nodes abort it
+    /// once passed. like the memo it is not part of the multi id
+    pub deadline: Option<u64>,
+    /// scheduling of the delivery retries and the submission, and the default fee tier
+    pub priority: TxPriority,
+    /// usd value and token price the amount of a fiat denominated transfer was computed with, the
+    /// sender node refuses to submit it once the price moved past the slippage
+    pub fiat_quote: Option<FiatQuote>,
+    /// paymaster sponsoring the gas of the user operation of a smart account sender, set by the
+    /// sender node when it builds the operation
+    pub sponsorship: Option<Sponsorship>,
+    /// nonce account the solana tx is signed against, it does not expire while the receiver attests
+    pub durable_nonce: Option<DurableNonce>,
+    /// token account of the solana receiver the tx creates, set by the sender node when it is
+    /// missing and the node creates the missing ones
+    pub receiver_token_account: Option<TokenAccountCreation>,
 }
 
 impl TxStateMachine {
-    pub fn recv_confirmation_passed(&mut self) {
-        self.status = TxStatus::RecvAddrConfirmationPassed
-    }
-    pub fn recv_confirmation_failed(&mut self) {
-        self.status = TxStatus::RecvAddrFailed
+    pub fn increment_nonce(&mut self) {
+        self.tx_nonce += 1
     }
-    pub fn recv_confirmed(&mut self) {
-        self.status = TxStatus::RecvAddrConfirmed
+
+    /// id of the tx shared by the sender and receiver nodes, binds the addresses and the sender tx nonce
+    pub fn tx_id(&self) -> H256 {
+        let mut id_preimage = self.multi_id.as_bytes().to_vec();
+        id_preimage.extend_from_slice(&self.tx_nonce.to_le_bytes());
+        H256::from(sp_core::hashing::blake2_256(&id_preimage[..]))
     }
-    pub fn sender_confirmation(&mut self) {
-        self.status = TxStatus::SenderConfirmed
+
+    /// the deadline of the sender passed at the unix timestamp `now`
+    pub fn is_overdue(&self, now: u64) -> bool {
+        self.deadline.is_some_and(|deadline| now >= deadline)
     }
-    pub fn sender_confirmation_failed(&mut self) {
-        self.status = TxStatus::SenderConfirmationfailed
+
+    /// amount the chain tx moves, the accepted amount when the receiver settled for less
+    pub fn transfer_amount(&self) -> u128 {
+        self.accepted_amount.unwrap_or(self.amount)
     }
-    pub fn tx_submission_failed(&mut self, reason: String) {
-        self.status = TxStatus::FailedToSubmitTxn(reason)
+
+    /// a receiver only accepts a nonzero amount below the initiated one, of a transfer
+    pub fn can_accept(&self, amount: u128) -> bool {
+        self.kind == TxKind::Transfer && amount > 0 && amount < self.amount
     }
-    pub fn tx_submission_passed(&mut self, tx_hash: [u8; 32]) {
-        self.status = TxStatus::TxSubmissionPassed(tx_hash)
+
+    /// message the approver signs, bound to the chain tx the sender signed
+    pub fn second_approval_message(&self) -> String {
+        alloc::format!(
+            "vane second approval of tx {:?} with call payload {:?}",
+            self.tx_id(),
+            H256::from(self.call_payload.unwrap_or_default())
+        )
     }
-    pub fn net_confirmed(&mut self) {
-        self.status = TxStatus::NetConfirmed
+}
+
+/// immutable record of a tx state transition, the latest event of a tx is its current state
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, Encode)]
+pub struct TxEvent {
+    #[serde(rename = "txId")]
+    pub tx_id: H256,
+    pub status: TxStatus,
+    /// tx as it was after the transition
+    pub tx: TxStateMachine,
+    /// unix timestamp in seconds
+    #[serde(rename = "recordedAt")]
+    pub recorded_at: u64,
+    /// payloads and memo of `tx` sealed at rest, taken out of it while they are sealed
+    #[serde(
+        rename = "sealedFields",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sealed_fields: Option<Vec<u8>>,
+}
+
+impl Decode for TxEvent {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        let mut event = TxEvent {
+            tx_id: Decode::decode(input)?,
+            status: Decode::decode(input)?,
+            tx: Decode::decode(input)?,
+            recorded_at: Decode::decode(input)?,
+            sealed_fields: None,
+        };
+        // events recorded before the sealed fields had their own place end here
+        if input.remaining_len()? != Some(0) {
+            event.sealed_fields = Decode::decode(input)?;
+        }
+        Ok(event)
     }
-    pub fn recv_not_registered(&mut self) {
-        self.status = TxStatus::ReceiverNotRegistered
+}
+
+impl TxEvent {
+    pub fn new(tx: TxStateMachine, recorded_at: u64) -> Self {
+        Self {
+            tx_id: tx.tx_id(),
+            status: tx.status.clone(),
+            tx,
+            recorded_at,
+            sealed_fields: None,
+        }
     }
-    pub fn increment_nonce(&mut self) {
-        self.tx_nonce += 1
+
+    /// current state of the txs still in flight or whose submission can be retried, folded from
+    /// an event log in recorded order. at most `MAX_REPLAYED_TXS`, the latest updated first
+    pub fn pending_txs(events: impl IntoIterator<Item = TxEvent>) -> Vec<TxStateMachine> {
+        // the position of the latest event of each tx, settled txs leave the set
+        let mut latest = alloc::collections::BTreeMap::new();
+        for (position, event) in events.into_iter().enumerate() {
+            if event.status.is_final() && !event.status.is_retryable() {
+                latest.remove(&event.tx_id);
+            } else {
+                latest.insert(event.tx_id, (position, event.tx));
+            }
+        }
+        let mut pending: Vec<(usize, TxStateMachine)> = latest.into_values().collect();
+        pending.sort_by_key(|(position, _)| core::cmp::Reverse(*position));
+        pending.truncate(MAX_REPLAYED_TXS);
+        pending.into_iter().map(|(_, tx)| tx).collect()
     }
 }
 
+/// attestation request from a sender waiting for this node's receiver to confirm or decline
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingReceive {
+    pub tx: TxStateMachine,
+    /// unix timestamp in seconds the request reached this node
+    pub received_at: u64,
+}
+
+/// result of one tx of a bulk confirm or decline
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkOutcome {
+    pub tx_nonce: u32,
+    /// why the tx was skipped, none when it went through
+    pub error: Option<VaneRpcError>,
+}
+
 // helper for hashing p2p swarm request ids
 pub trait HashId: Hash {
     fn get_hash_id(&self) -> u64 {
@@ -190,10 +733,37 @@ pub enum NetworkCommand {
         response: Vec<u8>,
         channel: ResponseChannel<Result<Vec<u8>, Error>>,
     },
+    /// `reply` gets the dial result once the peer is connected or the dial failed
     Dial {
         target_multi_addr: Multiaddr,
         target_peer_id: PeerId,
+        reply: libp2p::futures::channel::oneshot::Sender<Result<(), String>>,
+    },
+    /// sealed to the offline mobile node `peer_id` and handed to its relay
+    SendRelayedRequest { request: Vec<u8>, peer_id: PeerId },
+    /// answered right away by a responsive swarm loop
+    Ping {
+        reply: libp2p::futures::channel::oneshot::Sender<()>,
+    },
+    /// withdraw the attestation requests of the tx `tx_nonce` from the receiver devices
+    CancelRequests { tx_nonce: u32 },
+    /// pair with the device `target_peer_id` showing the pairing code `code`, `reply` gets the
+    /// paired device or why it refused
+    PairDevice {
+        target_multi_addr: Multiaddr,
+        target_peer_id: PeerId,
+        code: String,
+        reply: libp2p::futures::channel::oneshot::Sender<Result<PairedDevice, String>>,
     },
+    /// swap the swarm for one running under `keypair`, the old one keeps answering for `grace`.
+    /// `reply` gets the signed rotation or why it was refused
+    RotateIdentity {
+        keypair: libp2p::identity::Keypair,
+        grace: core::time::Duration,
+        reply: libp2p::futures::channel::oneshot::Sender<Result<IdentityRotation, String>>,
+    },
+    /// exchange the bindings, relay routes and revocations with every connected peer
+    ExchangePeers,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -206,10 +776,23 @@ pub enum SwarmMessage {
         data: Vec<u8>,
         outbound_id: OutboundRequestId,
     },
+    /// the receiver node acknowledged the request of the tx `tx_nonce`
+    Delivered { tx_nonce: u32 },
+    /// the sender withdrew the request `inbound_req_id`, another device of the receiver answered it
+    Cancelled { inbound_req_id: u64 },
+}
+
+/// requests of the receipt protocol, an attestation request is named by the hash of its encoding
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum ReceiptMessage {
+    /// sent by the receiver node as soon as it got the request
+    Delivered([u8; 32]),
+    /// sent by the sender node once another device of the receiver answered the request
+    Cancelled([u8; 32]),
 }
 
 /// Transaction data structure to store in the db
-#[derive(Clone, Deserialize, Serialize, Encode, Decode)]
+#[derive(Clone, Default, Deserialize, Serialize, Encode)]
 pub struct DbTxStateMachine {
     // Tx hash based on the chain hashing algorithm
     pub tx_hash: Vec<u8>,
@@ -219,10 +802,188 @@ pub struct DbTxStateMachine {
     pub network: ChainSupported,
     // status
     pub success: bool,
+    /// reconciliation fields of the tx, see `TxStateMachine::reference`
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default)]
+    pub reference: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// canonical addresses of the tx, searched as the counterparty
+    #[serde(default)]
+    pub sender_address: String,
+    #[serde(default)]
+    pub receiver_address: String,
+    /// display name of the sender profile, when the sender shared one
+    #[serde(default)]
+    pub sender_name: Option<String>,
+    /// explorer page of the submitted tx
+    #[serde(default)]
+    pub explorer_url: Option<String>,
+    /// inclusion details, once the chain has a receipt of the tx
+    #[serde(default)]
+    pub receipt: Option<ChainReceipt>,
+    /// fee tier the tx was built with, evm networks only
+    #[serde(default)]
+    pub fee_tier: Option<FeeTier>,
+    /// fee the tx would have paid at the low tier of its block, recorded with the receipt
+    #[serde(default)]
+    pub low_tier_fee: Option<u128>,
+    /// reason the receiver gave when it rejected the transfer
+    #[serde(default)]
+    pub reject_reason: Option<RejectReason>,
+    /// paymaster that sponsored the gas of the transfer, smart account senders only
+    #[serde(default)]
+    pub sponsorship: Option<Sponsorship>,
+    /// travel rule data the transfer carried, opened when the receipt is exported
+    #[serde(default)]
+    #[codec(skip)]
+    pub travel_rule: Option<TravelRuleData>,
+}
+
+impl DbTxStateMachine {
+    /// history record of `tx`
+    pub fn of(tx: &TxStateMachine, tx_hash: Vec<u8>, success: bool) -> Self {
+        DbTxStateMachine {
+            amount: tx.transfer_amount(),
+            network: tx.network,
+            success,
+            memo: tx.memo.clone(),
+            reference: tx.reference.clone(),
+            tags: tx.tags.clone(),
+            sender_address: tx.sender_address.to_string(),
+            receiver_address: tx.receiver_address.to_string(),
+            sender_name: tx
+                .sender_profile
+                .as_ref()
+                .map(|profile| profile.display_name.clone()),
+            explorer_url: (!tx_hash.is_empty())
+                .then(|| explorer_url(tx.network, tx.environment, &tx_hash)),
+            receipt: None,
+            fee_tier: matches!(tx.network, ChainSupported::Ethereum | ChainSupported::Bnb)
+                .then(|| tx.fee_preference.unwrap_or_default().tier),
+            low_tier_fee: None,
+            reject_reason: match tx.status {
+                TxStatus::RecvRejected(reason) => Some(reason),
+                _ => None,
+            },
+            sponsorship: tx.sponsorship.clone(),
+            travel_rule: None,
+            tx_hash,
+        }
+    }
+
+    /// `query` is the reference or one of the tags of the tx, or part of its memo, ignoring case
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        self.reference
+            .iter()
+            .chain(self.tags.iter())
+            .any(|field| field.to_lowercase() == query)
+            || self
+                .memo
+                .as_ref()
+                .is_some_and(|memo| memo.to_lowercase().contains(&query))
+    }
+}
+
+impl Decode for DbTxStateMachine {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        let mut tx = DbTxStateMachine {
+            tx_hash: Decode::decode(input)?,
+            amount: Decode::decode(input)?,
+            network: Decode::decode(input)?,
+            success: Decode::decode(input)?,
+            ..Default::default()
+        };
+        // records stored before the reconciliation fields end here
+        if input.remaining_len()? != Some(0) {
+            tx.memo = Decode::decode(input)?;
+            tx.reference = Decode::decode(input)?;
+            tx.tags = Decode::decode(input)?;
+        }
+        // and records stored before the search fields here
+        if input.remaining_len()? != Some(0) {
+            tx.sender_address = Decode::decode(input)?;
+            tx.receiver_address = Decode::decode(input)?;
+            tx.sender_name = Decode::decode(input)?;
+        }
+        // and records stored before the receipts here
+        if input.remaining_len()? != Some(0) {
+            tx.explorer_url = Decode::decode(input)?;
+            tx.receipt = Decode::decode(input)?;
+        }
+        // and records stored before the fee accounting here
+        if input.remaining_len()? != Some(0) {
+            tx.fee_tier = Decode::decode(input)?;
+            tx.low_tier_fee = Decode::decode(input)?;
+        }
+        // and records stored before the reject reasons here
+        if input.remaining_len()? != Some(0) {
+            tx.reject_reason = Decode::decode(input)?;
+        }
+        // and records stored before the sponsorships here
+        if input.remaining_len()? != Some(0) {
+            tx.sponsorship = Decode::decode(input)?;
+        }
+        Ok(tx)
+    }
+}
+
+/// chain rpc endpoint health as probed by the node
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainHealth {
+    pub network: ChainSupported,
+    pub environment: NetworkEnvironment,
+    pub reachable: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+    /// submissions to the chain are paused while the breaker is open
+    #[serde(default)]
+    pub breaker: BreakerState,
+}
+
+/// circuit breaker of the submissions to a chain
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BreakerState {
+    /// submissions go through
+    #[default]
+    Closed,
+    /// the provider rejected the latest submissions, the queued txs wait for a passed probe
+    Open,
+    /// a probe passed, the next submission closes the breaker or opens it again
+    HalfOpen,
+}
+
+/// connection level metrics of a peer the node exchanged with since it started
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerStats {
+    pub peer_id: String,
+    pub connected: bool,
+    /// round trip time of the last libp2p ping
+    pub rtt_ms: Option<u64>,
+    /// attestation requests sent to the peer
+    pub requests_sent: u64,
+    /// sent requests that failed, e.g. timed out or the peer was unreachable
+    pub requests_failed: u64,
+    pub responses_received: u64,
+    pub requests_received: u64,
+    /// answered over failed and answered requests, none before the first outcome
+    pub success_rate: Option<f64>,
+    /// unix timestamp in seconds the peer was last heard from
+    pub last_seen: Option<u64>,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// agent version the peer announced over identify, e.g. `vane/0.1.0`
+    pub agent_version: Option<String>,
+    /// vane protocol version the peer announced over identify
+    pub protocol_version: Option<String>,
 }
 
 /// Supported tokens
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize, Encode, Decode)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Deserialize, Serialize, Encode, Decode)]
 pub enum Token {
     Dot,
     Bnb,
@@ -251,23 +1012,35 @@ impl From<Token> for String {
     }
 }
 
-impl From<&str> for Token {
-    fn from(value: &str) -> Self {
+/// token of a name sent by a client, unknown names are refused
+impl FromStr for Token {
+    type Err = VaneRpcError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
         match value {
-            "Dot" => Token::Dot,
-            "Bnb" => Token::Bnb,
-            "Sol" => Token::Sol,
-            "Eth" => Token::Eth,
-            "UsdtSol" => Token::UsdtSol,
-            "UsdcSol" => Token::UsdcSol,
-            "UsdtEth" => Token::UsdtEth,
-            "UsdcEth" => Token::UsdcEth,
-            "UsdtDot" => Token::UsdtDot,
-            _ => unreachable!(),
+            "Dot" => Ok(Token::Dot),
+            "Bnb" => Ok(Token::Bnb),
+            "Sol" => Ok(Token::Sol),
+            "Eth" => Ok(Token::Eth),
+            "UsdtSol" => Ok(Token::UsdtSol),
+            "UsdcSol" => Ok(Token::UsdcSol),
+            "UsdtEth" => Ok(Token::UsdtEth),
+            "UsdcEth" => Ok(Token::UsdcEth),
+            "UsdtDot" => Ok(Token::UsdtDot),
+            _ => Err(VaneRpcError::InvalidParams {
+                reason: alloc::format!("unknown token {value}"),
+            }),
         }
     }
 }
 
+/// token of a name the node wrote itself, names from clients go through `FromStr`
+impl From<&str> for Token {
+    fn from(value: &str) -> Self {
+        value.parse().unwrap_or_else(|_| unreachable!())
+    }
+}
+
 impl From<Token> for ChainSupported {
     fn from(value: Token) -> Self {
         match value {
@@ -279,8 +1052,42 @@ impl From<Token> for ChainSupported {
     }
 }
 
+impl Token {
+    /// number of decimals of the token smallest unit
+    pub fn decimals(&self) -> u32 {
+        match self {
+            Token::Dot => 10,
+            Token::Bnb | Token::Eth => 18,
+            Token::Sol => 9,
+            Token::UsdtSol | Token::UsdcSol | Token::UsdtEth | Token::UsdcEth | Token::UsdtDot => 6,
+        }
+    }
+
+    /// ticker shown to users
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Token::Dot => "DOT",
+            Token::Bnb => "BNB",
+            Token::Sol => "SOL",
+            Token::Eth => "ETH",
+            Token::UsdtSol | Token::UsdtEth | Token::UsdtDot => "USDT",
+            Token::UsdcSol | Token::UsdcEth => "USDC",
+        }
+    }
+
+    /// native token of the network
+    pub fn native(network: ChainSupported) -> Self {
+        match network {
+            ChainSupported::Polkadot => Token::Dot,
+            ChainSupported::Ethereum => Token::Eth,
+            ChainSupported::Bnb => Token::Bnb,
+            ChainSupported::Solana => Token::Sol,
+        }
+    }
+}
+
 /// Supported blockchain networks along with rpc provider url
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize, Encode, Decode, Copy)]
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize, Encode, Decode, Copy, Hash)]
 pub enum ChainSupported {
     Polkadot,
     Ethereum,
@@ -305,20 +1112,30 @@ impl From<ChainSupported> for String {
     }
 }
 
-impl From<&str> for ChainSupported {
-    fn from(value: &str) -> Self {
+/// network of a name sent by a client, unknown names are refused
+impl FromStr for ChainSupported {
+    type Err = VaneRpcError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
         match value {
-            "Polkadot" => ChainSupported::Polkadot,
-            "Ethereum" => ChainSupported::Ethereum,
-            "Bnb" => ChainSupported::Bnb,
-            "Solana" => ChainSupported::Solana,
-            _ => {
-                unreachable!()
-            }
+            "Polkadot" => Ok(ChainSupported::Polkadot),
+            "Ethereum" => Ok(ChainSupported::Ethereum),
+            "Bnb" => Ok(ChainSupported::Bnb),
+            "Solana" => Ok(ChainSupported::Solana),
+            _ => Err(VaneRpcError::UnsupportedNetwork {
+                network: value.to_string(),
+            }),
         }
     }
 }
 
+/// network of a name the node wrote itself, names from clients go through `FromStr`
+impl From<&str> for ChainSupported {
+    fn from(value: &str) -> Self {
+        value.parse().unwrap_or_else(|_| unreachable!())
+    }
+}
+
 impl ChainSupported {
     // Associated constants representing network URLs or other constants
     const POLKADOT_URL: &'static str = "wss://polkadot-rpc.dwellir.com";
@@ -390,6 +1207,15 @@ pub struct Discovery {
     pub peer_id: Option<String>,
     pub multi_addr: Option<String>,
     pub account_ids: Vec<String>,
+    /// third party attestations of the peer accounts, unverified as stored in the registry
+    #[serde(default)]
+    pub attestations: Vec<IdentityAttestation>,
+    /// revocations of the peer accounts, unverified as stored in the registry
+    #[serde(default)]
+    pub revocations: Vec<AccountRevocation>,
+    /// tokens the peer accounts accept, as advertised by the peer
+    #[serde(default)]
+    pub accepted_assets: Vec<AcceptedAssets>,
 }
 
 impl From<Discovery> for PeerRecord {
@@ -479,6 +1305,15 @@ pub struct Fields {
     pub account_id3: Option<String>,
     #[serde(rename = "accountId4", default)]
     pub account_id4: Option<String>,
+    /// json list of `IdentityAttestation`
+    #[serde(default)]
+    pub attestations: Option<String>,
+    /// json list of `AccountRevocation`
+    #[serde(default)]
+    pub revocations: Option<String>,
+    /// json list of `AcceptedAssets`
+    #[serde(rename = "acceptedAssets", default)]
+    pub accepted_assets: Option<String>,
 }
 
 #[cfg(feature = "e2e")]
@@ -491,6 +1326,9 @@ impl Default for Fields {
             account_id2: Some("2".to_string()),
             account_id3: Some("3".to_string()),
             account_id4: Some("4".to_string()),
+            attestations: None,
+            revocations: None,
+            accepted_assets: None,
         }
     }
 }
@@ -507,12 +1345,23 @@ impl From<PeerRecord> for Fields {
             account_id2: None,
             account_id3: None,
             account_id4: None,
+            attestations: None,
+            revocations: None,
+            accepted_assets: None,
         };
 
         if let Some(acc_1) = value.account_id1 {
             fields.account_id1 = Some(acc_1)
         }
-        // TODO convert all accounts
+        if let Some(acc_2) = value.account_id2 {
+            fields.account_id2 = Some(acc_2)
+        }
+        if let Some(acc_3) = value.account_id3 {
+            fields.account_id3 = Some(acc_3)
+        }
+        if let Some(acc_4) = value.account_id4 {
+            fields.account_id4 = Some(acc_4)
+        }
 
         fields
     }
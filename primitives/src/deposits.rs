@@ -0,0 +1,170 @@
+//! Well known deposit addresses of bridges and exchanges
+//!
+//! evm addresses are valid on every evm chain, so a bridge deposit contract copied from one chain passes
+//! the address checks on another one where nothing, or something else, is deployed at that address.
+//! funds sent there are lost, the table lets the sender be warned before attesting.
+//!
+//! exchanges only credit the networks and tokens they list for an address.
+extern crate alloc;
+use crate::data_structure::{ChainSupported, Token, TxWarning};
+use alloc::string::ToString;
+
+/// bridge deposit contract and the chain it is deployed on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KnownDeposit {
+    pub address: &'static str,
+    pub name: &'static str,
+    /// chain the contract lives on, possibly one vane does not support
+    pub chain: &'static str,
+    /// networks sending to the contract is valid on
+    pub networks: &'static [ChainSupported],
+}
+
+pub const KNOWN_DEPOSITS: [KnownDeposit; 7] = [
+    KnownDeposit {
+        address: "0x4dbd4fc535ac27206064b68ffcf827b0a60bab3f",
+        name: "Arbitrum delayed inbox",
+        chain: "Ethereum",
+        networks: &[ChainSupported::Ethereum],
+    },
+    KnownDeposit {
+        address: "0x99c9fc46f92e8a1c0dec1b1747d010903e884be1",
+        name: "Optimism L1 standard bridge",
+        chain: "Ethereum",
+        networks: &[ChainSupported::Ethereum],
+    },
+    KnownDeposit {
+        address: "0x3154cf16ccdb4c6d922629664174b904d80f2c35",
+        name: "Base L1 standard bridge",
+        chain: "Ethereum",
+        networks: &[ChainSupported::Ethereum],
+    },
+    KnownDeposit {
+        address: "0xa0c68c638235ee32657e8f720a23cec1bfc77c77",
+        name: "Polygon PoS root chain manager",
+        chain: "Ethereum",
+        networks: &[ChainSupported::Ethereum],
+    },
+    KnownDeposit {
+        address: "0x5288c571fd7ad117bea99bf60fe0846c4e84f933",
+        name: "Arbitrum L2 gateway router",
+        chain: "Arbitrum One",
+        networks: &[],
+    },
+    KnownDeposit {
+        address: "0x4200000000000000000000000000000000000010",
+        name: "OP stack L2 standard bridge",
+        chain: "OP Mainnet and Base",
+        networks: &[],
+    },
+    KnownDeposit {
+        address: "0x0000000000000000000000000000000000001004",
+        name: "BNB Smart Chain token hub",
+        chain: "BNB Smart Chain",
+        networks: &[ChainSupported::Bnb],
+    },
+];
+
+/// known deposit contract at `address`
+pub fn known_deposit(address: &str) -> Option<&'static KnownDeposit> {
+    let address = address.to_lowercase();
+    KNOWN_DEPOSITS
+        .iter()
+        .find(|deposit| deposit.address == address)
+}
+
+/// exchange deposit address and what the exchange credits on it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExchangeDeposit {
+    pub address: &'static str,
+    pub exchange: &'static str,
+    /// networks and tokens credited when deposited to the address
+    pub credits: &'static [(ChainSupported, Token)],
+}
+
+const BINANCE_ETHEREUM_CREDITS: &[(ChainSupported, Token)] = &[
+    (ChainSupported::Ethereum, Token::Eth),
+    (ChainSupported::Ethereum, Token::UsdtEth),
+    (ChainSupported::Ethereum, Token::UsdcEth),
+];
+
+pub const KNOWN_EXCHANGE_DEPOSITS: [ExchangeDeposit; 3] = [
+    ExchangeDeposit {
+        address: "0x28c6c06298d514db089934071355e5743bf21d60",
+        exchange: "Binance",
+        credits: BINANCE_ETHEREUM_CREDITS,
+    },
+    ExchangeDeposit {
+        address: "0x21a31ee1afc51d94c2efccaa2092ad1028285549",
+        exchange: "Binance",
+        credits: BINANCE_ETHEREUM_CREDITS,
+    },
+    ExchangeDeposit {
+        address: "0xdfd5293d8e347dfe59e90efd55b2956a1343963d",
+        exchange: "Binance",
+        credits: BINANCE_ETHEREUM_CREDITS,
+    },
+];
+
+/// known exchange deposit address
+pub fn exchange_deposit(address: &str) -> Option<&'static ExchangeDeposit> {
+    let address = address.to_lowercase();
+    KNOWN_EXCHANGE_DEPOSITS
+        .iter()
+        .find(|deposit| deposit.address == address)
+}
+
+impl ExchangeDeposit {
+    /// warning when the exchange does not credit `token` on `network` at this address
+    pub fn check(&self, network: ChainSupported, token: Token) -> Option<TxWarning> {
+        if self.credits.contains(&(network, token)) {
+            return None;
+        }
+        Some(TxWarning::ExchangeNotCredited {
+            exchange: self.exchange.to_string(),
+            token,
+            network,
+        })
+    }
+}
+
+/// known deposit contract at `address` which does not live on `network`, sending to it is a loss
+pub fn cross_chain_deposit(
+    address: &str,
+    network: ChainSupported,
+) -> Option<&'static KnownDeposit> {
+    known_deposit(address).filter(|deposit| !deposit.networks.contains(&network))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deposits_on_other_chains_are_detected() {
+        let l1_bridge = "0x99C9fc46f92E8a1c0deC1b1747d010903E884bE1";
+        assert!(cross_chain_deposit(l1_bridge, ChainSupported::Ethereum).is_none());
+        assert!(cross_chain_deposit(l1_bridge, ChainSupported::Bnb).is_some());
+
+        let l2_bridge = "0x4200000000000000000000000000000000000010";
+        let deposit = cross_chain_deposit(l2_bridge, ChainSupported::Ethereum)
+            .expect("l2 bridge is not on ethereum");
+        assert_eq!(deposit.chain, "OP Mainnet and Base");
+
+        assert!(known_deposit("0x63F9725f107358c9115BC9d86c72dD5823E9B1E6").is_none());
+    }
+
+    #[test]
+    fn exchange_deposits_need_credited_tokens() {
+        let binance = exchange_deposit("0x28C6c06298d514Db089934071355E5743bf21d60")
+            .expect("known binance deposit address");
+        assert_eq!(
+            binance.check(ChainSupported::Ethereum, Token::UsdtEth),
+            None
+        );
+        assert!(matches!(
+            binance.check(ChainSupported::Bnb, Token::Bnb),
+            Some(TxWarning::ExchangeNotCredited { .. })
+        ));
+    }
+}
@@ -0,0 +1,164 @@
+//! Device sync records
+//!
+//! a user links its devices by showing a pairing code on a paired device and entering it, or
+//! scanning it as a QR code, on the new one. paired devices derive a session key from their x25519
+//! keys and exchange their contacts, policies and pending transfers sealed with it. the latest
+//! update of a record wins.
+extern crate alloc;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// scheme of the pairing uri shown as a QR code
+pub const PAIRING_URI_SCHEME: &str = "vane-pair:";
+
+/// pairing code a paired device shows to link a new one
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevicePairingCode {
+    /// `vane-pair:<code>@<multi addr>`, the content of the QR code
+    pub uri: String,
+    /// short code to enter by hand along with the multi addr
+    pub code: String,
+    /// unix timestamp in seconds, the code is refused afterwards
+    pub expires_at: u64,
+}
+
+impl DevicePairingCode {
+    pub fn uri(code: &str, multi_addr: &str) -> String {
+        alloc::format!("{PAIRING_URI_SCHEME}{code}@{multi_addr}")
+    }
+
+    /// code and multi addr of a pairing uri
+    pub fn parse_uri(uri: &str) -> Option<(String, String)> {
+        let (code, multi_addr) = uri.strip_prefix(PAIRING_URI_SCHEME)?.split_once('@')?;
+        if code.is_empty() || multi_addr.is_empty() {
+            return None;
+        }
+        Some((code.to_string(), multi_addr.to_string()))
+    }
+}
+
+/// device of the same user this node syncs with
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct PairedDevice {
+    pub peer_id: String,
+    pub multi_addr: String,
+    /// x25519 public key the session key is derived from
+    pub encryption_key: [u8; 32],
+    /// unix timestamp in seconds
+    pub paired_at: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+pub enum SyncKind {
+    /// encoded saved peer record, keyed by its peer id
+    Contact,
+    /// policy document
+    Policies,
+    /// encoded pending txs of the device keyed by its peer id
+    PendingTransfers,
+}
+
+/// synced value, stored in the sync table of every paired device
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncRecord {
+    pub kind: SyncKind,
+    pub key: String,
+    pub value: Vec<u8>,
+    /// unix timestamp in seconds of the update on the device it was made on
+    pub updated_at: u64,
+}
+
+impl SyncRecord {
+    /// key of the record in the sync table
+    pub fn id(&self) -> String {
+        alloc::format!("{:?}/{}", self.kind, self.key)
+    }
+
+    /// whether this record replaces `stored`, the same record updated earlier
+    pub fn supersedes(&self, stored: &SyncRecord) -> bool {
+        self.id() == stored.id() && self.updated_at > stored.updated_at
+    }
+}
+
+/// records sealed with the session key of the paired devices
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct SealedRecords {
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+}
+
+/// requests of the device sync protocol
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum SyncRequest {
+    /// the new device proves it got the pairing code, `proof` is `pairing_proof` of the code
+    Pair {
+        proof: [u8; 32],
+        encryption_key: [u8; 32],
+        multi_addr: String,
+    },
+    /// the records of the requesting device, answered with the records of the other one
+    Records(SealedRecords),
+}
+
+/// responses of the device sync protocol
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum SyncResponse {
+    Paired {
+        encryption_key: [u8; 32],
+        multi_addr: String,
+    },
+    Records(SealedRecords),
+    Refused(String),
+}
+
+/// hash binding the pairing code to the new device, so the code itself is never sent
+pub fn pairing_proof(code: &str, peer_id: &str, encryption_key: &[u8; 32]) -> [u8; 32] {
+    sp_core::hashing::blake2_256(
+        &[
+            b"vane device pairing".as_slice(),
+            code.as_bytes(),
+            peer_id.as_bytes(),
+            encryption_key,
+        ]
+        .concat(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairing_uri_round_trips() {
+        let multi_addr = "/ip4/127.0.0.1/tcp/30333/p2p/12D3KooWPeer";
+        let uri = DevicePairingCode::uri("04825113", multi_addr);
+        assert_eq!(
+            DevicePairingCode::parse_uri(&uri),
+            Some(("04825113".to_string(), multi_addr.to_string()))
+        );
+        assert_eq!(
+            DevicePairingCode::parse_uri("vane-pair:@/ip4/127.0.0.1"),
+            None
+        );
+        assert_eq!(DevicePairingCode::parse_uri(multi_addr), None);
+    }
+
+    #[test]
+    fn only_a_later_update_of_the_same_record_supersedes() {
+        let record = |kind, key: &str, updated_at| SyncRecord {
+            kind,
+            key: key.into(),
+            value: Vec::new(),
+            updated_at,
+        };
+        let stored = record(SyncKind::Contact, "12D3KooWPeer", 10);
+        assert!(record(SyncKind::Contact, "12D3KooWPeer", 11).supersedes(&stored));
+        assert!(!record(SyncKind::Contact, "12D3KooWPeer", 10).supersedes(&stored));
+        assert!(!record(SyncKind::PendingTransfers, "12D3KooWPeer", 11).supersedes(&stored));
+    }
+}
@@ -0,0 +1,476 @@
+//! Stuck transfer diagnostics
+//!
+//! most support requests are a transfer that does not move on. the diagnosis folds the event log of
+//! the tx with what the node found out about the chain and tells at which step the transfer waits,
+//! on whom, since when, and what the user can do about it.
+extern crate alloc;
+use crate::data_structure::{RejectReason, TxEvent, TxStateMachine, TxStatus, H256};
+use crate::explorer::{explorer_url, ChainReceipt};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// step a transfer waits at, or ended at
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TxStage {
+    /// the attestation request did not reach the receiver node yet
+    AwaitingDelivery,
+    /// the receiver node could not be dialed and no relay queued the request
+    PeerUnreachable,
+    /// the receiver node got the request, its user did not confirm yet
+    AwaitingReceiver,
+    /// an attestation request waiting for the receiver of this node to confirm or decline
+    AwaitingYourConfirmation,
+    /// attested by the receiver, the sender did not sign yet
+    AwaitingSenderSignature,
+    /// signed by the sender, the designated approver did not sign off yet
+    AwaitingSecondApproval,
+    /// signed, being submitted to the chain
+    Submitting,
+    /// submitted, not included in a block yet
+    MempoolPending,
+    /// the chain provider refused the tx or does not answer
+    ProviderError,
+    Included,
+    /// included in a block but reverted
+    Reverted,
+    /// ended without a chain tx, e.g. declined or the receiver is not registered
+    Ended,
+}
+
+impl TxStage {
+    /// how long a transfer usually waits at the stage before it is considered stuck, none when
+    /// nothing follows
+    pub fn expected_secs(&self) -> Option<u64> {
+        match self {
+            TxStage::AwaitingDelivery | TxStage::Submitting => Some(60),
+            TxStage::MempoolPending => Some(600),
+            TxStage::AwaitingSenderSignature | TxStage::AwaitingSecondApproval => Some(900),
+            TxStage::AwaitingReceiver | TxStage::AwaitingYourConfirmation => Some(3600),
+            TxStage::PeerUnreachable
+            | TxStage::ProviderError
+            | TxStage::Included
+            | TxStage::Reverted
+            | TxStage::Ended => None,
+        }
+    }
+}
+
+/// step a stuck transfer is driven again from, using the state the node persisted
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RetryStage {
+    /// dial the receiver devices again and resend the attestation request
+    Attestation,
+    /// build the chain tx again, the sender signs the new one
+    Simulation,
+    /// submit the signed tx again, or broadcast the submitted one again while it is not included
+    Broadcast,
+}
+
+impl RetryStage {
+    /// stage a sent tx is driven again from when the caller does not pick one, from the `events`
+    /// of the tx in recorded order, none when there is nothing to retry
+    pub fn of(events: &[TxEvent]) -> Option<Self> {
+        [
+            RetryStage::Broadcast,
+            RetryStage::Simulation,
+            RetryStage::Attestation,
+        ]
+        .into_iter()
+        .find(|stage| stage.applies_to_events(events))
+    }
+
+    /// the tx of `events` can be driven again from the stage. the chain tx is only built again
+    /// once the receiver attested, a tx failed before that is never simulated
+    pub fn applies_to_events(&self, events: &[TxEvent]) -> bool {
+        let Some(latest) = events.last() else {
+            return false;
+        };
+        let attested = events.iter().any(|event| {
+            matches!(
+                event.status,
+                TxStatus::RecvAddrConfirmationPassed | TxStatus::NetConfirmed
+            )
+        });
+        self.applies_to(&latest.tx) && (*self != RetryStage::Simulation || attested)
+    }
+
+    /// `tx` can be driven again from the stage as far as its latest status tells, only on the
+    /// sender node
+    pub fn applies_to(&self, tx: &TxStateMachine) -> bool {
+        if tx.inbound_req_id.is_some() {
+            return false;
+        }
+        let signed = tx.signed_call_payload.is_some();
+        match self {
+            RetryStage::Attestation => matches!(
+                tx.status,
+                TxStatus::Genesis
+                    | TxStatus::DeliveredToReceiver
+                    | TxStatus::ReceiverUnreachable(_)
+            ),
+            RetryStage::Simulation => match tx.status {
+                TxStatus::RecvAddrConfirmationPassed | TxStatus::NetConfirmed => true,
+                TxStatus::FailedToSubmitTxn(_) => !signed,
+                _ => false,
+            },
+            RetryStage::Broadcast => match tx.status {
+                TxStatus::SenderConfirmed | TxStatus::TxSubmissionPassed(_) => true,
+                TxStatus::FailedToSubmitTxn(_) => signed,
+                _ => false,
+            },
+        }
+    }
+}
+
+/// what the node found out about a tx on chain, or about the chain provider before it was submitted
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ChainLookup {
+    /// not looked up, the tx is not at a chain stage or the node has no client for the network
+    #[default]
+    Skipped,
+    /// the provider answers, the submitted tx has no receipt yet
+    Pending,
+    Included(ChainReceipt),
+    ProviderError(String),
+}
+
+/// where a transfer is and what to do about it
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxDiagnosis {
+    pub tx_id: H256,
+    pub stage: TxStage,
+    /// the transfer waits at its stage for longer than `TxStage::expected_secs`
+    pub stuck: bool,
+    /// unix timestamp in seconds of the latest transition
+    pub since: u64,
+    pub waiting_secs: u64,
+    /// what happened, in a sentence
+    pub detail: String,
+    /// what the user can do, most useful first
+    pub actions: Vec<String>,
+    /// explorer page of the submitted tx
+    pub explorer_url: Option<String>,
+}
+
+impl TxDiagnosis {
+    /// diagnose a tx from its events in recorded order, none without any
+    pub fn of(events: &[TxEvent], chain: ChainLookup, now: u64) -> Option<Self> {
+        let latest = events.last()?;
+        let tx = &latest.tx;
+        // attestation requests reach the receiver node with the inbound request id set
+        let receiving = tx.inbound_req_id.is_some();
+        let mut explorer = None;
+        let (stage, detail, actions): (TxStage, String, Vec<&str>) = match &latest.status {
+            TxStatus::Genesis if receiving => (
+                TxStage::AwaitingYourConfirmation,
+                "the sender waits for the receiver to attest the address".into(),
+                vec!["confirm or decline the request, see `vane inbox`"],
+            ),
+            TxStatus::Genesis => (
+                TxStage::AwaitingDelivery,
+                "the attestation request was not acknowledged by the receiver node yet".into(),
+                vec![
+                    "ask the receiver to start its vane node",
+                    "check the node has peers with `vane peers stats`",
+                    "resend the request with `vane retry`",
+                ],
+            ),
+            TxStatus::DeliveredToReceiver => (
+                TxStage::AwaitingReceiver,
+                "the receiver node got the request, the receiver did not confirm yet".into(),
+                vec!["ask the receiver to confirm the transfer in vane"],
+            ),
+            TxStatus::RecvAddrConfirmed
+            | TxStatus::RecvAddrConfirmationPassed
+            | TxStatus::NetConfirmed
+                if receiving =>
+            {
+                (
+                    TxStage::AwaitingSenderSignature,
+                    "the address was attested, the sender did not sign yet".into(),
+                    vec!["ask the sender to confirm the transfer"],
+                )
+            }
+            TxStatus::RecvAddrConfirmed
+            | TxStatus::RecvAddrConfirmationPassed
+            | TxStatus::NetConfirmed => (
+                TxStage::AwaitingSenderSignature,
+                "the receiver attested the address, the transfer waits for the sender signature"
+                    .into(),
+                vec!["review and sign the transfer with `vane pending` and `vane confirm`"],
+            ),
+            TxStatus::AwaitingSecondApproval => (
+                TxStage::AwaitingSecondApproval,
+                "the sender signed, the designated approver did not sign off yet".into(),
+                vec!["ask the designated approver to approve the transfer"],
+            ),
+            TxStatus::SenderConfirmed => match &chain {
+                ChainLookup::ProviderError(reason) => (
+                    TxStage::ProviderError,
+                    format!("the chain provider does not answer: {reason}"),
+                    vec!["check the chain rpc endpoint of the node with `vane tui`"],
+                ),
+                _ => (
+                    TxStage::Submitting,
+                    "the transfer is signed and being submitted".into(),
+                    vec!["submit the signed tx again with `vane retry`"],
+                ),
+            },
+            TxStatus::TxSubmissionPassed(tx_hash) => {
+                explorer = Some(explorer_url(tx.network, tx.environment, tx_hash));
+                match &chain {
+                    ChainLookup::Included(receipt) if receipt.success => (
+                        TxStage::Included,
+                        format!("included in block {}", receipt.block_number),
+                        vec![],
+                    ),
+                    ChainLookup::Included(receipt) => (
+                        TxStage::Reverted,
+                        format!("included in block {} but reverted", receipt.block_number),
+                        vec!["check the receiver contract accepts the transfer"],
+                    ),
+                    ChainLookup::ProviderError(reason) => (
+                        TxStage::ProviderError,
+                        format!("the chain provider did not tell the tx status: {reason}"),
+                        vec![
+                            "check the chain rpc endpoint of the node with `vane tui`",
+                            "follow the tx on the explorer",
+                        ],
+                    ),
+                    ChainLookup::Pending | ChainLookup::Skipped => (
+                        TxStage::MempoolPending,
+                        "submitted, not included in a block yet".into(),
+                        vec![
+                            "the fee may be below the current base fee, compare with `vane fees`",
+                            "broadcast it again with `vane retry` if the explorer does not know it",
+                            "follow the tx on the explorer",
+                        ],
+                    ),
+                }
+            }
+            TxStatus::FailedToSubmitTxn(reason) => (
+                TxStage::ProviderError,
+                format!("the chain refused the tx: {reason}"),
+                vec![
+                    "check the sender balance covers the amount and the fee",
+                    "submit it again with `vane retry`",
+                ],
+            ),
+            TxStatus::ReceiverUnreachable(reason) => (
+                TxStage::PeerUnreachable,
+                format!("the receiver node could not be reached: {reason}"),
+                vec!["resend the request with `vane retry` once the receiver node is online"],
+            ),
+            TxStatus::ReceiverNotRegistered => (
+                TxStage::Ended,
+                "the receiver address is not registered to vane".into(),
+                vec!["ask the receiver to register the address with vane"],
+            ),
+            TxStatus::ReceiverIncompatible(reason) => (
+                TxStage::Ended,
+                format!("the receiver node runs an incompatible version: {reason}"),
+                vec!["ask the receiver to update vane"],
+            ),
+            TxStatus::RecvAddrFailed => (
+                TxStage::Ended,
+                "the receiver declined the transfer or failed to attest the address".into(),
+                vec!["check the receiver address with the receiver"],
+            ),
+            TxStatus::SenderConfirmationfailed => (
+                TxStage::Ended,
+                "the sender signature did not match the sender address".into(),
+                vec!["send the transfer again, signing with the sender account"],
+            ),
+            TxStatus::AnsweredOnOtherDevice => (
+                TxStage::Ended,
+                "another device of the receiver answered the request".into(),
+                vec![],
+            ),
+            TxStatus::Expired => (
+                TxStage::Ended,
+                "the deadline of the sender passed before the transfer was submitted".into(),
+                vec!["send the transfer again with a later deadline"],
+            ),
+            TxStatus::Withdrawn(reason) => (
+                TxStage::Ended,
+                format!("the node withdrew the transfer: {reason}"),
+                vec![],
+            ),
+            TxStatus::RecvRejected(reason) => match &latest.tx.address_correction {
+                Some(correction) => (
+                    TxStage::Ended,
+                    format!(
+                        "the receiver rejected the transfer: {reason:?}, it proposed {} on {:?}",
+                        correction.address, correction.network
+                    ),
+                    vec!["send it there with `vane restart`"],
+                ),
+                None => (
+                    TxStage::Ended,
+                    format!("the receiver rejected the transfer: {reason:?}"),
+                    match reason {
+                        RejectReason::NotMyAddress => {
+                            vec!["check the receiver address with the receiver"]
+                        }
+                        RejectReason::WrongChain => {
+                            vec!["ask the receiver which network to send on and send there"]
+                        }
+                        RejectReason::UnexpectedAmount => {
+                            vec!["agree on the amount with the receiver and send it again"]
+                        }
+                        RejectReason::SuspectedPhishing => {
+                            vec!["contact the receiver through a channel it knows you by first"]
+                        }
+                    },
+                ),
+            },
+        };
+
+        let waiting_secs = now.saturating_sub(latest.recorded_at);
+        Some(TxDiagnosis {
+            tx_id: latest.tx_id,
+            stage,
+            stuck: stage
+                .expected_secs()
+                .is_some_and(|expected| waiting_secs > expected),
+            since: latest.recorded_at,
+            waiting_secs,
+            detail,
+            actions: actions.into_iter().map(ToString::to_string).collect(),
+            explorer_url: explorer,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structure::TxStateMachine;
+
+    #[test]
+    fn diagnoses_tell_where_a_transfer_waits() {
+        let event = |status: TxStatus, at: u64| {
+            TxEvent::new(
+                TxStateMachine {
+                    status,
+                    tx_nonce: 1,
+                    ..Default::default()
+                },
+                at,
+            )
+        };
+        let events = vec![
+            event(TxStatus::Genesis, 100),
+            event(TxStatus::DeliveredToReceiver, 110),
+        ];
+        let waiting = TxDiagnosis::of(&events, ChainLookup::Skipped, 200).unwrap();
+        assert_eq!(waiting.stage, TxStage::AwaitingReceiver);
+        assert_eq!((waiting.since, waiting.waiting_secs), (110, 90));
+        assert!(!waiting.stuck);
+        assert!(
+            TxDiagnosis::of(&events, ChainLookup::Skipped, 110 + 3601)
+                .unwrap()
+                .stuck
+        );
+
+        let submitted = vec![event(TxStatus::TxSubmissionPassed([1; 32]), 100)];
+        let pending = TxDiagnosis::of(&submitted, ChainLookup::Pending, 1000).unwrap();
+        assert_eq!(pending.stage, TxStage::MempoolPending);
+        assert!(pending.stuck);
+        assert!(pending.explorer_url.is_some());
+        let reverted = ChainLookup::Included(ChainReceipt {
+            block_number: 7,
+            success: false,
+            ..Default::default()
+        });
+        assert_eq!(
+            TxDiagnosis::of(&submitted, reverted, 1000).unwrap().stage,
+            TxStage::Reverted
+        );
+
+        let signing = vec![event(TxStatus::SenderConfirmed, 100)];
+        let provider_down = ChainLookup::ProviderError("connection refused".into());
+        let diagnosis = TxDiagnosis::of(&signing, provider_down, 120).unwrap();
+        assert_eq!(diagnosis.stage, TxStage::ProviderError);
+        assert!(!diagnosis.actions.is_empty());
+
+        let mut request = event(TxStatus::Genesis, 100);
+        request.tx.inbound_req_id = Some(3);
+        assert_eq!(
+            TxDiagnosis::of(&[request], ChainLookup::Skipped, 100)
+                .unwrap()
+                .stage,
+            TxStage::AwaitingYourConfirmation
+        );
+        assert!(TxDiagnosis::of(&[], ChainLookup::Skipped, 100).is_none());
+    }
+
+    #[test]
+    fn retries_start_from_the_stage_the_transfer_is_stuck_at() {
+        let tx = |status: TxStatus, signed: bool| TxStateMachine {
+            status,
+            signed_call_payload: signed.then(|| vec![1; 65]),
+            ..Default::default()
+        };
+        let log = |statuses: Vec<TxStatus>, signed: bool| {
+            statuses
+                .into_iter()
+                .map(|status| TxEvent::new(tx(status, signed), 100))
+                .collect::<Vec<_>>()
+        };
+        let unreachable = log(
+            vec![
+                TxStatus::Genesis,
+                TxStatus::ReceiverUnreachable("dial timed out".into()),
+            ],
+            false,
+        );
+        assert_eq!(RetryStage::of(&unreachable), Some(RetryStage::Attestation));
+        // the chain refused to build the attested tx, or refused the signed one
+        let failed = |signed| {
+            log(
+                vec![
+                    TxStatus::Genesis,
+                    TxStatus::RecvAddrConfirmationPassed,
+                    TxStatus::FailedToSubmitTxn("nonce too low".into()),
+                ],
+                signed,
+            )
+        };
+        assert_eq!(RetryStage::of(&failed(false)), Some(RetryStage::Simulation));
+        assert_eq!(RetryStage::of(&failed(true)), Some(RetryStage::Broadcast));
+        // the receiver never attested a tx failed at genesis
+        let unattested = log(
+            vec![
+                TxStatus::Genesis,
+                TxStatus::FailedToSubmitTxn("nonce too low".into()),
+            ],
+            false,
+        );
+        assert_eq!(RetryStage::of(&unattested), None);
+        let withdrawn = log(
+            vec![
+                TxStatus::Genesis,
+                TxStatus::RecvAddrConfirmationPassed,
+                TxStatus::Withdrawn("rerouted".into()),
+            ],
+            false,
+        );
+        assert_eq!(RetryStage::of(&withdrawn), None);
+        let submitted = tx(TxStatus::TxSubmissionPassed([1; 32]), true);
+        assert!(!RetryStage::Attestation.applies_to(&submitted));
+        assert_eq!(
+            RetryStage::of(&log(vec![TxStatus::RecvAddrFailed], false)),
+            None
+        );
+        assert_eq!(RetryStage::of(&[]), None);
+
+        let mut receiving = TxEvent::new(tx(TxStatus::Genesis, false), 100);
+        receiving.tx.inbound_req_id = Some(3);
+        assert_eq!(RetryStage::of(&[receiving]), None);
+    }
+}
@@ -0,0 +1,84 @@
+//! Solana durable nonces
+//!
+//! a solana tx is signed against a recent blockhash and expires about a minute later, sooner than a
+//! hardware signer or a slow receiver completes the attestation round. a tx signed against the
+//! nonce stored in a nonce account instead stays valid until the nonce is advanced, which its first
+//! instruction does when it lands. the sender is the authority of the nonce account so its single
+//! signature covers both the advance and the transfer.
+extern crate alloc;
+use alloc::string::String;
+use anyhow::anyhow;
+use base58::ToBase58;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// size of the data of a nonce account
+pub const NONCE_ACCOUNT_LENGTH: usize = 80;
+/// system program owning the nonce accounts
+pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+/// sysvar the advance nonce instruction reads the blockhash from
+pub const RECENT_BLOCKHASHES_SYSVAR: &str = "SysvarRecentB1ockHashes11111111111111111111";
+/// data of the system `AdvanceNonceAccount` instruction, the first instruction of the tx
+pub const ADVANCE_NONCE_ACCOUNT_DATA: [u8; 4] = [4, 0, 0, 0];
+
+/// nonce a tx is signed against instead of a recent blockhash
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct DurableNonce {
+    /// nonce account, base58
+    pub nonce_account: String,
+    /// account allowed to advance the nonce, base58
+    pub authority: String,
+    /// blockhash stored in the account, the recent blockhash of the tx, base58
+    pub nonce: String,
+}
+
+impl DurableNonce {
+    /// nonce stored in the `data` of `nonce_account`, `None` while the account is not initialized
+    pub fn from_account_data(
+        nonce_account: &str,
+        data: &[u8],
+    ) -> Result<Option<Self>, anyhow::Error> {
+        if data.len() != NONCE_ACCOUNT_LENGTH {
+            Err(anyhow!(
+                "{nonce_account} holds {} bytes, not a nonce account",
+                data.len()
+            ))?
+        }
+        // versions, legacy or current, then state, uninitialized or initialized
+        let state = u32::from_le_bytes(data[4..8].try_into()?);
+        if state == 0 {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            nonce_account: nonce_account.into(),
+            authority: data[8..40].to_base58(),
+            nonce: data[40..72].to_base58(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn nonces_are_read_from_initialized_accounts() {
+        let mut data = vec![0; NONCE_ACCOUNT_LENGTH];
+        assert_eq!(
+            DurableNonce::from_account_data("nonce", &data).unwrap(),
+            None
+        );
+        data[0] = 1;
+        data[4] = 1;
+        data[8..40].copy_from_slice(&[7; 32]);
+        data[40..72].copy_from_slice(&[9; 32]);
+        let nonce = DurableNonce::from_account_data("nonce", &data)
+            .unwrap()
+            .unwrap();
+        assert_eq!(nonce.authority, [7; 32].to_base58());
+        assert_eq!(nonce.nonce, [9; 32].to_base58());
+        assert!(DurableNonce::from_account_data("nonce", &data[..72]).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Versioned p2p message envelopes
+//!
+//! p2p messages are scale encoded, which has no room for unknown fields: a node decoding a message
+//! that gained a field fails, so any field addition used to split the network until every node
+//! upgraded. messages are sealed in an envelope carrying the wire version of the sender and the
+//! length of the payload. fields are only ever appended to a message, so a node decodes the fields
+//! it knows from the payload of a newer node and ignores the rest, and decodes the payloads of the
+//! previous version with the defaults of the fields they miss. bare payloads of nodes older than
+//! the envelope are version 0.
+extern crate alloc;
+use crate::data_structure::TxStateMachine;
+use alloc::vec::Vec;
+use codec::{Compact, Decode, Encode};
+
+/// wire version of the p2p messages of this node, bumped whenever a message gains a field
+pub const WIRE_VERSION: u8 = 10;
+/// first bytes of an envelope, never the start of a bare payload as 0xff is neither a valid compact
+/// length nor the variant index of a message
+pub const ENVELOPE_MAGIC: [u8; 2] = [0xff, b'v'];
+
+/// seal `message` in an envelope of `WIRE_VERSION`
+pub fn seal<T: Encode>(message: &T) -> Vec<u8> {
+    let payload = message.encode();
+    let mut envelope = ENVELOPE_MAGIC.to_vec();
+    envelope.push(WIRE_VERSION);
+    Compact(payload.len() as u32).encode_to(&mut envelope);
+    envelope.extend_from_slice(&payload);
+    envelope
+}
+
+/// wire version and payload of an envelope, a bare payload is returned as version 0
+pub fn open(bytes: &[u8]) -> Result<(u8, &[u8]), codec::Error> {
+    let Some(mut rest) = bytes.strip_prefix(&ENVELOPE_MAGIC[..]) else {
+        return Ok((0, bytes));
+    };
+    let version = u8::decode(&mut rest)?;
+    let length = Compact::<u32>::decode(&mut rest)?.0 as usize;
+    let payload = rest
+        .get(..length)
+        .ok_or("envelope payload shorter than its length")?;
+    Ok((version, payload))
+}
+
+/// decode a message whose layout is the same in every wire version, the fields a newer node
+/// appended are ignored
+pub fn decode<T: Decode>(bytes: &[u8]) -> Result<T, codec::Error> {
+    let (_, mut payload) = open(bytes)?;
+    T::decode(&mut payload)
+}
+
+/// decode a tx sent by a node of any wire version
+pub fn decode_tx(bytes: &[u8]) -> Result<TxStateMachine, codec::Error> {
+    let (version, payload) = open(bytes)?;
+    // encoded defaults of the fields appended since `version`
+    let missing: &[u8] = match version {
+        // the multi id version appended in version 1, left at 0 so the tx is refused as coming
+        // from an outdated client
+        0 => &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        // no reference and no tags, appended in version 2
+        1 => &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        // no address correction, accepted amount, parent payment nor travel rule data, appended
+        // in version 3
+        2 => &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        // no risk assessment, appended in version 4
+        3 => &[0, 0, 0, 0, 0, 0, 0],
+        // no deadline, appended in version 5
+        4 => &[0, 0, 0, 0, 0, 0],
+        // normal priority, appended in version 6
+        5 => &[0, 0, 0, 0, 0],
+        // no fiat quote, appended in version 7
+        6 => &[0, 0, 0, 0],
+        // no sponsorship, appended in version 8
+        7 => &[0, 0, 0],
+        // no durable nonce, appended in version 9
+        8 => &[0, 0],
+        // no receiver token account, appended in version 10
+        9 => &[0],
+        _ => &[],
+    };
+    TxStateMachine::decode(&mut &[payload, missing].concat()[..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn txs_of_every_wire_version_decode() {
+        let tx = TxStateMachine {
+            tx_nonce: 7,
+            amount: 10,
+            multi_id_version: 1,
+            ..Default::default()
+        };
+        assert_eq!(decode_tx(&seal(&tx)).ok(), Some(tx.clone()));
+        let envelope = |version: u8, payload: &[u8]| {
+            let mut envelope = ENVELOPE_MAGIC.to_vec();
+            envelope.push(version);
+            Compact(payload.len() as u32).encode_to(&mut envelope);
+            [envelope, payload.to_vec()].concat()
+        };
+        let encoded = tx.encode();
+        // fields appended in each wire version, encoded as in `tx`
+        let appended = [
+            (1, tx.multi_id_version.encode()),
+            (2, (&tx.reference, &tx.tags).encode()),
+            (
+                3,
+                (
+                    &tx.address_correction,
+                    &tx.accepted_amount,
+                    &tx.parent_payment,
+                    &tx.travel_rule,
+                )
+                    .encode(),
+            ),
+            (4, tx.risk_assessment.encode()),
+            (5, tx.deadline.encode()),
+            (6, tx.priority.encode()),
+            (7, tx.fiat_quote.encode()),
+            (8, tx.sponsorship.encode()),
+            (9, tx.durable_nonce.encode()),
+            (10, tx.receiver_token_account.encode()),
+        ];
+        assert_eq!(
+            appended.last().map(|(version, _)| *version),
+            Some(WIRE_VERSION)
+        );
+        let tail: Vec<u8> = appended
+            .iter()
+            .flat_map(|(_, fields)| fields.clone())
+            .collect();
+        assert!(encoded.ends_with(&tail));
+
+        for version in 0..WIRE_VERSION {
+            // the payload of a node of `version` lacks the fields appended after it
+            let missing: usize = appended
+                .iter()
+                .filter(|(appended_in, _)| *appended_in > version)
+                .map(|(_, fields)| fields.len())
+                .sum();
+            let payload = &encoded[..encoded.len() - missing];
+            let decoded = match version {
+                // a bare payload of a node older than the envelope, its tx is refused later on for
+                // the multi id version left at 0
+                0 => {
+                    let legacy = decode_tx(payload).unwrap();
+                    assert_eq!(legacy.multi_id_version, 0);
+                    TxStateMachine {
+                        multi_id_version: 1,
+                        ..legacy
+                    }
+                }
+                _ => decode_tx(&envelope(version, payload)).unwrap(),
+            };
+            assert_eq!(decoded, tx, "wire version {version}");
+        }
+
+        // a newer node appended a field
+        let newer = envelope(WIRE_VERSION + 1, &[encoded, vec![42]].concat());
+        assert_eq!(decode_tx(&newer).ok(), Some(tx));
+
+        assert!(open(&[0xff, b'v', 1, 40, 1, 2]).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Network environments
+//!
+//! a node runs each network on its mainnet or on its testnet: sepolia for ethereum, the bnb smart
+//! chain testnet, solana devnet and westend for polkadot. the node picks one environment and may
+//! override it per network, as well as the rpc endpoint of each network. transfers carry the
+//! environment they were initiated on and nodes refuse transfers of another environment, so test
+//! funds are never attested as real ones. dev mode and testnet nodes provision test accounts
+//! registered to the node in one call.
+extern crate alloc;
+use crate::data_structure::ChainSupported;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize, Encode, Decode,
+)]
+pub enum NetworkEnvironment {
+    #[default]
+    Mainnet,
+    Testnet,
+}
+
+impl NetworkEnvironment {
+    /// name of `network` in this environment, e.g. `sepolia`
+    pub fn name(&self, network: ChainSupported) -> &'static str {
+        match (self, network) {
+            (NetworkEnvironment::Mainnet, _) => "mainnet",
+            (NetworkEnvironment::Testnet, ChainSupported::Ethereum) => "sepolia",
+            (NetworkEnvironment::Testnet, ChainSupported::Bnb) => "bnb-testnet",
+            (NetworkEnvironment::Testnet, ChainSupported::Solana) => "solana-devnet",
+            (NetworkEnvironment::Testnet, ChainSupported::Polkadot) => "westend",
+        }
+    }
+
+    /// public rpc endpoint of `network` in this environment
+    pub fn default_url(&self, network: ChainSupported) -> &'static str {
+        match (self, network) {
+            (NetworkEnvironment::Mainnet, network) => network.url(),
+            (NetworkEnvironment::Testnet, ChainSupported::Ethereum) => {
+                "https://ethereum-sepolia-rpc.publicnode.com"
+            }
+            (NetworkEnvironment::Testnet, ChainSupported::Bnb) => {
+                "https://data-seed-prebsc-1-s1.bnbchain.org:8545"
+            }
+            (NetworkEnvironment::Testnet, ChainSupported::Solana) => {
+                "https://api.devnet.solana.com"
+            }
+            (NetworkEnvironment::Testnet, ChainSupported::Polkadot) => {
+                "wss://westend-rpc.polkadot.io"
+            }
+        }
+    }
+
+    /// evm chain id of `network` in this environment, `None` for the other networks
+    pub fn chain_id(&self, network: ChainSupported) -> Option<u64> {
+        match (self, network) {
+            (NetworkEnvironment::Mainnet, ChainSupported::Ethereum) => Some(1),
+            (NetworkEnvironment::Testnet, ChainSupported::Ethereum) => Some(11155111),
+            (NetworkEnvironment::Mainnet, ChainSupported::Bnb) => Some(56),
+            (NetworkEnvironment::Testnet, ChainSupported::Bnb) => Some(97),
+            (_, ChainSupported::Solana | ChainSupported::Polkadot) => None,
+        }
+    }
+}
+
+/// environments and rpc endpoints of the networks of a node, a node wide environment with per
+/// network overrides
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Environments {
+    pub default: NetworkEnvironment,
+    pub overrides: Vec<(ChainSupported, NetworkEnvironment)>,
+    /// rpc endpoints used instead of the public ones of the environment
+    pub urls: Vec<(ChainSupported, String)>,
+}
+
+impl Environments {
+    /// environment `network` runs on
+    pub fn of(&self, network: ChainSupported) -> NetworkEnvironment {
+        self.overrides
+            .iter()
+            .find(|(overridden, _)| *overridden == network)
+            .map(|(_, environment)| *environment)
+            .unwrap_or(self.default)
+    }
+
+    /// run `network` on `environment` whatever the node wide one
+    pub fn set(&mut self, network: ChainSupported, environment: NetworkEnvironment) {
+        self.overrides
+            .retain(|(overridden, _)| *overridden != network);
+        self.overrides.push((network, environment));
+    }
+
+    /// rpc endpoint of `network`, the configured one or else the public one of its environment
+    pub fn url(&self, network: ChainSupported) -> String {
+        self.urls
+            .iter()
+            .find(|(configured, _)| *configured == network)
+            .map(|(_, url)| url.clone())
+            .unwrap_or_else(|| self.of(network).default_url(network).to_string())
+    }
+
+    pub fn set_url(&mut self, network: ChainSupported, url: String) {
+        self.urls.retain(|(configured, _)| *configured != network);
+        self.urls.push((network, url));
+    }
+}
+
+/// account generated by `provisionTestAccounts`, registered to the node and funded on dev chains
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestAccount {
+    pub account: String,
+    /// hex encoded secret key, test funds only
+    pub private_key: String,
+    pub network: ChainSupported,
+    /// balance in the smallest unit, zero on testnets where the public faucets fund accounts
+    pub balance: u128,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_overrides_win_over_the_node_environment() {
+        let mut environments = Environments {
+            default: NetworkEnvironment::Testnet,
+            ..Default::default()
+        };
+        environments.set(ChainSupported::Bnb, NetworkEnvironment::Mainnet);
+        environments.set_url(ChainSupported::Solana, "http://127.0.0.1:8899".into());
+        assert_eq!(
+            environments.of(ChainSupported::Ethereum),
+            NetworkEnvironment::Testnet
+        );
+        assert_eq!(
+            environments.of(ChainSupported::Bnb),
+            NetworkEnvironment::Mainnet
+        );
+        let ethereum = environments.of(ChainSupported::Ethereum);
+        assert_eq!(ethereum.name(ChainSupported::Ethereum), "sepolia");
+        assert_eq!(ethereum.chain_id(ChainSupported::Ethereum), Some(11155111));
+        assert_eq!(
+            environments.url(ChainSupported::Ethereum),
+            "https://ethereum-sepolia-rpc.publicnode.com"
+        );
+        assert_eq!(
+            environments.url(ChainSupported::Solana),
+            "http://127.0.0.1:8899"
+        );
+    }
+}
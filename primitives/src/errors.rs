@@ -0,0 +1,312 @@
+//! Machine readable errors returned over rpc, shared by the node and its clients
+extern crate alloc;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+// application error codes, kept outside the json-rpc reserved range (-32768 to -32000)
+pub const INVALID_ADDRESS_CODE: i32 = 1001;
+pub const WRONG_NETWORK_SUSPECTED_CODE: i32 = 1002;
+pub const UNSUPPORTED_NETWORK_CODE: i32 = 1003;
+pub const INSUFFICIENT_FUNDS_CODE: i32 = 1004;
+pub const RECEIVER_NOT_REGISTERED_CODE: i32 = 1005;
+pub const RECEIVER_OFFLINE_CODE: i32 = 1006;
+pub const ATTESTATION_EXPIRED_CODE: i32 = 1007;
+pub const INVALID_TX_STATE_CODE: i32 = 1008;
+pub const SIGNATURE_MISSING_CODE: i32 = 1009;
+pub const RATE_LIMITED_CODE: i32 = 1010;
+pub const FEE_CAP_EXCEEDED_CODE: i32 = 1011;
+pub const WARNING_OVERRIDE_REQUIRED_CODE: i32 = 1013;
+pub const POLICY_VIOLATION_CODE: i32 = 1014;
+pub const ACCESS_DENIED_CODE: i32 = 1015;
+pub const SIGNER_NOT_PAIRED_CODE: i32 = 1016;
+pub const NODE_LOCKED_CODE: i32 = 1017;
+pub const ENVIRONMENT_MISMATCH_CODE: i32 = 1018;
+pub const AMBIGUOUS_NETWORK_CODE: i32 = 1019;
+pub const ASSET_NOT_ACCEPTED_CODE: i32 = 1020;
+pub const PLUGIN_DENIED_CODE: i32 = 1021;
+pub const APPROVAL_DENIED_CODE: i32 = 1022;
+pub const RISK_CHECK_FAILED_CODE: i32 = 1023;
+pub const DEADLINE_EXCEEDED_CODE: i32 = 1024;
+pub const PRICE_MOVED_CODE: i32 = 1025;
+pub const BINDING_NOT_ANCHORED_CODE: i32 = 1026;
+pub const COMPUTE_UNIT_PRICE_CAP_EXCEEDED_CODE: i32 = 1027;
+pub const STANDBY_NODE_CODE: i32 = 1028;
+pub const INVALID_PARAMS_CODE: i32 = -32602;
+pub const INTERNAL_ERROR_CODE: i32 = -32603;
+
+/// typed errors surfaced to rpc clients, serialized as the `data` payload of the json-rpc error object
+/// so clients can branch on `kind` instead of parsing messages
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VaneRpcError {
+    /// address is not valid for the selected token or network
+    InvalidAddress { address: String },
+    /// sender and receiver addresses point to different networks
+    WrongNetworkSuspected { expected: String, found: String },
+    /// network is not supported yet
+    UnsupportedNetwork { network: String },
+    /// sender balance cannot cover the amount and fees
+    #[serde(rename_all = "camelCase")]
+    InsufficientFunds { required: u128, available: u128 },
+    /// receiver address is not registered to vane
+    ReceiverNotRegistered { address: String },
+    /// receiver node could not be reached
+    #[serde(rename_all = "camelCase")]
+    ReceiverOffline { peer_id: String },
+    /// the pending tx is no longer tracked, the attestation window has passed
+    #[serde(rename_all = "camelCase")]
+    AttestationExpired { tx_nonce: u32 },
+    /// the tx is not in a state that allows the requested action
+    InvalidTxState { status: String, reason: String },
+    /// a required signature was not provided
+    SignatureMissing { who: String },
+    /// method quota exhausted
+    #[serde(rename_all = "camelCase")]
+    RateLimited {
+        method: String,
+        max_calls: u32,
+        window_secs: u64,
+    },
+    /// the fee of the selected tier is above the sender max fee or the node cap, in wei per gas
+    #[serde(rename_all = "camelCase")]
+    FeeCapExceeded { max_fee_per_gas: u128, cap: u128 },
+    /// the tx carries high severity warnings, the sender has to confirm with the override flag
+    WarningOverrideRequired { codes: Vec<String> },
+    /// an operator policy blocks the transfer
+    #[serde(rename_all = "camelCase")]
+    PolicyViolation { rule_id: String, reason: String },
+    /// the api key role is below the role the method requires
+    AccessDenied { method: String, role: String },
+    /// the node is watch-only and no signing device is paired for the sending account
+    SignerNotPaired { account: String },
+    /// the node has a send passphrase and was not unlocked with it within the unlock window
+    #[serde(rename_all = "camelCase")]
+    NodeLocked { window_secs: u64 },
+    /// the transfer targets another environment of the network than the one the node runs it on
+    EnvironmentMismatch {
+        network: String,
+        expected: String,
+        found: String,
+    },
+    /// no network was picked and the receiver address format exists on several networks
+    AmbiguousNetwork {
+        address: String,
+        candidates: Vec<String>,
+    },
+    /// the receiver advertised the tokens it accepts and the transfer token is not one of them
+    AssetNotAccepted {
+        token: String,
+        accepted: Vec<String>,
+    },
+    /// a validation plugin of the operator denied the transfer
+    PluginDenied { plugin: String, reason: String },
+    /// the external approval system of the operator denied the transfer or did not answer
+    ApprovalDenied { reason: String },
+    /// the receiver is under the minimum risk score of the operator, in a sanctioned jurisdiction
+    /// or could not be screened
+    RiskCheckFailed { address: String, reason: String },
+    /// the deadline of the sender passed, the tx is aborted
+    #[serde(rename_all = "camelCase")]
+    DeadlineExceeded { tx_nonce: u32, deadline: u64 },
+    /// the token price moved past the slippage the sender allowed since the amount of the fiat
+    /// transfer was computed, prices in millionths of a usd
+    #[serde(rename_all = "camelCase")]
+    PriceMoved {
+        locked_price: u64,
+        price: u64,
+        max_slippage_bps: u32,
+    },
+    /// the receiver account did not anchor its binding to the node the registry serves it from in
+    /// the on chain registry the operator requires
+    #[serde(rename_all = "camelCase")]
+    BindingNotAnchored { address: String, peer_id: String },
+    /// the compute unit price of the selected tier is above the node cap, in micro lamports
+    #[serde(rename_all = "camelCase")]
+    ComputeUnitPriceCapExceeded { compute_unit_price: u64, cap: u64 },
+    /// the node is a standby of redundant nodes, transfers are handled by the one leading the p2p
+    /// identity
+    StandbyNode,
+    /// request params are missing or malformed
+    InvalidParams { reason: String },
+    /// unexpected node error
+    Internal { reason: String },
+}
+
+impl VaneRpcError {
+    pub fn code(&self) -> i32 {
+        match self {
+            VaneRpcError::InvalidAddress { .. } => INVALID_ADDRESS_CODE,
+            VaneRpcError::WrongNetworkSuspected { .. } => WRONG_NETWORK_SUSPECTED_CODE,
+            VaneRpcError::UnsupportedNetwork { .. } => UNSUPPORTED_NETWORK_CODE,
+            VaneRpcError::InsufficientFunds { .. } => INSUFFICIENT_FUNDS_CODE,
+            VaneRpcError::ReceiverNotRegistered { .. } => RECEIVER_NOT_REGISTERED_CODE,
+            VaneRpcError::ReceiverOffline { .. } => RECEIVER_OFFLINE_CODE,
+            VaneRpcError::AttestationExpired { .. } => ATTESTATION_EXPIRED_CODE,
+            VaneRpcError::InvalidTxState { .. } => INVALID_TX_STATE_CODE,
+            VaneRpcError::SignatureMissing { .. } => SIGNATURE_MISSING_CODE,
+            VaneRpcError::RateLimited { .. } => RATE_LIMITED_CODE,
+            VaneRpcError::FeeCapExceeded { .. } => FEE_CAP_EXCEEDED_CODE,
+            VaneRpcError::WarningOverrideRequired { .. } => WARNING_OVERRIDE_REQUIRED_CODE,
+            VaneRpcError::PolicyViolation { .. } => POLICY_VIOLATION_CODE,
+            VaneRpcError::AccessDenied { .. } => ACCESS_DENIED_CODE,
+            VaneRpcError::SignerNotPaired { .. } => SIGNER_NOT_PAIRED_CODE,
+            VaneRpcError::NodeLocked { .. } => NODE_LOCKED_CODE,
+            VaneRpcError::EnvironmentMismatch { .. } => ENVIRONMENT_MISMATCH_CODE,
+            VaneRpcError::AmbiguousNetwork { .. } => AMBIGUOUS_NETWORK_CODE,
+            VaneRpcError::AssetNotAccepted { .. } => ASSET_NOT_ACCEPTED_CODE,
+            VaneRpcError::PluginDenied { .. } => PLUGIN_DENIED_CODE,
+            VaneRpcError::ApprovalDenied { .. } => APPROVAL_DENIED_CODE,
+            VaneRpcError::RiskCheckFailed { .. } => RISK_CHECK_FAILED_CODE,
+            VaneRpcError::DeadlineExceeded { .. } => DEADLINE_EXCEEDED_CODE,
+            VaneRpcError::PriceMoved { .. } => PRICE_MOVED_CODE,
+            VaneRpcError::BindingNotAnchored { .. } => BINDING_NOT_ANCHORED_CODE,
+            VaneRpcError::ComputeUnitPriceCapExceeded { .. } => {
+                COMPUTE_UNIT_PRICE_CAP_EXCEEDED_CODE
+            }
+            VaneRpcError::StandbyNode => STANDBY_NODE_CODE,
+            VaneRpcError::InvalidParams { .. } => INVALID_PARAMS_CODE,
+            VaneRpcError::Internal { .. } => INTERNAL_ERROR_CODE,
+        }
+    }
+}
+
+impl Display for VaneRpcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VaneRpcError::InvalidAddress { address } => {
+                write!(f, "invalid address for the selected token: {address}")
+            }
+            VaneRpcError::WrongNetworkSuspected { expected, found } => write!(
+                f,
+                "sender and receiver should be same network, expected: {expected} found: {found}"
+            ),
+            VaneRpcError::UnsupportedNetwork { network } => {
+                write!(f, "network not supported: {network}")
+            }
+            VaneRpcError::InsufficientFunds {
+                required,
+                available,
+            } => write!(
+                f,
+                "insufficient funds, required: {required} available: {available}"
+            ),
+            VaneRpcError::ReceiverNotRegistered { address } => {
+                write!(f, "receiver not registered to vane: {address}")
+            }
+            VaneRpcError::ReceiverOffline { peer_id } => {
+                write!(f, "receiver node is offline: {peer_id}")
+            }
+            VaneRpcError::AttestationExpired { tx_nonce } => {
+                write!(f, "attestation expired for tx: {tx_nonce}")
+            }
+            VaneRpcError::InvalidTxState { status, reason } => {
+                write!(f, "invalid tx state {status}: {reason}")
+            }
+            VaneRpcError::SignatureMissing { who } => write!(f, "{who} did not sign"),
+            VaneRpcError::RateLimited {
+                method,
+                max_calls,
+                window_secs,
+            } => write!(
+                f,
+                "rate limit exceeded for {method}, allowed {max_calls} calls per {window_secs}s"
+            ),
+            VaneRpcError::FeeCapExceeded {
+                max_fee_per_gas,
+                cap,
+            } => write!(
+                f,
+                "max fee per gas {max_fee_per_gas} exceeds the fee cap of {cap} wei"
+            ),
+            VaneRpcError::WarningOverrideRequired { codes } => write!(
+                f,
+                "high severity warnings need an explicit override: {}",
+                codes.join(", ")
+            ),
+            VaneRpcError::PolicyViolation { rule_id, reason } => {
+                write!(f, "blocked by policy {rule_id}: {reason}")
+            }
+            VaneRpcError::AccessDenied { method, role } => {
+                write!(f, "{method} requires the {role} role")
+            }
+            VaneRpcError::SignerNotPaired { account } => write!(
+                f,
+                "watch-only node, pair a signing device for {account} to send"
+            ),
+            VaneRpcError::NodeLocked { window_secs } => write!(
+                f,
+                "node is locked, unlock it with its send passphrase to send for {window_secs}s"
+            ),
+            VaneRpcError::EnvironmentMismatch {
+                network,
+                expected,
+                found,
+            } => write!(
+                f,
+                "the node runs {network} on {expected}, the transfer is on {found}"
+            ),
+            VaneRpcError::AmbiguousNetwork {
+                address,
+                candidates,
+            } => write!(
+                f,
+                "{address} is valid on several networks, pick one of: {}",
+                candidates.join(", ")
+            ),
+            VaneRpcError::AssetNotAccepted { token, accepted } => write!(
+                f,
+                "the receiver does not accept {token}, only: {}",
+                accepted.join(", ")
+            ),
+            VaneRpcError::PluginDenied { plugin, reason } => {
+                write!(f, "denied by plugin {plugin}: {reason}")
+            }
+            VaneRpcError::ApprovalDenied { reason } => {
+                write!(f, "denied by the approval webhook: {reason}")
+            }
+            VaneRpcError::RiskCheckFailed { address, reason } => {
+                write!(f, "risk check of {address} failed: {reason}")
+            }
+            VaneRpcError::DeadlineExceeded { tx_nonce, deadline } => {
+                write!(f, "tx {tx_nonce} passed its deadline {deadline}")
+            }
+            VaneRpcError::PriceMoved {
+                locked_price,
+                price,
+                max_slippage_bps,
+            } => write!(
+                f,
+                "token price moved from {locked_price} to {price} micro usd, over the {max_slippage_bps} bps allowed"
+            ),
+            VaneRpcError::BindingNotAnchored { address, peer_id } => write!(
+                f,
+                "{address} did not anchor its binding to the node {peer_id} on chain"
+            ),
+            VaneRpcError::ComputeUnitPriceCapExceeded {
+                compute_unit_price,
+                cap,
+            } => write!(
+                f,
+                "compute unit price {compute_unit_price} exceeds the cap of {cap} micro lamports"
+            ),
+            VaneRpcError::StandbyNode => write!(
+                f,
+                "the node is a standby, send to the node leading the p2p identity"
+            ),
+            VaneRpcError::InvalidParams { reason } => write!(f, "invalid params: {reason}"),
+            VaneRpcError::Internal { reason } => write!(f, "internal error: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for VaneRpcError {}
+
+impl From<anyhow::Error> for VaneRpcError {
+    fn from(value: anyhow::Error) -> Self {
+        VaneRpcError::Internal {
+            reason: value.to_string(),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Block explorer links and chain receipts
+//!
+//! a submitted tx is only a hash until it is included in a block. the node records the canonical
+//! explorer link of the hash right away and enriches the history record with the receipt once the
+//! chain has one: the block it landed in, the effective gas price and the fee the sender paid.
+extern crate alloc;
+use crate::data_structure::ChainSupported;
+use crate::environment::NetworkEnvironment;
+use alloc::format;
+use alloc::string::String;
+use base58::ToBase58;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// inclusion details of a submitted tx, as reported by the chain
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainReceipt {
+    pub block_number: u64,
+    /// price per gas the tx paid in wei, evm networks only
+    pub effective_gas_price: Option<u128>,
+    /// fee the sender paid in the smallest unit of the native token
+    pub fee_paid: u128,
+    /// false when the tx was included but reverted
+    pub success: bool,
+}
+
+impl ChainReceipt {
+    /// fee the tx would have paid at `gas_price` per gas, at most the fee it paid. none without an
+    /// effective gas price to tell the gas used
+    pub fn fee_at(&self, gas_price: u128) -> Option<u128> {
+        let paid_price = self.effective_gas_price.filter(|price| *price > 0)?;
+        let gas_used = self.fee_paid / paid_price;
+        Some(gas_used.saturating_mul(gas_price.min(paid_price)))
+    }
+}
+
+/// canonical explorer page of `tx_hash` on `network` in `environment`
+pub fn explorer_url(
+    network: ChainSupported,
+    environment: NetworkEnvironment,
+    tx_hash: &[u8],
+) -> String {
+    let hex_hash = tx_hash
+        .iter()
+        .fold(String::from("0x"), |hex, byte| hex + &format!("{byte:02x}"));
+    let testnet = environment == NetworkEnvironment::Testnet;
+    match (network, testnet) {
+        (ChainSupported::Ethereum, false) => format!("https://etherscan.io/tx/{hex_hash}"),
+        (ChainSupported::Ethereum, true) => format!("https://sepolia.etherscan.io/tx/{hex_hash}"),
+        (ChainSupported::Bnb, false) => format!("https://bscscan.com/tx/{hex_hash}"),
+        (ChainSupported::Bnb, true) => format!("https://testnet.bscscan.com/tx/{hex_hash}"),
+        (ChainSupported::Solana, false) => {
+            format!("https://solscan.io/tx/{}", tx_hash.to_base58())
+        }
+        (ChainSupported::Solana, true) => {
+            format!(
+                "https://solscan.io/tx/{}?cluster=devnet",
+                tx_hash.to_base58()
+            )
+        }
+        (ChainSupported::Polkadot, false) => {
+            format!("https://polkadot.subscan.io/extrinsic/{hex_hash}")
+        }
+        (ChainSupported::Polkadot, true) => {
+            format!("https://westend.subscan.io/extrinsic/{hex_hash}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explorer_urls_follow_the_network_environment() {
+        let hash = [0xab; 32];
+        assert_eq!(
+            explorer_url(ChainSupported::Ethereum, NetworkEnvironment::Mainnet, &hash),
+            format!("https://etherscan.io/tx/0x{}", "ab".repeat(32))
+        );
+        assert!(
+            explorer_url(ChainSupported::Bnb, NetworkEnvironment::Testnet, &hash)
+                .starts_with("https://testnet.bscscan.com/tx/0xabab")
+        );
+        assert!(
+            explorer_url(ChainSupported::Solana, NetworkEnvironment::Testnet, &hash)
+                .ends_with("?cluster=devnet")
+        );
+        assert!(
+            explorer_url(ChainSupported::Polkadot, NetworkEnvironment::Mainnet, &hash)
+                .starts_with("https://polkadot.subscan.io/extrinsic/0x")
+        );
+    }
+}
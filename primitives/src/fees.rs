@@ -0,0 +1,360 @@
+//! EIP-1559 fee tiers and sender fee preferences
+//!
+//! the node gas oracle turns recent base fees and paid priority fees into low / medium / high tiers,
+//! the sender picks a tier and optionally a hard max fee per gas. a tx whose tier fee is above the
+//! sender max or the node configured cap is not built.
+//!
+//! once included, the fee a transfer actually paid is taken from its receipt along with what the
+//! low tier of the same block would have cost, and the history is folded into per network and per
+//! tier fee stats. a tier whose latest transfers all paid well above the low tier is flagged.
+extern crate alloc;
+use crate::data_structure::{ChainSupported, DbTxStateMachine, Severity, Warning};
+use crate::errors::VaneRpcError;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// fee tier picked by the sender
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+pub enum FeeTier {
+    /// included if the base fee does not rise
+    Low,
+    #[default]
+    Medium,
+    /// included within the next blocks even if the base fee keeps rising
+    High,
+}
+
+/// sender fee choice carried by the tx until it is built
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct FeePreference {
+    #[serde(default)]
+    pub tier: FeeTier,
+    /// hard max fee per gas in wei, the tx is refused if the tier fee is above it
+    #[serde(default)]
+    pub max_fee_per_gas: Option<u128>,
+}
+
+/// EIP-1559 fee fields in wei per gas
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeEstimate {
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+}
+
+/// suggested fees for the next block
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeTiers {
+    /// base fee of the next block
+    pub base_fee_per_gas: u128,
+    /// highest base fee over the tracked blocks
+    pub peak_base_fee_per_gas: u128,
+    pub low: FeeEstimate,
+    pub medium: FeeEstimate,
+    pub high: FeeEstimate,
+}
+
+impl FeeTiers {
+    /// tiers from `eth_feeHistory`, `base_fees` ends with the next block base fee and each `rewards`
+    /// entry holds the priority fees paid in a block at the low, medium and high percentiles
+    pub fn from_fee_history(base_fees: &[u128], rewards: &[Vec<u128>]) -> Option<Self> {
+        let base_fee = *base_fees.last()?;
+        let peak_base_fee = base_fees.iter().copied().max()?;
+        let priority_fee = |percentile: usize| {
+            let paid = rewards
+                .iter()
+                .filter_map(|block| block.get(percentile).copied())
+                .collect::<Vec<u128>>();
+            if paid.is_empty() {
+                0
+            } else {
+                paid.iter().sum::<u128>() / paid.len() as u128
+            }
+        };
+        let estimate = |max_base_fee: u128, max_priority_fee_per_gas: u128| FeeEstimate {
+            max_fee_per_gas: max_base_fee.saturating_add(max_priority_fee_per_gas),
+            max_priority_fee_per_gas,
+        };
+
+        Some(Self {
+            base_fee_per_gas: base_fee,
+            peak_base_fee_per_gas: peak_base_fee,
+            low: estimate(base_fee, priority_fee(0)),
+            medium: estimate(
+                peak_base_fee.max(base_fee.saturating_mul(5) / 4),
+                priority_fee(1),
+            ),
+            high: estimate(
+                peak_base_fee.max(base_fee.saturating_mul(2)),
+                priority_fee(2),
+            ),
+        })
+    }
+
+    pub fn tier(&self, tier: FeeTier) -> FeeEstimate {
+        match tier {
+            FeeTier::Low => self.low,
+            FeeTier::Medium => self.medium,
+            FeeTier::High => self.high,
+        }
+    }
+
+    /// fees of the preferred tier, refused above the sender max fee or the node cap
+    pub fn select(
+        &self,
+        preference: FeePreference,
+        node_cap: Option<u128>,
+    ) -> Result<FeeEstimate, VaneRpcError> {
+        let estimate = self.tier(preference.tier);
+        let cap = match (preference.max_fee_per_gas, node_cap) {
+            (Some(max_fee), Some(node_cap)) => Some(max_fee.min(node_cap)),
+            (max_fee, node_cap) => max_fee.or(node_cap),
+        };
+        match cap {
+            Some(cap) if estimate.max_fee_per_gas > cap => Err(VaneRpcError::FeeCapExceeded {
+                max_fee_per_gas: estimate.max_fee_per_gas,
+                cap,
+            }),
+            _ => Ok(estimate),
+        }
+    }
+}
+
+/// percent a transfer may pay above the low tier of its block before it counts as overpaying
+pub const OVERPAY_MARGIN_PERCENT: u128 = 25;
+/// latest transfers of a tier that all overpaid before the tier is flagged
+pub const OVERPAY_STREAK: u32 = 5;
+
+/// fees paid on chain by the submitted transfers with a receipt
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeStats {
+    pub networks: Vec<NetworkFees>,
+    /// tiers consistently paying more than needed, see `OVERPAY_STREAK`
+    pub warnings: Vec<Warning>,
+}
+
+/// fees paid on a network, in the smallest unit of its native token
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkFees {
+    pub network: ChainSupported,
+    pub transfers: u32,
+    pub total_fee: u128,
+    pub average_fee: u128,
+    /// evm networks only
+    pub tiers: Vec<TierFees>,
+}
+
+/// fees paid by the transfers built with a fee tier
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TierFees {
+    pub tier: FeeTier,
+    pub transfers: u32,
+    pub total_fee: u128,
+    pub average_fee: u128,
+    /// paid above the low tier of the same blocks, over the transfers it is known for
+    pub overpaid: u128,
+    /// latest transfers in a row that overpaid by more than `OVERPAY_MARGIN_PERCENT`
+    pub overpay_streak: u32,
+}
+
+impl FeeStats {
+    /// fold the history records, oldest first
+    pub fn fold(records: impl IntoIterator<Item = DbTxStateMachine>) -> Self {
+        let mut stats = FeeStats::default();
+        for record in records {
+            let Some(receipt) = record.receipt else {
+                continue;
+            };
+            let network = match stats
+                .networks
+                .iter_mut()
+                .position(|fees| fees.network == record.network)
+            {
+                Some(index) => &mut stats.networks[index],
+                None => {
+                    stats.networks.push(NetworkFees {
+                        network: record.network,
+                        transfers: 0,
+                        total_fee: 0,
+                        average_fee: 0,
+                        tiers: Vec::new(),
+                    });
+                    stats.networks.last_mut().expect("just pushed")
+                }
+            };
+            network.transfers += 1;
+            network.total_fee = network.total_fee.saturating_add(receipt.fee_paid);
+            network.average_fee = network.total_fee / network.transfers as u128;
+
+            let Some(tier) = record.fee_tier else {
+                continue;
+            };
+            let fees = match network.tiers.iter().position(|fees| fees.tier == tier) {
+                Some(index) => &mut network.tiers[index],
+                None => {
+                    network.tiers.push(TierFees {
+                        tier,
+                        transfers: 0,
+                        total_fee: 0,
+                        average_fee: 0,
+                        overpaid: 0,
+                        overpay_streak: 0,
+                    });
+                    network.tiers.last_mut().expect("just pushed")
+                }
+            };
+            fees.transfers += 1;
+            fees.total_fee = fees.total_fee.saturating_add(receipt.fee_paid);
+            fees.average_fee = fees.total_fee / fees.transfers as u128;
+            if let Some(low_tier_fee) = record.low_tier_fee {
+                let overpaid = receipt.fee_paid.saturating_sub(low_tier_fee);
+                fees.overpaid = fees.overpaid.saturating_add(overpaid);
+                if overpaid.saturating_mul(100)
+                    > low_tier_fee.saturating_mul(OVERPAY_MARGIN_PERCENT)
+                {
+                    fees.overpay_streak += 1;
+                } else {
+                    fees.overpay_streak = 0;
+                }
+            }
+        }
+
+        stats.warnings = stats
+            .networks
+            .iter()
+            .flat_map(|network| {
+                network
+                    .tiers
+                    .iter()
+                    .filter_map(|fees| overpay_warning(network.network, fees))
+            })
+            .collect();
+        stats
+    }
+
+    /// warning about `tier` on `network` when it consistently overpays
+    pub fn warning(&self, network: ChainSupported, tier: FeeTier) -> Option<Warning> {
+        let network_fees = self.networks.iter().find(|fees| fees.network == network)?;
+        let fees = network_fees.tiers.iter().find(|fees| fees.tier == tier)?;
+        overpay_warning(network, fees)
+    }
+}
+
+fn overpay_warning(network: ChainSupported, fees: &TierFees) -> Option<Warning> {
+    if fees.tier == FeeTier::Low || fees.overpay_streak < OVERPAY_STREAK {
+        return None;
+    }
+    let (streak, tier) = (fees.overpay_streak, fees.tier);
+    Some(Warning {
+        code: "FEE_TIER_OVERPAYS".to_string(),
+        severity: Severity::Low,
+        message: format!(
+            "the last {streak} {tier:?} tier transfers on {network:?} paid over \
+             {OVERPAY_MARGIN_PERCENT}% more than the low tier would have"
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const GWEI: u128 = 1_000_000_000;
+
+    fn tiers() -> FeeTiers {
+        let base_fees = [10 * GWEI, 14 * GWEI, 12 * GWEI];
+        let rewards = [
+            vec![GWEI, 2 * GWEI, 4 * GWEI],
+            vec![GWEI, 2 * GWEI, 6 * GWEI],
+        ];
+        FeeTiers::from_fee_history(&base_fees, &rewards).unwrap()
+    }
+
+    #[test]
+    fn tiers_follow_recent_base_fees() {
+        let tiers = tiers();
+        assert_eq!(tiers.base_fee_per_gas, 12 * GWEI);
+        assert_eq!(tiers.peak_base_fee_per_gas, 14 * GWEI);
+        assert_eq!(tiers.low.max_fee_per_gas, 13 * GWEI);
+        assert_eq!(tiers.medium.max_fee_per_gas, 17 * GWEI);
+        assert_eq!(tiers.high.max_fee_per_gas, 29 * GWEI);
+        assert_eq!(tiers.high.max_priority_fee_per_gas, 5 * GWEI);
+        assert!(FeeTiers::from_fee_history(&[], &[]).is_none());
+    }
+
+    #[test]
+    fn fees_above_the_cap_are_refused() {
+        let tiers = tiers();
+        let high = FeePreference {
+            tier: FeeTier::High,
+            max_fee_per_gas: Some(20 * GWEI),
+        };
+        assert!(matches!(
+            tiers.select(high, None),
+            Err(VaneRpcError::FeeCapExceeded { cap, .. }) if cap == 20 * GWEI
+        ));
+        assert!(tiers
+            .select(FeePreference::default(), Some(16 * GWEI))
+            .is_err());
+        assert_eq!(
+            tiers.select(FeePreference::default(), Some(17 * GWEI)),
+            Ok(tiers.medium)
+        );
+    }
+
+    #[test]
+    fn fee_stats_flag_tiers_that_keep_overpaying() {
+        use crate::explorer::ChainReceipt;
+
+        let record = |tier: FeeTier, fee_paid: u128, low_tier_fee: u128| DbTxStateMachine {
+            network: ChainSupported::Ethereum,
+            success: true,
+            receipt: Some(ChainReceipt {
+                fee_paid,
+                ..Default::default()
+            }),
+            fee_tier: Some(tier),
+            low_tier_fee: Some(low_tier_fee),
+            ..Default::default()
+        };
+        let mut records = vec![record(FeeTier::High, 100, 100)];
+        records.extend((0..OVERPAY_STREAK).map(|_| record(FeeTier::High, 200, 100)));
+        records.push(record(FeeTier::Medium, 300, 100));
+        records.push(record(FeeTier::Medium, 100, 100));
+        // not included yet
+        records.push(DbTxStateMachine {
+            receipt: None,
+            ..record(FeeTier::Low, 0, 0)
+        });
+
+        let stats = FeeStats::fold(records);
+        assert_eq!(stats.networks.len(), 1);
+        let eth = &stats.networks[0];
+        assert_eq!(
+            (eth.transfers, eth.total_fee, eth.average_fee),
+            (8, 1500, 187)
+        );
+        assert_eq!(eth.tiers[0].tier, FeeTier::High);
+        assert_eq!(eth.tiers[0].overpaid, 500);
+        assert_eq!(eth.tiers[0].average_fee, 1100 / 6);
+        // the medium tier overpaid once, then paid the low tier fee
+        assert_eq!(eth.tiers[1].overpay_streak, 0);
+        assert_eq!(stats.warnings.len(), 1);
+        assert_eq!(stats.warnings[0].code, "FEE_TIER_OVERPAYS");
+        assert!(stats
+            .warning(ChainSupported::Ethereum, FeeTier::High)
+            .is_some());
+        assert!(stats
+            .warning(ChainSupported::Ethereum, FeeTier::Medium)
+            .is_none());
+    }
+}
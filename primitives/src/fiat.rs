@@ -0,0 +1,141 @@
+//! Fiat denominated transfers
+//!
+//! a sender may ask for e.g. $100 of ETH. the token amount is computed from the usd price of the
+//! token when the transfer is initiated and the price is locked with the tx, so the receiver
+//! attests and the sender signs a fixed amount. prices are kept in millionths of a usd, the quote
+//! goes over the wire and scale has no floats. the node refuses to confirm or submit the transfer
+//! once the price moved past the slippage the sender allowed.
+extern crate alloc;
+use crate::data_structure::Token;
+use crate::errors::VaneRpcError;
+use alloc::format;
+use alloc::string::ToString;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// prices are in millionths of a usd per whole token
+pub const PRICE_SCALE: u64 = 1_000_000;
+
+/// slippage allowed when the sender does not pick one, in basis points
+pub const DEFAULT_MAX_SLIPPAGE_BPS: u32 = 100;
+
+const BPS: u128 = 10_000;
+
+/// usd value of a transfer and the token price its amount was computed with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct FiatQuote {
+    /// usd value of the transfer, in cents
+    pub usd_cents: u64,
+    /// usd price of a whole token when the transfer was initiated, in millionths of a usd
+    pub locked_price: u64,
+    /// price move allowed until the transfer is submitted, in basis points
+    pub max_slippage_bps: u32,
+}
+
+impl FiatQuote {
+    /// lock the current `price` of the token, in usd, for a transfer worth `usd_cents`
+    pub fn lock(usd_cents: u64, price: f64, max_slippage_bps: u32) -> Result<Self, VaneRpcError> {
+        if usd_cents == 0 {
+            Err(VaneRpcError::InvalidParams {
+                reason: "the usd value of a transfer should be above zero".to_string(),
+            })?
+        }
+        if max_slippage_bps as u128 >= BPS {
+            Err(VaneRpcError::InvalidParams {
+                reason: format!("slippage of {max_slippage_bps} bps should be under {BPS}"),
+            })?
+        }
+        Ok(Self {
+            usd_cents,
+            locked_price: micro_usd(price)?,
+            max_slippage_bps,
+        })
+    }
+
+    /// amount worth `usd_cents` at the locked price, in the token smallest unit
+    pub fn token_amount(&self, token: Token) -> Result<u128, VaneRpcError> {
+        let amount = (self.usd_cents as u128)
+            .checked_mul(10u128.pow(token.decimals()))
+            .and_then(|value| value.checked_mul(PRICE_SCALE as u128 / 100))
+            .map(|value| value / self.locked_price as u128)
+            .ok_or_else(|| VaneRpcError::InvalidParams {
+                reason: format!("${} is too large an amount", self.usd_cents / 100),
+            })?;
+        if amount == 0 {
+            Err(VaneRpcError::InvalidParams {
+                reason: format!(
+                    "{} cents is under the smallest unit of {token:?}",
+                    self.usd_cents
+                ),
+            })?
+        }
+        Ok(amount)
+    }
+
+    /// move of the current `price` from the locked one, in basis points
+    pub fn slippage_bps(&self, price: u64) -> u32 {
+        let moved = (price as u128).abs_diff(self.locked_price as u128) * BPS;
+        u32::try_from(moved / self.locked_price as u128).unwrap_or(u32::MAX)
+    }
+
+    /// refuse the transfer once the current `price` of the token, in usd, moved past the slippage
+    pub fn check(&self, price: f64) -> Result<(), VaneRpcError> {
+        let price = micro_usd(price)?;
+        let slippage_bps = self.slippage_bps(price);
+        if slippage_bps > self.max_slippage_bps {
+            Err(VaneRpcError::PriceMoved {
+                locked_price: self.locked_price,
+                price,
+                max_slippage_bps: self.max_slippage_bps,
+            })?
+        }
+        Ok(())
+    }
+}
+
+/// `price` in usd as millionths of a usd
+pub fn micro_usd(price: f64) -> Result<u64, VaneRpcError> {
+    let scaled = (price * PRICE_SCALE as f64).round();
+    if !scaled.is_finite() || scaled < 1.0 || scaled >= u64::MAX as f64 {
+        Err(VaneRpcError::InvalidParams {
+            reason: format!("invalid usd price {price}"),
+        })?
+    }
+    Ok(scaled as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_follow_the_locked_price_within_the_slippage() {
+        let quote = FiatQuote::lock(10_000, 2_500.0, 100).unwrap();
+        assert_eq!(quote.locked_price, 2_500_000_000);
+        // $100 of ETH at $2500
+        assert_eq!(quote.token_amount(Token::Eth), Ok(40_000_000_000_000_000));
+        let usdc = FiatQuote::lock(10_000, 1.0, 100).unwrap();
+        assert_eq!(usdc.token_amount(Token::UsdcEth), Ok(100_000_000));
+
+        assert_eq!(quote.slippage_bps(2_525_000_000), 100);
+        assert_eq!(quote.slippage_bps(2_450_000_000), 200);
+        assert!(quote.check(2_525.0).is_ok());
+        assert!(quote.check(2_475.0).is_ok());
+        assert_eq!(
+            quote.check(2_450.0),
+            Err(VaneRpcError::PriceMoved {
+                locked_price: 2_500_000_000,
+                price: 2_450_000_000,
+                max_slippage_bps: 100,
+            })
+        );
+
+        assert!(FiatQuote::lock(0, 2_500.0, 100).is_err());
+        assert!(FiatQuote::lock(10_000, 0.0, 100).is_err());
+        assert!(FiatQuote::lock(10_000, f64::NAN, 100).is_err());
+        assert!(FiatQuote::lock(10_000, 2_500.0, 10_000).is_err());
+        let dust = FiatQuote::lock(1, 1_000_000.0, 100).unwrap();
+        assert!(dust.token_amount(Token::UsdcEth).is_err());
+    }
+}
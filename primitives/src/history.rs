@@ -0,0 +1,131 @@
+//! Transaction history search
+//!
+//! support staff look a customer payment up by the counterparty, the name the sender gave, the memo
+//! or reference of the transfer, an amount range and the network. the structured filters and the
+//! counterparty address map onto indexed history columns, the text is matched against the
+//! remaining fields once the db returned the candidates, as memos are only readable once opened.
+extern crate alloc;
+use crate::address::VaneAddress;
+use crate::data_structure::{ChainSupported, DbTxStateMachine};
+use alloc::string::{String, ToString};
+use serde::{Deserialize, Serialize};
+
+/// most records a search returns when it sets no limit
+pub const DEFAULT_SEARCH_LIMIT: u32 = 100;
+
+/// filters of a history search, every set filter has to match
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySearch {
+    /// counterparty address in any spelling, sender name, memo text, reference or tag
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub network: Option<ChainSupported>,
+    /// inclusive amount range in the smallest unit of the token
+    #[serde(default)]
+    pub min_amount: Option<u128>,
+    #[serde(default)]
+    pub max_amount: Option<u128>,
+    /// only succeeded or only failed txs
+    #[serde(default)]
+    pub success: Option<bool>,
+    /// most records returned, newest first, `DEFAULT_SEARCH_LIMIT` when missing
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+impl HistorySearch {
+    /// canonical form of the text when it is an address, matched on the indexed address columns
+    pub fn address(&self) -> Option<String> {
+        let text = self.text.as_deref()?;
+        VaneAddress::parse(text)
+            .ok()
+            .map(|address| address.to_string())
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_SEARCH_LIMIT) as usize
+    }
+
+    /// the structured filters and the counterparty address match `tx`, what a db query checks on
+    /// its own
+    pub fn matches_filters(&self, tx: &DbTxStateMachine) -> bool {
+        self.network.map_or(true, |network| tx.network == network)
+            && self.min_amount.map_or(true, |min| tx.amount >= min)
+            && self.max_amount.map_or(true, |max| tx.amount <= max)
+            && self.success.map_or(true, |success| tx.success == success)
+            && self.address().map_or(true, |address| {
+                tx.sender_address == address || tx.receiver_address == address
+            })
+    }
+
+    /// every filter matches `tx`, a text that is not an address being part of the sender name or
+    /// a reconciliation field
+    pub fn matches(&self, tx: &DbTxStateMachine) -> bool {
+        if !self.matches_filters(tx) {
+            return false;
+        }
+        match self.text.as_deref() {
+            Some(text) if self.address().is_none() => {
+                let lower = text.trim().to_lowercase();
+                tx.sender_name
+                    .as_ref()
+                    .is_some_and(|name| name.to_lowercase().contains(&lower))
+                    || tx.matches(text)
+            }
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn searches_match_counterparties_names_memos_n_amounts() {
+        let record = DbTxStateMachine {
+            amount: 2_500,
+            network: ChainSupported::Ethereum,
+            success: true,
+            memo: Some("march rent".to_string()),
+            reference: Some("INV-7".to_string()),
+            tags: vec!["payroll".to_string()],
+            sender_address: "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string(),
+            receiver_address: "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359".to_string(),
+            sender_name: Some("Acme Payroll".to_string()),
+            ..Default::default()
+        };
+        let search = |text: &str| HistorySearch {
+            text: Some(text.to_string()),
+            ..Default::default()
+        };
+        assert!(search("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").matches(&record));
+        assert!(search("acme").matches(&record));
+        assert!(search("RENT").matches(&record));
+        assert!(search("inv-7").matches(&record));
+        assert!(!search("0x0000000000000000000000000000000000000001").matches(&record));
+        assert!(!search("globex").matches(&record));
+
+        let in_range = HistorySearch {
+            network: Some(ChainSupported::Ethereum),
+            min_amount: Some(2_000),
+            max_amount: Some(2_500),
+            ..Default::default()
+        };
+        assert!(in_range.matches(&record));
+        let on_bnb = HistorySearch {
+            network: Some(ChainSupported::Bnb),
+            ..in_range.clone()
+        };
+        assert!(!on_bnb.matches(&record));
+        let failed_only = HistorySearch {
+            success: Some(false),
+            ..in_range
+        };
+        assert!(!failed_only.matches(&record));
+        assert_eq!(HistorySearch::default().limit(), 100);
+    }
+}
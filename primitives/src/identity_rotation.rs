@@ -0,0 +1,79 @@
+//! Node identity rotation records
+//!
+//! a node rotating its libp2p identity signs the rotation with its old key and with its new one, so
+//! contacts only move the node to its new peer id when both keys agree. the old identity keeps
+//! answering until `grace_until`. account bindings are signed by the accounts and name the old peer
+//! id, the accounts sign them again for the new one.
+extern crate alloc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// rotation of a node from `old_peer_id` to `new_peer_id`, signed by both keys
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityRotation {
+    pub old_peer_id: String,
+    pub new_peer_id: String,
+    pub new_multi_addr: String,
+    /// protobuf encoded libp2p public keys of both identities
+    pub old_public_key: Vec<u8>,
+    pub new_public_key: Vec<u8>,
+    /// unix timestamp in seconds
+    pub rotated_at: u64,
+    /// unix timestamp in seconds the old identity stops answering at
+    pub grace_until: u64,
+    /// signatures of `IdentityRotation::signed_message` by the old and the new key
+    pub old_signature: Vec<u8>,
+    pub new_signature: Vec<u8>,
+}
+
+impl IdentityRotation {
+    /// message both keys sign
+    pub fn signed_message(&self) -> String {
+        alloc::format!(
+            "vane identity rotation of node {} to node {} at {} rotated at {} valid until {}",
+            self.old_peer_id,
+            self.new_peer_id,
+            self.new_multi_addr,
+            self.rotated_at,
+            self.grace_until
+        )
+    }
+}
+
+/// outcome of `rotateNodeIdentity`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RotatedIdentity {
+    pub rotation: IdentityRotation,
+    /// accounts bound to the old peer id, each signs the peer binding message of the new one at
+    /// `rotatedAt` to bind itself again
+    pub rebind_accounts: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_message_covers_both_identities_and_the_grace_period() {
+        let rotation = IdentityRotation {
+            old_peer_id: "12D3KooWOld".into(),
+            new_peer_id: "12D3KooWNew".into(),
+            new_multi_addr: "/ip4/127.0.0.1/tcp/30334".into(),
+            old_public_key: Vec::new(),
+            new_public_key: Vec::new(),
+            rotated_at: 10,
+            grace_until: 20,
+            old_signature: Vec::new(),
+            new_signature: Vec::new(),
+        };
+        assert_eq!(
+            rotation.signed_message(),
+            "vane identity rotation of node 12D3KooWOld to node 12D3KooWNew at \
+             /ip4/127.0.0.1/tcp/30334 rotated at 10 valid until 20"
+        );
+    }
+}
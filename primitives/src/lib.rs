@@ -1 +1,39 @@
+pub mod accepted_assets;
+pub mod address;
+pub mod anchors;
+pub mod approvals;
+pub mod attestations;
+pub mod compute_budget;
+pub mod counterparty;
 pub mod data_structure;
+pub mod deposits;
+pub mod device_sync;
+pub mod diagnosis;
+pub mod durable_nonce;
+pub mod envelope;
+pub mod environment;
+pub mod errors;
+pub mod explorer;
+pub mod fees;
+pub mod fiat;
+pub mod history;
+pub mod identity_rotation;
+pub mod lifecycle;
+pub mod locale;
+pub mod loss_prevention;
+pub mod payments;
+pub mod peer_exchange;
+pub mod plugins;
+pub mod policy;
+pub mod relay;
+pub mod revocation;
+pub mod risk;
+pub mod routes;
+pub mod rpc_tx;
+pub mod solana_memo;
+pub mod sponsorship;
+pub mod token_account;
+pub mod travel_rule;
+pub mod tx_builder;
+pub mod tx_state;
+pub mod validation;
@@ -0,0 +1,112 @@
+//! Tx lifecycle events
+//!
+//! every state transition a node records is mirrored onto the message bus of the operator as a
+//! `TxLifecycleEvent`, so risk, analytics and notification systems follow the transfers without
+//! polling the rpc. the schema is versioned apart from the tx state machine: fields are only added
+//! under the same version, a renamed or removed field bumps `TX_LIFECYCLE_SCHEMA_VERSION`.
+extern crate alloc;
+use crate::data_structure::{ChainSupported, Token, TxEvent, TxKind, H256};
+use crate::rpc_tx::status_parts;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// version of the `TxLifecycleEvent` schema
+pub const TX_LIFECYCLE_SCHEMA_VERSION: u32 = 1;
+
+/// state transition of a tx as published on the message bus
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxLifecycleEvent {
+    pub schema_version: u32,
+    /// peer id the node recording the transition started with
+    pub node: String,
+    pub tx_id: H256,
+    pub tx_nonce: u32,
+    pub kind: TxKind,
+    /// name of the status reached, e.g. `SenderConfirmed`
+    pub status: String,
+    /// failure or rejection reason, hash of the submitted chain tx
+    pub detail: Option<String>,
+    /// no further transition follows
+    pub is_final: bool,
+    pub sender: String,
+    pub receiver: String,
+    pub network: ChainSupported,
+    pub token: Token,
+    /// amount in the smallest unit of the token, as a decimal string
+    pub amount: String,
+    pub reference: Option<String>,
+    pub tags: Vec<String>,
+    /// codes of the warnings raised on the transfer
+    pub warnings: Vec<String>,
+    /// unix timestamp in seconds
+    pub recorded_at: u64,
+}
+
+impl TxLifecycleEvent {
+    pub fn new(node: impl Into<String>, event: &TxEvent) -> Self {
+        let tx = &event.tx;
+        let (status, detail) = status_parts(&event.status);
+        Self {
+            schema_version: TX_LIFECYCLE_SCHEMA_VERSION,
+            node: node.into(),
+            tx_id: event.tx_id,
+            tx_nonce: tx.tx_nonce,
+            kind: tx.kind,
+            status: status.to_string(),
+            detail,
+            is_final: event.status.is_final(),
+            sender: tx.sender_address.to_string(),
+            receiver: tx.receiver_address.to_string(),
+            network: tx.network,
+            token: tx.token.unwrap_or(Token::native(tx.network)),
+            amount: tx.transfer_amount().to_string(),
+            reference: tx.reference.clone(),
+            tags: tx.tags.clone(),
+            warnings: tx
+                .warnings
+                .iter()
+                .map(|warning| warning.code.clone())
+                .collect(),
+            recorded_at: event.recorded_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structure::{TxStateMachine, TxStatus};
+    use alloc::format;
+
+    #[test]
+    fn lifecycle_events_keep_their_schema() {
+        let tx = TxStateMachine {
+            network: ChainSupported::Ethereum,
+            status: TxStatus::TxSubmissionPassed([7; 32]),
+            amount: 1_000,
+            accepted_amount: Some(900),
+            tx_nonce: 3,
+            reference: Some("INV-42".to_string()),
+            ..Default::default()
+        };
+        let event = TxEvent::new(tx, 1_700_000_000);
+        let lifecycle = TxLifecycleEvent::new("12D3KooW", &event);
+        assert_eq!(lifecycle.status, "TxSubmissionPassed");
+        assert_eq!(lifecycle.detail, Some(format!("0x{}", "07".repeat(32))));
+        assert!(lifecycle.is_final);
+        assert_eq!(lifecycle.amount, "900");
+        assert_eq!(lifecycle.token, Token::Eth);
+
+        let json = serde_json::to_value(&lifecycle).unwrap();
+        assert_eq!(json["schemaVersion"], TX_LIFECYCLE_SCHEMA_VERSION);
+        assert_eq!(json["node"], "12D3KooW");
+        assert_eq!(json["txNonce"], 3);
+        assert_eq!(json["isFinal"], true);
+        assert_eq!(json["reference"], "INV-42");
+        assert_eq!(json["recordedAt"], 1_700_000_000u64);
+        let decoded: TxLifecycleEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, lifecycle);
+    }
+}
@@ -0,0 +1,226 @@
+//! Locale aware amounts
+//!
+//! human amounts are read and rendered with the decimal and digit group separators of a locale, e.g.
+//! `1,234.56` in english and `1 234,56` in french. once the separators are stripped the amount goes
+//! through `parse_units`, so every frontend converts amounts with the same rules as the node.
+extern crate alloc;
+use crate::data_structure::Token;
+use crate::errors::VaneRpcError;
+use crate::tx_builder::{format_units, parse_units};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// no-break spaces grouping digits in french and other locales, read like a plain space
+const SPACES: [char; 3] = [' ', '\u{a0}', '\u{202f}'];
+
+/// decimal and digit group separators of amounts
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Separators {
+    pub decimal: char,
+    /// `None` reads and renders amounts without digit groups
+    pub group: Option<char>,
+}
+
+impl Default for Separators {
+    fn default() -> Self {
+        Self {
+            decimal: '.',
+            group: Some(','),
+        }
+    }
+}
+
+impl Separators {
+    /// separators of a BCP 47 locale tag, by its language and region, e.g. `de` or `de-CH`
+    pub fn of_locale(tag: &str) -> Result<Self, VaneRpcError> {
+        let tag = tag.replace('_', "-").to_lowercase();
+        let language = tag.split('-').next().unwrap_or_default();
+        let (decimal, group) = match (tag.as_str(), language) {
+            ("de-ch" | "it-ch", _) => ('.', '\''),
+            (_, "en" | "ja" | "zh" | "ko" | "he" | "th") => ('.', ','),
+            (_, "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da") => (',', '.'),
+            (_, "fr" | "ru" | "pl" | "sv" | "cs" | "nb" | "fi" | "uk" | "sk" | "hu") => (',', ' '),
+            _ => Err(VaneRpcError::InvalidParams {
+                reason: format!("unsupported locale {tag}, pass its separators instead"),
+            })?,
+        };
+        Ok(Self {
+            decimal,
+            group: Some(group),
+        })
+    }
+
+    fn validate(&self) -> Result<(), VaneRpcError> {
+        if !matches!(self.decimal, '.' | ',') {
+            Err(VaneRpcError::InvalidParams {
+                reason: format!("decimal separator {:?} should be . or ,", self.decimal),
+            })?
+        }
+        if let Some(group) = self.group {
+            if group == self.decimal
+                || !(matches!(group, '.' | ',' | '\'') || SPACES.contains(&group))
+            {
+                Err(VaneRpcError::InvalidParams {
+                    reason: format!("invalid digit group separator {group:?}"),
+                })?
+            }
+        }
+        Ok(())
+    }
+
+    fn is_group(&self, c: char) -> bool {
+        match self.group {
+            Some(group) if SPACES.contains(&group) => SPACES.contains(&c),
+            Some(group) => c == group,
+            None => false,
+        }
+    }
+}
+
+/// locale of an amount, a locale tag or its separators
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum AmountLocale {
+    Tag(String),
+    Separators(Separators),
+}
+
+impl AmountLocale {
+    pub fn separators(&self) -> Result<Separators, VaneRpcError> {
+        let separators = match self {
+            AmountLocale::Tag(tag) => Separators::of_locale(tag)?,
+            AmountLocale::Separators(separators) => *separators,
+        };
+        separators.validate()?;
+        Ok(separators)
+    }
+}
+
+/// amount in the token smallest unit along with its rendering in a locale
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizedAmount {
+    pub token: Token,
+    pub units: u128,
+    /// decimal amount in whole tokens with the separators of the locale, e.g. `1 234,56`
+    pub display: String,
+    pub symbol: String,
+}
+
+impl LocalizedAmount {
+    pub fn new(token: Token, units: u128, separators: Separators) -> Self {
+        Self {
+            token,
+            units,
+            display: format_amount(units, token.decimals(), separators),
+            symbol: token.symbol().to_string(),
+        }
+    }
+}
+
+/// read a human amount, e.g. `1,234.56`, into the token smallest unit. digit groups are optional
+/// but have three digits when present
+pub fn parse_amount(
+    amount: &str,
+    decimals: u32,
+    separators: Separators,
+) -> Result<u128, VaneRpcError> {
+    let invalid = |reason: &str| VaneRpcError::InvalidParams {
+        reason: format!("invalid amount {amount}: {reason}"),
+    };
+    let amount = amount.trim();
+    let (whole, fraction) = match amount.split_once(separators.decimal) {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (amount, None),
+    };
+    let groups: Vec<&str> = whole.split(|c: char| separators.is_group(c)).collect();
+    if groups.len() > 1
+        && (groups[0].is_empty()
+            || groups[0].len() > 3
+            || groups[1..].iter().any(|group| group.len() != 3))
+    {
+        Err(invalid("digits are grouped by three"))?
+    }
+    let whole = groups.concat();
+    let fraction = fraction.unwrap_or_default();
+    let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if !digits(&whole) || !digits(fraction) || (whole.is_empty() && fraction.is_empty()) {
+        Err(invalid("expected digits with a single decimal separator"))?
+    }
+    parse_units(&format!("{whole}.{fraction}"), decimals)
+}
+
+/// render an amount in the token smallest unit with the separators of a locale, e.g. `1 234,56`
+pub fn format_amount(amount: u128, decimals: u32, separators: Separators) -> String {
+    let formatted = format_units(amount, decimals);
+    let (whole, fraction) = formatted
+        .split_once('.')
+        .map_or((formatted.as_str(), None), |(whole, fraction)| {
+            (whole, Some(fraction))
+        });
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            if let Some(group) = separators.group {
+                grouped.push(group);
+            }
+        }
+        grouped.push(digit);
+    }
+    match fraction {
+        Some(fraction) => format!("{grouped}{}{fraction}", separators.decimal),
+        None => grouped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_are_read_and_rendered_in_locales() {
+        let en = AmountLocale::Tag("en-US".to_string()).separators().unwrap();
+        let fr = AmountLocale::Tag("fr".to_string()).separators().unwrap();
+        let de_ch = AmountLocale::Tag("de_CH".to_string()).separators().unwrap();
+
+        assert_eq!(parse_amount("1,234.56", 6, en), Ok(1_234_560_000));
+        assert_eq!(parse_amount("1234.56", 6, en), Ok(1_234_560_000));
+        assert_eq!(parse_amount("1 234,56", 6, fr), Ok(1_234_560_000));
+        assert_eq!(parse_amount("1\u{202f}234,56", 6, fr), Ok(1_234_560_000));
+        assert_eq!(parse_amount("1'234.5", 6, de_ch), Ok(1_234_500_000));
+        assert_eq!(parse_amount(",5", 6, fr), Ok(500_000));
+
+        // misplaced or foreign separators are refused rather than guessed
+        assert!(parse_amount("1,23.4", 6, en).is_err());
+        assert!(parse_amount("1.234,56", 6, en).is_err());
+        assert!(parse_amount("1,234.5678901", 6, en).is_err());
+        assert!(parse_amount(".", 6, en).is_err());
+
+        assert_eq!(format_amount(1_234_560_000, 6, en), "1,234.56");
+        assert_eq!(format_amount(1_234_560_000, 6, fr), "1 234,56");
+        assert_eq!(format_amount(123_000_000, 6, en), "123");
+        assert_eq!(
+            format_amount(1_000_000_000_000, 6, Separators::default()),
+            "1,000,000"
+        );
+
+        let explicit: AmountLocale =
+            serde_json::from_str(r#"{"decimal":",","group":"."}"#).unwrap();
+        assert_eq!(
+            explicit.separators().unwrap(),
+            Separators {
+                decimal: ',',
+                group: Some('.')
+            }
+        );
+        assert!(AmountLocale::Tag("xx".to_string()).separators().is_err());
+        let clashing = AmountLocale::Separators(Separators {
+            decimal: '.',
+            group: Some('.'),
+        });
+        assert!(clashing.separators().is_err());
+    }
+}
@@ -0,0 +1,271 @@
+//! Anonymous loss prevention aggregates
+//!
+//! folds the local tx event log into per day counts and rough usd values of the transfers vane stopped,
+//! e.g. a wrong network deposit the sender abandoned after the warning or an address the receiver did
+//! not attest. only the sums of a whole UTC day leave the node, values are rounded and a day with fewer
+//! than `MIN_REPORTED_TXS` prevented transfers is not reported, so no single transfer can be told apart.
+extern crate alloc;
+use crate::data_structure::{RejectReason, Token, TxEvent, TxStateMachine, TxStatus};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+pub const SECS_PER_DAY: u64 = 86_400;
+
+/// a warned tx never confirmed within this many seconds of its last event was abandoned by the sender
+pub const ABANDONED_AFTER_SECS: u64 = SECS_PER_DAY;
+
+/// days with fewer prevented transfers are not reported
+pub const MIN_REPORTED_TXS: u32 = 3;
+
+const WRONG_NETWORK_WARNINGS: [&str; 2] = ["CROSS_CHAIN_DEPOSIT", "EXCHANGE_NOT_CREDITED"];
+const RISKY_RECIPIENT_WARNINGS: [&str; 2] = ["BURN_ADDRESS", "CONTRACT_REJECTS_VALUE"];
+
+/// why a transfer did not go through
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreventedLoss {
+    /// warned the receiver does not credit the token on the selected network
+    WrongNetwork,
+    /// warned the receiver is a burn address or a contract rejecting the funds
+    RiskyRecipient,
+    /// the receiver did not attest the address
+    WrongAddress,
+    /// no vane user behind the address
+    UnregisteredReceiver,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PreventedTotal {
+    pub count: u32,
+    /// rounded to two significant digits
+    pub usd: u64,
+}
+
+/// prevented transfers of one UTC day
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LossPreventionReport {
+    /// days since the unix epoch
+    pub day: u64,
+    pub wrong_network: PreventedTotal,
+    pub risky_recipient: PreventedTotal,
+    pub wrong_address: PreventedTotal,
+    pub unregistered_receiver: PreventedTotal,
+}
+
+impl LossPreventionReport {
+    fn total_mut(&mut self, loss: PreventedLoss) -> &mut PreventedTotal {
+        match loss {
+            PreventedLoss::WrongNetwork => &mut self.wrong_network,
+            PreventedLoss::RiskyRecipient => &mut self.risky_recipient,
+            PreventedLoss::WrongAddress => &mut self.wrong_address,
+            PreventedLoss::UnregisteredReceiver => &mut self.unregistered_receiver,
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.wrong_network.count
+            + self.risky_recipient.count
+            + self.wrong_address.count
+            + self.unregistered_receiver.count
+    }
+}
+
+/// what a sent tx was prevented from, `abandoned` when the sender never confirmed a non final tx
+pub fn classify(tx: &TxStateMachine, abandoned: bool) -> Option<PreventedLoss> {
+    if matches!(tx.status, TxStatus::TxSubmissionPassed(_)) {
+        return None;
+    }
+    let warned = |codes: &[&str]| {
+        tx.warnings
+            .iter()
+            .any(|warning| codes.contains(&warning.code.as_str()))
+    };
+    if tx.status.is_final() || abandoned {
+        if warned(&WRONG_NETWORK_WARNINGS) {
+            return Some(PreventedLoss::WrongNetwork);
+        }
+        if warned(&RISKY_RECIPIENT_WARNINGS) {
+            return Some(PreventedLoss::RiskyRecipient);
+        }
+    }
+    match tx.status {
+        TxStatus::RecvAddrFailed => Some(PreventedLoss::WrongAddress),
+        TxStatus::RecvRejected(RejectReason::WrongChain) => Some(PreventedLoss::WrongNetwork),
+        TxStatus::RecvRejected(RejectReason::NotMyAddress | RejectReason::SuspectedPhishing) => {
+            Some(PreventedLoss::WrongAddress)
+        }
+        TxStatus::ReceiverNotRegistered => Some(PreventedLoss::UnregisteredReceiver),
+        _ => None,
+    }
+}
+
+/// usd value of `amount` in the token smallest unit, none without a price for the token
+pub fn usd_value(token: Token, amount: u128, usd_prices: &BTreeMap<String, f64>) -> Option<f64> {
+    let price = match usd_prices.get(&String::from(token)) {
+        Some(price) => *price,
+        None if token.symbol().starts_with("USD") => 1.0,
+        None => return None,
+    };
+    Some(amount as f64 / 10f64.powi(token.decimals() as i32) * price)
+}
+
+/// `value` rounded to two significant digits
+pub fn round_usd(value: f64) -> u64 {
+    if value < 1.0 {
+        return 0;
+    }
+    let magnitude = 10f64.powi(value.log10().floor() as i32 - 1);
+    ((value / magnitude).round() * magnitude) as u64
+}
+
+/// prevented transfers sent by this node and resolved on `day`, none when too few to be reported.
+/// a final tx resolves with its last event, an abandoned one `ABANDONED_AFTER_SECS` later, so a tx
+/// counts toward a single day and a day is only complete once `now` passed it
+pub fn aggregate(
+    events: impl IntoIterator<Item = TxEvent>,
+    day: u64,
+    now: u64,
+    usd_prices: &BTreeMap<String, f64>,
+) -> Option<LossPreventionReport> {
+    // first and latest event of every tx, the first one tells whether this node sent it
+    let mut txs: BTreeMap<_, (TxEvent, TxEvent)> = BTreeMap::new();
+    for event in events {
+        txs.entry(event.tx_id)
+            .and_modify(|(_, latest)| *latest = event.clone())
+            .or_insert((event.clone(), event));
+    }
+
+    let mut report = LossPreventionReport {
+        day,
+        ..Default::default()
+    };
+    let mut values = BTreeMap::<_, f64>::new();
+    for (first, latest) in txs.into_values() {
+        // requests received from other senders carry the inbound request id from the start
+        if first.tx.inbound_req_id.is_some() {
+            continue;
+        }
+        let (resolved_at, abandoned) = if latest.status.is_final() {
+            (latest.recorded_at, false)
+        } else {
+            (latest.recorded_at + ABANDONED_AFTER_SECS, true)
+        };
+        if resolved_at / SECS_PER_DAY != day || resolved_at > now {
+            continue;
+        }
+        let Some(loss) = classify(&latest.tx, abandoned) else {
+            continue;
+        };
+        report.total_mut(loss).count += 1;
+        let token = latest.tx.token.unwrap_or(Token::native(latest.tx.network));
+        if let Some(value) = usd_value(token, latest.tx.amount, usd_prices) {
+            *values.entry(loss as u8).or_default() += value;
+        }
+    }
+    if (day + 1) * SECS_PER_DAY > now || report.count() < MIN_REPORTED_TXS {
+        return None;
+    }
+    for loss in [
+        PreventedLoss::WrongNetwork,
+        PreventedLoss::RiskyRecipient,
+        PreventedLoss::WrongAddress,
+        PreventedLoss::UnregisteredReceiver,
+    ] {
+        report.total_mut(loss).usd = round_usd(values.get(&(loss as u8)).copied().unwrap_or(0.0));
+    }
+    Some(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structure::{TxWarning, Warning};
+    use alloc::vec;
+
+    const DAY: u64 = 20_000;
+
+    fn sent(status: TxStatus, warning: Option<TxWarning>, nonce: u32) -> TxStateMachine {
+        TxStateMachine {
+            tx_nonce: nonce,
+            status,
+            amount: 2_000_000_000_000_000_000,
+            token: Some(Token::Eth),
+            warnings: warning.map(Warning::from).into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    fn events(tx: TxStateMachine, at: u64) -> Vec<TxEvent> {
+        let genesis = TxStateMachine {
+            status: TxStatus::Genesis,
+            ..tx.clone()
+        };
+        vec![TxEvent::new(genesis, at - 10), TxEvent::new(tx, at)]
+    }
+
+    fn day_events() -> Vec<TxEvent> {
+        let noon = DAY * SECS_PER_DAY + SECS_PER_DAY / 2;
+        let wrong_network = TxWarning::CrossChainDeposit {
+            name: "bridge".into(),
+            chain: "Bnb".into(),
+        };
+        [
+            events(sent(TxStatus::RecvAddrFailed, None, 1), noon),
+            events(sent(TxStatus::ReceiverNotRegistered, None, 2), noon),
+            // abandoned the day before, resolves on `DAY`
+            events(
+                sent(TxStatus::NetConfirmed, Some(wrong_network), 3),
+                noon - SECS_PER_DAY,
+            ),
+            // submitted and unwarned abandoned txs are no prevention
+            events(sent(TxStatus::TxSubmissionPassed([0; 32]), None, 4), noon),
+            events(sent(TxStatus::NetConfirmed, None, 5), noon - SECS_PER_DAY),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn aggregates_the_prevented_transfers_of_a_complete_day() {
+        let mut prices = BTreeMap::new();
+        prices.insert(String::from("Eth"), 2_345.0);
+        let now = (DAY + 1) * SECS_PER_DAY;
+        let report = aggregate(day_events(), DAY, now, &prices).unwrap();
+        let total = PreventedTotal {
+            count: 1,
+            usd: 4_700,
+        };
+        assert_eq!(report.wrong_address, total);
+        assert_eq!(report.unregistered_receiver, total);
+        assert_eq!(report.wrong_network, total);
+        assert_eq!(report.risky_recipient, PreventedTotal::default());
+        assert_eq!(aggregate(day_events(), DAY, now - 1, &prices), None);
+    }
+
+    #[test]
+    fn received_txs_and_small_days_are_not_reported() {
+        let now = (DAY + 1) * SECS_PER_DAY;
+        let received = day_events()
+            .into_iter()
+            .map(|mut event| {
+                event.tx.inbound_req_id = Some(7);
+                event
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(aggregate(received, DAY, now, &BTreeMap::new()), None);
+        let report = aggregate(day_events(), DAY, now, &BTreeMap::new()).unwrap();
+        assert_eq!(report.count(), 3);
+        assert_eq!(report.wrong_address.usd, 0);
+    }
+
+    #[test]
+    fn usd_values_are_rounded() {
+        assert_eq!(round_usd(0.4), 0);
+        assert_eq!(round_usd(7.2), 7);
+        assert_eq!(round_usd(1_234.0), 1_200);
+        assert_eq!(round_usd(98_765.0), 99_000);
+        let prices = BTreeMap::new();
+        assert_eq!(usd_value(Token::UsdcEth, 5_000_000, &prices), Some(5.0));
+        assert_eq!(usd_value(Token::Eth, 1, &prices), None);
+    }
+}
@@ -0,0 +1,192 @@
+//! Split payments
+//!
+//! one logical payment can settle in several transfers, e.g. part of an invoice on ethereum and
+//! the rest on bnb. every part is an ordinary transfer attested by its receiver and signed by the
+//! sender on its own, carrying the id of the payment it belongs to. the payment status is folded
+//! from the tx event log like the other views of the node, a receiver accepting less than a part
+//! leaves the payment partially settled.
+extern crate alloc;
+use crate::data_structure::{Token, TxEvent, TxStateMachine, TxStatus};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use codec::Encode;
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+
+/// most transfers a payment is split into
+pub const MAX_PAYMENT_PARTS: usize = 4;
+
+/// one transfer of a split payment
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentPart {
+    pub receiver: String,
+    /// amount in the smallest unit of the token
+    pub amount: u128,
+    /// the network of the part is the one of the token
+    pub token: Token,
+    #[serde(default)]
+    pub memo: Option<String>,
+}
+
+/// id of a payment of `sender` split into `parts` within the idempotency `window`, a retry of the
+/// payment in the same window gets the same id and is not initiated again
+pub fn payment_id(sender: &str, parts: &[PaymentPart], window: u64) -> H256 {
+    let preimage = (sender, parts, window).encode();
+    H256::from(sp_core::hashing::blake2_256(&preimage[..]))
+}
+
+/// where a payment or one of its transfers is at
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PaymentState {
+    /// transfers are still waiting on an attestation or a signature
+    Pending,
+    /// every transfer was submitted on chain with the full amount
+    Settled,
+    /// some transfers were submitted, others failed or moved less than initiated
+    PartiallySettled,
+    /// no transfer was submitted
+    Failed,
+}
+
+/// a transfer of a split payment as it is now
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentChild {
+    pub tx_id: H256,
+    pub state: PaymentState,
+    /// latest state of the transfer, with the amount the receiver accepted if it settled for less
+    pub tx: TxStateMachine,
+}
+
+/// transfers of a split payment and how far they got
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentStatus {
+    pub payment_id: H256,
+    /// in the order they were initiated
+    pub children: Vec<PaymentChild>,
+    pub state: PaymentState,
+}
+
+impl PaymentStatus {
+    /// fold the events of the transfers of `payment_id`, in recorded order. none when no transfer
+    /// belongs to it
+    pub fn fold(payment_id: H256, events: impl IntoIterator<Item = TxEvent>) -> Option<Self> {
+        let mut first_seen: BTreeMap<H256, u64> = BTreeMap::new();
+        let mut latest: BTreeMap<H256, TxEvent> = BTreeMap::new();
+        for event in events {
+            if event.tx.parent_payment != Some(payment_id) {
+                continue;
+            }
+            first_seen.entry(event.tx_id).or_insert(event.recorded_at);
+            latest.insert(event.tx_id, event);
+        }
+
+        let mut children: Vec<PaymentChild> = latest
+            .into_values()
+            .map(|event| {
+                let state = match &event.status {
+                    TxStatus::TxSubmissionPassed(_) if event.tx.accepted_amount.is_some() => {
+                        PaymentState::PartiallySettled
+                    }
+                    TxStatus::TxSubmissionPassed(_) => PaymentState::Settled,
+                    status if status.is_final() => PaymentState::Failed,
+                    _ => PaymentState::Pending,
+                };
+                PaymentChild {
+                    tx_id: event.tx_id,
+                    state,
+                    tx: event.tx,
+                }
+            })
+            .collect();
+        children.sort_by_key(|child| first_seen.get(&child.tx_id).copied());
+        if children.is_empty() {
+            return None;
+        }
+
+        let count = |state: PaymentState| children.iter().filter(|c| c.state == state).count();
+        let state = if count(PaymentState::Pending) > 0 {
+            PaymentState::Pending
+        } else if count(PaymentState::Settled) == children.len() {
+            PaymentState::Settled
+        } else if count(PaymentState::Failed) == children.len() {
+            PaymentState::Failed
+        } else {
+            PaymentState::PartiallySettled
+        };
+        Some(PaymentStatus {
+            payment_id,
+            children,
+            state,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::VaneAddress;
+    use crate::data_structure::ChainSupported;
+    use alloc::vec;
+
+    #[test]
+    fn payments_fold_the_state_of_their_transfers() {
+        let payment = H256::repeat_byte(9);
+        let part = |nonce: u32, network: ChainSupported| TxStateMachine {
+            sender_address: VaneAddress::Evm([1; 20]),
+            receiver_address: VaneAddress::Evm([2; 20]),
+            network,
+            amount: 100,
+            tx_nonce: nonce,
+            parent_payment: Some(payment),
+            ..Default::default()
+        };
+        let event = |tx: &TxStateMachine, status: TxStatus, at: u64| {
+            TxEvent::new(
+                TxStateMachine {
+                    status,
+                    ..tx.clone()
+                },
+                at,
+            )
+        };
+        let on_eth = part(1, ChainSupported::Ethereum);
+        let on_bnb = part(2, ChainSupported::Bnb);
+        let unrelated = TxStateMachine {
+            parent_payment: None,
+            ..part(3, ChainSupported::Ethereum)
+        };
+        let mut events = vec![
+            event(&on_eth, TxStatus::Genesis, 10),
+            event(&on_bnb, TxStatus::Genesis, 11),
+            event(&unrelated, TxStatus::Genesis, 12),
+            event(&on_eth, TxStatus::TxSubmissionPassed([1; 32]), 20),
+        ];
+
+        let status = PaymentStatus::fold(payment, events.clone()).unwrap();
+        assert_eq!(status.state, PaymentState::Pending);
+        assert_eq!(status.children.len(), 2);
+        assert_eq!(status.children[0].tx.network, ChainSupported::Ethereum);
+        assert_eq!(status.children[0].state, PaymentState::Settled);
+
+        // the receiver on bnb settled for less
+        let accepted = TxStateMachine {
+            accepted_amount: Some(60),
+            ..on_bnb.clone()
+        };
+        events.push(event(&accepted, TxStatus::TxSubmissionPassed([2; 32]), 30));
+        let status = PaymentStatus::fold(payment, events.clone()).unwrap();
+        assert_eq!(status.children[1].tx.accepted_amount, Some(60));
+        assert_eq!(status.state, PaymentState::PartiallySettled);
+
+        events.truncate(4);
+        events.push(event(&on_bnb, TxStatus::TxSubmissionPassed([2; 32]), 30));
+        let status = PaymentStatus::fold(payment, events).unwrap();
+        assert_eq!(status.state, PaymentState::Settled);
+
+        assert_eq!(PaymentStatus::fold(H256::zero(), vec![]), None);
+    }
+}
@@ -0,0 +1,96 @@
+//! Peer exchange records
+//!
+//! an account owner signs a binding of the account to the node serving it. connected nodes exchange the
+//! bindings they verified, so a sender can find the receiver node without the registry. the signature
+//! covers the peer id and not the multi address, a wrong address only fails to dial as libp2p checks the
+//! peer id of the dialed node.
+extern crate alloc;
+use crate::data_structure::PeerRecord;
+use crate::relay::RelayRoute;
+use crate::revocation::AccountRevocation;
+use alloc::string::String;
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// binding of `account` to the node `peer_id`, signed by the account
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerBinding {
+    /// evm account
+    pub account: String,
+    pub peer_id: String,
+    pub multi_addr: String,
+    /// unix timestamp in seconds, the latest binding of an account wins
+    pub signed_at: u64,
+    /// EIP-191 signature of `PeerBinding::message` by the account
+    pub signature: Vec<u8>,
+}
+
+impl PeerBinding {
+    /// message the account signs to bind itself to the node `peer_id`
+    pub fn message(account: &str, peer_id: &str, signed_at: u64) -> String {
+        alloc::format!("vane peer binding of {account} to node {peer_id} signed at {signed_at}")
+    }
+
+    pub fn signed_message(&self) -> String {
+        Self::message(&self.account, &self.peer_id, self.signed_at)
+    }
+
+    /// whether this binding replaces `stored`, a binding of the same account signed earlier
+    pub fn supersedes(&self, stored: &PeerBinding) -> bool {
+        self.account.eq_ignore_ascii_case(&stored.account) && self.signed_at > stored.signed_at
+    }
+}
+
+/// payload of a peer exchange request and of its response
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub struct PeerExchangeMessage {
+    pub bindings: Vec<PeerBinding>,
+    /// routes of the mobile nodes reachable through a relay
+    pub relay_routes: Vec<RelayRoute>,
+    /// accounts revoked as compromised by their owner
+    pub revocations: Vec<AccountRevocation>,
+}
+
+impl From<PeerBinding> for PeerRecord {
+    fn from(binding: PeerBinding) -> Self {
+        PeerRecord {
+            record_id: String::new(),
+            peer_id: Some(binding.peer_id),
+            account_id1: Some(binding.account),
+            account_id2: None,
+            account_id3: None,
+            account_id4: None,
+            multi_addr: Some(binding.multi_addr),
+            keypair: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(account: &str, signed_at: u64) -> PeerBinding {
+        PeerBinding {
+            account: account.into(),
+            peer_id: "12D3KooWPeer".into(),
+            multi_addr: "/ip4/127.0.0.1/tcp/30333".into(),
+            signed_at,
+            signature: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn only_a_later_binding_of_the_same_account_supersedes() {
+        let stored = binding("0xAbC", 10);
+        assert!(binding("0xabc", 11).supersedes(&stored));
+        assert!(!binding("0xabc", 10).supersedes(&stored));
+        assert!(!binding("0xdef", 11).supersedes(&stored));
+        assert_eq!(
+            stored.signed_message(),
+            "vane peer binding of 0xAbC to node 12D3KooWPeer signed at 10"
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Validation plugins
+//!
+//! operators load wasm modules checking transfers against their own compliance rules, without
+//! forking the node. a plugin gets the tx as json at the hooks it exports and answers with a
+//! decision: a denial refuses the transition, a warning is shown to the sender like the ones of
+//! the safety checks.
+//!
+//! a plugin exports its `memory`, `alloc(len: i32) -> i32` returning where the node writes the tx
+//! json, and `pre_attestation` and/or `pre_submission`. a hook takes the `(ptr: i32, len: i32)` of
+//! the tx json and returns `(ptr << 32) | len` of its decision json, one of `{"decision":"allow"}`,
+//! `{"decision":"warn","reason":"…"}` or `{"decision":"deny","reason":"…"}`
+extern crate alloc;
+use crate::data_structure::{TxStateMachine, TxWarning, Warning};
+use crate::errors::VaneRpcError;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// where a transfer is handed to the plugins
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PluginHook {
+    /// the sender initiated the transfer, before the receiver is asked to attest
+    PreAttestation,
+    /// the sender confirmed the transfer, before it is submitted on chain
+    PreSubmission,
+}
+
+impl PluginHook {
+    pub const ALL: [PluginHook; 2] = [PluginHook::PreAttestation, PluginHook::PreSubmission];
+
+    /// name of the function a plugin exports for the hook
+    pub fn export(&self) -> &'static str {
+        match self {
+            PluginHook::PreAttestation => "pre_attestation",
+            PluginHook::PreSubmission => "pre_submission",
+        }
+    }
+}
+
+/// answer of a plugin about a transfer
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "decision", rename_all = "lowercase")]
+pub enum PluginDecision {
+    Allow,
+    Warn { reason: String },
+    Deny { reason: String },
+}
+
+/// apply the decisions of the plugins named with them to `tx`, the first denial refuses the
+/// transfer and warnings are added to the tx ones
+pub fn apply_decisions(
+    tx: &mut TxStateMachine,
+    decisions: Vec<(String, PluginDecision)>,
+) -> Result<(), VaneRpcError> {
+    for (plugin, decision) in decisions {
+        match decision {
+            PluginDecision::Allow => {}
+            PluginDecision::Warn { reason } => tx
+                .warnings
+                .push(Warning::from(TxWarning::Plugin { plugin, reason })),
+            PluginDecision::Deny { reason } => Err(VaneRpcError::PluginDenied { plugin, reason })?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn plugin_decisions_warn_or_refuse_transfers() {
+        let decision = |json: &str| serde_json::from_str::<PluginDecision>(json).unwrap();
+        assert_eq!(decision(r#"{"decision":"allow"}"#), PluginDecision::Allow);
+        let flagged = decision(r#"{"decision":"warn","reason":"first payout to the region"}"#);
+        let sanctioned = decision(r#"{"decision":"deny","reason":"sanctioned receiver"}"#);
+
+        let mut tx = TxStateMachine::default();
+        apply_decisions(
+            &mut tx,
+            vec![
+                ("geo".to_string(), flagged.clone()),
+                ("limits".to_string(), PluginDecision::Allow),
+            ],
+        )
+        .unwrap();
+        assert_eq!(tx.warnings.len(), 1);
+        assert_eq!(tx.warnings[0].code, "PLUGIN_WARNING");
+        assert_eq!(
+            tx.warnings[0].message,
+            "plugin geo: first payout to the region"
+        );
+        assert!(!tx.warnings[0].requires_override());
+
+        assert_eq!(
+            apply_decisions(
+                &mut tx,
+                vec![
+                    ("sanctions".to_string(), sanctioned),
+                    ("geo".to_string(), flagged),
+                ]
+            ),
+            Err(VaneRpcError::PluginDenied {
+                plugin: "sanctions".to_string(),
+                reason: "sanctioned receiver".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Operator defined transfer policies
+//!
+//! rules are stored by the node as the json document the operator submitted and evaluated at each tx
+//! state transition. a rule applies when all of its conditions hold, every applied rule is recorded in
+//! `TxStateMachine::policy_outcomes` so the sender sees why a transfer was blocked or held.
+extern crate alloc;
+use crate::data_structure::{ChainSupported, Token, TxStateMachine};
+use crate::errors::VaneRpcError;
+use alloc::string::String;
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PolicyRule {
+    pub id: String,
+    /// shown to the sender when the rule applies
+    pub description: String,
+    /// all of them must hold, a rule without conditions applies to every tx
+    pub conditions: Vec<Condition>,
+    pub action: PolicyAction,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Condition {
+    /// transfers of `token` above `amount`, in the token smallest unit
+    AmountAbove {
+        token: Token,
+        amount: u128,
+    },
+    /// the receiver is not in the local peer book, i.e. it was never resolved by this node
+    NotContact,
+    /// no transfer from the sender to the receiver was submitted before
+    NewRecipient,
+    OnNetworks {
+        networks: Vec<ChainSupported>,
+    },
+    /// utc hour within `from..to`, wrapping past midnight when `from` is after `to`
+    HoursUtc {
+        from: u8,
+        to: u8,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+pub enum PolicyAction {
+    /// the transition is refused
+    Block,
+    /// the transfer is only submitted once a second approver signed off
+    RequireSecondApprover,
+    /// the outcome is only reported
+    Warn,
+}
+
+/// a rule that applied to a tx
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleOutcome {
+    pub rule_id: String,
+    pub action: PolicyAction,
+    pub description: String,
+}
+
+/// facts about a tx the node looks up before evaluating
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PolicyContext {
+    pub is_contact: bool,
+    pub is_new_recipient: bool,
+    pub hour_utc: u8,
+}
+
+impl Condition {
+    pub fn holds(&self, tx: &TxStateMachine, context: &PolicyContext) -> bool {
+        match self {
+            Condition::AmountAbove { token, amount } => {
+                tx.token.unwrap_or(Token::native(tx.network)) == *token && tx.amount > *amount
+            }
+            Condition::NotContact => !context.is_contact,
+            Condition::NewRecipient => context.is_new_recipient,
+            Condition::OnNetworks { networks } => networks.contains(&tx.network),
+            Condition::HoursUtc { from, to } if from <= to => {
+                (*from..*to).contains(&context.hour_utc)
+            }
+            Condition::HoursUtc { from, to } => context.hour_utc >= *from || context.hour_utc < *to,
+        }
+    }
+}
+
+/// parse and validate a policy document, rule ids must be unique
+pub fn parse_policies(document: &str) -> Result<Vec<PolicyRule>, VaneRpcError> {
+    let rules: Vec<PolicyRule> =
+        serde_json::from_str(document).map_err(|err| VaneRpcError::InvalidParams {
+            reason: alloc::format!("invalid policy document: {err}"),
+        })?;
+    for (index, rule) in rules.iter().enumerate() {
+        if rules[..index].iter().any(|other| other.id == rule.id) {
+            Err(VaneRpcError::InvalidParams {
+                reason: alloc::format!("duplicate policy rule id {}", rule.id),
+            })?
+        }
+        let invalid_hours = rule.conditions.iter().any(|condition| {
+            matches!(condition, Condition::HoursUtc { from, to } if *from > 23 || *to > 24)
+        });
+        if invalid_hours {
+            Err(VaneRpcError::InvalidParams {
+                reason: alloc::format!("policy rule {} has hours outside of a day", rule.id),
+            })?
+        }
+    }
+    Ok(rules)
+}
+
+/// outcomes of the rules applying to `tx`, in rule order
+pub fn evaluate(
+    rules: &[PolicyRule],
+    tx: &TxStateMachine,
+    context: &PolicyContext,
+) -> Vec<RuleOutcome> {
+    rules
+        .iter()
+        .filter(|rule| {
+            rule.conditions
+                .iter()
+                .all(|condition| condition.holds(tx, context))
+        })
+        .map(|rule| RuleOutcome {
+            rule_id: rule.id.clone(),
+            action: rule.action,
+            description: rule.description.clone(),
+        })
+        .collect()
+}
+
+/// first blocking outcome as the error refusing the transition
+pub fn enforce(outcomes: &[RuleOutcome]) -> Result<(), VaneRpcError> {
+    match outcomes
+        .iter()
+        .find(|outcome| outcome.action == PolicyAction::Block)
+    {
+        Some(outcome) => Err(VaneRpcError::PolicyViolation {
+            rule_id: outcome.rule_id.clone(),
+            reason: outcome.description.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICIES: &str = r#"[
+        {
+            "id": "large-to-strangers",
+            "description": "transfers above 10k USDC to non contacts are blocked",
+            "conditions": [
+                { "type": "amountAbove", "token": "UsdcEth", "amount": 10000000000 },
+                { "type": "notContact" }
+            ],
+            "action": "Block"
+        },
+        {
+            "id": "new-recipients",
+            "description": "new recipients need a second approver",
+            "conditions": [{ "type": "newRecipient" }],
+            "action": "RequireSecondApprover"
+        },
+        {
+            "id": "bnb-curfew",
+            "description": "no bnb transfers after 22:00",
+            "conditions": [
+                { "type": "onNetworks", "networks": ["Bnb"] },
+                { "type": "hoursUtc", "from": 22, "to": 6 }
+            ],
+            "action": "Block"
+        }
+    ]"#;
+
+    fn usdc_transfer(amount: u128) -> TxStateMachine {
+        TxStateMachine {
+            network: ChainSupported::Ethereum,
+            token: Some(Token::UsdcEth),
+            amount,
+            ..Default::default()
+        }
+    }
+
+    fn applied(tx: &TxStateMachine, context: PolicyContext) -> Vec<String> {
+        let rules = parse_policies(POLICIES).unwrap();
+        evaluate(&rules, tx, &context)
+            .into_iter()
+            .map(|outcome| outcome.rule_id)
+            .collect()
+    }
+
+    #[test]
+    fn all_conditions_of_a_rule_must_hold() {
+        let stranger = PolicyContext::default();
+        let contact = PolicyContext {
+            is_contact: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            applied(&usdc_transfer(20_000_000_000), stranger),
+            vec!["large-to-strangers"]
+        );
+        assert!(applied(&usdc_transfer(20_000_000_000), contact).is_empty());
+        assert!(applied(&usdc_transfer(5_000_000_000), stranger).is_empty());
+
+        let outcomes = evaluate(
+            &parse_policies(POLICIES).unwrap(),
+            &usdc_transfer(20_000_000_000),
+            &stranger,
+        );
+        assert!(matches!(
+            enforce(&outcomes),
+            Err(VaneRpcError::PolicyViolation { rule_id, .. }) if rule_id == "large-to-strangers"
+        ));
+    }
+
+    #[test]
+    fn hour_windows_wrap_past_midnight() {
+        let bnb = TxStateMachine {
+            network: ChainSupported::Bnb,
+            ..Default::default()
+        };
+        let at = |hour_utc| PolicyContext {
+            is_contact: true,
+            hour_utc,
+            ..Default::default()
+        };
+        assert_eq!(applied(&bnb, at(23)), vec!["bnb-curfew"]);
+        assert_eq!(applied(&bnb, at(3)), vec!["bnb-curfew"]);
+        assert!(applied(&bnb, at(12)).is_empty());
+    }
+
+    #[test]
+    fn non_blocking_outcomes_are_only_reported() {
+        let context = PolicyContext {
+            is_contact: true,
+            is_new_recipient: true,
+            hour_utc: 12,
+        };
+        let outcomes = evaluate(
+            &parse_policies(POLICIES).unwrap(),
+            &usdc_transfer(1),
+            &context,
+        );
+        assert_eq!(outcomes[0].action, PolicyAction::RequireSecondApprover);
+        assert!(enforce(&outcomes).is_ok());
+    }
+
+    #[test]
+    fn invalid_documents_are_rejected() {
+        let duplicate = r#"[
+            { "id": "a", "description": "", "conditions": [], "action": "Warn" },
+            { "id": "a", "description": "", "conditions": [], "action": "Block" }
+        ]"#;
+        assert!(parse_policies(duplicate).is_err());
+        let hours = r#"[{ "id": "a", "description": "", "action": "Warn",
+            "conditions": [{ "type": "hoursUtc", "from": 25, "to": 3 }] }]"#;
+        assert!(parse_policies(hours).is_err());
+        assert!(parse_policies("{}").is_err());
+    }
+}
@@ -0,0 +1,106 @@
+//! Relay records
+//!
+//! a mobile node designates an always-on node of the same user as its relay. senders seal the
+//! attestation request to the mobile encryption key and hand it to the relay, which queues it until
+//! the mobile connects. the relay only ever sees the sealed request and the sealed reply.
+extern crate alloc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use base58::ToBase58;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// prefix of a sealed request a relay delivers over the attestation protocol
+pub const SEALED_REQUEST_PREFIX: &[u8] = b"\0vane-sealed\0";
+
+/// `mobile_peer_id` is reachable through `relay_peer_id`, signed by the mobile node identity
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayRoute {
+    pub mobile_peer_id: String,
+    pub relay_peer_id: String,
+    pub relay_multi_addr: String,
+    /// protobuf encoded libp2p public key of the mobile node, its peer id is derived from it
+    pub public_key: Vec<u8>,
+    /// x25519 public key requests to the mobile node are sealed to
+    pub encryption_key: [u8; 32],
+    /// seconds since the unix epoch the route was signed at, a later route replaces it and it
+    /// expires after a while
+    pub signed_at: u64,
+    /// signature of `RelayRoute::message` by the mobile node identity
+    pub signature: Vec<u8>,
+}
+
+impl RelayRoute {
+    /// message the mobile node signs to route its requests through the relay
+    pub fn message(&self) -> String {
+        alloc::format!(
+            "vane relay route of {} through {} sealed to {} at {}",
+            self.mobile_peer_id,
+            self.relay_peer_id,
+            self.encryption_key.to_base58(),
+            self.signed_at
+        )
+    }
+}
+
+/// attestation request sealed to the mobile node encryption key
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct SealedRequest {
+    pub mobile_peer_id: String,
+    /// x25519 public key of the sender for this request only
+    pub ephemeral_key: [u8; 32],
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+}
+
+/// reply of the mobile node, sealed with the key of the request it answers
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct SealedReply {
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+}
+
+/// requests of the relay protocol
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum RelayMessage {
+    /// a mobile node asks its relay to queue the requests sent to it, `push_token` is the device
+    /// token the relay passes to its push gateway when a request gets queued
+    Register {
+        route: RelayRoute,
+        push_token: Option<String>,
+    },
+    /// a sender hands a sealed request for a registered mobile node to the relay, answered with the
+    /// sealed reply once the mobile node connected and replied
+    Forward(SealedRequest),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_message_covers_both_nodes_and_the_key() {
+        let route = RelayRoute {
+            mobile_peer_id: "12D3KooWMobile".into(),
+            relay_peer_id: "12D3KooWRelay".into(),
+            relay_multi_addr: "/ip4/127.0.0.1/tcp/30333".into(),
+            public_key: Vec::new(),
+            encryption_key: [1; 32],
+            signed_at: 1_700_000_000,
+            signature: Vec::new(),
+        };
+        let message = route.message();
+        assert!(message.contains("12D3KooWMobile") && message.contains("12D3KooWRelay"));
+        let rotated = RelayRoute {
+            encryption_key: [2; 32],
+            ..route.clone()
+        };
+        assert_ne!(rotated.message(), message);
+        let resigned = RelayRoute {
+            signed_at: 1_700_000_001,
+            ..route.clone()
+        };
+        assert_ne!(resigned.message(), message);
+    }
+}
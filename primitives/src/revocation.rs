@@ -0,0 +1,70 @@
+//! Account revocations
+//!
+//! an account owner whose key leaked signs a revocation of the account with that key. nodes share the
+//! revocations they verified over peer exchange and the registry keeps them with the peer record of
+//! the node serving the account. a revoked account is never attested again and senders are warned
+//! about transfers to it. a revocation can not be withdrawn, whoever holds the leaked key could
+//! withdraw it as well.
+extern crate alloc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// revocation of the compromised `account`, signed by the account
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRevocation {
+    /// evm account
+    pub account: String,
+    /// unix timestamp in seconds
+    pub revoked_at: u64,
+    /// EIP-191 signature of `AccountRevocation::message` by the account
+    pub signature: Vec<u8>,
+}
+
+impl AccountRevocation {
+    /// message the account signs to revoke itself
+    pub fn message(account: &str, revoked_at: u64) -> String {
+        alloc::format!("vane revocation of compromised account {account} signed at {revoked_at}")
+    }
+
+    pub fn signed_message(&self) -> String {
+        Self::message(&self.account, self.revoked_at)
+    }
+
+    pub fn revokes(&self, account: &str) -> bool {
+        self.account.eq_ignore_ascii_case(account)
+    }
+}
+
+/// revocations of the registry `revocations` field, stored as a json list
+pub fn decode_revocations(field: Option<&str>) -> Vec<AccountRevocation> {
+    field
+        .and_then(|field| serde_json::from_str(field).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revocation_covers_the_account_in_any_casing() {
+        let revocation = AccountRevocation {
+            account: "0x63F9725f107358c9115BC9d86c72dD5823E9B1E6".into(),
+            revoked_at: 10,
+            signature: Vec::new(),
+        };
+        assert!(revocation.revokes("0x63f9725f107358c9115bc9d86c72dd5823e9b1e6"));
+        assert!(!revocation.revokes("0x4690152131E5399dE5E76801Fc7742A087829F00"));
+        assert_eq!(
+            revocation.signed_message(),
+            "vane revocation of compromised account 0x63F9725f107358c9115BC9d86c72dD5823E9B1E6 \
+             signed at 10"
+        );
+        let field = serde_json::to_string(&vec![revocation.clone()]).unwrap();
+        assert_eq!(decode_revocations(Some(&field)), vec![revocation]);
+        assert!(decode_revocations(Some("not json")).is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+//! Risk screening
+//!
+//! operators plug a risk provider, e.g. a chain analytics service, scoring the receiver of every
+//! transfer their node initiates and labelling the cluster the address belongs to. the assessment
+//! is recorded with the genesis tx in the tx event log. a receiver scored under the operator
+//! minimum or located in a jurisdiction the operator sanctions is refused before it is asked to
+//! attest.
+extern crate alloc;
+use crate::errors::VaneRpcError;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// highest risk score, the safest addresses
+pub const MAX_RISK_SCORE: u8 = 100;
+
+/// answer of a risk provider about an address
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressRisk {
+    /// from 0, the riskiest, to `MAX_RISK_SCORE`
+    pub score: u8,
+    /// labels of the cluster the address belongs to, e.g. `exchange` or `mixer`
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// ISO 3166-1 alpha-2 code of the jurisdiction the address is attributed to
+    #[serde(default)]
+    pub jurisdiction: Option<String>,
+}
+
+/// risk of the receiver of a tx as a provider assessed it
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskAssessment {
+    pub provider: String,
+    pub address: String,
+    pub score: u8,
+    pub labels: Vec<String>,
+    pub jurisdiction: Option<String>,
+    /// unix timestamp in seconds
+    pub assessed_at: u64,
+}
+
+/// what the operator requires of the receivers of its transfers
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RiskRequirements {
+    /// lowest score a receiver may have, unscored receivers are refused when set
+    pub min_score: Option<u8>,
+    /// ISO 3166-1 alpha-2 codes of the jurisdictions transfers are refused to
+    pub sanctioned_jurisdictions: Vec<String>,
+}
+
+impl RiskRequirements {
+    /// nothing is required, the screening is recorded only
+    pub fn is_empty(&self) -> bool {
+        self.min_score.is_none() && self.sanctioned_jurisdictions.is_empty()
+    }
+
+    /// check the assessment of `address`, `None` when the provider does not score it
+    pub fn check(
+        &self,
+        address: &str,
+        assessment: Option<&RiskAssessment>,
+    ) -> Result<(), VaneRpcError> {
+        let failed = |reason: String| VaneRpcError::RiskCheckFailed {
+            address: address.into(),
+            reason,
+        };
+        let Some(assessment) = assessment else {
+            return match self.min_score {
+                Some(_) => Err(failed("the address is not scored".into())),
+                None => Ok(()),
+            };
+        };
+        if let Some(min_score) = self.min_score.filter(|min| assessment.score < *min) {
+            Err(failed(format!(
+                "scored {} by {}, the minimum is {min_score}",
+                assessment.score, assessment.provider
+            )))?
+        }
+        let sanctioned = assessment.jurisdiction.as_ref().filter(|jurisdiction| {
+            self.sanctioned_jurisdictions
+                .iter()
+                .any(|sanctioned| sanctioned.eq_ignore_ascii_case(jurisdiction))
+        });
+        if let Some(jurisdiction) = sanctioned {
+            Err(failed(format!(
+                "attributed to the sanctioned jurisdiction {jurisdiction}"
+            )))?
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn receivers_are_checked_against_the_operator_requirements() {
+        let risk: AddressRisk =
+            serde_json::from_str(r#"{"score": 40, "labels": ["exchange"], "jurisdiction": "ir"}"#)
+                .unwrap();
+        let assessment = RiskAssessment {
+            provider: "analytics".to_string(),
+            address: "0x63F9725f107358c9115BC9d86c72dD5823E9B1E6".to_string(),
+            score: risk.score,
+            labels: risk.labels,
+            jurisdiction: risk.jurisdiction,
+            assessed_at: 1_700_000_000,
+        };
+        let address = assessment.address.clone();
+
+        // nothing required, screenings are only recorded
+        let record_only = RiskRequirements::default();
+        assert!(record_only.is_empty());
+        assert!(record_only.check(&address, Some(&assessment)).is_ok());
+        assert!(record_only.check(&address, None).is_ok());
+
+        let min_score = RiskRequirements {
+            min_score: Some(50),
+            ..Default::default()
+        };
+        assert_eq!(
+            min_score.check(&address, Some(&assessment)),
+            Err(VaneRpcError::RiskCheckFailed {
+                address: address.clone(),
+                reason: "scored 40 by analytics, the minimum is 50".to_string(),
+            })
+        );
+        assert!(min_score.check(&address, None).is_err());
+
+        let sanctions = RiskRequirements {
+            sanctioned_jurisdictions: vec!["IR".to_string(), "KP".to_string()],
+            ..Default::default()
+        };
+        assert!(sanctions.check(&address, Some(&assessment)).is_err());
+        // unscored receivers pass when no minimum score is required
+        assert!(sanctions.check(&address, None).is_ok());
+        let elsewhere = RiskAssessment {
+            jurisdiction: Some("DE".to_string()),
+            ..assessment
+        };
+        assert!(sanctions.check(&address, Some(&elsewhere)).is_ok());
+    }
+}
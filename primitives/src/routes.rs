@@ -0,0 +1,197 @@
+//! Cross chain payment routes
+//!
+//! the peer record of a vane user binds its accounts on every chain to the same node. when the sender
+//! balance does not cover a transfer on the chosen chain, the same asset can often be sent on another
+//! chain both the sender and the receiver have an account on, e.g. usdc on solana rather than on
+//! ethereum. the routes are suggested to the sender, never taken without it asking.
+extern crate alloc;
+use crate::accepted_assets::check_accepted;
+use crate::address::VaneAddress;
+use crate::data_structure::{ChainSupported, Discovery, Token, TxStateMachine};
+use crate::validation::candidate_chains;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+const TOKENS: [Token; 9] = [
+    Token::Dot,
+    Token::Bnb,
+    Token::Sol,
+    Token::Eth,
+    Token::UsdtSol,
+    Token::UsdcSol,
+    Token::UsdtEth,
+    Token::UsdcEth,
+    Token::UsdtDot,
+];
+
+/// the transfer sent on another chain from and to accounts bound to the same peers
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteSuggestion {
+    pub network: ChainSupported,
+    pub token: Token,
+    pub sender: String,
+    pub receiver: String,
+    /// amount of the transfer in the smallest unit of `token`
+    pub amount: u128,
+    /// sender balance of `token`, zero until the chain was asked
+    pub balance: u128,
+}
+
+/// token of the same asset as `token` on `network`, e.g. usdc on solana for usdc on ethereum.
+/// native tokens have no equivalent on other chains
+pub fn equivalent_token(token: Token, network: ChainSupported) -> Option<Token> {
+    TOKENS.into_iter().find(|candidate| {
+        ChainSupported::from(*candidate) == network && candidate.symbol() == token.symbol()
+    })
+}
+
+/// `amount` of `from` in the smallest unit of `to`, `None` when it has no exact equivalent
+pub fn convert_amount(amount: u128, from: Token, to: Token) -> Option<u128> {
+    let (from, to) = (from.decimals(), to.decimals());
+    if to >= from {
+        amount.checked_mul(10u128.checked_pow(to - from)?)
+    } else {
+        let scale = 10u128.checked_pow(from - to)?;
+        (amount % scale == 0).then_some(amount / scale)
+    }
+}
+
+/// accounts of the peer `account` is registered with, `account` included. empty when no peer
+/// registered it
+pub fn bound_accounts(peers: &[Discovery], account: &VaneAddress) -> Vec<String> {
+    peers
+        .iter()
+        .find(|peer| {
+            peer.account_ids
+                .iter()
+                .any(|id| VaneAddress::parse(id).is_ok_and(|id| id == *account))
+        })
+        .map(|peer| peer.account_ids.clone())
+        .unwrap_or_default()
+}
+
+/// routes of `tx` on the other chains of `networks` the accounts bound to the sender and the
+/// receiver share, for the asset of `tx` and when the receiver accepts it there
+pub fn candidate_routes(
+    peers: &[Discovery],
+    tx: &TxStateMachine,
+    networks: &[ChainSupported],
+) -> Vec<RouteSuggestion> {
+    let token = tx.token.unwrap_or(Token::native(tx.network));
+    let senders = bound_accounts(peers, &tx.sender_address);
+    let receivers = bound_accounts(peers, &tx.receiver_address);
+    let preferences: Vec<_> = peers
+        .iter()
+        .flat_map(|peer| peer.accepted_assets.clone())
+        .collect();
+    let on = |accounts: &[String], network: ChainSupported| {
+        accounts
+            .iter()
+            .find(|account| candidate_chains(account).contains(&network))
+            .cloned()
+    };
+
+    let mut routes = Vec::new();
+    for network in networks.iter().copied().filter(|n| *n != tx.network) {
+        let Some(route_token) = equivalent_token(token, network) else {
+            continue;
+        };
+        let (Some(sender), Some(receiver)) = (on(&senders, network), on(&receivers, network))
+        else {
+            continue;
+        };
+        let accepted = VaneAddress::parse(&receiver)
+            .is_ok_and(|address| check_accepted(&preferences, &address, route_token).is_ok());
+        let amount = convert_amount(tx.amount, token, route_token);
+        if let (true, Some(amount)) = (accepted, amount) {
+            routes.push(RouteSuggestion {
+                network,
+                token: route_token,
+                sender,
+                receiver,
+                amount,
+                balance: 0,
+            });
+        }
+    }
+    routes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accepted_assets::AcceptedAssets;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    const SOL_SENDER: &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+    const SOL_RECEIVER: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn peer(accounts: &[&str]) -> Discovery {
+        Discovery {
+            id: String::new(),
+            peer_id: None,
+            multi_addr: None,
+            account_ids: accounts.iter().map(|account| account.to_string()).collect(),
+            attestations: vec![],
+            revocations: vec![],
+            accepted_assets: vec![],
+        }
+    }
+
+    #[test]
+    fn routes_follow_the_accounts_both_peers_bound() {
+        let (sender, receiver) = (
+            "0x63F9725f107358c9115BC9d86c72dD5823E9B1E6",
+            "0x9858EfFD232B4033E47d90003D41EC34EcaEda94",
+        );
+        let mut peers = vec![
+            peer(&[sender, SOL_SENDER]),
+            peer(&[&receiver.to_lowercase(), SOL_RECEIVER]),
+        ];
+        let tx = TxStateMachine {
+            sender_address: VaneAddress::parse(sender).unwrap(),
+            receiver_address: VaneAddress::parse(receiver).unwrap(),
+            network: ChainSupported::Ethereum,
+            token: Some(Token::UsdcEth),
+            amount: 5_000_000,
+            ..Default::default()
+        };
+        let networks = [ChainSupported::Ethereum, ChainSupported::Solana];
+        assert_eq!(
+            candidate_routes(&peers, &tx, &networks),
+            vec![RouteSuggestion {
+                network: ChainSupported::Solana,
+                token: Token::UsdcSol,
+                sender: SOL_SENDER.to_string(),
+                receiver: SOL_RECEIVER.to_string(),
+                amount: 5_000_000,
+                balance: 0,
+            }]
+        );
+
+        // native tokens do not cross chains, nor do assets the receiver does not take there
+        let eth = TxStateMachine {
+            token: Some(Token::Eth),
+            ..tx.clone()
+        };
+        assert!(candidate_routes(&peers, &eth, &networks).is_empty());
+        peers[1].accepted_assets = vec![AcceptedAssets {
+            account: SOL_RECEIVER.to_string(),
+            tokens: vec![Token::UsdtSol],
+        }];
+        assert!(candidate_routes(&peers, &tx, &networks).is_empty());
+
+        assert_eq!(
+            convert_amount(1_000_000_000, Token::Eth, Token::Sol),
+            Some(1)
+        );
+        assert_eq!(convert_amount(1_500_000_001, Token::Eth, Token::Sol), None);
+        assert_eq!(
+            convert_amount(2, Token::Sol, Token::Eth),
+            Some(2_000_000_000)
+        );
+    }
+}
@@ -0,0 +1,404 @@
+//! Stable JSON representation of txs over the rpc
+//!
+//! `TxStateMachine` is the internal tx, scale encoded between nodes, and its fields change as the
+//! protocol grows. rpc clients and frontends see `RpcTx` instead: a documented JSON shape with
+//! explicit field names, amounts as decimal strings so javascript keeps their precision and the tx
+//! status as a plain string with its detail alongside. `TxStateMachine` serializes through it, so
+//! internal fields can change without breaking the frontend contract as long as the converters
+//! keep the shape. the OpenRPC document describes it as `TxStateMachine`.
+extern crate alloc;
+use crate::attestations::VerificationBadge;
+use crate::data_structure::{
+    AddressCorrection, ChainSupported, RejectReason, SecondApproval, SenderProfile,
+    SubmissionRoute, Token, TxKind, TxPriority, TxStateMachine, TxStatus, Warning,
+};
+use crate::durable_nonce::DurableNonce;
+use crate::environment::NetworkEnvironment;
+use crate::errors::VaneRpcError;
+use crate::fees::FeePreference;
+use crate::fiat::FiatQuote;
+use crate::policy::RuleOutcome;
+use crate::risk::RiskAssessment;
+use crate::sponsorship::Sponsorship;
+use crate::token_account::TokenAccountCreation;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::de::Error as SerdeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use sp_core::H256;
+
+/// tx as rpc clients see it
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTx {
+    /// canonical addresses
+    pub sender_address: String,
+    pub receiver_address: String,
+    pub multi_id: H256,
+    #[serde(default)]
+    pub multi_id_version: u8,
+    #[serde(default)]
+    pub recv_signature: Option<Vec<u8>>,
+    pub network: ChainSupported,
+    /// name of the `TxStatus`, e.g. `TxSubmissionPassed`
+    pub status: String,
+    /// reason of the failed statuses, reject reason of `RecvRejected` and 0x hex chain tx hash of
+    /// `TxSubmissionPassed`
+    #[serde(default)]
+    pub status_detail: Option<String>,
+    /// decimal amount in the smallest unit of the token
+    pub amount: String,
+    #[serde(default)]
+    pub signed_call_payload: Option<Vec<u8>>,
+    #[serde(default)]
+    pub call_payload: Option<[u8; 32]>,
+    /// p2p request ids, decimal strings out and decimal, hex or numbers in
+    #[serde(default)]
+    #[serde(serialize_with = "serialize_u64_as_string")]
+    #[serde(deserialize_with = "deserialize_u64_flexible")]
+    pub inbound_req_id: Option<u64>,
+    #[serde(default)]
+    #[serde(serialize_with = "serialize_u64_as_string")]
+    #[serde(deserialize_with = "deserialize_u64_flexible")]
+    pub outbound_req_id: Option<u64>,
+    pub tx_nonce: u32,
+    #[serde(default)]
+    pub fee_preference: Option<FeePreference>,
+    #[serde(default)]
+    pub submission_route: SubmissionRoute,
+    #[serde(default)]
+    pub token: Option<Token>,
+    #[serde(default)]
+    pub call_data: Option<Vec<u8>>,
+    #[serde(default)]
+    pub intent: Option<String>,
+    #[serde(default)]
+    pub warnings: Vec<Warning>,
+    #[serde(default)]
+    pub kind: TxKind,
+    #[serde(default)]
+    pub memo: Option<String>,
+    /// invoice or order id and labels of the transfer
+    #[serde(default)]
+    pub reference: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub policy_outcomes: Vec<RuleOutcome>,
+    #[serde(default)]
+    pub second_approval: Option<SecondApproval>,
+    #[serde(default)]
+    pub sender_profile: Option<SenderProfile>,
+    #[serde(default)]
+    pub receiver_badges: Vec<VerificationBadge>,
+    #[serde(default)]
+    pub sender_badges: Vec<VerificationBadge>,
+    #[serde(default)]
+    pub environment: NetworkEnvironment,
+    /// address and network the receiver proposed when rejecting the tx
+    #[serde(default)]
+    pub address_correction: Option<AddressCorrection>,
+    /// decimal amount the receiver accepted when lower than `amount`
+    #[serde(default)]
+    pub accepted_amount: Option<String>,
+    /// split payment the transfer is a part of
+    #[serde(default)]
+    pub parent_payment: Option<H256>,
+    /// risk provider assessment of the receiver, sender node only
+    #[serde(default)]
+    pub risk_assessment: Option<RiskAssessment>,
+    /// unix timestamp in seconds the transfer has to be submitted by
+    #[serde(default)]
+    pub deadline: Option<u64>,
+    #[serde(default)]
+    pub priority: TxPriority,
+    /// usd value and locked token price of a fiat denominated transfer
+    #[serde(default)]
+    pub fiat_quote: Option<FiatQuote>,
+    /// paymaster sponsoring the gas of a smart account sender, shown before the sender signs
+    #[serde(default)]
+    pub sponsorship: Option<Sponsorship>,
+    /// nonce account a solana tx is signed against
+    #[serde(default)]
+    pub durable_nonce: Option<DurableNonce>,
+    /// token account of a solana receiver the tx creates, its rent is paid by the sender
+    #[serde(default)]
+    pub receiver_token_account: Option<TokenAccountCreation>,
+}
+
+/// name and detail of `status`
+pub(crate) fn status_parts(status: &TxStatus) -> (&'static str, Option<String>) {
+    match status {
+        TxStatus::Genesis => ("Genesis", None),
+        TxStatus::RecvAddrConfirmed => ("RecvAddrConfirmed", None),
+        TxStatus::RecvAddrConfirmationPassed => ("RecvAddrConfirmationPassed", None),
+        TxStatus::NetConfirmed => ("NetConfirmed", None),
+        TxStatus::SenderConfirmed => ("SenderConfirmed", None),
+        TxStatus::SenderConfirmationfailed => ("SenderConfirmationfailed", None),
+        TxStatus::RecvAddrFailed => ("RecvAddrFailed", None),
+        TxStatus::FailedToSubmitTxn(reason) => ("FailedToSubmitTxn", Some(reason.clone())),
+        TxStatus::TxSubmissionPassed(hash) => (
+            "TxSubmissionPassed",
+            Some(format!("{:?}", H256::from(*hash))),
+        ),
+        TxStatus::ReceiverNotRegistered => ("ReceiverNotRegistered", None),
+        TxStatus::AwaitingSecondApproval => ("AwaitingSecondApproval", None),
+        TxStatus::ReceiverIncompatible(reason) => ("ReceiverIncompatible", Some(reason.clone())),
+        TxStatus::ReceiverUnreachable(reason) => ("ReceiverUnreachable", Some(reason.clone())),
+        TxStatus::DeliveredToReceiver => ("DeliveredToReceiver", None),
+        TxStatus::AnsweredOnOtherDevice => ("AnsweredOnOtherDevice", None),
+        TxStatus::RecvRejected(reason) => ("RecvRejected", Some(reason.name().to_string())),
+        TxStatus::Expired => ("Expired", None),
+        TxStatus::Withdrawn(reason) => ("Withdrawn", Some(reason.clone())),
+    }
+}
+
+fn parse_status(name: &str, detail: Option<String>) -> Result<TxStatus, VaneRpcError> {
+    let invalid = |reason: &str| VaneRpcError::InvalidParams {
+        reason: format!("invalid tx status {name}: {reason}"),
+    };
+    let status = match name {
+        "Genesis" => TxStatus::Genesis,
+        "RecvAddrConfirmed" => TxStatus::RecvAddrConfirmed,
+        "RecvAddrConfirmationPassed" => TxStatus::RecvAddrConfirmationPassed,
+        "NetConfirmed" => TxStatus::NetConfirmed,
+        "SenderConfirmed" => TxStatus::SenderConfirmed,
+        "SenderConfirmationfailed" => TxStatus::SenderConfirmationfailed,
+        "RecvAddrFailed" => TxStatus::RecvAddrFailed,
+        "FailedToSubmitTxn" => TxStatus::FailedToSubmitTxn(detail.unwrap_or_default()),
+        "TxSubmissionPassed" => {
+            let detail = detail.ok_or_else(|| invalid("missing tx hash"))?;
+            let hex = detail.strip_prefix("0x").unwrap_or(&detail);
+            if hex.len() != 64 {
+                Err(invalid("tx hash is not 32 bytes"))?
+            }
+            let mut hash = [0u8; 32];
+            for (i, byte) in hash.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)
+                    .map_err(|_| invalid("tx hash is not hex"))?;
+            }
+            TxStatus::TxSubmissionPassed(hash)
+        }
+        "ReceiverNotRegistered" => TxStatus::ReceiverNotRegistered,
+        "AwaitingSecondApproval" => TxStatus::AwaitingSecondApproval,
+        "ReceiverIncompatible" => TxStatus::ReceiverIncompatible(detail.unwrap_or_default()),
+        "ReceiverUnreachable" => TxStatus::ReceiverUnreachable(detail.unwrap_or_default()),
+        "DeliveredToReceiver" => TxStatus::DeliveredToReceiver,
+        "AnsweredOnOtherDevice" => TxStatus::AnsweredOnOtherDevice,
+        "Expired" => TxStatus::Expired,
+        "Withdrawn" => TxStatus::Withdrawn(detail.unwrap_or_default()),
+        "RecvRejected" => {
+            let detail = detail.ok_or_else(|| invalid("missing reject reason"))?;
+            TxStatus::RecvRejected(
+                RejectReason::parse(&detail).ok_or_else(|| invalid("unknown reject reason"))?,
+            )
+        }
+        _ => Err(invalid("unknown status"))?,
+    };
+    Ok(status)
+}
+
+fn serialize_u64_as_string<S>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(v) => serializer.serialize_str(&v.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+fn deserialize_u64_flexible<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    // First try as string
+    let value = Value::deserialize(deserializer)?;
+
+    match value {
+        // Handle direct number
+        Value::Number(n) => {
+            if let Some(num) = n.as_u64() {
+                Ok(Some(num))
+            } else {
+                Err(D::Error::custom("Invalid number format for u64"))
+            }
+        }
+        // Handle string (both normal and hex)
+        Value::String(s) => {
+            if let Some(stripped) = s.strip_prefix("0x") {
+                // Handle hex string
+                u64::from_str_radix(stripped, 16)
+                    .map(Some)
+                    .map_err(D::Error::custom)
+            } else {
+                // Handle decimal string
+                s.parse::<u64>().map(Some).map_err(D::Error::custom)
+            }
+        }
+        Value::Null => Ok(None),
+        _ => Err(D::Error::custom("Expected string, number, or null")),
+    }
+}
+
+impl From<TxStateMachine> for RpcTx {
+    fn from(tx: TxStateMachine) -> Self {
+        let (status, status_detail) = status_parts(&tx.status);
+        RpcTx {
+            sender_address: tx.sender_address.to_string(),
+            receiver_address: tx.receiver_address.to_string(),
+            multi_id: tx.multi_id,
+            multi_id_version: tx.multi_id_version,
+            recv_signature: tx.recv_signature,
+            network: tx.network,
+            status: status.to_string(),
+            status_detail,
+            amount: tx.amount.to_string(),
+            signed_call_payload: tx.signed_call_payload,
+            call_payload: tx.call_payload,
+            inbound_req_id: tx.inbound_req_id,
+            outbound_req_id: tx.outbound_req_id,
+            tx_nonce: tx.tx_nonce,
+            fee_preference: tx.fee_preference,
+            submission_route: tx.submission_route,
+            token: tx.token,
+            call_data: tx.call_data,
+            intent: tx.intent,
+            warnings: tx.warnings,
+            kind: tx.kind,
+            memo: tx.memo,
+            reference: tx.reference,
+            tags: tx.tags,
+            policy_outcomes: tx.policy_outcomes,
+            second_approval: tx.second_approval,
+            sender_profile: tx.sender_profile,
+            receiver_badges: tx.receiver_badges,
+            sender_badges: tx.sender_badges,
+            environment: tx.environment,
+            address_correction: tx.address_correction,
+            accepted_amount: tx.accepted_amount.map(|amount| amount.to_string()),
+            parent_payment: tx.parent_payment,
+            risk_assessment: tx.risk_assessment,
+            deadline: tx.deadline,
+            priority: tx.priority,
+            fiat_quote: tx.fiat_quote,
+            sponsorship: tx.sponsorship,
+            durable_nonce: tx.durable_nonce,
+            receiver_token_account: tx.receiver_token_account,
+        }
+    }
+}
+
+impl TryFrom<RpcTx> for TxStateMachine {
+    type Error = VaneRpcError;
+
+    fn try_from(tx: RpcTx) -> Result<Self, Self::Error> {
+        Ok(TxStateMachine {
+            sender_address: tx.sender_address.parse()?,
+            receiver_address: tx.receiver_address.parse()?,
+            multi_id: tx.multi_id,
+            multi_id_version: tx.multi_id_version,
+            recv_signature: tx.recv_signature,
+            network: tx.network,
+            status: parse_status(&tx.status, tx.status_detail)?,
+            amount: tx.amount.parse().map_err(|_| VaneRpcError::InvalidParams {
+                reason: format!("invalid amount {}", tx.amount),
+            })?,
+            signed_call_payload: tx.signed_call_payload,
+            call_payload: tx.call_payload,
+            inbound_req_id: tx.inbound_req_id,
+            outbound_req_id: tx.outbound_req_id,
+            tx_nonce: tx.tx_nonce,
+            fee_preference: tx.fee_preference,
+            submission_route: tx.submission_route,
+            token: tx.token,
+            call_data: tx.call_data,
+            intent: tx.intent,
+            warnings: tx.warnings,
+            kind: tx.kind,
+            memo: tx.memo,
+            reference: tx.reference,
+            tags: tx.tags,
+            policy_outcomes: tx.policy_outcomes,
+            second_approval: tx.second_approval,
+            sender_profile: tx.sender_profile,
+            receiver_badges: tx.receiver_badges,
+            sender_badges: tx.sender_badges,
+            environment: tx.environment,
+            address_correction: tx.address_correction,
+            accepted_amount: tx
+                .accepted_amount
+                .map(|amount| {
+                    amount.parse().map_err(|_| VaneRpcError::InvalidParams {
+                        reason: format!("invalid accepted amount {amount}"),
+                    })
+                })
+                .transpose()?,
+            parent_payment: tx.parent_payment,
+            // sealed for the nodes only
+            travel_rule: None,
+            risk_assessment: tx.risk_assessment,
+            deadline: tx.deadline,
+            priority: tx.priority,
+            fiat_quote: tx.fiat_quote,
+            sponsorship: tx.sponsorship,
+            durable_nonce: tx.durable_nonce,
+            receiver_token_account: tx.receiver_token_account,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn txs_round_trip_through_the_stable_json_shape() {
+        let tx = TxStateMachine {
+            amount: u128::MAX,
+            status: TxStatus::TxSubmissionPassed([7; 32]),
+            inbound_req_id: Some(u64::MAX),
+            tx_nonce: 3,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&tx).unwrap();
+        assert_eq!(json["amount"], u128::MAX.to_string());
+        assert_eq!(json["status"], "TxSubmissionPassed");
+        assert_eq!(json["statusDetail"], format!("0x{}", "07".repeat(32)));
+        assert_eq!(json["inboundReqId"], u64::MAX.to_string());
+        assert_eq!(json["network"], "Polkadot");
+        assert_eq!(serde_json::from_value::<TxStateMachine>(json).unwrap(), tx);
+
+        let unknown = RpcTx {
+            status: "Teleported".to_string(),
+            amount: "1".to_string(),
+            ..RpcTx::from(tx)
+        };
+        assert!(TxStateMachine::try_from(unknown).is_err());
+
+        let rejected = TxStateMachine {
+            status: TxStatus::RecvRejected(RejectReason::WrongChain),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&rejected).unwrap();
+        assert_eq!(json["status"], "RecvRejected");
+        assert_eq!(json["statusDetail"], "WrongChain");
+        assert_eq!(
+            serde_json::from_value::<TxStateMachine>(json).unwrap(),
+            rejected
+        );
+
+        let partial = TxStateMachine {
+            amount: 100,
+            accepted_amount: Some(60),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&partial).unwrap();
+        assert_eq!(json["acceptedAmount"], "60");
+        assert_eq!(
+            serde_json::from_value::<TxStateMachine>(json).unwrap(),
+            partial
+        );
+    }
+}
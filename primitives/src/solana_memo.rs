@@ -0,0 +1,40 @@
+//! Solana memos
+//!
+//! the memo of a solana transfer, e.g. the invoice reference the receiver reconciles it with or the
+//! memo an exchange credits a deposit by, goes on chain as an spl memo instruction next to the
+//! transfer. the memo program logs it for explorers and indexers, it has to be utf-8 and shares the
+//! tx size with the compute budget, durable nonce and token account creation instructions.
+extern crate alloc;
+use crate::errors::VaneRpcError;
+use alloc::format;
+use alloc::vec::Vec;
+
+/// spl memo program, v2 checking the signers listed with the instruction
+pub const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TyNXJxVFWxSL5LhNSoVFQ";
+/// longest memo, leaving room in the 1 232 bytes of a tx for the other instructions
+pub const MAX_MEMO_LEN: usize = 256;
+
+/// data of the memo instruction carrying `memo`
+pub fn memo_instruction_data(memo: &str) -> Result<Vec<u8>, VaneRpcError> {
+    if memo.is_empty() || memo.len() > MAX_MEMO_LEN {
+        Err(VaneRpcError::InvalidParams {
+            reason: format!("solana memos should have 1 to {MAX_MEMO_LEN} bytes"),
+        })?
+    }
+    Ok(memo.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memos_are_sent_as_their_utf8_bytes() {
+        let data = memo_instruction_data("INV-2024-031 März").unwrap();
+        assert_eq!(data, "INV-2024-031 März".as_bytes());
+        assert!(memo_instruction_data("").is_err());
+        let longest = "x".repeat(MAX_MEMO_LEN);
+        assert_eq!(memo_instruction_data(&longest).unwrap().len(), MAX_MEMO_LEN);
+        assert!(memo_instruction_data(&(longest + "x")).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! Gas sponsorship of smart account transfers
+//!
+//! the user operation of a smart account sender may have its gas paid by a paymaster rather than by
+//! the account, e.g. the receiver sponsoring the payments of its customers or an app paying for its
+//! users. the sender node asks its paymaster service when it builds the operation, the terms go
+//! with the tx so the sender sees who pays the gas before signing and the history records them.
+extern crate alloc;
+use alloc::string::String;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// terms a paymaster sponsors the gas of a user operation on
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct Sponsorship {
+    /// paymaster contract paying the gas
+    pub paymaster: String,
+    /// sponsor as named by the paymaster service, when it names one
+    pub sponsor: Option<String>,
+    /// most the paymaster pays, the gas limits of the operation at its max fee per gas, in wei
+    pub max_gas_cost: u128,
+}
+
+impl Sponsorship {
+    /// sponsorship of an operation using up to `gas_limit` gas at up to `max_fee_per_gas` wei
+    pub fn new(
+        paymaster: String,
+        sponsor: Option<String>,
+        gas_limit: u128,
+        max_fee_per_gas: u128,
+    ) -> Self {
+        Self {
+            paymaster,
+            sponsor,
+            max_gas_cost: gas_limit.saturating_mul(max_fee_per_gas),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn sponsorships_cover_the_gas_limits_at_the_max_fee() {
+        let paymaster = "0x0000000000000039cd5e8aE05257CE51C473ddd1".to_string();
+        let sponsorship = Sponsorship::new(paymaster, Some("Acme".to_string()), 250_000, 2_000);
+        assert_eq!(sponsorship.max_gas_cost, 500_000_000);
+        let json = serde_json::to_value(&sponsorship).unwrap();
+        assert_eq!(json["sponsor"], "Acme");
+        assert_eq!(json["maxGasCost"], 500_000_000);
+        let unbounded = Sponsorship::new(String::new(), None, u128::MAX, 2);
+        assert_eq!(unbounded.max_gas_cost, u128::MAX);
+    }
+}
@@ -0,0 +1,95 @@
+//! Solana associated token accounts
+//!
+//! spl tokens are held in a token account per wallet and mint, derived from both as the associated
+//! token account. a transfer to a wallet without one fails, so the sender node checks it exists
+//! before the receiver attests. a missing account can be created in the transfer tx itself, the
+//! sender then pays its rent exempt balance on top of the fee.
+extern crate alloc;
+use crate::data_structure::Token;
+use alloc::string::String;
+use anyhow::anyhow;
+use base58::{FromBase58, ToBase58};
+use codec::{Decode, Encode};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use serde::{Deserialize, Serialize};
+
+/// spl token program owning the token accounts
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+/// program deriving and creating the associated token accounts
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+/// size of the data of a token account, its rent exempt balance is queried for this size
+pub const TOKEN_ACCOUNT_LENGTH: usize = 165;
+/// data of the `CreateIdempotent` instruction, it does not fail when the account exists by then
+pub const CREATE_IDEMPOTENT_DATA: [u8; 1] = [1];
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+pub const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+
+/// mint of a solana token, `None` for sol and the tokens of other networks
+pub fn token_mint(token: Token) -> Option<&'static str> {
+    match token {
+        Token::UsdcSol => Some(USDC_MINT),
+        Token::UsdtSol => Some(USDT_MINT),
+        _ => None,
+    }
+}
+
+/// associated token account of `wallet` for `mint`, the first program address off the ed25519
+/// curve going down from bump 255
+pub fn associated_token_address(wallet: &str, mint: &str) -> Result<String, anyhow::Error> {
+    let mut seeds = account_bytes(wallet)?.to_vec();
+    seeds.extend_from_slice(&account_bytes(TOKEN_PROGRAM_ID)?);
+    seeds.extend_from_slice(&account_bytes(mint)?);
+    let program = account_bytes(ASSOCIATED_TOKEN_PROGRAM_ID)?;
+    for bump in (0..=u8::MAX).rev() {
+        let mut preimage = seeds.clone();
+        preimage.push(bump);
+        preimage.extend_from_slice(&program);
+        preimage.extend_from_slice(b"ProgramDerivedAddress");
+        let address = sp_core::hashing::sha2_256(&preimage);
+        if CompressedEdwardsY(address).decompress().is_none() {
+            return Ok(address.to_base58());
+        }
+    }
+    Err(anyhow!(
+        "{wallet} has no associated token address for {mint}"
+    ))
+}
+
+fn account_bytes(account: &str) -> Result<[u8; 32], anyhow::Error> {
+    account
+        .from_base58()
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(anyhow!("{account} is not a solana account"))
+}
+
+/// associated token account of the receiver the transfer tx creates before moving the tokens
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, Encode, Decode)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenAccountCreation {
+    /// associated token account, base58
+    pub token_account: String,
+    pub mint: String,
+    /// rent exempt balance the sender funds the account with, in lamports
+    pub rent: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn associated_token_accounts_are_off_curve_program_addresses() {
+        let wallet = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+        let mint = token_mint(Token::UsdcSol).unwrap();
+        let token_account = associated_token_address(wallet, mint).unwrap();
+        assert_eq!(
+            token_account,
+            "FGETo8T8wMcN2wCjav8VK6eh3dLk63evNDPxzLSJra8B"
+        );
+        let bytes = account_bytes(&token_account).unwrap();
+        assert!(CompressedEdwardsY(bytes).decompress().is_none());
+        assert_eq!(token_mint(Token::Sol), None);
+        assert!(associated_token_address("0xdead", mint).is_err());
+    }
+}
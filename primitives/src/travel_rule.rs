@@ -0,0 +1,137 @@
+//! Travel rule data
+//!
+//! exchanges and payment providers sending on behalf of their customers have to pass who the
+//! originator and the beneficiary of a transfer are to the receiving provider. the sender attaches
+//! that data, shaped after the IVMS101 natural and legal person records, to the transfer. the sender
+//! node seals it to itself and, when the attestation request goes out, to each receiver node, so
+//! only the two ends can read it. both export it along the tx receipt.
+extern crate alloc;
+use crate::address::VaneAddress;
+use crate::errors::VaneRpcError;
+use alloc::string::String;
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// originator and beneficiary of a transfer, with the providers serving them
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TravelRuleData {
+    pub originator: TravelRuleParty,
+    pub beneficiary: TravelRuleParty,
+    #[serde(default)]
+    pub originating_vasp: Option<Vasp>,
+    #[serde(default)]
+    pub beneficiary_vasp: Option<Vasp>,
+}
+
+/// a natural or legal person, as much of it as the jurisdiction requires
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TravelRuleParty {
+    /// full name of the natural person or registered name of the legal person
+    pub name: String,
+    /// account of the party in the transfer
+    pub account: String,
+    #[serde(default)]
+    pub geographic_address: Option<String>,
+    /// national identity, customer or registration number
+    #[serde(default)]
+    pub national_identification: Option<String>,
+    /// natural persons only, `YYYY-MM-DD`
+    #[serde(default)]
+    pub date_of_birth: Option<String>,
+    /// ISO 3166-1 alpha-2 code
+    #[serde(default)]
+    pub country_of_residence: Option<String>,
+}
+
+/// virtual asset service provider of one end
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vasp {
+    pub name: String,
+    /// legal entity identifier
+    #[serde(default)]
+    pub lei: Option<String>,
+}
+
+impl TravelRuleData {
+    /// the parties are named and hold the accounts of the transfer
+    pub fn check(&self, sender: &VaneAddress, receiver: &VaneAddress) -> Result<(), VaneRpcError> {
+        let parties = [
+            ("originator", &self.originator, sender),
+            ("beneficiary", &self.beneficiary, receiver),
+        ];
+        for (role, party, address) in parties {
+            if party.name.trim().is_empty() {
+                Err(VaneRpcError::InvalidParams {
+                    reason: alloc::format!("the travel rule {role} has no name"),
+                })?
+            }
+            if VaneAddress::parse(&party.account).ok().as_ref() != Some(address) {
+                Err(VaneRpcError::InvalidParams {
+                    reason: alloc::format!(
+                        "the travel rule {role} account is not the one of the transfer"
+                    ),
+                })?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// travel rule data sealed to the x25519 key of one node
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct SealedTravelRule {
+    /// x25519 public key the sender drew for this copy only
+    pub ephemeral_key: [u8; 32],
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+}
+
+/// travel rule data attached to a transfer
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct TravelRuleAttachment {
+    /// sealed to the sender node, which opens it for its receipts
+    pub sender_copy: SealedTravelRule,
+    /// sealed to the receiver node the attestation request was sent to
+    pub receiver_copy: Option<SealedTravelRule>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn travel_rule_parties_hold_the_transfer_accounts() {
+        let sender = "0x63F9725f107358c9115BC9d86c72dD5823E9B1E6";
+        let receiver = "0x4690152131E5399dE5E76801Fc7742A087829F00";
+        let data: TravelRuleData = serde_json::from_str(&alloc::format!(
+            r#"{{
+                "originator": {{"name": "Alice Doe", "account": "{}", "countryOfResidence": "DE"}},
+                "beneficiary": {{"name": "Acme GmbH", "account": "{receiver}"}},
+                "originatingVasp": {{"name": "Exchange A", "lei": "5299000J2N45DDNE4Y28"}}
+            }}"#,
+            sender.to_lowercase()
+        ))
+        .unwrap();
+        let (sender, receiver) = (
+            VaneAddress::parse(sender).unwrap(),
+            VaneAddress::parse(receiver).unwrap(),
+        );
+        assert_eq!(data.originator.country_of_residence.as_deref(), Some("DE"));
+        assert!(data.check(&sender, &receiver).is_ok());
+        assert!(data.check(&receiver, &sender).is_err());
+
+        let anonymous = TravelRuleData {
+            beneficiary: TravelRuleParty {
+                name: " ".to_string(),
+                ..data.beneficiary.clone()
+            },
+            ..data
+        };
+        assert!(anonymous.check(&sender, &receiver).is_err());
+    }
+}
@@ -0,0 +1,741 @@
+//! Fluent `TxStateMachine` construction for the node, clients and test fixtures
+//!
+//! ```ignore
+//! let tx = TxStateMachine::builder()
+//!     .eth()
+//!     .sender(sender)
+//!     .receiver(receiver)
+//!     .amount_ether("1.5")
+//!     .build()?;
+//! ```
+extern crate alloc;
+use crate::address::VaneAddress;
+use crate::data_structure::{
+    ChainSupported, SenderProfile, SubmissionRoute, Token, TxKind, TxPriority, TxStateMachine,
+    TxStatus, TxWarning, Warning,
+};
+use crate::deposits::{cross_chain_deposit, exchange_deposit};
+use crate::environment::NetworkEnvironment;
+use crate::errors::VaneRpcError;
+use crate::fees::FeePreference;
+use crate::fiat::FiatQuote;
+use crate::solana_memo::memo_instruction_data;
+use crate::travel_rule::TravelRuleAttachment;
+use crate::validation::{detect_network, fat_finger_amount, is_burn_address, validate_transfer};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use codec::Encode;
+use sp_core::H256;
+
+/// version of the multi id preimage, txs of another version come from outdated clients and are
+/// refused as such instead of failing as if they were tampered with
+pub const MULTI_ID_VERSION: u8 = 1;
+
+/// what a multi id binds, scale encoded with the version as the variant index so the preimages of
+/// two versions never collide
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum MultiIdPreimage {
+    #[codec(index = 1)]
+    V1 {
+        /// canonical address bytes, every spelling of an address binds the same
+        sender: Vec<u8>,
+        receiver: Vec<u8>,
+        network: ChainSupported,
+        /// evm chain id of the network environment, `None` on the other networks
+        chain_id: Option<u64>,
+        amount: u128,
+        tx_nonce: u32,
+    },
+}
+
+impl MultiIdPreimage {
+    /// preimage of `tx` under `MULTI_ID_VERSION`
+    pub fn of(tx: &TxStateMachine) -> Self {
+        MultiIdPreimage::V1 {
+            sender: tx.sender_address.as_bytes().to_vec(),
+            receiver: tx.receiver_address.as_bytes().to_vec(),
+            network: tx.network,
+            chain_id: tx.environment.chain_id(tx.network),
+            amount: tx.amount,
+            tx_nonce: tx.tx_nonce,
+        }
+    }
+
+    pub fn version(&self) -> u8 {
+        match self {
+            MultiIdPreimage::V1 { .. } => 1,
+        }
+    }
+
+    pub fn hash(&self) -> H256 {
+        H256::from(sp_core::hashing::blake2_256(&self.encode()))
+    }
+}
+
+/// multi id of `tx` under `MULTI_ID_VERSION`
+pub fn multi_id(tx: &TxStateMachine) -> H256 {
+    MultiIdPreimage::of(tx).hash()
+}
+
+/// check the multi id of `tx` binds its addresses, network, amount and nonce
+pub fn verify_multi_id(tx: &TxStateMachine) -> Result<(), VaneRpcError> {
+    if tx.multi_id_version != MULTI_ID_VERSION {
+        Err(VaneRpcError::InvalidParams {
+            reason: format!(
+                "multi id version {} of an outdated client, this node verifies version \
+                 {MULTI_ID_VERSION}",
+                tx.multi_id_version
+            ),
+        })?
+    }
+    if multi_id(tx) != tx.multi_id {
+        Err(VaneRpcError::InvalidTxState {
+            status: format!("{:?}", tx.status),
+            reason: "multi id does not match the tx".to_string(),
+        })?
+    }
+    Ok(())
+}
+
+/// idempotency key of a transfer when the client does not pass one, identical transfers share it
+pub fn derive_idempotency_key(
+    sender: &str,
+    receiver: &str,
+    amount: u128,
+    token: Token,
+    network: ChainSupported,
+) -> String {
+    let preimage = (sender, receiver, amount, token, network).encode();
+    format!(
+        "{:#x}",
+        H256::from(sp_core::hashing::blake2_256(&preimage[..]))
+    )
+}
+
+/// convert a decimal amount, e.g. `1.5`, into the token smallest unit
+pub fn parse_units(amount: &str, decimals: u32) -> Result<u128, VaneRpcError> {
+    let invalid = |reason: String| VaneRpcError::InvalidParams {
+        reason: format!("invalid amount {amount}: {reason}"),
+    };
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if fraction.len() > decimals as usize {
+        Err(invalid(format!("more than {decimals} decimals")))?
+    }
+    let whole = if whole.is_empty() { "0" } else { whole };
+    let padded_fraction = format!("{fraction:0<width$}", width = decimals as usize);
+
+    let whole = whole
+        .parse::<u128>()
+        .map_err(|err| invalid(err.to_string()))?;
+    let fraction = if padded_fraction.is_empty() {
+        0
+    } else {
+        padded_fraction
+            .parse::<u128>()
+            .map_err(|err| invalid(err.to_string()))?
+    };
+    whole
+        .checked_mul(10u128.pow(decimals))
+        .and_then(|whole| whole.checked_add(fraction))
+        .ok_or(invalid("overflow".to_string()))
+}
+
+/// render an amount in the token smallest unit as a decimal, e.g. `1500000` with 6 decimals is `1.5`
+pub fn format_units(amount: u128, decimals: u32) -> String {
+    let unit = 10u128.pow(decimals);
+    let fraction = format!("{:0>width$}", amount % unit, width = decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{}", amount / unit)
+    } else {
+        format!("{}.{fraction}", amount / unit)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Amount {
+    Units(u128),
+    /// decimal amount, `None` uses the token decimals
+    Decimal {
+        amount: String,
+        decimals: Option<u32>,
+    },
+}
+
+/// longest sender display name shown to the receiver
+pub const MAX_DISPLAY_NAME_LEN: usize = 64;
+/// longest reference of a transfer
+pub const MAX_REFERENCE_LEN: usize = 64;
+/// most tags of a transfer and longest tag
+pub const MAX_TAGS: usize = 8;
+pub const MAX_TAG_LEN: usize = 32;
+
+/// builder returned by `TxStateMachine::builder`
+#[derive(Clone, Debug, Default)]
+pub struct TxStateMachineBuilder {
+    sender: Option<String>,
+    receiver: Option<String>,
+    network: Option<ChainSupported>,
+    token: Option<Token>,
+    amount: Option<Amount>,
+    tx_nonce: u32,
+    status: TxStatus,
+    fee_preference: Option<FeePreference>,
+    submission_route: SubmissionRoute,
+    kind: TxKind,
+    memo: Option<String>,
+    reference: Option<String>,
+    tags: Vec<String>,
+    sender_profile: Option<SenderProfile>,
+    environment: NetworkEnvironment,
+    parent_payment: Option<H256>,
+    travel_rule: Option<TravelRuleAttachment>,
+    deadline: Option<u64>,
+    priority: TxPriority,
+    fiat_quote: Option<FiatQuote>,
+}
+
+impl TxStateMachine {
+    pub fn builder() -> TxStateMachineBuilder {
+        TxStateMachineBuilder::default()
+    }
+}
+
+impl TxStateMachineBuilder {
+    pub fn sender(mut self, address: impl Into<String>) -> Self {
+        self.sender = Some(address.into());
+        self
+    }
+
+    pub fn receiver(mut self, address: impl Into<String>) -> Self {
+        self.receiver = Some(address.into());
+        self
+    }
+
+    pub fn network(mut self, network: ChainSupported) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    pub fn eth(self) -> Self {
+        self.network(ChainSupported::Ethereum)
+    }
+
+    pub fn bnb(self) -> Self {
+        self.network(ChainSupported::Bnb)
+    }
+
+    pub fn sol(self) -> Self {
+        self.network(ChainSupported::Solana)
+    }
+
+    pub fn dot(self) -> Self {
+        self.network(ChainSupported::Polkadot)
+    }
+
+    /// token to send, defaults to the network native token
+    pub fn token(mut self, token: Token) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// amount in the token smallest unit
+    pub fn amount(mut self, amount: u128) -> Self {
+        self.amount = Some(Amount::Units(amount));
+        self
+    }
+
+    /// decimal amount in whole tokens, converted with the token decimals
+    pub fn amount_decimal(mut self, amount: impl Into<String>) -> Self {
+        self.amount = Some(Amount::Decimal {
+            amount: amount.into(),
+            decimals: None,
+        });
+        self
+    }
+
+    /// decimal amount with 18 decimals, e.g. ether or bnb
+    pub fn amount_ether(mut self, amount: impl Into<String>) -> Self {
+        self.amount = Some(Amount::Decimal {
+            amount: amount.into(),
+            decimals: Some(18),
+        });
+        self
+    }
+
+    /// fee tier and max fee of the chain tx, evm networks only
+    pub fn fee_preference(mut self, preference: FeePreference) -> Self {
+        self.fee_preference = Some(preference);
+        self
+    }
+
+    /// send the signed tx to a private relay instead of the public mempool, ethereum only
+    pub fn submission_route(mut self, route: SubmissionRoute) -> Self {
+        self.submission_route = route;
+        self
+    }
+
+    /// approve the receiver to spend `amount` of the sender tokens instead of transferring them,
+    /// a zero amount revokes the allowance
+    pub fn approval(mut self) -> Self {
+        self.kind = TxKind::Approval;
+        self
+    }
+
+    /// memo or destination tag carried with the transfer, e.g. the reference an exchange credits by
+    pub fn memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// invoice or order id the transfer settles, kept in the history for reconciliation
+    pub fn reference(mut self, reference: impl Into<String>) -> Self {
+        self.reference = Some(reference.into());
+        self
+    }
+
+    /// labels of the transfer, searchable in the history
+    pub fn tags(mut self, tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// split payment the transfer is a part of
+    pub fn parent_payment(mut self, payment_id: H256) -> Self {
+        self.parent_payment = Some(payment_id);
+        self
+    }
+
+    /// originator and beneficiary data, sealed by the sender node
+    pub fn travel_rule(mut self, attachment: TravelRuleAttachment) -> Self {
+        self.travel_rule = Some(attachment);
+        self
+    }
+
+    /// unix timestamp in seconds the transfer has to be submitted by
+    pub fn deadline(mut self, deadline: u64) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// priority of the transfer, normal when not set
+    pub fn priority(mut self, priority: TxPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// usd value and locked token price the amount of a fiat denominated transfer was computed with
+    pub fn fiat_quote(mut self, quote: FiatQuote) -> Self {
+        self.fiat_quote = Some(quote);
+        self
+    }
+
+    /// display name and sender signature of `SenderProfile::message`, shown to the receiver
+    pub fn sender_profile(mut self, display_name: impl Into<String>, signature: Vec<u8>) -> Self {
+        self.sender_profile = Some(SenderProfile {
+            display_name: display_name.into(),
+            signature,
+            ..Default::default()
+        });
+        self
+    }
+
+    /// environment of the network the tx is sent on, mainnet by default
+    pub fn environment(mut self, environment: NetworkEnvironment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// vane tx nonce
+    pub fn tx_nonce(mut self, tx_nonce: u32) -> Self {
+        self.tx_nonce = tx_nonce;
+        self
+    }
+
+    /// starting status, `Genesis` by default
+    pub fn status(mut self, status: TxStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// validate the addresses against the token and network and derive `multi_id`, without a
+    /// network or token the network is inferred from the receiver address format
+    pub fn build(self) -> Result<TxStateMachine, VaneRpcError> {
+        let missing = |field: &str| VaneRpcError::InvalidParams {
+            reason: format!("{field} is required"),
+        };
+        let sender = self.sender.ok_or(missing("sender"))?;
+        let receiver = self.receiver.ok_or(missing("receiver"))?;
+        let network = match (self.network, self.token) {
+            (Some(network), _) => network,
+            (None, Some(token)) => token.into(),
+            (None, None) => detect_network(&receiver)?,
+        };
+        let token = self.token.unwrap_or(Token::native(network));
+        if ChainSupported::from(token) != network {
+            Err(VaneRpcError::WrongNetworkSuspected {
+                expected: network.into(),
+                found: ChainSupported::from(token).into(),
+            })?
+        }
+        let network = validate_transfer(&sender, &receiver, token, network)?;
+
+        let amount = match self.amount.ok_or(missing("amount"))? {
+            Amount::Units(amount) => amount,
+            Amount::Decimal { amount, decimals } => {
+                parse_units(&amount, decimals.unwrap_or(token.decimals()))?
+            }
+        };
+        match self.kind {
+            TxKind::Transfer if amount == 0 => Err(VaneRpcError::InvalidParams {
+                reason: "amount should be greater than zero".to_string(),
+            })?,
+            TxKind::Approval if token == Token::native(network) => {
+                Err(VaneRpcError::InvalidParams {
+                    reason: format!("{token:?} is native, only contract tokens are approved"),
+                })?
+            }
+            _ => {}
+        }
+        if let Some(preference) = self.fee_preference {
+            if !matches!(network, ChainSupported::Ethereum | ChainSupported::Bnb) {
+                Err(VaneRpcError::InvalidParams {
+                    reason: format!("fee preference is not supported on {network:?}"),
+                })?
+            }
+            if preference.max_fee_per_gas == Some(0) {
+                Err(VaneRpcError::InvalidParams {
+                    reason: "max fee per gas should be greater than zero".to_string(),
+                })?
+            }
+        }
+        if self.submission_route == SubmissionRoute::PrivateRelay
+            && network != ChainSupported::Ethereum
+        {
+            Err(VaneRpcError::InvalidParams {
+                reason: format!("private relay submission is not supported on {network:?}"),
+            })?
+        }
+
+        // evm chains carry the memo as calldata of a native transfer, token transfers have no room for it
+        if self.memo.is_some()
+            && matches!(network, ChainSupported::Ethereum | ChainSupported::Bnb)
+            && token != Token::native(network)
+        {
+            Err(VaneRpcError::InvalidParams {
+                reason: format!("a memo can not be attached to {token:?} transfers"),
+            })?
+        }
+        // solana carries it as a memo instruction next to the transfer, of native and spl tokens
+        if let (Some(memo), ChainSupported::Solana) = (&self.memo, network) {
+            memo_instruction_data(memo)?;
+        }
+
+        if let Some(profile) = &self.sender_profile {
+            let name_len = profile.display_name.trim().chars().count();
+            if name_len == 0 || name_len > MAX_DISPLAY_NAME_LEN {
+                Err(VaneRpcError::InvalidParams {
+                    reason: format!(
+                        "display name should have 1 to {MAX_DISPLAY_NAME_LEN} characters"
+                    ),
+                })?
+            }
+        }
+
+        let reference = self.reference.map(|reference| reference.trim().to_string());
+        if reference
+            .as_ref()
+            .is_some_and(|reference| reference.is_empty() || reference.len() > MAX_REFERENCE_LEN)
+        {
+            Err(VaneRpcError::InvalidParams {
+                reason: format!("reference should have 1 to {MAX_REFERENCE_LEN} bytes"),
+            })?
+        }
+        let mut tags = Vec::new();
+        for tag in self.tags.iter().map(|tag| tag.trim()) {
+            if tag.is_empty() || tag.len() > MAX_TAG_LEN {
+                Err(VaneRpcError::InvalidParams {
+                    reason: format!("tags should have 1 to {MAX_TAG_LEN} bytes"),
+                })?
+            }
+            if !tags
+                .iter()
+                .any(|kept: &String| kept.eq_ignore_ascii_case(tag))
+            {
+                tags.push(tag.to_string());
+            }
+        }
+        if tags.len() > MAX_TAGS {
+            Err(VaneRpcError::InvalidParams {
+                reason: format!("a transfer has at most {MAX_TAGS} tags"),
+            })?
+        }
+
+        let mut warnings = Vec::new();
+        if let Some(deposit) = exchange_deposit(&receiver) {
+            warnings.extend(deposit.check(network, token));
+        }
+        if is_burn_address(&receiver, network) {
+            warnings.push(TxWarning::BurnAddress);
+        }
+        if let Some(deposit) = cross_chain_deposit(&receiver, network) {
+            warnings.push(TxWarning::CrossChainDeposit {
+                name: deposit.name.to_string(),
+                chain: deposit.chain.to_string(),
+            });
+        }
+        if self.kind == TxKind::Transfer && fat_finger_amount(amount, token).is_some() {
+            warnings.push(TxWarning::RoundAmount { token, amount });
+        }
+
+        let (sender, receiver) = (VaneAddress::parse(&sender)?, VaneAddress::parse(&receiver)?);
+        let mut tx = TxStateMachine {
+            multi_id_version: MULTI_ID_VERSION,
+            sender_address: sender,
+            receiver_address: receiver,
+            network,
+            status: self.status,
+            amount,
+            tx_nonce: self.tx_nonce,
+            fee_preference: self.fee_preference,
+            submission_route: self.submission_route,
+            token: Some(token),
+            warnings: warnings.into_iter().map(Warning::from).collect(),
+            kind: self.kind,
+            memo: self.memo,
+            reference,
+            tags,
+            sender_profile: self.sender_profile,
+            environment: self.environment,
+            parent_payment: self.parent_payment,
+            travel_rule: self.travel_rule,
+            deadline: self.deadline,
+            priority: self.priority,
+            fiat_quote: self.fiat_quote,
+            ..Default::default()
+        };
+        tx.multi_id = multi_id(&tx);
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structure::DbTxStateMachine;
+    use codec::Decode;
+
+    const SENDER: &str = "0x4690152131E5399dE5E76801Fc7742A087829F00";
+    const RECEIVER: &str = "0x63F9725f107358c9115BC9d86c72dD5823E9B1E6";
+
+    #[test]
+    fn builder_derives_multi_id_and_amount() {
+        let tx = TxStateMachine::builder()
+            .eth()
+            .sender(SENDER)
+            .receiver(RECEIVER)
+            .amount_ether("1.5")
+            .tx_nonce(3)
+            .build()
+            .unwrap();
+
+        assert_eq!(tx.amount, 1_500_000_000_000_000_000);
+        assert_eq!(tx.multi_id, multi_id(&tx));
+        assert_eq!(tx.multi_id_version, MULTI_ID_VERSION);
+        assert_eq!(verify_multi_id(&tx), Ok(()));
+        assert_eq!(tx.receiver_address, RECEIVER.parse().unwrap());
+        // another spelling of the receiver binds the same
+        let lower_case = TxStateMachine::builder()
+            .eth()
+            .sender(SENDER)
+            .receiver(RECEIVER.to_lowercase())
+            .amount_ether("1.5")
+            .tx_nonce(3)
+            .build()
+            .unwrap();
+        assert_eq!(lower_case.multi_id, tx.multi_id);
+        assert_eq!(lower_case.receiver_address.to_string(), RECEIVER);
+        // the amount, nonce and chain id are bound too
+        let tampered = TxStateMachine {
+            amount: 1,
+            ..tx.clone()
+        };
+        assert!(matches!(
+            verify_multi_id(&tampered),
+            Err(VaneRpcError::InvalidTxState { .. })
+        ));
+        let sepolia = TxStateMachine {
+            environment: NetworkEnvironment::Testnet,
+            ..tx.clone()
+        };
+        assert_ne!(multi_id(&sepolia), tx.multi_id);
+        let outdated = TxStateMachine {
+            multi_id_version: 0,
+            ..tx.clone()
+        };
+        assert!(matches!(
+            verify_multi_id(&outdated),
+            Err(VaneRpcError::InvalidParams { .. })
+        ));
+        assert_eq!(tx.network, ChainSupported::Ethereum);
+        assert_eq!(tx.status, TxStatus::Genesis);
+        assert_eq!(tx.tx_nonce, 3);
+        assert!(tx.warnings.is_empty());
+
+        let burn = TxStateMachine::builder()
+            .eth()
+            .sender(SENDER)
+            .receiver("0x000000000000000000000000000000000000dEaD")
+            .amount(1)
+            .build()
+            .unwrap();
+        assert_eq!(burn.warnings, vec![Warning::from(TxWarning::BurnAddress)]);
+        assert_eq!(burn.warnings[0].code, "BURN_ADDRESS");
+
+        let l2_bridge = TxStateMachine::builder()
+            .eth()
+            .sender(SENDER)
+            .receiver("0x4200000000000000000000000000000000000010")
+            .amount(1)
+            .build()
+            .unwrap();
+        assert!(l2_bridge.warnings[0].requires_override());
+
+        let round = TxStateMachine::builder()
+            .token(Token::UsdtEth)
+            .sender(SENDER)
+            .receiver(RECEIVER)
+            .amount_decimal("10000")
+            .build()
+            .unwrap();
+        assert_eq!(round.warnings[0].code, "ROUND_AMOUNT");
+        assert!(round.warnings[0].message.contains("not 100 typed"));
+    }
+
+    #[test]
+    fn builder_rejects_invalid_txs() {
+        let missing_receiver = TxStateMachine::builder().eth().sender(SENDER).amount(1);
+        assert!(matches!(
+            missing_receiver.build(),
+            Err(VaneRpcError::InvalidParams { .. })
+        ));
+
+        let wrong_token = TxStateMachine::builder()
+            .eth()
+            .token(Token::Sol)
+            .sender(SENDER)
+            .receiver(RECEIVER)
+            .amount(1);
+        assert!(matches!(
+            wrong_token.build(),
+            Err(VaneRpcError::WrongNetworkSuspected { .. })
+        ));
+
+        // evm addresses exist on ethereum and bnb alike
+        let no_network = TxStateMachine::builder()
+            .sender(SENDER)
+            .receiver(RECEIVER)
+            .amount(1);
+        assert!(matches!(
+            no_network.build(),
+            Err(VaneRpcError::AmbiguousNetwork { .. })
+        ));
+
+        let zero_amount = TxStateMachine::builder()
+            .eth()
+            .sender(SENDER)
+            .receiver(RECEIVER)
+            .amount(0);
+        assert!(zero_amount.build().is_err());
+
+        let zero_max_fee = TxStateMachine::builder()
+            .eth()
+            .sender(SENDER)
+            .receiver(RECEIVER)
+            .amount(1)
+            .fee_preference(FeePreference {
+                max_fee_per_gas: Some(0),
+                ..Default::default()
+            });
+        assert!(zero_max_fee.build().is_err());
+
+        let private_bnb = TxStateMachine::builder()
+            .bnb()
+            .sender(SENDER)
+            .receiver(RECEIVER)
+            .amount(1)
+            .submission_route(SubmissionRoute::PrivateRelay);
+        assert!(private_bnb.build().is_err());
+
+        let native_approval = TxStateMachine::builder()
+            .eth()
+            .sender(SENDER)
+            .receiver(RECEIVER)
+            .amount(1)
+            .approval();
+        assert!(native_approval.build().is_err());
+
+        let blank_name = TxStateMachine::builder()
+            .eth()
+            .sender(SENDER)
+            .receiver(RECEIVER)
+            .amount(1)
+            .sender_profile("  ", vec![]);
+        assert!(blank_name.build().is_err());
+
+        let long_tag = TxStateMachine::builder()
+            .eth()
+            .sender(SENDER)
+            .receiver(RECEIVER)
+            .amount(1)
+            .tags(["x".repeat(MAX_TAG_LEN + 1)]);
+        assert!(long_tag.build().is_err());
+    }
+
+    #[test]
+    fn references_and_tags_are_kept_in_the_history() {
+        let tx = TxStateMachine::builder()
+            .eth()
+            .sender(SENDER)
+            .receiver(RECEIVER)
+            .amount(1)
+            .memo("march rent")
+            .reference(" INV-2024-031 ")
+            .tags(["payroll", "Payroll", "q1"])
+            .build()
+            .unwrap();
+        assert_eq!(tx.reference.as_deref(), Some("INV-2024-031"));
+        assert_eq!(tx.tags, ["payroll", "q1"]);
+        // not bound by the multi id
+        let retagged = TxStateMachine {
+            tags: vec![],
+            ..tx.clone()
+        };
+        assert_eq!(multi_id(&retagged), tx.multi_id);
+
+        let record = DbTxStateMachine::of(&tx, vec![], true);
+        assert!(record.matches("inv-2024-031"));
+        assert!(record.matches("Q1"));
+        assert!(record.matches("rent"));
+        assert!(!record.matches("INV-2024"));
+        // records stored before the reconciliation fields still decode
+        let legacy = (Vec::<u8>::new(), 1u128, ChainSupported::Ethereum, true).encode();
+        let legacy = DbTxStateMachine::decode(&mut &legacy[..]).unwrap();
+        assert_eq!((legacy.amount, legacy.reference), (1, None));
+        let stored = DbTxStateMachine::decode(&mut &record.encode()[..]).unwrap();
+        assert_eq!(stored.tags, record.tags);
+    }
+
+    #[test]
+    fn parse_n_format_units_works() {
+        assert_eq!(parse_units("1.5", 6).unwrap(), 1_500_000);
+        assert_eq!(parse_units(".25", 2).unwrap(), 25);
+        assert_eq!(parse_units("7", 0).unwrap(), 7);
+        assert!(parse_units("1.1234567", 6).is_err());
+        assert!(parse_units("abc", 6).is_err());
+        assert_eq!(format_units(1_500_000, 6), "1.5");
+        assert_eq!(format_units(7, 0), "7");
+        assert_eq!(format_units(25, 2), "0.25");
+    }
+}
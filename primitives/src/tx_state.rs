@@ -0,0 +1,336 @@
+//! Typestate wrappers over `TxStateMachine`
+//!
+//! each stage of a tx is its own type and transitions consume the previous stage, so skipping a stage
+//! or going backwards does not compile:
+//!
+//! `GenesisTx` → `RecvConfirmedTx` → `NetConfirmedTx` → `SenderConfirmedTx` → `SubmittedTx`
+//!
+//! nodes with a designated approver hold sender confirmed txs in `AwaitingApprovalTx` until it signs off.
+//! any stage before `SubmittedTx` enters `UnsubmittedTx` to expire once the deadline passed.
+//!
+//! untyped txs coming from rpc or p2p enter through `TryFrom<TxStateMachine>`, which rejects any status
+//! the stage does not accept. failure transitions return the plain `TxStateMachine` as nothing follows them.
+extern crate alloc;
+use crate::data_structure::{RejectReason, SecondApproval, TxStateMachine, TxStatus};
+use crate::errors::VaneRpcError;
+use crate::sponsorship::Sponsorship;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+macro_rules! typed_tx {
+    ($(#[$doc:meta])* $name:ident, $expected:literal, $pattern:pat) => {
+        $(#[$doc])*
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct $name(TxStateMachine);
+
+        impl $name {
+            pub fn into_inner(self) -> TxStateMachine {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = TxStateMachine;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl From<$name> for TxStateMachine {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl TryFrom<TxStateMachine> for $name {
+            type Error = VaneRpcError;
+
+            fn try_from(tx: TxStateMachine) -> Result<Self, Self::Error> {
+                match tx.status {
+                    $pattern => Ok(Self(tx)),
+                    ref status => Err(VaneRpcError::InvalidTxState {
+                        status: format!("{status:?}"),
+                        reason: format!("expected {}", $expected),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+typed_tx!(
+    /// initiated by the sender, waiting for the receiver attestation. `DeliveredToReceiver` once
+    /// the receiver node acknowledged the request
+    GenesisTx,
+    "Genesis or DeliveredToReceiver",
+    TxStatus::Genesis | TxStatus::DeliveredToReceiver
+);
+typed_tx!(
+    /// receiver signed its address, waiting for the sender node to verify the signature
+    RecvConfirmedTx,
+    "RecvAddrConfirmed",
+    TxStatus::RecvAddrConfirmed
+);
+typed_tx!(
+    /// receiver attestation verified, waiting for the sender to sign the call payload.
+    /// network simulation is not in place yet so `RecvAddrConfirmationPassed` is accepted as well
+    NetConfirmedTx,
+    "RecvAddrConfirmationPassed or NetConfirmed",
+    TxStatus::RecvAddrConfirmationPassed | TxStatus::NetConfirmed
+);
+typed_tx!(
+    /// sender signed the call payload, ready to be submitted
+    SenderConfirmedTx,
+    "SenderConfirmed",
+    TxStatus::SenderConfirmed
+);
+typed_tx!(
+    /// sender signed the call payload, waiting for the designated approver
+    AwaitingApprovalTx,
+    "AwaitingSecondApproval",
+    TxStatus::AwaitingSecondApproval
+);
+typed_tx!(
+    /// not handed to the chain yet, aborted once the deadline of the sender passed
+    UnsubmittedTx,
+    "a status before submission",
+    TxStatus::Genesis
+        | TxStatus::DeliveredToReceiver
+        | TxStatus::RecvAddrConfirmed
+        | TxStatus::RecvAddrConfirmationPassed
+        | TxStatus::NetConfirmed
+        | TxStatus::SenderConfirmed
+        | TxStatus::AwaitingSecondApproval
+);
+typed_tx!(
+    /// accepted by the chain
+    SubmittedTx,
+    "TxSubmissionPassed",
+    TxStatus::TxSubmissionPassed(_)
+);
+
+impl GenesisTx {
+    /// receiver node got the attestation request, its user has yet to confirm
+    pub fn delivered(mut self) -> GenesisTx {
+        self.0.status = TxStatus::DeliveredToReceiver;
+        self
+    }
+
+    /// receiver attested the address by signing it
+    pub fn recv_signed(mut self, recv_signature: Vec<u8>) -> RecvConfirmedTx {
+        self.0.recv_signature = Some(recv_signature);
+        self.0.status = TxStatus::RecvAddrConfirmed;
+        RecvConfirmedTx(self.0)
+    }
+
+    /// receiver declined the attestation request
+    pub fn recv_declined(mut self) -> TxStateMachine {
+        self.0.status = TxStatus::RecvAddrFailed;
+        self.0
+    }
+
+    /// receiver rejected the attestation request on purpose, telling the sender why
+    pub fn recv_rejected(mut self, reason: RejectReason) -> TxStateMachine {
+        self.0.status = TxStatus::RecvRejected(reason);
+        self.0
+    }
+
+    pub fn recv_not_registered(mut self) -> TxStateMachine {
+        self.0.status = TxStatus::ReceiverNotRegistered;
+        self.0
+    }
+
+    /// receiver node speaks an incompatible vane protocol, `reason` is shown to the user
+    pub fn recv_incompatible(mut self, reason: String) -> TxStateMachine {
+        self.0.status = TxStatus::ReceiverIncompatible(reason);
+        self.0
+    }
+
+    /// another device of the receiver answered the request, the sender withdrew it from this one
+    pub fn answered_on_other_device(mut self) -> TxStateMachine {
+        self.0.status = TxStatus::AnsweredOnOtherDevice;
+        self.0
+    }
+
+    /// receiver node could not be dialed, `reason` is shown to the user
+    pub fn recv_unreachable(mut self, reason: String) -> TxStateMachine {
+        self.0.status = TxStatus::ReceiverUnreachable(reason);
+        self.0
+    }
+}
+
+impl RecvConfirmedTx {
+    pub fn confirmation_passed(mut self) -> NetConfirmedTx {
+        self.0.status = TxStatus::RecvAddrConfirmationPassed;
+        NetConfirmedTx(self.0)
+    }
+
+    pub fn confirmation_failed(mut self) -> TxStateMachine {
+        self.0.status = TxStatus::RecvAddrFailed;
+        self.0
+    }
+}
+
+impl NetConfirmedTx {
+    /// signing hash of the chain tx the sender has to sign
+    pub fn set_call_payload(&mut self, call_payload: [u8; 32]) {
+        self.0.call_payload = Some(call_payload);
+    }
+
+    /// the chain tx is a contract call, `intent` describes it to the sender
+    pub fn set_contract_call(&mut self, call_data: Vec<u8>, intent: String) {
+        self.0.call_data = Some(call_data);
+        self.0.intent = Some(intent);
+    }
+
+    /// a paymaster sponsors the gas of the chain tx, the sender sees it before signing
+    pub fn set_sponsorship(&mut self, sponsorship: Option<Sponsorship>) {
+        self.0.sponsorship = sponsorship;
+    }
+
+    /// sender signed the call payload
+    pub fn sender_signed(mut self, signed_call_payload: Vec<u8>) -> SenderConfirmedTx {
+        self.0.signed_call_payload = Some(signed_call_payload);
+        self.0.status = TxStatus::SenderConfirmed;
+        SenderConfirmedTx(self.0)
+    }
+
+    /// the chain tx could not be built, e.g. its fee is above the cap
+    pub fn creation_failed(mut self, reason: String) -> TxStateMachine {
+        self.0.status = TxStatus::FailedToSubmitTxn(reason);
+        self.0
+    }
+}
+
+impl SenderConfirmedTx {
+    pub fn submission_passed(mut self, tx_hash: [u8; 32]) -> SubmittedTx {
+        self.0.status = TxStatus::TxSubmissionPassed(tx_hash);
+        SubmittedTx(self.0)
+    }
+
+    pub fn submission_failed(mut self, reason: String) -> TxStateMachine {
+        self.0.status = TxStatus::FailedToSubmitTxn(reason);
+        self.0
+    }
+
+    /// signer is not the original sender
+    pub fn sender_confirmation_failed(mut self) -> TxStateMachine {
+        self.0.status = TxStatus::SenderConfirmationfailed;
+        self.0
+    }
+
+    /// held for the designated approver
+    pub fn awaiting_second_approval(mut self) -> AwaitingApprovalTx {
+        self.0.status = TxStatus::AwaitingSecondApproval;
+        AwaitingApprovalTx(self.0)
+    }
+}
+
+impl AwaitingApprovalTx {
+    /// approver signed off, the sender signature is kept and the tx is ready to be submitted
+    pub fn approved(mut self, approval: SecondApproval) -> SenderConfirmedTx {
+        self.0.second_approval = Some(approval);
+        self.0.status = TxStatus::SenderConfirmed;
+        SenderConfirmedTx(self.0)
+    }
+}
+
+impl UnsubmittedTx {
+    pub fn expired(mut self) -> TxStateMachine {
+        self.0.status = TxStatus::Expired;
+        self.0
+    }
+}
+
+impl SubmittedTx {
+    pub fn tx_hash(&self) -> [u8; 32] {
+        match self.0.status {
+            TxStatus::TxSubmissionPassed(tx_hash) => tx_hash,
+            _ => unreachable!("submitted tx always carries its hash"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transitions_follow_the_tx_flow() {
+        let tx = GenesisTx::try_from(TxStateMachine::default()).unwrap();
+        let tx = tx.recv_signed(vec![1]).confirmation_passed();
+        let tx = tx.sender_signed(vec![2]).submission_passed([3; 32]);
+
+        assert_eq!(tx.tx_hash(), [3; 32]);
+        assert_eq!(tx.recv_signature, Some(vec![1]));
+        assert_eq!(tx.signed_call_payload, Some(vec![2]));
+    }
+
+    #[test]
+    fn held_txs_keep_the_sender_signature_once_approved() {
+        let tx = GenesisTx::try_from(TxStateMachine::default())
+            .unwrap()
+            .recv_signed(vec![1])
+            .confirmation_passed()
+            .sender_signed(vec![2])
+            .awaiting_second_approval();
+        assert!(SenderConfirmedTx::try_from(tx.clone().into_inner()).is_err());
+
+        let approval = SecondApproval {
+            approver: "approver".to_string(),
+            signature: vec![3],
+            approved_at: 4,
+        };
+        let tx = tx.approved(approval.clone());
+        assert_eq!(tx.status, TxStatus::SenderConfirmed);
+        assert_eq!(tx.signed_call_payload, Some(vec![2]));
+        assert_eq!(tx.second_approval, Some(approval));
+    }
+
+    #[test]
+    fn untyped_txs_are_checked_against_the_stage() {
+        let genesis = TxStateMachine::default();
+        assert!(matches!(
+            SenderConfirmedTx::try_from(genesis.clone()),
+            Err(VaneRpcError::InvalidTxState { .. })
+        ));
+
+        let failed = GenesisTx::try_from(genesis)
+            .unwrap()
+            .recv_signed(vec![1])
+            .confirmation_failed();
+        assert!(NetConfirmedTx::try_from(failed).is_err());
+    }
+
+    #[test]
+    fn only_unsubmitted_txs_expire() {
+        let confirmed = GenesisTx::try_from(TxStateMachine::default())
+            .unwrap()
+            .recv_signed(vec![1])
+            .confirmation_passed()
+            .sender_signed(vec![2]);
+        let expired = UnsubmittedTx::try_from(confirmed.clone().into_inner())
+            .unwrap()
+            .expired();
+        assert_eq!(expired.status, TxStatus::Expired);
+        assert!(expired.status.is_final());
+
+        let submitted = confirmed.submission_passed([3; 32]).into_inner();
+        assert!(UnsubmittedTx::try_from(submitted).is_err());
+        assert!(UnsubmittedTx::try_from(expired).is_err());
+    }
+
+    #[test]
+    fn delivered_txs_still_wait_for_the_receiver() {
+        let delivered = GenesisTx::try_from(TxStateMachine::default())
+            .unwrap()
+            .delivered()
+            .into_inner();
+        assert_eq!(delivered.status, TxStatus::DeliveredToReceiver);
+        let declined = GenesisTx::try_from(delivered).unwrap().recv_declined();
+        assert_eq!(declined.status, TxStatus::RecvAddrFailed);
+    }
+}
@@ -0,0 +1,413 @@
+//! Sender side address and network checks, free of io so they run in the node, native clients and wasm
+extern crate alloc;
+use crate::address::{checksummed_hex, ss58_checksum, VaneAddress};
+use crate::data_structure::{ChainSupported, Token};
+use crate::errors::VaneRpcError;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use anyhow::anyhow;
+use base58::FromBase58;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+
+/// per the network selected verify that it makes sense cryptographically to have that account address bytes format
+pub fn verify_public_bytes(
+    account: &str,
+    token: Token,
+    _network: ChainSupported,
+) -> Result<ChainSupported, anyhow::Error> {
+    match token {
+        Token::Dot | Token::UsdtDot => {
+            // ss58 with a valid prefix and checksum over the 32 bytes public key, sr25519 keys
+            // are ristretto points whose encoding can not be told apart from random bytes
+            match VaneAddress::parse(account) {
+                Ok(VaneAddress::Ss58 { .. }) => Ok(ChainSupported::Polkadot),
+                _ => Err(anyhow!("Not polkadot address")),
+            }
+        }
+        Token::Bnb => {
+            // check if it belongs to a point on Ecdsa secp256k1 curve
+            // check the derivation path which is m/44'/60'/0'/0
+            // check if it belongs to a point on Ecdsa secp256k1 curve !!! cannot do this as the public key is hashed
+            // check if the account is 20 bytes
+            match VaneAddress::parse(account) {
+                Ok(VaneAddress::Evm(_)) => Ok(ChainSupported::Bnb),
+                _ => Err(anyhow!("Not bnb address")),
+            }
+        }
+        Token::Sol | Token::UsdcSol | Token::UsdtSol => {
+            // check if it belongs to a point on Ed25519 curve
+            let bytes = account
+                .from_base58()
+                .map_err(|_| anyhow!("failed addr from base58"))?;
+            let compressed_point = CompressedEdwardsY::from_slice(&bytes)
+                .map_err(|_| anyhow!("accounts bytes not 32"))?;
+            if let Some(_) = compressed_point.decompress() {
+                Ok(ChainSupported::Solana)
+            } else {
+                Err(anyhow!("not a valid ed25519 curve point"))
+            }
+        }
+        Token::Eth | Token::UsdtEth | Token::UsdcEth => {
+            // check if it belongs to a point on Ecdsa secp256k1 curve !!! cannot do this as the public key is hashed
+            // check if the account is 20 bytes
+            match VaneAddress::parse(account) {
+                Ok(VaneAddress::Evm(_)) => Ok(ChainSupported::Ethereum),
+                _ => Err(anyhow!("Not ethereum address")),
+            }
+        }
+    }
+}
+
+/// encoding of an account address, recognized from its text alone
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressFormat {
+    /// 0x prefixed 20 bytes in hex, `checksummed` when mixed case with a valid EIP-55 checksum
+    Evm { checksummed: bool },
+    /// base58 encoded ed25519 public key
+    Base58Ed25519,
+    /// substrate SS58 with its network `prefix`
+    Ss58 { prefix: u16 },
+    /// bech32 or bech32m, e.g. bitcoin or cosmos accounts, no supported network uses it
+    Bech32,
+}
+
+impl AddressFormat {
+    /// networks accounts of this format live on
+    pub fn chains(&self) -> Vec<ChainSupported> {
+        match self {
+            AddressFormat::Evm { .. } => vec![ChainSupported::Ethereum, ChainSupported::Bnb],
+            AddressFormat::Base58Ed25519 => vec![ChainSupported::Solana],
+            AddressFormat::Ss58 { .. } => vec![ChainSupported::Polkadot],
+            AddressFormat::Bech32 => vec![],
+        }
+    }
+}
+
+/// format of `address`, `None` when no known format matches or its checksum is wrong
+pub fn address_format(address: &str) -> Option<AddressFormat> {
+    if let Some(hex) = address.strip_prefix("0x") {
+        return evm_format(hex);
+    }
+    if is_bech32(address) {
+        return Some(AddressFormat::Bech32);
+    }
+    let bytes = address.from_base58().ok()?;
+    match bytes.len() {
+        32 => CompressedEdwardsY::from_slice(&bytes)
+            .ok()?
+            .decompress()
+            .map(|_| AddressFormat::Base58Ed25519),
+        35 | 36 => ss58_prefix(&bytes).map(|prefix| AddressFormat::Ss58 { prefix }),
+        _ => None,
+    }
+}
+
+/// networks `address` can live on going by its format, empty when the format is unknown
+pub fn candidate_chains(address: &str) -> Vec<ChainSupported> {
+    address_format(address)
+        .map(|format| format.chains())
+        .unwrap_or_default()
+}
+
+/// network of a transfer to `receiver` when the sender picked none, the only candidate network
+/// of the receiver address
+pub fn detect_network(receiver: &str) -> Result<ChainSupported, VaneRpcError> {
+    match candidate_chains(receiver).as_slice() {
+        [] => Err(VaneRpcError::InvalidAddress {
+            address: receiver.to_string(),
+        }),
+        [network] => Ok(*network),
+        candidates => Err(VaneRpcError::AmbiguousNetwork {
+            address: receiver.to_string(),
+            candidates: candidates
+                .iter()
+                .map(|network| String::from(*network))
+                .collect(),
+        }),
+    }
+}
+
+fn evm_format(hex: &str) -> Option<AddressFormat> {
+    if hex.len() != 40 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    if hex == hex.to_ascii_lowercase() || hex == hex.to_ascii_uppercase() {
+        return Some(AddressFormat::Evm { checksummed: false });
+    }
+    let mut bytes = [0u8; 20];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+    }
+    // EIP-55, mixed case has to be the checksummed case
+    (checksummed_hex(&bytes) == hex).then_some(AddressFormat::Evm { checksummed: true })
+}
+
+/// network prefix of an SS58 encoded 32 bytes account with a valid checksum
+fn ss58_prefix(bytes: &[u8]) -> Option<u16> {
+    let (prefix, prefix_len) = match bytes[0] {
+        0..=63 => (u16::from(bytes[0]), 1),
+        64..=127 => {
+            let (lower, upper) = (bytes[0], bytes[1]);
+            let prefix = u16::from(((lower & 0b0011_1111) << 2) | (upper >> 6))
+                | (u16::from(upper & 0b0011_1111) << 8);
+            (prefix, 2)
+        }
+        _ => return None,
+    };
+    if bytes.len() != prefix_len + 32 + 2 {
+        return None;
+    }
+    let (body, checksum) = bytes.split_at(bytes.len() - 2);
+    (ss58_checksum(body) == checksum).then_some(prefix)
+}
+
+const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// bech32 or bech32m string with a valid checksum
+fn is_bech32(address: &str) -> bool {
+    let lower = address.to_ascii_lowercase();
+    if address != lower && address != address.to_ascii_uppercase() || lower.len() > 90 {
+        return false;
+    }
+    let Some((hrp, data)) = lower.rsplit_once('1') else {
+        return false;
+    };
+    if hrp.is_empty() || data.len() < 6 || !hrp.bytes().all(|c| (33..=126).contains(&c)) {
+        return false;
+    }
+    let Some(values) = data
+        .chars()
+        .map(|c| BECH32_CHARSET.find(c).map(|value| value as u32))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return false;
+    };
+    let mut expanded = hrp.bytes().map(|c| u32::from(c >> 5)).collect::<Vec<_>>();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|c| u32::from(c & 31)));
+    expanded.extend(values);
+    // bech32 and bech32m constants
+    matches!(bech32_polymod(&expanded), 1 | 0x2bc8_30a3)
+}
+
+fn bech32_polymod(values: &[u32]) -> u32 {
+    const GENERATOR: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    values.iter().fold(1, |checksum, value| {
+        let top = checksum >> 25;
+        let checksum = ((checksum & 0x01ff_ffff) << 5) ^ value;
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(checksum, |checksum, (_, generator)| checksum ^ generator)
+    })
+}
+
+/// `address` is in a known format that does not exist on `network`, e.g. a solana address picked
+/// for an ethereum transfer
+fn wrong_network(address: &str, network: ChainSupported) -> Option<VaneRpcError> {
+    let candidates = candidate_chains(address);
+    if candidates.is_empty() || candidates.contains(&network) {
+        return None;
+    }
+    Some(VaneRpcError::WrongNetworkSuspected {
+        expected: candidates[0].into(),
+        found: network.into(),
+    })
+}
+
+/// checks run before a transfer is initiated: both addresses are valid for the token and on the same network
+/// returns the network the transfer will happen on
+pub fn validate_transfer(
+    sender: &str,
+    receiver: &str,
+    token: Token,
+    network: ChainSupported,
+) -> Result<ChainSupported, VaneRpcError> {
+    // an address recognized for another network is a wrong pick rather than a typo
+    if let Some(err) = wrong_network(receiver, network).or(wrong_network(sender, network)) {
+        Err(err)?
+    }
+    let net_sender =
+        verify_public_bytes(sender, token, network).map_err(|_| VaneRpcError::InvalidAddress {
+            address: sender.to_string(),
+        })?;
+    let net_recv = verify_public_bytes(receiver, token, network).map_err(|_| {
+        VaneRpcError::InvalidAddress {
+            address: receiver.to_string(),
+        }
+    })?;
+
+    if net_sender != net_recv {
+        Err(VaneRpcError::WrongNetworkSuspected {
+            expected: net_sender.into(),
+            found: net_recv.into(),
+        })?
+    }
+    Ok(net_sender)
+}
+
+/// evm addresses tokens are sent to on purpose to destroy them
+pub const EVM_BURN_ADDRESSES: [&str; 2] = [
+    "0x000000000000000000000000000000000000dead",
+    "0xdead000000000000000042069420694206942069",
+];
+/// solana incinerator, lamports sent to it are burnt
+pub const SOLANA_INCINERATOR: &str = "1nc1nerator11111111111111111111111111111111";
+
+/// `address` is a well known burn address or a system address no one controls, e.g. the zero address
+/// or an evm precompile
+pub fn is_burn_address(address: &str, network: ChainSupported) -> bool {
+    match network {
+        ChainSupported::Ethereum | ChainSupported::Bnb => {
+            let address = address.to_lowercase();
+            let Some(hex) = address.strip_prefix("0x") else {
+                return false;
+            };
+            // zero address and the precompiles 0x01..=0x0a, non ascii input is no char boundary
+            let system = hex.len() == 40
+                && hex
+                    .get(..38)
+                    .is_some_and(|zeros| zeros.chars().all(|c| c == '0'))
+                && hex
+                    .get(38..)
+                    .and_then(|last| u8::from_str_radix(last, 16).ok())
+                    .is_some_and(|last| last <= 0x0a);
+            system || EVM_BURN_ADDRESSES.contains(&address.as_str())
+        }
+        ChainSupported::Solana => address == SOLANA_INCINERATOR,
+        ChainSupported::Polkadot => false,
+    }
+}
+
+/// fewest whole tokens of a round amount warned as a likely typo
+pub const ROUND_AMOUNT_MIN_WHOLE: u128 = 10_000;
+
+/// `amount` is a whole number of tokens, large and a multiple of a hundred, e.g. `10000` typed for
+/// `100.00`. returns the amount it likely stands for
+pub fn fat_finger_amount(amount: u128, token: Token) -> Option<u128> {
+    let unit = 10u128.pow(token.decimals());
+    let whole = amount / unit;
+    (amount % unit == 0 && whole >= ROUND_AMOUNT_MIN_WHOLE && whole % 100 == 0)
+        .then_some(amount / 100)
+}
+
+/// decimals `amount` carries in whole tokens, e.g. 2 for `1.25`
+pub fn amount_decimals(amount: u128, token: Token) -> u32 {
+    let decimals = token.decimals();
+    let trailing_zeros = (0..decimals)
+        .take_while(|zeros| amount % 10u128.pow(zeros + 1) == 0)
+        .count() as u32;
+    decimals - trailing_zeros
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suspicious_amounts_are_detected() {
+        let usdt = 1_000_000;
+        assert_eq!(
+            fat_finger_amount(10_000 * usdt, Token::UsdtEth),
+            Some(100 * usdt)
+        );
+        assert_eq!(fat_finger_amount(100 * usdt, Token::UsdtEth), None);
+        assert_eq!(fat_finger_amount(10_050 * usdt, Token::UsdtEth), None);
+        assert_eq!(fat_finger_amount(10_000 * usdt + 1, Token::UsdtEth), None);
+
+        assert_eq!(amount_decimals(1_250_000, Token::UsdtEth), 2);
+        assert_eq!(amount_decimals(3_000_000, Token::UsdtEth), 0);
+        assert_eq!(amount_decimals(1, Token::UsdtEth), 6);
+        assert_eq!(amount_decimals(0, Token::Eth), 0);
+    }
+
+    #[test]
+    fn burn_addresses_are_detected() {
+        let eth = ChainSupported::Ethereum;
+        assert!(is_burn_address(
+            "0x0000000000000000000000000000000000000000",
+            eth
+        ));
+        assert!(is_burn_address(
+            "0x000000000000000000000000000000000000dEaD",
+            eth
+        ));
+        assert!(is_burn_address(
+            "0x0000000000000000000000000000000000000004",
+            eth
+        ));
+        assert!(!is_burn_address(
+            "0x63F9725f107358c9115BC9d86c72dD5823E9B1E6",
+            eth
+        ));
+        assert!(is_burn_address(SOLANA_INCINERATOR, ChainSupported::Solana));
+        // short and non ascii input is not a burn address rather than a panic
+        assert!(!is_burn_address("0x00", eth));
+        assert!(!is_burn_address(
+            "0x0000000000000000000000000000000000000é0",
+            eth
+        ));
+    }
+
+    #[test]
+    fn address_formats_point_to_their_networks() {
+        assert_eq!(
+            address_format("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+            Some(AddressFormat::Evm { checksummed: true })
+        );
+        // a single letter case flipped breaks the EIP-55 checksum
+        assert_eq!(
+            address_format("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            None
+        );
+        assert_eq!(
+            candidate_chains("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"),
+            vec![ChainSupported::Ethereum, ChainSupported::Bnb]
+        );
+        assert_eq!(
+            address_format("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"),
+            Some(AddressFormat::Ss58 { prefix: 42 })
+        );
+        assert_eq!(
+            detect_network("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5"),
+            Ok(ChainSupported::Polkadot)
+        );
+        assert_eq!(
+            detect_network("11111111111111111111111111111111"),
+            Ok(ChainSupported::Solana)
+        );
+        assert_eq!(
+            address_format("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+            Some(AddressFormat::Bech32)
+        );
+        assert!(matches!(
+            detect_network("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+            Err(VaneRpcError::AmbiguousNetwork { .. })
+        ));
+        assert!(matches!(
+            wrong_network("11111111111111111111111111111111", ChainSupported::Ethereum),
+            Some(VaneRpcError::WrongNetworkSuspected { .. })
+        ));
+    }
+
+    #[test]
+    fn polkadot_accounts_are_verified_without_panicking() {
+        let dot = ChainSupported::Polkadot;
+        let account = "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5";
+        assert_eq!(
+            verify_public_bytes(account, Token::Dot, dot).ok(),
+            Some(dot)
+        );
+        let evm = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        assert!(verify_public_bytes(evm, Token::UsdtDot, dot).is_err());
+        assert!(verify_public_bytes("15oF4uVJwmo4", Token::Dot, dot).is_err());
+    }
+}